@@ -390,6 +390,7 @@ impl BenchShell {
             &mut TxGasMeter::new_from_sub_limit(u64::MAX.into()),
             &TxIndex(0),
             tx,
+            None,
             &mut self.inner.vp_wasm_cache,
             &mut self.inner.tx_wasm_cache,
         )
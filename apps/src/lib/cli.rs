@@ -253,6 +253,8 @@ pub mod cmds {
                 .subcommand(QueryDelegations::def().display_order(5))
                 .subcommand(QueryFindValidator::def().display_order(5))
                 .subcommand(QueryResult::def().display_order(5))
+                .subcommand(QueryTxDump::def().display_order(5))
+                .subcommand(QueryTxDecode::def().display_order(5))
                 .subcommand(QueryRawBytes::def().display_order(5))
                 .subcommand(QueryProposal::def().display_order(5))
                 .subcommand(QueryProposalResult::def().display_order(5))
@@ -263,6 +265,12 @@ pub mod cmds {
                 .subcommand(QueryMetaData::def().display_order(5))
                 // Actions
                 .subcommand(SignTx::def().display_order(6))
+                .subcommand(ExportUnsignedTx::def().display_order(6))
+                .subcommand(SignUnsignedTx::def().display_order(6))
+                .subcommand(ImportSignatures::def().display_order(6))
+                .subcommand(MultisigCreate::def().display_order(6))
+                .subcommand(MultisigSign::def().display_order(6))
+                .subcommand(MultisigSubmit::def().display_order(6))
                 .subcommand(GenIbcShieldedTransafer::def().display_order(6))
                 // Utils
                 .subcommand(Utils::def().display_order(7))
@@ -320,6 +328,8 @@ pub mod cmds {
             let query_find_validator =
                 Self::parse_with_ctx(matches, QueryFindValidator);
             let query_result = Self::parse_with_ctx(matches, QueryResult);
+            let query_tx_dump = Self::parse_with_ctx(matches, QueryTxDump);
+            let query_tx_decode = Self::parse_with_ctx(matches, QueryTxDecode);
             let query_raw_bytes = Self::parse_with_ctx(matches, QueryRawBytes);
             let query_proposal = Self::parse_with_ctx(matches, QueryProposal);
             let query_proposal_result =
@@ -335,6 +345,15 @@ pub mod cmds {
             let add_to_eth_bridge_pool =
                 Self::parse_with_ctx(matches, AddToEthBridgePool);
             let sign_tx = Self::parse_with_ctx(matches, SignTx);
+            let export_unsigned_tx =
+                Self::parse_with_ctx(matches, ExportUnsignedTx);
+            let sign_unsigned_tx =
+                Self::parse_with_ctx(matches, SignUnsignedTx);
+            let import_signatures =
+                Self::parse_with_ctx(matches, ImportSignatures);
+            let multisig_create = Self::parse_with_ctx(matches, MultisigCreate);
+            let multisig_sign = Self::parse_with_ctx(matches, MultisigSign);
+            let multisig_submit = Self::parse_with_ctx(matches, MultisigSubmit);
             let gen_ibc_shielded =
                 Self::parse_with_ctx(matches, GenIbcShieldedTransafer);
             let utils = SubCmd::parse(matches).map(Self::WithoutContext);
@@ -372,6 +391,8 @@ pub mod cmds {
                 .or(query_delegations)
                 .or(query_find_validator)
                 .or(query_result)
+                .or(query_tx_dump)
+                .or(query_tx_decode)
                 .or(query_raw_bytes)
                 .or(query_proposal)
                 .or(query_proposal_result)
@@ -382,6 +403,12 @@ pub mod cmds {
                 .or(query_metadata)
                 .or(query_account)
                 .or(sign_tx)
+                .or(export_unsigned_tx)
+                .or(sign_unsigned_tx)
+                .or(import_signatures)
+                .or(multisig_create)
+                .or(multisig_sign)
+                .or(multisig_submit)
                 .or(gen_ibc_shielded)
                 .or(utils)
         }
@@ -424,6 +451,8 @@ pub mod cmds {
         TxTransfer(TxTransfer),
         TxIbcTransfer(TxIbcTransfer),
         QueryResult(QueryResult),
+        QueryTxDump(QueryTxDump),
+        QueryTxDecode(QueryTxDecode),
         TxUpdateAccount(TxUpdateAccount),
         TxInitAccount(TxInitAccount),
         TxInitValidator(TxInitValidator),
@@ -464,6 +493,12 @@ pub mod cmds {
         QueryPgf(QueryPgf),
         QueryValidatorState(QueryValidatorState),
         SignTx(SignTx),
+        ExportUnsignedTx(ExportUnsignedTx),
+        SignUnsignedTx(SignUnsignedTx),
+        ImportSignatures(ImportSignatures),
+        MultisigCreate(MultisigCreate),
+        MultisigSign(MultisigSign),
+        MultisigSubmit(MultisigSubmit),
         GenIbcShieldedTransafer(GenIbcShieldedTransafer),
     }
 
@@ -1200,6 +1235,51 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryTxDump(pub args::QueryTxDump<args::CliTypes>);
+
+    impl SubCmd for QueryTxDump {
+        const CMD: &'static str = "tx-dump";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryTxDump(args::QueryTxDump::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Fetch a transaction included in a block and dump it as \
+                     JSON.",
+                )
+                .add_args::<args::QueryTxDump<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryTxDecode(pub args::QueryTxDecode<args::CliTypes>);
+
+    impl SubCmd for QueryTxDecode {
+        const CMD: &'static str = "tx-decode";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryTxDecode(args::QueryTxDecode::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Decode a transaction from the hex or base64 encoding of \
+                     its bytes, as shown by block explorers, and dump it as \
+                     JSON or broadcast it again.",
+                )
+                .add_args::<args::QueryTxDecode<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryProposal(pub args::QueryProposal<args::CliTypes>);
 
@@ -1742,6 +1822,139 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct ExportUnsignedTx(pub args::ExportUnsignedTx<args::CliTypes>);
+
+    impl SubCmd for ExportUnsignedTx {
+        const CMD: &'static str = "export-unsigned-tx";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                ExportUnsignedTx(args::ExportUnsignedTx::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Export a serialized transaction for signing on an \
+                     offline machine.",
+                )
+                .add_args::<args::ExportUnsignedTx<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct SignUnsignedTx(pub args::SignUnsignedTx<args::CliTypes>);
+
+    impl SubCmd for SignUnsignedTx {
+        const CMD: &'static str = "sign-unsigned-tx";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                SignUnsignedTx(args::SignUnsignedTx::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Sign an exported transaction with keys from the wallet. \
+                     This doesn't require a connection to a node.",
+                )
+                .add_args::<args::SignUnsignedTx<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct ImportSignatures(pub args::ImportSignatures<args::CliTypes>);
+
+    impl SubCmd for ImportSignatures {
+        const CMD: &'static str = "import-signatures";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                ImportSignatures(args::ImportSignatures::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Attach signatures made offline to a serialized \
+                     transaction.",
+                )
+                .add_args::<args::ImportSignatures<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct MultisigCreate(pub args::MultisigCreate<args::CliTypes>);
+
+    impl SubCmd for MultisigCreate {
+        const CMD: &'static str = "multisig-create";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                MultisigCreate(args::MultisigCreate::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Create a bundle to collect the signatures of the members \
+                     of a multisig account over a serialized transaction.",
+                )
+                .add_args::<args::MultisigCreate<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct MultisigSign(pub args::MultisigSign<args::CliTypes>);
+
+    impl SubCmd for MultisigSign {
+        const CMD: &'static str = "multisig-sign";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| MultisigSign(args::MultisigSign::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Add signatures to a multisig bundle with keys from the \
+                     wallet. This doesn't require a connection to a node.",
+                )
+                .add_args::<args::MultisigSign<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct MultisigSubmit(pub args::MultisigSubmit<args::CliTypes>);
+
+    impl SubCmd for MultisigSubmit {
+        const CMD: &'static str = "multisig-submit";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                MultisigSubmit(args::MultisigSubmit::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Merge the multisig bundles signed by the co-signers and \
+                     submit their transaction once the account's threshold is \
+                     met.",
+                )
+                .add_args::<args::MultisigSubmit<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryValidatorState(
         pub args::QueryValidatorState<args::CliTypes>,
@@ -2823,6 +3036,7 @@ pub mod args {
     use namada::types::chain::{ChainId, ChainIdPrefix};
     use namada::types::dec::Dec;
     use namada::types::ethereum_events::EthAddress;
+    use namada::types::hash::Hash;
     use namada::types::keccak::KeccakHash;
     use namada::types::key::*;
     use namada::types::masp::MaspValue;
@@ -2894,6 +3108,7 @@ pub mod args {
     pub const CODE_PATH_OPT: ArgOpt<PathBuf> = CODE_PATH.opt();
     pub const COMMISSION_RATE: Arg<Dec> = arg("commission-rate");
     pub const COMMISSION_RATE_OPT: ArgOpt<Dec> = COMMISSION_RATE.opt();
+    pub const CONFIRM_SIGNING: ArgFlag = flag("confirm-signing");
     pub const CONSENSUS_TIMEOUT_COMMIT: ArgDefault<Timeout> = arg_default(
         "consensus-timeout-commit",
         DefaultFn(|| Timeout::from_str("1s").unwrap()),
@@ -2931,6 +3146,7 @@ pub mod args {
     pub const EXPIRATION_OPT: ArgOpt<DateTimeUtc> = arg_opt("expiration");
     pub const EMAIL: Arg<String> = arg("email");
     pub const EMAIL_OPT: ArgOpt<String> = EMAIL.opt();
+    pub const EMBED_MISSING_CODE: ArgFlag = flag("embed-missing-code");
     pub const FEE_UNSHIELD_SPENDING_KEY: ArgOpt<WalletTransferSource> =
         arg_opt("gas-spending-key");
     pub const FEE_AMOUNT_OPT: ArgOpt<token::DenominatedAmount> =
@@ -2959,6 +3175,7 @@ pub mod args {
         arg_default("hd-path", DefaultFn(|| "default".to_string()));
     pub const HISTORIC: ArgFlag = flag("historic");
     pub const IBC_TRANSFER_MEMO_PATH: ArgOpt<PathBuf> = arg_opt("memo-path");
+    pub const INNER_TIMEOUT: ArgOpt<Duration> = arg_opt("inner-timeout");
     pub const LEDGER_ADDRESS_ABOUT: &str =
         "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
          scheme is not supplied, it is assumed to be TCP.";
@@ -2975,10 +3192,12 @@ pub mod args {
         arg("max-commission-rate-change");
     pub const MAX_ETH_GAS: ArgOpt<u64> = arg_opt("max_eth-gas");
     pub const MODE: ArgOpt<String> = arg_opt("mode");
+    pub const MULTISIG_BUNDLES: ArgMulti<PathBuf> = arg_multi("bundles");
     pub const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
     pub const NAMADA_START_TIME: ArgOpt<DateTimeUtc> = arg_opt("time");
     pub const NO_CONVERSIONS: ArgFlag = flag("no-conversions");
     pub const NUT: ArgFlag = flag("nut");
+    pub const OFFLINE_TX: ArgFlag = flag("offline-tx");
     pub const OUT_FILE_PATH_OPT: ArgOpt<PathBuf> = arg_opt("out-file-path");
     pub const OUTPUT: ArgOpt<PathBuf> = arg_opt("output");
     pub const OUTPUT_FOLDER_PATH: ArgOpt<PathBuf> =
@@ -3011,6 +3230,7 @@ pub mod args {
     pub const RAW_PUBLIC_KEY_OPT: ArgOpt<common::PublicKey> =
         arg_opt("public-key");
     pub const RAW_SOURCE: Arg<String> = arg("source");
+    pub const REBROADCAST: ArgFlag = flag("rebroadcast");
     pub const RECEIVER: Arg<String> = arg("receiver");
     pub const RELAYER: Arg<Address> = arg("relayer");
     pub const SAFE_MODE: ArgFlag = flag("safe-mode");
@@ -3038,9 +3258,11 @@ pub mod args {
     pub const TOKEN: Arg<WalletAddress> = arg("token");
     pub const TRANSFER_FROM_SOURCE_AMOUNT: Arg<token::DenominatedAmount> =
         arg("transfer-from-source-amount");
+    pub const TRANSFER_MEMO: ArgOpt<String> = arg_opt("memo");
     pub const TRANSFER_SOURCE: Arg<WalletTransferSource> = arg("source");
     pub const TRANSFER_TARGET: Arg<WalletTransferTarget> = arg("target");
-    pub const TX_HASH: Arg<String> = arg("tx-hash");
+    pub const TX_BLOB: Arg<String> = arg("tx-blob");
+    pub const TX_HASH: Arg<Hash> = arg("tx-hash");
     pub const THRESOLD: ArgOpt<u8> = arg_opt("threshold");
     pub const UNSAFE_DONT_ENCRYPT: ArgFlag = flag("unsafe-dont-encrypt");
     pub const UNSAFE_SHOW_SECRET: ArgFlag = flag("unsafe-show-secret");
@@ -3067,6 +3289,7 @@ pub mod args {
     pub const WASM_CHECKSUMS_PATH: Arg<PathBuf> = arg("wasm-checksums-path");
     pub const WASM_DIR: ArgOpt<PathBuf> = arg_opt("wasm-dir");
     pub const WEBSITE_OPT: ArgOpt<String> = arg_opt("website");
+    pub const WRAPPER_TIMEOUT: ArgOpt<Duration> = arg_opt("wrapper-timeout");
     pub const TX_PATH: Arg<PathBuf> = arg("tx-path");
     pub const TX_PATH_OPT: ArgOpt<PathBuf> = TX_PATH.opt();
 
@@ -3278,11 +3501,103 @@ pub mod args {
         }
 
         fn def(app: App) -> App {
-            app.add_args::<Query<CliTypes>>().arg(
-                TX_HASH
-                    .def()
-                    .help("The hash of the transaction being looked up."),
-            )
+            app.add_args::<Query<CliTypes>>().arg(TX_HASH.def().help(
+                "The hash of the transaction being looked up, in upper or \
+                 lower case hex, optionally prefixed with 0x.",
+            ))
+        }
+    }
+
+    impl CliToSdk<QueryTxDump<SdkTypes>> for QueryTxDump<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryTxDump<SdkTypes> {
+            QueryTxDump::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                tx_hash: self.tx_hash,
+                decrypted: self.decrypted,
+                output_folder: self.output_folder,
+            }
+        }
+    }
+
+    impl Args for QueryTxDump<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let tx_hash = TX_HASH.parse(matches);
+            let decrypted = DECRYPT.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
+            Self {
+                query,
+                tx_hash,
+                decrypted,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+                .arg(TX_HASH.def().help(
+                    "The hash of the wrapper or inner transaction to dump, in \
+                     upper or lower case hex, optionally prefixed with 0x.",
+                ))
+                .arg(DECRYPT.def().help(
+                    "Also dump the sections of the inner transaction that the \
+                     node applied after decrypting the wrapper.",
+                ))
+                .arg(OUTPUT_FOLDER_PATH.def().help(
+                    "The output folder path where the dump will be stored.",
+                ))
+        }
+    }
+
+    impl CliToSdk<QueryTxDecode<SdkTypes>> for QueryTxDecode<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryTxDecode<SdkTypes> {
+            QueryTxDecode::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                blob: self.blob,
+                rebroadcast: self.rebroadcast,
+                wrapper_timeout: self.wrapper_timeout,
+                inner_timeout: self.inner_timeout,
+            }
+        }
+    }
+
+    impl Args for QueryTxDecode<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let blob = TX_BLOB.parse(matches);
+            let rebroadcast = REBROADCAST.parse(matches);
+            let wrapper_timeout =
+                WRAPPER_TIMEOUT.parse(matches).map(|dur| dur.0);
+            let inner_timeout = INNER_TIMEOUT.parse(matches).map(|dur| dur.0);
+            Self {
+                query,
+                blob,
+                rebroadcast,
+                wrapper_timeout,
+                inner_timeout,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+                .arg(TX_BLOB.def().help(
+                    "The bytes of the transaction, encoded in hex or base64. \
+                     Whitespace is ignored.",
+                ))
+                .arg(REBROADCAST.def().help(
+                    "Broadcast the decoded transaction again instead of \
+                     dumping it, and wait for both its wrapper and its inner \
+                     transaction to be applied.",
+                ))
+                .arg(WRAPPER_TIMEOUT.def().requires(REBROADCAST.name).help(
+                    "The maximum time to wait for the wrapper transaction to \
+                     be included in a block. Defaults to 60s.",
+                ))
+                .arg(INNER_TIMEOUT.def().requires(REBROADCAST.name).help(
+                    "The maximum time to wait for the inner transaction to be \
+                     applied once its wrapper has been included in a block. \
+                     Defaults to 60s.",
+                ))
         }
     }
 
@@ -3847,6 +4162,7 @@ pub mod args {
                 token: chain_ctx.get(&self.token),
                 amount: self.amount,
                 native_token: chain_ctx.native_token.clone(),
+                memo: self.memo,
                 tx_code_path: self.tx_code_path.to_path_buf(),
             }
         }
@@ -3859,6 +4175,7 @@ pub mod args {
             let target = TRANSFER_TARGET.parse(matches);
             let token = TOKEN.parse(matches);
             let amount = InputAmount::Unvalidated(AMOUNT.parse(matches));
+            let memo = TRANSFER_MEMO.parse(matches);
             let tx_code_path = PathBuf::from(TX_TRANSFER_WASM);
             Self {
                 tx,
@@ -3868,6 +4185,7 @@ pub mod args {
                 amount,
                 tx_code_path,
                 native_token: (),
+                memo,
             }
         }
 
@@ -3883,6 +4201,11 @@ pub mod args {
                 ))
                 .arg(TOKEN.def().help("The transfer token."))
                 .arg(AMOUNT.def().help("The amount to transfer in decimal."))
+                .arg(
+                    TRANSFER_MEMO
+                        .def()
+                        .help("An optional memo to attach to the transfer."),
+                )
         }
     }
 
@@ -5404,6 +5727,232 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<ExportUnsignedTx<SdkTypes>> for ExportUnsignedTx<CliTypes> {
+        fn to_sdk(self, _ctx: &mut Context) -> ExportUnsignedTx<SdkTypes> {
+            ExportUnsignedTx::<SdkTypes> {
+                tx_data: std::fs::read(self.tx_data).expect(""),
+                output_folder: self.output_folder,
+            }
+        }
+    }
+
+    impl Args for ExportUnsignedTx<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx_data = TX_PATH.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
+            Self {
+                tx_data,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .help("The path to the tx file with the serialized tx."),
+            )
+            .arg(OUTPUT_FOLDER_PATH.def().help(
+                "The output folder path where the unsigned tx file will be \
+                 stored.",
+            ))
+        }
+    }
+
+    impl CliToSdk<SignUnsignedTx<SdkTypes>> for SignUnsignedTx<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> SignUnsignedTx<SdkTypes> {
+            let ctx = ctx.borrow_mut_chain_or_exit();
+            SignUnsignedTx::<SdkTypes> {
+                unsigned_tx: std::fs::read(self.unsigned_tx).expect(""),
+                signing_keys: self
+                    .signing_keys
+                    .iter()
+                    .map(|key| ctx.get_cached(key))
+                    .collect(),
+                output_folder: self.output_folder,
+            }
+        }
+    }
+
+    impl Args for SignUnsignedTx<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let unsigned_tx = TX_PATH.parse(matches);
+            let signing_keys = SIGNING_KEYS.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
+            Self {
+                unsigned_tx,
+                signing_keys,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .help("The path to the exported unsigned tx file."),
+            )
+            .arg(SIGNING_KEYS.def().required(true).help(
+                "Sign the transaction with the key for the given public key, \
+                 public key hash or alias from your wallet.",
+            ))
+            .arg(OUTPUT_FOLDER_PATH.def().help(
+                "The output folder path where the signatures file will be \
+                 stored.",
+            ))
+        }
+    }
+
+    impl CliToSdk<ImportSignatures<SdkTypes>> for ImportSignatures<CliTypes> {
+        fn to_sdk(self, _ctx: &mut Context) -> ImportSignatures<SdkTypes> {
+            ImportSignatures::<SdkTypes> {
+                tx_data: std::fs::read(self.tx_data).expect(""),
+                signatures: self
+                    .signatures
+                    .iter()
+                    .map(|path| std::fs::read(path).unwrap())
+                    .collect(),
+                output_folder: self.output_folder,
+            }
+        }
+    }
+
+    impl Args for ImportSignatures<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx_data = TX_PATH.parse(matches);
+            let signatures = SIGNATURES.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
+            Self {
+                tx_data,
+                signatures,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .help("The path to the tx file with the serialized tx."),
+            )
+            .arg(SIGNATURES.def().required(true).help(
+                "List of file paths containing the signatures made with \
+                 `sign-unsigned-tx`.",
+            ))
+            .arg(OUTPUT_FOLDER_PATH.def().help(
+                "The output folder path where the signed tx will be stored.",
+            ))
+        }
+    }
+
+    impl CliToSdk<MultisigCreate<SdkTypes>> for MultisigCreate<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> MultisigCreate<SdkTypes> {
+            MultisigCreate::<SdkTypes> {
+                tx_data: std::fs::read(self.tx_data).expect(""),
+                owner: ctx.borrow_chain_or_exit().get(&self.owner),
+                output_folder: self.output_folder,
+            }
+        }
+    }
+
+    impl Args for MultisigCreate<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx_data = TX_PATH.parse(matches);
+            let owner = OWNER.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
+            Self {
+                tx_data,
+                owner,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .help("The path to the tx file with the serialized tx."),
+            )
+            .arg(OWNER.def().help(
+                "The multisig account on behalf of which the tx is signed.",
+            ))
+            .arg(OUTPUT_FOLDER_PATH.def().help(
+                "The output folder path where the multisig bundle will be \
+                 stored.",
+            ))
+        }
+    }
+
+    impl CliToSdk<MultisigSign<SdkTypes>> for MultisigSign<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> MultisigSign<SdkTypes> {
+            let ctx = ctx.borrow_mut_chain_or_exit();
+            MultisigSign::<SdkTypes> {
+                bundle: std::fs::read(self.bundle).expect(""),
+                signing_keys: self
+                    .signing_keys
+                    .iter()
+                    .map(|key| ctx.get_cached(key))
+                    .collect(),
+                output_folder: self.output_folder,
+            }
+        }
+    }
+
+    impl Args for MultisigSign<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let bundle = TX_PATH.parse(matches);
+            let signing_keys = SIGNING_KEYS.parse(matches);
+            let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
+            Self {
+                bundle,
+                signing_keys,
+                output_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(TX_PATH.def().help("The path to the multisig bundle."))
+                .arg(SIGNING_KEYS.def().required(true).help(
+                    "Sign the transaction with the key for the given public \
+                     key, public key hash or alias from your wallet.",
+                ))
+                .arg(OUTPUT_FOLDER_PATH.def().help(
+                    "The output folder path where the signed multisig bundle \
+                     will be stored.",
+                ))
+        }
+    }
+
+    impl CliToSdk<MultisigSubmit<SdkTypes>> for MultisigSubmit<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> MultisigSubmit<SdkTypes> {
+            MultisigSubmit::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                bundles: self
+                    .bundles
+                    .iter()
+                    .map(|path| std::fs::read(path).unwrap())
+                    .collect(),
+            }
+        }
+    }
+
+    impl Args for MultisigSubmit<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let bundles = MULTISIG_BUNDLES.parse(matches);
+            Self { tx, bundles }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>().arg(
+                MULTISIG_BUNDLES.def().required(true).help(
+                    "List of file paths containing the multisig bundles \
+                     signed with `multisig-sign`.",
+                ),
+            )
+        }
+    }
+
     impl CliToSdk<GenIbcShieldedTransafer<SdkTypes>>
         for GenIbcShieldedTransafer<CliTypes>
     {
@@ -5686,6 +6235,10 @@ pub mod args {
                     .wrapper_fee_payer
                     .map(|x| ctx.get_cached(&x)),
                 use_device: self.use_device,
+                confirm_signing: self.confirm_signing,
+                offline: self.offline,
+                embed_missing_code: self.embed_missing_code,
+                wasm_dir: Some(ctx.wasm_dir()),
             }
         }
     }
@@ -5815,6 +6368,20 @@ pub mod args {
                 "Use an attached hardware wallet device to sign the \
                  transaction.",
             ))
+            .arg(CONFIRM_SIGNING.def().help(
+                "Show a summary of what the transaction does and wait for \
+                 confirmation before signing it.",
+            ))
+            .arg(OFFLINE_TX.def().help(
+                "Build the transaction header from the given chain ID and \
+                 expiration only, instead of checking them against the \
+                 connected node.",
+            ))
+            .arg(EMBED_MISSING_CODE.def().help(
+                "Embed the transaction code from the wasm directory if the \
+                 chain doesn't store it. By default, building the transaction \
+                 fails in that case.",
+            ))
         }
 
         fn parse(matches: &ArgMatches) -> Self {
@@ -5843,6 +6410,9 @@ pub mod args {
             let wrapper_fee_payer = FEE_PAYER_OPT.parse(matches);
             let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
             let use_device = USE_DEVICE.parse(matches);
+            let confirm_signing = CONFIRM_SIGNING.parse(matches);
+            let offline = OFFLINE_TX.parse(matches);
+            let embed_missing_code = EMBED_MISSING_CODE.parse(matches);
             Self {
                 dry_run,
                 dry_run_wrapper,
@@ -5867,6 +6437,10 @@ pub mod args {
                 wrapper_fee_payer,
                 output_folder,
                 use_device,
+                confirm_signing,
+                offline,
+                embed_missing_code,
+                wasm_dir: None,
             }
         }
     }
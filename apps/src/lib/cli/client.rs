@@ -502,6 +502,28 @@ impl CliApi {
                         let namada = ctx.to_sdk(&client, io);
                         rpc::query_result(&namada, args).await;
                     }
+                    Sub::QueryTxDump(QueryTxDump(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.query.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        rpc::query_tx_dump(&namada, args).await;
+                    }
+                    Sub::QueryTxDecode(QueryTxDecode(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.query.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        rpc::query_tx_decode(&namada, args).await;
+                    }
                     Sub::QueryRawBytes(QueryRawBytes(mut args)) => {
                         let client = client.unwrap_or_else(|| {
                             C::from_tendermint_address(
@@ -581,6 +603,37 @@ impl CliApi {
                         let namada = ctx.to_sdk(&client, io);
                         tx::sign_tx(&namada, args).await?;
                     }
+                    Sub::ExportUnsignedTx(ExportUnsignedTx(args)) => {
+                        let args = args.to_sdk(&mut ctx);
+                        tx::export_unsigned_tx(io, args)?;
+                    }
+                    Sub::SignUnsignedTx(SignUnsignedTx(args)) => {
+                        let args = args.to_sdk(&mut ctx);
+                        tx::sign_unsigned_tx(io, args)?;
+                    }
+                    Sub::ImportSignatures(ImportSignatures(args)) => {
+                        let args = args.to_sdk(&mut ctx);
+                        tx::import_signatures(io, args)?;
+                    }
+                    Sub::MultisigCreate(MultisigCreate(args)) => {
+                        let args = args.to_sdk(&mut ctx);
+                        tx::multisig_create(io, args)?;
+                    }
+                    Sub::MultisigSign(MultisigSign(args)) => {
+                        let args = args.to_sdk(&mut ctx);
+                        tx::multisig_sign(io, args)?;
+                    }
+                    Sub::MultisigSubmit(MultisigSubmit(mut args)) => {
+                        let client = client.unwrap_or_else(|| {
+                            C::from_tendermint_address(
+                                &mut args.tx.ledger_address,
+                            )
+                        });
+                        client.wait_until_node_is_synced(io).await?;
+                        let args = args.to_sdk(&mut ctx);
+                        let namada = ctx.to_sdk(&client, io);
+                        tx::multisig_submit(&namada, args).await?;
+                    }
                     Sub::GenIbcShieldedTransafer(GenIbcShieldedTransafer(
                         mut args,
                     )) => {
@@ -377,7 +377,7 @@ pub fn decode_derivation_path(
 ) -> Result<DerivationPath, DerivationPathError> {
     let is_default = derivation_path.eq_ignore_ascii_case("DEFAULT");
     let parsed_derivation_path = if is_default {
-        DerivationPath::default_for_scheme(scheme)
+        DerivationPath::default_for_scheme(scheme)?
     } else {
         DerivationPath::from_path_str(scheme, &derivation_path)?
     };
@@ -61,16 +61,16 @@ use crate::cli::{self, args};
 use crate::facade::tendermint::merkle::proof::ProofOps;
 use crate::facade::tendermint_rpc::error::Error as TError;
 
-/// Query the status of a given transaction.
+/// Wait for the event of a given transaction.
 ///
 /// If a response is not delivered until `deadline`, we exit the cli with an
 /// error.
-pub async fn query_tx_status<'a>(
+pub async fn wait_for_tx_event<'a>(
     namada: &impl Namada<'a>,
     status: namada_sdk::rpc::TxEventQuery<'_>,
     deadline: Instant,
 ) -> Event {
-    rpc::query_tx_status(namada, status, deadline)
+    rpc::wait_for_tx_event(namada, status, deadline)
         .await
         .unwrap()
 }
@@ -2478,7 +2478,7 @@ pub async fn query_tx_events<C: namada::ledger::queries::Client + Sync>(
 }
 
 /// Lookup the full response accompanying the specified transaction event
-// TODO: maybe remove this in favor of `query_tx_status`
+// TODO: maybe remove this in favor of `wait_for_tx_event`
 pub async fn query_tx_response<C: namada::ledger::queries::Client + Sync>(
     client: &C,
     tx_query: namada_sdk::rpc::TxEventQuery<'_>,
@@ -2492,10 +2492,12 @@ pub async fn query_result<'a>(
     context: &impl Namada<'a>,
     args: args::QueryResult,
 ) {
+    // The events record the hash in upper case hex
+    let tx_hash = args.tx_hash.to_string();
     // First try looking up application event pertaining to given hash.
     let tx_response = query_tx_response(
         context.client(),
-        namada_sdk::rpc::TxEventQuery::Applied(&args.tx_hash),
+        namada_sdk::rpc::TxEventQuery::Applied(&tx_hash),
     )
     .await;
     match tx_response {
@@ -2510,7 +2512,7 @@ pub async fn query_result<'a>(
             // If this fails then instead look for an acceptance event.
             let tx_response = query_tx_response(
                 context.client(),
-                namada_sdk::rpc::TxEventQuery::Accepted(&args.tx_hash),
+                namada_sdk::rpc::TxEventQuery::Accepted(&tx_hash),
             )
             .await;
             match tx_response {
@@ -2529,6 +2531,210 @@ pub async fn query_result<'a>(
     }
 }
 
+/// Fetch a transaction included in a block and dump it as JSON to a file, or
+/// to screen if no output folder is given.
+pub async fn query_tx_dump<'a>(
+    context: &impl Namada<'a>,
+    args: args::QueryTxDump,
+) {
+    let tx_hash = args.tx_hash;
+    let (height, tx) =
+        match rpc::query_tx_by_hash(context.client(), &tx_hash).await {
+            Ok(Some(found)) => found,
+            Ok(None) => {
+                edisplay_line!(
+                    context.io(),
+                    "No transaction with hash {} was found.",
+                    tx_hash
+                );
+                cli::safe_exit(1)
+            }
+            Err(err) => {
+                edisplay_line!(context.io(), "{}", err);
+                cli::safe_exit(1)
+            }
+        };
+    // The inner tx of a wrapper is applied in a later block once decrypted
+    let decrypted = if args.decrypted && tx.header().wrapper().is_some() {
+        match rpc::query_tx_by_hash(context.client(), &tx.raw_header_hash())
+            .await
+        {
+            Ok(Some((_, decrypted))) => Some(decrypted),
+            Ok(None) => {
+                display_line!(
+                    context.io(),
+                    "The decrypted inner transaction {} was not found.",
+                    tx.raw_header_hash()
+                );
+                None
+            }
+            Err(err) => {
+                edisplay_line!(context.io(), "{}", err);
+                cli::safe_exit(1)
+            }
+        }
+    } else {
+        None
+    };
+
+    let dump = namada_sdk::tx::TxDump::new(height, tx, decrypted.as_ref());
+    let json = serde_json::to_string_pretty(&dump)
+        .expect("Serializing a transaction dump shouldn't fail");
+    match args.output_folder {
+        Some(path) => {
+            let dump_path = path.join(format!("{}.json", dump.header_hash));
+            fs::write(&dump_path, json).unwrap_or_else(|err| {
+                edisplay_line!(
+                    context.io(),
+                    "Couldn't write to {}: {}",
+                    dump_path.display(),
+                    err
+                );
+                cli::safe_exit(1)
+            });
+            display_line!(
+                context.io(),
+                "Transaction dumped to {}.",
+                dump_path.display()
+            );
+        }
+        None => display_line!(context.io(), "{}", json),
+    }
+}
+
+/// Decode a transaction from the hex or base64 encoding of its bytes and dump
+/// it as JSON to screen, or broadcast it again.
+pub async fn query_tx_decode<'a>(
+    context: &impl Namada<'a>,
+    args: args::QueryTxDecode,
+) {
+    let (encoding, tx) = namada_sdk::tx::decode_tx_blob(&args.blob)
+        .unwrap_or_else(|err| {
+            edisplay_line!(context.io(), "{}", err);
+            cli::safe_exit(1)
+        });
+    display_line!(
+        context.io(),
+        "Decoded a {:?} encoded transaction with header hash {} and raw \
+         header hash {}.",
+        encoding,
+        tx.header_hash(),
+        tx.raw_header_hash()
+    );
+    if !args.rebroadcast {
+        display_line!(context.io(), "{}", tx.to_json_pretty());
+        return;
+    }
+
+    let warnings =
+        namada_sdk::tx::validate_before_broadcast(context.client(), &tx).await;
+    for warning in warnings {
+        if let namada_sdk::tx::TxWarning::Replay(_) = warning {
+            edisplay_line!(
+                context.io(),
+                "Warning: {}. The transaction has likely already been \
+                 included in a block.",
+                warning
+            );
+        } else {
+            edisplay_line!(context.io(), "Warning: {}", warning);
+        }
+    }
+    let receipt =
+        namada_sdk::tx::broadcast_tx_with_receipt(context.client(), &tx)
+            .await
+            .unwrap_or_else(|err| {
+                edisplay_line!(context.io(), "{}", err);
+                cli::safe_exit(1)
+            });
+    display_line!(
+        context.io(),
+        "Transaction broadcast with wrapper hash {} and inner hash {}.",
+        receipt.wrapper_hash,
+        receipt.decrypted_hash
+    );
+
+    let default_timeouts = namada_sdk::tx::TxTrackingTimeouts::default();
+    let timeouts = namada_sdk::tx::TxTrackingTimeouts {
+        wrapper: args.wrapper_timeout.unwrap_or(default_timeouts.wrapper),
+        inner: args.inner_timeout.unwrap_or(default_timeouts.inner),
+        ..default_timeouts
+    };
+    let outcome =
+        namada_sdk::tx::track_tx(context.client(), &receipt, &timeouts)
+            .await
+            .unwrap_or_else(|err| {
+                edisplay_line!(context.io(), "{}", err);
+                cli::safe_exit(1)
+            });
+    display_tracked_tx(context, &outcome, &timeouts);
+    if !outcome.is_success() {
+        cli::safe_exit(1)
+    }
+}
+
+/// Report both phases of a tracked tx: the inclusion of its wrapper, then the
+/// application of its inner tx. Only the latter tells whether the tx
+/// succeeded.
+fn display_tracked_tx<'a>(
+    context: &impl Namada<'a>,
+    outcome: &namada_sdk::tx::TrackedTxOutcome,
+    timeouts: &namada_sdk::tx::TxTrackingTimeouts,
+) {
+    use namada_sdk::tx::TrackedTxOutcome;
+
+    match outcome.wrapper() {
+        Some(wrapper) if wrapper.is_success() => display_line!(
+            context.io(),
+            "Wrapper transaction accepted at height {}.",
+            wrapper.outcome.height
+        ),
+        Some(wrapper) => edisplay_line!(
+            context.io(),
+            "Wrapper transaction rejected at height {} with code {:?}: {}",
+            wrapper.outcome.height,
+            wrapper.outcome.code,
+            wrapper.outcome.info
+        ),
+        None => edisplay_line!(
+            context.io(),
+            "Wrapper transaction not included in a block within {:?}.",
+            timeouts.wrapper
+        ),
+    }
+    match outcome {
+        TrackedTxOutcome::WrapperTimedOut
+        | TrackedTxOutcome::WrapperRejected { .. } => {
+            edisplay_line!(context.io(), "Inner transaction not applied.")
+        }
+        TrackedTxOutcome::InnerTimedOut { .. } => edisplay_line!(
+            context.io(),
+            "Inner transaction not applied within {:?}, its status is unknown.",
+            timeouts.inner
+        ),
+        TrackedTxOutcome::Undecryptable { inner, .. } => edisplay_line!(
+            context.io(),
+            "Inner transaction could not be decrypted at height {}, it was \
+             not applied.",
+            inner.outcome.height
+        ),
+        TrackedTxOutcome::InnerApplied { inner, .. } if inner.is_success() => {
+            display_line!(
+                context.io(),
+                "Inner transaction applied successfully at height {}.",
+                inner.outcome.height
+            )
+        }
+        TrackedTxOutcome::InnerApplied { inner, .. } => edisplay_line!(
+            context.io(),
+            "Inner transaction failed at height {} with code {:?}: {}",
+            inner.outcome.height,
+            inner.outcome.code,
+            inner.outcome.info
+        ),
+    }
+}
+
 pub async fn epoch_sleep<'a>(context: &impl Namada<'a>, _args: args::Query) {
     let start_epoch = query_and_print_epoch(context).await;
     loop {
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::Write;
+use std::path::PathBuf;
 
 use borsh::BorshDeserialize;
 use borsh_ext::BorshSerializeExt;
@@ -19,11 +20,16 @@ use namada::types::address::{Address, ImplicitAddress};
 use namada::types::dec::Dec;
 use namada::types::io::Io;
 use namada::types::key::{self, *};
-use namada::types::transaction::pos::{ConsensusKeyChange, InitValidator};
+use namada::types::transaction::pos::{
+    ConsensusKeyChange, InitValidator, ValidatorKeyProofs,
+};
 use namada_sdk::rpc::{TxBroadcastData, TxResponse};
 use namada_sdk::wallet::alias::validator_consensus_key;
-use namada_sdk::{display_line, edisplay_line, error, signing, tx, Namada};
+use namada_sdk::{
+    display_line, edisplay_line, error, prompt, signing, tx, Namada,
+};
 use rand::rngs::OsRng;
+use serde::Serialize;
 
 use super::rpc;
 use crate::cli::{args, safe_exit};
@@ -33,7 +39,6 @@ use crate::client::tx::signing::{
 };
 use crate::client::tx::tx::ProcessTxResponse;
 use crate::config::TendermintMode;
-use crate::facade::tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use crate::node::ledger::tendermint_node;
 use crate::wallet::{gen_validator_keys, read_and_confirm_encryption_password};
 
@@ -75,6 +80,17 @@ pub async fn sign<'a>(
     args: &args::Tx,
     signing_data: SigningTxData,
 ) -> Result<(), error::Error> {
+    // Show what is about to be signed, as decoded from the signed sections
+    display_line!(context.io(), "Transaction summary:\n{}", tx.describe());
+    if args.confirm_signing {
+        let answer =
+            prompt!(context.io(), "Sign this transaction? [y/N]: ").await;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            return Err(error::Error::Other(
+                "Signing the transaction was not confirmed".to_string(),
+            ));
+        }
+    }
     // Setup a reusable context for signing transactions using the Ledger
     if args.use_device {
         // Setup a reusable context for signing transactions using the Ledger
@@ -136,7 +152,7 @@ pub async fn sign<'a>(
                     }
                     // Get the Ledger to sign using our obtained derivation path
                     let response = app
-                        .sign(&path, &tx.serialize_to_vec())
+                        .sign(&path, &tx.signing_payload())
                         .await
                         .map_err(|err| error::Error::Other(err.to_string()))?;
                     // Sign the raw header if that is requested
@@ -381,7 +397,7 @@ pub async fn submit_change_consensus_key<'a>(
     let consensus_key = consensus_key
         .map(|key| match key {
             common::SecretKey::Ed25519(_) => key,
-            common::SecretKey::Secp256k1(_) => {
+            _ => {
                 edisplay_line!(
                     namada.io(),
                     "Consensus key can only be ed25519"
@@ -421,13 +437,9 @@ pub async fn submit_change_consensus_key<'a>(
             .await
             .unwrap();
 
-    let chain_id = tx_args.chain_id.clone().unwrap();
-    let mut tx = Tx::new(chain_id, tx_args.expiration);
+    let mut tx = tx::new_tx(namada, &tx_args).await?;
 
-    let data = ConsensusKeyChange {
-        validator: validator.clone(),
-        consensus_key: new_ck,
-    };
+    let data = ConsensusKeyChange::new(validator.clone(), &consensus_key);
 
     tx.add_code_from_hash(
         tx_code_hash,
@@ -539,7 +551,7 @@ pub async fn submit_init_validator<'a>(
     let consensus_key = consensus_key
         .map(|key| match key {
             common::SecretKey::Ed25519(_) => key,
-            common::SecretKey::Secp256k1(_) => {
+            _ => {
                 edisplay_line!(
                     namada.io(),
                     "Consensus key can only be ed25519"
@@ -564,10 +576,10 @@ pub async fn submit_init_validator<'a>(
                 .1
         });
 
-    let eth_cold_pk = eth_cold_key
+    let eth_cold_sk = eth_cold_key
         .map(|key| match key {
-            common::SecretKey::Secp256k1(_) => key.ref_to(),
-            common::SecretKey::Ed25519(_) => {
+            common::SecretKey::Secp256k1(_) => key,
+            _ => {
                 edisplay_line!(
                     namada.io(),
                     "Eth cold key can only be secp256k1"
@@ -590,13 +602,13 @@ pub async fn submit_init_validator<'a>(
                 )
                 .expect("Key generation should not fail.")
                 .1
-                .ref_to()
         });
+    let eth_cold_pk = eth_cold_sk.ref_to();
 
-    let eth_hot_pk = eth_hot_key
+    let eth_hot_sk = eth_hot_key
         .map(|key| match key {
-            common::SecretKey::Secp256k1(_) => key.ref_to(),
-            common::SecretKey::Ed25519(_) => {
+            common::SecretKey::Secp256k1(_) => key,
+            _ => {
                 edisplay_line!(
                     namada.io(),
                     "Eth hot key can only be secp256k1"
@@ -619,8 +631,8 @@ pub async fn submit_init_validator<'a>(
                 )
                 .expect("Key generation should not fail.")
                 .1
-                .ref_to()
         });
+    let eth_hot_pk = eth_hot_sk.ref_to();
     // To avoid wallet deadlocks in following operations
     drop(wallet);
 
@@ -699,13 +711,32 @@ pub async fn submit_init_validator<'a>(
             .await
             .unwrap();
 
-    let chain_id = tx_args.chain_id.clone().unwrap();
-    let mut tx = Tx::new(chain_id, tx_args.expiration);
+    let mut tx = tx::new_tx(namada, &tx_args).await?;
     let extra_section_hash = tx.add_extra_section_from_hash(
         validator_vp_code_hash,
         Some(validator_vp_code_path.to_string_lossy().into_owned()),
     );
 
+    // Prove the possession of the validator keys for the address of the
+    // first account key
+    let registering_address = match account_keys.first() {
+        Some(pk) => Address::from(pk),
+        None => {
+            edisplay_line!(
+                namada.io(),
+                "A validator needs at least one account key"
+            );
+            safe_exit(1)
+        }
+    };
+    let key_proofs = ValidatorKeyProofs::new(
+        &registering_address,
+        &consensus_key,
+        &eth_cold_sk,
+        &eth_hot_sk,
+        protocol_sk,
+    );
+
     let data = InitValidator {
         account_keys,
         threshold,
@@ -722,6 +753,7 @@ pub async fn submit_init_validator<'a>(
         website,
         discord_handle,
         validator_vp_code_hash: extra_section_hash,
+        key_proofs,
     };
 
     // Put together all the PKs that we have to sign with to verify ownership
@@ -1225,6 +1257,253 @@ where
     Ok(())
 }
 
+/// Write the given value as pretty JSON to the named file in the given folder,
+/// or in the current directory if no folder is given
+fn write_json_file<IO: Io>(
+    io: &IO,
+    output_folder: Option<PathBuf>,
+    filename: String,
+    value: &impl Serialize,
+) -> PathBuf {
+    let output_path = match output_folder {
+        Some(path) => path.join(filename),
+        None => filename.into(),
+    };
+    let out = File::create(&output_path).unwrap_or_else(|err| {
+        edisplay_line!(
+            io,
+            "Couldn't create the file {}: {}",
+            output_path.display(),
+            err
+        );
+        safe_exit(1)
+    });
+    serde_json::to_writer_pretty(out, value)
+        .expect("Should be able to write to file.");
+    output_path
+}
+
+pub fn export_unsigned_tx<IO: Io>(
+    io: &IO,
+    args::ExportUnsignedTx {
+        tx_data,
+        output_folder,
+    }: args::ExportUnsignedTx,
+) -> Result<(), error::Error> {
+    let tx = if let Ok(transaction) = Tx::deserialize(tx_data.as_ref()) {
+        transaction
+    } else {
+        edisplay_line!(io, "Couldn't decode the transaction.");
+        safe_exit(1)
+    };
+    let unsigned_tx = signing::export_unsigned_tx(&tx);
+    let output_path = write_json_file(
+        io,
+        output_folder,
+        format!("unsigned_{}.json", unsigned_tx.raw_header_hash),
+        &unsigned_tx,
+    );
+    display_line!(
+        io,
+        "Unsigned transaction exported to {}",
+        output_path.display()
+    );
+    Ok(())
+}
+
+pub fn sign_unsigned_tx<IO: Io>(
+    io: &IO,
+    args::SignUnsignedTx {
+        unsigned_tx,
+        signing_keys,
+        output_folder,
+    }: args::SignUnsignedTx,
+) -> Result<(), error::Error> {
+    let unsigned_tx = if let Ok(unsigned_tx) =
+        serde_json::from_slice::<signing::UnsignedTxFile>(&unsigned_tx)
+    {
+        unsigned_tx
+    } else {
+        edisplay_line!(io, "Couldn't decode the unsigned transaction.");
+        safe_exit(1)
+    };
+    display_line!(io, "Signing the transaction:");
+    for line in &unsigned_tx.summary {
+        display_line!(io, "  {}", line);
+    }
+    let signatures = signing::sign_unsigned_tx(&unsigned_tx, &signing_keys)?;
+    let output_path = write_json_file(
+        io,
+        output_folder,
+        format!("signatures_{}.json", signatures.raw_header_hash),
+        &signatures,
+    );
+    display_line!(
+        io,
+        "{} signature(s) serialized at {}",
+        signatures.signatures.len(),
+        output_path.display()
+    );
+    Ok(())
+}
+
+pub fn import_signatures<IO: Io>(
+    io: &IO,
+    args::ImportSignatures {
+        tx_data,
+        signatures,
+        output_folder,
+    }: args::ImportSignatures,
+) -> Result<(), error::Error> {
+    let mut tx = if let Ok(transaction) = Tx::deserialize(tx_data.as_ref()) {
+        transaction
+    } else {
+        edisplay_line!(io, "Couldn't decode the transaction.");
+        safe_exit(1)
+    };
+    for bytes in signatures {
+        let signatures = if let Ok(signatures) =
+            serde_json::from_slice::<signing::SignaturesFile>(&bytes)
+        {
+            signatures
+        } else {
+            edisplay_line!(io, "Couldn't decode the signatures.");
+            safe_exit(1)
+        };
+        signing::import_signatures(&mut tx, signatures)?;
+    }
+    let output_path = write_json_file(
+        io,
+        output_folder,
+        format!("{}.tx", tx.header_hash()),
+        &tx.serialize(),
+    );
+    display_line!(
+        io,
+        "Signed transaction serialized to {}",
+        output_path.display()
+    );
+    Ok(())
+}
+
+pub fn multisig_create<IO: Io>(
+    io: &IO,
+    args::MultisigCreate {
+        tx_data,
+        owner,
+        output_folder,
+    }: args::MultisigCreate,
+) -> Result<(), error::Error> {
+    let tx = if let Ok(transaction) = Tx::deserialize(tx_data.as_ref()) {
+        transaction
+    } else {
+        edisplay_line!(io, "Couldn't decode the transaction.");
+        safe_exit(1)
+    };
+    let bundle = signing::MultisigBundle::new(&tx, owner);
+    let output_path = write_json_file(
+        io,
+        output_folder,
+        format!("multisig_{}.json", bundle.raw_header_hash),
+        &bundle,
+    );
+    display_line!(io, "Multisig bundle created at {}", output_path.display());
+    Ok(())
+}
+
+pub fn multisig_sign<IO: Io>(
+    io: &IO,
+    args::MultisigSign {
+        bundle,
+        signing_keys,
+        output_folder,
+    }: args::MultisigSign,
+) -> Result<(), error::Error> {
+    let mut bundle = if let Ok(bundle) =
+        serde_json::from_slice::<signing::MultisigBundle>(&bundle)
+    {
+        bundle
+    } else {
+        edisplay_line!(io, "Couldn't decode the multisig bundle.");
+        safe_exit(1)
+    };
+    // Only display the summary once it is known to describe the tx
+    bundle.tx()?;
+    display_line!(io, "Signing the transaction on behalf of {}:", bundle.owner);
+    for line in &bundle.summary {
+        display_line!(io, "  {}", line);
+    }
+    for signature in &bundle.signatures {
+        display_line!(io, "Already signed by {}", signature.pubkey);
+    }
+    bundle.sign(&signing_keys)?;
+    let signer = signing_keys
+        .first()
+        .map(|key| PublicKeyHash::from(&key.ref_to()).to_string())
+        .unwrap_or_default();
+    let output_path = write_json_file(
+        io,
+        output_folder,
+        format!("multisig_{}_{}.json", bundle.raw_header_hash, signer),
+        &bundle,
+    );
+    display_line!(
+        io,
+        "Multisig bundle with {} signature(s) serialized at {}",
+        bundle.signatures.len(),
+        output_path.display()
+    );
+    Ok(())
+}
+
+pub async fn multisig_submit<'a, N: Namada<'a>>(
+    namada: &N,
+    args::MultisigSubmit { tx, bundles }: args::MultisigSubmit,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada::ledger::queries::Client>::Error: std::fmt::Display,
+{
+    let mut decoded = Vec::with_capacity(bundles.len());
+    for bytes in bundles {
+        if let Ok(bundle) =
+            serde_json::from_slice::<signing::MultisigBundle>(&bytes)
+        {
+            decoded.push(bundle);
+        } else {
+            edisplay_line!(namada.io(), "Couldn't decode the multisig bundle.");
+            safe_exit(1)
+        }
+    }
+    let bundle = signing::MultisigBundle::merge(decoded)?;
+    let owner = bundle.owner.clone();
+    // The threshold is checked against the account's current key set
+    let account = namada_sdk::rpc::get_account_info(namada.client(), &owner)
+        .await?
+        .ok_or_else(|| error::TxError::LocationDoesNotExist(owner.clone()))?;
+    let signed_tx = bundle.finalize(&account)?;
+    display_line!(
+        namada.io(),
+        "The signatures meet the threshold of {} for {}.",
+        account.threshold,
+        owner
+    );
+
+    submit_custom(
+        namada,
+        args::TxCustom {
+            tx,
+            code_path: None,
+            data_path: None,
+            serialized_tx: Some(
+                serde_json::to_vec(&signed_tx.serialize())
+                    .expect("Serializing a string to JSON shouldn't fail"),
+            ),
+            owner,
+        },
+    )
+    .await
+}
+
 pub async fn submit_reveal_pk<'a, N: Namada<'a>>(
     namada: &N,
     args: args::RevealPk,
@@ -1568,7 +1847,7 @@ pub async fn save_initialized_accounts<'a>(
 pub async fn broadcast_tx<'a>(
     namada: &impl Namada<'a>,
     to_broadcast: &TxBroadcastData,
-) -> Result<Response, error::Error> {
+) -> Result<tx::BroadcastResponse, error::Error> {
     tx::broadcast_tx(namada, to_broadcast).await
 }
 
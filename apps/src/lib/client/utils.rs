@@ -374,6 +374,7 @@ pub fn id_from_pk(pk: &common::PublicKey) -> TendermintNodeId {
             let digest = Sha256::digest(_pk.serialize_to_vec().as_slice());
             bytes.copy_from_slice(&digest[..TENDERMINT_NODE_ID_LENGTH]);
         }
+        _ => panic!("Tendermint node keys can only be ed25519 or secp256k1"),
     }
     TendermintNodeId::new(bytes)
 }
@@ -756,6 +757,7 @@ pub fn write_tendermint_node_key(
         common::SecretKey::Secp256k1(sk) => {
             (sk.serialize_to_vec(), "Secp256k1")
         }
+        _ => panic!("Tendermint node keys can only be ed25519 or secp256k1"),
     };
 
     let tm_node_keypair_json = json!({
@@ -119,6 +119,22 @@ pub struct Shell {
     pub action_at_height: Option<ActionAtHeight>,
     /// Specify if tendermint is started as validator, fullnode or seednode
     pub tendermint_mode: TendermintMode,
+    /// The number of threads checking the txs of the mempool in parallel
+    /// when preparing a proposal. The proposed txs don't depend on it. When
+    /// not set or lower than 2, the txs are checked serially.
+    #[serde(default)]
+    pub prepare_proposal_threads: Option<usize>,
+    /// Serve the debug queries, under the `/debug/` path, with the recent
+    /// rejections of txs, the statistics of the caches of the shell and a
+    /// summary of its mode. These are only served when the CometBFT RPC
+    /// listens on a loopback address. Disabled by default.
+    #[serde(default)]
+    pub debug_rpc: bool,
+    /// Retain the sections of the txs included in the given number of most
+    /// recent blocks, to serve them with the `tx_section` query. The sections
+    /// of older blocks are pruned. Disabled by default.
+    #[serde(default)]
+    pub retain_tx_sections: Option<u64>,
 }
 
 impl Ledger {
@@ -147,6 +163,9 @@ impl Ledger {
                 cometbft_dir: COMETBFT_DIR.into(),
                 action_at_height: None,
                 tendermint_mode: mode,
+                prepare_proposal_threads: None,
+                debug_rpc: false,
+                retain_tx_sections: None,
             },
             cometbft: tendermint_config,
             ethereum_bridge: ethereum_bridge::ledger::Config::default(),
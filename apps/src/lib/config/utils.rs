@@ -1,6 +1,6 @@
 //! Configuration utilities
 
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::str::FromStr;
 use std::{cmp, env};
 
@@ -47,6 +47,18 @@ fn num_of_threads_aux(
     }
 }
 
+/// Check whether the given address is only reachable from the local host,
+/// either as a loopback address or as a Unix socket
+pub fn is_local_addr(tm_addr: &TendermintAddress) -> bool {
+    match tm_addr {
+        TendermintAddress::Tcp { host, .. } => {
+            host == "localhost"
+                || IpAddr::from_str(host).map_or(false, |ip| ip.is_loopback())
+        }
+        TendermintAddress::Unix { .. } => true,
+    }
+}
+
 // FIXME: Handle this gracefully with either an Option or a Result.
 pub fn convert_tm_addr_to_socket_addr(
     tm_addr: &TendermintAddress,
@@ -140,4 +152,19 @@ mod test {
             assert_eq!(num_of_threads_aux(env_var, default), Either::Left(1));
         }
     }
+
+    /// Test that only loopback addresses and Unix sockets are local.
+    #[test]
+    fn test_is_local_addr() {
+        for (addr, local) in [
+            ("tcp://127.0.0.1:26657", true),
+            ("tcp://localhost:26657", true),
+            ("unix:///tmp/cometbft.sock", true),
+            ("tcp://0.0.0.0:26657", false),
+            ("tcp://10.0.0.1:26657", false),
+        ] {
+            let addr = TendermintAddress::from_str(addr).unwrap();
+            assert_eq!(is_local_addr(&addr), local, "{addr}");
+        }
+    }
 }
@@ -174,6 +174,22 @@ impl<M> BlockAllocator<states::BuildingEncryptedTxBatch<M>> {
             decrypted_txs: TxBin::default(),
         }
     }
+
+    /// Reserve space for a tx placed ahead of every other tx of the block,
+    /// such as the slash txs derived from the evidence of the block. The
+    /// space is taken off the block, and thus off the bins that are yet to
+    /// be initialized.
+    pub fn try_reserve(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
+        let space = tx.len() as u64;
+        let space_left = self.uninitialized_space_in_bytes();
+        if space > space_left {
+            return Err(AllocFailure::Rejected {
+                bin_resource_left: space_left,
+            });
+        }
+        self.block.allotted -= space;
+        Ok(())
+    }
 }
 
 impl<State> BlockAllocator<State> {
@@ -397,6 +413,34 @@ mod tests {
         );
     }
 
+    /// Check that the space reserved ahead of the encrypted txs is taken off
+    /// the bins of the decrypted and protocol txs.
+    #[test]
+    fn test_reserved_space_is_taken_off_the_block() {
+        const BLOCK_SIZE: u64 = 60;
+        const BLOCK_GAS: u64 = 1_000;
+
+        let mut alloc = BsaWrapperTxs::init(BLOCK_SIZE, BLOCK_GAS);
+        assert!(alloc.try_reserve(&[0; 10]).is_ok());
+        // encrypted txs still get 1/3 of the whole block
+        assert_eq!(alloc.encrypted_txs.space.allotted, 20);
+        // but the reserved space can't overlap with their bin
+        assert_matches!(
+            alloc.try_reserve(&[0; 31]),
+            Err(AllocFailure::Rejected {
+                bin_resource_left: 30
+            })
+        );
+        assert!(alloc.try_alloc(BlockResources::new(&[0; 20], 0)).is_ok());
+
+        let mut alloc = alloc.next_state();
+        assert_eq!(alloc.decrypted_txs.allotted, BLOCK_SIZE - (10 + 20));
+        assert!(alloc.try_alloc(&[0; 12]).is_ok());
+
+        let alloc = alloc.next_state();
+        assert_eq!(alloc.protocol_txs.allotted, BLOCK_SIZE - (10 + 20 + 12));
+    }
+
     // Test that we cannot include encrypted txs in a block
     // when the state invariants banish them from inclusion.
     #[test]
@@ -89,6 +89,22 @@ fn next_state<Mode>(
     }
 }
 
+impl EncryptedTxBatchAllocator {
+    /// Reserve space for a tx placed ahead of every other tx of the block,
+    /// see [`BlockAllocator::try_reserve`]
+    #[inline]
+    pub fn try_reserve(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
+        match self {
+            EncryptedTxBatchAllocator::WithEncryptedTxs(state) => {
+                state.try_reserve(tx)
+            }
+            EncryptedTxBatchAllocator::WithoutEncryptedTxs(state) => {
+                state.try_reserve(tx)
+            }
+        }
+    }
+}
+
 impl TryAlloc for EncryptedTxBatchAllocator {
     type Resources<'tx> = BlockResources<'tx>;
 
@@ -0,0 +1,270 @@
+//! A log of what the shell thinks of the txs it was given, for operators.
+//!
+//! When txs go missing, the [`DebugLog`] of the shell tells why: it keeps the
+//! last [`MAX_REJECTIONS`] txs rejected from the mempool or found invalid in
+//! a proposal, along with some counters. With the `debug_rpc` option of the
+//! shell, these are served as JSON by the queries under [`PATH_PREFIX`],
+//! along with the statistics of the caches of the shell and a summary of its
+//! mode. The summary only tells which keys are available, never the secret
+//! keys themselves.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use namada::types::hash::Hash;
+use namada::types::key::RefTo;
+use namada::types::time::DateTimeUtc;
+use namada::types::transaction::ResultCode;
+use serde::Serialize;
+
+use super::decode_cache::TxDecodeCache;
+use super::ShellMode;
+
+/// The prefix of the paths of the debug queries
+pub const PATH_PREFIX: &str = "/debug/";
+
+/// The maximum number of recent rejections that are kept
+pub const MAX_REJECTIONS: usize = 256;
+
+/// Where a tx was rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionSource {
+    /// The tx was rejected from the mempool
+    CheckTx,
+    /// The tx was found invalid in a proposal
+    ProcessProposal,
+}
+
+/// A rejected tx
+#[derive(Clone, Debug, Serialize)]
+pub struct Rejection {
+    /// Where the tx was rejected
+    pub source: RejectionSource,
+    /// The SHA-256 hash of the tx bytes, as known to CometBFT
+    pub hash: Hash,
+    /// The result code of the rejection
+    pub code: u32,
+    /// The reason of the rejection
+    pub reason: String,
+    /// When the tx was rejected
+    pub time: DateTimeUtc,
+}
+
+/// The counters of the rejections and of the hits of the caches of the shell
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CacheStats {
+    /// The number of txs decoded at the current height
+    pub decoded_txs: usize,
+    /// The number of times some tx bytes were found already decoded
+    pub decode_hits: usize,
+    /// The number of verified wrapper signatures that are kept
+    pub verified_sigs: usize,
+    /// The number of times the signatures of a wrapper were found verified
+    pub sig_hits: usize,
+    /// The number of times the signatures of a wrapper were verified
+    pub sig_verifications: usize,
+    /// The number of txs rejected from the mempool
+    pub check_tx_rejections: usize,
+    /// The number of txs rejected from the mempool as replays of applied txs
+    pub replay_rejections: usize,
+    /// The number of invalid txs found in proposals
+    pub proposal_tx_rejections: usize,
+    /// The number of proposals rejected
+    pub rejected_proposals: usize,
+    /// The number of mempool wrappers left out of the proposals of this node
+    pub excluded_wrappers: usize,
+}
+
+/// A summary of the mode of the shell
+#[derive(Clone, Debug, Serialize)]
+pub struct ModeSummary {
+    /// Either `validator`, `full` or `seed`
+    pub mode: &'static str,
+    /// The address of the validator, if any
+    pub validator_address: Option<String>,
+    /// The public protocol key of the validator, if any
+    pub protocol_key: Option<String>,
+    /// The public Ethereum bridge key of the validator, if any
+    pub eth_bridge_key: Option<String>,
+    /// Whether the validator has an Ethereum oracle
+    pub eth_oracle: bool,
+}
+
+impl ModeSummary {
+    /// Summarize the given mode of the shell
+    pub(super) fn new(mode: &ShellMode) -> Self {
+        match mode {
+            ShellMode::Validator {
+                data, eth_oracle, ..
+            } => Self {
+                mode: "validator",
+                validator_address: Some(data.address.to_string()),
+                protocol_key: Some(
+                    data.keys.protocol_keypair.ref_to().to_string(),
+                ),
+                eth_bridge_key: Some(
+                    data.keys.eth_bridge_keypair.ref_to().to_string(),
+                ),
+                eth_oracle: eth_oracle.is_some(),
+            },
+            ShellMode::Full => Self::without_keys("full"),
+            ShellMode::Seed => Self::without_keys("seed"),
+        }
+    }
+
+    fn without_keys(mode: &'static str) -> Self {
+        Self {
+            mode,
+            validator_address: None,
+            protocol_key: None,
+            eth_bridge_key: None,
+            eth_oracle: false,
+        }
+    }
+}
+
+/// A log of the recent rejections of txs
+#[derive(Debug, Default)]
+pub struct DebugLog {
+    /// Whether the rejections are logged and served. A disabled log doesn't
+    /// record anything.
+    enabled: bool,
+    /// The recent rejections, oldest first
+    rejections: Mutex<VecDeque<Rejection>>,
+    check_tx_rejections: AtomicUsize,
+    replay_rejections: AtomicUsize,
+    proposal_tx_rejections: AtomicUsize,
+    rejected_proposals: AtomicUsize,
+    excluded_wrappers: AtomicUsize,
+}
+
+impl DebugLog {
+    /// A log that records rejections if enabled
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the rejections are logged and served
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Record the rejection of the given tx bytes, evicting the oldest
+    /// rejection if the log is full
+    pub fn record(
+        &self,
+        source: RejectionSource,
+        tx_bytes: &[u8],
+        code: u32,
+        reason: &str,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let counter = match source {
+            RejectionSource::CheckTx => &self.check_tx_rejections,
+            RejectionSource::ProcessProposal => &self.proposal_tx_rejections,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if code == u32::from(ResultCode::ReplayTx) {
+            self.replay_rejections.fetch_add(1, Ordering::Relaxed);
+        }
+        let rejection = Rejection {
+            source,
+            hash: Hash::sha256(tx_bytes),
+            code,
+            reason: reason.to_owned(),
+            time: DateTimeUtc::now(),
+        };
+        let mut rejections = self.rejections();
+        if rejections.len() == MAX_REJECTIONS {
+            rejections.pop_front();
+        }
+        rejections.push_back(rejection);
+    }
+
+    /// Count a rejected proposal
+    pub fn count_rejected_proposal(&self) {
+        if self.enabled {
+            self.rejected_proposals.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a mempool wrapper left out of a proposal of this node
+    pub fn count_excluded_wrapper(&self) {
+        if self.enabled {
+            self.excluded_wrappers.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The recent rejections, oldest first
+    pub fn recent_rejections(&self) -> Vec<Rejection> {
+        self.rejections().iter().cloned().collect()
+    }
+
+    /// The counters of the rejections, along with those of the given cache
+    pub fn cache_stats(&self, decode_cache: &TxDecodeCache) -> CacheStats {
+        let sigs = decode_cache.verified_sigs();
+        CacheStats {
+            decoded_txs: decode_cache.len(),
+            decode_hits: decode_cache.hits(),
+            verified_sigs: sigs.len(),
+            sig_hits: sigs.hits(),
+            sig_verifications: sigs.verifications(),
+            check_tx_rejections: load(&self.check_tx_rejections),
+            replay_rejections: load(&self.replay_rejections),
+            proposal_tx_rejections: load(&self.proposal_tx_rejections),
+            rejected_proposals: load(&self.rejected_proposals),
+            excluded_wrappers: load(&self.excluded_wrappers),
+        }
+    }
+
+    fn rejections(&self) -> MutexGuard<'_, VecDeque<Rejection>> {
+        // The rejections are left consistent by every critical section
+        self.rejections
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn load(counter: &AtomicUsize) -> usize {
+    counter.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that only the last [`MAX_REJECTIONS`] rejections are kept, and
+    /// that a disabled log doesn't record anything.
+    #[test]
+    fn test_debug_log() {
+        let log = DebugLog::new(true);
+        let disabled = DebugLog::new(false);
+        for i in 0..MAX_REJECTIONS + 2 {
+            let tx_bytes = i.to_le_bytes();
+            for log in [&log, &disabled] {
+                log.record(
+                    RejectionSource::CheckTx,
+                    &tx_bytes,
+                    ResultCode::ReplayTx.into(),
+                    "replay",
+                );
+            }
+        }
+        let rejections = log.recent_rejections();
+        assert_eq!(rejections.len(), MAX_REJECTIONS);
+        assert_eq!(rejections[0].hash, Hash::sha256(2_usize.to_le_bytes()));
+        let stats = log.cache_stats(&TxDecodeCache::default());
+        assert_eq!(stats.check_tx_rejections, MAX_REJECTIONS + 2);
+        assert_eq!(stats.replay_rejections, MAX_REJECTIONS + 2);
+        assert!(disabled.recent_rejections().is_empty());
+        let stats = disabled.cache_stats(&TxDecodeCache::default());
+        assert_eq!(stats.check_tx_rejections, 0);
+    }
+}
@@ -0,0 +1,272 @@
+//! A cache of the txs decoded at the current height.
+//!
+//! The same tx bytes are decoded when checked by the mempool, when proposed,
+//! when the proposal is processed and when the block is finalized. The
+//! [`TxDecodeCache`] of the shell keeps the outcome of decoding some bytes,
+//! keyed by their SHA-256 hash, along with the verdict of the validation of
+//! the decoded tx, so that each is only computed once per height. Bytes that
+//! do not decode are cached as well: the outcome only depends on the bytes,
+//! whether they are well-formed or not. The cache is cleared when a block is
+//! committed.
+//!
+//! The wrapper signatures found valid are kept for the next blocks by the
+//! [`VerifiedSigs`] of the cache, so that the verdicts of the txs decoded
+//! again at a later height don't verify them again.
+//!
+//! Txs decoded from the shared buffers of [`TxBytes`] keep these buffers
+//! alive, as the payloads of their sections are sliced out of them.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use namada::proto::{SigFailure, Tx, TxError};
+use namada::types::hash::Hash;
+
+use super::verified_sigs::VerifiedSigs;
+use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
+
+/// The maximum number of tx bytes whose decoding is cached at a height.
+/// Bytes decoded past this limit are not cached.
+const MAX_CACHED_BYTES: usize = 64 * 1024 * 1024;
+
+/// The outcome of decoding some tx bytes
+#[derive(Debug)]
+pub struct DecodedTx {
+    /// The decoded tx, or the reason the bytes couldn't be decoded
+    tx: Result<Tx, String>,
+    /// The verdict of the validation of the decoded tx, once computed
+    verdict: OnceLock<Result<(), String>>,
+    /// The wrapper signatures verified in the last blocks
+    sigs: Arc<VerifiedSigs>,
+}
+
+impl DecodedTx {
+    /// Wrap the outcome of decoding some tx bytes
+    fn new(
+        tx: Result<Tx, namada::proto::Error>,
+        sigs: Arc<VerifiedSigs>,
+    ) -> Self {
+        Self {
+            tx: tx.map_err(|err| err.to_string()),
+            verdict: OnceLock::new(),
+            sigs,
+        }
+    }
+
+    /// The decoded tx, or the reason the bytes couldn't be decoded
+    pub fn tx(&self) -> Result<&Tx, &str> {
+        self.tx.as_ref().map_err(String::as_str)
+    }
+
+    /// The verdict of [`Tx::validate_tx`] on the decoded tx, which is only
+    /// computed the first time it is asked for, and doesn't verify again the
+    /// wrapper signatures verified in the last blocks. Fails with the
+    /// decoding error if the bytes couldn't be decoded, or with the
+    /// description of the validation failure, see [`validation_failure`].
+    pub fn validate(&self) -> Result<(), &str> {
+        let tx = self.tx()?;
+        self.verdict
+            .get_or_init(|| {
+                self.sigs
+                    .validate_tx(tx)
+                    .map_err(|err| validation_failure(&err))
+            })
+            .as_ref()
+            .map(|_| ())
+            .map_err(String::as_str)
+    }
+
+    /// The verdict of the validation of the decoded tx, if already computed
+    pub fn known_verdict(&self) -> Option<Result<(), &str>> {
+        self.verdict
+            .get()
+            .map(|verdict| verdict.as_ref().map(|_| ()).map_err(String::as_str))
+    }
+}
+
+/// Describe the failure of the validation of a tx, as reported in the info of
+/// its result. The kind of a failure of signature verification is named
+/// first, so that a missing signature, a signature by an unexpected key and
+/// an invalid signature are told apart.
+fn validation_failure(err: &TxError) -> String {
+    let kind = match err {
+        TxError::SigVerification { failure, .. } => match failure {
+            SigFailure::NoSignatureForTarget => "Missing signature",
+            SigFailure::SignerMismatch => "Unexpected signer",
+            SigFailure::InvalidSignature => "Invalid signature",
+            SigFailure::Other => return err.to_string(),
+        },
+        _ => return err.to_string(),
+    };
+    format!("{kind}. {err}")
+}
+
+/// The txs decoded at the current height, by hash of their bytes
+#[derive(Debug, Default)]
+struct Entries {
+    decoded: HashMap<Hash, Arc<DecodedTx>>,
+    /// The total length of the cached tx bytes
+    bytes: usize,
+}
+
+/// A cache of the txs decoded at the current height
+#[derive(Debug)]
+pub struct TxDecodeCache {
+    /// Whether decoded txs are kept. A disabled cache decodes the given bytes
+    /// every time.
+    enabled: bool,
+    entries: Mutex<Entries>,
+    /// The wrapper signatures verified in the last blocks
+    sigs: Arc<VerifiedSigs>,
+    /// The number of times the outcome of decoding some bytes was found in
+    /// the cache
+    hits: AtomicUsize,
+}
+
+impl Default for TxDecodeCache {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            entries: Mutex::default(),
+            sigs: Arc::default(),
+            hits: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl TxDecodeCache {
+    /// A cache that doesn't keep anything, for comparison purposes
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            entries: Mutex::default(),
+            sigs: Arc::new(VerifiedSigs::disabled()),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Decode the given tx bytes, or get the outcome of their previous
+    /// decoding at this height
+    pub fn decode(&self, tx_bytes: &[u8]) -> Arc<DecodedTx> {
+        self.get_or_decode(tx_bytes, || Tx::try_from(tx_bytes))
+    }
+
+    /// Decode the given tx bytes as with [`TxDecodeCache::decode`], sharing
+    /// their buffer with the payloads of the sections of the decoded tx
+    pub fn decode_shared(&self, tx_bytes: &TxBytes) -> Arc<DecodedTx> {
+        self.get_or_decode(tx_bytes, || Tx::try_from(tx_bytes.clone()))
+    }
+
+    /// Get the outcome of the previous decoding of the given tx bytes at this
+    /// height, or decode them with the given function
+    fn get_or_decode(
+        &self,
+        tx_bytes: &[u8],
+        decode: impl FnOnce() -> Result<Tx, namada::proto::Error>,
+    ) -> Arc<DecodedTx> {
+        if !self.enabled {
+            return Arc::new(DecodedTx::new(decode(), self.sigs.clone()));
+        }
+        let hash = Hash::sha256(tx_bytes);
+        if let Some(decoded) = self.entries().decoded.get(&hash) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return decoded.clone();
+        }
+        // Don't hold the lock while decoding
+        let decoded = Arc::new(DecodedTx::new(decode(), self.sigs.clone()));
+        let mut entries = self.entries();
+        if entries.bytes + tx_bytes.len() <= MAX_CACHED_BYTES
+            && entries.decoded.insert(hash, decoded.clone()).is_none()
+        {
+            entries.bytes += tx_bytes.len();
+        }
+        decoded
+    }
+
+    /// The number of cached txs
+    pub fn len(&self) -> usize {
+        self.entries().decoded.len()
+    }
+
+    /// Check whether no tx is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of times the outcome of decoding some bytes was found in
+    /// the cache, since the cache was created
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The wrapper signatures verified in the last blocks
+    pub fn verified_sigs(&self) -> &VerifiedSigs {
+        &self.sigs
+    }
+
+    /// Drop the cached txs and age the verified signatures, once their block
+    /// is committed
+    pub fn clear(&self) {
+        *self.entries() = Entries::default();
+        self.sigs.commit();
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // The entries are left consistent by every critical section
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::types::chain::ChainId;
+
+    use super::*;
+
+    /// Check that decoding outcomes are cached, including failures, and are
+    /// the same as without a cache.
+    #[test]
+    fn test_decode_cache() {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1], None).add_serialized_data(vec![2]);
+        let tx_bytes = tx.to_bytes();
+        let garbage = vec![0xff; 32];
+
+        let cache = TxDecodeCache::default();
+        let disabled = TxDecodeCache::disabled();
+        for bytes in [&tx_bytes, &garbage] {
+            let cached = cache.decode(bytes);
+            let uncached = disabled.decode(bytes);
+            assert_eq!(
+                cached.tx().map(Tx::to_bytes),
+                uncached.tx().map(Tx::to_bytes)
+            );
+            assert_eq!(cached.validate(), uncached.validate());
+            assert!(Arc::ptr_eq(&cached, &cache.decode(bytes)));
+            assert!(!Arc::ptr_eq(&uncached, &disabled.decode(bytes)));
+        }
+        assert!(cache.decode(&garbage).tx().is_err());
+        assert_eq!(cache.decode(&tx_bytes).known_verdict(), Some(Ok(())));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.hits(), 4);
+        assert!(disabled.is_empty());
+        assert_eq!(disabled.hits(), 0);
+        // Shared bytes are looked up by their contents
+        let shared = TxBytes::from(tx_bytes.clone());
+        assert!(Arc::ptr_eq(
+            &cache.decode(&tx_bytes),
+            &cache.decode_shared(&shared)
+        ));
+        assert_eq!(
+            disabled.decode_shared(&shared).tx().map(Tx::to_bytes),
+            Ok(tx_bytes.clone())
+        );
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.decode(&tx_bytes).known_verdict(), None);
+    }
+}
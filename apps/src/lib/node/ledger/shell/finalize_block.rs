@@ -1,11 +1,12 @@
 //! Implementation of the `FinalizeBlock` ABCI++ method for the Shell
 
 use data_encoding::HEXUPPER;
+use namada::core::ledger::fee_records;
 use namada::core::ledger::inflation;
 use namada::core::ledger::masp_conversions::update_allowed_conversions;
 use namada::core::ledger::pgf::ADDRESS as pgf_address;
 use namada::ledger::events::EventType;
-use namada::ledger::gas::{GasMetering, TxGasMeter};
+use namada::ledger::gas::{Gas, GasMetering, TxGasMeter};
 use namada::ledger::parameters::storage as params_storage;
 use namada::ledger::pos::{namada_proof_of_stake, staking_token_address};
 use namada::ledger::protocol;
@@ -23,6 +24,7 @@ use namada::types::storage::{BlockHash, BlockResults, Epoch, Header};
 use namada::types::transaction::protocol::{
     ethereum_tx_data_variants, ProtocolTxType,
 };
+use namada::types::transaction::hash_tx;
 use namada::types::vote_extensions::ethereum_events::MultiSignedEthEvent;
 
 use super::governance::execute_governance_proposals;
@@ -82,10 +84,12 @@ where
         );
 
         // Finalize the transactions' hashes from the previous block
-        for hash in self.wl_storage.storage.iter_replay_protection() {
+        for (hash, expiration) in
+            self.wl_storage.storage.iter_replay_protection()
+        {
             self.wl_storage
                 .write_log
-                .finalize_tx_hash(hash)
+                .finalize_tx_hash(hash, expiration)
                 .expect("Failed tx hashes finalization")
         }
 
@@ -181,6 +185,17 @@ where
                 &mut self.wl_storage,
                 current_epoch,
             )?;
+            fee_records::prune_block_fees(&mut self.wl_storage, current_epoch)?;
+        }
+
+        if let Some(retained_blocks) = self.retain_tx_sections {
+            // Prune the tx sections of the blocks that fall out of the
+            // retention window with this one
+            let oldest_retained =
+                (height.0 + 1).saturating_sub(retained_blocks);
+            self.wl_storage
+                .write_log
+                .prune_tx_sections(BlockHeight(oldest_retained));
         }
 
         let mut stats = InternalStats::default();
@@ -199,8 +214,9 @@ where
         // Tracks the accepted transactions
         self.wl_storage.storage.block.results = BlockResults::default();
         for (tx_index, processed_tx) in req.txs.iter().enumerate() {
-            let tx = if let Ok(tx) = Tx::try_from(processed_tx.tx.as_ref()) {
-                tx
+            let decoded = self.tx_decode_cache.decode_shared(&processed_tx.tx);
+            let tx = if let Ok(tx) = decoded.tx() {
+                tx.clone()
             } else {
                 tracing::error!(
                     "FinalizeBlock received a tx that could not be \
@@ -211,8 +227,8 @@ where
             };
             // If [`process_proposal`] rejected a Tx due to invalid signature,
             // emit an event here and move on to next tx.
-            if ErrorCodes::from_u32(processed_tx.result.code).unwrap()
-                == ErrorCodes::InvalidSig
+            if ResultCode::from_u32(processed_tx.result.code).unwrap()
+                == ResultCode::InvalidSig
             {
                 let mut tx_event = match tx.header().tx_type {
                     TxType::Wrapper(_) | TxType::Protocol(_) => {
@@ -231,12 +247,13 @@ where
                 tx_event["code"] = processed_tx.result.code.to_string();
                 tx_event["info"] =
                     format!("Tx rejected: {}", &processed_tx.result.info);
+                tx_event["gas_wanted"] = declared_gas_limit(&tx).to_string();
                 tx_event["gas_used"] = "0".into();
                 response.events.push(tx_event);
                 continue;
             }
 
-            if tx.validate_tx().is_err() {
+            if decoded.validate().is_err() {
                 tracing::error!(
                     "Internal logic error: FinalizeBlock received tx that \
                      could not be deserialized to a valid TxType"
@@ -246,33 +263,43 @@ where
             let tx_header = tx.header();
             // If [`process_proposal`] rejected a Tx, emit an event here and
             // move on to next tx
-            if ErrorCodes::from_u32(processed_tx.result.code).unwrap()
-                != ErrorCodes::Ok
+            if ResultCode::from_u32(processed_tx.result.code).unwrap()
+                != ResultCode::Ok
             {
                 let mut tx_event = Event::new_tx_event(&tx, height.0);
                 tx_event["code"] = processed_tx.result.code.to_string();
                 tx_event["info"] =
                     format!("Tx rejected: {}", &processed_tx.result.info);
+                tx_event["gas_wanted"] = declared_gas_limit(&tx).to_string();
                 tx_event["gas_used"] = "0".into();
-                response.events.push(tx_event);
                 // if the rejected tx was decrypted, remove it
                 // from the queue of txs to be processed
                 if let TxType::Decrypted(_) = &tx_header.tx_type {
-                    self.wl_storage
+                    let tx_in_queue = self
+                        .wl_storage
                         .storage
                         .tx_queue
                         .pop()
                         .expect("Missing wrapper tx in queue");
+                    tx_event["gas_wanted"] =
+                        declared_gas_limit(&tx_in_queue.tx).to_string();
                 }
+                response.events.push(tx_event);
 
                 continue;
             }
 
+            // Whether the wrapper of a decrypted tx carried a valid testnet
+            // faucet withdrawal PoW solution
+            #[cfg(not(feature = "mainnet"))]
+            let mut has_valid_pow = false;
             let (mut tx_event, embedding_wrapper, mut tx_gas_meter, wrapper) =
                 match &tx_header.tx_type {
                     TxType::Wrapper(wrapper) => {
                         stats.increment_wrapper_txs();
-                        let tx_event = Event::new_tx_event(&tx, height.0);
+                        let mut tx_event = Event::new_tx_event(&tx, height.0);
+                        tx_event["tendermint_hash"] =
+                            hash_tx(processed_tx.tx.as_ref()).to_string();
                         let gas_meter = TxGasMeter::new(wrapper.gas_limit);
                         (tx_event, None, gas_meter, Some(tx.clone()))
                     }
@@ -285,6 +312,10 @@ where
                             .pop()
                             .expect("Missing wrapper tx in queue");
                         let mut event = Event::new_tx_event(&tx, height.0);
+                        // Allow following the inner tx by the hash of the
+                        // broadcast wrapper bytes
+                        event["tendermint_hash"] =
+                            tx_in_queue.tx.tendermint_hash().to_string();
 
                         match inner {
                             DecryptedTx::Decrypted => {
@@ -308,12 +339,24 @@ where
                                                 decrypted."
                                     .into();
                                 event["code"] =
-                                    ErrorCodes::Undecryptable.into();
+                                    ResultCode::Undecryptable.into();
+                                // Only the gas of the wrapper was used
+                                set_gas_attributes(
+                                    &mut event,
+                                    declared_gas_limit(&tx_in_queue.tx),
+                                    &TxGasMeter::new_from_sub_limit(
+                                        tx_in_queue.gas,
+                                    ),
+                                );
                                 response.events.push(event);
                                 continue;
                             }
                         }
 
+                        #[cfg(not(feature = "mainnet"))]
+                        {
+                            has_valid_pow = tx_in_queue.has_valid_pow;
+                        }
                         (
                             event,
                             Some(tx_in_queue.tx),
@@ -338,6 +381,29 @@ where
                             TxGasMeter::new_from_sub_limit(0.into()),
                             None,
                         ),
+                        ProtocolTxType::Slash => {
+                            // The evidence was checked against the block's
+                            // in process proposal, and is only slashed once
+                            // if it was also delivered by CometBFT
+                            match tx.data().and_then(|data| {
+                                SlashEvidence::try_from_slice(&data).ok()
+                            }) {
+                                Some(evidence) => {
+                                    self.apply_slash_evidence(&evidence)
+                                }
+                                None => tracing::error!(
+                                    "Internal logic error: FinalizeBlock \
+                                     received a slash tx whose evidence \
+                                     couldn't be decoded"
+                                ),
+                            }
+                            (
+                                Event::new_tx_event(&tx, height.0),
+                                None,
+                                TxGasMeter::new_from_sub_limit(0.into()),
+                                None,
+                            )
+                        }
                         ProtocolTxType::EthEventsVext => {
                             let ext =
                             ethereum_tx_data_variants::EthEventsVext::try_from(
@@ -394,6 +460,21 @@ where
                         }
                     },
                 };
+            if self.retain_tx_sections.is_some() {
+                self.retain_tx_sections(&tx);
+            }
+            let gas_wanted =
+                declared_gas_limit(embedding_wrapper.as_ref().unwrap_or(&tx));
+            // The payer and token of the fee of a wrapper, along with the
+            // amount charged to it
+            let mut wrapper_fee = match &tx_header.tx_type {
+                TxType::Wrapper(wrapper) => Some((
+                    wrapper.fee_payer(),
+                    wrapper.fee.token.clone(),
+                    token::Amount::default(),
+                )),
+                _ => None,
+            };
 
             match protocol::dispatch_tx(
                 tx,
@@ -403,15 +484,23 @@ where
                         .try_into()
                         .expect("transaction index out of bounds"),
                 ),
+                embedding_wrapper.as_ref().map(|wrapper| &wrapper.header),
                 &mut tx_gas_meter,
                 &mut self.wl_storage,
                 &mut self.vp_wasm_cache,
                 &mut self.tx_wasm_cache,
                 Some(&native_block_proposer_address),
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow,
             )
             .map_err(Error::TxApply)
             {
                 Ok(result) => {
+                    if let (Some((_, _, fee)), Some(charged)) =
+                        (wrapper_fee.as_mut(), result.wrapper_fee)
+                    {
+                        *fee = charged;
+                    }
                     if result.is_accepted() {
                         if let EventType::Accepted = tx_event.event_type {
                             // Wrapper transaction
@@ -419,9 +508,16 @@ where
                                 "Wrapper transaction {} was accepted",
                                 tx_event["hash"]
                             );
+                            let wrapper =
+                                wrapper.expect("Missing expected wrapper");
+                            #[cfg(not(feature = "mainnet"))]
+                            let has_valid_pow =
+                                self.invalidate_pow_solution_if_valid(&wrapper);
                             self.wl_storage.storage.tx_queue.push(TxInQueue {
-                                tx: wrapper.expect("Missing expected wrapper"),
+                                tx: wrapper,
                                 gas: tx_gas_meter.get_available_gas(),
+                                #[cfg(not(feature = "mainnet"))]
+                                has_valid_pow,
                             });
                         } else {
                             tracing::trace!(
@@ -437,7 +533,7 @@ where
                         }
                         self.wl_storage.commit_tx();
                         if !tx_event.contains_key("code") {
-                            tx_event["code"] = ErrorCodes::Ok.into();
+                            tx_event["code"] = ResultCode::Ok.into();
                             self.wl_storage
                                 .storage
                                 .block
@@ -451,6 +547,14 @@ where
                             event["height"] = height.to_string();
                             response.events.push(event);
                         }
+                        for event in &result.tx_events {
+                            // Add the tx events besides the tx_event, with
+                            // the hash and height of the tx
+                            let mut event = Event::from(event.clone());
+                            event["hash"] = tx_event["hash"].clone();
+                            event["height"] = height.to_string();
+                            response.events.push(event);
+                        }
                         match serde_json::to_string(
                             &result.initialized_accounts,
                         ) {
@@ -485,9 +589,8 @@ where
 
                         stats.increment_rejected_txs();
                         self.wl_storage.drop_tx();
-                        tx_event["code"] = ErrorCodes::InvalidTx.into();
+                        tx_event["code"] = ResultCode::InvalidTx.into();
                     }
-                    tx_event["gas_used"] = result.gas_used.to_string();
                     tx_event["info"] = result.to_string();
                 }
                 Err(msg) => {
@@ -496,6 +599,16 @@ where
                         tx_event["hash"],
                         msg
                     );
+                    if let (
+                        Some((_, _, fee)),
+                        Error::TxApply(protocol::Error::WrapperGasError {
+                            fee: charged,
+                            ..
+                        }),
+                    ) = (wrapper_fee.as_mut(), &msg)
+                    {
+                        *fee = *charged;
+                    }
 
                     // If transaction type is Decrypted and didn't failed
                     // because of out of gas nor invalid
@@ -531,17 +644,35 @@ where
                     stats.increment_errored_txs();
                     self.wl_storage.drop_tx();
 
-                    tx_event["gas_used"] =
-                        tx_gas_meter.get_tx_consumed_gas().to_string();
                     tx_event["info"] = msg.to_string();
                     if let EventType::Accepted = tx_event.event_type {
                         // If wrapper, invalid tx error code
-                        tx_event["code"] = ErrorCodes::InvalidTx.into();
+                        tx_event["code"] = ResultCode::InvalidTx.into();
                     } else {
-                        tx_event["code"] = ErrorCodes::WasmRuntimeError.into();
+                        let code = match &msg {
+                            Error::TxApply(protocol::Error::TxAborted(
+                                error,
+                            )) => {
+                                // The tx wasm aborted with a typed error
+                                tx_event["tx_error"] = error.to_string();
+                                ResultCode::from(error)
+                            }
+                            Error::TxApply(
+                                protocol::Error::MissingSection(_),
+                            ) => ResultCode::MissingTxSection,
+                            _ => ResultCode::WasmRuntimeError,
+                        };
+                        tx_event["code"] = code.into();
                     }
                 }
             }
+            set_gas_attributes(&mut tx_event, gas_wanted, &tx_gas_meter);
+            // The fee is charged even if the wrapper then failed
+            if let Some((payer, token, fee)) = wrapper_fee {
+                response
+                    .events
+                    .push(Event::new_fee_event(&tx_event, &payer, &token, fee));
+            }
             response.events.push(tx_event);
         }
 
@@ -861,13 +992,116 @@ where
     // both the hashes.
     fn commit_inner_tx_hash(&mut self, wrapper_tx: Tx) {
         self.wl_storage
-            .write_tx_hash(wrapper_tx.raw_header_hash())
+            .write_tx_hash(
+                wrapper_tx.raw_header_hash(),
+                wrapper_tx.header.expiration,
+            )
             .expect("Error while writing tx hash to storage");
 
         self.wl_storage
             .delete_tx_hash(wrapper_tx.header_hash())
             .expect("Error while deleting tx hash from storage");
     }
+
+    // Validate the testnet faucet withdrawal PoW solution attached to an
+    // accepted wrapper tx, if any, and invalidate it so that it cannot be used
+    // again. Returns whether the wrapper had a valid solution.
+    #[cfg(not(feature = "mainnet"))]
+    fn invalidate_pow_solution_if_valid(&mut self, wrapper_tx: &Tx) -> bool {
+        wrapper_tx
+            .pow_solution()
+            .map(|solution| {
+                solution
+                    .invalidate_if_valid(&mut self.wl_storage)
+                    .unwrap_or_else(|err| {
+                        tracing::error!(
+                            "Failed to validate the PoW solution of wrapper \
+                             tx {}: {err}",
+                            wrapper_tx.header_hash()
+                        );
+                        false
+                    })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the gas wanted and used by a tx delivered by CometBFT, before the
+    /// block is finalized. The gas used by a wrapper is known from its
+    /// validation alone. A decrypted tx is only executed once the block is
+    /// finalized, after CometBFT got its response, so it reports no gas used
+    /// here: its executed gas is reported in the `gas_used` attribute of its
+    /// `applied` event. The decrypted txs of a block are delivered in the
+    /// order of the queue of wrappers, so the wrapper declaring the gas
+    /// wanted by a decrypted tx is found at its index among them.
+    pub(crate) fn delivered_tx_gas(
+        &self,
+        tx: &Tx,
+        tx_bytes: &[u8],
+        decrypted_index: usize,
+    ) -> (Gas, Gas) {
+        match tx.header().tx_type {
+            TxType::Wrapper(wrapper) => {
+                let mut tx_gas_meter = TxGasMeter::new(wrapper.gas_limit);
+                // A wrapper out of gas is rejected at finalization, with its
+                // gas used still reported
+                let _ = tx_gas_meter.add_wrapper_gas(tx_bytes);
+                let gas_wanted = declared_gas_limit(tx);
+                (gas_wanted, gas_used(gas_wanted, &tx_gas_meter))
+            }
+            TxType::Decrypted(_) => (
+                self.wl_storage
+                    .storage
+                    .tx_queue
+                    .get(decrypted_index)
+                    .map(|wrapper| declared_gas_limit(&wrapper.tx))
+                    .unwrap_or_default(),
+                Gas::default(),
+            ),
+            _ => (Gas::default(), Gas::default()),
+        }
+    }
+
+    // Retain the sections of a tx included in the current block, so that they
+    // can be queried by their hash
+    fn retain_tx_sections(&mut self, tx: &Tx) {
+        for section in &tx.sections {
+            self.wl_storage.write_log.write_tx_section(
+                section.get_hash(),
+                section.serialize_to_vec(),
+            );
+        }
+    }
+}
+
+/// Get the gas limit declared by a wrapper tx. Any other tx type declares no
+/// gas.
+fn declared_gas_limit(tx: &Tx) -> Gas {
+    tx.header()
+        .wrapper()
+        .map(|wrapper| wrapper.gas_limit.into())
+        .unwrap_or_default()
+}
+
+/// Set the gas wanted and used by a tx in its event
+fn set_gas_attributes(
+    tx_event: &mut Event,
+    gas_wanted: Gas,
+    tx_gas_meter: &TxGasMeter,
+) {
+    tx_event["gas_wanted"] = gas_wanted.to_string();
+    tx_event["gas_used"] = gas_used(gas_wanted, tx_gas_meter).to_string();
+}
+
+/// Get the gas used by a tx from its gas meter. The meter of a decrypted tx
+/// only tracks the gas left over by its wrapper, so the gas used by the
+/// wrapper is accounted for as well.
+fn gas_used(gas_wanted: Gas, tx_gas_meter: &TxGasMeter) -> Gas {
+    let wrapper_gas = gas_wanted
+        .checked_sub(tx_gas_meter.tx_gas_limit)
+        .unwrap_or_default();
+    wrapper_gas
+        .checked_add(tx_gas_meter.get_tx_consumed_gas())
+        .unwrap_or(gas_wanted)
 }
 
 /// Convert ABCI vote info to PoS vote info. Any info which fails the conversion
@@ -959,6 +1193,7 @@ mod test_finalize_block {
     use namada::ledger::native_vp::NativeVp;
     use namada::ledger::parameters::EpochDuration;
     use namada::ledger::pos::PosQueries;
+    use namada::ledger::queries::RPC;
     use namada::ledger::storage_api;
     use namada::ledger::storage_api::StorageWrite;
     use namada::proof_of_stake::storage::{
@@ -1005,11 +1240,13 @@ mod test_finalize_block {
     use crate::facade::tendermint::abci::types::{
         Misbehavior, Validator, VoteInfo,
     };
+    use crate::facade::tendermint_proto::v0_37::abci::RequestPrepareProposal;
     use crate::node::ledger::oracle::control::Command;
     use crate::node::ledger::shell::test_utils::*;
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::{
         FinalizeBlock, ProcessedTx,
     };
+    use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
 
     const GAS_LIMIT_MULTIPLIER: u64 = 100_000_000;
 
@@ -1046,13 +1283,21 @@ mod test_finalize_block {
             ProcessedTx {
                 tx: tx.into(),
                 result: TxResult {
-                    code: ErrorCodes::Ok.into(),
+                    code: ResultCode::Ok.into(),
                     info: "".into(),
                 },
             },
         )
     }
 
+    /// The events of a block, but for the fee events of its wrapper txs
+    fn without_fee_events(events: Vec<Event>) -> Vec<Event> {
+        events
+            .into_iter()
+            .filter(|event| event.event_type != EventType::Fee)
+            .collect()
+    }
+
     /// Make a wrapper tx and a processed tx from the wrapped tx that can be
     /// added to `FinalizeBlock` request.
     fn mk_decrypted_tx(
@@ -1085,7 +1330,7 @@ mod test_finalize_block {
         ProcessedTx {
             tx: outer_tx.to_bytes().into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         }
@@ -1140,14 +1385,16 @@ mod test_finalize_block {
         }
 
         // check that the correct events were created
-        for (index, event) in shell
-            .finalize_block(FinalizeBlock {
-                txs: processed_txs.clone(),
-                ..Default::default()
-            })
-            .expect("Test failed")
-            .iter()
-            .enumerate()
+        for (index, event) in without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: processed_txs.clone(),
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        )
+        .iter()
+        .enumerate()
         {
             assert_eq!(event.event_type.to_string(), String::from("accepted"));
             let code = event.attributes.get("code").expect("Test failed");
@@ -1201,7 +1448,7 @@ mod test_finalize_block {
         let processed_tx = ProcessedTx {
             tx: outer_tx.to_bytes().into(),
             result: TxResult {
-                code: ErrorCodes::InvalidTx.into(),
+                code: ResultCode::InvalidTx.into(),
                 info: "".into(),
             },
         };
@@ -1216,7 +1463,7 @@ mod test_finalize_block {
         {
             assert_eq!(event.event_type.to_string(), String::from("applied"));
             let code = event.attributes.get("code").expect("Test failed");
-            assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+            assert_eq!(code, &String::from(ResultCode::InvalidTx));
         }
         // check that the corresponding wrapper tx was removed from the queue
         assert!(shell.wl_storage.storage.tx_queue.is_empty());
@@ -1245,7 +1492,7 @@ mod test_finalize_block {
                 .to_bytes()
                 .into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
@@ -1266,7 +1513,7 @@ mod test_finalize_block {
         {
             assert_eq!(event.event_type.to_string(), String::from("applied"));
             let code = event.attributes.get("code").expect("Test failed");
-            assert_eq!(code, &String::from(ErrorCodes::Undecryptable));
+            assert_eq!(code, &String::from(ResultCode::Undecryptable));
             let log = event.attributes.get("log").expect("Test failed");
             assert!(log.contains("Transaction could not be decrypted."))
         }
@@ -1274,6 +1521,445 @@ mod test_finalize_block {
         assert!(shell.wl_storage.storage.tx_queue.is_empty());
     }
 
+    /// Test that the gas declared by the wrappers and the gas used by the
+    /// txs are reported in their events, including when an inner tx runs
+    /// out of gas
+    #[test]
+    fn test_gas_reported_in_tx_events() {
+        let (mut shell, _, _, _) = setup();
+        let keypair = gen_keypair();
+
+        // Add unshielded balance for fee payment
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&keypair.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1000).serialize_to_vec())
+            .unwrap();
+
+        let (_, processed_wrapper) = mk_wrapper_tx(&shell, &keypair);
+        let processed_cheap = mk_decrypted_tx(&mut shell, &keypair);
+        // An inner tx left with almost no gas by its wrapper
+        let mut outer_tx =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: 1.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            ))));
+        outer_tx.header.chain_id = shell.chain_id.clone();
+        outer_tx.set_code(Code::new(TestWasms::TxNoOp.read_bytes(), None));
+        outer_tx.set_data(Data::new(
+            "Decrypted transaction data".as_bytes().to_owned(),
+        ));
+        shell.enqueue_tx(outer_tx.clone(), Gas::from(1));
+        outer_tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        let processed_out_of_gas = ProcessedTx {
+            tx: outer_tx.to_bytes().into(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let events = without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: vec![
+                        processed_wrapper,
+                        processed_cheap,
+                        processed_out_of_gas,
+                    ],
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        );
+        let gas = |event: &Event, key: &str| -> u64 {
+            event
+                .attributes
+                .get(key)
+                .expect("Test failed")
+                .parse()
+                .unwrap()
+        };
+
+        // The wrapper only pays for its own validation
+        let wrapper_event = &events[0];
+        assert_eq!(wrapper_event.event_type, EventType::Accepted);
+        assert_eq!(gas(wrapper_event, "gas_wanted"), GAS_LIMIT_MULTIPLIER);
+        assert!(gas(wrapper_event, "gas_used") > 0);
+        assert!(gas(wrapper_event, "gas_used") < GAS_LIMIT_MULTIPLIER);
+
+        // The cheap inner tx stays within the limit of its wrapper
+        let cheap_event = &events[1];
+        assert_eq!(cheap_event.event_type, EventType::Applied);
+        assert_eq!(
+            cheap_event.attributes.get("code").expect("Test failed"),
+            &String::from(ResultCode::Ok)
+        );
+        assert_eq!(gas(cheap_event, "gas_wanted"), GAS_LIMIT_MULTIPLIER);
+        assert!(gas(cheap_event, "gas_used") > 0);
+        assert!(gas(cheap_event, "gas_used") < GAS_LIMIT_MULTIPLIER);
+
+        // The inner tx running out of gas uses up all of it
+        let out_of_gas_event = &events[2];
+        assert_eq!(out_of_gas_event.event_type, EventType::Applied);
+        assert_ne!(
+            out_of_gas_event
+                .attributes
+                .get("code")
+                .expect("Test failed"),
+            &String::from(ResultCode::Ok)
+        );
+        assert_eq!(gas(out_of_gas_event, "gas_wanted"), GAS_LIMIT_MULTIPLIER);
+        assert!(gas(out_of_gas_event, "gas_used") >= GAS_LIMIT_MULTIPLIER);
+        assert!(
+            gas(out_of_gas_event, "gas_used") > gas(cheap_event, "gas_used")
+        );
+    }
+
+    /// Test that the gas wanted and used by the wrappers delivered in a block
+    /// is known before the block is finalized, while the gas used by the
+    /// decrypted txs is only reported once they are applied
+    #[test]
+    fn test_delivered_tx_gas() {
+        let (mut shell, _, _, _) = setup();
+        let keypair = gen_keypair();
+
+        let (wrapper, processed_wrapper) = mk_wrapper_tx(&shell, &keypair);
+        let (gas_wanted, gas_used) =
+            shell.delivered_tx_gas(&wrapper, &processed_wrapper.tx, 0);
+        assert_eq!(u64::from(gas_wanted), GAS_LIMIT_MULTIPLIER);
+        assert!(u64::from(gas_used) > 0);
+        assert!(gas_used < gas_wanted);
+
+        // A decrypted tx wants the gas of its wrapper, but hasn't used any
+        // before it gets applied
+        let processed_decrypted = mk_decrypted_tx(&mut shell, &keypair);
+        let decrypted = Tx::try_from(&processed_decrypted.tx[..]).unwrap();
+        let (gas_wanted, gas_used) =
+            shell.delivered_tx_gas(&decrypted, &processed_decrypted.tx, 0);
+        assert_eq!(u64::from(gas_wanted), GAS_LIMIT_MULTIPLIER);
+        assert_eq!(gas_used, Gas::default());
+
+        // Its executed gas is reported once applied, on top of the gas used
+        // by its wrapper
+        let queued = shell.wl_storage.storage.tx_queue.get(0).unwrap();
+        let wrapper_gas = GAS_LIMIT_MULTIPLIER - u64::from(queued.gas);
+        let events = without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: vec![processed_decrypted],
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        );
+        let applied = &events[0];
+        assert_eq!(applied.event_type, EventType::Applied);
+        let executed_gas: u64 = applied
+            .attributes
+            .get("gas_used")
+            .expect("Test failed")
+            .parse()
+            .unwrap();
+        assert!(executed_gas > wrapper_gas);
+        assert!(executed_gas < GAS_LIMIT_MULTIPLIER);
+    }
+
+    /// Test that the status of a tx is reported by the hash of its wrapper,
+    /// of its inner tx and by its CometBFT hash as it goes through the two
+    /// blocks that accept its wrapper and apply its inner tx
+    #[test]
+    fn test_tx_status_transitions() {
+        use namada_sdk::rpc::{
+            query_tx_status, query_tx_status_with_mempool, TxStatus,
+        };
+
+        let (mut shell, _, _, _) = setup();
+        let keypair = gen_keypair();
+
+        // Add unshielded balance for fee payment
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&keypair.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1000).serialize_to_vec())
+            .unwrap();
+
+        let mut wrapper_tx =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: 1.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            ))));
+        wrapper_tx.header.chain_id = shell.chain_id.clone();
+        wrapper_tx.set_code(Code::new(TestWasms::TxNoOp.read_bytes(), None));
+        wrapper_tx.set_data(Data::new(
+            "Encrypted transaction data".as_bytes().to_owned(),
+        ));
+        wrapper_tx.add_section(Section::Signature(Signature::new(
+            wrapper_tx.sechashes(),
+            [(0, keypair.clone())].into_iter().collect(),
+            None,
+        )));
+        let wrapper_hash = wrapper_tx.header_hash();
+        let inner_hash = wrapper_tx.raw_header_hash();
+        let tendermint_hash = wrapper_tx.tendermint_hash();
+        let hashes = [wrapper_hash, inner_hash, tendermint_hash];
+        let status = |shell: &TestShell, hash: &Hash| {
+            tokio_test::block_on(query_tx_status(shell, hash)).unwrap()
+        };
+
+        // Before the wrapper is included, it is only known to the mempool
+        let mempool = vec![wrapper_tx.to_bytes()];
+        for hash in &hashes {
+            assert_eq!(status(&shell, hash), TxStatus::Unknown);
+            let pending = tokio_test::block_on(query_tx_status_with_mempool(
+                &shell, hash, &mempool,
+            ))
+            .unwrap();
+            assert_eq!(pending, TxStatus::Pending);
+        }
+
+        // The first block accepts the wrapper
+        shell.finalize_and_commit(Some(FinalizeBlock {
+            txs: vec![ProcessedTx {
+                tx: wrapper_tx.to_bytes().into(),
+                result: TxResult {
+                    code: ResultCode::Ok.into(),
+                    info: "".into(),
+                },
+            }],
+            ..Default::default()
+        }));
+        let accepted_height = shell.wl_storage.storage.get_last_block_height();
+        let accepted = TxStatus::WrapperAccepted {
+            height: accepted_height,
+        };
+        assert_eq!(status(&shell, &wrapper_hash), accepted);
+        assert_eq!(status(&shell, &tendermint_hash), accepted);
+        // The inner tx hash is unknown until the inner tx is applied
+        assert_eq!(status(&shell, &inner_hash), TxStatus::Unknown);
+
+        // The second block applies the inner tx
+        let mut decrypted_tx = wrapper_tx;
+        decrypted_tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        shell.finalize_and_commit(Some(FinalizeBlock {
+            txs: vec![ProcessedTx {
+                tx: decrypted_tx.to_bytes().into(),
+                result: TxResult {
+                    code: ResultCode::Ok.into(),
+                    info: "".into(),
+                },
+            }],
+            ..Default::default()
+        }));
+        for hash in &hashes {
+            match status(&shell, hash) {
+                TxStatus::InnerApplied { height, result } => {
+                    assert_eq!(height, accepted_height + 1);
+                    assert!(result.is_success());
+                    assert_eq!(result.outcome.hash, inner_hash.to_string());
+                }
+                other => panic!("Unexpected tx status {other:?}"),
+            }
+        }
+    }
+
+    /// Make a signed wrapper tx running the given code, whose fee payer can
+    /// afford it
+    fn mk_tracked_wrapper(shell: &mut TestShell, code: Vec<u8>) -> Tx {
+        let keypair = gen_keypair();
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&keypair.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1000).serialize_to_vec())
+            .unwrap();
+
+        let mut wrapper_tx =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: 1.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            ))));
+        wrapper_tx.header.chain_id = shell.chain_id.clone();
+        wrapper_tx.set_code(Code::new(code, None));
+        wrapper_tx.set_data(Data::new(
+            "Encrypted transaction data".as_bytes().to_owned(),
+        ));
+        wrapper_tx.add_section(Section::Signature(Signature::new(
+            wrapper_tx.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+        wrapper_tx
+    }
+
+    /// Finalize and commit a block containing the given tx
+    fn finalize_tracked_tx(shell: &mut TestShell, tx: &Tx) {
+        shell.finalize_and_commit(Some(FinalizeBlock {
+            txs: vec![ProcessedTx {
+                tx: tx.to_bytes().into(),
+                result: TxResult {
+                    code: ResultCode::Ok.into(),
+                    info: "".into(),
+                },
+            }],
+            ..Default::default()
+        }));
+    }
+
+    /// Test that tracking a tx reports the acceptance of its wrapper and the
+    /// application of its inner tx as distinct phases, and only deems the tx
+    /// successful if its inner tx succeeded
+    #[test]
+    fn test_track_tx_phases() {
+        use namada_sdk::tx::{
+            track_tx, BroadcastReceipt, TrackedTxOutcome, TxTrackingTimeouts,
+        };
+
+        let timeouts = TxTrackingTimeouts {
+            wrapper: std::time::Duration::from_millis(50),
+            inner: std::time::Duration::from_millis(50),
+            poll_interval: std::time::Duration::from_millis(5),
+        };
+        let (mut shell, _, _, _) = setup();
+
+        for (code, inner_code) in [
+            (TestWasms::TxNoOp.read_bytes(), ResultCode::Ok),
+            (
+                "not wasm".as_bytes().to_owned(),
+                ResultCode::WasmRuntimeError,
+            ),
+        ] {
+            let wrapper_tx = mk_tracked_wrapper(&mut shell, code);
+            let receipt = BroadcastReceipt::new(&wrapper_tx, 0, String::new());
+            let track = |shell: &TestShell| {
+                tokio_test::block_on(track_tx(shell, &receipt, &timeouts))
+                    .unwrap()
+            };
+
+            // Nothing has been included yet
+            assert_eq!(track(&shell), TrackedTxOutcome::WrapperTimedOut);
+
+            // The first block accepts the wrapper, but that is not a success
+            finalize_tracked_tx(&mut shell, &wrapper_tx);
+            let accepted_height =
+                shell.wl_storage.storage.get_last_block_height();
+            let outcome = track(&shell);
+            match &outcome {
+                TrackedTxOutcome::InnerTimedOut { wrapper } => {
+                    assert!(wrapper.is_success());
+                    assert_eq!(wrapper.outcome.height, accepted_height);
+                }
+                other => panic!("Unexpected outcome {other:?}"),
+            }
+            assert!(!outcome.is_success());
+
+            // The second block applies the inner tx
+            let mut decrypted_tx = wrapper_tx;
+            decrypted_tx
+                .update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+            finalize_tracked_tx(&mut shell, &decrypted_tx);
+            let outcome = track(&shell);
+            match &outcome {
+                TrackedTxOutcome::InnerApplied { wrapper, inner } => {
+                    assert_eq!(wrapper.outcome.height, accepted_height);
+                    assert_eq!(inner.outcome.height, accepted_height + 1);
+                    assert_eq!(inner.outcome.code, inner_code);
+                }
+                other => panic!("Unexpected outcome {other:?}"),
+            }
+            assert_eq!(outcome.is_success(), inner_code == ResultCode::Ok);
+        }
+    }
+
+    /// Test that the signature of a wrapper tx is verified only once as the
+    /// tx goes through the mempool, then through the processing of a proposal
+    /// and the finalization of a block at a later height
+    #[test]
+    fn test_wrapper_sig_verified_once() {
+        let (mut shell, _, _, _) = setup();
+        let wrapper_tx =
+            mk_tracked_wrapper(&mut shell, TestWasms::TxNoOp.read_bytes());
+        let tx_bytes = wrapper_tx.to_bytes();
+
+        shell.mempool_validate(&tx_bytes, MempoolTxType::NewTransaction);
+        assert_eq!(shell.tx_decode_cache.verified_sigs().verifications(), 1);
+        shell.finalize_and_commit(None);
+
+        let processed = match shell.process_proposal(ProcessProposal {
+            txs: vec![tx_bytes.clone()],
+        }) {
+            Ok(processed) | Err(TestError::RejectProposal(processed)) => {
+                processed
+            }
+        };
+        assert_ne!(processed[0].result.code, u32::from(ResultCode::InvalidSig));
+        finalize_tracked_tx(&mut shell, &wrapper_tx);
+        assert_eq!(shell.tx_decode_cache.verified_sigs().verifications(), 1);
+    }
+
+    /// Test that a wrapper tx whose signature was replaced after it went
+    /// through the mempool is verified again when proposed, and rejected
+    #[test]
+    fn test_mutated_wrapper_sig_verified_again() {
+        use namada::types::key::SigScheme;
+
+        let (mut shell, _, _, _) = setup();
+        let mut wrapper_tx =
+            mk_tracked_wrapper(&mut shell, TestWasms::TxNoOp.read_bytes());
+        shell.mempool_validate(
+            &wrapper_tx.to_bytes(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(shell.tx_decode_cache.verified_sigs().verifications(), 1);
+        shell.finalize_and_commit(None);
+
+        for section in &mut wrapper_tx.sections {
+            if let Section::Signature(signature) = section {
+                let forged = common::SigScheme::sign(
+                    &gen_keypair(),
+                    signature.get_raw_hash(),
+                );
+                signature.signatures.insert(0, forged);
+            }
+        }
+        let processed = match shell.process_proposal(ProcessProposal {
+            txs: vec![wrapper_tx.to_bytes()],
+        }) {
+            Ok(processed) | Err(TestError::RejectProposal(processed)) => {
+                processed
+            }
+        };
+        assert_eq!(processed[0].result.code, u32::from(ResultCode::InvalidSig));
+        assert!(shell.tx_decode_cache.verified_sigs().verifications() > 1);
+    }
+
     /// Test that the wrapper txs are queued in the order they
     /// are received from the block. Tests that the previously
     /// decrypted txs are de-queued.
@@ -1308,14 +1994,16 @@ mod test_finalize_block {
         // Put the wrapper txs in front of the decrypted txs
         processed_txs.rotate_left(2);
         // check that the correct events were created
-        for (index, event) in shell
-            .finalize_block(FinalizeBlock {
-                txs: processed_txs,
-                ..Default::default()
-            })
-            .expect("Test failed")
-            .iter()
-            .enumerate()
+        for (index, event) in without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: processed_txs,
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        )
+        .iter()
+        .enumerate()
         {
             if index < 2 {
                 // these should be accepted wrapper txs
@@ -1325,7 +2013,7 @@ mod test_finalize_block {
                 );
                 let code =
                     event.attributes.get("code").expect("Test failed").as_str();
-                assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+                assert_eq!(code, String::from(ResultCode::Ok).as_str());
             } else {
                 // these should be accepted decrypted txs
                 assert_eq!(
@@ -1334,7 +2022,7 @@ mod test_finalize_block {
                 );
                 let code =
                     event.attributes.get("code").expect("Test failed").as_str();
-                assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+                assert_eq!(code, String::from(ResultCode::Ok).as_str());
             }
         }
 
@@ -1372,7 +2060,7 @@ mod test_finalize_block {
             txs: vec![ProcessedTx {
                 tx: tx.into(),
                 result: TxResult {
-                    code: ErrorCodes::InvalidTx.into(),
+                    code: ResultCode::InvalidTx.into(),
                     info: Default::default(),
                 },
             }],
@@ -1383,7 +2071,7 @@ mod test_finalize_block {
         let event = resp.remove(0);
         assert_eq!(event.event_type.to_string(), String::from("applied"));
         let code = event.attributes.get("code").expect("Test failed");
-        assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+        assert_eq!(code, &String::from(ResultCode::InvalidTx));
     }
 
     /// Test that once a validator's vote for an Ethereum event lands
@@ -1442,7 +2130,7 @@ mod test_finalize_block {
                     .to_bytes()
                     .into(),
                 result: TxResult {
-                    code: ErrorCodes::Ok.into(),
+                    code: ResultCode::Ok.into(),
                     info: "".into(),
                 },
             }
@@ -1459,7 +2147,7 @@ mod test_finalize_block {
             .expect("Test failed");
         assert_eq!(result.event_type.to_string(), String::from("applied"));
         let code = result.attributes.get("code").expect("Test failed").as_str();
-        assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+        assert_eq!(code, String::from(ResultCode::Ok).as_str());
 
         // --- The event is removed from the queue
         assert!(shell.new_ethereum_events().is_empty());
@@ -1502,7 +2190,7 @@ mod test_finalize_block {
                 .to_bytes()
                 .into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
@@ -1518,7 +2206,7 @@ mod test_finalize_block {
             .expect("Test failed");
         assert_eq!(result.event_type.to_string(), String::from("applied"));
         let code = result.attributes.get("code").expect("Test failed").as_str();
-        assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+        assert_eq!(code, String::from(ResultCode::Ok).as_str());
 
         // --- The event is removed from the queue
         assert!(shell.new_ethereum_events().is_empty());
@@ -1561,7 +2249,7 @@ mod test_finalize_block {
         let processed_tx = ProcessedTx {
             tx: tx.to_bytes().into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
@@ -2898,12 +3586,14 @@ mod test_finalize_block {
         // merkle tree root before finalize_block
         let root_pre = shell.shell.wl_storage.storage.block.tree.root();
 
-        let event = &shell
-            .finalize_block(FinalizeBlock {
-                txs: vec![processed_tx],
-                ..Default::default()
-            })
-            .expect("Test failed")[0];
+        let event = &without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: vec![processed_tx],
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        )[0];
         assert_eq!(event.event_type.to_string(), String::from("accepted"));
         let code = event
             .attributes
@@ -2913,7 +3603,7 @@ mod test_finalize_block {
         failed",
             )
             .as_str();
-        assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+        assert_eq!(code, String::from(ResultCode::Ok).as_str());
 
         // the merkle tree root should not change after finalize_block
         let root_post = shell.shell.wl_storage.storage.block.tree.root();
@@ -2941,6 +3631,140 @@ mod test_finalize_block {
         );
     }
 
+    /// Test that the sections of a tx included in a block are retained, and can
+    /// be queried back by their hash
+    #[test]
+    fn test_tx_sections_retained() {
+        let (mut shell, _, _, _) = setup();
+        shell.retain_tx_sections = Some(10);
+
+        let (wrapper_tx, processed_tx) =
+            mk_wrapper_tx(&shell, &crate::wallet::defaults::albert_keypair());
+        shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        let height = shell.wl_storage.storage.block.height;
+        shell.commit();
+
+        for (hash, expected) in [
+            (
+                wrapper_tx.code_sechash(),
+                wrapper_tx.get_section(wrapper_tx.code_sechash()),
+            ),
+            (
+                wrapper_tx.data_sechash(),
+                wrapper_tx.get_section(wrapper_tx.data_sechash()),
+            ),
+        ] {
+            let response = shell.query(request::Query {
+                data: Default::default(),
+                path: RPC.shell().tx_section_path(&height, hash),
+                height: Default::default(),
+                prove: false,
+            });
+            let retained: Option<Vec<u8>> =
+                BorshDeserialize::try_from_slice(&response.value)
+                    .expect("Test failed");
+            assert_eq!(
+                retained.expect("Test failed"),
+                expected.expect("Test failed").serialize_to_vec()
+            );
+        }
+
+        // Sections are only retained at the height of their block
+        let response = shell.query(request::Query {
+            data: Default::default(),
+            path: RPC.shell().tx_section_path(
+                &height.next_height(),
+                wrapper_tx.code_sechash(),
+            ),
+            height: Default::default(),
+            prove: false,
+        });
+        let retained: Option<Vec<u8>> =
+            BorshDeserialize::try_from_slice(&response.value)
+                .expect("Test failed");
+        assert!(retained.is_none());
+    }
+
+    /// Test that the sections retained at the heights that fall out of the
+    /// retention window are pruned
+    #[test]
+    fn test_tx_sections_pruned() {
+        let (mut shell, _, _, _) = setup();
+        shell.retain_tx_sections = Some(2);
+
+        let (wrapper_tx, processed_tx) =
+            mk_wrapper_tx(&shell, &crate::wallet::defaults::albert_keypair());
+        shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        let height = shell.wl_storage.storage.block.height;
+        shell.commit();
+
+        let is_retained = |shell: &TestShell| {
+            let response = shell.query(request::Query {
+                data: Default::default(),
+                path: RPC
+                    .shell()
+                    .tx_section_path(&height, wrapper_tx.code_sechash()),
+                height: Default::default(),
+                prove: false,
+            });
+            let retained: Option<Vec<u8>> =
+                BorshDeserialize::try_from_slice(&response.value)
+                    .expect("Test failed");
+            retained.is_some()
+        };
+        assert!(is_retained(&shell));
+
+        // The sections are kept while their block is one of the two most
+        // recent ones
+        shell.finalize_and_commit(None);
+        assert!(is_retained(&shell));
+
+        // And pruned afterwards
+        shell.finalize_and_commit(None);
+        assert!(!is_retained(&shell));
+    }
+
+    /// Test that the sections of a tx are not retained unless the node is
+    /// configured to
+    #[test]
+    fn test_tx_sections_not_retained_by_default() {
+        let (mut shell, _, _, _) = setup();
+
+        let (wrapper_tx, processed_tx) =
+            mk_wrapper_tx(&shell, &crate::wallet::defaults::albert_keypair());
+        shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        let height = shell.wl_storage.storage.block.height;
+        shell.commit();
+
+        let response = shell.query(request::Query {
+            data: Default::default(),
+            path: RPC
+                .shell()
+                .tx_section_path(&height, wrapper_tx.code_sechash()),
+            height: Default::default(),
+            prove: false,
+        });
+        let retained: Option<Vec<u8>> =
+            BorshDeserialize::try_from_slice(&response.value)
+                .expect("Test failed");
+        assert!(retained.is_none());
+    }
+
     /// Test that a decrypted tx that has already been applied in the same block
     /// doesn't get reapplied
     #[test]
@@ -3007,7 +3831,7 @@ mod test_finalize_block {
             shell
                 .wl_storage
                 .storage
-                .write_replay_protection_entry(&mut batch, &hash_subkey)
+                .write_replay_protection_entry(&mut batch, &hash_subkey, None)
                 .expect("Test failed");
         }
 
@@ -3016,7 +3840,7 @@ mod test_finalize_block {
             processed_txs.push(ProcessedTx {
                 tx: inner.to_bytes().into(),
                 result: TxResult {
-                    code: ErrorCodes::Ok.into(),
+                    code: ResultCode::Ok.into(),
                     info: "".into(),
                 },
             })
@@ -3040,10 +3864,10 @@ mod test_finalize_block {
 
         assert_eq!(event[0].event_type.to_string(), String::from("applied"));
         let code = event[0].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+        assert_eq!(code, String::from(ResultCode::Ok).as_str());
         assert_eq!(event[1].event_type.to_string(), String::from("applied"));
         let code = event[1].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::WasmRuntimeError).as_str());
+        assert_eq!(code, String::from(ResultCode::WasmRuntimeError).as_str());
 
         for (inner, wrapper) in [(inner, wrapper), (new_inner, new_wrapper)] {
             assert!(
@@ -3148,7 +3972,7 @@ mod test_finalize_block {
             shell
                 .wl_storage
                 .storage
-                .write_replay_protection_entry(&mut batch, &hash_subkey)
+                .write_replay_protection_entry(&mut batch, &hash_subkey, None)
                 .unwrap();
         }
 
@@ -3163,7 +3987,7 @@ mod test_finalize_block {
             processed_txs.push(ProcessedTx {
                 tx: inner.to_bytes().into(),
                 result: TxResult {
-                    code: ErrorCodes::Ok.into(),
+                    code: ResultCode::Ok.into(),
                     info: "".into(),
                 },
             })
@@ -3196,19 +4020,19 @@ mod test_finalize_block {
 
         assert_eq!(event[0].event_type.to_string(), String::from("applied"));
         let code = event[0].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::WasmRuntimeError).as_str());
+        assert_eq!(code, String::from(ResultCode::WasmRuntimeError).as_str());
         assert_eq!(event[1].event_type.to_string(), String::from("applied"));
         let code = event[1].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::Undecryptable).as_str());
+        assert_eq!(code, String::from(ResultCode::Undecryptable).as_str());
         assert_eq!(event[2].event_type.to_string(), String::from("applied"));
         let code = event[2].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::InvalidTx).as_str());
+        assert_eq!(code, String::from(ResultCode::InvalidTx).as_str());
         assert_eq!(event[3].event_type.to_string(), String::from("applied"));
         let code = event[3].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::WasmRuntimeError).as_str());
+        assert_eq!(code, String::from(ResultCode::MissingTxSection).as_str());
         assert_eq!(event[4].event_type.to_string(), String::from("applied"));
         let code = event[4].attributes.get("code").unwrap().as_str();
-        assert_eq!(code, String::from(ErrorCodes::WasmRuntimeError).as_str());
+        assert_eq!(code, String::from(ResultCode::WasmRuntimeError).as_str());
 
         for (invalid_inner, valid_wrapper) in [
             (out_of_gas_inner, out_of_gas_wrapper),
@@ -3286,19 +4110,21 @@ mod test_finalize_block {
         let processed_txs = vec![ProcessedTx {
             tx: wrapper.to_bytes().into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         }];
         // merkle tree root before finalize_block
         let root_pre = shell.shell.wl_storage.storage.block.tree.root();
 
-        let event = &shell
-            .finalize_block(FinalizeBlock {
-                txs: processed_txs,
-                ..Default::default()
-            })
-            .expect("Test failed");
+        let event = &without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: processed_txs,
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        );
 
         // the merkle tree root should not change after finalize_block
         let root_post = shell.shell.wl_storage.storage.block.tree.root();
@@ -3310,7 +4136,7 @@ mod test_finalize_block {
             .get("code")
             .expect("Test failed")
             .as_str();
-        assert_eq!(code, String::from(ErrorCodes::InvalidTx).as_str());
+        assert_eq!(code, String::from(ResultCode::InvalidTx).as_str());
 
         assert!(
             shell
@@ -3361,22 +4187,24 @@ mod test_finalize_block {
         let processed_tx = ProcessedTx {
             tx: wrapper.to_bytes().into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
 
-        let event = &shell
-            .finalize_block(FinalizeBlock {
-                txs: vec![processed_tx],
-                ..Default::default()
-            })
-            .expect("Test failed")[0];
+        let event = &without_fee_events(
+            shell
+                .finalize_block(FinalizeBlock {
+                    txs: vec![processed_tx],
+                    ..Default::default()
+                })
+                .expect("Test failed"),
+        )[0];
 
         // Check balance of fee payer is 0
         assert_eq!(event.event_type.to_string(), String::from("accepted"));
         let code = event.attributes.get("code").expect("Testfailed").as_str();
-        assert_eq!(code, String::from(ErrorCodes::InvalidTx).as_str());
+        assert_eq!(code, String::from(ResultCode::InvalidTx).as_str());
         let balance_key = namada::core::types::token::balance_key(
             &shell.wl_storage.storage.native_token,
             &Address::from(&keypair.to_public()),
@@ -3455,23 +4283,37 @@ mod test_finalize_block {
         let processed_tx = ProcessedTx {
             tx: wrapper.to_bytes().into(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
 
-        let event = &shell
+        let events = &shell
             .finalize_block(FinalizeBlock {
                 txs: vec![processed_tx],
                 proposer_address,
                 ..Default::default()
             })
-            .expect("Test failed")[0];
+            .expect("Test failed");
 
         // Check fee payment
+        let [fee_event, event] = events.as_slice() else {
+            panic!("Test failed");
+        };
         assert_eq!(event.event_type.to_string(), String::from("accepted"));
         let code = event.attributes.get("code").expect("Test failed").as_str();
-        assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+        assert_eq!(code, String::from(ResultCode::Ok).as_str());
+        assert_eq!(fee_event.event_type, EventType::Fee);
+        assert_eq!(fee_event["hash"], event["hash"]);
+        assert_eq!(
+            fee_event["payer"],
+            wrapper.header().wrapper().unwrap().fee_payer().to_string()
+        );
+        assert_eq!(
+            fee_event["token"],
+            shell.wl_storage.storage.native_token.to_string()
+        );
+        assert_eq!(fee_event["amount"], fee_amount.raw_amount().to_string());
 
         let new_proposer_balance = storage_api::token::read_balance(
             &shell.wl_storage,
@@ -3865,6 +4707,98 @@ mod test_finalize_block {
         Ok(())
     }
 
+    /// Test that the proposer of a block records its evidence in slash
+    /// protocol txs, that process proposal rejects the slash txs that don't
+    /// match the evidence of the block, and that evidence both delivered by
+    /// CometBFT and carried by a slash tx is only slashed once.
+    #[test]
+    fn test_slash_protocol_txs() -> storage_api::Result<()> {
+        let (mut shell, _, _, _) = setup();
+        shell.finalize_and_commit(None);
+        let params = read_pos_params(&shell.wl_storage)?;
+        let validator = shell.mode.get_validator_address().unwrap().clone();
+        let pkh: [u8; 20] = HEXUPPER
+            .decode(
+                crate::wallet::defaults::validator_keypair()
+                    .to_public()
+                    .tm_raw_hash()
+                    .as_bytes(),
+            )
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let misbehavior = Misbehavior {
+            kind: MisbehaviorKind::DuplicateVote,
+            validator: Validator {
+                address: pkh,
+                power: Default::default(),
+            },
+            height: 1_u32.into(),
+            time: tendermint::Time::unix_epoch(),
+            total_voting_power: Default::default(),
+        };
+
+        // The proposer records the evidence in a slash tx
+        let slash_txs: Vec<TxBytes> = shell
+            .prepare_proposal(RequestPrepareProposal {
+                misbehavior: vec![misbehavior.clone().into()],
+                ..Default::default()
+            })
+            .txs
+            .into_iter()
+            .filter(|tx_bytes| {
+                let tx = Tx::try_from(tx_bytes.as_ref()).unwrap();
+                matches!(
+                    tx.header().tx_type,
+                    TxType::Protocol(protocol_tx)
+                        if matches!(protocol_tx.tx, ProtocolTxType::Slash)
+                )
+            })
+            .collect();
+        assert_eq!(slash_txs.len(), 1);
+
+        // The slash tx is only accepted along with its evidence
+        let block_time = DateTimeUtc::now();
+        let (results, _) = shell.process_txs(
+            &slash_txs,
+            block_time,
+            &validator,
+            &[misbehavior.clone()],
+        );
+        assert_eq!(results[0].code, u32::from(ResultCode::Ok));
+        let (results, _) =
+            shell.process_txs(&slash_txs, block_time, &validator, &[]);
+        assert_eq!(results[0].code, u32::from(ResultCode::InvalidTx));
+
+        // The evidence is slashed once
+        shell
+            .finalize_block(FinalizeBlock {
+                txs: slash_txs
+                    .into_iter()
+                    .map(|tx| ProcessedTx {
+                        tx,
+                        result: TxResult {
+                            code: ResultCode::Ok.into(),
+                            info: "".into(),
+                        },
+                    })
+                    .collect(),
+                byzantine_validators: vec![misbehavior],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        let processing_epoch =
+            Epoch::default() + params.slash_processing_epoch_offset();
+        let enqueued_slashes = enqueued_slashes_handle()
+            .get_data_handler()
+            .at(&processing_epoch)
+            .at(&validator)
+            .iter(&shell.wl_storage)?
+            .count();
+        assert_eq!(enqueued_slashes, 1);
+        Ok(())
+    }
+
     /// NOTE: must call `get_default_true_votes` before every call to
     /// `next_block_for_inflation`
     #[test]
@@ -281,11 +281,14 @@ where
                   * based on the code size. We dont
                   * need it here. */
             TxIndex::default(),
+            None,
             &mut TxGasMeter::new_from_sub_limit(u64::MAX.into()), /* No gas limit for governance proposal */
             &mut shell.wl_storage,
             &mut shell.vp_wasm_cache,
             &mut shell.tx_wasm_cache,
             None,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         );
         shell
             .wl_storage
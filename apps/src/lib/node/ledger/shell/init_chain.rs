@@ -12,7 +12,7 @@ use namada::proof_of_stake::BecomeValidator;
 use namada::types::hash::Hash as CodeHash;
 use namada::types::key::*;
 use namada::types::storage::KeySeg;
-use namada::types::time::{DateTimeUtc, TimeZone, Utc};
+use namada::types::time::DateTimeUtc;
 use namada::vm::validate_untrusted_wasm;
 use namada_sdk::eth_bridge::EthBridgeStatus;
 use namada_sdk::proof_of_stake::types::ValidatorMetaData;
@@ -87,16 +87,12 @@ where
         }
 
         let ts: protobuf::Timestamp = init.time.into();
+        let genesis_time = DateTimeUtc::try_from(ts)
+            .expect("genesis time should be a valid timestamp");
         let initial_height = init
             .initial_height
             .try_into()
             .expect("Unexpected block height");
-        // TODO hacky conversion, depends on https://github.com/informalsystems/tendermint-rs/issues/870
-        let genesis_time: DateTimeUtc = (Utc
-            .timestamp_opt(ts.seconds, ts.nanos as u32))
-        .single()
-        .expect("genesis time should be a valid timestamp")
-        .into();
 
         // Initialize protocol parameters
         let parameters = genesis.get_chain_parameters(&self.wasm_dir);
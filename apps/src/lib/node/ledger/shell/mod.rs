@@ -6,6 +6,8 @@
 //! (unless we can simply overwrite them in the next block).
 //! More info in <https://github.com/anoma/namada/issues/362>.
 pub mod block_alloc;
+pub mod debug_log;
+pub mod decode_cache;
 mod finalize_block;
 mod governance;
 mod init_chain;
@@ -17,6 +19,7 @@ mod stats;
 #[allow(dead_code)]
 pub mod testing;
 pub mod utils;
+pub mod verified_sigs;
 mod vote_extensions;
 
 use std::collections::{BTreeSet, HashSet};
@@ -36,7 +39,7 @@ use namada::ledger::events::Event;
 use namada::ledger::gas::{Gas, TxGasMeter};
 use namada::ledger::pos::into_tm_voting_power;
 use namada::ledger::pos::namada_proof_of_stake::types::{
-    ConsensusValidator, ValidatorSetUpdate,
+    ConsensusValidator, SlashEvidence, ValidatorSetUpdate,
 };
 use namada::ledger::protocol::{
     apply_wasm_tx, get_fee_unshielding_transaction,
@@ -52,7 +55,7 @@ use namada::ledger::storage_api::tx::validate_tx_bytes;
 use namada::ledger::storage_api::{self, StorageRead};
 use namada::ledger::{parameters, pos, protocol};
 use namada::proof_of_stake::{self, process_slashes, read_pos_params, slash};
-use namada::proto::{self, Section, Tx};
+use namada::proto::{Section, Tx};
 use namada::types::address::Address;
 use namada::types::chain::ChainId;
 use namada::types::ethereum_events::EthereumEvent;
@@ -61,18 +64,22 @@ use namada::types::key::*;
 use namada::types::storage::{BlockHeight, Key, TxIndex};
 use namada::types::time::DateTimeUtc;
 use namada::types::transaction::protocol::EthereumTxData;
-use namada::types::transaction::{DecryptedTx, TxType, WrapperTx};
+use namada::types::transaction::{
+    DecryptedTx, ResultCode, TxType, TxWasmError, WrapperTx,
+};
 use namada::types::{address, token};
 use namada::vm::wasm::{TxCache, VpCache};
 use namada::vm::{WasmCacheAccess, WasmCacheRwAccess};
 use namada_sdk::eth_bridge::{EthBridgeQueries, EthereumOracleConfig};
 use namada_sdk::tendermint::AppHash;
-use num_derive::{FromPrimitive, ToPrimitive};
-use num_traits::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
 use thiserror::Error;
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
 
+use self::debug_log::{DebugLog, RejectionSource};
+use self::decode_cache::TxDecodeCache;
 use super::ethereum_oracle::{self as oracle, last_processed_block};
+use crate::config::utils::is_local_addr;
 use crate::config::{self, genesis, TendermintMode, ValidatorLocalConfig};
 use crate::facade::tendermint::abci::types::{Misbehavior, MisbehaviorKind};
 use crate::facade::tendermint::v0_37::abci::{request, response};
@@ -94,6 +101,7 @@ fn key_to_tendermint(
             secp256k1::PublicKey::try_from_pk(pk)
                 .map(|pk| public_key::Sum::Secp256k1(pk.serialize_to_vec()))
         }
+        _ => Err(ParsePublicKeyError::MismatchedScheme),
     }
 }
 
@@ -104,7 +112,7 @@ pub enum Error {
     #[error("chain ID mismatch: {0}")]
     ChainId(String),
     #[error("Error decoding a transaction from bytes: {0}")]
-    TxDecoding(proto::Error),
+    TxDecoding(String),
     #[error("Error trying to apply a transaction: {0}")]
     TxApply(protocol::Error),
     #[error("{0}")]
@@ -136,65 +144,20 @@ impl From<Error> for TxResult {
     }
 }
 
-/// The different error codes that the ledger may
-/// send back to a client indicating the status
-/// of their submitted tx
-#[derive(Debug, Copy, Clone, FromPrimitive, ToPrimitive, PartialEq, Eq)]
-pub enum ErrorCodes {
-    Ok = 0,
-    WasmRuntimeError = 1,
-    InvalidTx = 2,
-    InvalidSig = 3,
-    InvalidOrder = 4,
-    ExtraTxs = 5,
-    Undecryptable = 6,
-    AllocationError = 7,
-    ReplayTx = 8,
-    InvalidChainId = 9,
-    ExpiredTx = 10,
-    TxGasLimit = 11,
-    FeeError = 12,
-    InvalidVoteExtension = 13,
-    TooLarge = 14,
-}
-
-impl ErrorCodes {
-    /// Checks if the given [`ErrorCodes`] value is a protocol level error,
-    /// that can be recovered from at the finalize block stage.
-    pub const fn is_recoverable(&self) -> bool {
-        use ErrorCodes::*;
-        // NOTE: pattern match on all `ErrorCodes` variants, in order
-        // to catch potential bugs when adding new codes
-        match self {
-            Ok | WasmRuntimeError => true,
-            InvalidTx | InvalidSig | InvalidOrder | ExtraTxs
-            | Undecryptable | AllocationError | ReplayTx | InvalidChainId
-            | ExpiredTx | TxGasLimit | FeeError | InvalidVoteExtension
-            | TooLarge => false,
-        }
-    }
-}
-
-impl From<ErrorCodes> for u32 {
-    fn from(code: ErrorCodes) -> u32 {
-        code.to_u32().unwrap()
-    }
-}
-
-impl From<ErrorCodes> for String {
-    fn from(code: ErrorCodes) -> String {
-        u32::from(code).to_string()
-    }
-}
+pub type Result<T> = std::result::Result<T, Error>;
 
-impl From<ErrorCodes> for crate::facade::tendermint::abci::Code {
-    fn from(value: ErrorCodes) -> Self {
-        Self::from(u32::from(value))
-    }
+/// Build the pool of threads checking the txs of the mempool when preparing a
+/// proposal, unless they are to be checked serially
+fn proposal_pool(threads: Option<usize>) -> Option<rayon::ThreadPool> {
+    threads.filter(|threads| *threads > 1).map(|threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("ledger-proposal-worker-{}", i))
+            .build()
+            .expect("Creating the proposal thread pool should not fail")
+    })
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
-
 pub fn reset(config: config::Ledger) -> Result<()> {
     // simply nuke the DB files
     let db_path = &config.db_dir();
@@ -400,6 +363,17 @@ where
     pub proposal_data: HashSet<u64>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     event_log: EventLog,
+    /// The txs decoded at the current height
+    pub tx_decode_cache: TxDecodeCache,
+    /// The threads checking the txs of the mempool in parallel when preparing
+    /// a proposal, if any
+    proposal_pool: Option<rayon::ThreadPool>,
+    /// The recent rejections of txs, served by the debug queries
+    pub debug_log: DebugLog,
+    /// Taken from config `retain_tx_sections`. When set, the sections of
+    /// the txs included in the given number of most recent blocks are
+    /// retained in the DB.
+    retain_tx_sections: Option<u64>,
 }
 
 /// Channels for communicating with an Ethereum oracle.
@@ -447,6 +421,17 @@ where
         let mode = config.shell.tendermint_mode;
         let storage_read_past_height_limit =
             config.shell.storage_read_past_height_limit;
+        let proposal_pool =
+            proposal_pool(config.shell.prepare_proposal_threads);
+        // The debug queries are only served to the local host
+        let debug_rpc =
+            config.shell.debug_rpc && is_local_addr(&config.cometbft.rpc.laddr);
+        if config.shell.debug_rpc && !debug_rpc {
+            tracing::warn!(
+                "The debug queries are disabled, as the CometBFT RPC doesn't \
+                 listen on a loopback address"
+            );
+        }
         if !Path::new(&base_dir).is_dir() {
             std::fs::create_dir(&base_dir)
                 .expect("Creating directory for Namada should not fail");
@@ -568,6 +553,10 @@ where
             proposal_data: HashSet::new(),
             // TODO: config event log params
             event_log: EventLog::default(),
+            tx_decode_cache: TxDecodeCache::default(),
+            proposal_pool,
+            debug_log: DebugLog::new(debug_rpc),
+            retain_tx_sections: config.shell.retain_tx_sections,
         };
 
         shell.update_eth_oracle();
@@ -586,6 +575,20 @@ where
         &mut self.event_log
     }
 
+    /// Set the number of threads checking the txs of the mempool when
+    /// preparing a proposal, see
+    /// [`config::Shell::prepare_proposal_threads`]
+    pub fn set_prepare_proposal_threads(&mut self, threads: Option<usize>) {
+        self.proposal_pool = proposal_pool(threads);
+    }
+
+    /// Enable or disable the debug queries, see
+    /// [`config::Shell::debug_rpc`]. The rejections logged so far are
+    /// dropped.
+    pub fn set_debug_rpc(&mut self, enabled: bool) {
+        self.debug_log = DebugLog::new(enabled);
+    }
+
     /// Iterate over the wrapper txs in order
     #[allow(dead_code)]
     fn iter_tx_queue(&mut self) -> impl Iterator<Item = &TxInQueue> {
@@ -677,108 +680,164 @@ where
         if !self.byzantine_validators.is_empty() {
             let byzantine_validators =
                 mem::take(&mut self.byzantine_validators);
-            // TODO: resolve this unwrap() better
-            let pos_params = read_pos_params(&self.wl_storage).unwrap();
-            let current_epoch = self.wl_storage.storage.block.epoch;
             for evidence in byzantine_validators {
                 // dbg!(&evidence);
                 tracing::info!("Processing evidence {evidence:?}.");
-                let evidence_height = match u64::try_from(evidence.height) {
-                    Ok(height) => height,
-                    Err(err) => {
-                        tracing::error!(
-                            "Unexpected evidence block height {}",
-                            err
-                        );
-                        continue;
-                    }
-                };
-                let evidence_epoch = match self
-                    .wl_storage
-                    .storage
-                    .block
-                    .pred_epochs
-                    .get_epoch(BlockHeight(evidence_height))
-                {
-                    Some(epoch) => epoch,
-                    None => {
-                        tracing::error!(
-                            "Couldn't find epoch for evidence block height {}",
-                            evidence_height
-                        );
-                        continue;
-                    }
-                };
-                // Disregard evidences that should have already been processed
-                // at this time
-                if evidence_epoch + pos_params.slash_processing_epoch_offset()
-                    - pos_params.cubic_slashing_window_length
-                    <= current_epoch
-                {
-                    tracing::info!(
-                        "Skipping outdated evidence from epoch \
-                         {evidence_epoch}"
-                    );
-                    continue;
+                if let Some(evidence) = self.slash_evidence(&evidence) {
+                    self.apply_slash_evidence(&evidence);
                 }
-                let slash_type = match evidence.kind {
-                    MisbehaviorKind::DuplicateVote => {
-                        pos::types::SlashType::DuplicateVote
-                    }
-                    MisbehaviorKind::LightClientAttack => {
-                        pos::types::SlashType::LightClientAttack
-                    }
-                    MisbehaviorKind::Unknown => {
-                        tracing::error!("Unknown evidence: {:#?}", evidence);
-                        continue;
-                    }
-                };
-                let validator_raw_hash =
-                    tm_raw_hash_to_string(evidence.validator.address);
-                let validator =
-                    match proof_of_stake::find_validator_by_raw_hash(
-                        &self.wl_storage,
-                        &validator_raw_hash,
-                    )
-                    .expect("Must be able to read storage")
-                    {
-                        Some(validator) => validator,
-                        None => {
-                            tracing::error!(
-                                "Cannot find validator's address from raw \
-                                 hash {}",
-                                validator_raw_hash
-                            );
-                            continue;
-                        }
-                    };
-                // Check if we're gonna switch to a new epoch after a delay
-                let validator_set_update_epoch =
-                    self.get_validator_set_update_epoch(current_epoch);
+            }
+        }
+    }
+
+    /// The evidence to slash from the given misbehaviors, as carried by slash
+    /// protocol txs
+    fn evidence_to_slash(
+        &self,
+        misbehaviors: &[Misbehavior],
+    ) -> BTreeSet<SlashEvidence> {
+        misbehaviors
+            .iter()
+            .filter_map(|evidence| self.slash_evidence(evidence))
+            .collect()
+    }
+
+    /// Find the validator and the type of slashable event of the given
+    /// misbehavior, if known
+    fn slash_evidence(&self, evidence: &Misbehavior) -> Option<SlashEvidence> {
+        let evidence_height = match u64::try_from(evidence.height) {
+            Ok(height) => height,
+            Err(err) => {
+                tracing::error!("Unexpected evidence block height {}", err);
+                return None;
+            }
+        };
+        let slash_type = match evidence.kind {
+            MisbehaviorKind::DuplicateVote => {
+                pos::types::SlashType::DuplicateVote
+            }
+            MisbehaviorKind::LightClientAttack => {
+                pos::types::SlashType::LightClientAttack
+            }
+            MisbehaviorKind::Unknown => {
+                tracing::error!("Unknown evidence: {:#?}", evidence);
+                return None;
+            }
+        };
+        let validator_raw_hash =
+            tm_raw_hash_to_string(evidence.validator.address);
+        let validator = match proof_of_stake::find_validator_by_raw_hash(
+            &self.wl_storage,
+            &validator_raw_hash,
+        )
+        .expect("Must be able to read storage")
+        {
+            Some(validator) => validator,
+            None => {
+                tracing::error!(
+                    "Cannot find validator's address from raw hash {}",
+                    validator_raw_hash
+                );
+                return None;
+            }
+        };
+        Some(SlashEvidence {
+            validator,
+            block_height: evidence_height,
+            r#type: slash_type,
+        })
+    }
+
+    /// Slash the validator of the given evidence, unless the evidence is
+    /// outdated or was already slashed, be it from the evidence delivered by
+    /// CometBFT or from a slash protocol tx
+    fn apply_slash_evidence(&mut self, evidence: &SlashEvidence) {
+        let SlashEvidence {
+            validator,
+            block_height: evidence_height,
+            r#type: slash_type,
+        } = evidence;
+        // TODO: resolve this unwrap() better
+        let pos_params = read_pos_params(&self.wl_storage).unwrap();
+        let current_epoch = self.wl_storage.storage.block.epoch;
+        let evidence_epoch = match self
+            .wl_storage
+            .storage
+            .block
+            .pred_epochs
+            .get_epoch(BlockHeight(*evidence_height))
+        {
+            Some(epoch) => epoch,
+            None => {
+                tracing::error!(
+                    "Couldn't find epoch for evidence block height {}",
+                    evidence_height
+                );
+                return;
+            }
+        };
+        // Disregard evidences that should have already been processed
+        // at this time
+        if evidence_epoch + pos_params.slash_processing_epoch_offset()
+            - pos_params.cubic_slashing_window_length
+            <= current_epoch
+        {
+            tracing::info!(
+                "Skipping outdated evidence from epoch {evidence_epoch}"
+            );
+            return;
+        }
+        match proof_of_stake::is_slash_enqueued(
+            &self.wl_storage,
+            &pos_params,
+            evidence_epoch,
+            *evidence_height,
+            *slash_type,
+            validator,
+        ) {
+            Ok(false) => {}
+            Ok(true) => {
                 tracing::info!(
-                    "Slashing {} for {} in epoch {}, block height {} (current \
-                     epoch = {}, validator set update epoch = \
-                     {validator_set_update_epoch})",
+                    "Skipping evidence against {} for {} at block height {}, \
+                     which was already slashed",
                     validator,
                     slash_type,
-                    evidence_epoch,
-                    evidence_height,
-                    current_epoch
+                    evidence_height
                 );
-                if let Err(err) = slash(
-                    &mut self.wl_storage,
-                    &pos_params,
-                    current_epoch,
-                    evidence_epoch,
-                    evidence_height,
-                    slash_type,
-                    &validator,
-                    validator_set_update_epoch,
-                ) {
-                    tracing::error!("Error in slashing: {}", err);
-                }
+                return;
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Error in reading the enqueued slashes: {}",
+                    err
+                );
+                return;
             }
         }
+        // Check if we're gonna switch to a new epoch after a delay
+        let validator_set_update_epoch =
+            self.get_validator_set_update_epoch(current_epoch);
+        tracing::info!(
+            "Slashing {} for {} in epoch {}, block height {} (current epoch = \
+             {}, validator set update epoch = {validator_set_update_epoch})",
+            validator,
+            slash_type,
+            evidence_epoch,
+            evidence_height,
+            current_epoch
+        );
+        if let Err(err) = slash(
+            &mut self.wl_storage,
+            &pos_params,
+            current_epoch,
+            evidence_epoch,
+            *evidence_height,
+            *slash_type,
+            validator,
+            validator_set_update_epoch,
+        ) {
+            tracing::error!("Error in slashing: {}", err);
+        }
     }
 
     /// Get the next epoch for which we can request validator set changed
@@ -839,6 +898,9 @@ where
             self.wl_storage.storage.get_last_block_height(),
         );
         response.data = root.0.to_vec().into();
+        self.tx_decode_cache.clear();
+        self.vp_wasm_cache.code_loader().clear();
+        self.tx_wasm_cache.code_loader().clear();
 
         self.bump_last_processed_eth_block();
         self.broadcast_queued_txs();
@@ -977,7 +1039,7 @@ where
 
         // Write wrapper hash to WAL
         temp_wl_storage
-            .write_tx_hash(wrapper_hash)
+            .write_tx_hash(wrapper_hash, wrapper.header.expiration)
             .map_err(|e| Error::ReplayAttempt(e.to_string()))
     }
 
@@ -1070,6 +1132,24 @@ where
     /// included in the mempool and propagated to peers, otherwise it will be
     /// rejected.
     pub fn mempool_validate(
+        &self,
+        tx_bytes: &[u8],
+        r#type: MempoolTxType,
+    ) -> response::CheckTx {
+        let response = self.check_mempool_tx(tx_bytes, r#type);
+        if response.code.is_err() {
+            self.debug_log.record(
+                RejectionSource::CheckTx,
+                tx_bytes,
+                response.code.value(),
+                &response.log,
+            );
+        }
+        response
+    }
+
+    /// The checks of [`Shell::mempool_validate`]
+    fn check_mempool_tx(
         &self,
         tx_bytes: &[u8],
         r#_type: MempoolTxType,
@@ -1090,16 +1170,20 @@ where
         if !validate_tx_bytes(&self.wl_storage, tx_bytes.len())
             .expect("Failed to get max tx bytes param from storage")
         {
-            response.code = ErrorCodes::TooLarge.into();
+            response.code = ResultCode::TooLarge.into();
             response.log = format!("{INVALID_MSG}: Tx too large");
             return response;
         }
 
         // Tx format check
-        let tx = match Tx::try_from(tx_bytes).map_err(Error::TxDecoding) {
+        let decoded = self.tx_decode_cache.decode(tx_bytes);
+        let tx = match decoded
+            .tx()
+            .map_err(|err| Error::TxDecoding(err.to_string()))
+        {
             Ok(t) => t,
             Err(msg) => {
-                response.code = ErrorCodes::InvalidTx.into();
+                response.code = ResultCode::InvalidTx.into();
                 response.log = format!("{INVALID_MSG}: {msg}");
                 return response;
             }
@@ -1107,7 +1191,7 @@ where
 
         // Tx chain id
         if tx.header.chain_id != self.chain_id {
-            response.code = ErrorCodes::InvalidChainId.into();
+            response.code = ResultCode::InvalidChainId.into();
             response.log = format!(
                 "{INVALID_MSG}: Tx carries a wrong chain id: expected {}, \
                  found {}",
@@ -1121,7 +1205,7 @@ where
             let last_block_timestamp = self.get_block_timestamp(None);
 
             if last_block_timestamp > exp {
-                response.code = ErrorCodes::ExpiredTx.into();
+                response.code = ResultCode::ExpiredTx.into();
                 response.log = format!(
                     "{INVALID_MSG}: Tx expired at {exp:#?}, last committed \
                      block time: {last_block_timestamp:#?}",
@@ -1131,10 +1215,10 @@ where
         }
 
         // Tx signature check
-        let tx_type = match tx.validate_tx() {
-            Ok(_) => tx.header(),
+        let tx_type = match decoded.validate() {
+            Ok(()) => tx.header(),
             Err(msg) => {
-                response.code = ErrorCodes::InvalidSig.into();
+                response.code = ResultCode::InvalidSig.into();
                 response.log = format!("{INVALID_MSG}: {msg}");
                 return response;
             }
@@ -1147,7 +1231,7 @@ where
                 match $result {
                     Ok(ext) => ext,
                     Err(err) => {
-                        $rsp.code = ErrorCodes::InvalidVoteExtension.into();
+                        $rsp.code = ResultCode::InvalidVoteExtension.into();
                         $rsp.log = format!(
                             "{INVALID_MSG}: Invalid {} vote extension: {err}",
                             $kind,
@@ -1164,7 +1248,7 @@ where
                     let ext = try_vote_extension!(
                         "Ethereum events",
                         response,
-                        ethereum_tx_data_variants::EthEventsVext::try_from(&tx),
+                        ethereum_tx_data_variants::EthEventsVext::try_from(tx),
                     );
                     if let Err(err) = self
                         .validate_eth_events_vext_and_get_it_back(
@@ -1172,7 +1256,7 @@ where
                             self.wl_storage.storage.get_last_block_height(),
                         )
                     {
-                        response.code = ErrorCodes::InvalidVoteExtension.into();
+                        response.code = ResultCode::InvalidVoteExtension.into();
                         response.log = format!(
                             "{INVALID_MSG}: Invalid Ethereum events vote \
                              extension: {err}",
@@ -1185,9 +1269,7 @@ where
                     let ext = try_vote_extension!(
                         "Bridge pool roots",
                         response,
-                        ethereum_tx_data_variants::BridgePoolVext::try_from(
-                            &tx
-                        ),
+                        ethereum_tx_data_variants::BridgePoolVext::try_from(tx),
                     );
                     if let Err(err) = self
                         .validate_bp_roots_vext_and_get_it_back(
@@ -1195,7 +1277,7 @@ where
                             self.wl_storage.storage.get_last_block_height(),
                         )
                     {
-                        response.code = ErrorCodes::InvalidVoteExtension.into();
+                        response.code = ResultCode::InvalidVoteExtension.into();
                         response.log = format!(
                             "{INVALID_MSG}: Invalid Brige pool roots vote \
                              extension: {err}",
@@ -1209,7 +1291,7 @@ where
                         "validator set update",
                         response,
                         ethereum_tx_data_variants::ValSetUpdateVext::try_from(
-                            &tx
+                            tx
                         ),
                     );
                     if let Err(err) = self
@@ -1226,7 +1308,7 @@ where
                             self.wl_storage.storage.last_epoch,
                         )
                     {
-                        response.code = ErrorCodes::InvalidVoteExtension.into();
+                        response.code = ResultCode::InvalidVoteExtension.into();
                         response.log = format!(
                             "{INVALID_MSG}: Invalid validator set update vote \
                              extension: {err}",
@@ -1239,7 +1321,7 @@ where
                     }
                 }
                 _ => {
-                    response.code = ErrorCodes::InvalidTx.into();
+                    response.code = ResultCode::InvalidTx.into();
                     response.log = format!(
                         "{INVALID_MSG}: The given protocol tx cannot be added \
                          to the mempool"
@@ -1250,7 +1332,7 @@ where
                 // Tx gas limit
                 let mut gas_meter = TxGasMeter::new(wrapper.gas_limit);
                 if gas_meter.add_wrapper_gas(tx_bytes).is_err() {
-                    response.code = ErrorCodes::TxGasLimit.into();
+                    response.code = ResultCode::TxGasLimit.into();
                     response.log = "{INVALID_MSG}: Wrapper transactions \
                                     exceeds its gas limit"
                         .to_string();
@@ -1265,7 +1347,7 @@ where
                     .unwrap(),
                 );
                 if gas_meter.tx_gas_limit > block_gas_limit {
-                    response.code = ErrorCodes::AllocationError.into();
+                    response.code = ResultCode::AllocationError.into();
                     response.log = "{INVALID_MSG}: Wrapper transaction \
                                     exceeds the maximum block gas limit"
                         .to_string();
@@ -1280,7 +1362,7 @@ where
                     .has_replay_protection_entry(&tx.raw_header_hash())
                     .expect("Error while checking inner tx hash key in storage")
                 {
-                    response.code = ErrorCodes::ReplayTx.into();
+                    response.code = ResultCode::ReplayTx.into();
                     response.log = format!(
                         "{INVALID_MSG}: Inner transaction hash {} already in \
                          storage, replay attempt",
@@ -1289,8 +1371,6 @@ where
                     return response;
                 }
 
-                let tx = Tx::try_from(tx_bytes)
-                    .expect("Deserialization shouldn't fail");
                 let wrapper_hash = &tx.header_hash();
                 if self
                     .wl_storage
@@ -1300,7 +1380,7 @@ where
                         "Error while checking wrapper tx hash key in storage",
                     )
                 {
-                    response.code = ErrorCodes::ReplayTx.into();
+                    response.code = ResultCode::ReplayTx.into();
                     response.log = format!(
                         "{INVALID_MSG}: Wrapper transaction hash {} already \
                          in storage, replay attempt",
@@ -1312,34 +1392,34 @@ where
                 // Validate wrapper fees
                 if let Err(e) = self.wrapper_fee_check(
                     &wrapper,
-                    get_fee_unshielding_transaction(&tx, &wrapper),
+                    get_fee_unshielding_transaction(tx, &wrapper),
                     &mut TempWlStorage::new(&self.wl_storage.storage),
                     &mut self.vp_wasm_cache.clone(),
                     &mut self.tx_wasm_cache.clone(),
                     None,
                     false,
                 ) {
-                    response.code = ErrorCodes::FeeError.into();
+                    response.code = ResultCode::FeeError.into();
                     response.log = format!("{INVALID_MSG}: {e}");
                     return response;
                 }
             }
             TxType::Raw => {
-                response.code = ErrorCodes::InvalidTx.into();
+                response.code = ResultCode::InvalidTx.into();
                 response.log = format!(
                     "{INVALID_MSG}: Raw transactions cannot be accepted into \
                      the mempool"
                 );
             }
             TxType::Decrypted(_) => {
-                response.code = ErrorCodes::InvalidTx.into();
+                response.code = ResultCode::InvalidTx.into();
                 response.log = format!(
                     "{INVALID_MSG}: Decrypted txs cannot be sent by clients"
                 );
             }
         }
 
-        if response.code == ErrorCodes::Ok.into() {
+        if response.code == ResultCode::Ok.into() {
             response.log = VALID_MSG.into();
         }
         response
@@ -1451,12 +1531,15 @@ where
             match apply_wasm_tx(
                 unshield,
                 &TxIndex::default(),
+                None,
                 ShellParams::new(
                     &mut TxGasMeter::new(fee_unshielding_gas_limit),
                     temp_wl_storage,
                     vp_wasm_cache,
                     tx_wasm_cache,
                 ),
+                #[cfg(not(feature = "mainnet"))]
+                false,
             ) {
                 Ok(result) => {
                     if !result.is_accepted() {
@@ -1482,6 +1565,7 @@ where
         let result = match block_proposer {
             Some(proposer) => {
                 protocol::transfer_fee(temp_wl_storage, proposer, wrapper)
+                    .map(|_fee| ())
             }
             None => protocol::check_fees(temp_wl_storage, wrapper),
         };
@@ -1553,12 +1637,16 @@ mod test_utils {
     use namada::core::ledger::masp_conversions::update_allowed_conversions;
     use namada::core::ledger::storage::EPOCH_SWITCH_BLOCKS_DELAY;
     use namada::ledger::parameters::{EpochDuration, Parameters};
+    use namada::ledger::pos::PosQueries;
+    use namada::ledger::queries::{
+        Client, EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
+    };
     use namada::ledger::storage::mockdb::MockDB;
     use namada::ledger::storage::{LastBlock, Sha256Hasher};
     use namada::ledger::storage_api::StorageWrite;
     use namada::proof_of_stake::parameters::PosParams;
     use namada::proof_of_stake::validator_consensus_key_handle;
-    use namada::proto::{Code, Data};
+    use namada::proto::{Code, Data, SigFailure, Signature, Signer};
     use namada::tendermint::abci::types::VoteInfo;
     use namada::types::address;
     use namada::types::chain::ChainId;
@@ -1574,16 +1662,17 @@ mod test_utils {
 
     use super::*;
     use crate::config::ethereum_bridge::ledger::ORACLE_CHANNEL_BUFFER_SIZE;
-    use crate::facade::tendermint;
     use crate::facade::tendermint::abci::types::Misbehavior;
     use crate::facade::tendermint_proto::google::protobuf::Timestamp;
     use crate::facade::tendermint_proto::v0_37::abci::{
         RequestPrepareProposal, RequestProcessProposal,
     };
+    use crate::facade::{tendermint, tendermint_rpc};
     use crate::node::ledger::shims::abcipp_shim_types;
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::{
         FinalizeBlock, ProcessedTx,
     };
+    use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
     use crate::node::ledger::storage::{PersistentDB, PersistentStorageHasher};
 
     #[derive(Error, Debug)]
@@ -1612,6 +1701,56 @@ mod test_utils {
         gen_ed25519_keypair()
     }
 
+    /// Make a wrapper tx of the given shell whose signature fails
+    /// verification in the given way, along with the start of the info of
+    /// its rejection
+    pub(super) fn wrapper_failing_sig(
+        shell: &TestShell,
+        failure: SigFailure,
+    ) -> (Tx, &'static str) {
+        let keypair = gen_keypair();
+        let other_keypair = gen_keypair();
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: Default::default(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                Default::default(),
+                None,
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        let info = match failure {
+            SigFailure::NoSignatureForTarget => "Missing signature.",
+            SigFailure::SignerMismatch => {
+                wrapper.add_section(Section::Signature(Signature::new(
+                    wrapper.sechashes(),
+                    [(0, other_keypair)].into_iter().collect(),
+                    None,
+                )));
+                "Unexpected signer."
+            }
+            SigFailure::InvalidSignature => {
+                // The section claims the key of the wrapper, but is signed
+                // by another one
+                let mut section = Signature::new(
+                    wrapper.sechashes(),
+                    [(0, other_keypair)].into_iter().collect(),
+                    None,
+                );
+                section.signer = Signer::PubKeys(vec![keypair.ref_to()]);
+                wrapper.add_section(Section::Signature(section));
+                "Invalid signature."
+            }
+            SigFailure::Other => panic!("Test failed"),
+        };
+        (wrapper, info)
+    }
+
     /// Generate a random ed25519 public/private keypair
     pub(super) fn gen_ed25519_keypair() -> common::SecretKey {
         use rand::prelude::ThreadRng;
@@ -1656,6 +1795,7 @@ mod test_utils {
                         .unwrap();
                 common::Signature::Secp256k1((&bytes).try_into().unwrap())
             }
+            _ => panic!("Unexpected signature scheme"),
         }
     }
 
@@ -1697,6 +1837,49 @@ mod test_utils {
         }
     }
 
+    /// Handle client queries directly against the storage and event log of
+    /// the shell
+    #[cfg_attr(feature = "async-send", async_trait::async_trait)]
+    #[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+    impl Client for TestShell {
+        type Error = std::io::Error;
+
+        async fn request(
+            &self,
+            path: String,
+            data: Option<Vec<u8>>,
+            height: Option<BlockHeight>,
+            prove: bool,
+        ) -> std::result::Result<EncodedResponseQuery, Self::Error> {
+            let request = RequestQuery {
+                data: data.unwrap_or_default().into(),
+                path,
+                height: height.unwrap_or_default().try_into().unwrap(),
+                prove,
+            };
+            let ctx = RequestCtx {
+                wl_storage: &self.wl_storage,
+                event_log: self.event_log(),
+                vp_wasm_cache: self.vp_wasm_cache.read_only(),
+                tx_wasm_cache: self.tx_wasm_cache.read_only(),
+                storage_read_past_height_limit: None,
+            };
+            RPC.handle(ctx, &request).map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::Other, err)
+            })
+        }
+
+        async fn perform<R>(
+            &self,
+            _request: R,
+        ) -> std::result::Result<R::Output, tendermint_rpc::Error>
+        where
+            R: tendermint_rpc::SimpleRequest,
+        {
+            unreachable!()
+        }
+    }
+
     #[derive(Clone)]
     /// Helper for testing process proposal which has very different
     /// input types depending on whether the ABCI++ feature is on or not.
@@ -1852,6 +2035,8 @@ mod test_utils {
             self.shell.wl_storage.storage.tx_queue.push(TxInQueue {
                 tx,
                 gas: inner_tx_gas,
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow: false,
             });
         }
 
@@ -2101,6 +2286,8 @@ mod test_utils {
         shell.wl_storage.storage.tx_queue.push(TxInQueue {
             tx: wrapper,
             gas: u64::MAX.into(),
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow: false,
         });
         // Artificially increase the block height so that chain
         // will read the new block when restarted
@@ -2257,25 +2444,246 @@ mod test_utils {
         shell.finalize_block(req).unwrap();
         shell.commit();
     }
+
+    /// The outcome of a block driven by a [`BlockDriver`]
+    #[derive(Debug)]
+    pub(super) struct BlockOutcome {
+        /// The txs of the proposal
+        pub proposal: Vec<TxBytes>,
+        /// Whether the proposal was accepted, in which case the block was
+        /// finalized and committed
+        pub accepted: bool,
+        /// The results of processing each tx of the proposal
+        pub results: Vec<TxResult>,
+        /// The events of the block, if it was finalized
+        pub events: Vec<Event>,
+    }
+
+    impl BlockOutcome {
+        /// The type, hash and result code of each tx event of the block
+        pub fn tx_events(&self) -> Vec<(String, String, ResultCode)> {
+            self.events
+                .iter()
+                .filter_map(|event| {
+                    let hash = event.get("hash")?;
+                    let code = event.get("code")?.parse().ok()?;
+                    Some((
+                        event.event_type.to_string(),
+                        hash.clone(),
+                        ResultCode::from_u32(code)?,
+                    ))
+                })
+                .collect()
+        }
+    }
+
+    /// Drives a [`TestShell`] through whole blocks, as CometBFT would: the
+    /// submitted txs go through `CheckTx` into a mempool, from which each
+    /// block is prepared, processed, finalized and committed, after which
+    /// the rest of the mempool is checked again. Epochs don't change while
+    /// blocks are driven, so that wrapper txs are always allowed.
+    pub(super) struct BlockDriver {
+        pub shell: TestShell,
+        /// The bytes of the txs admitted to the mempool, in order
+        mempool: Vec<Vec<u8>>,
+        /// The time of the next block
+        time: DateTimeUtc,
+    }
+
+    impl BlockDriver {
+        /// Drive the given shell, from past the first heights of its current
+        /// epoch
+        pub fn new(mut shell: TestShell) -> Self {
+            shell.start_new_epoch_in(1_000_000);
+            let mut driver = Self {
+                shell,
+                mempool: vec![],
+                time: DateTimeUtc::now(),
+            };
+            // Wrapper txs are not allowed at some of the first heights of
+            // an epoch
+            while (0..=2).any(|offset| {
+                driver
+                    .shell
+                    .wl_storage
+                    .pos_queries()
+                    .is_deciding_offset_within_epoch(offset)
+            }) {
+                assert!(driver.next_block().accepted);
+            }
+            driver
+        }
+
+        /// Submit a tx to the mempool, which admits it if `CheckTx` accepts
+        /// it
+        pub fn submit(&mut self, tx: &Tx) -> response::CheckTx {
+            let tx_bytes = tx.to_bytes();
+            let response = self
+                .shell
+                .mempool_validate(&tx_bytes, MempoolTxType::NewTransaction);
+            if response.code.is_ok() {
+                self.mempool.push(tx_bytes);
+            }
+            response
+        }
+
+        /// Prepare the proposal of the next block from the mempool
+        pub fn propose(&self) -> Vec<TxBytes> {
+            self.shell
+                .prepare_proposal(RequestPrepareProposal {
+                    txs: self
+                        .mempool
+                        .iter()
+                        .cloned()
+                        .map(TxBytes::from)
+                        .collect(),
+                    time: Some(self.timestamp()),
+                    ..Default::default()
+                })
+                .txs
+        }
+
+        /// Propose the next block, then process, finalize and commit it
+        pub fn next_block(&mut self) -> BlockOutcome {
+            self.next_block_with(|_| ())
+        }
+
+        /// Same as [`BlockDriver::next_block`], but the proposal is first
+        /// altered with the given function, as a byzantine proposer would.
+        /// A rejected proposal is neither finalized nor committed, and the
+        /// time of the next block doesn't advance.
+        pub fn next_block_with(
+            &mut self,
+            tamper: impl FnOnce(&mut Vec<TxBytes>),
+        ) -> BlockOutcome {
+            let mut proposal = self.propose();
+            tamper(&mut proposal);
+            let proposer_address = HEXUPPER
+                .decode(
+                    crate::wallet::defaults::validator_keypair()
+                        .to_public()
+                        .tm_raw_hash()
+                        .as_bytes(),
+                )
+                .unwrap();
+            let (response, results) =
+                self.shell.shell.process_proposal(RequestProcessProposal {
+                    txs: proposal.clone(),
+                    proposer_address: proposer_address.clone().into(),
+                    time: Some(self.timestamp()),
+                    ..Default::default()
+                });
+            if response != tendermint::abci::response::ProcessProposal::Accept {
+                return BlockOutcome {
+                    proposal,
+                    accepted: false,
+                    results,
+                    events: vec![],
+                };
+            }
+            let events = self
+                .shell
+                .finalize_block(FinalizeBlock {
+                    header: Header {
+                        time: self.time,
+                        ..Default::default()
+                    },
+                    txs: proposal
+                        .iter()
+                        .cloned()
+                        .zip(results.iter().cloned())
+                        .map(|(tx, result)| ProcessedTx { tx, result })
+                        .collect(),
+                    proposer_address,
+                    ..Default::default()
+                })
+                .expect("Test failed");
+            self.shell.commit();
+            // Drop the included txs from the mempool, then check the others
+            // again against the new state
+            let shell = &self.shell;
+            self.mempool.retain(|tx_bytes| {
+                !proposal.iter().any(|tx| tx == tx_bytes)
+                    && shell
+                        .mempool_validate(
+                            tx_bytes,
+                            MempoolTxType::RecheckTransaction,
+                        )
+                        .code
+                        .is_ok()
+            });
+            self.time = self.time + DurationSecs(1);
+            BlockOutcome {
+                proposal,
+                accepted: true,
+                results,
+                events,
+            }
+        }
+
+        /// The time of the next block
+        pub fn time(&self) -> DateTimeUtc {
+            self.time
+        }
+
+        /// The bytes of the txs in the mempool, in order
+        pub fn mempool(&self) -> &[Vec<u8>] {
+            &self.mempool
+        }
+
+        /// The header hashes of the wrappers queued for decryption in the
+        /// next block
+        pub fn queued(&self) -> Vec<Hash> {
+            self.shell
+                .wl_storage
+                .storage
+                .tx_queue
+                .iter()
+                .map(|wrapper| wrapper.tx.header_hash())
+                .collect()
+        }
+
+        /// The committed native token balance of the given owner
+        pub fn balance(&self, owner: &Address) -> token::Amount {
+            storage_api::token::read_balance(
+                &self.shell.wl_storage,
+                &self.shell.wl_storage.storage.native_token,
+                owner,
+            )
+            .expect("Test failed")
+        }
+
+        /// The time of the next block as a protobuf timestamp
+        fn timestamp(&self) -> Timestamp {
+            Timestamp {
+                seconds: self.time.0.timestamp(),
+                nanos: self.time.0.timestamp_subsec_nanos() as i32,
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod shell_tests {
     use namada::core::ledger::replay_protection;
     use namada::proto::{
-        Code, Data, Section, SignableEthMessage, Signature, Signed, Tx,
+        Ciphertext, Code, Data, Section, SigFailure, SignableEthMessage,
+        Signature, Signed, Tx,
     };
     use namada::types::ethereum_events::EthereumEvent;
     use namada::types::key::RefTo;
     use namada::types::storage::{BlockHeight, Epoch};
+    use namada::types::time::Duration;
     use namada::types::transaction::protocol::{
         ethereum_tx_data_variants, ProtocolTx, ProtocolTxType,
     };
     use namada::types::transaction::{Fee, WrapperTx};
     use namada::types::vote_extensions::{bridge_pool_roots, ethereum_events};
+    use namada_test_utils::TestWasms;
 
     use super::*;
     use crate::node::ledger::shell::test_utils;
+    use crate::node::ledger::shell::test_utils::{BlockDriver, TestShell};
     use crate::wallet;
 
     const GAS_LIMIT_MULTIPLIER: u64 = 100_000;
@@ -2410,7 +2818,7 @@ mod shell_tests {
         for (tx_bytes, err_msg) in txs_to_validate {
             let rsp = shell.mempool_validate(&tx_bytes, Default::default());
             assert!(
-                rsp.code == ErrorCodes::InvalidVoteExtension.into(),
+                rsp.code == ResultCode::InvalidVoteExtension.into(),
                 "{err_msg}"
             );
         }
@@ -2492,7 +2900,7 @@ mod shell_tests {
         }
         .to_bytes();
         let rsp = shell.mempool_validate(&tx, Default::default());
-        assert_eq!(rsp.code, ErrorCodes::InvalidVoteExtension.into());
+        assert_eq!(rsp.code, ResultCode::InvalidVoteExtension.into());
     }
 
     /// Mempool validation must reject unsigned wrappers
@@ -2524,12 +2932,12 @@ mod shell_tests {
             unsigned_wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::InvalidSig.into());
+        assert_eq!(result.code, ResultCode::InvalidSig.into());
         result = shell.mempool_validate(
             unsigned_wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::InvalidSig.into());
+        assert_eq!(result.code, ResultCode::InvalidSig.into());
     }
 
     /// Mempool validation must reject wrappers with an invalid signature
@@ -2572,12 +2980,40 @@ mod shell_tests {
             invalid_wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::InvalidSig.into());
+        assert_eq!(result.code, ResultCode::InvalidSig.into());
         result = shell.mempool_validate(
             invalid_wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::InvalidSig.into());
+        assert_eq!(result.code, ResultCode::InvalidSig.into());
+    }
+
+    /// Mempool validation must tell apart the ways the signature of a wrapper
+    /// can fail verification
+    #[test]
+    fn test_signature_failures_told_apart() {
+        let (shell, _recv, _, _) = test_utils::setup();
+
+        for failure in [
+            SigFailure::NoSignatureForTarget,
+            SigFailure::SignerMismatch,
+            SigFailure::InvalidSignature,
+        ] {
+            let (wrapper, info) =
+                test_utils::wrapper_failing_sig(&shell, failure);
+            let result = shell.mempool_validate(
+                wrapper.to_bytes().as_ref(),
+                MempoolTxType::NewTransaction,
+            );
+            assert_eq!(result.code, ResultCode::InvalidSig.into());
+            assert!(
+                result
+                    .log
+                    .starts_with(&format!("Mempool validation failed: {info}")),
+                "Unexpected log {} for {failure:?}",
+                result.log
+            );
+        }
     }
 
     /// Mempool validation must reject non-wrapper txs
@@ -2592,7 +3028,7 @@ mod shell_tests {
             tx.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::InvalidTx.into());
+        assert_eq!(result.code, ResultCode::InvalidTx.into());
         assert_eq!(
             result.log,
             "Mempool validation failed: Raw transactions cannot be accepted \
@@ -2600,6 +3036,45 @@ mod shell_tests {
         )
     }
 
+    /// Mempool validation must reject wrappers carrying ciphertext sections
+    #[test]
+    fn test_wrapper_with_ciphertext() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let keypair = super::test_utils::gen_keypair();
+
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Ciphertext(Ciphertext {
+            opaque: "encrypted sections".as_bytes().to_owned().into(),
+        }));
+        wrapper.add_section(Section::Signature(Signature::new(
+            wrapper.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, ResultCode::InvalidSig.into());
+        assert!(result.log.ends_with(
+            "A wrapper tx must carry no ciphertext sections, but carries 1"
+        ));
+    }
+
     /// Mempool validation must reject already applied wrapper and decrypted
     /// transactions
     #[test]
@@ -2638,7 +3113,7 @@ mod shell_tests {
         shell
             .wl_storage
             .storage
-            .write_replay_protection_entry(&mut batch, &wrapper_hash_key)
+            .write_replay_protection_entry(&mut batch, &wrapper_hash_key, None)
             .expect("Test failed");
 
         // Try wrapper tx replay attack
@@ -2646,7 +3121,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::ReplayTx.into());
+        assert_eq!(result.code, ResultCode::ReplayTx.into());
         assert_eq!(
             result.log,
             format!(
@@ -2660,7 +3135,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::ReplayTx.into());
+        assert_eq!(result.code, ResultCode::ReplayTx.into());
         assert_eq!(
             result.log,
             format!(
@@ -2679,7 +3154,7 @@ mod shell_tests {
         shell
             .wl_storage
             .storage
-            .write_replay_protection_entry(&mut batch, &inner_hash_key)
+            .write_replay_protection_entry(&mut batch, &inner_hash_key, None)
             .expect("Test failed");
 
         // Try inner tx replay attack
@@ -2687,7 +3162,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::ReplayTx.into());
+        assert_eq!(result.code, ResultCode::ReplayTx.into());
         assert_eq!(
             result.log,
             format!(
@@ -2701,7 +3176,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::ReplayTx.into());
+        assert_eq!(result.code, ResultCode::ReplayTx.into());
         assert_eq!(
             result.log,
             format!(
@@ -2729,7 +3204,7 @@ mod shell_tests {
             tx.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::InvalidChainId.into());
+        assert_eq!(result.code, ResultCode::InvalidChainId.into());
         assert_eq!(
             result.log,
             format!(
@@ -2757,7 +3232,7 @@ mod shell_tests {
             tx.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::ExpiredTx.into());
+        assert_eq!(result.code, ResultCode::ExpiredTx.into());
     }
 
     /// Check that a tx requiring more gas than the block limit gets rejected
@@ -2794,7 +3269,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::AllocationError.into());
+        assert_eq!(result.code, ResultCode::AllocationError.into());
     }
 
     // Check that a tx requiring more gas than its limit gets rejected
@@ -2827,7 +3302,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::TxGasLimit.into());
+        assert_eq!(result.code, ResultCode::TxGasLimit.into());
     }
 
     // Check that a wrapper using a non-whitelisted token for fee payment is
@@ -2862,7 +3337,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::FeeError.into());
+        assert_eq!(result.code, ResultCode::FeeError.into());
     }
 
     // Check that a wrapper setting a fee amount lower than the minimum required
@@ -2897,7 +3372,48 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::FeeError.into());
+        assert_eq!(result.code, ResultCode::FeeError.into());
+    }
+
+    /// Check that a wrapper built the way a browser wallet builds it is
+    /// accepted. Such a wallet only links against the minimal feature set of
+    /// the core crate on `wasm32-unknown-unknown`, where the current time is
+    /// read from `Date.now()` in milliseconds instead of the system clock.
+    #[test]
+    fn test_accept_wallet_built_wrapper() {
+        use namada::types::time::DateTimeUtc;
+
+        let (shell, _recv, _, _) = test_utils::setup();
+        let keypair = crate::wallet::defaults::albert_keypair();
+
+        // The time a browser wallet reads from `Date.now()`
+        let date_now = DateTimeUtc::now().0.timestamp_millis();
+        let mut wrapper = Tx::new(
+            shell.chain_id.clone(),
+            DateTimeUtc::from_unix_millis(date_now + 60_000),
+        );
+        wrapper.header.timestamp =
+            DateTimeUtc::from_unix_millis(date_now).unwrap();
+        wrapper
+            .add_code("wasm_code".as_bytes().to_owned(), None)
+            .add_serialized_data("transaction data".as_bytes().to_owned())
+            .add_wrapper(
+                Fee {
+                    amount_per_gas_unit: token::Amount::from_uint(100, 0)
+                        .expect("This can't fail"),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            )
+            .sign_wrapper(keypair);
+        let tx_bytes = wrapper.to_bytes();
+
+        let result =
+            shell.mempool_validate(&tx_bytes, MempoolTxType::NewTransaction);
+        assert_eq!(result.code, ResultCode::Ok.into(), "{}", result.log);
     }
 
     // Check that a wrapper transactions whose fees cannot be paid is rejected
@@ -2931,7 +3447,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::FeeError.into());
+        assert_eq!(result.code, ResultCode::FeeError.into());
     }
 
     // Check that a fee overflow in the wrapper transaction is rejected
@@ -2965,7 +3481,7 @@ mod shell_tests {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::FeeError.into());
+        assert_eq!(result.code, ResultCode::FeeError.into());
     }
 
     /// Test max tx bytes parameter in CheckTx
@@ -3012,13 +3528,339 @@ mod shell_tests {
             new_tx(50).to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert!(result.code != ErrorCodes::TooLarge.into());
+        assert!(result.code != ResultCode::TooLarge.into());
 
         // max tx bytes + 1, on the other hand, is not
         let result = shell.mempool_validate(
             new_tx(max_tx_bytes + 1).to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, ErrorCodes::TooLarge.into());
+        assert_eq!(result.code, ResultCode::TooLarge.into());
+    }
+
+    /// Test that the errors a tx wasm aborts with are surfaced with distinct
+    /// result codes, which are recoverable
+    #[test]
+    fn test_tx_wasm_error_codes() {
+        let invalid_data = ResultCode::from(&TxWasmError::InvalidData);
+        let missing_section = ResultCode::from(&TxWasmError::MissingSection(
+            namada::types::hash::Hash::sha256(b"section"),
+        ));
+        let unauthorized = ResultCode::from(&TxWasmError::Unauthorized);
+        let custom =
+            ResultCode::from(&TxWasmError::Custom("custom".to_string()));
+        assert_eq!(invalid_data, ResultCode::InvalidTxData);
+        assert_eq!(missing_section, ResultCode::MissingTxSection);
+        assert_eq!(unauthorized, ResultCode::UnauthorizedTx);
+        assert_eq!(custom, ResultCode::WasmRuntimeError);
+        assert_ne!(u32::from(invalid_data), u32::from(missing_section));
+        for code in [invalid_data, missing_section, unauthorized, custom] {
+            assert!(code.is_recoverable());
+        }
+    }
+
+    /// Make a wrapper of a no-op tx on the chain of the given shell, whose
+    /// fee is paid by Albert, expiring at the given time if any
+    fn no_op_wrapper(shell: &TestShell, expiration: Option<DateTimeUtc>) -> Tx {
+        let keypair = wallet::defaults::albert_keypair();
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: 1.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.header.expiration = expiration;
+        wrapper.set_code(Code::new(TestWasms::TxNoOp.read_bytes(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            wrapper.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+        wrapper
+    }
+
+    /// Check that a wrapper tx admitted to the mempool transfers its fee to
+    /// the proposer of the block including it and is queued, and that its
+    /// inner tx is applied in the next block.
+    #[test]
+    fn test_driven_wrapper_and_inner_tx() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let mut driver = BlockDriver::new(shell);
+        let proposer =
+            driver.shell.mode.get_validator_address().unwrap().clone();
+        let wrapper = no_op_wrapper(&driver.shell, None);
+        let payer = wrapper.header().wrapper().unwrap().fee_payer();
+        let fee = wrapper.header().wrapper().unwrap().get_tx_fee().unwrap();
+        let payer_balance = driver.balance(&payer);
+        let proposer_balance = driver.balance(&proposer);
+        let wrapper_hash = wrapper.header_hash();
+        let inner_hash = wrapper.raw_header_hash();
+
+        assert!(driver.submit(&wrapper).code.is_ok());
+        assert_eq!(driver.mempool(), [wrapper.to_bytes()]);
+        assert!(driver.queued().is_empty());
+
+        // The wrapper is included, pays its fee and is queued
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert_eq!(outcome.proposal, [wrapper.to_bytes()]);
+        assert_eq!(
+            outcome.tx_events(),
+            [(
+                "accepted".to_string(),
+                wrapper_hash.to_string(),
+                ResultCode::Ok
+            )]
+        );
+        assert!(driver.mempool().is_empty());
+        assert_eq!(driver.queued(), [wrapper_hash]);
+        assert_eq!(
+            driver.balance(&payer),
+            payer_balance.checked_sub(fee).unwrap()
+        );
+        assert_eq!(
+            driver.balance(&proposer),
+            proposer_balance.checked_add(fee).unwrap()
+        );
+        let replay_protected =
+            |driver: &BlockDriver, hash: &namada::types::hash::Hash| {
+                driver
+                    .shell
+                    .wl_storage
+                    .has_replay_protection_entry(hash)
+                    .unwrap()
+            };
+        assert!(replay_protected(&driver, &wrapper_hash));
+        assert!(!replay_protected(&driver, &inner_hash));
+
+        // The inner tx is applied, without any other fee
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert_eq!(outcome.proposal.len(), 1);
+        assert_eq!(
+            outcome.tx_events(),
+            [(
+                "applied".to_string(),
+                inner_hash.to_string(),
+                ResultCode::Ok
+            )]
+        );
+        assert!(driver.queued().is_empty());
+        assert_eq!(
+            driver.balance(&payer),
+            payer_balance.checked_sub(fee).unwrap()
+        );
+        assert_eq!(
+            driver.balance(&proposer),
+            proposer_balance.checked_add(fee).unwrap()
+        );
+        assert!(!replay_protected(&driver, &wrapper_hash));
+        assert!(replay_protected(&driver, &inner_hash));
+    }
+
+    /// Check that a proposal marking a queued tx as undecryptable is rejected
+    /// without changing anything, and that the honest proposal that follows
+    /// applies the tx.
+    #[test]
+    fn test_driven_undecryptable_tx() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let mut driver = BlockDriver::new(shell);
+        let wrapper = no_op_wrapper(&driver.shell, None);
+        let inner_hash = wrapper.raw_header_hash();
+
+        assert!(driver.submit(&wrapper).code.is_ok());
+        assert!(driver.next_block().accepted);
+        assert_eq!(driver.queued(), [wrapper.header_hash()]);
+        let height = driver.shell.wl_storage.storage.get_last_block_height();
+
+        // DKG is disabled, so no tx is actually undecryptable
+        let outcome = driver.next_block_with(|proposal| {
+            let mut tx = Tx::try_from(proposal[0].as_ref()).unwrap();
+            tx.update_header(TxType::Decrypted(DecryptedTx::Undecryptable));
+            proposal[0] = tx.to_bytes().into();
+        });
+        assert!(!outcome.accepted);
+        assert_eq!(outcome.results[0].code, u32::from(ResultCode::InvalidTx));
+        assert!(outcome.events.is_empty());
+        assert_eq!(driver.queued(), [wrapper.header_hash()]);
+        assert_eq!(
+            driver.shell.wl_storage.storage.get_last_block_height(),
+            height
+        );
+
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert_eq!(
+            outcome.tx_events(),
+            [(
+                "applied".to_string(),
+                inner_hash.to_string(),
+                ResultCode::Ok
+            )]
+        );
+        assert!(driver.queued().is_empty());
+    }
+
+    /// Check that a wrapper tx expiring before the next block is left out of
+    /// the proposal, then dropped from the mempool, and that a proposal
+    /// including it anyway is rejected.
+    #[test]
+    fn test_driven_expired_wrapper() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let mut driver = BlockDriver::new(shell);
+        // Commit a block, whose time the expiration is checked against by
+        // the mempool
+        assert!(driver.next_block().accepted);
+        let expiration = driver.time() - Duration::milliseconds(500);
+        let wrapper = no_op_wrapper(&driver.shell, Some(expiration));
+        let payer = wrapper.header().wrapper().unwrap().fee_payer();
+        let payer_balance = driver.balance(&payer);
+
+        assert!(driver.submit(&wrapper).code.is_ok());
+        assert_eq!(driver.mempool(), [wrapper.to_bytes()]);
+
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert!(outcome.proposal.is_empty());
+        assert!(outcome.tx_events().is_empty());
+        assert!(driver.mempool().is_empty());
+        assert!(driver.queued().is_empty());
+        assert_eq!(driver.balance(&payer), payer_balance);
+
+        let outcome = driver.next_block_with(|proposal| {
+            proposal.push(wrapper.to_bytes().into());
+        });
+        assert!(!outcome.accepted);
+        assert_eq!(outcome.results[0].code, u32::from(ResultCode::ExpiredTx));
+        assert!(outcome.events.is_empty());
+        assert!(driver.queued().is_empty());
+        assert_eq!(driver.balance(&payer), payer_balance);
+        assert_eq!(driver.submit(&wrapper).code, ResultCode::ExpiredTx.into());
+    }
+
+    /// Check that a wrapper tx can't be replayed, neither while its inner tx
+    /// is queued nor once it was applied, be it through the mempool or
+    /// directly in a proposal.
+    #[test]
+    fn test_driven_replayed_wrapper() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let mut driver = BlockDriver::new(shell);
+        let wrapper = no_op_wrapper(&driver.shell, None);
+        let payer = wrapper.header().wrapper().unwrap().fee_payer();
+
+        assert!(driver.submit(&wrapper).code.is_ok());
+        assert!(driver.next_block().accepted);
+        assert_eq!(driver.submit(&wrapper).code, ResultCode::ReplayTx.into());
+        assert!(driver.mempool().is_empty());
+        assert_eq!(driver.queued(), [wrapper.header_hash()]);
+
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert_eq!(outcome.proposal.len(), 1);
+        assert!(driver.queued().is_empty());
+        let payer_balance = driver.balance(&payer);
+
+        assert_eq!(driver.submit(&wrapper).code, ResultCode::ReplayTx.into());
+        assert!(driver.mempool().is_empty());
+        let outcome = driver.next_block_with(|proposal| {
+            proposal.push(wrapper.to_bytes().into());
+        });
+        assert!(!outcome.accepted);
+        assert_eq!(outcome.results[0].code, u32::from(ResultCode::ReplayTx));
+        assert!(outcome.events.is_empty());
+        assert!(driver.queued().is_empty());
+        assert_eq!(driver.balance(&payer), payer_balance);
+    }
+
+    /// Check that the fee charged to each wrapper tx is reported in a fee
+    /// event and recorded at the block of the wrapper, and that the records
+    /// of the epoch are served by the fees query.
+    #[test]
+    fn test_driven_fee_records() {
+        use namada::core::ledger::fee_records::{BlockFees, EpochFees};
+        use namada::ledger::events::EventType;
+        use namada::ledger::queries::RPC;
+
+        let (shell, _recv, _, _) = test_utils::setup();
+        let mut driver = BlockDriver::new(shell);
+        let proposer =
+            driver.shell.mode.get_validator_address().unwrap().clone();
+        let token = driver.shell.wl_storage.storage.native_token.clone();
+        let epoch = driver.shell.wl_storage.storage.get_current_epoch().0;
+        let first = no_op_wrapper(&driver.shell, None);
+        let second = no_op_wrapper(
+            &driver.shell,
+            Some(driver.time() + Duration::hours(1)),
+        );
+        let payer = first.header().wrapper().unwrap().fee_payer();
+        let fee = first.header().wrapper().unwrap().get_tx_fee().unwrap();
+        let fee_events = |events: &[Event]| -> Vec<(String, String)> {
+            events
+                .iter()
+                .filter(|event| event.event_type == EventType::Fee)
+                .map(|event| {
+                    assert_eq!(event["payer"], payer.to_string());
+                    assert_eq!(event["token"], token.to_string());
+                    (event["hash"].clone(), event["amount"].clone())
+                })
+                .collect()
+        };
+
+        // The first wrapper pays its fee
+        assert!(driver.submit(&first).code.is_ok());
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert_eq!(
+            fee_events(&outcome.events),
+            [(
+                first.header_hash().to_string(),
+                fee.raw_amount().to_string()
+            )]
+        );
+        let first_height =
+            driver.shell.wl_storage.storage.get_last_block_height();
+
+        // The inner tx of the first wrapper doesn't pay any other fee, while
+        // the second wrapper pays its own
+        assert!(driver.submit(&second).code.is_ok());
+        let outcome = driver.next_block();
+        assert!(outcome.accepted);
+        assert_eq!(outcome.proposal.len(), 2);
+        assert_eq!(
+            fee_events(&outcome.events),
+            [(
+                second.header_hash().to_string(),
+                fee.raw_amount().to_string()
+            )]
+        );
+        let second_height =
+            driver.shell.wl_storage.storage.get_last_block_height();
+
+        let block_fees = BlockFees {
+            proposer,
+            fees: [(token.clone(), fee)].into_iter().collect(),
+        };
+        let expected = EpochFees {
+            totals: [(token.clone(), fee.checked_add(fee).unwrap())]
+                .into_iter()
+                .collect(),
+            blocks: [
+                (first_height, block_fees.clone()),
+                (second_height, block_fees),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let recorded =
+            tokio_test::block_on(RPC.shell().epoch_fees(&driver.shell, &epoch))
+                .unwrap();
+        assert_eq!(recorded, expected);
     }
 }
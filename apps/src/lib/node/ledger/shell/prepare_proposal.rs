@@ -1,19 +1,23 @@
 //! Implementation of the [`RequestPrepareProposal`] ABCI++ method for the Shell
 
+use std::sync::Arc;
+
+use borsh_ext::BorshSerializeExt;
 use namada::core::hints;
 use namada::core::ledger::gas::TxGasMeter;
 use namada::ledger::pos::PosQueries;
 use namada::ledger::protocol::get_fee_unshielding_transaction;
 use namada::ledger::storage::{DBIter, StorageHasher, TempWlStorage, DB};
 use namada::proof_of_stake::find_validator_by_raw_hash;
-use namada::proto::Tx;
 use namada::types::address::Address;
 use namada::types::internal::TxInQueue;
 use namada::types::key::tm_raw_hash_to_string;
 use namada::types::time::DateTimeUtc;
+use namada::types::transaction::protocol::{sign_protocol_tx, ProtocolTxType};
 use namada::types::transaction::{DecryptedTx, TxType};
 use namada::vm::wasm::{TxCache, VpCache};
 use namada::vm::WasmCacheAccess;
+use rayon::prelude::*;
 
 use super::super::*;
 use super::block_alloc::states::{
@@ -21,11 +25,44 @@ use super::block_alloc::states::{
     EncryptedTxBatchAllocator, NextState, TryAlloc,
 };
 use super::block_alloc::{AllocFailure, BlockAllocator, BlockResources};
+use super::decode_cache::{DecodedTx, TxDecodeCache};
+use crate::facade::tendermint::abci::types::Misbehavior;
 use crate::facade::tendermint_proto::google::protobuf::Timestamp;
 use crate::facade::tendermint_proto::v0_37::abci::RequestPrepareProposal;
 use crate::node::ledger::shell::ShellMode;
 use crate::node::ledger::shims::abcipp_shim_types::shim::{response, TxBytes};
 
+/// Validity checks on a wrapper tx that don't depend on the state, so that
+/// the txs of the mempool can be checked in parallel. Returns the decoded
+/// wrapper.
+fn check_wrapper_bytes(
+    decode_cache: &TxDecodeCache,
+    tx_bytes: &[u8],
+    block_time: Option<&DateTimeUtc>,
+) -> Result<Arc<DecodedTx>, ()> {
+    let decoded = decode_cache.decode(tx_bytes);
+    let tx = decoded.tx().map_err(|_| ())?;
+
+    // If tx doesn't have an expiration it is valid. If time cannot be
+    // retrieved from block default to last block datetime which has
+    // already been checked by mempool_validate, so it's valid
+    if let (Some(block_time), Some(exp)) = (block_time, &tx.header().expiration)
+    {
+        if block_time > exp {
+            return Err(());
+        }
+    }
+
+    decoded.validate().map_err(|_| ())?;
+    let TxType::Wrapper(wrapper) = tx.header().tx_type else {
+        return Err(());
+    };
+    // Check tx gas limit for tx size
+    let mut tx_gas_meter = TxGasMeter::new(wrapper.gas_limit);
+    tx_gas_meter.add_wrapper_gas(tx_bytes).map_err(|_| ())?;
+    Ok(decoded)
+}
+
 impl<D, H> Shell<D, H>
 where
     D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
@@ -45,7 +82,16 @@ where
     ) -> response::PrepareProposal {
         let txs = if let ShellMode::Validator { .. } = self.mode {
             // start counting allotted space for txs
-            let alloc = self.get_encrypted_txs_allocator();
+            let mut alloc = self.get_encrypted_txs_allocator();
+
+            // the evidence of the block is recorded by slash txs, whose
+            // space is reserved ahead of the mempool txs
+            let misbehaviors: Vec<Misbehavior> = req
+                .misbehavior
+                .into_iter()
+                .filter_map(|evidence| evidence.try_into().ok())
+                .collect();
+            let mut slash_txs = self.build_slash_txs(&mut alloc, &misbehaviors);
 
             // add encrypted txs
             let tm_raw_hash_string =
@@ -70,7 +116,8 @@ where
             let (mut decrypted_txs, alloc) = self.build_decrypted_txs(alloc);
             txs.append(&mut decrypted_txs);
 
-            // add vote extension protocol txs
+            // add slash and vote extension protocol txs
+            txs.append(&mut slash_txs);
             let mut protocol_txs = self.build_protocol_txs(alloc, &req.txs);
             txs.append(&mut protocol_txs);
 
@@ -123,6 +170,45 @@ where
         }
     }
 
+    /// Builds a slash protocol tx for each of the given misbehaviors of
+    /// validators, signed by the protocol key of this validator. The space of
+    /// these txs is reserved ahead of every other tx of the block.
+    fn build_slash_txs(
+        &self,
+        alloc: &mut EncryptedTxBatchAllocator,
+        misbehaviors: &[Misbehavior],
+    ) -> Vec<TxBytes> {
+        let Some(protocol_key) = self.mode.get_protocol_key() else {
+            return vec![];
+        };
+        self.evidence_to_slash(misbehaviors)
+            .into_iter()
+            .map(|evidence| {
+                sign_protocol_tx(
+                    ProtocolTxType::Slash,
+                    evidence.serialize_to_vec(),
+                    protocol_key,
+                    self.chain_id.clone(),
+                )
+                .to_bytes()
+                .into()
+            })
+            .filter(|tx_bytes: &TxBytes| {
+                alloc.try_reserve(tx_bytes).map_or_else(
+                    |status| {
+                        tracing::warn!(
+                            ?tx_bytes,
+                            ?status,
+                            "Dropping slash tx from the current proposal",
+                        );
+                        false
+                    },
+                    |()| true,
+                )
+            })
+            .collect()
+    }
+
     /// Builds a batch of encrypted transactions, retrieved from
     /// Tendermint's mempool.
     fn build_encrypted_txs(
@@ -141,17 +227,29 @@ where
         let mut temp_wl_storage = TempWlStorage::new(&self.wl_storage.storage);
         let mut vp_wasm_cache = self.vp_wasm_cache.clone();
         let mut tx_wasm_cache = self.tx_wasm_cache.clone();
+        // The wrappers of this block make up the decryption queue of the next
+        // one, whose total length is bounded
+        let max_queue_bytes = self.wl_storage.storage.tx_queue.max_bytes();
+        let mut queue_bytes = 0;
+        let checked = self.check_wrappers_bytes(txs, block_time.as_ref());
 
         let txs = txs
             .iter()
-            .filter_map(|tx_bytes| {
-                match self.validate_wrapper_bytes(tx_bytes, block_time, &mut temp_wl_storage, &mut vp_wasm_cache, &mut tx_wasm_cache, block_proposer) {
-                    Ok(gas) => {
+            .zip(checked)
+            .filter_map(|(tx_bytes, decoded)| {
+                let validated = match &decoded {
+                    Some(decoded) => self.validate_wrapper_state(decoded, &mut temp_wl_storage, &mut vp_wasm_cache, &mut tx_wasm_cache, block_proposer),
+                    None => Err(()),
+                };
+                match validated {
+                    Ok((gas, len)) if queue_bytes + len <= max_queue_bytes => {
+                        queue_bytes += len;
                         temp_wl_storage.write_log.commit_tx();
                         Some((tx_bytes.to_owned(), gas))
                     },
-                    Err(()) => {
+                    _ => {
                         temp_wl_storage.write_log.drop_tx();
+                        self.debug_log.count_excluded_wrapper();
                         None
                     }
                 }
@@ -193,57 +291,57 @@ where
         (txs, alloc)
     }
 
-    /// Validity checks on a wrapper tx
-    #[allow(clippy::too_many_arguments)]
-    fn validate_wrapper_bytes<CA>(
+    /// Run [`check_wrapper_bytes`] on the given txs, in parallel if the
+    /// shell has a pool of threads for it. Returns the decoded wrappers that
+    /// passed the checks, in the order of the given txs.
+    pub fn check_wrappers_bytes(
+        &self,
+        txs: &[TxBytes],
+        block_time: Option<&DateTimeUtc>,
+    ) -> Vec<Option<Arc<DecodedTx>>> {
+        let decode_cache = &self.tx_decode_cache;
+        let check = |tx_bytes: &TxBytes| {
+            check_wrapper_bytes(decode_cache, tx_bytes, block_time).ok()
+        };
+        match &self.proposal_pool {
+            Some(pool) => pool.install(|| txs.par_iter().map(check).collect()),
+            None => txs.iter().map(check).collect(),
+        }
+    }
+
+    /// Validity checks on a wrapper tx that depend on the state, once it
+    /// passed [`check_wrapper_bytes`]. Returns the gas limit of the wrapper
+    /// and its length once queued for decryption.
+    fn validate_wrapper_state<CA>(
         &self,
-        tx_bytes: &[u8],
-        block_time: Option<DateTimeUtc>,
+        decoded: &DecodedTx,
         temp_wl_storage: &mut TempWlStorage<D, H>,
         vp_wasm_cache: &mut VpCache<CA>,
         tx_wasm_cache: &mut TxCache<CA>,
         block_proposer: &Address,
-    ) -> Result<u64, ()>
+    ) -> Result<(u64, usize), ()>
     where
         CA: 'static + WasmCacheAccess + Sync,
     {
-        let tx = Tx::try_from(tx_bytes).map_err(|_| ())?;
-
-        // If tx doesn't have an expiration it is valid. If time cannot be
-        // retrieved from block default to last block datetime which has
-        // already been checked by mempool_validate, so it's valid
-        if let (Some(block_time), Some(exp)) =
-            (block_time.as_ref(), &tx.header().expiration)
-        {
-            if block_time > exp {
-                return Err(());
-            }
-        }
-
-        tx.validate_tx().map_err(|_| ())?;
-        if let TxType::Wrapper(wrapper) = tx.header().tx_type {
-            // Check tx gas limit for tx size
-            let mut tx_gas_meter = TxGasMeter::new(wrapper.gas_limit);
-            tx_gas_meter.add_wrapper_gas(tx_bytes).map_err(|_| ())?;
-
-            self.replay_protection_checks(&tx, temp_wl_storage)
-                .map_err(|_| ())?;
-
-            // Check fees
-            match self.wrapper_fee_check(
-                &wrapper,
-                get_fee_unshielding_transaction(&tx, &wrapper),
-                temp_wl_storage,
-                vp_wasm_cache,
-                tx_wasm_cache,
-                Some(block_proposer),
-                true,
-            ) {
-                Ok(()) => Ok(u64::from(wrapper.gas_limit)),
-                Err(_) => Err(()),
-            }
-        } else {
-            Err(())
+        let tx = decoded.tx().map_err(|_| ())?;
+        let TxType::Wrapper(wrapper) = tx.header().tx_type else {
+            return Err(());
+        };
+        self.replay_protection_checks(tx, temp_wl_storage)
+            .map_err(|_| ())?;
+
+        // Check fees
+        match self.wrapper_fee_check(
+            &wrapper,
+            get_fee_unshielding_transaction(tx, &wrapper),
+            temp_wl_storage,
+            vp_wasm_cache,
+            tx_wasm_cache,
+            Some(block_proposer),
+            true,
+        ) {
+            Ok(()) => Ok((u64::from(wrapper.gas_limit), tx.encoded_len())),
+            Err(_) => Err(()),
         }
     }
 
@@ -265,16 +363,11 @@ where
             .storage
             .tx_queue
             .iter()
-            .map(
-                |TxInQueue {
-                     tx,
-                     gas: _,
-                }| {
-                    let mut tx = tx.clone();
-                    tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
-                    tx.to_bytes().into()
-                },
-            )
+            .map(|TxInQueue { tx, .. }| {
+                let mut tx = tx.clone();
+                tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+                tx.to_bytes().into()
+            })
             // TODO: make sure all decrypted txs are accepted
             .take_while(|tx_bytes: &TxBytes| {
                 alloc.try_alloc(&tx_bytes[..]).map_or_else(
@@ -387,7 +480,7 @@ mod test_prepare_proposal {
         consensus_validator_set_handle,
         read_consensus_validator_set_addresses_with_stake, Epoch,
     };
-    use namada::proto::{Code, Data, Header, Section, Signature, Signed};
+    use namada::proto::{Section, Signature, Signed, Tx};
     use namada::types::address::{self, Address};
     use namada::types::ethereum_events::EthereumEvent;
     use namada::types::key::RefTo;
@@ -397,11 +490,13 @@ mod test_prepare_proposal {
     use namada::types::transaction::protocol::EthereumTxData;
     use namada::types::transaction::{Fee, TxType, WrapperTx};
     use namada::types::vote_extensions::ethereum_events;
+    use namada_test_utils::fixtures::TestFixtures;
+    use proptest::prelude::*;
 
     use super::*;
     use crate::config::ValidatorLocalConfig;
     use crate::node::ledger::shell::test_utils::{
-        self, gen_keypair, get_pkh_from_address, TestShell,
+        self, get_pkh_from_address, TestShell,
     };
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::FinalizeBlock;
     use crate::wallet;
@@ -444,23 +539,26 @@ mod test_prepare_proposal {
     #[test]
     fn test_error_in_processing_tx() {
         let (shell, _recv, _, _) = test_utils::setup();
-        let keypair = gen_keypair();
+        let mut fixtures = TestFixtures::new(0);
+        let keypair = fixtures.ed25519_keypair();
         // an unsigned wrapper will cause an error in processing
-        let mut wrapper =
-            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
-                Fee {
-                    amount_per_gas_unit: Default::default(),
-                    token: shell.wl_storage.storage.native_token.clone(),
-                },
-                keypair.ref_to(),
-                Epoch(0),
-                Default::default(),
-                None,
-            ))));
-        wrapper.header.chain_id = shell.chain_id.clone();
-        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper.set_data(Data::new("transaction_data".as_bytes().to_owned()));
-        let wrapper = wrapper.to_bytes();
+        let wrapper = fixtures
+            .tx(
+                TxType::Wrapper(Box::new(WrapperTx::new(
+                    Fee {
+                        amount_per_gas_unit: Default::default(),
+                        token: shell.wl_storage.storage.native_token.clone(),
+                    },
+                    keypair.ref_to(),
+                    Epoch(0),
+                    Default::default(),
+                    None,
+                ))),
+                shell.chain_id.clone(),
+                "wasm_code",
+                "transaction_data",
+            )
+            .to_bytes();
         #[allow(clippy::redundant_clone)]
         let req = RequestPrepareProposal {
             txs: vec![wrapper.clone().into()],
@@ -722,7 +820,8 @@ mod test_prepare_proposal {
     #[test]
     fn test_decrypted_txs_in_correct_order() {
         let (mut shell, _recv, _, _) = test_utils::setup();
-        let keypair = gen_keypair();
+        let mut fixtures = TestFixtures::new(0);
+        let keypair = fixtures.ed25519_keypair();
         let mut expected_wrapper = vec![];
         let mut expected_decrypted = vec![];
 
@@ -744,66 +843,47 @@ mod test_prepare_proposal {
         // create a request with two new wrappers from mempool and
         // two wrappers from the previous block to be decrypted
         for i in 0..2 {
-            let mut tx =
-                Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
-                    Fee {
-                        amount_per_gas_unit: 1.into(),
-                        token: shell.wl_storage.storage.native_token.clone(),
-                    },
-                    keypair.ref_to(),
-                    Epoch(0),
-                    GAS_LIMIT_MULTIPLIER.into(),
-                    None,
-                ))));
-            tx.header.chain_id = shell.chain_id.clone();
-            tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-            tx.set_data(Data::new(
-                format!("transaction data: {}", i).as_bytes().to_owned(),
-            ));
-            tx.add_section(Section::Signature(Signature::new(
-                tx.sechashes(),
-                [(0, keypair.clone())].into_iter().collect(),
-                None,
-            )));
+            let (wrapper, decrypted) = fixtures.wrapper_and_decrypted_txs(
+                shell.chain_id.clone(),
+                Fee {
+                    amount_per_gas_unit: 1.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                GAS_LIMIT_MULTIPLIER.into(),
+                "wasm_code",
+                format!("transaction data: {}", i),
+            );
 
             let gas = Gas::from(
-                tx.header().wrapper().expect("Wrong tx type").gas_limit,
+                wrapper.header().wrapper().expect("Wrong tx type").gas_limit,
             )
-            .checked_sub(Gas::from(tx.to_bytes().len() as u64))
+            .checked_sub(Gas::from(wrapper.to_bytes().len() as u64))
             .unwrap();
-            shell.enqueue_tx(tx.clone(), gas);
-            expected_wrapper.push(tx.clone());
-            req.txs.push(tx.to_bytes().into());
-            tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
-            expected_decrypted.push(tx.clone());
+            shell.enqueue_tx(wrapper.clone(), gas);
+            req.txs.push(wrapper.to_bytes().into());
+            expected_wrapper.push(wrapper);
+            expected_decrypted.push(decrypted);
         }
-        // we extract the inner data from the txs for testing
-        // equality since otherwise changes in timestamps would
-        // fail the test
-        let expected_txs: Vec<Header> = expected_wrapper
-            .into_iter()
-            .chain(expected_decrypted.into_iter())
-            .map(|tx| tx.header)
+        // the fixture txs are deterministic, so that the proposed txs can be
+        // compared by their exact hashes
+        let expected_hashes: Vec<_> = expected_wrapper
+            .iter()
+            .chain(expected_decrypted.iter())
+            .map(|tx| tx.header_hash())
             .collect();
-        let received: Vec<Header> = shell
+        let received_hashes: Vec<_> = shell
             .prepare_proposal(req)
             .txs
             .into_iter()
             .map(|tx_bytes| {
-                Tx::try_from(tx_bytes.as_ref()).expect("Test failed").header
+                Tx::try_from(tx_bytes.as_ref())
+                    .expect("Test failed")
+                    .header_hash()
             })
             .collect();
         // check that the order of the txs is correct
-        assert_eq!(
-            received
-                .iter()
-                .map(|x| x.serialize_to_vec())
-                .collect::<Vec<_>>(),
-            expected_txs
-                .iter()
-                .map(|x| x.serialize_to_vec())
-                .collect::<Vec<_>>(),
-        );
+        assert_eq!(received_hashes, expected_hashes);
     }
 
     /// Test that if the unsigned wrapper tx hash is known (replay attack), the
@@ -812,26 +892,19 @@ mod test_prepare_proposal {
     fn test_wrapper_tx_hash() {
         let (mut shell, _recv, _, _) = test_utils::setup();
 
+        let mut fixtures = TestFixtures::new(0);
         let keypair = crate::wallet::defaults::daewon_keypair();
-        let mut wrapper =
-            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
-                Fee {
-                    amount_per_gas_unit: 0.into(),
-                    token: shell.wl_storage.storage.native_token.clone(),
-                },
-                keypair.ref_to(),
-                Epoch(0),
-                Default::default(),
-                None,
-            ))));
-        wrapper.header.chain_id = shell.chain_id.clone();
-        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper.add_section(Section::Signature(Signature::new(
-            wrapper.sechashes(),
-            [(0, keypair)].into_iter().collect(),
-            None,
-        )));
+        let wrapper = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
+            Fee {
+                amount_per_gas_unit: 0.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            Default::default(),
+            "wasm_code",
+            "transaction data",
+        );
 
         // Write wrapper hash to storage
         let wrapper_unsigned_hash = wrapper.header_hash();
@@ -859,26 +932,19 @@ mod test_prepare_proposal {
     fn test_wrapper_tx_hash_same_block() {
         let (shell, _recv, _, _) = test_utils::setup();
 
+        let mut fixtures = TestFixtures::new(0);
         let keypair = crate::wallet::defaults::daewon_keypair();
-        let mut wrapper =
-            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
-                Fee {
-                    amount_per_gas_unit: 1.into(),
-                    token: shell.wl_storage.storage.native_token.clone(),
-                },
-                keypair.ref_to(),
-                Epoch(0),
-                GAS_LIMIT_MULTIPLIER.into(),
-                None,
-            ))));
-        wrapper.header.chain_id = shell.chain_id.clone();
-        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper.add_section(Section::Signature(Signature::new(
-            wrapper.sechashes(),
-            [(0, keypair)].into_iter().collect(),
-            None,
-        )));
+        let wrapper = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
+            Fee {
+                amount_per_gas_unit: 1.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            GAS_LIMIT_MULTIPLIER.into(),
+            "wasm_code",
+            "transaction data",
+        );
 
         let req = RequestPrepareProposal {
             txs: vec![wrapper.to_bytes().into(); 2],
@@ -894,26 +960,19 @@ mod test_prepare_proposal {
     fn test_inner_tx_hash() {
         let (mut shell, _recv, _, _) = test_utils::setup();
 
+        let mut fixtures = TestFixtures::new(0);
         let keypair = crate::wallet::defaults::daewon_keypair();
-        let mut wrapper =
-            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
-                Fee {
-                    amount_per_gas_unit: Amount::zero(),
-                    token: shell.wl_storage.storage.native_token.clone(),
-                },
-                keypair.ref_to(),
-                Epoch(0),
-                Default::default(),
-                None,
-            ))));
-        wrapper.header.chain_id = shell.chain_id.clone();
-        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper.add_section(Section::Signature(Signature::new(
-            wrapper.sechashes(),
-            [(0, keypair)].into_iter().collect(),
-            None,
-        )));
+        let wrapper = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
+            Fee {
+                amount_per_gas_unit: Amount::zero(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            Default::default(),
+            "wasm_code",
+            "transaction data",
+        );
         let inner_unsigned_hash = wrapper.raw_header_hash();
 
         // Write inner hash to storage
@@ -941,10 +1000,11 @@ mod test_prepare_proposal {
     fn test_inner_tx_hash_same_block() {
         let (shell, _recv, _, _) = test_utils::setup();
 
+        let mut fixtures = TestFixtures::new(0);
         let keypair = crate::wallet::defaults::daewon_keypair();
         let keypair_2 = crate::wallet::defaults::albert_keypair();
-        let mut wrapper =
-            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+        let mut wrapper = fixtures.tx(
+            TxType::Wrapper(Box::new(WrapperTx::new(
                 Fee {
                     amount_per_gas_unit: 1.into(),
                     token: shell.wl_storage.storage.native_token.clone(),
@@ -953,12 +1013,11 @@ mod test_prepare_proposal {
                 Epoch(0),
                 GAS_LIMIT_MULTIPLIER.into(),
                 None,
-            ))));
-        wrapper.header.chain_id = shell.chain_id.clone();
-        let tx_code = Code::new("wasm_code".as_bytes().to_owned(), None);
-        wrapper.set_code(tx_code);
-        let tx_data = Data::new("transaction data".as_bytes().to_owned());
-        wrapper.set_data(tx_data);
+            ))),
+            shell.chain_id.clone(),
+            "wasm_code",
+            "transaction data",
+        );
         let mut new_wrapper = wrapper.clone();
         wrapper.add_section(Section::Signature(Signature::new(
             wrapper.sechashes(),
@@ -994,9 +1053,10 @@ mod test_prepare_proposal {
     #[test]
     fn test_expired_wrapper_tx() {
         let (shell, _recv, _, _) = test_utils::setup();
-        let keypair = gen_keypair();
-        let mut wrapper_tx =
-            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let keypair = fixtures.ed25519_keypair();
+        let mut wrapper_tx = fixtures.tx(
+            TxType::Wrapper(Box::new(WrapperTx::new(
                 Fee {
                     amount_per_gas_unit: 1.into(),
                     token: shell.wl_storage.storage.native_token.clone(),
@@ -1005,12 +1065,12 @@ mod test_prepare_proposal {
                 Epoch(0),
                 Default::default(),
                 None,
-            ))));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
+            ))),
+            shell.chain_id.clone(),
+            "wasm_code",
+            "transaction data",
+        );
         wrapper_tx.header.expiration = Some(DateTimeUtc::default());
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
         wrapper_tx.add_section(Section::Signature(Signature::new(
             wrapper_tx.sechashes(),
             [(0, keypair)].into_iter().collect(),
@@ -1043,28 +1103,20 @@ mod test_prepare_proposal {
         let block_gas_limit =
             namada::core::ledger::gas::get_max_block_gas(&shell.wl_storage)
                 .unwrap();
-        let keypair = gen_keypair();
+        let mut fixtures = TestFixtures::new(0);
+        let keypair = fixtures.ed25519_keypair();
 
-        let wrapper = WrapperTx::new(
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 100.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
-            keypair.ref_to(),
-            Epoch(0),
+            &keypair,
             (block_gas_limit + 1).into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, keypair)].into_iter().collect(),
-            None,
-        )));
 
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
@@ -1077,35 +1129,82 @@ mod test_prepare_proposal {
         assert!(result.txs.is_empty());
     }
 
+    /// Check that the wrappers that would make the decryption queue of the
+    /// next block exceed its ceiling are not included in the block
+    #[test]
+    fn test_tx_queue_max_bytes() {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        let mut fixtures = TestFixtures::new(0);
+        let keypair = fixtures.ed25519_keypair();
+
+        // Load some tokens to tx signer to pay fees
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&keypair.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1_000).serialize_to_vec())
+            .unwrap();
+
+        let wrappers: Vec<Tx> = (0..3)
+            .map(|i| {
+                fixtures.wrapper_tx(
+                    shell.chain_id.clone(),
+                    Fee {
+                        amount_per_gas_unit: 1.into(),
+                        token: shell.wl_storage.storage.native_token.clone(),
+                    },
+                    &keypair,
+                    GAS_LIMIT_MULTIPLIER.into(),
+                    "wasm_code",
+                    format!("transaction data: {}", i),
+                )
+            })
+            .collect();
+        // Only leave room for two of the wrappers
+        let max_bytes = wrappers[..2].iter().map(Tx::encoded_len).sum();
+        shell.wl_storage.storage.tx_queue.set_max_bytes(max_bytes);
+
+        let req = RequestPrepareProposal {
+            txs: wrappers.iter().map(|tx| tx.to_bytes().into()).collect(),
+            ..Default::default()
+        };
+        let received_hashes: Vec<_> = shell
+            .prepare_proposal(req)
+            .txs
+            .into_iter()
+            .map(|tx_bytes| {
+                Tx::try_from(tx_bytes.as_ref())
+                    .expect("Test failed")
+                    .header_hash()
+            })
+            .collect();
+        let expected_hashes: Vec<_> =
+            wrappers[..2].iter().map(Tx::header_hash).collect();
+        assert_eq!(received_hashes, expected_hashes);
+    }
+
     // Check that a wrapper requiring more gas than its limit is not included in
     // the block
     #[test]
     fn test_exceeding_gas_limit_wrapper() {
         let (shell, _recv, _, _) = test_utils::setup();
-        let keypair = gen_keypair();
-
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let keypair = fixtures.ed25519_keypair();
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 100.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
-            keypair.ref_to(),
-            Epoch(0),
+            &keypair,
             0.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
 
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, keypair)].into_iter().collect(),
-            None,
-        )));
-
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
             max_tx_bytes: 0,
@@ -1133,30 +1232,19 @@ mod test_prepare_proposal {
             });
         }
 
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 100.into(),
                 token: address::btc(),
             },
-            crate::wallet::defaults::albert_keypair().ref_to(),
-            Epoch(0),
+            &crate::wallet::defaults::albert_keypair(),
             GAS_LIMIT_MULTIPLIER.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
 
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, crate::wallet::defaults::albert_keypair())]
-                .into_iter()
-                .collect(),
-            None,
-        )));
-
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
             max_tx_bytes: 0,
@@ -1174,30 +1262,19 @@ mod test_prepare_proposal {
     fn test_fee_non_whitelisted_token() {
         let (shell, _recv, _, _) = test_utils::setup();
 
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 100.into(),
                 token: address::apfel(),
             },
-            crate::wallet::defaults::albert_keypair().ref_to(),
-            Epoch(0),
+            &crate::wallet::defaults::albert_keypair(),
             GAS_LIMIT_MULTIPLIER.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
 
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, crate::wallet::defaults::albert_keypair())]
-                .into_iter()
-                .collect(),
-            None,
-        )));
-
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
             max_tx_bytes: 0,
@@ -1225,28 +1302,18 @@ mod test_prepare_proposal {
             });
         }
 
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 10.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
-            crate::wallet::defaults::albert_keypair().ref_to(),
-            Epoch(0),
+            &crate::wallet::defaults::albert_keypair(),
             GAS_LIMIT_MULTIPLIER.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, crate::wallet::defaults::albert_keypair())]
-                .into_iter()
-                .collect(),
-            None,
-        )));
 
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
@@ -1265,28 +1332,18 @@ mod test_prepare_proposal {
     fn test_fee_wrong_minimum_amount() {
         let (shell, _recv, _, _) = test_utils::setup();
 
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 0.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
-            crate::wallet::defaults::albert_keypair().ref_to(),
-            Epoch(0),
+            &crate::wallet::defaults::albert_keypair(),
             GAS_LIMIT_MULTIPLIER.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, crate::wallet::defaults::albert_keypair())]
-                .into_iter()
-                .collect(),
-            None,
-        )));
 
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
@@ -1304,28 +1361,18 @@ mod test_prepare_proposal {
     fn test_insufficient_balance_for_fee() {
         let (shell, _recv, _, _) = test_utils::setup();
 
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: 1_000_000_000.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
-            crate::wallet::defaults::albert_keypair().ref_to(),
-            Epoch(0),
+            &crate::wallet::defaults::albert_keypair(),
             GAS_LIMIT_MULTIPLIER.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, crate::wallet::defaults::albert_keypair())]
-                .into_iter()
-                .collect(),
-            None,
-        )));
 
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
@@ -1343,28 +1390,18 @@ mod test_prepare_proposal {
     fn test_wrapper_fee_overflow() {
         let (shell, _recv, _, _) = test_utils::setup();
 
-        let wrapper = WrapperTx::new(
+        let mut fixtures = TestFixtures::new(0);
+        let wrapper_tx = fixtures.wrapper_tx(
+            shell.chain_id.clone(),
             Fee {
                 amount_per_gas_unit: token::Amount::max(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
-            crate::wallet::defaults::albert_keypair().ref_to(),
-            Epoch(0),
+            &crate::wallet::defaults::albert_keypair(),
             GAS_LIMIT_MULTIPLIER.into(),
-            None,
+            "wasm_code",
+            "transaction data",
         );
-        let mut wrapper_tx = Tx::from_type(TxType::Wrapper(Box::new(wrapper)));
-        wrapper_tx.header.chain_id = shell.chain_id.clone();
-        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
-        wrapper_tx
-            .set_data(Data::new("transaction data".as_bytes().to_owned()));
-        wrapper_tx.add_section(Section::Signature(Signature::new(
-            wrapper_tx.sechashes(),
-            [(0, crate::wallet::defaults::albert_keypair())]
-                .into_iter()
-                .collect(),
-            None,
-        )));
 
         let req = RequestPrepareProposal {
             txs: vec![wrapper_tx.to_bytes().into()],
@@ -1376,4 +1413,72 @@ mod test_prepare_proposal {
         eprintln!("Proposal: {:?}", result.txs);
         assert!(result.txs.is_empty());
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        /// Check that the txs proposed out of a mempool are the same whether
+        /// they are checked serially or in parallel.
+        #[test]
+        fn test_parallel_wrapper_checks(
+            kinds in prop::collection::vec(0..4u8, 0..32)
+        ) {
+            proptest_parallel_wrapper_checks(kinds)
+        }
+    }
+
+    /// Propose a mempool of valid wrappers, wrappers whose fees can't be paid,
+    /// replayed txs and garbage, as given by `kinds`, checking its txs
+    /// serially and then in parallel
+    fn proptest_parallel_wrapper_checks(kinds: Vec<u8>) {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        let mut fixtures = TestFixtures::new(0);
+        let funded = fixtures.ed25519_keypair();
+        let unfunded = fixtures.ed25519_keypair();
+
+        // Load some tokens to the funded signer to pay fees
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&funded.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1_000).serialize_to_vec())
+            .unwrap();
+
+        let mut txs: Vec<TxBytes> = vec![];
+        for (i, kind) in kinds.into_iter().enumerate() {
+            let fee = Fee {
+                amount_per_gas_unit: 1.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            };
+            let tx_bytes = match (kind, txs.last()) {
+                (0 | 1, _) => fixtures
+                    .wrapper_tx(
+                        shell.chain_id.clone(),
+                        fee,
+                        if kind == 0 { &funded } else { &unfunded },
+                        GAS_LIMIT_MULTIPLIER.into(),
+                        "wasm_code",
+                        format!("transaction data: {}", i),
+                    )
+                    .to_bytes()
+                    .into(),
+                (2, Some(last)) => last.clone(),
+                _ => vec![0xff; 32].into(),
+            };
+            txs.push(tx_bytes);
+        }
+        let req = RequestPrepareProposal {
+            txs,
+            ..Default::default()
+        };
+
+        let serial = shell.prepare_proposal(req.clone()).txs;
+        shell.set_prepare_proposal_threads(Some(4));
+        shell.tx_decode_cache = TxDecodeCache::default();
+        let parallel = shell.prepare_proposal(req).txs;
+        assert_eq!(serial, parallel);
+    }
 }
@@ -9,6 +9,7 @@ use namada::ledger::protocol::get_fee_unshielding_transaction;
 use namada::ledger::storage::TempWlStorage;
 use namada::ledger::storage_api::tx::validate_tx_bytes;
 use namada::proof_of_stake::find_validator_by_raw_hash;
+use namada::proto::TxError;
 use namada::types::internal::TxInQueue;
 use namada::types::transaction::protocol::{
     ethereum_tx_data_variants, ProtocolTxType,
@@ -16,6 +17,7 @@ use namada::types::transaction::protocol::{
 use namada_sdk::eth_bridge::{EthBridgeQueries, SendValsetUpd};
 
 use super::block_alloc::{BlockSpace, EncryptedTxsBins};
+use super::decode_cache::TxDecodeCache;
 use super::*;
 use crate::facade::tendermint_proto::v0_37::abci::RequestProcessProposal;
 use crate::node::ledger::shell::block_alloc::{AllocFailure, TxBin};
@@ -39,6 +41,12 @@ pub struct ValidationMeta {
     pub decrypted_queue_has_remaining_txs: bool,
     /// Check if a block has decrypted txs.
     pub has_decrypted_txs: bool,
+    /// The total encoded length of the wrapper txs, which make up the
+    /// decryption queue of the next block.
+    pub tx_queue_bytes: usize,
+    /// The evidence delivered along with the proposal, which its slash
+    /// protocol txs must correspond to.
+    pub evidence: BTreeSet<SlashEvidence>,
 }
 
 impl<D, H> From<&WlStorage<D, H>> for ValidationMeta
@@ -59,6 +67,8 @@ where
             has_decrypted_txs: false,
             encrypted_txs_bins: encrypted_txs_bin,
             txs_bin,
+            tx_queue_bytes: 0,
+            evidence: BTreeSet::new(),
         }
     }
 }
@@ -102,10 +112,17 @@ where
                 )
         };
 
+        let misbehaviors: Vec<Misbehavior> = req
+            .misbehavior
+            .iter()
+            .cloned()
+            .filter_map(|evidence| evidence.try_into().ok())
+            .collect();
         let (tx_results, meta) = self.process_txs(
             &req.txs,
             self.get_block_timestamp(req.time),
             &native_block_proposer_address,
+            &misbehaviors,
         );
 
         // Erroneous transactions were detected when processing
@@ -113,7 +130,7 @@ where
         // deserialize properly, that have invalid signatures
         // and that have invalid wasm code to reach FinalizeBlock.
         let invalid_txs = tx_results.iter().any(|res| {
-            let error = ErrorCodes::from_u32(res.code).expect(
+            let error = ResultCode::from_u32(res.code).expect(
                 "All error codes returned from process_single_tx are valid",
             );
             !error.is_recoverable()
@@ -139,7 +156,21 @@ where
             );
         }
 
+        for (tx_bytes, result) in req.txs.iter().zip(&tx_results) {
+            if result.code != u32::from(ResultCode::Ok) {
+                self.debug_log.record(
+                    RejectionSource::ProcessProposal,
+                    tx_bytes,
+                    result.code,
+                    &result.info,
+                );
+            }
+        }
+
         let will_reject_proposal = invalid_txs || has_remaining_decrypted_txs;
+        if will_reject_proposal {
+            self.debug_log.count_rejected_proposal();
+        }
         (
             if will_reject_proposal {
                 ProcessProposal::Reject
@@ -162,18 +193,24 @@ where
         txs: &[TxBytes],
         block_time: DateTimeUtc,
         block_proposer: &Address,
+        misbehaviors: &[Misbehavior],
     ) -> (Vec<TxResult>, ValidationMeta) {
         let mut tx_queue_iter = self.wl_storage.storage.tx_queue.iter();
         let mut temp_wl_storage = TempWlStorage::new(&self.wl_storage.storage);
         let mut metadata = ValidationMeta::from(&self.wl_storage);
+        metadata.evidence = self.evidence_to_slash(misbehaviors);
         let mut vp_wasm_cache = self.vp_wasm_cache.clone();
         let mut tx_wasm_cache = self.tx_wasm_cache.clone();
+        let verified_wrapper_sigs =
+            batch_verify_wrapper_sigs(&self.tx_decode_cache, txs);
 
         let tx_results: Vec<_> = txs
             .iter()
-            .map(|tx_bytes| {
+            .zip(verified_wrapper_sigs)
+            .map(|(tx_bytes, wrapper_sig_verified)| {
                 let result = self.check_proposal_tx(
                     tx_bytes,
+                    wrapper_sig_verified,
                     &mut tx_queue_iter,
                     &mut metadata,
                     &mut temp_wl_storage,
@@ -182,8 +219,8 @@ where
                     &mut tx_wasm_cache,
                     block_proposer,
                 );
-                let error_code = ErrorCodes::from_u32(result.code).unwrap();
-                if let ErrorCodes::Ok = error_code {
+                let error_code = ResultCode::from_u32(result.code).unwrap();
+                if let ResultCode::Ok = error_code {
                     temp_wl_storage.write_log.commit_tx();
                 } else {
                     tracing::info!(
@@ -217,12 +254,12 @@ where
     {
         if vote_extensions.all(|maybe_ext| maybe_ext.is_some()) {
             TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "Process proposal accepted this transaction".into(),
             }
         } else {
             TxResult {
-                code: ErrorCodes::InvalidVoteExtension.into(),
+                code: ResultCode::InvalidVoteExtension.into(),
                 info: "Process proposal rejected this proposal because at \
                        least one of the vote extensions included was invalid."
                     .into(),
@@ -232,6 +269,8 @@ where
 
     /// Checks if the Tx can be deserialized from bytes. Checks the fees and
     /// signatures of the fee payer for a transaction if it is a wrapper tx.
+    /// The latter are skipped if `wrapper_sig_verified` is set, as they were
+    /// already verified together with those of the rest of the proposal.
     ///
     /// Checks validity of a decrypted tx or that a tx marked un-decryptable
     /// is in fact so. Also checks that decrypted txs were submitted in
@@ -256,6 +295,7 @@ where
     pub fn check_proposal_tx<'a, CA>(
         &self,
         tx_bytes: &[u8],
+        wrapper_sig_verified: bool,
         tx_queue_iter: &mut impl Iterator<Item = &'a TxInQueue>,
         metadata: &mut ValidationMeta,
         temp_wl_storage: &mut TempWlStorage<D, H>,
@@ -275,7 +315,7 @@ where
             .expect("Failed to get max tx bytes param from storage")
         {
             return TxResult {
-                code: ErrorCodes::TooLarge.into(),
+                code: ResultCode::TooLarge.into(),
                 info: "Tx too large".into(),
             };
         }
@@ -283,7 +323,7 @@ where
         // try to allocate space for this tx
         if let Err(e) = metadata.txs_bin.try_dump(tx_bytes) {
             return TxResult {
-                code: ErrorCodes::AllocationError.into(),
+                code: ResultCode::AllocationError.into(),
                 info: match e {
                     AllocFailure::Rejected { .. } => {
                         "No more space left in the block"
@@ -297,7 +337,8 @@ where
             };
         }
 
-        let maybe_tx = Tx::try_from(tx_bytes).map_or_else(
+        let decoded = self.tx_decode_cache.decode(tx_bytes);
+        let maybe_tx = decoded.tx().map_or_else(
             |err| {
                 tracing::debug!(
                     ?err,
@@ -305,7 +346,7 @@ where
                      PrepareProposal"
                 );
                 Err(TxResult {
-                    code: ErrorCodes::InvalidTx.into(),
+                    code: ResultCode::InvalidTx.into(),
                     info: "The submitted transaction was not deserializable"
                         .into(),
                 })
@@ -313,14 +354,30 @@ where
             |tx| {
                 let tx_chain_id = tx.header.chain_id.clone();
                 let tx_expiration = tx.header.expiration;
-                if let Err(err) = tx.validate_tx() {
-                    // This occurs if the wrapper / protocol tx signature is
-                    // invalid
+                // Checked even if the wrapper signature was verified in a
+                // batch, without the rest of the validation
+                if let Err(err) = tx.header.check_timestamps() {
+                    return Err(TxResult {
+                        code: ResultCode::InvalidTx.into(),
+                        info: TxError::InvalidTimestamp(err).to_string(),
+                    });
+                }
+                if let Err(err) = tx.check_sections() {
                     return Err(TxResult {
-                        code: ErrorCodes::InvalidSig.into(),
+                        code: ResultCode::InvalidTx.into(),
                         info: err.to_string(),
                     });
                 }
+                if !wrapper_sig_verified {
+                    if let Err(err) = decoded.validate() {
+                        // This occurs if the wrapper / protocol tx signature
+                        // is invalid
+                        return Err(TxResult {
+                            code: ResultCode::InvalidSig.into(),
+                            info: err.to_string(),
+                        });
+                    }
+                }
                 Ok((tx_chain_id, tx_expiration, tx))
             },
         );
@@ -329,16 +386,18 @@ where
             Err(tx_result) => return tx_result,
         };
 
-        if let Err(err) = tx.validate_tx() {
-            return TxResult {
-                code: ErrorCodes::InvalidSig.into(),
-                info: err.to_string(),
-            };
+        if !wrapper_sig_verified {
+            if let Err(err) = decoded.validate() {
+                return TxResult {
+                    code: ResultCode::InvalidSig.into(),
+                    info: err.to_string(),
+                };
+            }
         }
         match tx.header().tx_type {
             // If it is a raw transaction, we do no further validation
             TxType::Raw => TxResult {
-                code: ErrorCodes::InvalidTx.into(),
+                code: ResultCode::InvalidTx.into(),
                 info: "Transaction rejected: Non-encrypted transactions are \
                        not supported"
                     .into(),
@@ -347,7 +406,7 @@ where
                 // Tx chain id
                 if tx_chain_id != self.chain_id {
                     return TxResult {
-                        code: ErrorCodes::InvalidChainId.into(),
+                        code: ResultCode::InvalidChainId.into(),
                         info: format!(
                             "Tx carries a wrong chain id: expected {}, found \
                              {}",
@@ -360,7 +419,7 @@ where
                 if let Some(exp) = tx_expiration {
                     if block_time > exp {
                         return TxResult {
-                            code: ErrorCodes::ExpiredTx.into(),
+                            code: ResultCode::ExpiredTx.into(),
                             info: format!(
                                 "Tx expired at {:#?}, block time: {:#?}",
                                 exp, block_time
@@ -370,7 +429,7 @@ where
                 }
                 match protocol_tx.tx {
                     ProtocolTxType::EthEventsVext => {
-                        ethereum_tx_data_variants::EthEventsVext::try_from(&tx)
+                        ethereum_tx_data_variants::EthEventsVext::try_from(tx)
                             .map_err(|err| err.to_string())
                             .and_then(|ext| {
                                 self.validate_eth_events_vext_and_get_it_back(
@@ -380,7 +439,7 @@ where
                                         .get_last_block_height(),
                                 )
                                 .map(|_| TxResult {
-                                    code: ErrorCodes::Ok.into(),
+                                    code: ResultCode::Ok.into(),
                                     info: "Process Proposal accepted this \
                                            transaction"
                                         .into(),
@@ -388,7 +447,7 @@ where
                                 .map_err(|err| err.to_string())
                             })
                             .unwrap_or_else(|err| TxResult {
-                                code: ErrorCodes::InvalidVoteExtension.into(),
+                                code: ResultCode::InvalidVoteExtension.into(),
                                 info: format!(
                                     "Process proposal rejected this proposal \
                                      because one of the included Ethereum \
@@ -397,7 +456,7 @@ where
                             })
                     }
                     ProtocolTxType::BridgePoolVext => {
-                        ethereum_tx_data_variants::BridgePoolVext::try_from(&tx)
+                        ethereum_tx_data_variants::BridgePoolVext::try_from(tx)
                             .map_err(|err| err.to_string())
                             .and_then(|ext| {
                                 self.validate_bp_roots_vext_and_get_it_back(
@@ -407,7 +466,7 @@ where
                                         .get_last_block_height(),
                                 )
                                 .map(|_| TxResult {
-                                    code: ErrorCodes::Ok.into(),
+                                    code: ResultCode::Ok.into(),
                                     info: "Process Proposal accepted this \
                                            transaction"
                                         .into(),
@@ -415,7 +474,7 @@ where
                                 .map_err(|err| err.to_string())
                             })
                             .unwrap_or_else(|err| TxResult {
-                                code: ErrorCodes::InvalidVoteExtension.into(),
+                                code: ResultCode::InvalidVoteExtension.into(),
                                 info: format!(
                                     "Process proposal rejected this proposal \
                                      because one of the included Bridge pool \
@@ -425,7 +484,7 @@ where
                     }
                     ProtocolTxType::ValSetUpdateVext => {
                         ethereum_tx_data_variants::ValSetUpdateVext::try_from(
-                            &tx,
+                            tx,
                         )
                         .map_err(|err| err.to_string())
                         .and_then(|ext| {
@@ -439,7 +498,7 @@ where
                                 self.wl_storage.storage.get_current_epoch().0,
                             )
                             .map(|_| TxResult {
-                                code: ErrorCodes::Ok.into(),
+                                code: ResultCode::Ok.into(),
                                 info: "Process Proposal accepted this \
                                        transaction"
                                     .into(),
@@ -448,7 +507,7 @@ where
                         })
                         .unwrap_or_else(|err| {
                             TxResult {
-                                code: ErrorCodes::InvalidVoteExtension.into(),
+                                code: ResultCode::InvalidVoteExtension.into(),
                                 info: format!(
                                     "Process proposal rejected this proposal \
                                      because one of the included validator \
@@ -461,7 +520,7 @@ where
                     ProtocolTxType::EthereumEvents => {
                         let digest =
                             ethereum_tx_data_variants::EthereumEvents::try_from(
-                                &tx,
+                                tx,
                             )
                             .unwrap();
                         let extensions = digest.decompress(
@@ -477,10 +536,8 @@ where
                     }
                     ProtocolTxType::BridgePool => {
                         let digest =
-                            ethereum_tx_data_variants::BridgePool::try_from(
-                                &tx,
-                            )
-                            .unwrap();
+                            ethereum_tx_data_variants::BridgePool::try_from(tx)
+                                .unwrap();
                         let valid_extensions = self
                             .validate_bp_roots_vext_list(digest)
                             .map(|maybe_ext| {
@@ -488,10 +545,40 @@ where
                             });
                         self.validate_vexts_in_proposal(valid_extensions)
                     }
+                    ProtocolTxType::Slash => {
+                        let evidence = tx.data().and_then(|data| {
+                            SlashEvidence::try_from_slice(&data).ok()
+                        });
+                        match evidence {
+                            Some(evidence)
+                                if metadata.evidence.contains(&evidence) =>
+                            {
+                                TxResult {
+                                    code: ResultCode::Ok.into(),
+                                    info: "Process Proposal accepted this \
+                                           transaction"
+                                        .into(),
+                                }
+                            }
+                            Some(_) => TxResult {
+                                code: ResultCode::InvalidTx.into(),
+                                info: "Process proposal rejected a slash tx \
+                                       that doesn't correspond to any \
+                                       evidence of the block"
+                                    .into(),
+                            },
+                            None => TxResult {
+                                code: ResultCode::InvalidTx.into(),
+                                info: "Process proposal rejected a slash tx \
+                                       whose evidence couldn't be decoded"
+                                    .into(),
+                            },
+                        }
+                    }
                     ProtocolTxType::ValidatorSetUpdate => {
                         let digest =
                             ethereum_tx_data_variants::ValidatorSetUpdate::try_from(
-                                &tx,
+                                tx,
                             )
                             .unwrap();
                         if !self
@@ -500,7 +587,7 @@ where
                             .must_send_valset_upd(SendValsetUpd::AtPrevHeight)
                         {
                             return TxResult {
-                                code: ErrorCodes::InvalidVoteExtension.into(),
+                                code: ResultCode::InvalidVoteExtension.into(),
                                 info: "Process proposal rejected a validator \
                                        set update vote extension issued at an \
                                        invalid block height"
@@ -528,7 +615,7 @@ where
                         if wrapper.tx.raw_header_hash() != tx.raw_header_hash()
                         {
                             TxResult {
-                                code: ErrorCodes::InvalidOrder.into(),
+                                code: ResultCode::InvalidOrder.into(),
                                 info: "Process proposal rejected a decrypted \
                                        transaction that violated the tx order \
                                        determined in the previous block"
@@ -540,14 +627,14 @@ where
                         ) {
                             // DKG is disabled, txs are not actually encrypted
                             TxResult {
-                                code: ErrorCodes::InvalidTx.into(),
+                                code: ResultCode::InvalidTx.into(),
                                 info: "The encrypted payload of tx was \
                                        incorrectly marked as un-decryptable"
                                     .into(),
                             }
                         } else {
                             TxResult {
-                                code: ErrorCodes::Ok.into(),
+                                code: ResultCode::Ok.into(),
                                 info: "Process Proposal accepted this \
                                        tranasction"
                                     .into(),
@@ -555,7 +642,7 @@ where
                         }
                     }
                     None => TxResult {
-                        code: ErrorCodes::ExtraTxs.into(),
+                        code: ResultCode::ExtraTxs.into(),
                         info: "Received more decrypted txs than expected"
                             .into(),
                     },
@@ -576,7 +663,7 @@ where
                         .try_dump(tx_bytes, u64::from(wrapper.gas_limit));
 
                     return TxResult {
-                        code: ErrorCodes::TxGasLimit.into(),
+                        code: ResultCode::TxGasLimit.into(),
                         info: "Wrapper transactions exceeds its gas limit"
                             .to_string(),
                     };
@@ -588,14 +675,26 @@ where
                     .try_dump(tx_bytes, u64::from(wrapper.gas_limit))
                 {
                     return TxResult {
-                        code: ErrorCodes::AllocationError.into(),
+                        code: ResultCode::AllocationError.into(),
                         info: e,
                     };
                 }
+                // the wrapper txs must fit in the decryption queue
+                metadata.tx_queue_bytes += tx.encoded_len();
+                if metadata.tx_queue_bytes
+                    > self.wl_storage.storage.tx_queue.max_bytes()
+                {
+                    return TxResult {
+                        code: ResultCode::AllocationError.into(),
+                        info: "Wrapper txs exceed the capacity of the \
+                               decryption queue"
+                            .into(),
+                    };
+                }
                 // decrypted txs shouldn't show up before wrapper txs
                 if metadata.has_decrypted_txs {
                     return TxResult {
-                        code: ErrorCodes::InvalidTx.into(),
+                        code: ResultCode::InvalidTx.into(),
                         info: "Decrypted txs should not be proposed before \
                                wrapper txs"
                             .into(),
@@ -603,7 +702,7 @@ where
                 }
                 if hints::unlikely(self.encrypted_txs_not_allowed()) {
                     return TxResult {
-                        code: ErrorCodes::AllocationError.into(),
+                        code: ResultCode::AllocationError.into(),
                         info: "Wrapper txs not allowed at the current block \
                                height"
                             .into(),
@@ -613,7 +712,7 @@ where
                 // ChainId check
                 if tx_chain_id != self.chain_id {
                     return TxResult {
-                        code: ErrorCodes::InvalidChainId.into(),
+                        code: ResultCode::InvalidChainId.into(),
                         info: format!(
                             "Tx carries a wrong chain id: expected {}, found \
                              {}",
@@ -626,7 +725,7 @@ where
                 if let Some(exp) = tx_expiration {
                     if block_time > exp {
                         return TxResult {
-                            code: ErrorCodes::ExpiredTx.into(),
+                            code: ResultCode::ExpiredTx.into(),
                             info: format!(
                                 "Tx expired at {:#?}, block time: {:#?}",
                                 exp, block_time
@@ -637,10 +736,10 @@ where
 
                 // Replay protection checks
                 if let Err(e) =
-                    self.replay_protection_checks(&tx, temp_wl_storage)
+                    self.replay_protection_checks(tx, temp_wl_storage)
                 {
                     return TxResult {
-                        code: ErrorCodes::ReplayTx.into(),
+                        code: ResultCode::ReplayTx.into(),
                         info: e.to_string(),
                     };
                 }
@@ -648,7 +747,7 @@ where
                 // Check that the fee payer has sufficient balance.
                 match self.wrapper_fee_check(
                     &wrapper,
-                    get_fee_unshielding_transaction(&tx, &wrapper),
+                    get_fee_unshielding_transaction(tx, &wrapper),
                     temp_wl_storage,
                     vp_wasm_cache,
                     tx_wasm_cache,
@@ -656,12 +755,12 @@ where
                     false,
                 ) {
                     Ok(()) => TxResult {
-                        code: ErrorCodes::Ok.into(),
+                        code: ResultCode::Ok.into(),
                         info: "Process proposal accepted this transaction"
                             .into(),
                     },
                     Err(e) => TxResult {
-                        code: ErrorCodes::FeeError.into(),
+                        code: ResultCode::FeeError.into(),
                         info: e.to_string(),
                     },
                 }
@@ -686,6 +785,58 @@ where
     }
 }
 
+/// Verify the wrapper signatures of all the given txs in a single batch.
+/// Returns, for each tx, whether its wrapper signature is known to be valid.
+/// Txs that aren't wrappers or whose wrapper signature is invalid are left to
+/// be validated one at a time, so that they are rejected as usual. Txs whose
+/// validation verdict is already cached, or whose signatures were verified in
+/// the last blocks, are not verified again.
+fn batch_verify_wrapper_sigs(
+    decode_cache: &TxDecodeCache,
+    txs: &[TxBytes],
+) -> Vec<bool> {
+    let verified_sigs = decode_cache.verified_sigs();
+    let mut verified = vec![false; txs.len()];
+    let mut items = vec![];
+    // The position in `txs` of the tx each item belongs to
+    let mut owners = vec![];
+    // The number of txs whose signatures are in the batch
+    let mut batched = 0;
+    for (position, tx_bytes) in txs.iter().enumerate() {
+        let decoded = decode_cache.decode_shared(tx_bytes);
+        if let Some(verdict) = decoded.known_verdict() {
+            verified[position] = verdict.is_ok();
+            continue;
+        }
+        let Some(tx_items) =
+            decoded.tx().ok().and_then(|tx| tx.wrapper_signatures())
+        else {
+            continue;
+        };
+        verified[position] = true;
+        if verified_sigs.contains_all(&tx_items) {
+            continue;
+        }
+        owners.extend(std::iter::repeat(position).take(tx_items.len()));
+        items.extend(tx_items);
+        batched += 1;
+    }
+    if let Err(err) = verified_sigs.verify_batch(batched, &items) {
+        for offender in err.offenders() {
+            verified[owners[offender]] = false;
+        }
+    }
+    // Only the signatures of the txs without any offender are valid
+    verified_sigs.insert(
+        items
+            .iter()
+            .zip(&owners)
+            .filter(|(_, owner)| verified[**owner])
+            .map(|(item, _)| item),
+    );
+    verified
+}
+
 /// We test the failure cases of [`process_proposal`]. The happy flows
 /// are covered by the e2e tests.
 #[cfg(test)]
@@ -693,12 +844,13 @@ mod test_process_proposal {
     use namada::ledger::replay_protection;
     use namada::ledger::storage_api::StorageWrite;
     use namada::proto::{
-        Code, Data, Section, SignableEthMessage, Signature, Signed,
+        Ciphertext, Code, Data, Section, SigFailure, SignableEthMessage,
+        Signature, Signed,
     };
     use namada::types::ethereum_events::EthereumEvent;
     use namada::types::key::*;
     use namada::types::storage::Epoch;
-    use namada::types::time::DateTimeUtc;
+    use namada::types::time::{DateTimeUtc, MIN_UTC};
     use namada::types::token;
     use namada::types::token::Amount;
     use namada::types::transaction::protocol::EthereumTxData;
@@ -742,7 +894,7 @@ mod test_process_proposal {
             .expect("Test failed")
             .try_into()
             .expect("Test failed");
-        assert_eq!(resp.result.code, u32::from(ErrorCodes::Ok));
+        assert_eq!(resp.result.code, u32::from(ResultCode::Ok));
         deactivate_bridge(&mut shell);
         let response = if let Err(TestError::RejectProposal(resp)) =
             shell.process_proposal(request)
@@ -757,7 +909,7 @@ mod test_process_proposal {
         };
         assert_eq!(
             response.result.code,
-            u32::from(ErrorCodes::InvalidVoteExtension)
+            u32::from(ResultCode::InvalidVoteExtension)
         );
     }
 
@@ -794,7 +946,7 @@ mod test_process_proposal {
             .try_into()
             .expect("Test failed");
 
-        assert_eq!(resp.result.code, u32::from(ErrorCodes::Ok));
+        assert_eq!(resp.result.code, u32::from(ResultCode::Ok));
         deactivate_bridge(&mut shell);
         let response = if let Err(TestError::RejectProposal(resp)) =
             shell.process_proposal(request)
@@ -809,7 +961,7 @@ mod test_process_proposal {
         };
         assert_eq!(
             response.result.code,
-            u32::from(ErrorCodes::InvalidVoteExtension)
+            u32::from(ResultCode::InvalidVoteExtension)
         );
     }
 
@@ -835,7 +987,7 @@ mod test_process_proposal {
         };
         assert_eq!(
             response.result.code,
-            u32::from(ErrorCodes::InvalidVoteExtension)
+            u32::from(ResultCode::InvalidVoteExtension)
         );
     }
 
@@ -966,14 +1118,12 @@ mod test_process_proposal {
 
         println!("{}", response.result.info);
 
-        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidSig));
-        assert_eq!(
-            response.result.info,
-            String::from(
-                "WrapperTx signature verification failed: The wrapper \
-                 signature is invalid."
-            )
-        );
+        assert_eq!(response.result.code, u32::from(ResultCode::InvalidSig));
+        assert!(response.result.info.starts_with(
+            "Missing signature. WrapperTx signature verification failed: The \
+             wrapper signature is invalid: No signature section signs over \
+             the target"
+        ));
     }
 
     /// Test that a block including a wrapper tx with invalid signature is
@@ -1023,10 +1173,11 @@ mod test_process_proposal {
                 };
                 let expected_error = "WrapperTx signature verification \
                                       failed: The wrapper signature is \
-                                      invalid.";
+                                      invalid: No signature section signs \
+                                      over the target";
                 assert_eq!(
                     response.result.code,
-                    u32::from(ErrorCodes::InvalidSig)
+                    u32::from(ResultCode::InvalidSig)
                 );
                 assert!(
                     response.result.info.contains(expected_error),
@@ -1038,6 +1189,177 @@ mod test_process_proposal {
         }
     }
 
+    /// Test that the ways the signature of a wrapper can fail verification are
+    /// told apart in the info of the rejection of a proposal
+    #[test]
+    fn test_wrapper_signature_failures_told_apart() {
+        let (shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+
+        for failure in [
+            SigFailure::NoSignatureForTarget,
+            SigFailure::SignerMismatch,
+            SigFailure::InvalidSignature,
+        ] {
+            let (wrapper, info) =
+                test_utils::wrapper_failing_sig(&shell, failure);
+            let request = ProcessProposal {
+                txs: vec![wrapper.to_bytes()],
+            };
+            let Err(TestError::RejectProposal(response)) =
+                shell.process_proposal(request)
+            else {
+                panic!("Test failed")
+            };
+            assert_eq!(
+                response[0].result.code,
+                u32::from(ResultCode::InvalidSig)
+            );
+            assert!(
+                response[0].result.info.starts_with(info),
+                "Unexpected info {} for {failure:?}",
+                response[0].result.info
+            );
+        }
+    }
+
+    /// Test that batch verification of the wrapper signatures of a proposal
+    /// only leaves out the wrapper whose signature is invalid, and the txs
+    /// that are not wrappers
+    #[test]
+    fn test_batch_verify_wrapper_sigs() {
+        let (shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+        let mut txs: Vec<TxBytes> = (0..4)
+            .map(|i| {
+                let keypair = gen_keypair();
+                let mut outer_tx =
+                    Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                        Fee {
+                            amount_per_gas_unit: Amount::from_uint(100, 0)
+                                .expect("Test failed"),
+                            token: shell
+                                .wl_storage
+                                .storage
+                                .native_token
+                                .clone(),
+                        },
+                        keypair.ref_to(),
+                        Epoch(0),
+                        GAS_LIMIT_MULTIPLIER.into(),
+                        None,
+                    ))));
+                outer_tx.header.chain_id = shell.chain_id.clone();
+                outer_tx.set_code(Code::new(
+                    "wasm_code".as_bytes().to_owned(),
+                    None,
+                ));
+                outer_tx.set_data(Data::new(vec![i]));
+                outer_tx.add_section(Section::Signature(Signature::new(
+                    outer_tx.sechashes(),
+                    [(0, keypair)].into_iter().collect(),
+                    None,
+                )));
+                if i == 2 {
+                    if let TxType::Wrapper(wrapper) =
+                        &mut outer_tx.header.tx_type
+                    {
+                        wrapper.fee.amount_per_gas_unit = Default::default();
+                    }
+                }
+                outer_tx.to_bytes().into()
+            })
+            .collect();
+        let mut raw_tx = Tx::from_type(TxType::Raw);
+        raw_tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        txs.push(raw_tx.to_bytes().into());
+
+        assert_eq!(
+            batch_verify_wrapper_sigs(&shell.tx_decode_cache, &txs),
+            vec![true, true, false, true, false]
+        );
+    }
+
+    /// Test that processing a proposal gives the same results with and without
+    /// the tx decode cache, whether its txs are cached yet or not, including
+    /// txs that can't be decoded or validated
+    #[test]
+    fn test_decode_cache_is_transparent() {
+        let (mut shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+        let keypair = gen_keypair();
+        let mut txs: Vec<Vec<u8>> = (0..2)
+            .map(|i| {
+                let mut wrapper =
+                    Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                        Fee {
+                            amount_per_gas_unit: Default::default(),
+                            token: shell
+                                .wl_storage
+                                .storage
+                                .native_token
+                                .clone(),
+                        },
+                        keypair.ref_to(),
+                        Epoch(0),
+                        GAS_LIMIT_MULTIPLIER.into(),
+                        None,
+                    ))));
+                wrapper.header.chain_id = shell.chain_id.clone();
+                wrapper.set_code(Code::new(
+                    "wasm_code".as_bytes().to_owned(),
+                    None,
+                ));
+                wrapper.set_data(Data::new(vec![i]));
+                let signer = if i == 0 {
+                    keypair.clone()
+                } else {
+                    gen_keypair()
+                };
+                wrapper.add_section(Section::Signature(Signature::new(
+                    wrapper.sechashes(),
+                    [(0, signer)].into_iter().collect(),
+                    None,
+                )));
+                wrapper.to_bytes()
+            })
+            .collect();
+        let mut raw_tx = Tx::from_type(TxType::Raw);
+        raw_tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        txs.push(raw_tx.to_bytes());
+        txs.push(vec![0xff; 32]);
+
+        let mut process = |cache: TxDecodeCache| {
+            shell.tx_decode_cache = cache;
+            (0..2)
+                .map(|_| {
+                    let results = match shell
+                        .process_proposal(ProcessProposal { txs: txs.clone() })
+                    {
+                        Ok(results) => results,
+                        Err(TestError::RejectProposal(results)) => results,
+                    };
+                    results
+                        .into_iter()
+                        .map(|processed| processed.result)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let cached = process(TxDecodeCache::default());
+        let uncached = process(TxDecodeCache::disabled());
+        assert_eq!(cached[0], cached[1]);
+        assert_eq!(cached, uncached);
+        assert_eq!(
+            cached[0][1..]
+                .iter()
+                .map(|result| ResultCode::from_u32(result.code).unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                ResultCode::InvalidSig,
+                ResultCode::InvalidTx,
+                ResultCode::InvalidTx,
+            ]
+        );
+    }
+
     /// Test that if the account submitting the tx is not known and the fee is
     /// non-zero, [`process_proposal`] rejects that block
     #[test]
@@ -1091,7 +1413,7 @@ mod test_process_proposal {
                 panic!("Test failed")
             }
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::FeeError));
+        assert_eq!(response.result.code, u32::from(ResultCode::FeeError));
         assert_eq!(
             response.result.info,
             String::from(
@@ -1157,7 +1479,7 @@ mod test_process_proposal {
                 panic!("Test failed")
             }
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::FeeError));
+        assert_eq!(response.result.code, u32::from(ResultCode::FeeError));
         assert_eq!(
             response.result.info,
             String::from(
@@ -1220,7 +1542,7 @@ mod test_process_proposal {
                 panic!("Test failed")
             }
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidOrder));
+        assert_eq!(response.result.code, u32::from(ResultCode::InvalidOrder));
         assert_eq!(
             response.result.info,
             String::from(
@@ -1273,7 +1595,7 @@ mod test_process_proposal {
                 panic!("Test failed")
             }
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(response.result.code, u32::from(ResultCode::InvalidTx));
         assert_eq!(
             response.result.info,
             String::from(
@@ -1316,6 +1638,156 @@ mod test_process_proposal {
         shell.process_proposal(request).expect_err("Test failed");
     }
 
+    /// Test that a block containing a decrypted tx that still carries a
+    /// ciphertext section is rejected by [`process_proposal`], naming the
+    /// offending section
+    #[test]
+    fn test_decrypted_tx_with_ciphertext() {
+        let (mut shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+        let keypair = gen_keypair();
+
+        let mut tx = Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: Default::default(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            keypair.ref_to(),
+            Epoch(0),
+            GAS_LIMIT_MULTIPLIER.into(),
+            None,
+        ))));
+        tx.header.chain_id = shell.chain_id.clone();
+        tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        tx.add_section(Section::Ciphertext(Ciphertext {
+            opaque: "encrypted sections".as_bytes().to_owned().into(),
+        }));
+        let gas_limit = Gas::from(tx.header().wrapper().unwrap().gas_limit)
+            .checked_sub(Gas::from(tx.to_bytes().len() as u64))
+            .unwrap();
+        shell.enqueue_tx(tx.clone(), gas_limit);
+
+        tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+
+        let request = ProcessProposal {
+            txs: vec![tx.to_bytes()],
+        };
+        match shell.process_proposal(request) {
+            Ok(_) => panic!("Test failed"),
+            Err(TestError::RejectProposal(response)) => {
+                assert_eq!(
+                    response[0].result.code,
+                    u32::from(ResultCode::InvalidTx)
+                );
+                assert_eq!(
+                    response[0].result.info,
+                    "A decrypted tx must carry no ciphertext sections, but \
+                     carries 1"
+                );
+            }
+        }
+    }
+
+    /// Test that a block containing a wrapper that carries a ciphertext
+    /// section is rejected by [`process_proposal`]
+    #[test]
+    fn test_wrapper_with_ciphertext() {
+        let (shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+        let keypair = gen_keypair();
+
+        let mut tx = Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: Default::default(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            keypair.ref_to(),
+            Epoch(0),
+            GAS_LIMIT_MULTIPLIER.into(),
+            None,
+        ))));
+        tx.header.chain_id = shell.chain_id.clone();
+        tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        tx.add_section(Section::Ciphertext(Ciphertext {
+            opaque: "encrypted sections".as_bytes().to_owned().into(),
+        }));
+        tx.add_section(Section::Signature(Signature::new(
+            tx.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+
+        let request = ProcessProposal {
+            txs: vec![tx.to_bytes()],
+        };
+        match shell.process_proposal(request) {
+            Ok(_) => panic!("Test failed"),
+            Err(TestError::RejectProposal(response)) => {
+                assert_eq!(
+                    response[0].result.code,
+                    u32::from(ResultCode::InvalidTx)
+                );
+                assert_eq!(
+                    response[0].result.info,
+                    "A wrapper tx must carry no ciphertext sections, but \
+                     carries 1"
+                );
+            }
+        }
+    }
+
+    /// Test that a wrapper tx whose timestamp is out of range is rejected,
+    /// even if its signature was already verified
+    #[test]
+    fn test_wrapper_with_out_of_range_timestamp() {
+        let (shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+        let keypair = gen_keypair();
+
+        let mut tx = Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: Default::default(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            keypair.ref_to(),
+            Epoch(0),
+            GAS_LIMIT_MULTIPLIER.into(),
+            None,
+        ))));
+        tx.header.chain_id = shell.chain_id.clone();
+        tx.header.timestamp = MIN_UTC;
+        tx.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        tx.add_section(Section::Signature(Signature::new(
+            tx.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+        shell
+            .tx_decode_cache
+            .verified_sigs()
+            .insert(&tx.wrapper_signatures().expect("Test failed"));
+
+        let request = ProcessProposal {
+            txs: vec![tx.to_bytes()],
+        };
+        match shell.process_proposal(request) {
+            Ok(_) => panic!("Test failed"),
+            Err(TestError::RejectProposal(response)) => {
+                assert_eq!(
+                    response[0].result.code,
+                    u32::from(ResultCode::InvalidTx)
+                );
+                assert_eq!(
+                    response[0].result.info,
+                    TxError::InvalidTimestamp(
+                        tx.header.check_timestamps().unwrap_err()
+                    )
+                    .to_string()
+                );
+            }
+        }
+    }
+
     /// Test that if more decrypted txs are submitted to
     /// [`process_proposal`] than expected, they are rejected
     #[test]
@@ -1340,7 +1812,7 @@ mod test_process_proposal {
         } else {
             panic!("Test failed")
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::ExtraTxs));
+        assert_eq!(response.result.code, u32::from(ResultCode::ExtraTxs));
         assert_eq!(
             response.result.info,
             String::from("Received more decrypted txs than expected"),
@@ -1375,7 +1847,7 @@ mod test_process_proposal {
                 panic!("Test failed")
             }
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(response.result.code, u32::from(ResultCode::InvalidTx));
         assert_eq!(
             response.result.info,
             String::from(
@@ -1423,7 +1895,7 @@ mod test_process_proposal {
         shell
             .wl_storage
             .storage
-            .write_replay_protection_entry(&mut batch, &hash_key)
+            .write_replay_protection_entry(&mut batch, &hash_key, None)
             .expect("Test failed");
 
         // Run validation
@@ -1436,7 +1908,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -1495,10 +1967,10 @@ mod test_process_proposal {
         match shell.process_proposal(request) {
             Ok(_) => panic!("Test failed"),
             Err(TestError::RejectProposal(response)) => {
-                assert_eq!(response[0].result.code, u32::from(ErrorCodes::Ok));
+                assert_eq!(response[0].result.code, u32::from(ResultCode::Ok));
                 assert_eq!(
                     response[1].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 assert_eq!(
                     response[1].result.info,
@@ -1512,6 +1984,149 @@ mod test_process_proposal {
         }
     }
 
+    /// Test that a signed inner tx wrapped by the SDK is accepted
+    #[test]
+    fn test_sdk_wrapped_tx_accepted() {
+        use namada::types::account::AccountPublicKeysMap;
+        use namada_sdk::signer::{self, Signer, SoftwareSigner};
+
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        let author = SoftwareSigner::new(gen_keypair());
+        let fee_payer =
+            SoftwareSigner::new(crate::wallet::defaults::daewon_keypair());
+
+        // Add unshielded balance for fee payment
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&fee_payer.public_key()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1000).serialize_to_vec())
+            .unwrap();
+
+        let mut inner = Tx::from_type(TxType::Raw);
+        inner.header.chain_id = shell.chain_id.clone();
+        inner.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        inner.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        let pks_map = AccountPublicKeysMap::from_iter([author.public_key()]);
+        tokio_test::block_on(signer::sign_raw(
+            &mut inner,
+            &[&author],
+            &pks_map,
+            None,
+        ))
+        .expect("Test failed");
+        let wrapper = tokio_test::block_on(signer::wrap_tx(
+            inner,
+            Fee {
+                amount_per_gas_unit: 1.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            GAS_LIMIT_MULTIPLIER.into(),
+            Epoch(0),
+            &fee_payer,
+        ))
+        .expect("Test failed");
+
+        let request = ProcessProposal {
+            txs: vec![wrapper.to_bytes()],
+        };
+        let response = shell.process_proposal(request).expect("Test failed");
+        assert_eq!(response[0].result.code, u32::from(ResultCode::Ok));
+    }
+
+    /// Test that a tx signed with a secp256k1 key generated by the wallet is
+    /// accepted, with the fees paid by the key's implicit address
+    #[test]
+    fn test_wallet_secp256k1_signed_tx_accepted() {
+        use namada::types::account::AccountPublicKeysMap;
+        use namada_sdk::signer::{self, Signer, SoftwareSigner};
+        use rand_core::OsRng;
+        use zeroize::Zeroizing;
+
+        let (mut shell, _recv, _, _) = test_utils::setup();
+
+        // Generate an encrypted secp256k1 key and reload it from disk
+        let store_dir = tempfile::tempdir().expect("Test failed");
+        let password = Zeroizing::new("password".to_string());
+        let mut cli_wallet =
+            wallet::CliWalletUtils::new(store_dir.path().to_path_buf());
+        let (alias, keypair) = cli_wallet
+            .gen_store_secret_key(
+                SchemeType::Secp256k1,
+                Some("secp".into()),
+                false,
+                Some(password.clone()),
+                &mut OsRng,
+            )
+            .expect("Test failed");
+        cli_wallet.save().expect("Test failed");
+        let mut cli_wallet =
+            wallet::load(store_dir.path()).expect("Test failed");
+        let stored_key = cli_wallet
+            .store()
+            .find_secret_key(&alias)
+            .expect("Test failed");
+        assert!(stored_key.is_encrypted());
+        let keypair_from_disk = cli_wallet
+            .find_secret_key(&alias, Some(password))
+            .expect("Test failed");
+        let pk = keypair_from_disk.ref_to();
+        assert_eq!(pk, keypair.ref_to());
+        assert!(matches!(pk, common::PublicKey::Secp256k1(_)));
+
+        // The wallet derives the implicit address the ledger verifies against
+        let address = cli_wallet
+            .find_address(&alias)
+            .expect("Test failed")
+            .into_owned();
+        assert_eq!(address, Address::from(&pk));
+
+        // Add unshielded balance for fee payment
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &address,
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, Amount::native_whole(1000).serialize_to_vec())
+            .unwrap();
+
+        let signer = SoftwareSigner::new(keypair_from_disk);
+        let mut inner = Tx::from_type(TxType::Raw);
+        inner.header.chain_id = shell.chain_id.clone();
+        inner.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        inner.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        let pks_map = AccountPublicKeysMap::from_iter([signer.public_key()]);
+        tokio_test::block_on(signer::sign_raw(
+            &mut inner,
+            &[&signer],
+            &pks_map,
+            None,
+        ))
+        .expect("Test failed");
+        let wrapper = tokio_test::block_on(signer::wrap_tx(
+            inner,
+            Fee {
+                amount_per_gas_unit: 1.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            GAS_LIMIT_MULTIPLIER.into(),
+            Epoch(0),
+            &signer,
+        ))
+        .expect("Test failed");
+
+        let request = ProcessProposal {
+            txs: vec![wrapper.to_bytes()],
+        };
+        let response = shell.process_proposal(request).expect("Test failed");
+        assert_eq!(response[0].result.code, u32::from(ResultCode::Ok));
+    }
+
     /// Test that if the unsigned inner tx hash is known (replay attack), the
     /// block is rejected
     #[test]
@@ -1549,7 +2164,7 @@ mod test_process_proposal {
         shell
             .wl_storage
             .storage
-            .write_replay_protection_entry(&mut batch, &hash_key)
+            .write_replay_protection_entry(&mut batch, &hash_key, None)
             .expect("Test failed");
 
         // Run validation
@@ -1561,7 +2176,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -1678,7 +2293,7 @@ mod test_process_proposal {
                 for res in response {
                     assert_eq!(
                         res.result.code,
-                        u32::from(ErrorCodes::InvalidChainId)
+                        u32::from(ResultCode::InvalidChainId)
                     );
                     assert_eq!(
                         res.result.info,
@@ -1729,7 +2344,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ExpiredTx)
+                    u32::from(ResultCode::ExpiredTx)
                 );
             }
         }
@@ -1775,7 +2390,54 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::AllocationError)
+                    u32::from(ResultCode::AllocationError)
+                );
+            }
+        }
+    }
+
+    /// Check that wrapper txs exceeding the capacity of the decryption queue
+    /// cause a block rejection
+    #[test]
+    fn test_exceeding_tx_queue_max_bytes() {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        let keypair = super::test_utils::gen_keypair();
+
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair.ref_to(),
+                Epoch(0),
+                GAS_LIMIT_MULTIPLIER.into(),
+                None,
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            wrapper.sechashes(),
+            [(0, keypair)].into_iter().collect(),
+            None,
+        )));
+        shell
+            .wl_storage
+            .storage
+            .tx_queue
+            .set_max_bytes(wrapper.encoded_len() - 1);
+
+        // Run validation
+        let request = ProcessProposal {
+            txs: vec![wrapper.to_bytes()],
+        };
+        match shell.process_proposal(request) {
+            Ok(_) => panic!("Test failed"),
+            Err(TestError::RejectProposal(response)) => {
+                assert_eq!(
+                    response[0].result.code,
+                    u32::from(ResultCode::AllocationError)
                 );
             }
         }
@@ -1817,7 +2479,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::TxGasLimit)
+                    u32::from(ResultCode::TxGasLimit)
                 );
             }
         }
@@ -1860,7 +2522,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::FeeError)
+                    u32::from(ResultCode::FeeError)
                 );
             }
         }
@@ -1903,7 +2565,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::FeeError)
+                    u32::from(ResultCode::FeeError)
                 );
             }
         }
@@ -1946,7 +2608,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::FeeError)
+                    u32::from(ResultCode::FeeError)
                 );
             }
         }
@@ -1989,7 +2651,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::FeeError)
+                    u32::from(ResultCode::FeeError)
                 );
             }
         }
@@ -2043,7 +2705,7 @@ mod test_process_proposal {
             };
             assert_eq!(
                 response.result.code,
-                u32::from(ErrorCodes::AllocationError)
+                u32::from(ResultCode::AllocationError)
             );
             assert_eq!(
                 response.result.info,
@@ -2102,7 +2764,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::TooLarge)
+                    u32::from(ResultCode::TooLarge)
                 );
             }
         }
@@ -2114,7 +2776,7 @@ mod test_process_proposal {
             Ok(_) => panic!("Test failed"),
             Err(TestError::RejectProposal(response)) => {
                 assert!(
-                    response[0].result.code != u32::from(ErrorCodes::TooLarge)
+                    response[0].result.code != u32::from(ResultCode::TooLarge)
                 );
             }
         }
@@ -4,7 +4,9 @@ use namada::ledger::dry_run_tx;
 use namada::ledger::queries::{RequestCtx, ResponseQuery};
 use namada::ledger::storage_api::token;
 use namada::types::address::Address;
+use serde::Serialize;
 
+use super::debug_log::{self, ModeSummary};
 use super::*;
 use crate::node::ledger::response;
 
@@ -18,6 +20,10 @@ where
     /// the default if `path` is not a supported string.
     /// INVARIANT: This method must be stateless.
     pub fn query(&self, query: request::Query) -> response::Query {
+        if let Some(path) = query.path.strip_prefix(debug_log::PATH_PREFIX) {
+            return self.debug_query(path);
+        }
+
         let ctx = RequestCtx {
             wl_storage: &self.wl_storage,
             event_log: self.event_log(),
@@ -47,6 +53,33 @@ where
         }
     }
 
+    /// Serve the debug query with the given path, relative to
+    /// [`debug_log::PATH_PREFIX`], with a JSON response
+    fn debug_query(&self, path: &str) -> response::Query {
+        let value = match path {
+            _ if !self.debug_log.is_enabled() => {
+                Err("The debug queries are disabled".to_string())
+            }
+            "rejections" => to_json(&self.debug_log.recent_rejections()),
+            "caches" => {
+                to_json(&self.debug_log.cache_stats(&self.tx_decode_cache))
+            }
+            "mode" => to_json(&ModeSummary::new(&self.mode)),
+            _ => Err(format!("Unknown debug query {path}")),
+        };
+        match value {
+            Ok(value) => response::Query {
+                value: value.into(),
+                ..Default::default()
+            },
+            Err(err) => response::Query {
+                code: 1.into(),
+                info: format!("RPC error: {}", err),
+                ..Default::default()
+            },
+        }
+    }
+
     /// Simple helper function for the ledger to get balances
     /// of the specified token at the specified address
     pub fn get_balance(
@@ -61,6 +94,11 @@ where
     }
 }
 
+/// Encode the response of a debug query
+fn to_json(value: &impl Serialize) -> std::result::Result<Vec<u8>, String> {
+    serde_json::to_vec(value).map_err(|err| err.to_string())
+}
+
 // NOTE: we are testing `namada::ledger::queries_ext`,
 // which is not possible from `namada` since we do not have
 // access to the `Shell` there
@@ -71,6 +109,7 @@ mod test_queries {
     use namada::proof_of_stake::read_consensus_validator_set_addresses_with_stake;
     use namada::proof_of_stake::types::WeightedValidator;
     use namada::tendermint::abci::types::VoteInfo;
+    use namada::types::hash::Hash;
     use namada::types::storage::Epoch;
     use namada_sdk::eth_bridge::{EthBridgeQueries, SendValsetUpd};
 
@@ -201,4 +240,68 @@ mod test_queries {
             (2, 28, false),
         ],
     }
+
+    /// Query the given debug path
+    fn debug_query(
+        shell: &test_utils::TestShell,
+        path: &str,
+    ) -> response::Query {
+        shell.query(request::Query {
+            data: Default::default(),
+            path: format!("{}{path}", debug_log::PATH_PREFIX),
+            height: Default::default(),
+            prove: false,
+        })
+    }
+
+    /// Test that the debug queries are only served when enabled, and that
+    /// they return the txs rejected from the mempool.
+    #[test]
+    fn test_debug_queries() {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        let garbage = vec![0xff; 32];
+        shell.mempool_validate(&garbage, MempoolTxType::NewTransaction);
+        assert!(debug_query(&shell, "rejections").code.is_err());
+
+        shell.set_debug_rpc(true);
+        let check =
+            shell.mempool_validate(&garbage, MempoolTxType::NewTransaction);
+        assert_eq!(check.code, ResultCode::InvalidTx.into());
+        let rsp = debug_query(&shell, "rejections");
+        assert!(rsp.code.is_ok());
+        let rejections: serde_json::Value =
+            serde_json::from_slice(&rsp.value).unwrap();
+        let rejections = rejections.as_array().unwrap();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0]["source"], "check_tx");
+        assert_eq!(
+            rejections[0]["hash"],
+            Hash::sha256(&garbage).to_string().as_str()
+        );
+        assert_eq!(rejections[0]["code"], u32::from(ResultCode::InvalidTx));
+        assert_eq!(rejections[0]["reason"], check.log.as_str());
+
+        let rsp = debug_query(&shell, "caches");
+        let caches: serde_json::Value =
+            serde_json::from_slice(&rsp.value).unwrap();
+        assert_eq!(caches["check_tx_rejections"], 1);
+        assert_eq!(caches["replay_rejections"], 0);
+
+        let rsp = debug_query(&shell, "mode");
+        let mode: serde_json::Value =
+            serde_json::from_slice(&rsp.value).unwrap();
+        assert_eq!(mode["mode"], "validator");
+        assert_eq!(
+            mode["protocol_key"],
+            shell
+                .mode
+                .get_protocol_key()
+                .unwrap()
+                .ref_to()
+                .to_string()
+                .as_str()
+        );
+
+        assert!(debug_query(&shell, "unknown").code.is_err());
+    }
 }
@@ -0,0 +1,284 @@
+//! A cache of the wrapper signatures verified in the last blocks.
+//!
+//! The signature of a wrapper tx is verified when the tx is admitted to the
+//! mempool, again when a proposal including it is processed and again when
+//! the block is finalized, usually at different heights. The
+//! [`VerifiedSigs`] of the [`TxDecodeCache`] record the signatures that were
+//! successfully verified, keyed by the hash they sign, the public key of the
+//! signer and the hash of the signature itself, so that each is only
+//! verified once. As the signed hash commits to every section of the tx, a
+//! tx whose bytes changed in any way doesn't match its former entries.
+//!
+//! Failed verifications are never recorded: an invalid signature is verified
+//! again every time it's checked. Entries are evicted, least recently used
+//! first, when the cache is full, and once they were recorded more than
+//! [`MAX_AGE`] blocks ago.
+//!
+//! [`TxDecodeCache`]: super::decode_cache::TxDecodeCache
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use borsh_ext::BorshSerializeExt;
+use clru::CLruCache;
+use namada::proto::{Tx, TxError};
+use namada::types::hash::Hash;
+use namada::types::key::{common, BatchVerifyError, SigScheme};
+use namada::types::transaction::TxType;
+
+/// The maximum number of verified signatures that are kept
+const MAX_ENTRIES: usize = 50_000;
+
+/// The number of committed blocks after which a verified signature is
+/// evicted
+pub const MAX_AGE: u64 = 20;
+
+/// A signature over a hash, paired with the public key of its signer
+pub type WrapperSig = (common::PublicKey, Hash, common::Signature);
+
+/// The key of a verified signature: the signed hash, the public key of the
+/// signer and the hash of the signature
+type Key = (Hash, common::PublicKey, Hash);
+
+/// The verified signatures, with the number of blocks committed when each was
+/// recorded
+struct Entries {
+    verified: CLruCache<Key, u64>,
+    /// The number of blocks committed since the cache was created
+    commits: u64,
+}
+
+/// A cache of the verified wrapper signatures
+pub struct VerifiedSigs {
+    /// Whether verified signatures are kept. A disabled cache verifies the
+    /// given signatures every time.
+    enabled: bool,
+    entries: Mutex<Entries>,
+    /// The number of times the signatures of a wrapper tx were verified
+    verifications: AtomicUsize,
+    /// The number of times the signatures of a wrapper tx were all found in
+    /// the cache
+    hits: AtomicUsize,
+}
+
+impl std::fmt::Debug for VerifiedSigs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VerifiedSigs")
+            .field("enabled", &self.enabled)
+            .field("len", &self.len())
+            .field("verifications", &self.verifications())
+            .field("hits", &self.hits())
+            .finish()
+    }
+}
+
+impl Default for VerifiedSigs {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl VerifiedSigs {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            entries: Mutex::new(Entries {
+                verified: CLruCache::new(
+                    NonZeroUsize::new(MAX_ENTRIES).unwrap(),
+                ),
+                commits: 0,
+            }),
+            verifications: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// A cache that doesn't keep anything, for comparison purposes
+    pub fn disabled() -> Self {
+        Self::new(false)
+    }
+
+    /// Check the given tx as [`Tx::validate_tx`] does, unless it's a wrapper
+    /// whose signatures were all verified already. The signatures of a valid
+    /// wrapper are recorded.
+    pub fn validate_tx(&self, tx: &Tx) -> Result<(), TxError> {
+        let sigs = tx.wrapper_signatures();
+        if sigs
+            .as_deref()
+            .map_or(false, |sigs| self.contains_all(sigs))
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+        if let TxType::Wrapper(_) = tx.header.tx_type {
+            self.verifications.fetch_add(1, Ordering::Relaxed);
+        }
+        tx.validate_tx()?;
+        if let Some(sigs) = sigs {
+            self.insert(&sigs);
+        }
+        Ok(())
+    }
+
+    /// Verify the signatures of some wrapper txs in a single batch, see
+    /// [`common::SigScheme::verify_batch`]. The given signatures are not
+    /// recorded, as the batch may have failed because of some of them
+    /// only.
+    pub fn verify_batch(
+        &self,
+        txs: usize,
+        sigs: &[WrapperSig],
+    ) -> Result<(), BatchVerifyError> {
+        self.verifications.fetch_add(txs, Ordering::Relaxed);
+        common::SigScheme::verify_batch(sigs)
+    }
+
+    /// Check whether the given signatures were all verified
+    pub fn contains_all(&self, sigs: &[WrapperSig]) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let mut entries = self.entries();
+        sigs.iter()
+            .all(|sig| entries.verified.get(&key(sig)).is_some())
+    }
+
+    /// Record the given signatures as verified
+    pub fn insert<'a>(&self, sigs: impl IntoIterator<Item = &'a WrapperSig>) {
+        if !self.enabled {
+            return;
+        }
+        let mut entries = self.entries();
+        let commits = entries.commits;
+        for sig in sigs {
+            entries.verified.put(key(sig), commits);
+        }
+    }
+
+    /// Age the verified signatures once a block is committed, evicting those
+    /// older than [`MAX_AGE`] blocks
+    pub fn commit(&self) {
+        let mut entries = self.entries();
+        entries.commits += 1;
+        let oldest = entries.commits.saturating_sub(MAX_AGE);
+        let expired: Vec<Key> = entries
+            .verified
+            .iter()
+            .filter(|(_, recorded)| **recorded < oldest)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            entries.verified.pop(&key);
+        }
+    }
+
+    /// The number of verified signatures
+    pub fn len(&self) -> usize {
+        self.entries().verified.len()
+    }
+
+    /// Check whether no signature is recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of times the signatures of a wrapper tx were verified,
+    /// rather than found in the cache
+    pub fn verifications(&self) -> usize {
+        self.verifications.load(Ordering::Relaxed)
+    }
+
+    /// The number of times the signatures of a wrapper tx were all found in
+    /// the cache, rather than verified
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    fn entries(&self) -> MutexGuard<'_, Entries> {
+        // The entries are left consistent by every critical section
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The key of the given signature
+fn key((pk, hash, sig): &WrapperSig) -> Key {
+    (*hash, pk.clone(), Hash::sha256(sig.serialize_to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::proto::{Code, Data, Section, Signature};
+    use namada::types::address;
+    use namada::types::key::RefTo;
+    use namada::types::storage::Epoch;
+    use namada::types::transaction::{Fee, WrapperTx};
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::gen_keypair;
+
+    /// Make a wrapper tx signed by the given key
+    fn wrapper(keypair: &common::SecretKey) -> Tx {
+        let mut tx = Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: Default::default(),
+                token: address::nam(),
+            },
+            keypair.ref_to(),
+            Epoch(0),
+            Default::default(),
+            None,
+        ))));
+        tx.set_code(Code::new(vec![1], None));
+        tx.set_data(Data::new(vec![2]));
+        tx.add_section(Section::Signature(Signature::new(
+            tx.sechashes(),
+            [(0, keypair.clone())].into_iter().collect(),
+            None,
+        )));
+        tx
+    }
+
+    /// Check that only valid signatures are recorded, and that they expire
+    /// after [`MAX_AGE`] blocks.
+    #[test]
+    fn test_verified_sigs() {
+        let keypair = gen_keypair();
+        let tx = wrapper(&keypair);
+        let mut forged = tx.clone();
+        for section in &mut forged.sections {
+            if let Section::Signature(sig) = section {
+                sig.signatures.insert(
+                    0,
+                    common::SigScheme::sign(&gen_keypair(), sig.get_raw_hash()),
+                );
+            }
+        }
+
+        let cache = VerifiedSigs::default();
+        let disabled = VerifiedSigs::disabled();
+        for _ in 0..2 {
+            assert!(cache.validate_tx(&forged).is_err());
+            assert!(disabled.validate_tx(&tx).is_ok());
+        }
+        assert!(cache.is_empty());
+        assert!(disabled.is_empty());
+        assert_eq!(cache.verifications(), 2);
+        assert_eq!(disabled.verifications(), 2);
+
+        assert!(cache.validate_tx(&tx).is_ok());
+        for _ in 0..MAX_AGE {
+            cache.commit();
+            assert!(cache.validate_tx(&tx).is_ok());
+        }
+        assert_eq!(cache.verifications(), 3);
+        assert_eq!(cache.hits(), MAX_AGE as usize);
+        assert_eq!(cache.len(), 1);
+        cache.commit();
+        assert!(cache.is_empty());
+        assert!(cache.validate_tx(&tx).is_ok());
+        assert_eq!(cache.verifications(), 4);
+    }
+}
@@ -217,7 +217,8 @@ where
         txs: &'shell [TxBytes],
     ) -> impl Iterator<Item = TxBytes> + 'shell {
         txs.iter().filter_map(move |tx_bytes| {
-            let tx = match Tx::try_from(tx_bytes.as_ref()) {
+            let decoded = self.tx_decode_cache.decode_shared(tx_bytes);
+            let tx = match decoded.tx() {
                 Ok(tx) => tx,
                 Err(err) => {
                     tracing::warn!(
@@ -228,7 +229,7 @@ where
                     return None;
                 }
             };
-            match (&tx).try_into().ok()? {
+            match tx.try_into().ok()? {
                 EthereumTxData::EthEventsVext(_)
                 | EthereumTxData::BridgePoolVext(_) => Some(tx_bytes.clone()),
                 EthereumTxData::ValSetUpdateVext(ext) => {
@@ -10,7 +10,7 @@ use namada::proto::Tx;
 use namada::types::hash::Hash;
 use namada::types::key::tm_raw_hash_to_string;
 use namada::types::storage::{BlockHash, BlockHeight};
-use namada::types::transaction::hash_tx;
+use namada::types::transaction::{hash_tx, TxType};
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedSender;
 use tower::Service;
@@ -35,6 +35,8 @@ pub struct AbcippShim {
     service: Shell,
     begin_block_request: Option<request::BeginBlock>,
     delivered_txs: Vec<TxBytes>,
+    /// The number of decrypted txs delivered so far in the current block
+    delivered_decrypted_txs: usize,
     shell_recv: std::sync::mpsc::Receiver<(
         Req,
         tokio::sync::oneshot::Sender<Result<Resp, BoxError>>,
@@ -73,6 +75,7 @@ impl AbcippShim {
                 ),
                 begin_block_request: None,
                 delivered_txs: vec![],
+                delivered_decrypted_txs: 0,
                 shell_recv,
             },
             AbciService {
@@ -97,11 +100,6 @@ impl AbcippShim {
     pub fn run(mut self) {
         while let Ok((req, resp_sender)) = self.shell_recv.recv() {
             let resp = match req {
-                Req::ProcessProposal(proposal) => self
-                    .service
-                    .call(Request::ProcessProposal(proposal))
-                    .map_err(Error::from)
-                    .and_then(|resp| resp.try_into()),
                 Req::BeginBlock(block) => {
                     // we save this data to be forwarded to finalize later
                     self.begin_block_request = Some(block);
@@ -110,13 +108,30 @@ impl AbcippShim {
                 Req::DeliverTx(tx) => {
                     let mut deliver: DeliverTx = Default::default();
                     // Attach events to this transaction if possible
-                    if Tx::try_from(&tx.tx[..]).is_ok() {
+                    if let Ok(decoded) = Tx::try_from(&tx.tx[..]) {
                         let resp = ResponseDeliverTx::default();
                         deliver.events = resp
                             .events
                             .into_iter()
                             .map(|v| TryFrom::try_from(v).unwrap())
                             .collect();
+                        // Txs are only executed when the block is finalized,
+                        // so the gas used by inner txs is only reported in
+                        // the tx events
+                        let (gas_wanted, gas_used) =
+                            self.service.delivered_tx_gas(
+                                &decoded,
+                                &tx.tx,
+                                self.delivered_decrypted_txs,
+                            );
+                        if let TxType::Decrypted(_) = decoded.header().tx_type {
+                            self.delivered_decrypted_txs += 1;
+                        }
+                        deliver.gas_wanted = u64::from(gas_wanted)
+                            .try_into()
+                            .unwrap_or(i64::MAX);
+                        deliver.gas_used =
+                            u64::from(gas_used).try_into().unwrap_or(i64::MAX);
                     }
                     self.delivered_txs.push(tx.tx);
                     Ok(Resp::DeliverTx(deliver))
@@ -147,10 +162,12 @@ impl AbcippShim {
                         &self.delivered_txs,
                         block_time,
                         &block_proposer,
+                        &begin_block_request.byzantine_validators,
                     );
                     let mut txs = Vec::with_capacity(self.delivered_txs.len());
                     let mut delivered = vec![];
                     std::mem::swap(&mut self.delivered_txs, &mut delivered);
+                    self.delivered_decrypted_txs = 0;
                     for (result, tx) in processing_results
                         .into_iter()
                         .zip(delivered.into_iter())
@@ -84,6 +84,9 @@ pub mod shim {
                 Req::PrepareProposal(inner) => {
                     Ok(Request::PrepareProposal(inner))
                 }
+                Req::ProcessProposal(inner) => {
+                    Ok(Request::ProcessProposal(inner))
+                }
                 _ => Err(Error::ConvertReq(req)),
             }
         }
@@ -253,3 +256,57 @@ pub mod shim {
         }
     }
 }
+
+#[cfg(test)]
+mod test_shim_types {
+    use std::convert::TryFrom;
+
+    use super::shim::{self, response};
+    use super::{Request as Req, Response as Resp};
+    use crate::facade::tendermint::v0_37::abci::request;
+    use crate::facade::tendermint::{account, block, Hash, Time};
+
+    /// A proposal of the given txs
+    fn proposal(txs: Vec<shim::TxBytes>) -> request::ProcessProposal {
+        request::ProcessProposal {
+            txs,
+            proposed_last_commit: None,
+            misbehavior: vec![],
+            hash: Hash::None,
+            height: block::Height::from(1_u32),
+            time: Time::unix_epoch(),
+            next_validators_hash: Hash::None,
+            proposer_address: account::Id::new([1; 20]),
+        }
+    }
+
+    /// Check that a proposal is forwarded to the shell as is, with its txs
+    /// and proposer.
+    #[test]
+    fn test_process_proposal_request() {
+        let req = proposal(vec![vec![1, 2].into(), vec![3].into()]);
+        match shim::Request::try_from(Req::ProcessProposal(req.clone())) {
+            Ok(shim::Request::ProcessProposal(converted)) => {
+                assert_eq!(converted, req)
+            }
+            _ => panic!("Test failed: the proposal wasn't forwarded"),
+        }
+    }
+
+    /// Check that the verdict of the shell on a proposal is returned as is,
+    /// such that a rejected proposal isn't accepted.
+    #[test]
+    fn test_process_proposal_response() {
+        for verdict in [
+            response::ProcessProposal::Accept,
+            response::ProcessProposal::Reject,
+        ] {
+            let resp = Resp::try_from(shim::Response::ProcessProposal(verdict))
+                .expect("Test failed");
+            match resp {
+                Resp::ProcessProposal(status) => assert_eq!(status, verdict),
+                _ => panic!("Test failed: the verdict wasn't returned"),
+            }
+        }
+    }
+}
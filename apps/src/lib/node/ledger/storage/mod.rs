@@ -58,13 +58,14 @@ mod tests {
     use namada::ledger::gas::STORAGE_ACCESS_GAS_PER_BYTE;
     use namada::ledger::ibc::storage::ibc_key;
     use namada::ledger::parameters::{EpochDuration, Parameters};
+    use namada::ledger::replay_protection;
     use namada::ledger::storage::write_log::WriteLog;
-    use namada::ledger::storage::{types, StoreType, WlStorage};
+    use namada::ledger::storage::{types, StoreType, WlStorage, DB};
     use namada::ledger::storage_api::{self, StorageWrite};
     use namada::types::chain::ChainId;
     use namada::types::hash::Hash;
-    use namada::types::storage::{BlockHash, BlockHeight, Key};
-    use namada::types::time::DurationSecs;
+    use namada::types::storage::{BlockHash, BlockHeight, Epoch, Key};
+    use namada::types::time::{DateTimeUtc, DurationSecs};
     use namada::types::{address, storage, token};
     use proptest::collection::vec;
     use proptest::prelude::*;
@@ -633,6 +634,112 @@ mod tests {
         assert!(result.is_ok(), "The tree should be restored");
     }
 
+    /// Test the pruning of the replay protection buckets with RocksDB
+    #[test]
+    fn test_prune_replay_protection() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        let tx_hash = |epoch: u64| Hash::sha256(epoch.to_be_bytes());
+
+        // Fill the bucket of every epoch with the hash of an expired tx, and
+        // commit a block per epoch
+        let last_epoch = replay_protection::RETENTION_EPOCHS + 2;
+        for epoch in 0..=last_epoch {
+            let height = BlockHeight(epoch + 1);
+            storage
+                .begin_block(BlockHash::default(), height)
+                .expect("begin_block failed");
+            storage.block.epoch = Epoch(epoch);
+            let mut batch = PersistentStorage::batch();
+            storage
+                .write_replay_protection_entry(
+                    &mut batch,
+                    &replay_protection::get_replay_protection_bucket_subkey(
+                        Epoch(epoch),
+                        &tx_hash(epoch),
+                    ),
+                    Some(DateTimeUtc::default()),
+                )
+                .expect("write failed");
+            storage.commit_block(batch).expect("commit failed");
+        }
+
+        // The buckets before the retention window were pruned in one go
+        let oldest =
+            replay_protection::oldest_retained_bucket(Epoch(last_epoch));
+        for epoch in 0..=last_epoch {
+            let bucket: Vec<Hash> = storage
+                .iter_replay_protection_bucket(Epoch(epoch))
+                .collect();
+            let retained = Epoch(epoch) >= oldest;
+            assert_eq!(bucket.len(), usize::from(retained));
+            assert_eq!(
+                storage
+                    .has_replay_protection_entry(&tx_hash(epoch))
+                    .expect("read failed"),
+                retained
+            );
+        }
+    }
+
+    /// Test that the replay protection entries written before the hashes
+    /// were bucketed are never pruned
+    #[test]
+    fn test_keep_unbucketed_replay_protection() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let open = || {
+            PersistentStorage::open(
+                db_path.path(),
+                ChainId::default(),
+                address::nam(),
+                None,
+                None,
+            )
+        };
+        let tx_hash = Hash::sha256("unbucketed".as_bytes());
+
+        // Write the entry with the empty value of the former layout, then
+        // commit a block per epoch past the retention window
+        let mut storage = open();
+        let mut batch = PersistentStorage::batch();
+        storage
+            .db
+            .write_replay_protection_entry(
+                &mut batch,
+                &replay_protection::get_replay_protection_all_subkey(&tx_hash),
+                vec![],
+            )
+            .expect("write failed");
+        let last_epoch = replay_protection::RETENTION_EPOCHS + 2;
+        for epoch in 0..=last_epoch {
+            storage
+                .begin_block(BlockHash::default(), BlockHeight(epoch + 1))
+                .expect("begin_block failed");
+            storage.block.epoch = Epoch(epoch);
+            storage.commit_block(batch).expect("commit failed");
+            batch = PersistentStorage::batch();
+        }
+        drop(storage);
+
+        let mut storage = open();
+        storage
+            .load_last_state()
+            .expect("loading the last state failed");
+        assert!(
+            storage
+                .has_replay_protection_entry(&tx_hash)
+                .expect("read failed")
+        );
+    }
+
     /// Test the prefix iterator with RocksDB.
     #[test]
     fn test_persistent_storage_prefix_iter() {
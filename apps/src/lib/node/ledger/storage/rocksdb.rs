@@ -25,6 +25,8 @@
 //!   - `old/{dyn}`: value from predecessor block height
 //! - `block`: block state
 //!   - `results/{h}`: block results at height `h`
+//!   - `sections/{h}/{hash}`: the retained sections of the txs included in
+//!     the block at height `h`
 //!   - `h`: for each block at height `h`:
 //!     - `tree`: merkle tree
 //!       - `root`: root hash
@@ -35,7 +37,10 @@
 //!     - `address_gen`: established address generator
 //!     - `header`: block's header
 //! - `replay_protection`: hashes of processed tx
-//!     - `all`: the hashes included up to the last block
+//!     - `all`: the hashes included up to the last block, of the txs that
+//!       never expire
+//!     - `buckets/{epoch}`: the hashes included up to the last block, of the
+//!       txs that expire, by the epoch at which they were finalized
 //!     - `last`: the hashes included in the last block
 
 use std::fs::File;
@@ -50,6 +55,7 @@ use data_encoding::HEXLOWER;
 use itertools::Either;
 use namada::core::ledger::masp_conversions::ConversionState;
 use namada::core::types::ethereum_structs;
+use namada::ledger::replay_protection;
 use namada::ledger::storage::merkle_tree::{
     base_tree_key_prefix, subtree_key_prefix,
 };
@@ -1156,15 +1162,17 @@ impl DB for RocksDB {
     fn has_replay_protection_entry(
         &self,
         hash: &namada::types::hash::Hash,
+        buckets: impl Iterator<Item = Epoch>,
     ) -> Result<bool> {
         let replay_protection_cf =
             self.get_column_family(REPLAY_PROTECTION_CF)?;
 
-        for prefix in ["last", "all"] {
-            let key = Key::parse(prefix)
-                .map_err(Error::KeyError)?
-                .push(&hash.to_string())
-                .map_err(Error::KeyError)?;
+        let last_key =
+            replay_protection::get_replay_protection_last_subkey(hash);
+        let all_key = replay_protection::get_replay_protection_all_subkey(hash);
+        for key in [last_key, all_key].into_iter().chain(buckets.map(|epoch| {
+            replay_protection::get_replay_protection_bucket_subkey(epoch, hash)
+        })) {
             if self
                 .0
                 .get_pinned_cf(replay_protection_cf, key.to_string())
@@ -1450,13 +1458,12 @@ impl DB for RocksDB {
         &mut self,
         batch: &mut Self::WriteBatch,
         key: &Key,
+        value: impl AsRef<[u8]>,
     ) -> Result<()> {
         let replay_protection_cf =
             self.get_column_family(REPLAY_PROTECTION_CF)?;
 
-        batch
-            .0
-            .put_cf(replay_protection_cf, key.to_string(), vec![]);
+        batch.0.put_cf(replay_protection_cf, key.to_string(), value);
 
         Ok(())
     }
@@ -1473,6 +1480,82 @@ impl DB for RocksDB {
 
         Ok(())
     }
+
+    fn prune_replay_protection_buckets(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: Epoch,
+    ) -> Result<()> {
+        let replay_protection_cf =
+            self.get_column_family(REPLAY_PROTECTION_CF)?;
+
+        // The keys of the buckets are ordered by epoch, so that the older
+        // buckets are deleted with a single range
+        let start = Key::parse("buckets").map_err(Error::KeyError)?;
+        let end =
+            replay_protection::get_replay_protection_bucket_prefix(before);
+        batch.0.delete_range_cf(
+            replay_protection_cf,
+            start.to_string(),
+            end.to_string(),
+        );
+
+        Ok(())
+    }
+
+    fn read_tx_section(
+        &self,
+        height: BlockHeight,
+        hash: &namada::types::hash::Hash,
+    ) -> Result<Option<Vec<u8>>> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let key = Key::parse("sections")
+            .map_err(Error::KeyError)?
+            .push(&height)
+            .map_err(Error::KeyError)?
+            .push(&hash.to_string())
+            .map_err(Error::KeyError)?;
+        self.0
+            .get_cf(block_cf, key.to_string())
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
+
+    fn write_tx_section(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        hash: &namada::types::hash::Hash,
+        section: &[u8],
+    ) -> Result<()> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let key = Key::parse("sections")
+            .map_err(Error::KeyError)?
+            .push(&height)
+            .map_err(Error::KeyError)?
+            .push(&hash.to_string())
+            .map_err(Error::KeyError)?;
+        batch.0.put_cf(block_cf, key.to_string(), section);
+
+        Ok(())
+    }
+
+    fn prune_tx_sections(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+
+        // The keys of the sections are ordered by height, so that the
+        // sections of the older heights are deleted with a single range
+        let start = Key::parse("sections").map_err(Error::KeyError)?;
+        let end = start.push(&before).map_err(Error::KeyError)?;
+        batch
+            .0
+            .delete_range_cf(block_cf, start.to_string(), end.to_string());
+
+        Ok(())
+    }
 }
 
 impl<'iter> DBIter<'iter> for RocksDB {
@@ -1522,8 +1605,32 @@ impl<'iter> DBIter<'iter> for RocksDB {
             .get_column_family(REPLAY_PROTECTION_CF)
             .expect("{REPLAY_PROTECTION_CF} column family should exist");
 
-        iter_prefix(self, replay_protection_cf, "last".to_string(), None)
+        iter_prefix(
+            self,
+            replay_protection_cf,
+            "last".to_string(),
+            Some("last/".to_string()),
+        )
+    }
+
+    fn iter_replay_protection_bucket(
+        &'iter self,
+        epoch: Epoch,
+    ) -> Self::PrefixIter {
+        let replay_protection_cf = self
+            .get_column_family(REPLAY_PROTECTION_CF)
+            .expect("{REPLAY_PROTECTION_CF} column family should exist");
+
+        let bucket =
+            replay_protection::get_replay_protection_bucket_prefix(epoch);
+        iter_prefix(
+            self,
+            replay_protection_cf,
+            "".to_string(),
+            Some(format!("{bucket}/")),
+        )
     }
+
 }
 
 fn iter_subspace_prefix<'iter>(
@@ -236,6 +236,7 @@ fn validator_key_to_json(
                 sk_sec.serialize_to_vec(),
             )
         }
+        _ => return Err(ParseSecretKeyError::MismatchedScheme),
     };
 
     Ok(json!({
@@ -341,6 +342,7 @@ pub fn id_from_pk(pk: &common::PublicKey) -> TendermintNodeId {
             let digest = Sha256::digest(_pk.serialize_to_vec().as_slice());
             bytes.copy_from_slice(&digest[..TENDERMINT_NODE_ID_LENGTH]);
         }
+        _ => panic!("Tendermint node keys can only be ed25519 or secp256k1"),
     }
     TendermintNodeId::new(bytes)
 }
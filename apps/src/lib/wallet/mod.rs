@@ -4,6 +4,7 @@ mod store;
 
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::{env, fs};
 
 use namada::bip39::{Language, Mnemonic};
@@ -16,6 +17,7 @@ use namada_sdk::wallet::{
 };
 pub use namada_sdk::wallet::{ValidatorData, ValidatorKeys};
 use rand_core::OsRng;
+use subtle::ConstantTimeEq;
 pub use store::wallet_file;
 use zeroize::Zeroizing;
 
@@ -49,24 +51,27 @@ impl WalletIo for CliWalletUtils {
             ),
             Err(_) => match env::var("NAMADA_WALLET_PASSWORD") {
                 Ok(password) => Zeroizing::new(password),
-                Err(_) if confirm => {
-                    let prompt = "Enter your encryption password: ";
-                    read_and_confirm_passphrase_tty(prompt).unwrap_or_else(
-                        |e| {
-                            eprintln!("{e}");
-                            eprintln!(
-                                "Action cancelled, no changes persisted."
-                            );
-                            cli::safe_exit(1)
-                        },
-                    )
-                }
-                Err(_) => {
-                    let prompt = "Enter your decryption password: ";
-                    rpassword::read_password_from_tty(Some(prompt))
-                        .map(Zeroizing::new)
-                        .expect("Failed reading password from tty.")
-                }
+                Err(_) => match env::var("NAMADA_WALLET_PASSWORD_COMMAND") {
+                    Ok(command) => read_password_from_command(&command),
+                    Err(_) if confirm => {
+                        let prompt = "Enter your encryption password: ";
+                        read_and_confirm_passphrase_tty(prompt).unwrap_or_else(
+                            |e| {
+                                eprintln!("{e}");
+                                eprintln!(
+                                    "Action cancelled, no changes persisted."
+                                );
+                                cli::safe_exit(1)
+                            },
+                        )
+                    }
+                    Err(_) => {
+                        let prompt = "Enter your decryption password: ";
+                        rpassword::read_password_from_tty(Some(prompt))
+                            .map(Zeroizing::new)
+                            .expect("Failed reading password from tty.")
+                    }
+                },
             },
         };
         if confirm && pwd.as_str().is_empty() {
@@ -174,7 +179,7 @@ pub fn read_and_confirm_passphrase_tty(
             "Enter same passphrase again: ",
         ))
         .map(Zeroizing::new)?;
-        if confirmed != passphrase {
+        if !bool::from(confirmed.as_bytes().ct_eq(passphrase.as_bytes())) {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 "Passphrases did not match",
@@ -267,6 +272,29 @@ pub fn exists(store_dir: &Path) -> bool {
     file.exists()
 }
 
+/// Read the password from the standard output of a shell command, such as a
+/// call to a password manager or a key agent, without its trailing newline.
+fn read_password_from_command(command: &str) -> Zeroizing<String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stderr(Stdio::inherit())
+        .output()
+        .expect("Something went wrong running the password command");
+    if !output.status.success() {
+        eprintln!("The password command failed with {}", output.status);
+        cli::safe_exit(1)
+    }
+    let mut pwd =
+        Zeroizing::new(String::from_utf8(output.stdout).unwrap_or_else(|_| {
+            eprintln!("The password command output is not valid UTF-8");
+            cli::safe_exit(1)
+        }));
+    let len = pwd.trim_end_matches(|c| c == '\n' || c == '\r').len();
+    pwd.truncate(len);
+    pwd
+}
+
 /// Read the password for encryption from the file/env/stdin, with
 /// confirmation if read from stdin.
 pub fn read_and_confirm_encryption_password(
@@ -7,7 +7,7 @@ use namada::types::key::SchemeType;
 use namada_sdk::wallet::pre_genesis::{
     ReadError, ValidatorStore, ValidatorWallet,
 };
-use namada_sdk::wallet::{gen_key_to_store, WalletIo};
+use namada_sdk::wallet::{gen_key_to_store, GenRestoreKeyError, WalletIo};
 use rand::rngs::OsRng;
 use zeroize::Zeroizing;
 
@@ -30,7 +30,9 @@ pub fn gen_and_store(
     store_dir: &Path,
 ) -> std::io::Result<ValidatorWallet> {
     let password = read_and_confirm_encryption_password(unsafe_dont_encrypt);
-    let validator = gen(scheme, password);
+    let validator = gen(scheme, password).map_err(|err| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    })?;
     let data = validator.store.encode();
     let wallet_path = validator_file_name(store_dir);
     // Make sure the dir exists
@@ -110,23 +112,23 @@ pub fn load(store_dir: &Path) -> Result<ValidatorWallet, ReadError> {
 fn gen(
     scheme: SchemeType,
     password: Option<Zeroizing<String>>,
-) -> ValidatorWallet {
+) -> Result<ValidatorWallet, GenRestoreKeyError> {
     let (account_key, account_sk) =
-        gen_key_to_store(scheme, password.clone(), &mut OsRng);
+        gen_key_to_store(scheme, password.clone(), &mut OsRng)?;
     let (consensus_key, consensus_sk) = gen_key_to_store(
         // Note that TM only allows ed25519 for consensus key
         SchemeType::Ed25519,
         password.clone(),
         &mut OsRng,
-    );
+    )?;
     let (eth_cold_key, eth_cold_sk) =
-        gen_key_to_store(SchemeType::Secp256k1, password.clone(), &mut OsRng);
+        gen_key_to_store(SchemeType::Secp256k1, password.clone(), &mut OsRng)?;
     let (tendermint_node_key, tendermint_node_sk) = gen_key_to_store(
         // Note that TM only allows ed25519 for node IDs
         SchemeType::Ed25519,
         password,
         &mut OsRng,
-    );
+    )?;
     let validator_keys = gen_validator_keys(None, None, scheme);
     let eth_hot_key = validator_keys.eth_bridge_keypair.clone();
     let store = ValidatorStore {
@@ -136,12 +138,12 @@ fn gen(
         tendermint_node_key,
         validator_keys,
     };
-    ValidatorWallet {
+    Ok(ValidatorWallet {
         store,
         account_key: account_sk,
         consensus_key: consensus_sk,
         eth_cold_key: eth_cold_sk,
         eth_hot_key,
         tendermint_node_key: tendermint_node_sk,
-    }
+    })
 }
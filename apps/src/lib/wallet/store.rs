@@ -48,9 +48,14 @@ pub fn gen_validator_keys(
             }
             k
         })
-        .unwrap_or_else(|| gen_secret_key(SchemeType::Secp256k1, &mut OsRng));
-    let protocol_keypair = protocol_keypair
-        .unwrap_or_else(|| gen_secret_key(protocol_keypair_scheme, &mut OsRng));
+        .unwrap_or_else(|| {
+            gen_secret_key(SchemeType::Secp256k1, &mut OsRng)
+                .expect("Key generation should not fail.")
+        });
+    let protocol_keypair = protocol_keypair.unwrap_or_else(|| {
+        gen_secret_key(protocol_keypair_scheme, &mut OsRng)
+            .unwrap_or_else(|err| panic!("{err}"))
+    });
     ValidatorKeys {
         protocol_keypair,
         eth_bridge_keypair,
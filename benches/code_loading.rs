@@ -0,0 +1,106 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use namada::core::types::token::{Amount, Transfer};
+use namada::proto::Tx;
+use namada::types::address;
+use namada::types::token::DenominatedAmount;
+use namada::vm::wasm::TxCache;
+use namada_apps::bench_utils::{BenchShell, TX_TRANSFER_WASM};
+use namada_apps::wallet::defaults;
+use tempfile::TempDir;
+
+/// The number of transfers in the executed block
+const BLOCK_TXS: usize = 50;
+/// The size of the in-memory cache of compiled txs
+const TX_CACHE_SIZE: usize = 50 * 1024 * 1024;
+
+/// The number of bytes currently allocated
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// The highest number of bytes allocated since the last reset
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, tracking the peak of the allocated bytes
+struct PeakAlloc;
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED
+                .fetch_add(layout.size(), Ordering::Relaxed)
+                + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+/// A shell with a cold cache of compiled txs, along with a block of
+/// transparent transfers referencing the code of the transfer tx by hash
+fn block_of_transfers() -> (BenchShell, TempDir, Vec<Tx>) {
+    let mut shell = BenchShell::default();
+    let cache_dir = tempfile::tempdir().unwrap();
+    shell.inner.tx_wasm_cache = TxCache::new(cache_dir.path(), TX_CACHE_SIZE);
+    let txs = (0..BLOCK_TXS)
+        .map(|_| {
+            shell.generate_tx(
+                TX_TRANSFER_WASM,
+                Transfer {
+                    source: defaults::albert_address(),
+                    target: defaults::bertha_address(),
+                    token: address::nam(),
+                    amount: DenominatedAmount::native(Amount::native_whole(1)),
+                    key: None,
+                    shielded: None,
+                },
+                None,
+                None,
+                vec![&defaults::albert_keypair()],
+            )
+        })
+        .collect();
+    (shell, cache_dir, txs)
+}
+
+/// Execute a block of transfers sharing their code, reporting the peak of the
+/// allocations made and the number of times the code was read
+fn execute_block(c: &mut Criterion) {
+    let (mut shell, _cache_dir, txs) = block_of_transfers();
+    let base = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    for tx in &txs {
+        shell.execute_tx(tx);
+    }
+    println!(
+        "execute_block: peak allocation of {} bytes for {BLOCK_TXS} \
+         transfers, reading their code {} time(s)",
+        PEAK.load(Ordering::Relaxed) - base,
+        shell.tx_wasm_cache.code_loader().code_reads()
+    );
+    drop(shell);
+
+    c.bench_function("execute_block", |b| {
+        b.iter_batched_ref(
+            block_of_transfers,
+            |(shell, _, txs)| {
+                for tx in txs.iter() {
+                    shell.execute_tx(tx);
+                }
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(code_loading, execute_block);
+criterion_main!(code_loading);
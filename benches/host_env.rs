@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use criterion::{criterion_group, criterion_main, Criterion};
 use namada::core::types::account::AccountPublicKeysMap;
 use namada::core::types::address;
+use namada::core::types::key::{common, ed25519, RefTo, SigScheme};
 use namada::core::types::token::{Amount, Transfer};
 use namada::ledger::storage::DB;
 use namada::proto::Signature;
@@ -59,6 +60,35 @@ fn tx_section_signature_validation(c: &mut Criterion) {
     });
 }
 
+// Compares verifying the signatures of a block full of wrappers one at a time
+// against verifying them in a single batch
+fn batch_signature_verification(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let items: Vec<_> = (0..500u32)
+        .map(|i| {
+            let sk = common::SecretKey::Ed25519(ed25519::SigScheme::generate(
+                &mut rng,
+            ));
+            let msg = i.to_le_bytes().to_vec();
+            let sig = common::SigScheme::sign(&sk, &msg);
+            (sk.ref_to(), msg, sig)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("batch_signature_verification");
+    group.bench_function("individual", |b| {
+        b.iter(|| {
+            for (pk, msg, sig) in &items {
+                common::SigScheme::verify_signature(pk, msg, sig).unwrap()
+            }
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| common::SigScheme::verify_batch(&items).unwrap())
+    });
+    group.finish();
+}
+
 fn compile_wasm(c: &mut Criterion) {
     let mut group = c.benchmark_group("compile_wasm");
     let mut txs: HashMap<&str, Vec<u8>> = HashMap::default();
@@ -108,6 +138,52 @@ fn compile_wasm(c: &mut Criterion) {
     group.finish();
 }
 
+// Benchmarks executing the same tx code 100 times, either reusing the module
+// from the wasm compilation cache or compiling it anew for every execution
+fn repeated_tx_execution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("repeated_tx_execution");
+    let transfer = BenchShell::default().generate_tx(
+        TX_TRANSFER_WASM,
+        Transfer {
+            source: defaults::albert_address(),
+            target: defaults::bertha_address(),
+            token: address::nam(),
+            amount: Amount::native_whole(1).native_denominated(),
+            key: None,
+            shielded: None,
+        },
+        None,
+        None,
+        vec![&defaults::albert_keypair()],
+    );
+
+    for cached in [true, false] {
+        let label = if cached { "cached" } else { "uncached" };
+        group.bench_function(format!("Tx: {TX_TRANSFER_WASM}, {label}"), |b| {
+            b.iter_batched_ref(
+                BenchShell::default,
+                |shell| {
+                    for _ in 0..100 {
+                        // Re-initialize the tx cache to force the
+                        // compilation of the module
+                        let _tempdir = (!cached).then(|| {
+                            let tempdir = tempfile::tempdir().unwrap();
+                            let path = tempdir.path().canonicalize().unwrap();
+                            shell.tx_wasm_cache =
+                                TxCache::new(path, 50 * 1024 * 1024);
+                            tempdir
+                        });
+                        shell.execute_tx(&transfer);
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
 fn untrusted_wasm_validation(c: &mut Criterion) {
     let mut group = c.benchmark_group("untrusted_wasm_validation");
     let mut txs: HashMap<&str, Vec<u8>> = HashMap::default();
@@ -333,7 +409,9 @@ fn storage_write(c: &mut Criterion) {
 criterion_group!(
     host_env,
     tx_section_signature_validation,
+    batch_signature_verification,
     compile_wasm,
+    repeated_tx_execution,
     untrusted_wasm_validation,
     write_log_read,
     storage_read,
@@ -2,29 +2,27 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use namada::core::types::address;
 use namada::core::types::token::{Amount, Transfer};
 use namada::ledger::storage::TempWlStorage;
-use namada::proto::Signature;
+use namada::proto::{Signature, Tx};
 use namada::types::key::RefTo;
 use namada::types::storage::BlockHeight;
 use namada::types::time::DateTimeUtc;
 use namada::types::transaction::{Fee, WrapperTx};
 use namada_apps::bench_utils::{BenchShell, TX_TRANSFER_WASM};
+use namada_apps::node::ledger::shell::decode_cache::TxDecodeCache;
 use namada_apps::node::ledger::shell::process_proposal::ValidationMeta;
+use namada_apps::node::ledger::shell::MempoolTxType;
+use namada_apps::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
 use namada_apps::wallet::defaults;
 
-fn process_tx(c: &mut Criterion) {
-    let mut shell = BenchShell::default();
-    // Advance chain height to allow the inclusion of wrapper txs by the block
-    // space allocator
-    shell.wl_storage.storage.last_block.as_mut().unwrap().height =
-        BlockHeight(2);
-
+/// Make a wrapper of a transfer of the given amount from Albert to Bertha
+fn transfer_wrapper(shell: &BenchShell, amount: u64) -> Tx {
     let mut tx = shell.generate_tx(
         TX_TRANSFER_WASM,
         Transfer {
             source: defaults::albert_address(),
             target: defaults::bertha_address(),
             token: address::nam(),
-            amount: Amount::native_whole(1).native_denominated(),
+            amount: Amount::native_whole(amount).native_denominated(),
             key: None,
             shielded: None,
         },
@@ -52,7 +50,17 @@ fn process_tx(c: &mut Criterion) {
         [(0, defaults::albert_keypair())].into_iter().collect(),
         None,
     )));
-    let wrapper = tx.to_bytes();
+    tx
+}
+
+fn process_tx(c: &mut Criterion) {
+    let mut shell = BenchShell::default();
+    // Advance chain height to allow the inclusion of wrapper txs by the block
+    // space allocator
+    shell.wl_storage.storage.last_block.as_mut().unwrap().height =
+        BlockHeight(2);
+
+    let wrapper = transfer_wrapper(&shell, 1).to_bytes();
 
     let datetime = DateTimeUtc::now();
 
@@ -99,5 +107,73 @@ fn process_tx(c: &mut Criterion) {
     });
 }
 
-criterion_group!(process_wrapper, process_tx);
+/// Check a full block of wrappers in the mempool, then process it as a
+/// proposal, with and without the tx decode cache of the shell
+fn process_block(c: &mut Criterion) {
+    let mut shell = BenchShell::default();
+    // Advance chain height to allow the inclusion of wrapper txs by the block
+    // space allocator
+    shell.wl_storage.storage.last_block.as_mut().unwrap().height =
+        BlockHeight(2);
+
+    let txs: Vec<TxBytes> = (1..=100)
+        .map(|amount| transfer_wrapper(&shell, amount).to_bytes().into())
+        .collect();
+    let datetime = DateTimeUtc::now();
+
+    let mut group = c.benchmark_group("process_block");
+    for (label, cache) in [
+        ("with_decode_cache", TxDecodeCache::default()),
+        ("without_decode_cache", TxDecodeCache::disabled()),
+    ] {
+        shell.tx_decode_cache = cache;
+        group.bench_function(label, |b| {
+            b.iter(|| {
+                shell.tx_decode_cache.clear();
+                for tx_bytes in &txs {
+                    shell.mempool_validate(
+                        tx_bytes,
+                        MempoolTxType::NewTransaction,
+                    );
+                }
+                shell.process_txs(
+                    &txs,
+                    datetime,
+                    &defaults::daewon_address(),
+                    &[],
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Check a mempool of 5k wrappers as done when preparing a proposal, serially
+/// and in parallel
+fn check_mempool(c: &mut Criterion) {
+    let mut shell = BenchShell::default();
+    let txs: Vec<TxBytes> = (1..=5_000)
+        .map(|amount| transfer_wrapper(&shell, amount).to_bytes().into())
+        .collect();
+    let datetime = DateTimeUtc::now();
+    // Decode and verify the txs at each iteration
+    shell.tx_decode_cache = TxDecodeCache::disabled();
+
+    let mut group = c.benchmark_group("check_mempool");
+    for (label, threads) in [
+        ("serial", None),
+        (
+            "parallel",
+            std::thread::available_parallelism().ok().map(usize::from),
+        ),
+    ] {
+        shell.set_prepare_proposal_threads(threads);
+        group.bench_function(label, |b| {
+            b.iter(|| shell.check_wrappers_bytes(&txs, Some(&datetime)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(process_wrapper, process_tx, process_block, check_mempool);
 criterion_main!(process_wrapper);
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use borsh_ext::BorshSerializeExt;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use namada::proto::{Section, Signature, Signer, Tx};
+use namada::types::chain::ChainId;
+use namada::types::hash::Hash;
+use namada::types::key::testing::{keypair_1, keypair_2};
+use sha2::{Digest, Sha256};
+
+/// The length of the code of the hashed tx
+const CODE_LEN: usize = 2 * 1024 * 1024;
+/// The number of signature sections of the signature-heavy tx
+const SIGNATURE_SECTIONS: usize = 64;
+
+/// Look up every section of a tx with 2 MiB of code by hash, on a freshly
+/// decoded tx whose section digests are yet to be computed and on a tx whose
+/// digests are memoized
+fn hash_code_section(c: &mut Criterion) {
+    let mut tx = Tx::new(ChainId::default(), None);
+    tx.add_code(vec![1; CODE_LEN], None)
+        .add_serialized_data(vec![2; 1024]);
+    let tx_bytes = tx.to_bytes();
+    let lookup = |tx: &Tx| {
+        for hash in tx.sechashes() {
+            assert!(tx.get_section(&hash).is_some());
+        }
+    };
+
+    let mut group = c.benchmark_group("hash_code_section");
+    group.bench_function("fresh", |b| {
+        b.iter_batched(
+            || Tx::try_from(tx_bytes.as_slice()).unwrap(),
+            |tx| lookup(&tx),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("memoized", |b| {
+        let tx = Tx::try_from(tx_bytes.as_slice()).unwrap();
+        b.iter(|| lookup(&tx))
+    });
+    group.finish();
+}
+
+/// Hash the signature sections of a tx signed many times, along with the raw
+/// hashes they sign, by streaming their fields into the hasher and by hashing
+/// their encodings as done before
+fn hash_signature_sections(c: &mut Criterion) {
+    let mut tx = Tx::new(ChainId::default(), None);
+    tx.add_serialized_data(vec![2; 1024]);
+    let targets = tx.sechashes();
+    for _ in 0..SIGNATURE_SECTIONS {
+        tx.add_section(Section::Signature(Signature::new(
+            targets.clone(),
+            [(0, keypair_1()), (1, keypair_2())]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            None,
+        )));
+    }
+    let signatures: Vec<(&Section, &Signature)> = tx
+        .sections
+        .iter()
+        .filter_map(|section| match section {
+            Section::Signature(signature) => Some((section, signature)),
+            _ => None,
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("hash_signature_sections");
+    group.bench_function("streamed", |b| {
+        b.iter(|| {
+            for (section, signature) in &signatures {
+                section.get_hash();
+                signature.get_raw_hash();
+            }
+        })
+    });
+    group.bench_function("encoded", |b| {
+        b.iter(|| {
+            for (section, signature) in &signatures {
+                Hash(Sha256::digest(section.serialize_to_vec()).into());
+                let stripped = Signature {
+                    signer: Signer::PubKeys(vec![]),
+                    signatures: BTreeMap::new(),
+                    ..(*signature).clone()
+                };
+                Hash(Sha256::digest(stripped.serialize_to_vec()).into());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(section_hashing, hash_code_section, hash_signature_sections);
+criterion_main!(section_hashing);
@@ -0,0 +1,101 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use borsh::BorshDeserialize;
+use criterion::{criterion_group, criterion_main, Criterion};
+use namada::proto::generated::types;
+use namada::proto::Tx;
+use namada::types::chain::ChainId;
+use namada_apps::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
+use prost::Message;
+
+/// The number of txs in the decoded proposal
+const PROPOSAL_TXS: usize = 80;
+/// The length of the data of each tx of the proposal, making for a proposal
+/// of about 5 MiB
+const TX_DATA_LEN: usize = 64 * 1024;
+
+/// The number of bytes currently allocated
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// The highest number of bytes allocated since the last reset
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, tracking the peak of the allocated bytes
+struct PeakAlloc;
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED
+                .fetch_add(layout.size(), Ordering::Relaxed)
+                + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+/// Decode a tx by copying its section payloads out of the given buffer, as
+/// done before txs could share the buffers they are decoded from
+fn decode_by_copy(tx_bytes: &TxBytes) -> Tx {
+    let tx = types::Tx::decode(&tx_bytes[..]).unwrap();
+    Tx::try_from_slice(&tx.data).unwrap()
+}
+
+/// Decode a tx whose section payloads share the given buffer
+fn decode_shared(tx_bytes: &TxBytes) -> Tx {
+    Tx::try_from(tx_bytes.clone()).unwrap()
+}
+
+/// The peak number of bytes allocated on top of the already allocated ones
+/// while decoding the txs of the given proposal, which are all kept alive
+fn peak_allocation(proposal: &[TxBytes], decode: fn(&TxBytes) -> Tx) -> usize {
+    let base = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    let txs: Vec<Tx> = proposal.iter().map(decode).collect();
+    let peak = PEAK.load(Ordering::Relaxed) - base;
+    drop(txs);
+    peak
+}
+
+/// Decode the txs of a proposal of about 5 MiB, reporting the peak of the
+/// allocations made with and without sharing the buffers of the txs
+fn decode_proposal(c: &mut Criterion) {
+    let proposal: Vec<TxBytes> = (0..PROPOSAL_TXS)
+        .map(|index| {
+            let mut tx = Tx::new(ChainId::default(), None);
+            tx.add_code(vec![1; 32], None)
+                .add_serialized_data(vec![index as u8; TX_DATA_LEN]);
+            tx.to_bytes().into()
+        })
+        .collect();
+    let proposal_len: usize = proposal.iter().map(TxBytes::len).sum();
+
+    let mut group = c.benchmark_group("decode_proposal");
+    for (label, decode) in [
+        ("by_copy", decode_by_copy as fn(&TxBytes) -> Tx),
+        ("shared", decode_shared),
+    ] {
+        println!(
+            "decode_proposal/{label}: peak allocation of {} bytes for a \
+             proposal of {proposal_len} bytes",
+            peak_allocation(&proposal, decode)
+        );
+        group.bench_function(label, |b| {
+            b.iter(|| proposal.iter().map(decode).collect::<Vec<Tx>>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(tx_decoding, decode_proposal);
+criterion_main!(tx_decoding);
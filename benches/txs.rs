@@ -39,7 +39,8 @@ use namada::types::transaction::governance::{
     InitProposalData, VoteProposalData,
 };
 use namada::types::transaction::pos::{
-    Bond, CommissionChange, ConsensusKeyChange, Redelegation, Withdraw,
+    Bond, CommissionChange, ConsensusKeyChange, Redelegation,
+    ValidatorKeyProofs, Withdraw,
 };
 use namada_apps::bench_utils::{
     BenchShell, BenchShieldedCtx, ALBERT_PAYMENT_ADDRESS, ALBERT_SPENDING_KEY,
@@ -383,6 +384,7 @@ fn update_account(c: &mut Criterion) {
                 .finalize_reset()
                 .into(),
         )),
+        stored_vp_code_hash: None,
         public_keys: vec![defaults::albert_keypair().ref_to()],
         threshold: None,
     };
@@ -639,6 +641,13 @@ fn init_validator(c: &mut Criterion) {
         website: None,
         discord_handle: None,
         validator_vp_code_hash: extra_hash,
+        key_proofs: ValidatorKeyProofs::new(
+            &Address::from(&defaults::albert_keypair().to_public()),
+            &consensus_key_sk,
+            eth_cold_key_sk,
+            eth_hot_key_sk,
+            &protocol_key_sk,
+        ),
     };
     let tx = shell.generate_tx(
         TX_INIT_VALIDATOR_WASM,
@@ -689,16 +698,12 @@ fn change_consensus_key(c: &mut Criterion) {
     let mut csprng = rand::rngs::OsRng {};
     let consensus_key = ed25519::SigScheme::generate(&mut csprng)
         .try_to_sk::<common::SecretKey>()
-        .unwrap()
-        .to_public();
+        .unwrap();
 
     let shell = BenchShell::default();
     let signed_tx = shell.generate_tx(
         TX_CHANGE_CONSENSUS_KEY_WASM,
-        ConsensusKeyChange {
-            validator: defaults::validator_address(),
-            consensus_key,
-        },
+        ConsensusKeyChange::new(defaults::validator_address(), &consensus_key),
         None,
         None,
         vec![&defaults::validator_keypair()],
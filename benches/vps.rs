@@ -84,6 +84,7 @@ fn vp_user(c: &mut Criterion) {
                 .finalize_reset()
                 .into(),
         )),
+        stored_vp_code_hash: None,
         public_keys: vec![defaults::albert_keypair().to_public()],
         threshold: None,
     };
@@ -153,6 +154,7 @@ fn vp_user(c: &mut Criterion) {
                         vp_code_hash,
                         signed_tx,
                         &TxIndex(0),
+                        None,
                         &defaults::albert_address(),
                         &shell.wl_storage.storage,
                         &shell.wl_storage.write_log,
@@ -162,6 +164,8 @@ fn vp_user(c: &mut Criterion) {
                         &keys_changed,
                         &verifiers,
                         shell.vp_wasm_cache.clone(),
+                        #[cfg(not(feature = "mainnet"))]
+                        false,
                     )
                     .unwrap(),
                     "VP \"{bench_name}\" bench call failed"
@@ -300,6 +304,7 @@ fn vp_implicit(c: &mut Criterion) {
                         vp_code_hash,
                         tx,
                         &TxIndex(0),
+                        None,
                         &Address::from(&implicit_account.to_public()),
                         &shell.wl_storage.storage,
                         &shell.wl_storage.write_log,
@@ -309,6 +314,8 @@ fn vp_implicit(c: &mut Criterion) {
                         &keys_changed,
                         &verifiers,
                         shell.vp_wasm_cache.clone(),
+                        #[cfg(not(feature = "mainnet"))]
+                        false,
                     )
                     .unwrap()
                 )
@@ -371,6 +378,7 @@ fn vp_validator(c: &mut Criterion) {
                 .finalize_reset()
                 .into(),
         )),
+        stored_vp_code_hash: None,
         public_keys: vec![defaults::validator_keypair().to_public()],
         threshold: None,
     };
@@ -452,6 +460,7 @@ fn vp_validator(c: &mut Criterion) {
                         vp_code_hash,
                         signed_tx,
                         &TxIndex(0),
+                        None,
                         &defaults::validator_address(),
                         &shell.wl_storage.storage,
                         &shell.wl_storage.write_log,
@@ -461,6 +470,8 @@ fn vp_validator(c: &mut Criterion) {
                         &keys_changed,
                         &verifiers,
                         shell.vp_wasm_cache.clone(),
+                        #[cfg(not(feature = "mainnet"))]
+                        false,
                     )
                     .unwrap()
                 );
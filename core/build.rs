@@ -17,6 +17,8 @@ fn main() {
     tonic_build::configure()
         .out_dir("src/proto/generated")
         .protoc_arg("--experimental_allow_proto3_optional")
+        // Decode the bytes fields into buffers that can be shared
+        .bytes(".")
         .compile(&[format!("{}/types.proto", PROTO_SRC)], &[PROTO_SRC])
         .unwrap();
 }
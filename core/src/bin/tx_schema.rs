@@ -0,0 +1,18 @@
+//! Write the Borsh schema artifact of the transaction format, to be consumed
+//! by external wallets.
+//!
+//! Run from anywhere with `cargo run -p namada_core --bin namada_tx_schema`.
+
+use std::path::PathBuf;
+
+use namada_core::proto::schema::{tx_schema_pretty, TX_SCHEMA_PATH};
+
+fn main() -> std::io::Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(TX_SCHEMA_PATH);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, tx_schema_pretty())?;
+    println!("Wrote the tx schema to {}", path.display());
+    Ok(())
+}
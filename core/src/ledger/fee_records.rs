@@ -0,0 +1,299 @@
+//! Records of the fees charged to wrapper txs
+//!
+//! The fees charged to the wrappers of a block are recorded per token, along
+//! with the address of the proposer that collected them, under the
+//! `fee_records/blocks/{epoch}/{height}` subkey of the PoS address. The fees
+//! collected over an epoch are totaled per token under the
+//! `fee_records/totals/{epoch}` subkey. A fee is recorded as soon as it's
+//! charged, regardless of what happens to the wrapper and its inner tx
+//! afterwards.
+//!
+//! The records of the blocks are only kept for the current epoch and the
+//! last [`BLOCK_FEES_PAST_EPOCHS`] epochs: the records of the blocks of an
+//! older epoch are pruned at the start of a new epoch. The totals of the
+//! epochs are kept.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
+
+use crate::ledger::storage::wl_storage::WriteLogAndStorage;
+use crate::ledger::storage_api::{self, ResultExt, StorageRead, StorageWrite};
+use crate::types::address::{self, Address};
+use crate::types::storage::{BlockHeight, DbKeySeg, Epoch, Key, KeySeg};
+use crate::types::token::Amount;
+
+const FEE_RECORDS_KEY: &str = "fee_records";
+const BLOCKS_KEY: &str = "blocks";
+const TOTALS_KEY: &str = "totals";
+
+/// The number of past epochs for which the fee records of the blocks are
+/// kept, besides the current epoch
+pub const BLOCK_FEES_PAST_EPOCHS: u64 = 2;
+
+/// The fees collected by the proposer of a block
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BlockFees {
+    /// The address of the proposer of the block
+    pub proposer: Address,
+    /// The fees collected, per token
+    pub fees: BTreeMap<Address, Amount>,
+}
+
+/// The fees collected over an epoch
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct EpochFees {
+    /// The fees collected over the epoch, per token
+    pub totals: BTreeMap<Address, Amount>,
+    /// The fees collected at each block of the epoch in which some fees were
+    /// charged
+    pub blocks: BTreeMap<BlockHeight, BlockFees>,
+}
+
+/// Get the key prefix of the fee records of the blocks of the given epoch
+pub fn block_fees_prefix(epoch: Epoch) -> Key {
+    Key::from(address::POS.to_db_key())
+        .push(&FEE_RECORDS_KEY.to_owned())
+        .and_then(|key| key.push(&BLOCKS_KEY.to_owned()))
+        .and_then(|key| key.push(&epoch))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the fee record of the block at the given height of the
+/// given epoch
+pub fn block_fees_key(epoch: Epoch, height: BlockHeight) -> Key {
+    block_fees_prefix(epoch)
+        .push(&height)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the fees collected over the given epoch
+pub fn epoch_totals_key(epoch: Epoch) -> Key {
+    Key::from(address::POS.to_db_key())
+        .push(&FEE_RECORDS_KEY.to_owned())
+        .and_then(|key| key.push(&TOTALS_KEY.to_owned()))
+        .and_then(|key| key.push(&epoch))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Record a fee collected by the given proposer at the current block, in the
+/// record of the block and in the total of the epoch. Like the transfer of
+/// the fee itself, the records are updated in the tx write log.
+pub fn record_fee<WLS>(
+    wl_storage: &mut WLS,
+    proposer: &Address,
+    token: &Address,
+    amount: Amount,
+) -> storage_api::Result<()>
+where
+    WLS: WriteLogAndStorage + StorageRead,
+{
+    let epoch = wl_storage.get_block_epoch()?;
+    let height = wl_storage.get_block_height()?;
+
+    let block_key = block_fees_key(epoch, height);
+    let mut block =
+        wl_storage
+            .read::<BlockFees>(&block_key)?
+            .unwrap_or_else(|| BlockFees {
+                proposer: proposer.clone(),
+                fees: BTreeMap::new(),
+            });
+    add_fee(&mut block.fees, token, amount)?;
+
+    let totals_key = epoch_totals_key(epoch);
+    let mut totals = wl_storage
+        .read::<BTreeMap<Address, Amount>>(&totals_key)?
+        .unwrap_or_default();
+    add_fee(&mut totals, token, amount)?;
+
+    let write_log = wl_storage.write_log_mut();
+    write_log
+        .write(&block_key, block.serialize_to_vec())
+        .into_storage_result()?;
+    write_log
+        .write(&totals_key, totals.serialize_to_vec())
+        .into_storage_result()?;
+    Ok(())
+}
+
+/// Read the fees collected at the block at the given height of the given
+/// epoch, if any
+pub fn read_block_fees<S>(
+    storage: &S,
+    epoch: Epoch,
+    height: BlockHeight,
+) -> storage_api::Result<Option<BlockFees>>
+where
+    S: StorageRead,
+{
+    storage.read(&block_fees_key(epoch, height))
+}
+
+/// Read the fees collected over the given epoch, along with the fees
+/// collected at each of its blocks
+pub fn read_epoch_fees<S>(
+    storage: &S,
+    epoch: Epoch,
+) -> storage_api::Result<EpochFees>
+where
+    S: StorageRead,
+{
+    let totals = storage.read(&epoch_totals_key(epoch))?.unwrap_or_default();
+    let blocks = storage_api::iter_prefix::<BlockFees>(
+        storage,
+        &block_fees_prefix(epoch),
+    )?
+    .map(|record| {
+        let (key, fees) = record?;
+        match key.last() {
+            Some(DbKeySeg::StringSeg(height)) => Ok((
+                BlockHeight::parse(height.clone()).into_storage_result()?,
+                fees,
+            )),
+            _ => Err(storage_api::Error::new(format!(
+                "Unexpected fee record key {key}"
+            ))),
+        }
+    })
+    .collect::<storage_api::Result<_>>()?;
+    Ok(EpochFees { totals, blocks })
+}
+
+/// Prune the fee records of the blocks of the epoch that is no longer
+/// retained as of the given epoch. This must be called at the start of every
+/// epoch.
+pub fn prune_block_fees<S>(
+    storage: &mut S,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let Some(epoch) = current_epoch.checked_sub(BLOCK_FEES_PAST_EPOCHS + 1)
+    else {
+        return Ok(());
+    };
+    let keys =
+        storage_api::iter_prefix_bytes(storage, &block_fees_prefix(epoch))?
+            .map(|record| record.map(|(key, _)| key))
+            .collect::<storage_api::Result<Vec<_>>>()?;
+    for key in keys {
+        storage.delete(&key)?;
+    }
+    Ok(())
+}
+
+/// Add a fee to the given amounts per token
+fn add_fee(
+    fees: &mut BTreeMap<Address, Amount>,
+    token: &Address,
+    amount: Amount,
+) -> storage_api::Result<()> {
+    let fee = fees.entry(token.clone()).or_default();
+    *fee = fee
+        .checked_add(amount)
+        .ok_or_else(|| storage_api::Error::new_const("Fee record overflow"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::nam;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+
+    /// Check that fees are added up per block and per epoch, and that the
+    /// records of an epoch are read back in order of height.
+    #[test]
+    fn test_fee_records() {
+        let mut wl_storage = TestWlStorage::default();
+        let proposer = established_address_1();
+        let token = established_address_2();
+        let mut record =
+            |wl_storage: &mut TestWlStorage, height: u64, amount: u64| {
+                wl_storage.storage.block.height = BlockHeight(height);
+                record_fee(wl_storage, &proposer, &nam(), Amount::from(amount))
+                    .unwrap();
+            };
+        record(&mut wl_storage, 9, 1);
+        record(&mut wl_storage, 9, 2);
+        record(&mut wl_storage, 10, 4);
+        record_fee(&mut wl_storage, &proposer, &token, Amount::from(8))
+            .unwrap();
+        wl_storage.commit_tx();
+
+        let fees = read_epoch_fees(&wl_storage, Epoch(0)).unwrap();
+        assert_eq!(
+            fees.totals,
+            BTreeMap::from([
+                (nam(), Amount::from(7)),
+                (token.clone(), Amount::from(8))
+            ])
+        );
+        assert_eq!(
+            fees.blocks,
+            BTreeMap::from([
+                (
+                    BlockHeight(9),
+                    BlockFees {
+                        proposer: proposer.clone(),
+                        fees: BTreeMap::from([(nam(), Amount::from(3))]),
+                    }
+                ),
+                (
+                    BlockHeight(10),
+                    BlockFees {
+                        proposer: proposer.clone(),
+                        fees: BTreeMap::from([
+                            (nam(), Amount::from(4)),
+                            (token, Amount::from(8))
+                        ]),
+                    }
+                ),
+            ])
+        );
+        assert_eq!(
+            read_block_fees(&wl_storage, Epoch(0), BlockHeight(10)).unwrap(),
+            fees.blocks.get(&BlockHeight(10)).cloned()
+        );
+        assert_eq!(
+            read_epoch_fees(&wl_storage, Epoch(1)).unwrap(),
+            EpochFees::default()
+        );
+    }
+
+    /// Check that the records of the blocks of an epoch are pruned once the
+    /// epoch is no longer retained, while its totals are kept.
+    #[test]
+    fn test_prune_block_fees() {
+        let mut wl_storage = TestWlStorage::default();
+        let proposer = established_address_1();
+        record_fee(&mut wl_storage, &proposer, &nam(), Amount::from(1))
+            .unwrap();
+        wl_storage.commit_tx();
+        let fees = read_epoch_fees(&wl_storage, Epoch(0)).unwrap();
+        assert_eq!(fees.blocks.len(), 1);
+
+        // The epoch is still retained
+        prune_block_fees(&mut wl_storage, Epoch(BLOCK_FEES_PAST_EPOCHS))
+            .unwrap();
+        assert_eq!(read_epoch_fees(&wl_storage, Epoch(0)).unwrap(), fees);
+
+        prune_block_fees(&mut wl_storage, Epoch(BLOCK_FEES_PAST_EPOCHS + 1))
+            .unwrap();
+        assert_eq!(
+            read_epoch_fees(&wl_storage, Epoch(0)).unwrap(),
+            EpochFees {
+                totals: fees.totals,
+                blocks: BTreeMap::new(),
+            }
+        );
+    }
+}
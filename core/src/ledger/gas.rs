@@ -24,7 +24,8 @@ pub enum Error {
 
 const COMPILE_GAS_PER_BYTE: u64 = 24;
 const PARALLEL_GAS_DIVIDER: u64 = 10;
-const WASM_CODE_VALIDATION_GAS_PER_BYTE: u64 = 1;
+/// The cost of validating untrusted wasm code, per byte
+pub const WASM_CODE_VALIDATION_GAS_PER_BYTE: u64 = 1;
 const WRAPPER_TX_VALIDATION_GAS: u64 = 58_371;
 const STORAGE_OCCUPATION_GAS_PER_BYTE: u64 =
     100 + PHYSICAL_STORAGE_LATENCY_PER_BYTE;
@@ -46,6 +47,9 @@ pub const STORAGE_WRITE_GAS_PER_BYTE: u64 =
     MEMORY_ACCESS_GAS_PER_BYTE + 848 + STORAGE_OCCUPATION_GAS_PER_BYTE;
 /// The cost of verifying a signle signature of a transaction
 pub const VERIFY_TX_SIG_GAS: u64 = 9_793;
+/// The fixed cost of fetching a section of the executing transaction, on top
+/// of the cost of the memory access to the returned section
+pub const TX_SECTION_ACCESS_GAS: u64 = 1_000;
 /// The cost for requesting one more page in wasm (64KiB)
 pub const WASM_MEMORY_PAGE_GAS: u32 =
     MEMORY_ACCESS_GAS_PER_BYTE as u32 * 64 * 1_024;
@@ -74,6 +78,16 @@ pub fn get_max_block_gas(
         ))
 }
 
+/// Get the gas charged to a wrapper transaction of the given encoded length,
+/// accounting for its validation and the space it occupies in the block
+pub fn get_wrapper_gas(tx_len: u64) -> Result<Gas> {
+    tx_len
+        .checked_mul(STORAGE_OCCUPATION_GAS_PER_BYTE)
+        .and_then(|gas| gas.checked_add(WRAPPER_TX_VALIDATION_GAS))
+        .map(Gas::from)
+        .ok_or(Error::GasOverflow)
+}
+
 /// Representation of gas in sub-units. This effectively decouples gas metering
 /// from fee payment, allowing higher resolution when accounting for gas while,
 /// at the same time, providing a contained gas value when paying fees.
@@ -105,7 +119,7 @@ impl Gas {
 
     /// Converts the sub gas units to whole ones. If the sub units are not a
     /// multiple of the `SCALE` than ceil the quotient
-    fn get_whole_gas_units(&self) -> u64 {
+    pub fn get_whole_gas_units(&self) -> u64 {
         let quotient = self.sub / SCALE;
         if self.sub % SCALE == 0 {
             quotient
@@ -274,14 +288,7 @@ impl TxGasMeter {
     ///  - cost of validating the wrapper tx
     ///  - space that the transaction requires in the block
     pub fn add_wrapper_gas(&mut self, tx_bytes: &[u8]) -> Result<()> {
-        self.consume(WRAPPER_TX_VALIDATION_GAS)?;
-
-        let bytes_len = tx_bytes.len() as u64;
-        self.consume(
-            bytes_len
-                .checked_mul(STORAGE_OCCUPATION_GAS_PER_BYTE)
-                .ok_or(Error::GasOverflow)?,
-        )
+        self.consume(get_wrapper_gas(tx_bytes.len() as u64)?.into())
     }
 
     /// Add the gas cost used in validity predicates to the current transaction.
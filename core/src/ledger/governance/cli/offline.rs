@@ -317,7 +317,7 @@ fn compute_signatures_index(
     hashed_data: &Hash,
 ) -> BTreeSet<SignatureIndex> {
     account_public_keys_map
-        .index_secret_keys(keys.to_vec())
+        .index_secret_keys(keys)
         .values()
         .map(|signing_key| {
             let public_key = signing_key.ref_to();
@@ -8,6 +8,8 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use std::str::FromStr;
 
+use borsh::{BorshDeserialize, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
 pub use context::common::IbcCommonContext;
 use context::router::IbcRouter;
 pub use context::storage::{IbcStorageContext, ProofSpec};
@@ -15,6 +17,7 @@ pub use context::token_transfer::TokenTransferContext;
 pub use context::transfer_mod::{ModuleWrapper, TransferModule};
 use context::IbcContext;
 pub use context::ValidationParams;
+use prost::encoding::{decode_key, skip_field, DecodeContext};
 use prost::Message;
 use thiserror::Error;
 
@@ -31,7 +34,9 @@ use crate::ibc::core::ics24_host::identifier::{
 use crate::ibc::core::router::ModuleId;
 use crate::ibc::core::{execute, validate, MsgEnvelope, RouterError};
 use crate::ibc_proto::google::protobuf::Any;
+use crate::proto::Tx;
 use crate::types::address::{Address, MASP};
+use crate::types::hash::Hash;
 use crate::types::ibc::{
     get_shielded_transfer, is_ibc_denom, EVENT_TYPE_DENOM_TRACE,
     EVENT_TYPE_PACKET,
@@ -61,6 +66,80 @@ pub enum Error {
     ChainId(IdentifierError),
     #[error("Handling MASP transaction error: {0}")]
     MaspTx(String),
+    #[error("Decoding IBC message sections error: {0}")]
+    DecodingMsgSections(std::io::Error),
+    #[error("The IBC message section {0} is missing from the transaction")]
+    MissingSection(Hash),
+    #[error("The IBC message section {0} is not an extra data section")]
+    InvalidSection(Hash),
+}
+
+/// The type URL of an IBC message whose encoded value is carried by the extra
+/// data sections of the transaction
+pub const MSG_SECTIONS_TYPE_URL: &str = "/namada.ibc.MsgSections";
+
+/// The envelope of an IBC message split across extra data sections. The
+/// encoded message value is the concatenation of the referenced sections in
+/// the given order.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct MsgSections {
+    /// The type URL of the IBC message
+    pub type_url: String,
+    /// The hashes of the extra data sections holding the message value
+    pub sections: Vec<Hash>,
+}
+
+/// Split the given IBC message into extra data sections of the transaction,
+/// one for each top-level field of the message (e.g. a proof or a packet),
+/// and return the encoded envelope to be set as the transaction data
+pub fn add_msg_sections(tx: &mut Tx, msg: &Any) -> Result<Vec<u8>, Error> {
+    let mut sections = vec![];
+    let mut value = &msg.value[..];
+    while !value.is_empty() {
+        let field = value;
+        let (tag, wire_type) =
+            decode_key(&mut value).map_err(Error::DecodingData)?;
+        skip_field(wire_type, tag, &mut value, DecodeContext::default())
+            .map_err(Error::DecodingData)?;
+        let field = field[..field.len() - value.len()].to_vec();
+        sections.push(tx.add_extra_section(field, None).1);
+    }
+    let msg_sections = MsgSections {
+        type_url: msg.type_url.clone(),
+        sections,
+    };
+    let envelope = Any {
+        type_url: MSG_SECTIONS_TYPE_URL.to_string(),
+        value: msg_sections.serialize_to_vec(),
+    };
+    Ok(envelope.encode_to_vec())
+}
+
+/// Get the encoded IBC message of the transaction. When the data of the
+/// transaction is a [`MsgSections`] envelope, the message is rebuilt from the
+/// referenced extra data sections. Otherwise, the data is the message itself.
+pub fn resolve_msg_sections(
+    tx: &Tx,
+    tx_data: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    let any_msg = Any::decode(&tx_data[..]).map_err(Error::DecodingData)?;
+    if any_msg.type_url != MSG_SECTIONS_TYPE_URL {
+        return Ok(tx_data);
+    }
+    let msg_sections = MsgSections::try_from_slice(&any_msg.value)
+        .map_err(Error::DecodingMsgSections)?;
+    let mut value = vec![];
+    for hash in &msg_sections.sections {
+        let section =
+            tx.get_section(hash).ok_or(Error::MissingSection(*hash))?;
+        let data = section.extra_data().ok_or(Error::InvalidSection(*hash))?;
+        value.extend(data);
+    }
+    let any_msg = Any {
+        type_url: msg_sections.type_url,
+        value,
+    };
+    Ok(any_msg.encode_to_vec())
 }
 
 /// IBC actions to handle IBC operations
@@ -298,3 +377,124 @@ pub fn received_ibc_token(
     }
     Ok(storage::ibc_token(ibc_denom.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ibc_proto::ibc::core::client::v1::MsgUpdateClient;
+    use crate::proto::{Data, Section};
+    use crate::types::chain::ChainId;
+
+    const MSG_UPDATE_CLIENT_TYPE_URL: &str =
+        "/ibc.core.client.v1.MsgUpdateClient";
+
+    /// A MsgUpdateClient with a large header to be split across sections
+    fn msg_update_client() -> Any {
+        let header = Any {
+            type_url: "/ibc.lightclients.tendermint.v1.Header".to_string(),
+            value: (0..=u8::MAX).cycle().take(2048).collect(),
+        };
+        let msg = MsgUpdateClient {
+            client_id: "07-tendermint-0".to_string(),
+            client_message: Some(header),
+            signer: "relayer".to_string(),
+        };
+        Any {
+            type_url: MSG_UPDATE_CLIENT_TYPE_URL.to_string(),
+            value: msg.encode_to_vec(),
+        }
+    }
+
+    fn envelope(type_url: &str, sections: Vec<Hash>) -> Vec<u8> {
+        let msg_sections = MsgSections {
+            type_url: type_url.to_string(),
+            sections,
+        };
+        Any {
+            type_url: MSG_SECTIONS_TYPE_URL.to_string(),
+            value: msg_sections.serialize_to_vec(),
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_msg_sections_round_trip() {
+        let msg = msg_update_client();
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = add_msg_sections(&mut tx, &msg).unwrap();
+
+        // One section for each of the client ID, the header and the signer
+        let envelope = Any::decode(&tx_data[..]).unwrap();
+        assert_eq!(envelope.type_url, MSG_SECTIONS_TYPE_URL);
+        let msg_sections =
+            MsgSections::try_from_slice(&envelope.value).unwrap();
+        assert_eq!(msg_sections.type_url, MSG_UPDATE_CLIENT_TYPE_URL);
+        assert_eq!(msg_sections.sections.len(), 3);
+        assert_eq!(tx.sections.len(), 3);
+
+        let resolved = resolve_msg_sections(&tx, tx_data).unwrap();
+        assert_eq!(resolved, msg.encode_to_vec());
+        let resolved = Any::decode(&resolved[..]).unwrap();
+        let decoded = MsgUpdateClient::decode(&resolved.value[..]).unwrap();
+        assert_eq!(decoded, MsgUpdateClient::decode(&msg.value[..]).unwrap());
+    }
+
+    #[test]
+    fn test_msg_sections_resolution_order() {
+        let msg = msg_update_client();
+        let (head, tail) = msg.value.split_at(msg.value.len() / 2);
+        // Add the sections to the tx in the reverse order of the message
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tail_hash = tx.add_extra_section(tail.to_vec(), None).1;
+        let head_hash = tx.add_extra_section(head.to_vec(), None).1;
+
+        // The message is rebuilt in the order given by the envelope
+        let tx_data =
+            envelope(MSG_UPDATE_CLIENT_TYPE_URL, vec![head_hash, tail_hash]);
+        let resolved = resolve_msg_sections(&tx, tx_data).unwrap();
+        assert_eq!(resolved, msg.encode_to_vec());
+
+        let tx_data =
+            envelope(MSG_UPDATE_CLIENT_TYPE_URL, vec![tail_hash, head_hash]);
+        let resolved = resolve_msg_sections(&tx, tx_data).unwrap();
+        assert_ne!(resolved, msg.encode_to_vec());
+    }
+
+    #[test]
+    fn test_msg_sections_missing_section() {
+        let msg = msg_update_client();
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = add_msg_sections(&mut tx, &msg).unwrap();
+        let removed = tx.sections.remove(1).get_hash();
+
+        match resolve_msg_sections(&tx, tx_data) {
+            Err(Error::MissingSection(hash)) => assert_eq!(hash, removed),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_msg_sections_invalid_section() {
+        let msg = msg_update_client();
+        let mut tx = Tx::new(ChainId::default(), None);
+        let data_hash = tx
+            .add_section(Section::Data(Data::new(msg.value.clone())))
+            .get_hash();
+
+        let tx_data = envelope(MSG_UPDATE_CLIENT_TYPE_URL, vec![data_hash]);
+        match resolve_msg_sections(&tx, tx_data) {
+            Err(Error::InvalidSection(hash)) => assert_eq!(hash, data_hash),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_msg_without_sections() {
+        // A message set as the tx data is used as is
+        let msg = msg_update_client();
+        let tx = Tx::new(ChainId::default(), None);
+        let tx_data = msg.encode_to_vec();
+        let resolved = resolve_msg_sections(&tx, tx_data.clone()).unwrap();
+        assert_eq!(resolved, tx_data);
+    }
+}
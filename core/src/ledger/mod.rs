@@ -1,6 +1,7 @@
 //! The ledger modules
 
 pub mod eth_bridge;
+pub mod fee_records;
 pub mod gas;
 pub mod governance;
 pub mod ibc;
@@ -11,5 +12,7 @@ pub mod pgf;
 pub mod replay_protection;
 pub mod storage;
 pub mod storage_api;
+#[cfg(not(feature = "mainnet"))]
+pub mod testnet_pow;
 pub mod tx_env;
 pub mod vp_env;
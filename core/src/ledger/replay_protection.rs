@@ -1,10 +1,43 @@
 //! Replay protection storage
+//!
+//! The hashes of the txs of the last block are kept under the `last` subkey,
+//! together with the expiration of their tx, until they are finalized at the
+//! following block.
+//!
+//! The finalized hashes of txs without expiration are kept forever under the
+//! `all` subkey, as are all the hashes finalized before they were bucketed.
+//! The finalized hashes of expiring txs are instead bucketed by the epoch at
+//! which they were finalized, under the `buckets/{epoch}` subkey. Buckets
+//! older than [`RETENTION_EPOCHS`] epochs are pruned at the start of every
+//! epoch. The hashes of the pruned buckets whose tx hasn't expired yet are
+//! carried over to the bucket of the current epoch, so that a hash only stops
+//! preventing the resubmission of its tx once the tx can't be included in a
+//! block anymore.
 
 use crate::types::hash::Hash;
-use crate::types::storage::Key;
+use crate::types::storage::{Epoch, Key};
 
 const ERROR_MSG: &str = "Cannot obtain a valid db key";
 
+/// The number of past epochs whose buckets of finalized tx hashes are
+/// retained, on top of the bucket of the current epoch
+pub const RETENTION_EPOCHS: u64 = 7;
+
+/// Get the oldest retained bucket of finalized tx hashes at the given epoch
+pub fn oldest_retained_bucket(current_epoch: Epoch) -> Epoch {
+    current_epoch.sub_or_default(Epoch(RETENTION_EPOCHS))
+}
+
+/// Get the buckets of finalized tx hashes retained at the given epoch, from
+/// the newest to the oldest
+pub fn retained_buckets(
+    current_epoch: Epoch,
+) -> impl Iterator<Item = Epoch> + Clone {
+    (oldest_retained_bucket(current_epoch).0..=current_epoch.0)
+        .rev()
+        .map(Epoch)
+}
+
 /// Get the transaction hash key under the `last` subkey
 pub fn get_replay_protection_last_subkey(hash: &Hash) -> Key {
     Key::parse("last")
@@ -13,7 +46,26 @@ pub fn get_replay_protection_last_subkey(hash: &Hash) -> Key {
         .expect(ERROR_MSG)
 }
 
-/// Get the transaction hash key under the `all` subkey
+/// Get the key prefix of the bucket of the given epoch, under the `buckets`
+/// subkey. The epoch is encoded as a fixed-length big-endian integer
+/// segment, so that the keys of the buckets are ordered by epoch.
+pub fn get_replay_protection_bucket_prefix(epoch: Epoch) -> Key {
+    Key::parse("buckets")
+        .expect(ERROR_MSG)
+        .push(&epoch.0)
+        .expect(ERROR_MSG)
+}
+
+/// Get the transaction hash key in the bucket of the given epoch, under the
+/// `buckets` subkey
+pub fn get_replay_protection_bucket_subkey(epoch: Epoch, hash: &Hash) -> Key {
+    get_replay_protection_bucket_prefix(epoch)
+        .push(&hash.to_string())
+        .expect(ERROR_MSG)
+}
+
+/// Get the transaction hash key under the `all` subkey, where the hashes that
+/// never expire are kept
 pub fn get_replay_protection_all_subkey(hash: &Hash) -> Key {
     Key::parse("all")
         .expect(ERROR_MSG)
@@ -30,3 +82,57 @@ pub fn get_replay_protection_last_key(hash: &Hash) -> Key {
         .push(&hash.to_string())
         .expect(ERROR_MSG)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that the keys of the buckets are ordered by epoch, which lets
+    /// older buckets be pruned as a single range.
+    #[test]
+    fn test_buckets_ordered_by_epoch() {
+        let hash = Hash::sha256(b"tx");
+        let keys: Vec<String> = [0, 1, 255, 256, 1 << 32, u64::MAX]
+            .into_iter()
+            .map(|epoch| {
+                get_replay_protection_bucket_subkey(Epoch(epoch), &hash)
+                    .to_string()
+            })
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+
+        // Pruning the buckets before an epoch deletes exactly these buckets,
+        // and never the hashes that are kept forever
+        let start = Key::parse("buckets").unwrap().to_string();
+        let kept = get_replay_protection_all_subkey(&hash).to_string();
+        for (before, current) in [(Epoch(93), Epoch(100)), (Epoch(2), Epoch(9))]
+        {
+            let end = get_replay_protection_bucket_prefix(before).to_string();
+            let current =
+                get_replay_protection_bucket_subkey(current, &hash).to_string();
+            let pruned =
+                get_replay_protection_bucket_subkey(Epoch(before.0 - 1), &hash)
+                    .to_string();
+            assert!(start <= pruned && pruned < end);
+            assert!(current >= end);
+            assert!(kept < start || kept >= end);
+        }
+    }
+
+    /// Check the window of retained buckets.
+    #[test]
+    fn test_retained_buckets() {
+        assert_eq!(
+            retained_buckets(Epoch(2)).collect::<Vec<_>>(),
+            vec![Epoch(2), Epoch(1), Epoch(0)]
+        );
+        let current = Epoch(RETENTION_EPOCHS + 3);
+        assert_eq!(oldest_retained_bucket(current), Epoch(3));
+        assert_eq!(
+            retained_buckets(current).count() as u64,
+            RETENTION_EPOCHS + 1
+        );
+    }
+}
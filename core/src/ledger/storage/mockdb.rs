@@ -17,6 +17,7 @@ use super::{
     BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error, Result, DB,
 };
 use crate::ledger::masp_conversions::ConversionState;
+use crate::ledger::replay_protection;
 use crate::ledger::storage::types::{self, KVBytes, PrefixIterator};
 use crate::types::ethereum_structs;
 use crate::types::hash::Hash;
@@ -442,16 +443,20 @@ impl DB for MockDB {
         Ok(Some(merkle_tree_stores))
     }
 
-    fn has_replay_protection_entry(&self, hash: &Hash) -> Result<bool> {
+    fn has_replay_protection_entry(
+        &self,
+        hash: &Hash,
+        buckets: impl Iterator<Item = Epoch>,
+    ) -> Result<bool> {
         let prefix_key =
             Key::parse("replay_protection").map_err(Error::KeyError)?;
-        for prefix in ["last", "all"] {
-            let key = prefix_key
-                .push(&prefix.to_string())
-                .map_err(Error::KeyError)?
-                .push(&hash.to_string())
-                .map_err(Error::KeyError)?;
-
+        let last_key =
+            replay_protection::get_replay_protection_last_subkey(hash);
+        let all_key = replay_protection::get_replay_protection_all_subkey(hash);
+        for key in [last_key, all_key].into_iter().chain(buckets.map(|epoch| {
+            replay_protection::get_replay_protection_bucket_subkey(epoch, hash)
+        })) {
+            let key = prefix_key.join(&key);
             if self.0.borrow().contains_key(&key.to_string()) {
                 return Ok(true);
             }
@@ -592,12 +597,17 @@ impl DB for MockDB {
         &mut self,
         _batch: &mut Self::WriteBatch,
         key: &Key,
+        value: impl AsRef<[u8]>,
     ) -> Result<()> {
         let key = Key::parse("replay_protection")
             .map_err(Error::KeyError)?
             .join(key);
 
-        match self.0.borrow_mut().insert(key.to_string(), vec![]) {
+        match self
+            .0
+            .borrow_mut()
+            .insert(key.to_string(), value.as_ref().to_vec())
+        {
             Some(_) => Err(Error::DBError(format!(
                 "Replay protection key {key} already in storage"
             ))),
@@ -618,6 +628,92 @@ impl DB for MockDB {
 
         Ok(())
     }
+
+    fn prune_replay_protection_buckets(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        before: Epoch,
+    ) -> Result<()> {
+        let prefix_key =
+            Key::parse("replay_protection").map_err(Error::KeyError)?;
+        // The keys of the buckets are ordered by epoch
+        let start = prefix_key
+            .push(&"buckets".to_string())
+            .map_err(Error::KeyError)?
+            .to_string();
+        let end = prefix_key
+            .join(&replay_protection::get_replay_protection_bucket_prefix(
+                before,
+            ))
+            .to_string();
+        let mut db = self.0.borrow_mut();
+        let pruned: Vec<String> = db
+            .range((Included(start), Excluded(end)))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in pruned {
+            db.remove(&key);
+        }
+
+        Ok(())
+    }
+
+    fn read_tx_section(
+        &self,
+        height: BlockHeight,
+        hash: &Hash,
+    ) -> Result<Option<Vec<u8>>> {
+        let key = Key::parse("sections")
+            .map_err(Error::KeyError)?
+            .push(&height)
+            .map_err(Error::KeyError)?
+            .push(&hash.to_string())
+            .map_err(Error::KeyError)?;
+        Ok(self.0.borrow().get(&key.to_string()).cloned())
+    }
+
+    fn write_tx_section(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        hash: &Hash,
+        section: &[u8],
+    ) -> Result<()> {
+        let key = Key::parse("sections")
+            .map_err(Error::KeyError)?
+            .push(&height)
+            .map_err(Error::KeyError)?
+            .push(&hash.to_string())
+            .map_err(Error::KeyError)?;
+        self.0
+            .borrow_mut()
+            .insert(key.to_string(), section.to_vec());
+        Ok(())
+    }
+
+    fn prune_tx_sections(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()> {
+        let prefix_key = Key::parse("sections").map_err(Error::KeyError)?;
+        // The keys of the sections are ordered by height
+        let start = prefix_key.to_string();
+        let end = prefix_key
+            .push(&before)
+            .map_err(Error::KeyError)?
+            .to_string();
+        let mut db = self.0.borrow_mut();
+        let pruned: Vec<String> = db
+            .range((Included(start), Excluded(end)))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in pruned {
+            db.remove(&key);
+        }
+
+        Ok(())
+    }
 }
 
 impl<'iter> DBIter<'iter> for MockDB {
@@ -697,12 +793,30 @@ impl<'iter> DBIter<'iter> for MockDB {
         let iter = self.0.borrow().clone().into_iter();
         MockPrefixIterator::new(
             MockIterator {
-                prefix: "last".to_string(),
+                prefix: format!("{db_prefix}last/"),
                 iter,
             },
             db_prefix,
         )
     }
+
+    fn iter_replay_protection_bucket(
+        &'iter self,
+        epoch: Epoch,
+    ) -> Self::PrefixIter {
+        let db_prefix = "replay_protection/".to_owned();
+        let bucket =
+            replay_protection::get_replay_protection_bucket_prefix(epoch);
+        let iter = self.0.borrow().clone().into_iter();
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix: format!("{db_prefix}{bucket}/"),
+                iter,
+            },
+            db_prefix,
+        )
+    }
+
 }
 
 /// A prefix iterator base for the [`MockPrefixIterator`].
@@ -33,6 +33,7 @@ pub use crate::ledger::masp_conversions::{
     calculate_masp_rewards, encode_asset_type, ConversionState,
 };
 use crate::ledger::parameters::{self, EpochDuration, Parameters};
+use crate::ledger::replay_protection;
 use crate::ledger::storage::merkle_tree::{
     Error as MerkleTreeError, MerkleRoot,
 };
@@ -286,8 +287,14 @@ pub trait DB: std::fmt::Debug {
         store_type: Option<StoreType>,
     ) -> Result<Option<MerkleTreeStoresRead>>;
 
-    /// Check if the given replay protection entry exists
-    fn has_replay_protection_entry(&self, hash: &Hash) -> Result<bool>;
+    /// Check if the given replay protection entry exists, either among the
+    /// entries of the last block, among the ones kept forever or in one of the
+    /// given buckets
+    fn has_replay_protection_entry(
+        &self,
+        hash: &Hash,
+        buckets: impl Iterator<Item = Epoch>,
+    ) -> Result<bool>;
 
     /// Read the latest value for account subspace key from the DB
     fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>>;
@@ -362,6 +369,7 @@ pub trait DB: std::fmt::Debug {
         &mut self,
         batch: &mut Self::WriteBatch,
         key: &Key,
+        value: impl AsRef<[u8]>,
     ) -> Result<()>;
 
     /// Delete a replay protection entry
@@ -370,6 +378,39 @@ pub trait DB: std::fmt::Debug {
         batch: &mut Self::WriteBatch,
         key: &Key,
     ) -> Result<()>;
+
+    /// Delete the buckets of replay protection entries of all the epochs
+    /// before the given one
+    fn prune_replay_protection_buckets(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: Epoch,
+    ) -> Result<()>;
+
+    /// Read the Borsh encoded tx section with the given hash retained at the
+    /// given height
+    fn read_tx_section(
+        &self,
+        height: BlockHeight,
+        hash: &Hash,
+    ) -> Result<Option<Vec<u8>>>;
+
+    /// Write a Borsh encoded tx section retained at the given height
+    fn write_tx_section(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        hash: &Hash,
+        section: &[u8],
+    ) -> Result<()>;
+
+    /// Delete the tx sections retained at all the heights before the given
+    /// one
+    fn prune_tx_sections(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()>;
 }
 
 /// A database prefix iterator.
@@ -404,6 +445,12 @@ pub trait DBIter<'iter> {
 
     /// Read replay protection storage from the last block
     fn iter_replay_protection(&'iter self) -> Self::PrefixIter;
+
+    /// Read the bucket of replay protection storage of the given epoch
+    fn iter_replay_protection_bucket(
+        &'iter self,
+        epoch: Epoch,
+    ) -> Self::PrefixIter;
 }
 
 /// Atomic batch write.
@@ -566,6 +613,8 @@ where
         if is_full_commit {
             // prune old merkle tree stores
             self.prune_merkle_tree_stores(&mut batch)?;
+            // prune the replay protection buckets of old epochs
+            self.prune_replay_protection(&mut batch, header.time)?;
         }
         self.db.exec_batch(batch)
     }
@@ -1182,18 +1231,27 @@ where
             .unwrap_or_default()
     }
 
-    /// Check it the given transaction's hash is already present in storage
+    /// Check it the given transaction's hash is already present in storage,
+    /// either from the last block or in a retained bucket
     pub fn has_replay_protection_entry(&self, hash: &Hash) -> Result<bool> {
-        self.db.has_replay_protection_entry(hash)
+        self.db.has_replay_protection_entry(
+            hash,
+            replay_protection::retained_buckets(self.block.epoch),
+        )
     }
 
-    /// Write the provided tx hash to storage
+    /// Write the provided tx hash to storage, with the expiration of its tx
     pub fn write_replay_protection_entry(
         &mut self,
         batch: &mut D::WriteBatch,
         key: &Key,
+        expiration: Option<DateTimeUtc>,
     ) -> Result<()> {
-        self.db.write_replay_protection_entry(batch, key)
+        self.db.write_replay_protection_entry(
+            batch,
+            key,
+            expiration.serialize_to_vec(),
+        )
     }
 
     /// Delete the provided tx hash from storage
@@ -1205,18 +1263,117 @@ where
         self.db.delete_replay_protection_entry(batch, key)
     }
 
-    /// Iterate the replay protection storage from the last block
+    /// Read the Borsh encoded tx section with the given hash retained at the
+    /// given height
+    pub fn read_tx_section(
+        &self,
+        height: BlockHeight,
+        hash: &Hash,
+    ) -> Result<Option<Vec<u8>>> {
+        self.db.read_tx_section(height, hash)
+    }
+
+    /// Write a Borsh encoded tx section retained at the given height
+    pub fn write_tx_section(
+        &mut self,
+        batch: &mut D::WriteBatch,
+        height: BlockHeight,
+        hash: &Hash,
+        section: &[u8],
+    ) -> Result<()> {
+        self.db.write_tx_section(batch, height, hash, section)
+    }
+
+    /// Delete the tx sections retained at all the heights before the given
+    /// one
+    pub fn prune_tx_sections(
+        &mut self,
+        batch: &mut D::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()> {
+        self.db.prune_tx_sections(batch, before)
+    }
+
+    /// Iterate the replay protection storage from the last block, together
+    /// with the expiration of the txs
     pub fn iter_replay_protection(
         &self,
-    ) -> Box<dyn Iterator<Item = Hash> + '_> {
-        Box::new(self.db.iter_replay_protection().map(|(key, _, _)| {
-            key.rsplit_once('/')
-                .expect("Missing tx hash in storage key")
-                .1
-                .parse()
-                .expect("Failed hash conversion")
+    ) -> Box<dyn Iterator<Item = (Hash, Option<DateTimeUtc>)> + '_> {
+        Box::new(self.db.iter_replay_protection().map(|(key, value, _)| {
+            (
+                parse_replay_protection_hash(&key),
+                decode_replay_protection_expiration(&value),
+            )
         }))
     }
+
+    /// Iterate the bucket of replay protection storage of the given epoch
+    pub fn iter_replay_protection_bucket(
+        &self,
+        epoch: Epoch,
+    ) -> Box<dyn Iterator<Item = Hash> + '_> {
+        Box::new(
+            self.db
+                .iter_replay_protection_bucket(epoch)
+                .map(|(key, _, _)| parse_replay_protection_hash(&key)),
+        )
+    }
+
+    // Prune the buckets of replay protection storage that fell out of the
+    // retention window, carrying over to the bucket of the current epoch the
+    // hashes of the txs that haven't expired at the given block time. Use
+    // after updating self.block.epoch in the commit.
+    fn prune_replay_protection(
+        &mut self,
+        batch: &mut D::WriteBatch,
+        time: DateTimeUtc,
+    ) -> Result<()> {
+        let oldest =
+            replay_protection::oldest_retained_bucket(self.block.epoch);
+        if oldest.0 == 0 {
+            return Ok(());
+        }
+        // The older buckets were already emptied at the previous epochs
+        let unexpired: Vec<(Hash, Option<DateTimeUtc>)> = self
+            .db
+            .iter_replay_protection_bucket(Epoch(oldest.0 - 1))
+            .filter_map(|(key, value, _)| {
+                let expiration = decode_replay_protection_expiration(&value);
+                // A tx can't be included in a block past its expiration, and
+                // the times of the next blocks are past this one
+                match expiration {
+                    Some(expiration) if expiration <= time => None,
+                    _ => Some((parse_replay_protection_hash(&key), expiration)),
+                }
+            })
+            .collect();
+        for (hash, expiration) in unexpired {
+            self.write_replay_protection_entry(
+                batch,
+                &replay_protection::get_replay_protection_bucket_subkey(
+                    self.block.epoch,
+                    &hash,
+                ),
+                expiration,
+            )?;
+        }
+        self.db.prune_replay_protection_buckets(batch, oldest)
+    }
+}
+
+/// Decode the tx expiration stored with a replay protection entry. Entries
+/// written without one never expire.
+fn decode_replay_protection_expiration(value: &[u8]) -> Option<DateTimeUtc> {
+    Option::<DateTimeUtc>::try_from_slice(value).ok().flatten()
+}
+
+/// Parse the tx hash at the end of a replay protection storage key
+fn parse_replay_protection_hash(key: &str) -> Hash {
+    key.rsplit_once('/')
+        .expect("Missing tx hash in storage key")
+        .1
+        .parse()
+        .expect("Failed hash conversion")
 }
 
 impl From<MerkleTreeError> for Error {
@@ -101,10 +101,11 @@ pub trait WriteLogAndStorage {
     /// borrow checker)
     fn split_borrow(&mut self) -> (&mut WriteLog, &Storage<Self::D, Self::H>);
 
-    /// Write the provided tx hash to storage.
+    /// Write the provided tx hash to storage, with the expiration of its tx.
     fn write_tx_hash(
         &mut self,
         hash: Hash,
+        expiration: Option<DateTimeUtc>,
     ) -> crate::ledger::storage::write_log::Result<()>;
 }
 
@@ -135,8 +136,9 @@ where
     fn write_tx_hash(
         &mut self,
         hash: Hash,
+        expiration: Option<DateTimeUtc>,
     ) -> crate::ledger::storage::write_log::Result<()> {
-        self.write_log.write_tx_hash(hash)
+        self.write_log.write_tx_hash(hash, expiration)
     }
 }
 
@@ -167,8 +169,9 @@ where
     fn write_tx_hash(
         &mut self,
         hash: Hash,
+        expiration: Option<DateTimeUtc>,
     ) -> crate::ledger::storage::write_log::Result<()> {
-        self.write_log.write_tx_hash(hash)
+        self.write_log.write_tx_hash(hash, expiration)
     }
 }
 
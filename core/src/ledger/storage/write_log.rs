@@ -11,14 +11,17 @@ use crate::ledger::gas::{
     MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_WRITE_GAS_PER_BYTE,
 };
 use crate::ledger::replay_protection::{
-    get_replay_protection_all_subkey, get_replay_protection_last_subkey,
+    get_replay_protection_all_subkey, get_replay_protection_bucket_subkey,
+    get_replay_protection_last_subkey,
 };
 use crate::ledger::storage::traits::StorageHasher;
 use crate::ledger::storage::Storage;
 use crate::types::address::{Address, EstablishedAddressGen, InternalAddress};
+use crate::types::event::TxEvent;
 use crate::types::hash::Hash;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage;
+use crate::types::time::DateTimeUtc;
 use crate::types::token::{
     is_any_minted_balance_key, is_any_minter_key, is_any_token_balance_key,
 };
@@ -74,12 +77,12 @@ pub enum StorageModification {
 #[derive(Debug, Clone)]
 /// A replay protection storage modification
 enum ReProtStorageModification {
-    /// Write an entry
-    Write,
+    /// Write an entry, with the expiration of its tx
+    Write(Option<DateTimeUtc>),
     /// Delete an entry
     Delete,
-    /// Finalize an entry
-    Finalize,
+    /// Finalize an entry, with the expiration of its tx
+    Finalize(Option<DateTimeUtc>),
 }
 
 /// The write log storage
@@ -103,9 +106,17 @@ pub struct WriteLog {
     tx_precommit_write_log: HashMap<storage::Key, StorageModification>,
     /// The IBC events for the current transaction
     ibc_events: BTreeSet<IbcEvent>,
+    /// The events emitted by the current transaction, in order
+    tx_events: Vec<TxEvent>,
     /// Storage modifications for the replay protection storage, always
     /// committed regardless of the result of the transaction
     replay_protection: HashMap<Hash, ReProtStorageModification>,
+    /// The Borsh encoded sections of the txs included in the current block,
+    /// retained by the node regardless of the result of their transactions
+    tx_sections: HashMap<Hash, Vec<u8>>,
+    /// The height before which the retained tx sections are pruned when
+    /// committing the current block, if any
+    tx_sections_pruned_before: Option<storage::BlockHeight>,
 }
 
 /// Write log prefix iterator
@@ -132,7 +143,10 @@ impl Default for WriteLog {
             tx_write_log: HashMap::with_capacity(100),
             tx_precommit_write_log: HashMap::with_capacity(100),
             ibc_events: BTreeSet::new(),
+            tx_events: Vec::new(),
             replay_protection: HashMap::with_capacity(1_000),
+            tx_sections: HashMap::with_capacity(1_000),
+            tx_sections_pruned_before: None,
         }
     }
 }
@@ -386,6 +400,18 @@ impl WriteLog {
         len as u64 * MEMORY_ACCESS_GAS_PER_BYTE
     }
 
+    /// Set an event of the current transaction and return the gas cost.
+    pub fn emit_tx_event(&mut self, event: TxEvent) -> u64 {
+        let len = event
+            .attributes
+            .iter()
+            .fold(event.event_type.len(), |acc, (k, v)| {
+                acc + k.len() + v.len()
+            });
+        self.tx_events.push(event);
+        len as u64 * MEMORY_ACCESS_GAS_PER_BYTE
+    }
+
     /// Get the storage keys changed and accounts keys initialized in the
     /// current transaction. The account keys point to the validity predicates
     /// of the newly created accounts. The keys in the precommit are not
@@ -450,6 +476,16 @@ impl WriteLog {
         &self.ibc_events
     }
 
+    /// Take the events emitted by the current transaction
+    pub fn take_tx_events(&mut self) -> Vec<TxEvent> {
+        std::mem::take(&mut self.tx_events)
+    }
+
+    /// Get the events emitted by the current transaction
+    pub fn get_tx_events(&self) -> &[TxEvent] {
+        &self.tx_events
+    }
+
     /// Add the entire content of the tx write log to the precommit one. The tx
     /// log gets reset in the process.
     pub fn precommit_tx(&mut self) {
@@ -479,6 +515,7 @@ impl WriteLog {
 
         self.block_write_log.extend(tx_precommit_write_log);
         self.take_ibc_events();
+        self.take_tx_events();
     }
 
     /// Drop the current transaction's write log and precommit when it's
@@ -487,6 +524,7 @@ impl WriteLog {
     pub fn drop_tx(&mut self) {
         self.tx_precommit_write_log.clear();
         self.tx_write_log.clear();
+        self.tx_events.clear();
     }
 
     /// Drop the current transaction's write log but keep the precommit one.
@@ -532,14 +570,16 @@ impl WriteLog {
             }
         }
 
+        let epoch = storage.block.epoch;
         for (hash, entry) in self.replay_protection.iter() {
             match entry {
-                ReProtStorageModification::Write => storage
+                ReProtStorageModification::Write(expiration) => storage
                     .write_replay_protection_entry(
                         batch,
                         // Can only write tx hashes to the previous block, no
                         // further
                         &get_replay_protection_last_subkey(hash),
+                        *expiration,
                     )
                     .map_err(Error::StorageError)?,
                 ReProtStorageModification::Delete => storage
@@ -550,12 +590,17 @@ impl WriteLog {
                         &get_replay_protection_last_subkey(hash),
                     )
                     .map_err(Error::StorageError)?,
-                ReProtStorageModification::Finalize => {
+                ReProtStorageModification::Finalize(expiration) => {
+                    // Finalized tx hashes are bucketed by the epoch of their
+                    // finalization, unless their tx never expires
+                    let key = match expiration {
+                        Some(_) => {
+                            get_replay_protection_bucket_subkey(epoch, hash)
+                        }
+                        None => get_replay_protection_all_subkey(hash),
+                    };
                     storage
-                        .write_replay_protection_entry(
-                            batch,
-                            &get_replay_protection_all_subkey(hash),
-                        )
+                        .write_replay_protection_entry(batch, &key, *expiration)
                         .map_err(Error::StorageError)?;
                     storage
                         .delete_replay_protection_entry(
@@ -567,11 +612,24 @@ impl WriteLog {
             }
         }
 
+        if let Some(before) = self.tx_sections_pruned_before.take() {
+            storage
+                .prune_tx_sections(batch, before)
+                .map_err(Error::StorageError)?;
+        }
+        let height = storage.block.height;
+        for (hash, section) in self.tx_sections.iter() {
+            storage
+                .write_tx_section(batch, height, hash, section)
+                .map_err(Error::StorageError)?;
+        }
+
         if let Some(address_gen) = self.address_gen.take() {
             storage.address_gen = address_gen
         }
         self.block_write_log.clear();
         self.replay_protection.clear();
+        self.tx_sections.clear();
         Ok(())
     }
 
@@ -674,11 +732,15 @@ impl WriteLog {
             .map(|action| !matches!(action, ReProtStorageModification::Delete))
     }
 
-    /// Write the transaction hash
-    pub(crate) fn write_tx_hash(&mut self, hash: Hash) -> Result<()> {
+    /// Write the transaction hash, with the expiration of its transaction
+    pub(crate) fn write_tx_hash(
+        &mut self,
+        hash: Hash,
+        expiration: Option<DateTimeUtc>,
+    ) -> Result<()> {
         if self
             .replay_protection
-            .insert(hash, ReProtStorageModification::Write)
+            .insert(hash, ReProtStorageModification::Write(expiration))
             .is_some()
         {
             // Cannot write an hash if other requests have already been
@@ -699,7 +761,7 @@ impl WriteLog {
         {
             None => Ok(()),
             // Allow overwriting a previous finalize request
-            Some(ReProtStorageModification::Finalize) => Ok(()),
+            Some(ReProtStorageModification::Finalize(_)) => Ok(()),
             Some(_) =>
             // Cannot delete an hash that still has to be written to
             // storage or has already been deleted
@@ -715,10 +777,14 @@ impl WriteLog {
     /// Move the transaction hash of the previous block to the list of all
     /// blocks. This functions should be called at the beginning of the block
     /// processing, before any other replay protection operation is done
-    pub fn finalize_tx_hash(&mut self, hash: Hash) -> Result<()> {
+    pub fn finalize_tx_hash(
+        &mut self,
+        hash: Hash,
+        expiration: Option<DateTimeUtc>,
+    ) -> Result<()> {
         if self
             .replay_protection
-            .insert(hash, ReProtStorageModification::Finalize)
+            .insert(hash, ReProtStorageModification::Finalize(expiration))
             .is_some()
         {
             // Cannot finalize an hash if other requests have already been
@@ -730,6 +796,18 @@ impl WriteLog {
 
         Ok(())
     }
+
+    /// Retain the given tx section, to be persisted together with the current
+    /// block
+    pub fn write_tx_section(&mut self, hash: Hash, section: Vec<u8>) {
+        self.tx_sections.insert(hash, section);
+    }
+
+    /// Prune the tx sections retained at the heights before the given one,
+    /// together with the current block
+    pub fn prune_tx_sections(&mut self, before: storage::BlockHeight) {
+        self.tx_sections_pruned_before = Some(before);
+    }
 }
 
 #[cfg(test)]
@@ -968,13 +1046,13 @@ mod tests {
 
         // write some replay protection keys
         write_log
-            .write_tx_hash(Hash::sha256("tx1".as_bytes()))
+            .write_tx_hash(Hash::sha256("tx1".as_bytes()), None)
             .unwrap();
         write_log
-            .write_tx_hash(Hash::sha256("tx2".as_bytes()))
+            .write_tx_hash(Hash::sha256("tx2".as_bytes()), None)
             .unwrap();
         write_log
-            .write_tx_hash(Hash::sha256("tx3".as_bytes()))
+            .write_tx_hash(Hash::sha256("tx3".as_bytes()), None)
             .unwrap();
 
         // commit a block
@@ -993,13 +1071,13 @@ mod tests {
 
         // write some replay protection keys
         write_log
-            .write_tx_hash(Hash::sha256("tx4".as_bytes()))
+            .write_tx_hash(Hash::sha256("tx4".as_bytes()), None)
             .unwrap();
         write_log
-            .write_tx_hash(Hash::sha256("tx5".as_bytes()))
+            .write_tx_hash(Hash::sha256("tx5".as_bytes()), None)
             .unwrap();
         write_log
-            .write_tx_hash(Hash::sha256("tx6".as_bytes()))
+            .write_tx_hash(Hash::sha256("tx6".as_bytes()), None)
             .unwrap();
 
         // delete previous hash
@@ -1010,7 +1088,7 @@ mod tests {
         // finalize previous hashes
         for tx in ["tx2", "tx3"] {
             write_log
-                .finalize_tx_hash(Hash::sha256(tx.as_bytes()))
+                .finalize_tx_hash(Hash::sha256(tx.as_bytes()), None)
                 .unwrap();
         }
 
@@ -1051,6 +1129,134 @@ mod tests {
         );
     }
 
+    /// Fill the replay protection buckets of several epochs and check that
+    /// the buckets falling out of the retention window are pruned at the
+    /// start of an epoch, except for the hashes whose tx hasn't expired.
+    #[test]
+    fn test_replay_protection_pruning() {
+        use crate::ledger::replay_protection::RETENTION_EPOCHS;
+        use crate::types::storage::{BlockHeight, Epoch};
+        use crate::types::time::Duration;
+
+        let mut storage =
+            crate::ledger::storage::testing::TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let tx_hash = |epoch: u64| Hash::sha256(epoch.to_be_bytes());
+        let expired = Some(DateTimeUtc::default());
+        let unexpired = Hash::sha256("unexpired".as_bytes());
+        let unexpiring = Hash::sha256("unexpiring".as_bytes());
+
+        // One block per epoch, including an expired tx whose hash is
+        // finalized in the bucket of the next epoch
+        let last_epoch = RETENTION_EPOCHS + 2;
+        for epoch in 0..=last_epoch {
+            storage.block.height = BlockHeight(epoch + 1);
+            storage.block.epoch = Epoch(epoch);
+            let finalized: Vec<(Hash, Option<DateTimeUtc>)> =
+                storage.iter_replay_protection().collect();
+            for (hash, expiration) in finalized {
+                write_log.finalize_tx_hash(hash, expiration).unwrap();
+            }
+            write_log.write_tx_hash(tx_hash(epoch), expired).unwrap();
+            if epoch == 0 {
+                write_log
+                    .write_tx_hash(
+                        unexpired,
+                        Some(DateTimeUtc::now() + Duration::days(1)),
+                    )
+                    .unwrap();
+                write_log.write_tx_hash(unexpiring, None).unwrap();
+            }
+            let mut batch =
+                crate::ledger::storage::testing::TestStorage::batch();
+            write_log
+                .commit_block(&mut storage, &mut batch)
+                .expect("commit failed");
+            storage.commit_block(batch).expect("commit failed");
+        }
+
+        // The bucket of the first epoch is empty, and the bucket of the
+        // second epoch was pruned
+        assert_eq!(storage.iter_replay_protection_bucket(Epoch(0)).count(), 0);
+        assert_eq!(storage.iter_replay_protection_bucket(Epoch(1)).count(), 0);
+        assert!(
+            !storage
+                .has_replay_protection_entry(&tx_hash(0))
+                .expect("read failed")
+        );
+        for epoch in 2..=last_epoch {
+            let mut expected = HashSet::from([tx_hash(epoch - 1)]);
+            // The hash of the unexpired tx was carried over from the pruned
+            // bucket to the bucket of the current epoch, while the one of the
+            // tx without expiration was never bucketed
+            if epoch == last_epoch {
+                expected.insert(unexpired);
+            }
+            assert_eq!(
+                storage
+                    .iter_replay_protection_bucket(Epoch(epoch))
+                    .collect::<HashSet<_>>(),
+                expected
+            );
+        }
+        for epoch in 1..=last_epoch {
+            assert!(
+                storage
+                    .has_replay_protection_entry(&tx_hash(epoch))
+                    .expect("read failed")
+            );
+        }
+        for hash in [unexpired, unexpiring] {
+            assert!(
+                storage
+                    .has_replay_protection_entry(&hash)
+                    .expect("read failed")
+            );
+        }
+
+        // A bucket left out of the retention window is ignored, even before
+        // it's pruned
+        let stale = Hash::sha256("stale".as_bytes());
+        let mut batch = crate::ledger::storage::testing::TestStorage::batch();
+        storage
+            .write_replay_protection_entry(
+                &mut batch,
+                &get_replay_protection_bucket_subkey(Epoch(1), &stale),
+                expired,
+            )
+            .unwrap();
+        assert!(
+            !storage
+                .has_replay_protection_entry(&stale)
+                .expect("read failed")
+        );
+
+        // The hash of the oldest retained bucket no longer blocks its tx at
+        // the next epoch
+        storage.block.height = storage.block.height.next_height();
+        storage.block.epoch = storage.block.epoch.next();
+        let batch = crate::ledger::storage::testing::TestStorage::batch();
+        storage.commit_block(batch).expect("commit failed");
+        assert!(
+            !storage
+                .has_replay_protection_entry(&tx_hash(1))
+                .expect("read failed")
+        );
+        assert_eq!(storage.iter_replay_protection_bucket(Epoch(1)).count(), 0);
+        assert!(
+            storage
+                .has_replay_protection_entry(&tx_hash(2))
+                .expect("read failed")
+        );
+        for hash in [unexpired, unexpiring] {
+            assert!(
+                storage
+                    .has_replay_protection_entry(&hash)
+                    .expect("read failed")
+            );
+        }
+    }
+
     prop_compose! {
         fn arb_verifiers_changed_key_tx_all_key()
             (verifiers_from_tx in testing::arb_verifiers_from_tx())
@@ -1,10 +1,17 @@
 //! Cryptographic signature keys storage API
 
+use std::collections::HashMap;
+
+use super::key::ensure_user_key;
 use super::*;
-use crate::types::account::AccountPublicKeysMap;
+use crate::types::account::{AccountPublicKeysMap, VpUpdate};
 use crate::types::address::Address;
 use crate::types::key::*;
-use crate::types::storage::Key;
+use crate::types::storage::{BlockHeight, Key};
+
+/// The maximum number of VP updates kept in the history of an account. Older
+/// entries are pruned when new ones get recorded.
+pub const MAX_VP_HISTORY_LEN: u64 = 16;
 
 /// Init the subspace of a new account
 pub fn init_account_storage<S>(
@@ -17,6 +24,7 @@ where
     S: StorageWrite + StorageRead,
 {
     for (index, public_key) in public_keys.iter().enumerate() {
+        ensure_user_key(public_key)?;
         let index = index as u8;
         pks_handle(owner).insert(storage, index, public_key.clone())?;
     }
@@ -65,6 +73,71 @@ where
     Ok(AccountPublicKeysMap::from_iter(public_keys))
 }
 
+/// Resolves the public keys with which accounts sign txs, given the address
+/// of an account and the index of the key in its set of keys
+pub trait PkResolver<E = Error> {
+    /// Get the public key at `index` in the set of keys of `owner`, if any
+    fn resolve_pk(
+        &mut self,
+        owner: &Address,
+        index: u8,
+    ) -> std::result::Result<Option<common::PublicKey>, E>;
+}
+
+impl<E, F> PkResolver<E> for F
+where
+    F: FnMut(&Address, u8) -> std::result::Result<Option<common::PublicKey>, E>,
+{
+    fn resolve_pk(
+        &mut self,
+        owner: &Address,
+        index: u8,
+    ) -> std::result::Result<Option<common::PublicKey>, E> {
+        self(owner, index)
+    }
+}
+
+/// A [`PkResolver`] that reads the public keys of accounts from storage and
+/// caches them, including the keys found to be missing. The cache is never
+/// invalidated, so a resolver must only be used while the keys of accounts
+/// cannot change, e.g. over the committed state of a block.
+pub struct CachedPkResolver<'a, S> {
+    storage: &'a S,
+    cache: HashMap<(Address, u8), Option<common::PublicKey>>,
+}
+
+impl<'a, S> CachedPkResolver<'a, S>
+where
+    S: StorageRead,
+{
+    /// Resolve the public keys of accounts from the given storage
+    pub fn new(storage: &'a S) -> Self {
+        Self {
+            storage,
+            cache: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, S> PkResolver for CachedPkResolver<'a, S>
+where
+    S: StorageRead,
+{
+    fn resolve_pk(
+        &mut self,
+        owner: &Address,
+        index: u8,
+    ) -> Result<Option<common::PublicKey>> {
+        let key = (owner.clone(), index);
+        if let Some(public_key) = self.cache.get(&key) {
+            return Ok(public_key.clone());
+        }
+        let public_key = pks_handle(owner).get(self.storage, &index)?;
+        self.cache.insert(key, public_key.clone());
+        Ok(public_key)
+    }
+}
+
 /// Check if a user account exists in storage
 pub fn exists<S>(storage: &S, owner: &Address) -> Result<bool>
 where
@@ -90,6 +163,7 @@ pub fn set_public_key_at<S>(
 where
     S: StorageWrite + StorageRead,
 {
+    ensure_user_key(public_key)?;
     pks_handle(owner).insert(storage, index, public_key.clone())?;
     Ok(())
 }
@@ -105,3 +179,91 @@ where
     }
     Ok(())
 }
+
+/// Replace all the public keys and the threshold of an established account.
+/// The new keys must be distinct and the new threshold must be at least 1 and
+/// at most the number of the new keys.
+pub fn change_keys<S>(
+    storage: &mut S,
+    owner: &Address,
+    new_keys: &[common::PublicKey],
+    new_threshold: u8,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    if !matches!(owner, Address::Established(_)) || !exists(storage, owner)? {
+        return Err(Error::new_const(
+            "Only the keys of an existing established account can be changed",
+        ));
+    }
+    if new_keys.len() > usize::from(u8::MAX) {
+        return Err(Error::new_const("Too many public keys"));
+    }
+    if new_threshold == 0 || usize::from(new_threshold) > new_keys.len() {
+        return Err(Error::new_const(
+            "The threshold must be at least 1 and at most the number of the \
+             public keys",
+        ));
+    }
+    let distinct_keys: std::collections::HashSet<_> = new_keys.iter().collect();
+    if distinct_keys.len() != new_keys.len() {
+        return Err(Error::new_const("The public keys must be distinct"));
+    }
+
+    clear_public_keys(storage, owner)?;
+    init_account_storage(storage, owner, new_keys, new_threshold)
+}
+
+/// Record an update of an account's VP applied at the given block height.
+///
+/// Only one entry is kept per block height: when an account's VP is updated
+/// several times in the same block, the entry keeps the VP code hash from
+/// before the first update. The history is pruned to the last
+/// [`MAX_VP_HISTORY_LEN`] entries.
+pub fn record_vp_update<S>(
+    storage: &mut S,
+    owner: &Address,
+    height: BlockHeight,
+    update: VpUpdate,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    let handle = vp_history_handle(owner);
+    let update = match handle.get(storage, &height)? {
+        Some(VpUpdate {
+            old_vp_code_hash, ..
+        }) => VpUpdate {
+            old_vp_code_hash,
+            ..update
+        },
+        None => update,
+    };
+    handle.insert(storage, height, update)?;
+
+    let len = handle.len(storage)?;
+    if len > MAX_VP_HISTORY_LEN {
+        let pruned = handle
+            .iter(storage)?
+            .take((len - MAX_VP_HISTORY_LEN) as usize)
+            .map(|entry| entry.map(|(height, _)| height))
+            .collect::<Result<Vec<BlockHeight>>>()?;
+        for height in pruned {
+            handle.remove(storage, &height)?;
+        }
+    }
+    Ok(())
+}
+
+/// Get the recorded history of an account's VP updates, in the order in
+/// which they were applied
+pub fn vp_history<S>(
+    storage: &S,
+    owner: &Address,
+) -> Result<Vec<(BlockHeight, VpUpdate)>>
+where
+    S: StorageRead,
+{
+    vp_history_handle(owner).iter(storage)?.collect()
+}
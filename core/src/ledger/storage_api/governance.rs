@@ -30,6 +30,20 @@ pub fn init_proposal<S>(
 where
     S: StorageRead + StorageWrite,
 {
+    let params = get_parameters(storage)?;
+    if content.len() as u64 > params.max_proposal_content_size {
+        return Err(storage_api::Error::new_const(
+            "Proposal content exceeds the maximum size",
+        ));
+    }
+    if let Some(code) = &code {
+        if code.len() as u64 > params.max_proposal_code_size {
+            return Err(storage_api::Error::new_const(
+                "Proposal code exceeds the maximum size",
+            ));
+        }
+    }
+
     let counter_key = governance_keys::get_counter_key();
     let proposal_id = if let Some(id) = data.id {
         id
@@ -79,10 +93,7 @@ where
 
     storage.write(&counter_key, proposal_id + 1)?;
 
-    let min_proposal_funds_key = governance_keys::get_min_proposal_fund_key();
-    let min_proposal_funds: token::Amount =
-        storage.read(&min_proposal_funds_key)?.unwrap();
-
+    let min_proposal_funds = params.min_proposal_fund;
     let funds_key = governance_keys::get_funds_key(proposal_id);
     storage.write(&funds_key, min_proposal_funds)?;
 
@@ -111,6 +122,26 @@ pub fn vote_proposal<S>(
 where
     S: StorageRead + StorageWrite,
 {
+    let start_epoch_key = governance_keys::get_voting_start_epoch_key(data.id);
+    let end_epoch_key = governance_keys::get_voting_end_epoch_key(data.id);
+    let (start_epoch, end_epoch): (Epoch, Epoch) = match (
+        storage.read(&start_epoch_key)?,
+        storage.read(&end_epoch_key)?,
+    ) {
+        (Some(start_epoch), Some(end_epoch)) => (start_epoch, end_epoch),
+        _ => {
+            return Err(storage_api::Error::new_const(
+                "The voted proposal doesn't exist",
+            ));
+        }
+    };
+    let current_epoch = storage.get_block_epoch()?;
+    if current_epoch < start_epoch || current_epoch > end_epoch {
+        return Err(storage_api::Error::new_const(
+            "The voted proposal is not in its voting period",
+        ));
+    }
+
     for delegation in data.delegations {
         let vote_key = governance_keys::get_vote_proposal_key(
             data.id,
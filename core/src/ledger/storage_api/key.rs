@@ -4,8 +4,21 @@ use super::*;
 use crate::types::address::Address;
 use crate::types::key::*;
 
+/// Check that the given PK can be used by a user account. Protocol-only keys,
+/// such as BLS keys, are rejected.
+pub fn ensure_user_key(public_key: &common::PublicKey) -> Result<()> {
+    if public_key.is_user_key() {
+        Ok(())
+    } else {
+        Err(Error::new_const(
+            "Protocol-only keys cannot be used by user accounts",
+        ))
+    }
+}
+
 /// Reveal a PK of an implicit account - the PK is written into the storage
-/// of the address derived from the PK.
+/// of the address derived from the PK. Revealing a PK that's already revealed
+/// is a no-op.
 pub fn reveal_pk<S>(
     storage: &mut S,
     public_key: &common::PublicKey,
@@ -13,8 +26,13 @@ pub fn reveal_pk<S>(
 where
     S: StorageWrite + StorageRead,
 {
+    ensure_user_key(public_key)?;
     let owner: Address = public_key.into();
-    pks_handle(&owner).insert(storage, 0, public_key.clone())?;
+    let pks = pks_handle(&owner);
+    if pks.get(storage, &0)?.as_ref() == Some(public_key) {
+        return Ok(());
+    }
+    pks.insert(storage, 0, public_key.clone())?;
 
     Ok(())
 }
@@ -0,0 +1,450 @@
+//! Data types and functions for a testnet "faucet" account that allows anyone
+//! to withdraw a limited amount of tokens from it without the faucet's
+//! signature, but with a valid proof-of-work (PoW) solution of a challenge.
+//!
+//! A challenge is given by the faucet's PoW difficulty and a counter of the
+//! requester's used solutions, so that a solution cannot be replayed. A
+//! requester must also wait for the faucet's cooldown period since its last
+//! withdrawal before it can withdraw again.
+//!
+//! The solution is attached to a wrapper tx in a [`Section::PowSolution`]. The
+//! protocol validates and invalidates the solution when it applies the
+//! wrapper tx and lets the faucet's VP know whether the inner tx had a valid
+//! PoW.
+//!
+//! [`Section::PowSolution`]: crate::proto::Section::PowSolution
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
+use namada_macros::StorageKeys;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ledger::storage_api::collections::{LazyCollection, LazyMap};
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
+use crate::types::address::Address;
+use crate::types::storage::{DbKeySeg, Key};
+use crate::types::token;
+
+/// The maximum PoW difficulty
+pub const MAX_DIFFICULTY: u8 = 64;
+
+/// Storage keys for the faucet's PoW data in the faucet's subspace.
+#[derive(StorageKeys)]
+struct Keys {
+    difficulty: &'static str,
+    counters: &'static str,
+    withdrawal_limit: &'static str,
+    cooldown: &'static str,
+    last_withdrawals: &'static str,
+}
+
+/// PoW difficulty, given by the number of leading zero bits of a valid
+/// solution's hash.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Difficulty(u8);
+
+impl Difficulty {
+    /// Try to create a new difficulty. Returns `None` for a difficulty larger
+    /// than [`MAX_DIFFICULTY`].
+    pub fn try_new(raw: u8) -> Option<Self> {
+        (raw <= MAX_DIFFICULTY).then_some(Self(raw))
+    }
+}
+
+/// A counter of the used solutions of a requester
+pub type Counter = u64;
+
+/// A value that solves a challenge
+pub type SolutionValue = u64;
+
+/// The parameters of a challenge read from the faucet's storage
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct ChallengeParams {
+    /// The faucet's PoW difficulty
+    pub difficulty: Difficulty,
+    /// The requester's counter of used solutions
+    pub counter: Counter,
+}
+
+/// A PoW challenge for a requester of a faucet withdrawal
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Challenge {
+    /// The faucet's address
+    pub faucet: Address,
+    /// The address of the requester, which is also the destination of the
+    /// withdrawal
+    pub source: Address,
+    /// The parameters of the challenge
+    pub params: ChallengeParams,
+}
+
+/// A solution of a PoW challenge
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Solution {
+    /// The solved challenge
+    pub challenge: Challenge,
+    /// The solution value
+    pub value: SolutionValue,
+}
+
+impl Challenge {
+    /// Obtain the current PoW challenge of the given faucet for the given
+    /// requester.
+    pub fn new<S>(
+        storage: &S,
+        faucet: &Address,
+        source: Address,
+    ) -> storage_api::Result<Self>
+    where
+        S: StorageRead,
+    {
+        let difficulty = read_difficulty(storage, faucet)?;
+        let counter = read_counter(storage, faucet, &source)?;
+        Ok(Self {
+            faucet: faucet.clone(),
+            source,
+            params: ChallengeParams {
+                difficulty,
+                counter,
+            },
+        })
+    }
+
+    /// Find a solution of this challenge by brute force.
+    pub fn solve(self) -> Solution {
+        let mut solution = Solution {
+            challenge: self,
+            value: 0,
+        };
+        while !solution.verify_solution() {
+            solution.value += 1;
+        }
+        solution
+    }
+}
+
+impl Solution {
+    /// Check that the hash of the challenge with the solution value satisfies
+    /// the challenge's difficulty.
+    pub fn verify_solution(&self) -> bool {
+        let mut hasher = Sha256::new();
+        hasher.update(self.challenge.serialize_to_vec());
+        hasher.update(self.value.to_le_bytes());
+        let hash: [u8; 32] = hasher.finalize().into();
+
+        let mut leading_zeros = 0_u32;
+        for byte in hash {
+            leading_zeros += byte.leading_zeros();
+            if byte != 0 {
+                break;
+            }
+        }
+        leading_zeros >= u32::from(self.challenge.params.difficulty.0)
+    }
+
+    /// Validate the solution against the faucet's current state. The
+    /// challenge must match the faucet's difficulty and the requester's
+    /// counter, the requester's cooldown period since the last withdrawal
+    /// must have elapsed and the solution must satisfy the difficulty.
+    pub fn validate<S>(&self, storage: &S) -> storage_api::Result<bool>
+    where
+        S: StorageRead,
+    {
+        let Challenge {
+            faucet,
+            source,
+            params,
+        } = &self.challenge;
+
+        // A faucet must have its difficulty in storage
+        let difficulty_key = difficulty_key(faucet);
+        if !storage.has_key(&difficulty_key)? {
+            return Ok(false);
+        }
+        let current_params = ChallengeParams {
+            difficulty: read_difficulty(storage, faucet)?,
+            counter: read_counter(storage, faucet, source)?,
+        };
+        if params != &current_params {
+            return Ok(false);
+        }
+
+        if let Some(last_withdrawal) =
+            last_withdrawals_handle(faucet).get(storage, source)?
+        {
+            let cooldown = read_cooldown(storage, faucet)?;
+            let current_height = storage.get_block_height()?.0;
+            if current_height < last_withdrawal.saturating_add(cooldown) {
+                return Ok(false);
+            }
+        }
+
+        Ok(self.verify_solution())
+    }
+
+    /// Validate the solution and if it's valid, invalidate it for any further
+    /// use by incrementing the requester's counter and record the height of
+    /// the requester's withdrawal for the cooldown. Returns the validity of
+    /// the solution.
+    pub fn invalidate_if_valid<S>(
+        &self,
+        storage: &mut S,
+    ) -> storage_api::Result<bool>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        if !self.validate(storage)? {
+            return Ok(false);
+        }
+        let Challenge {
+            faucet,
+            source,
+            params,
+        } = &self.challenge;
+        counters_handle(faucet).insert(
+            storage,
+            source.clone(),
+            params.counter + 1,
+        )?;
+        let current_height = storage.get_block_height()?.0;
+        last_withdrawals_handle(faucet).insert(
+            storage,
+            source.clone(),
+            current_height,
+        )?;
+        Ok(true)
+    }
+}
+
+/// Storage key of the faucet's PoW difficulty
+pub fn difficulty_key(faucet: &Address) -> Key {
+    faucet_key(faucet, Keys::VALUES.difficulty)
+}
+
+/// Storage key of the faucet's withdrawal limit per tx
+pub fn withdrawal_limit_key(faucet: &Address) -> Key {
+    faucet_key(faucet, Keys::VALUES.withdrawal_limit)
+}
+
+/// Storage key of the faucet's cooldown period, in number of blocks, between
+/// two withdrawals of a requester
+pub fn cooldown_key(faucet: &Address) -> Key {
+    faucet_key(faucet, Keys::VALUES.cooldown)
+}
+
+/// LazyMap handle of the requesters' counters of used solutions
+pub fn counters_handle(faucet: &Address) -> LazyMap<Address, Counter> {
+    LazyMap::open(faucet_key(faucet, Keys::VALUES.counters))
+}
+
+/// LazyMap handle of the block heights of the requesters' last withdrawals
+pub fn last_withdrawals_handle(faucet: &Address) -> LazyMap<Address, u64> {
+    LazyMap::open(faucet_key(faucet, Keys::VALUES.last_withdrawals))
+}
+
+fn faucet_key(faucet: &Address, segment: &str) -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(faucet.clone()),
+            DbKeySeg::StringSeg(segment.to_string()),
+        ],
+    }
+}
+
+/// Initialize the faucet's PoW data in its storage.
+pub fn init_faucet_storage<S>(
+    storage: &mut S,
+    faucet: &Address,
+    difficulty: Difficulty,
+    withdrawal_limit: token::Amount,
+    cooldown: u64,
+) -> storage_api::Result<()>
+where
+    S: StorageWrite,
+{
+    storage.write(&difficulty_key(faucet), difficulty)?;
+    storage.write(&withdrawal_limit_key(faucet), withdrawal_limit)?;
+    storage.write(&cooldown_key(faucet), cooldown)
+}
+
+/// Read the faucet's PoW difficulty
+pub fn read_difficulty<S>(
+    storage: &S,
+    faucet: &Address,
+) -> storage_api::Result<Difficulty>
+where
+    S: StorageRead,
+{
+    storage.read(&difficulty_key(faucet))?.ok_or_else(|| {
+        storage_api::Error::new_const("Missing the faucet's PoW difficulty")
+    })
+}
+
+/// Read the faucet's withdrawal limit per tx
+pub fn read_withdrawal_limit<S>(
+    storage: &S,
+    faucet: &Address,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    storage.read(&withdrawal_limit_key(faucet))?.ok_or_else(|| {
+        storage_api::Error::new_const("Missing the faucet's withdrawal limit")
+    })
+}
+
+/// Read the faucet's cooldown period. Defaults to no cooldown.
+pub fn read_cooldown<S>(
+    storage: &S,
+    faucet: &Address,
+) -> storage_api::Result<u64>
+where
+    S: StorageRead,
+{
+    Ok(storage.read(&cooldown_key(faucet))?.unwrap_or_default())
+}
+
+/// Read the requester's counter of used solutions
+pub fn read_counter<S>(
+    storage: &S,
+    faucet: &Address,
+    source: &Address,
+) -> storage_api::Result<Counter>
+where
+    S: StorageRead,
+{
+    Ok(counters_handle(faucet)
+        .get(storage, source)?
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::storage::BlockHeight;
+
+    const COOLDOWN: u64 = 10;
+
+    fn init_faucet(storage: &mut TestWlStorage) -> Address {
+        let faucet = established_address_1();
+        init_faucet_storage(
+            storage,
+            &faucet,
+            Difficulty::try_new(1).unwrap(),
+            token::Amount::native_whole(1_000),
+            COOLDOWN,
+        )
+        .unwrap();
+        faucet
+    }
+
+    #[test]
+    fn test_difficulty_bounds() {
+        assert!(Difficulty::try_new(MAX_DIFFICULTY).is_some());
+        assert!(Difficulty::try_new(MAX_DIFFICULTY + 1).is_none());
+    }
+
+    #[test]
+    fn test_solution_cannot_be_replayed() {
+        let mut storage = TestWlStorage::default();
+        let faucet = init_faucet(&mut storage);
+        let source = established_address_2();
+
+        let solution = Challenge::new(&storage, &faucet, source.clone())
+            .unwrap()
+            .solve();
+        assert!(solution.verify_solution());
+        assert!(solution.invalidate_if_valid(&mut storage).unwrap());
+        assert_eq!(read_counter(&storage, &faucet, &source).unwrap(), 1);
+
+        // The same solution is no longer valid
+        assert!(!solution.validate(&storage).unwrap());
+    }
+
+    #[test]
+    fn test_solution_cooldown() {
+        let mut storage = TestWlStorage::default();
+        let faucet = init_faucet(&mut storage);
+        let source = established_address_2();
+        storage.storage.block.height = BlockHeight(1);
+
+        let solution = Challenge::new(&storage, &faucet, source.clone())
+            .unwrap()
+            .solve();
+        assert!(solution.invalidate_if_valid(&mut storage).unwrap());
+
+        // A new solution is invalid within the cooldown period...
+        let solution =
+            Challenge::new(&storage, &faucet, source).unwrap().solve();
+        storage.storage.block.height = BlockHeight(COOLDOWN);
+        assert!(!solution.validate(&storage).unwrap());
+
+        // ...and valid after it
+        storage.storage.block.height = BlockHeight(1 + COOLDOWN);
+        assert!(solution.validate(&storage).unwrap());
+    }
+
+    #[test]
+    fn test_solution_of_unknown_faucet() {
+        let storage = TestWlStorage::default();
+        let solution = Solution {
+            challenge: Challenge {
+                faucet: established_address_1(),
+                source: established_address_2(),
+                params: ChallengeParams {
+                    difficulty: Difficulty::try_new(0).unwrap(),
+                    counter: 0,
+                },
+            },
+            value: 0,
+        };
+        assert!(solution.verify_solution());
+        assert!(!solution.validate(&storage).unwrap());
+    }
+}
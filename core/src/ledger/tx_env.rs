@@ -5,8 +5,10 @@ use borsh::BorshSerialize;
 
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::address::Address;
+use crate::types::event::TxEvent;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage;
+use crate::types::transaction::TxWasmError;
 
 /// Transaction host functions
 pub trait TxEnv: StorageRead + StorageWrite {
@@ -58,6 +60,13 @@ pub trait TxEnv: StorageRead + StorageWrite {
         event: &IbcEvent,
     ) -> Result<(), storage_api::Error>;
 
+    /// Emit an event of the transaction. On multiple calls, the events are
+    /// reported in the order in which they were emitted.
+    fn emit_event(
+        &mut self,
+        event: &TxEvent,
+    ) -> Result<(), storage_api::Error>;
+
     /// Request to charge the provided amount of gas for the current transaction
     fn charge_gas(&mut self, used_gas: u64) -> Result<(), storage_api::Error>;
 
@@ -69,4 +78,7 @@ pub trait TxEnv: StorageRead + StorageWrite {
 
     /// Set the sentinel for an invalid section commitment
     fn set_commitment_sentinel(&mut self);
+
+    /// Report the error with which the tx is about to abort to the protocol
+    fn set_error(&mut self, error: &TxWasmError);
 }
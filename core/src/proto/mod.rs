@@ -1,19 +1,31 @@
 #![allow(missing_docs)]
 
 pub mod generated;
+pub mod schema;
+mod section_bytes;
+mod section_policy;
+mod signed_json;
+mod summary;
 mod types;
 
+pub use section_bytes::SectionBytes;
+pub use section_policy::{
+    allowed_sections, SectionCount, SectionKind, SectionPolicyError,
+};
+pub use summary::{TxAction, TxSummary};
 pub use types::{
-    standalone_signature, verify_standalone_sig, Code, Commitment,
-    CompressedSignature, Data, Error, Header, MaspBuilder, Section,
-    SerializeWithBorsh, Signable, SignableEthMessage, Signature,
-    SignatureIndex, Signed, Signer, Tx, TxError,
+    standalone_signature, verify_standalone_sig, AggregatedSignature,
+    Ciphertext, Code, Commitment, CompressedSignature, Data, Error, Header,
+    MaspBuilder, Section, SerializeWithBorsh, SigFailure, Signable,
+    SignableEthMessage, Signature, SignatureIndex, Signed, Signer, Tx, TxError,
+    MAX_MEMO_LEN,
 };
 
 #[cfg(test)]
 mod tests {
     use data_encoding::HEXLOWER;
     use generated::types::Tx;
+    use proptest::prelude::*;
     use prost::Message;
 
     use super::*;
@@ -21,7 +33,7 @@ mod tests {
     #[test]
     fn encoding_round_trip() {
         let tx = Tx {
-            data: "arbitrary data".as_bytes().to_owned(),
+            data: "arbitrary data".as_bytes().to_owned().into(),
         };
         let mut tx_bytes = vec![];
         tx.encode(&mut tx_bytes).unwrap();
@@ -30,4 +42,610 @@ mod tests {
         let tx_from_bytes = Tx::decode(&tx_from_hex[..]).unwrap();
         assert_eq!(tx, tx_from_bytes);
     }
+
+    #[test]
+    fn tendermint_hash_matches_broadcast_bytes() {
+        use sha2::{Digest, Sha256};
+
+        use crate::types::chain::ChainId;
+        use crate::types::hash::Hash;
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_code("wasm code".as_bytes().to_owned(), None)
+            .add_serialized_data("arbitrary data".as_bytes().to_owned());
+        // The bytes that would be broadcast to CometBFT
+        let tx_bytes = tx.to_bytes();
+        let expected = Hash(Sha256::digest(&tx_bytes).into());
+        assert_eq!(tx.tendermint_hash(), expected);
+        // The header hash commits to different data
+        assert_ne!(tx.header_hash(), expected);
+    }
+
+    #[test]
+    fn memo_is_committed_in_header() {
+        use crate::types::chain::ChainId;
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_serialized_data("arbitrary data".as_bytes().to_owned());
+        assert_eq!(tx.memo(), None);
+        let header_hash = tx.header_hash();
+
+        let memo_hash = tx.add_memo("a memo".as_bytes()).1;
+        assert_eq!(*tx.memo_sechash(), memo_hash);
+        assert_eq!(tx.memo(), Some("a memo".as_bytes().to_vec()));
+        // Signing the header authorizes the memo
+        assert_ne!(tx.header_hash(), header_hash);
+    }
+
+    #[test]
+    fn json_round_trip_is_exact() {
+        use crate::types::address::nam;
+        use crate::types::chain::ChainId;
+        use crate::types::key::testing::keypair_1;
+        use crate::types::key::RefTo;
+        use crate::types::storage::Epoch;
+        use crate::types::time::DateTimeUtc;
+        use crate::types::token;
+        use crate::types::transaction::{Fee, GasLimit};
+
+        let keypair = keypair_1();
+        let mut tx =
+            types::Tx::new(ChainId::default(), Some(DateTimeUtc::now()));
+        tx.add_code("wasm code".as_bytes().to_owned(), Some("tag".into()))
+            .add_serialized_data("arbitrary data".as_bytes().to_owned())
+            .add_memo("a memo".as_bytes());
+        tx.add_wrapper(
+            Fee {
+                amount_per_gas_unit: token::Amount::native_whole(1),
+                token: nam(),
+            },
+            keypair.ref_to(),
+            Epoch(1),
+            GasLimit::from(100_000),
+            None,
+        )
+        .sign_wrapper(keypair);
+
+        let json = tx.to_json_pretty();
+        let decoded = types::Tx::from_json(&json).unwrap();
+        assert_eq!(decoded.to_bytes(), tx.to_bytes());
+        assert_eq!(decoded.to_json_pretty(), json);
+    }
+
+    #[test]
+    fn resolve_signer_keys_from_storage() {
+        use std::collections::BTreeMap;
+
+        use assert_matches::assert_matches;
+
+        use crate::ledger::storage::testing::TestWlStorage;
+        use crate::ledger::storage_api::account::CachedPkResolver;
+        use crate::ledger::storage_api::key::reveal_pk;
+        use crate::types::address::Address;
+        use crate::types::chain::ChainId;
+        use crate::types::key::testing::{keypair_1, keypair_2};
+        use crate::types::key::{common, RefTo};
+
+        let mut storage = TestWlStorage::default();
+        let revealed = keypair_1();
+        reveal_pk(&mut storage, &revealed.ref_to()).unwrap();
+        let unrevealed = keypair_2();
+
+        let mut resolver = CachedPkResolver::new(&storage);
+        for (keypair, is_revealed) in [(revealed, true), (unrevealed, false)] {
+            let owner = Address::from(&keypair.ref_to());
+            let mut tx = types::Tx::new(ChainId::default(), None);
+            tx.add_serialized_data("arbitrary data".as_bytes().to_owned());
+            // Sign on behalf of the account, which identifies the key only
+            // by its index
+            let hashes = [tx.raw_header_hash()];
+            let keypairs: BTreeMap<u8, common::SecretKey> =
+                [(0, keypair)].into_iter().collect();
+            tx.add_section(Section::Signature(Signature::new(
+                hashes.to_vec(),
+                keypairs,
+                Some(owner.clone()),
+            )));
+
+            let keys = tx
+                .resolve_signer_keys(&hashes, &owner, &mut resolver)
+                .unwrap();
+            let result = tx.verify_signatures(
+                &hashes,
+                keys,
+                &Some(owner.clone()),
+                1,
+                None,
+                || Ok(()),
+            );
+            if is_revealed {
+                assert!(result.is_ok());
+            } else {
+                assert_matches!(
+                    result,
+                    Err(Error::UnknownSigner(addr, 0)) if addr == owner
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn verify_signature_error_variants() {
+        use std::collections::BTreeMap;
+
+        use assert_matches::assert_matches;
+
+        use crate::types::chain::ChainId;
+        use crate::types::key::testing::{keypair_1, keypair_2, keypair_3};
+        use crate::types::key::{RefTo, VerifySigError};
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_serialized_data("arbitrary data".as_bytes().to_owned());
+        let hashes = [tx.raw_header_hash()];
+
+        // Nothing signs over the header yet
+        assert_matches!(
+            tx.verify_signature(&keypair_1().ref_to(), &hashes),
+            Err(Error::InvalidWrapperSignature(
+                VerifySigError::NoSignatureForTarget { target }
+            )) if target == hashes[0]
+        );
+
+        let mut forged = tx.clone();
+
+        // The header is signed, but by another key
+        tx.add_section(Section::Signature(Signature::new(
+            hashes.to_vec(),
+            [(0, keypair_1())].into_iter().collect::<BTreeMap<_, _>>(),
+            None,
+        )));
+        assert!(tx.verify_signature(&keypair_1().ref_to(), &hashes).is_ok());
+        assert_matches!(
+            tx.verify_signature(&keypair_2().ref_to(), &hashes),
+            Err(Error::InvalidWrapperSignature(
+                VerifySigError::SignerMismatch { expected }
+            )) if expected == keypair_2().ref_to()
+        );
+
+        // The section claims a signer whose signature doesn't verify
+        let mut section = Signature::new(
+            hashes.to_vec(),
+            [(0, keypair_1())].into_iter().collect::<BTreeMap<_, _>>(),
+            None,
+        );
+        section.signer = Signer::PubKeys(vec![keypair_2().ref_to()]);
+        let raw_hash = section.get_raw_hash();
+        forged.add_section(Section::Signature(section));
+        assert_matches!(
+            forged.verify_signature(&keypair_2().ref_to(), &hashes),
+            Err(Error::InvalidWrapperSignature(
+                VerifySigError::InvalidSignature { target, signer }
+            )) if target == raw_hash && signer == keypair_2().ref_to()
+        );
+
+        // Standalone signatures report the signer and the scheme
+        let signed =
+            Signed::<_, SerializeWithBorsh>::new(&keypair_1(), hashes[0]);
+        assert!(signed.verify(&keypair_1().ref_to()).is_ok());
+        assert_matches!(
+            signed.verify(&keypair_2().ref_to()),
+            Err(VerifySigError::InvalidSignature { signer, .. })
+                if signer == keypair_2().ref_to()
+        );
+        assert_matches!(
+            signed.verify(&keypair_3().ref_to()),
+            Err(VerifySigError::MismatchedScheme)
+        );
+    }
+
+    #[test]
+    fn json_decode_encrypted_sections() {
+        use crate::types::chain::ChainId;
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_serialized_data("arbitrary data".as_bytes().to_owned());
+        let ciphertext_hash = tx
+            .add_section(Section::Ciphertext(Ciphertext {
+                opaque: "encrypted sections".as_bytes().to_owned().into(),
+            }))
+            .get_hash();
+
+        let json = tx.to_json_pretty();
+        assert!(json.contains("\"Ciphertext\""));
+        let decoded = types::Tx::from_json(&json).unwrap();
+        assert_eq!(decoded.to_bytes(), tx.to_bytes());
+        let section = decoded.get_section(&ciphertext_hash).unwrap();
+        assert_eq!(
+            section.ciphertext().unwrap().opaque,
+            "encrypted sections".as_bytes().to_vec()
+        );
+
+        // Malformed dumps are rejected
+        assert!(types::Tx::from_json(&json[1..]).is_err());
+    }
+
+    #[test]
+    fn sealed_len_bounds_the_sealed_encoding() {
+        use borsh_ext::BorshSerializeExt;
+
+        use crate::types::chain::ChainId;
+
+        for data_len in [0, 100, 16_000, 100_000] {
+            let mut tx = types::Tx::new(ChainId::default(), None);
+            tx.add_serialized_data(vec![1; data_len]);
+            tx.add_memo(b"memo");
+            let estimate = tx.sealed_len();
+
+            // Seal every section, emulating the encryption overhead
+            let mut sealed = tx.clone();
+            for section in &mut sealed.sections {
+                let len = section.serialize_to_vec().len();
+                *section = Section::Ciphertext(Ciphertext {
+                    opaque: vec![0; len + Ciphertext::ENCRYPTION_OVERHEAD]
+                        .into(),
+                });
+            }
+            assert_eq!(sealed.to_bytes().len(), estimate);
+
+            // Only the protobuf length prefix may grow on top of the modeled
+            // per-section overhead
+            let delta = estimate - tx.to_bytes().len();
+            let modeled = tx.sections.len() * Ciphertext::SECTION_OVERHEAD;
+            assert!(delta >= modeled);
+            assert!(delta - modeled <= 2);
+
+            // Sealed sections are not sealed again
+            assert_eq!(sealed.sealed_len(), estimate);
+        }
+    }
+
+    #[test]
+    fn section_hashes_are_memoized() {
+        use borsh::BorshDeserialize;
+        use borsh_ext::BorshSerializeExt;
+        use sha2::{Digest, Sha256};
+
+        use crate::types::chain::ChainId;
+        use crate::types::hash::Hash;
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1; 1000], None)
+            .add_serialized_data(vec![2; 1000])
+            .add_extra_section(vec![3; 1000], None);
+        tx.add_section(Section::Ciphertext(Ciphertext {
+            opaque: vec![4; 1000].into(),
+        }));
+
+        for section in &tx.sections {
+            assert_eq!(section.serialize_to_vec()[0], section.discriminant());
+            let payload = match section {
+                Section::Data(data) => &data.data,
+                Section::Code(code) | Section::ExtraData(code) => {
+                    match &code.code {
+                        Commitment::Id(code) => code,
+                        Commitment::Hash(_) => unreachable!(),
+                    }
+                }
+                Section::Ciphertext(ct) => &ct.opaque,
+                _ => unreachable!(),
+            };
+            assert!(!payload.is_memoized());
+            let hash = section.get_hash();
+            assert!(payload.is_memoized());
+            assert_eq!(section.get_hash(), hash);
+            // The memoized hash is the one of the encoding of the section
+            let decoded =
+                Section::try_from_slice(&section.serialize_to_vec()).unwrap();
+            assert_eq!(decoded.get_hash(), hash);
+            assert_eq!(
+                Hash(section.hash(&mut Sha256::new()).finalize_reset().into()),
+                hash
+            );
+        }
+        assert_eq!(tx.code(), Some(vec![1; 1000]));
+        assert_eq!(tx.data(), Some(vec![2; 1000]));
+    }
+
+    #[test]
+    fn mutations_invalidate_section_hashes() {
+        use borsh::BorshDeserialize;
+        use borsh_ext::BorshSerializeExt;
+
+        use crate::types::chain::ChainId;
+
+        /// The hash of the section, computed from scratch
+        fn fresh_hash(section: &Section) -> crate::types::hash::Hash {
+            Section::try_from_slice(&section.serialize_to_vec())
+                .unwrap()
+                .get_hash()
+        }
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1; 1000], None)
+            .add_serialized_data(vec![2; 1000]);
+        let hashes = tx.sechashes();
+
+        // Mutating the payloads or the fields preceding them
+        for section in &mut tx.sections {
+            match section {
+                Section::Data(data) => {
+                    data.data.modify(|data| data[0] = 0);
+                }
+                Section::Code(code) => {
+                    code.salt = [0xff; 8];
+                }
+                _ => unreachable!(),
+            }
+        }
+        // Replacing a payload
+        let modified_hash = tx.sections[1].get_hash();
+        let Section::Data(data) = &mut tx.sections[1] else {
+            unreachable!()
+        };
+        data.data = vec![5; 10].into();
+        assert_ne!(tx.sections[1].get_hash(), modified_hash);
+        for (section, hash) in tx.sections.iter().zip(&hashes[1..]) {
+            assert_ne!(section.get_hash(), *hash);
+            assert_eq!(section.get_hash(), fresh_hash(section));
+        }
+        assert!(tx.get_section(&hashes[2]).is_none());
+
+        // Decrypting a ciphertext section in place
+        let plaintext = tx.sections[1].clone();
+        let ciphertext = Section::Ciphertext(Ciphertext {
+            opaque: plaintext.serialize_to_vec().into(),
+        });
+        let ciphertext_hash = ciphertext.get_hash();
+        tx.sections[1] = ciphertext;
+        assert!(tx.get_section(&ciphertext_hash).is_some());
+        tx.sections[1] = plaintext;
+        assert!(tx.get_section(&ciphertext_hash).is_none());
+        let plaintext_hash = fresh_hash(&tx.sections[1]);
+        assert_eq!(tx.sections[1].get_hash(), plaintext_hash);
+        assert!(tx.get_section(&plaintext_hash).is_some());
+    }
+
+    #[test]
+    fn txs_with_out_of_range_times_are_invalid() {
+        use assert_matches::assert_matches;
+
+        use crate::types::chain::ChainId;
+        use crate::types::time::{DateTimeUtc, TimestampError, MIN_UTC};
+
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_serialized_data("arbitrary data".as_bytes().to_owned());
+        assert!(tx.validate_tx().is_ok());
+
+        let year_0: DateTimeUtc = "0000-12-31T23:59:59Z".parse().unwrap();
+        let mut expiring = tx.clone();
+        expiring.header.expiration = Some(year_0);
+        tx.header.timestamp = MIN_UTC;
+        for tx in [tx, expiring] {
+            assert_matches!(
+                tx.validate_tx(),
+                Err(TxError::InvalidTimestamp(
+                    TimestampError::SecondsOutOfRange(_)
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn streamed_section_hashes_match_encodings() {
+        use std::collections::BTreeMap;
+
+        use borsh_ext::BorshSerializeExt;
+        use sha2::{Digest, Sha256};
+
+        use crate::types::address::nam;
+        use crate::types::chain::ChainId;
+        use crate::types::hash::Hash;
+        use crate::types::key::testing::{keypair_1, keypair_2};
+
+        let targets = vec![Hash([1; 32]), Hash([2; 32])];
+        let single = Signature::new(
+            targets.clone(),
+            [(0, keypair_1())].into_iter().collect::<BTreeMap<_, _>>(),
+            None,
+        );
+        let multisig = Signature::new(
+            targets.clone(),
+            [(0, keypair_1()), (1, keypair_2())]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            Some(nam()),
+        );
+        let ciphertext = Ciphertext {
+            opaque: "encrypted sections".as_bytes().to_owned().into(),
+        };
+        let mut tx = types::Tx::new(ChainId::default(), None);
+        tx.add_serialized_data("arbitrary data".as_bytes().to_owned());
+
+        // The hashes of these sections are the ones of their encodings
+        for section in [
+            Section::Signature(single.clone()),
+            Section::Signature(multisig.clone()),
+            Section::Ciphertext(ciphertext.clone()),
+            Section::Header(tx.header()),
+            tx.sections[0].clone(),
+        ] {
+            let expected =
+                Hash(Sha256::digest(section.serialize_to_vec()).into());
+            assert_eq!(section.get_hash(), expected);
+            assert_eq!(
+                Hash(section.hash(&mut Sha256::new()).finalize_reset().into()),
+                expected
+            );
+        }
+        // The raw hash of a signature is the one of the encoding of the
+        // section stripped of its signer and signatures
+        for signature in [&single, &multisig] {
+            let stripped = Signature {
+                signer: Signer::PubKeys(vec![]),
+                signatures: BTreeMap::new(),
+                ..signature.clone()
+            };
+            assert_eq!(
+                signature.get_raw_hash(),
+                Hash(Sha256::digest(stripped.serialize_to_vec()).into())
+            );
+        }
+
+        // Pinned hashes
+        assert_eq!(
+            Section::Ciphertext(ciphertext).get_hash().to_string(),
+            "FED681B41004C677414AA402BA01FEC5EB64FFD270517CAE03810FE9EC6DEFFB"
+        );
+        assert_eq!(
+            single.get_raw_hash().to_string(),
+            "58B33508B0A6AFB6F918CCC71C891A8B3B42A41AD45292EE57FB000D82FAEEF0"
+        );
+        assert_eq!(multisig.get_raw_hash(), single.get_raw_hash());
+    }
+
+    /// Make a wrapper tx carrying the given data and code, signed over all of
+    /// its sections
+    fn signed_wrapper(data: Vec<u8>, code: Vec<u8>) -> types::Tx {
+        use crate::types::address::nam;
+        use crate::types::chain::ChainId;
+        use crate::types::key::testing::keypair_1;
+        use crate::types::key::RefTo;
+        use crate::types::storage::Epoch;
+        use crate::types::transaction::{Fee, TxType, WrapperTx};
+
+        let mut tx =
+            types::Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: Default::default(),
+                    token: nam(),
+                },
+                keypair_1().ref_to(),
+                Epoch(0),
+                Default::default(),
+                None,
+            ))));
+        tx.header.chain_id = ChainId::default();
+        tx.set_data(Data::new(data));
+        tx.set_code(Code::new(code, None));
+        tx.add_section(Section::Signature(Signature::new(
+            tx.sechashes(),
+            [(0, keypair_1())].into_iter().collect(),
+            None,
+        )));
+        tx
+    }
+
+    #[test]
+    fn every_header_field_is_signed() {
+        use crate::types::chain::ChainId;
+        use crate::types::hash::Hash;
+        use crate::types::storage::Epoch;
+        use crate::types::time::DateTimeUtc;
+        use crate::types::transaction::{GasLimit, TxType};
+
+        let wrapper = signed_wrapper(b"data".to_vec(), b"code".to_vec());
+        assert!(wrapper.validate_tx().is_ok());
+        let timestamp = wrapper.header.timestamp;
+        let mutations: [fn(&mut Header, DateTimeUtc); 8] = [
+            |header, _| header.chain_id = ChainId("other-chain".to_owned()),
+            |header, timestamp| {
+                header.expiration = Some(timestamp.next_second())
+            },
+            |header, timestamp| header.timestamp = timestamp.next_second(),
+            |header, _| header.code_hash = Hash([1; 32]),
+            |header, _| header.data_hash = Hash([1; 32]),
+            |header, _| header.memo_hash = Hash([1; 32]),
+            |header, _| {
+                if let TxType::Wrapper(wrapper) = &mut header.tx_type {
+                    wrapper.epoch = Epoch(1);
+                }
+            },
+            |header, _| {
+                if let TxType::Wrapper(wrapper) = &mut header.tx_type {
+                    wrapper.gas_limit = GasLimit::from(1);
+                }
+            },
+        ];
+        // Changing any field of a signed header invalidates its signature
+        for mutate in mutations {
+            let mut tx = wrapper.clone();
+            mutate(&mut tx.header, timestamp);
+            assert_ne!(tx.header_hash(), wrapper.header_hash());
+            assert!(tx.validate_tx().is_err());
+        }
+    }
+
+    proptest! {
+        /// Check the invariants of the wrapper flow of this tree, in which a
+        /// wrapper is decrypted by swapping its header for a decrypted one:
+        /// the wrapper goes through encoding byte for byte, decrypting keeps
+        /// every section, payload and commitment of the wrapper, and a
+        /// wrapper whose data lost a single byte or that carries a
+        /// ciphertext is never accepted. Section sizes are bounded to keep
+        /// shrinking tractable.
+        #[test]
+        fn wrapper_flow_invariants(
+            data in proptest::collection::vec(any::<u8>(), 1..256),
+            code in proptest::collection::vec(any::<u8>(), 0..256),
+            ciphertext in proptest::collection::vec(any::<u8>(), 0..256),
+            corrupted in any::<prop::sample::Index>(),
+        ) {
+            use borsh_ext::BorshSerializeExt;
+
+            use crate::types::key::testing::keypair_1;
+            use crate::types::transaction::{DecryptedTx, TxType};
+
+            let wrapper = signed_wrapper(data.clone(), code.clone());
+            prop_assert!(wrapper.validate_tx().is_ok());
+
+            // The wrapper goes through encoding unchanged
+            let decoded =
+                types::Tx::try_from(wrapper.to_bytes().as_slice()).unwrap();
+            prop_assert_eq!(decoded.to_bytes(), wrapper.to_bytes());
+
+            // Decrypting keeps the sections, the payloads and the
+            // commitments of the wrapper
+            let mut decrypted = decoded;
+            decrypted.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+            prop_assert_eq!(
+                decrypted.sections.serialize_to_vec(),
+                wrapper.sections.serialize_to_vec()
+            );
+            prop_assert_eq!(decrypted.data(), Some(data));
+            prop_assert_eq!(decrypted.code(), Some(code));
+            prop_assert_eq!(
+                decrypted.raw_header_hash(),
+                wrapper.raw_header_hash()
+            );
+
+            // Corrupting any single byte of the data breaks the signature of
+            // the wrapper
+            let mut corrupted_tx = wrapper.clone();
+            for section in &mut corrupted_tx.sections {
+                if let Section::Data(data) = section {
+                    let mut bytes = data.data.to_vec();
+                    let index = corrupted.index(bytes.len());
+                    bytes[index] ^= 0xff;
+                    data.data = bytes.into();
+                }
+            }
+            prop_assert_ne!(corrupted_tx.sechashes(), wrapper.sechashes());
+            prop_assert!(corrupted_tx.validate_tx().is_err());
+
+            // A wrapper carrying a ciphertext is rejected, even if signed
+            let mut sealed = wrapper.clone();
+            sealed
+                .sections
+                .retain(|section| !matches!(section, Section::Signature(_)));
+            sealed.add_section(Section::Ciphertext(Ciphertext {
+                opaque: ciphertext.into(),
+            }));
+            sealed.add_section(Section::Signature(Signature::new(
+                sealed.sechashes(),
+                [(0, keypair_1())].into_iter().collect(),
+                None,
+            )));
+            prop_assert!(sealed.validate_tx().is_err());
+        }
+    }
 }
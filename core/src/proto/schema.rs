@@ -0,0 +1,290 @@
+//! Borsh schema of the transaction format, exported as a single versioned JSON
+//! artifact for wallets that cannot link against this crate.
+//!
+//! The artifact is checked in at [`TX_SCHEMA_PATH`] and must be regenerated
+//! whenever one of the described types changes with:
+//! `cargo run -p namada_core --bin namada_tx_schema`
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use borsh::schema::{Declaration, Definition, Fields};
+use borsh::{schema_container_of, BorshSchema};
+use serde_json::{json, Value};
+
+use super::{Section, Tx};
+use crate::types::token;
+use crate::types::transaction::{account, pos, TxType, WrapperTx};
+
+/// The version of the layout of the artifact. It must be bumped when the
+/// shape of the JSON changes, not when the described types change.
+pub const TX_SCHEMA_VERSION: u32 = 1;
+
+/// The path of the checked-in artifact, relative to the core crate's root
+pub const TX_SCHEMA_PATH: &str = "schema/tx.json";
+
+/// The declarations and merged definitions of a set of types
+#[derive(Default)]
+struct SchemaBuilder {
+    declarations: BTreeMap<&'static str, Declaration>,
+    definitions: BTreeMap<Declaration, Definition>,
+}
+
+impl SchemaBuilder {
+    /// Add the given type and all the types it refers to
+    fn add<T: BorshSchema>(mut self, name: &'static str) -> Self {
+        let container = schema_container_of::<T>();
+        for (declaration, definition) in container.definitions() {
+            match self.definitions.entry(declaration.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(definition.clone());
+                }
+                Entry::Occupied(entry) => assert_eq!(
+                    entry.get(),
+                    definition,
+                    "Conflicting Borsh schema definitions of {declaration}"
+                ),
+            }
+        }
+        self.declarations.insert(name, T::declaration());
+        self
+    }
+}
+
+/// Build the JSON schema artifact of the transaction format.
+///
+/// The `types` object maps the name of each described type to its Borsh
+/// declaration. Every declaration that has no entry in `definitions` is a
+/// Borsh primitive (`u8`..`u128`, `i8`..`i128`, `bool`, `string` or `nil`).
+pub fn tx_schema() -> Value {
+    let schema = SchemaBuilder::default()
+        .add::<Tx>("Tx")
+        .add::<Section>("Section")
+        .add::<TxType>("TxType")
+        .add::<WrapperTx>("WrapperTx")
+        .add::<token::Transfer>("token::Transfer")
+        .add::<account::InitAccount>("account::InitAccount")
+        .add::<account::UpdateAccount>("account::UpdateAccount")
+        .add::<pos::InitValidator>("pos::InitValidator")
+        .add::<pos::Bond>("pos::Bond")
+        .add::<pos::Withdraw>("pos::Withdraw")
+        .add::<pos::ClaimRewards>("pos::ClaimRewards")
+        .add::<pos::Redelegation>("pos::Redelegation")
+        .add::<pos::CommissionChange>("pos::CommissionChange")
+        .add::<pos::MetaDataChange>("pos::MetaDataChange")
+        .add::<pos::ConsensusKeyChange>("pos::ConsensusKeyChange");
+
+    let definitions: serde_json::Map<String, Value> = schema
+        .definitions
+        .iter()
+        .map(|(declaration, definition)| {
+            (declaration.clone(), definition_to_json(definition))
+        })
+        .collect();
+    json!({
+        "version": TX_SCHEMA_VERSION,
+        "types": schema.declarations,
+        "definitions": definitions,
+    })
+}
+
+/// Render the schema artifact as it is checked in
+pub fn tx_schema_pretty() -> String {
+    let mut schema = serde_json::to_string_pretty(&tx_schema())
+        .expect("Serializing JSON values shouldn't fail");
+    schema.push('\n');
+    schema
+}
+
+/// Convert a Borsh schema definition to its JSON form in the artifact
+fn definition_to_json(definition: &Definition) -> Value {
+    match definition {
+        Definition::Array { length, elements } => json!({
+            "kind": "array",
+            "length": length,
+            "elements": elements,
+        }),
+        // Prefixed with the number of elements as a `u32`
+        Definition::Sequence { elements } => json!({
+            "kind": "sequence",
+            "elements": elements,
+        }),
+        Definition::Tuple { elements } => json!({
+            "kind": "tuple",
+            "elements": elements,
+        }),
+        // Prefixed with the index of the variant as a `u8`
+        Definition::Enum { variants } => {
+            let variants: Vec<Value> = variants
+                .iter()
+                .enumerate()
+                .map(|(index, (name, declaration))| {
+                    json!({
+                        "index": index,
+                        "name": name,
+                        "type": declaration,
+                    })
+                })
+                .collect();
+            json!({
+                "kind": "enum",
+                "variants": variants,
+            })
+        }
+        Definition::Struct { fields } => {
+            let fields: Vec<Value> = match fields {
+                Fields::NamedFields(fields) => fields
+                    .iter()
+                    .map(|(name, declaration)| {
+                        json!({
+                            "name": name,
+                            "type": declaration,
+                        })
+                    })
+                    .collect(),
+                Fields::UnnamedFields(fields) => fields
+                    .iter()
+                    .map(|declaration| json!({ "type": declaration }))
+                    .collect(),
+                Fields::Empty => vec![],
+            };
+            json!({
+                "kind": "struct",
+                "fields": fields,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Split `len` bytes off the front of `bytes`
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> &'a [u8] {
+        assert!(bytes.len() >= len, "Unexpected end of input");
+        let (taken, rest) = bytes.split_at(len);
+        *bytes = rest;
+        taken
+    }
+
+    /// Split a `u32` length prefix off the front of `bytes`
+    fn take_len(bytes: &mut &[u8]) -> usize {
+        u32::from_le_bytes(take(bytes, 4).try_into().unwrap()) as usize
+    }
+
+    /// Get the declarations of a list of tuple elements or struct fields
+    fn declarations(value: &Value) -> Vec<&str> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|elem| elem.get("type").unwrap_or(elem).as_str().unwrap())
+            .collect()
+    }
+
+    /// Consume the Borsh encoding of a value of the given declaration from
+    /// the front of `bytes`, only looking at the schema artifact
+    fn skip_value(schema: &Value, declaration: &str, bytes: &mut &[u8]) {
+        let primitive_len = match declaration {
+            "nil" => Some(0),
+            "u8" | "i8" | "bool" => Some(1),
+            "u16" | "i16" => Some(2),
+            "u32" | "i32" | "f32" => Some(4),
+            "u64" | "i64" | "f64" => Some(8),
+            "u128" | "i128" => Some(16),
+            "string" => Some(take_len(bytes)),
+            _ => None,
+        };
+        if let Some(len) = primitive_len {
+            take(bytes, len);
+            return;
+        }
+        let definition = &schema["definitions"][declaration];
+        match definition["kind"].as_str() {
+            Some("array") => {
+                let elements = definition["elements"].as_str().unwrap();
+                for _ in 0..definition["length"].as_u64().unwrap() {
+                    skip_value(schema, elements, bytes);
+                }
+            }
+            Some("sequence") => {
+                let elements = definition["elements"].as_str().unwrap();
+                for _ in 0..take_len(bytes) {
+                    skip_value(schema, elements, bytes);
+                }
+            }
+            Some("tuple") => {
+                for elem in declarations(&definition["elements"]) {
+                    skip_value(schema, elem, bytes);
+                }
+            }
+            Some("enum") => {
+                let index = take(bytes, 1)[0] as usize;
+                let variant = &definition["variants"][index]["type"];
+                skip_value(schema, variant.as_str().unwrap(), bytes);
+            }
+            Some("struct") => {
+                for field in declarations(&definition["fields"]) {
+                    skip_value(schema, field, bytes);
+                }
+            }
+            _ => panic!("No definition of {declaration}"),
+        }
+    }
+
+    /// Check that the checked-in artifact is up-to-date
+    #[test]
+    fn verify_schema() {
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(TX_SCHEMA_PATH);
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == tx_schema_pretty(),
+            "The schema artifact at {} is stale, regenerate it with `cargo \
+             run -p namada_core --bin namada_tx_schema`",
+            path.display()
+        );
+    }
+
+    /// Check that a signed wrapper tx can be decoded with the artifact only
+    #[test]
+    fn decode_tx_with_schema() {
+        use borsh_ext::BorshSerializeExt;
+
+        use crate::types::address::nam;
+        use crate::types::chain::ChainId;
+        use crate::types::key::testing::keypair_1;
+        use crate::types::key::RefTo;
+        use crate::types::storage::Epoch;
+        use crate::types::time::DateTimeUtc;
+        use crate::types::transaction::{Fee, GasLimit};
+
+        let keypair = keypair_1();
+        let mut tx = Tx::new(ChainId::default(), Some(DateTimeUtc::now()));
+        tx.add_code("wasm code".as_bytes().to_owned(), Some("tag".into()))
+            .add_serialized_data("arbitrary data".as_bytes().to_owned())
+            .add_memo("a memo".as_bytes());
+        tx.add_wrapper(
+            Fee {
+                amount_per_gas_unit: token::Amount::native_whole(1),
+                token: nam(),
+            },
+            keypair.ref_to(),
+            Epoch(1),
+            GasLimit::from(100_000),
+            None,
+        )
+        .sign_wrapper(keypair);
+
+        // Round-trip the artifact through its textual form, as a wallet would
+        let schema: Value = serde_json::from_str(&tx_schema_pretty()).unwrap();
+        let declaration = schema["types"]["Tx"].as_str().unwrap();
+        let encoded = tx.serialize_to_vec();
+        let mut bytes = &encoded[..];
+        skip_value(&schema, declaration, &mut bytes);
+        assert!(bytes.is_empty());
+    }
+}
@@ -0,0 +1,420 @@
+//! Section payloads that may share the buffer a tx was decoded from
+//!
+//! The txs received over ABCI each come in a single buffer, most of which is
+//! usually taken by the payloads of their data, code and extra data sections.
+//! When a tx is decoded from [`Bytes`], these payloads are sliced out of the
+//! buffer instead of being copied, and only the remaining sections are
+//! deserialized as usual. A payload is encoded exactly as a `Vec<u8>` would
+//! be, so the wire format of txs is unaffected.
+//!
+//! A payload keeps the whole buffer it was sliced from alive. Mutating a
+//! payload with [`SectionBytes::modify`] detaches it from the buffer.
+//!
+//! Payloads also memoize their digest, so that hashing the sections of a tx
+//! over and over doesn't feed megabytes of code into SHA-256 every time.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use borsh::schema::{Declaration, Definition};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use prost::bytes::Bytes;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use super::types::{Code, Commitment, Data, Header, Section, Tx};
+use crate::types::hash::Hash;
+
+/// The Borsh tag of [`Section::Data`]
+const DATA_SECTION_TAG: u8 = 0;
+/// The Borsh tag of [`Section::ExtraData`]
+const EXTRA_DATA_SECTION_TAG: u8 = 1;
+/// The Borsh tag of [`Section::Code`]
+const CODE_SECTION_TAG: u8 = 2;
+/// The Borsh tag of [`Commitment::Hash`]
+const HASH_COMMITMENT_TAG: u8 = 0;
+/// The Borsh tag of [`Commitment::Id`]
+const ID_COMMITMENT_TAG: u8 = 1;
+
+/// The last digest computed over a payload, along with the prefix that was
+/// hashed before the payload
+type Memo = Option<(Vec<u8>, Hash)>;
+
+/// The payload of a section, possibly shared with the buffer that its tx was
+/// decoded from. Cloning a payload doesn't copy it.
+#[derive(Clone, Default)]
+pub struct SectionBytes {
+    bytes: Bytes,
+    /// The memoized digest, shared by the clones of the payload. It is not
+    /// part of the encodings of the payload.
+    memo: Arc<Mutex<Memo>>,
+}
+
+impl SectionBytes {
+    /// Mutate the payload. The payload is copied first, so that the buffer
+    /// it shares and its clones are left unchanged, and its memoized digest
+    /// is dropped.
+    pub fn modify<R>(&mut self, f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+        let mut bytes = self.bytes.to_vec();
+        let res = f(&mut bytes);
+        *self = bytes.into();
+        res
+    }
+
+    /// Get the payload as a shared buffer
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Get the SHA-256 digest of the given prefix followed by the payload.
+    /// The digest is memoized for the last prefix it was computed with.
+    pub fn digest(&self, prefix: &[u8]) -> Hash {
+        let mut memo = self.memo.lock().unwrap_or_else(PoisonError::into_inner);
+        match memo.as_ref() {
+            Some((memo_prefix, hash)) if memo_prefix == prefix => *hash,
+            _ => {
+                let mut hasher = Sha256::new();
+                hasher.update(prefix);
+                hasher.update(&self.bytes);
+                let hash = Hash(hasher.finalize().into());
+                *memo = Some((prefix.to_vec(), hash));
+                hash
+            }
+        }
+    }
+
+    /// Check whether the digest of the payload is memoized
+    #[cfg(test)]
+    pub(super) fn is_memoized(&self) -> bool {
+        self.memo
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+}
+
+impl fmt::Debug for SectionBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SectionBytes").field(&self.bytes).finish()
+    }
+}
+
+impl PartialEq for SectionBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for SectionBytes {}
+
+impl PartialOrd for SectionBytes {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SectionBytes {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bytes.cmp(&other.bytes)
+    }
+}
+
+impl std::hash::Hash for SectionBytes {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state)
+    }
+}
+
+impl Deref for SectionBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for SectionBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<Vec<u8>> for SectionBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes::from(bytes).into()
+    }
+}
+
+impl From<Bytes> for SectionBytes {
+    fn from(bytes: Bytes) -> Self {
+        Self {
+            bytes,
+            memo: Arc::default(),
+        }
+    }
+}
+
+impl PartialEq<Vec<u8>> for SectionBytes {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.bytes == other[..]
+    }
+}
+
+impl PartialEq<[u8]> for SectionBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.bytes == other
+    }
+}
+
+impl BorshSerialize for SectionBytes {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&self.bytes[..], writer)
+    }
+}
+
+impl BorshDeserialize for SectionBytes {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Vec::<u8>::deserialize_reader(reader).map(Self::from)
+    }
+}
+
+impl BorshSchema for SectionBytes {
+    fn add_definitions_recursively(
+        definitions: &mut BTreeMap<Declaration, Definition>,
+    ) {
+        Vec::<u8>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        Vec::<u8>::declaration()
+    }
+}
+
+impl Serialize for SectionBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.bytes.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for SectionBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<u8>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Deserialize a Borsh encoded tx from the given buffer, slicing the payloads
+/// of its data, code and extra data sections out of it
+pub(super) fn deserialize_tx(buf: &Bytes) -> io::Result<Tx> {
+    let mut reader = SharedReader { buf, pos: 0 };
+    let header: Header = reader.read()?;
+    let len: u32 = reader.read()?;
+    // The number of sections is untrusted, so don't allocate for it upfront
+    let mut sections = Vec::new();
+    for _ in 0..len {
+        sections.push(reader.section()?);
+    }
+    if reader.pos != buf.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not all bytes read",
+        ));
+    }
+    Ok(Tx { header, sections })
+}
+
+/// A reader over a shared buffer, which can slice byte payloads out of it
+struct SharedReader<'a> {
+    buf: &'a Bytes,
+    /// The position of the next byte to read
+    pos: usize,
+}
+
+impl SharedReader<'_> {
+    /// The bytes that are left to read
+    fn rest(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    /// Deserialize a value by copy
+    fn read<T: BorshDeserialize>(&mut self) -> io::Result<T> {
+        let mut rest = self.rest();
+        let value = T::deserialize_reader(&mut rest)?;
+        self.pos = self.buf.len() - rest.len();
+        Ok(value)
+    }
+
+    /// Slice a length-prefixed byte payload out of the buffer
+    fn payload(&mut self) -> io::Result<SectionBytes> {
+        let len = self.read::<u32>()? as usize;
+        if self.rest().len() < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let payload = self.buf.slice(self.pos..self.pos + len);
+        self.pos += len;
+        Ok(payload.into())
+    }
+
+    /// Deserialize a data section
+    fn data(&mut self) -> io::Result<Data> {
+        Ok(Data {
+            salt: self.read()?,
+            data: self.payload()?,
+        })
+    }
+
+    /// Deserialize a code or extra data section
+    fn code(&mut self) -> io::Result<Code> {
+        let salt = self.read()?;
+        let code = match self.read::<u8>()? {
+            HASH_COMMITMENT_TAG => Commitment::Hash(self.read()?),
+            ID_COMMITMENT_TAG => Commitment::Id(self.payload()?),
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unexpected variant tag: {tag}"),
+                ));
+            }
+        };
+        Ok(Code {
+            salt,
+            code,
+            tag: self.read()?,
+        })
+    }
+
+    /// Deserialize a section, sharing its payload if it has any
+    fn section(&mut self) -> io::Result<Section> {
+        let section = match self.rest().first() {
+            Some(&DATA_SECTION_TAG) => {
+                self.pos += 1;
+                Section::Data(self.data()?)
+            }
+            Some(&EXTRA_DATA_SECTION_TAG) => {
+                self.pos += 1;
+                Section::ExtraData(self.code()?)
+            }
+            Some(&CODE_SECTION_TAG) => {
+                self.pos += 1;
+                Section::Code(self.code()?)
+            }
+            _ => self.read()?,
+        };
+        Ok(section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh_ext::BorshSerializeExt;
+
+    use super::*;
+    use crate::types::chain::ChainId;
+    use crate::types::hash::Hash;
+
+    /// Check whether the given payload lies within the given buffer
+    fn is_slice_of(payload: &SectionBytes, buf: &Bytes) -> bool {
+        let range = buf.as_ptr_range();
+        range.contains(&payload.as_ptr())
+            && payload.as_ptr() as usize + payload.len() <= range.end as usize
+    }
+
+    /// Make a tx with sections of every kind whose payload can be shared
+    fn tx() -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1; 100], Some("tag".into()))
+            .add_serialized_data(vec![2; 200])
+            .add_extra_section(vec![3; 300], None);
+        tx.add_memo(b"memo");
+        tx.add_section(Section::ExtraData(Code::from_hash(
+            Hash([4; 32]),
+            None,
+        )));
+        tx
+    }
+
+    /// Check that the tags of the sections with a payload are those of
+    /// their Borsh encoding.
+    #[test]
+    fn test_section_tags() {
+        let data = Section::Data(Data::new(vec![]));
+        let extra = Section::ExtraData(Code::new(vec![], None));
+        let code = Section::Code(Code::new(vec![], None));
+        assert_eq!(data.serialize_to_vec()[0], DATA_SECTION_TAG);
+        assert_eq!(extra.serialize_to_vec()[0], EXTRA_DATA_SECTION_TAG);
+        assert_eq!(code.serialize_to_vec()[0], CODE_SECTION_TAG);
+        let hash = Commitment::Hash(Hash::zero());
+        let id = Commitment::Id(vec![].into());
+        assert_eq!(hash.serialize_to_vec()[0], HASH_COMMITMENT_TAG);
+        assert_eq!(id.serialize_to_vec()[0], ID_COMMITMENT_TAG);
+    }
+
+    /// Check that a tx deserialized from a shared buffer is the same as when
+    /// deserialized by copy, and that its payloads are slices of the buffer.
+    #[test]
+    fn test_shared_deserialization() {
+        let tx = tx();
+        let buf = Bytes::from(tx.serialize_to_vec());
+        let shared = deserialize_tx(&buf).unwrap();
+        let copied = Tx::try_from_slice(&buf).unwrap();
+        assert_eq!(shared.serialize_to_vec(), buf);
+        assert_eq!(copied.serialize_to_vec(), buf);
+        assert_eq!(shared.header_hash(), tx.header_hash());
+        assert_eq!(shared.sechashes(), tx.sechashes());
+
+        let mut payloads = 0;
+        for section in &shared.sections {
+            let payload = match section {
+                Section::Data(data) => &data.data,
+                Section::Code(code) | Section::ExtraData(code) => {
+                    match &code.code {
+                        Commitment::Id(code) => code,
+                        Commitment::Hash(_) => continue,
+                    }
+                }
+                _ => continue,
+            };
+            assert!(is_slice_of(payload, &buf));
+            payloads += 1;
+        }
+        assert_eq!(payloads, 4);
+        assert_eq!(shared.data(), Some(vec![2; 200]));
+        assert_eq!(shared.code(), Some(vec![1; 100]));
+
+        // Truncated or padded buffers are rejected
+        for len in [buf.len() - 1, buf.len() - 301, 0] {
+            assert!(deserialize_tx(&buf.slice(..len)).is_err());
+        }
+        let mut padded = buf.to_vec();
+        padded.push(0);
+        assert!(deserialize_tx(&Bytes::from(padded)).is_err());
+    }
+
+    /// Check that mutating a shared payload detaches it from the buffer,
+    /// leaving the buffer and the clones of the payload unchanged.
+    #[test]
+    fn test_modify_detaches() {
+        let buf = Bytes::from(tx().serialize_to_vec());
+        let mut tx = deserialize_tx(&buf).unwrap();
+        let Section::Data(data) = &mut tx.sections[1] else {
+            panic!("Expected a data section");
+        };
+        let original = data.data.clone();
+        data.data.modify(|bytes| bytes[0] = 0xff);
+        assert!(!is_slice_of(&data.data, &buf));
+        assert_eq!(data.data[0], 0xff);
+        assert_eq!(original[0], 2);
+        assert!(is_slice_of(&original, &buf));
+        assert_eq!(deserialize_tx(&buf).unwrap().data(), Some(vec![2; 200]));
+        assert_ne!(tx.serialize_to_vec(), buf);
+    }
+}
@@ -0,0 +1,344 @@
+//! The sections that a tx may carry, depending on the type of its header
+//!
+//! Each type of header has its legitimate shapes: no tx carries ciphertexts,
+//! since txs aren't encrypted, and a protocol tx only carries its payload and
+//! the signature of the validator that issued it. The policy of each type tells
+//! how many sections of each kind its txs may carry, and [`Tx::check_sections`]
+//! rejects the txs that don't comply, naming the offending kind of section.
+
+use std::fmt::{self, Display};
+
+use super::types::{Section, Tx};
+use crate::types::transaction::TxType;
+
+/// The kind of a section, regardless of its contents
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SectionKind {
+    /// See [`Section::Data`]
+    Data,
+    /// See [`Section::ExtraData`]
+    ExtraData,
+    /// See [`Section::Code`]
+    Code,
+    /// See [`Section::Signature`]
+    Signature,
+    /// See [`Section::Ciphertext`]
+    Ciphertext,
+    /// See [`Section::MaspTx`]
+    MaspTx,
+    /// See [`Section::MaspBuilder`]
+    MaspBuilder,
+    /// See [`Section::Header`]
+    Header,
+    /// See [`Section::AggregatedSignature`]
+    AggregatedSignature,
+    /// See [`Section::PowSolution`]
+    #[cfg(not(feature = "mainnet"))]
+    PowSolution,
+}
+
+impl Display for SectionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Data => "data",
+            Self::ExtraData => "extra data",
+            Self::Code => "code",
+            Self::Signature => "signature",
+            Self::Ciphertext => "ciphertext",
+            Self::MaspTx => "MASP tx",
+            Self::MaspBuilder => "MASP builder",
+            Self::Header => "header",
+            Self::AggregatedSignature => "aggregated signature",
+            #[cfg(not(feature = "mainnet"))]
+            Self::PowSolution => "PoW solution",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How many sections of some kind a tx may carry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionCount {
+    /// No section of the kind
+    Forbidden,
+    /// At most one section of the kind
+    AtMostOne,
+    /// Exactly one section of the kind
+    ExactlyOne,
+    /// Any number of sections of the kind
+    Any,
+}
+
+impl SectionCount {
+    /// Check whether the given number of sections is allowed
+    pub fn allows(self, count: usize) -> bool {
+        match self {
+            Self::Forbidden => count == 0,
+            Self::AtMostOne => count <= 1,
+            Self::ExactlyOne => count == 1,
+            Self::Any => true,
+        }
+    }
+}
+
+impl Display for SectionCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = match self {
+            Self::Forbidden => "no",
+            Self::AtMostOne => "at most one",
+            Self::ExactlyOne => "exactly one",
+            Self::Any => "any number of",
+        };
+        f.write_str(count)
+    }
+}
+
+/// A tx carrying a number of sections of some kind that the policy of its
+/// header type doesn't allow
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+#[error(
+    "A {tx_type} tx must carry {allowed} {kind} sections, but carries {found}"
+)]
+pub struct SectionPolicyError {
+    /// The name of the type of the header of the tx
+    pub tx_type: &'static str,
+    /// The offending kind of section
+    pub kind: SectionKind,
+    /// How many sections of the kind are allowed
+    pub allowed: SectionCount,
+    /// How many sections of the kind the tx carries
+    pub found: usize,
+}
+
+/// The policy of the given header type: how many sections of the given kind
+/// its txs may carry
+pub fn allowed_sections(tx_type: &TxType, kind: SectionKind) -> SectionCount {
+    match (tx_type, kind) {
+        // Header sections only wrap headers to compute their hashes
+        (_, SectionKind::Header) => SectionCount::Forbidden,
+        // Txs can't be decrypted, so a wrapper carrying ciphertexts would
+        // end up as a decrypted tx that can never be valid
+        (_, SectionKind::Ciphertext) => SectionCount::Forbidden,
+        // Protocol txs only carry their payload and the signature of the
+        // validator issuing them
+        (TxType::Protocol(_), SectionKind::Data | SectionKind::Signature) => {
+            SectionCount::ExactlyOne
+        }
+        (TxType::Protocol(_), _) => SectionCount::Forbidden,
+        // Only the fee payer of a wrapper may have solved a PoW challenge
+        #[cfg(not(feature = "mainnet"))]
+        (TxType::Raw, SectionKind::PowSolution) => SectionCount::Forbidden,
+        #[cfg(not(feature = "mainnet"))]
+        (_, SectionKind::PowSolution) => SectionCount::AtMostOne,
+        _ => SectionCount::Any,
+    }
+}
+
+/// The name of the given header type, as used in policy errors
+fn tx_type_name(tx_type: &TxType) -> &'static str {
+    match tx_type {
+        TxType::Raw => "raw",
+        TxType::Wrapper(_) => "wrapper",
+        TxType::Decrypted(_) => "decrypted",
+        TxType::Protocol(_) => "protocol",
+    }
+}
+
+/// Every kind of section
+const SECTION_KINDS: &[SectionKind] = &[
+    SectionKind::Data,
+    SectionKind::ExtraData,
+    SectionKind::Code,
+    SectionKind::Signature,
+    SectionKind::Ciphertext,
+    SectionKind::MaspTx,
+    SectionKind::MaspBuilder,
+    SectionKind::Header,
+    SectionKind::AggregatedSignature,
+    #[cfg(not(feature = "mainnet"))]
+    SectionKind::PowSolution,
+];
+
+impl Tx {
+    /// Check that the sections of this tx comply with the policy of the type
+    /// of its header, see [`allowed_sections`]
+    pub fn check_sections(&self) -> Result<(), SectionPolicyError> {
+        let tx_type = &self.header.tx_type;
+        for &kind in SECTION_KINDS {
+            let allowed = allowed_sections(tx_type, kind);
+            let found = self
+                .sections
+                .iter()
+                .filter(|section| section.kind() == kind)
+                .count();
+            if !allowed.allows(found) {
+                return Err(SectionPolicyError {
+                    tx_type: tx_type_name(tx_type),
+                    kind,
+                    allowed,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Section {
+    /// Get the kind of this section
+    pub fn kind(&self) -> SectionKind {
+        match self {
+            Self::Data(_) => SectionKind::Data,
+            Self::ExtraData(_) => SectionKind::ExtraData,
+            Self::Code(_) => SectionKind::Code,
+            Self::Signature(_) => SectionKind::Signature,
+            Self::Ciphertext(_) => SectionKind::Ciphertext,
+            Self::MaspTx(_) => SectionKind::MaspTx,
+            Self::MaspBuilder(_) => SectionKind::MaspBuilder,
+            Self::Header(_) => SectionKind::Header,
+            Self::AggregatedSignature(_) => SectionKind::AggregatedSignature,
+            #[cfg(not(feature = "mainnet"))]
+            Self::PowSolution(_) => SectionKind::PowSolution,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::{Ciphertext, Data, Signature, TxError};
+    use crate::types::chain::ChainId;
+    use crate::types::key::testing::keypair_1;
+    use crate::types::key::RefTo;
+    use crate::types::storage::Epoch;
+    use crate::types::transaction::protocol::{ProtocolTx, ProtocolTxType};
+    use crate::types::transaction::{DecryptedTx, Fee, WrapperTx};
+
+    /// Make a tx of the given type carrying some data and code, signed over
+    /// all of its sections if it's a wrapper or a protocol tx
+    fn tx(tx_type: TxType, extra: &[Section]) -> Tx {
+        let signed =
+            matches!(tx_type, TxType::Wrapper(_) | TxType::Protocol(_));
+        let mut tx = Tx::from_type(tx_type);
+        tx.header.chain_id = ChainId::default();
+        tx.set_data(Data::new(b"data".to_vec()));
+        if !matches!(tx.header.tx_type, TxType::Protocol(_)) {
+            tx.add_code(b"code".to_vec(), None);
+        }
+        for section in extra {
+            tx.add_section(section.clone());
+        }
+        if signed {
+            tx.add_section(Section::Signature(Signature::new(
+                tx.sechashes(),
+                [(0, keypair_1())].into_iter().collect(),
+                None,
+            )));
+        }
+        tx
+    }
+
+    fn wrapper() -> TxType {
+        TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: Default::default(),
+                token: crate::types::address::nam(),
+            },
+            keypair_1().ref_to(),
+            Epoch(0),
+            Default::default(),
+            None,
+        )))
+    }
+
+    fn protocol() -> TxType {
+        TxType::Protocol(Box::new(ProtocolTx {
+            pk: keypair_1().ref_to(),
+            tx: ProtocolTxType::EthEventsVext,
+        }))
+    }
+
+    fn decrypted() -> TxType {
+        TxType::Decrypted(DecryptedTx::Decrypted)
+    }
+
+    fn ciphertext() -> Section {
+        Section::Ciphertext(Ciphertext {
+            opaque: b"encrypted sections".to_vec().into(),
+        })
+    }
+
+    /// Check that the compliant shapes of each header type pass validation.
+    #[test]
+    fn test_compliant_sections() {
+        for tx in [
+            tx(TxType::Raw, &[]),
+            tx(wrapper(), &[]),
+            tx(decrypted(), &[]),
+            tx(protocol(), &[]),
+        ] {
+            assert_eq!(tx.check_sections(), Ok(()));
+            assert!(tx.validate_tx().is_ok());
+        }
+    }
+
+    /// Check that each rule of the policy is enforced, naming the offending
+    /// kind of section.
+    #[test]
+    fn test_section_policy_violations() {
+        let header = Section::Header(tx(TxType::Raw, &[]).header());
+        let violations = [
+            (tx(wrapper(), &[header]), "wrapper", SectionKind::Header, 1),
+            (
+                tx(TxType::Raw, &[ciphertext()]),
+                "raw",
+                SectionKind::Ciphertext,
+                1,
+            ),
+            (
+                tx(wrapper(), &[ciphertext(), ciphertext()]),
+                "wrapper",
+                SectionKind::Ciphertext,
+                2,
+            ),
+            (
+                tx(decrypted(), &[ciphertext()]),
+                "decrypted",
+                SectionKind::Ciphertext,
+                1,
+            ),
+            (
+                tx(protocol(), &[Section::Data(Data::new(b"more".to_vec()))]),
+                "protocol",
+                SectionKind::Data,
+                2,
+            ),
+            (
+                {
+                    let mut tx = tx(protocol(), &[]);
+                    tx.sections.retain(|section| {
+                        section.kind() != SectionKind::Signature
+                    });
+                    tx
+                },
+                "protocol",
+                SectionKind::Signature,
+                0,
+            ),
+            (
+                tx(protocol(), &[ciphertext()]),
+                "protocol",
+                SectionKind::Ciphertext,
+                1,
+            ),
+        ];
+        for (tx, tx_type, kind, found) in violations {
+            let err = tx.check_sections().unwrap_err();
+            assert_eq!(err.tx_type, tx_type);
+            assert_eq!(err.kind, kind);
+            assert_eq!(err.found, found);
+            assert_eq!(err.allowed, allowed_sections(&tx.header.tx_type, kind));
+            assert_eq!(tx.validate_tx(), Err(TxError::InvalidSections(err)));
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! The canonical JSON representation of [`Signed`] values
+//!
+//! Signed values passed around off-chain, such as collected governance votes
+//! or validator attestations, are encoded as a JSON object of the form:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "data": <the data, as encoded by its own serde implementation>,
+//!   "signature": "<scheme>:<base64 of the Borsh encoded signature>"
+//! }
+//! ```
+//!
+//! where the scheme is one of `ed25519`, `secp256k1` or `bls`. The fields may
+//! come in any order. The JSON form is never signed over: a signature is
+//! always verified against the Borsh encoding of the decoded data, so that
+//! the way the JSON was laid out has no bearing on its validity.
+
+use borsh::BorshDeserialize;
+use borsh_ext::BorshSerializeExt;
+use data_encoding::BASE64;
+use serde::de::Error as SerdeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::types::Signed;
+#[cfg(feature = "bls")]
+use crate::types::key::bls;
+use crate::types::key::{common, ed25519, secp256k1};
+
+/// The version of the JSON representation of [`Signed`] values
+const SIGNED_JSON_VERSION: u8 = 1;
+
+/// The JSON representation of a [`Signed`] value being serialized
+#[derive(Serialize)]
+struct SignedJsonRef<'a, T> {
+    version: u8,
+    data: &'a T,
+    signature: String,
+}
+
+/// The JSON representation of a [`Signed`] value being deserialized
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignedJson<T> {
+    version: u8,
+    data: T,
+    signature: String,
+}
+
+/// Encode a signature as a string tagged with its scheme
+fn encode_signature(sig: &common::Signature) -> String {
+    let (scheme, bytes) = match sig {
+        common::Signature::Ed25519(sig) => ("ed25519", sig.serialize_to_vec()),
+        common::Signature::Secp256k1(sig) => {
+            ("secp256k1", sig.serialize_to_vec())
+        }
+        #[cfg(feature = "bls")]
+        common::Signature::Bls(sig) => ("bls", sig.serialize_to_vec()),
+    };
+    format!("{scheme}:{}", BASE64.encode(&bytes))
+}
+
+/// Decode a signature from a string tagged with its scheme
+fn decode_signature(encoded: &str) -> Result<common::Signature, String> {
+    let (scheme, encoded) = encoded
+        .split_once(':')
+        .ok_or_else(|| "the signature is missing its scheme".to_string())?;
+    let bytes = BASE64
+        .decode(encoded.as_bytes())
+        .map_err(|err| format!("invalid base64 signature: {err}"))?;
+    let invalid =
+        |err: std::io::Error| format!("invalid {scheme} signature: {err}");
+    match scheme {
+        "ed25519" => ed25519::Signature::try_from_slice(&bytes)
+            .map(common::Signature::Ed25519)
+            .map_err(invalid),
+        "secp256k1" => secp256k1::Signature::try_from_slice(&bytes)
+            .map(common::Signature::Secp256k1)
+            .map_err(invalid),
+        #[cfg(feature = "bls")]
+        "bls" => bls::Signature::try_from_slice(&bytes)
+            .map(common::Signature::Bls)
+            .map_err(invalid),
+        _ => Err(format!("unknown signature scheme {scheme}")),
+    }
+}
+
+impl<T: Serialize, S> Serialize for Signed<T, S> {
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: Serializer,
+    {
+        SignedJsonRef {
+            version: SIGNED_JSON_VERSION,
+            data: &self.data,
+            signature: encode_signature(&self.sig),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, S> Deserialize<'de> for Signed<T, S> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let json = SignedJson::<T>::deserialize(deserializer)?;
+        if json.version != SIGNED_JSON_VERSION {
+            return Err(D::Error::custom(format!(
+                "unsupported version {} of signed data",
+                json.version
+            )));
+        }
+        let sig =
+            decode_signature(&json.signature).map_err(D::Error::custom)?;
+        Ok(Signed::new_from(json.data, sig))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+    use crate::types::key::testing::{gen_keypair, keypair_1};
+    use crate::types::key::RefTo;
+
+    /// A payload signed off-chain
+    #[derive(
+        Debug,
+        PartialEq,
+        BorshSerialize,
+        BorshDeserialize,
+        Serialize,
+        Deserialize,
+    )]
+    struct Attestation {
+        height: u64,
+        memo: String,
+    }
+
+    fn attestation() -> Attestation {
+        Attestation {
+            height: 42,
+            memo: "attested".to_string(),
+        }
+    }
+
+    /// Check that signed data of each scheme survives a round-trip through
+    /// JSON, and still verifies.
+    #[test]
+    fn test_signed_json_round_trip() {
+        let secp_key =
+            common::SecretKey::Secp256k1(gen_keypair::<secp256k1::SigScheme>());
+        for keypair in [keypair_1(), secp_key] {
+            let signed = Signed::<_>::new(&keypair, attestation());
+            let json = serde_json::to_string(&signed).unwrap();
+            let decoded: Signed<Attestation> =
+                serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, signed);
+            assert!(decoded.verify(&keypair.ref_to()).is_ok());
+        }
+    }
+
+    /// Check that the order of the JSON fields has no bearing on the decoded
+    /// value or its signature.
+    #[test]
+    fn test_signed_json_field_order() {
+        let keypair = keypair_1();
+        let signed = Signed::<_>::new(&keypair, attestation());
+        let signature = encode_signature(&signed.sig);
+        let reordered = format!(
+            r#"{{"signature":"{signature}","data":{{"memo":"attested","height":42}},"version":1}}"#
+        );
+        let decoded: Signed<Attestation> =
+            serde_json::from_str(&reordered).unwrap();
+        assert_eq!(decoded, signed);
+        assert!(decoded.verify(&keypair.ref_to()).is_ok());
+    }
+
+    /// Check that malformed JSON representations are rejected.
+    #[test]
+    fn test_signed_json_rejects_malformed() {
+        let signed = Signed::<_>::new(&keypair_1(), attestation());
+        let signature = encode_signature(&signed.sig);
+        let (_, base64) = signature.split_once(':').unwrap();
+        for json in [
+            format!(
+                r#"{{"version":2,"data":{{"height":42,"memo":"attested"}},"signature":"{signature}"}}"#
+            ),
+            format!(
+                r#"{{"version":1,"data":{{"height":42,"memo":"attested"}},"signature":"ecdsa:{base64}"}}"#
+            ),
+            format!(
+                r#"{{"version":1,"data":{{"height":42,"memo":"attested"}},"signature":"secp256k1:{base64}"}}"#
+            ),
+            format!(
+                r#"{{"version":1,"data":{{"height":42,"memo":"attested"}},"signature":"{base64}"}}"#
+            ),
+            format!(
+                r#"{{"version":1,"data":{{"height":42,"memo":"attested"}},"signature":"{signature}","extra":0}}"#
+            ),
+        ] {
+            assert!(
+                serde_json::from_str::<Signed<Attestation>>(&json).is_err()
+            );
+        }
+    }
+
+    /// Check the checked-in fixture, which pins the JSON representation.
+    /// Changing this test or its fixture breaks the compatibility of signed
+    /// data exchanged off-chain.
+    #[test]
+    fn test_signed_json_fixture() {
+        let fixture = include_str!("testdata/signed_v1.json");
+        let keypair = keypair_1();
+        let decoded: Signed<Attestation> =
+            serde_json::from_str(fixture).unwrap();
+        assert_eq!(decoded.data, attestation());
+        assert!(decoded.verify(&keypair.ref_to()).is_ok());
+        assert_eq!(decoded, Signed::new(&keypair, attestation()));
+        assert_eq!(
+            serde_json::to_string_pretty(&decoded).unwrap() + "\n",
+            fixture
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! Human-readable summaries of what signing a tx authorizes
+
+use std::fmt::{self, Display};
+
+use borsh::BorshDeserialize;
+
+use super::{Section, Tx};
+use crate::types::address::{self, Address};
+use crate::types::hash::Hash;
+use crate::types::key::common;
+use crate::types::time::DateTimeUtc;
+use crate::types::token;
+use crate::types::transaction::{account, pos, WrapperTx};
+
+/// The action that the inner tx of a [`Tx`] performs, as decoded from the
+/// data section committed to by the header
+#[derive(Clone, Debug, PartialEq)]
+pub enum TxAction {
+    /// A token transfer
+    Transfer {
+        /// The source of the tokens
+        source: Address,
+        /// The target of the tokens
+        target: Address,
+        /// The transferred token
+        token: Address,
+        /// The transferred amount
+        amount: token::DenominatedAmount,
+        /// Whether the transfer carries a shielded part
+        shielded: bool,
+    },
+    /// The initialization of a new account
+    InitAccount {
+        /// The public keys of the new account
+        public_keys: Vec<common::PublicKey>,
+        /// The signature threshold of the new account
+        threshold: u8,
+        /// The hash of the VP of the new account
+        vp_code_hash: Hash,
+    },
+    /// An update of an existing account
+    UpdateAccount {
+        /// The updated account
+        address: Address,
+        /// The hash of the new VP, if it is updated
+        vp_code_hash: Option<Hash>,
+        /// The new public keys of the account
+        public_keys: Vec<common::PublicKey>,
+        /// The new signature threshold, if it is updated
+        threshold: Option<u8>,
+    },
+    /// A bond of native tokens to a validator
+    Bond {
+        /// The owner of the bonded tokens, the validator itself if none
+        source: Option<Address>,
+        /// The validator to bond to
+        validator: Address,
+        /// The bonded amount
+        amount: token::Amount,
+    },
+    /// An unbond of native tokens from a validator
+    Unbond {
+        /// The owner of the bond, the validator itself if none
+        source: Option<Address>,
+        /// The validator to unbond from
+        validator: Address,
+        /// The unbonded amount
+        amount: token::Amount,
+    },
+    /// A withdrawal of unbonded tokens
+    Withdraw {
+        /// The owner of the unbonds, the validator itself if none
+        source: Option<Address>,
+        /// The validator to withdraw from
+        validator: Address,
+    },
+    /// A reveal of a public key
+    RevealPk(common::PublicKey),
+    /// A tx whose data could not be decoded against the known tx kinds
+    Unknown {
+        /// The hash of the data section
+        data_hash: Hash,
+        /// The length of the data in bytes
+        size: usize,
+    },
+}
+
+/// A best-effort summary of a tx to be shown before signing it. It is only
+/// derived from the sections that the signatures commit to.
+#[derive(Clone, Debug)]
+pub struct TxSummary {
+    /// The action of the inner tx
+    pub action: TxAction,
+    /// The wrapper paying the fees, if the tx is wrapped
+    pub wrapper: Option<WrapperTx>,
+    /// The expiration of the tx, if any
+    pub expiration: Option<DateTimeUtc>,
+}
+
+impl Tx {
+    /// Summarize what signing this tx authorizes. The data section is decoded
+    /// according to the tag of the code section, falling back to
+    /// [`TxAction::Unknown`] when either is missing or unknown.
+    pub fn describe(&self) -> TxSummary {
+        let data = self.data().unwrap_or_default();
+        let action = self
+            .code_tag()
+            .and_then(|tag| self.decode_action(&tag, &data))
+            .unwrap_or(TxAction::Unknown {
+                data_hash: *self.data_sechash(),
+                size: data.len(),
+            });
+        TxSummary {
+            action,
+            wrapper: self.header.wrapper(),
+            expiration: self.header.expiration,
+        }
+    }
+
+    /// Get the tag of the code section committed to by the header
+    fn code_tag(&self) -> Option<String> {
+        self.get_section(self.code_sechash())
+            .and_then(|section| section.code_sec())
+            .and_then(|code| code.tag)
+    }
+
+    /// Get the hash of the code in the given extra data section, or the
+    /// section hash itself if it's not in this tx
+    fn extra_code_hash(&self, section_hash: &Hash) -> Hash {
+        self.get_section(section_hash)
+            .and_then(|section| Section::extra_data_sec(section.as_ref()))
+            .map(|code| code.code.hash())
+            .unwrap_or(*section_hash)
+    }
+
+    /// Decode the data of the tx with the given code tag
+    fn decode_action(&self, tag: &str, data: &[u8]) -> Option<TxAction> {
+        // The tags of the tx wasms, as set by the SDK tx builders
+        let action = match tag {
+            "tx_transfer.wasm" => {
+                let transfer = token::Transfer::try_from_slice(data).ok()?;
+                TxAction::Transfer {
+                    source: transfer.source,
+                    target: transfer.target,
+                    token: transfer.token,
+                    amount: transfer.amount,
+                    shielded: transfer.shielded.is_some(),
+                }
+            }
+            "tx_init_account.wasm" => {
+                let init = account::InitAccount::try_from_slice(data).ok()?;
+                TxAction::InitAccount {
+                    vp_code_hash: self.extra_code_hash(&init.vp_code_hash),
+                    public_keys: init.public_keys,
+                    threshold: init.threshold,
+                }
+            }
+            "tx_update_account.wasm" => {
+                let update =
+                    account::UpdateAccount::try_from_slice(data).ok()?;
+                let vp_code_hash = update.stored_vp_code_hash.or_else(|| {
+                    update.vp_code_hash.map(|hash| self.extra_code_hash(&hash))
+                });
+                TxAction::UpdateAccount {
+                    address: update.addr,
+                    vp_code_hash,
+                    public_keys: update.public_keys,
+                    threshold: update.threshold,
+                }
+            }
+            "tx_bond.wasm" => {
+                let bond = pos::Bond::try_from_slice(data).ok()?;
+                TxAction::Bond {
+                    source: bond.source,
+                    validator: bond.validator,
+                    amount: bond.amount,
+                }
+            }
+            "tx_unbond.wasm" => {
+                let unbond = pos::Unbond::try_from_slice(data).ok()?;
+                TxAction::Unbond {
+                    source: unbond.source,
+                    validator: unbond.validator,
+                    amount: unbond.amount,
+                }
+            }
+            "tx_withdraw.wasm" => {
+                let withdraw = pos::Withdraw::try_from_slice(data).ok()?;
+                TxAction::Withdraw {
+                    source: withdraw.source,
+                    validator: withdraw.validator,
+                }
+            }
+            "tx_reveal_pk.wasm" => TxAction::RevealPk(
+                common::PublicKey::try_from_slice(data).ok()?,
+            ),
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// Format the source of a PoS tx, which defaults to the validator
+fn pos_source(source: &Option<Address>, validator: &Address) -> String {
+    source.as_ref().unwrap_or(validator).encode()
+}
+
+/// Format the public keys of an account
+fn public_keys(public_keys: &[common::PublicKey]) -> String {
+    public_keys
+        .iter()
+        .map(|pk| pk.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for TxAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transfer {
+                source,
+                target,
+                token,
+                amount,
+                shielded,
+            } => {
+                write!(
+                    f,
+                    "Transfer {amount} of {token} from {source} to {target}"
+                )?;
+                if *shielded {
+                    write!(f, " with a shielded part")?;
+                }
+                Ok(())
+            }
+            Self::InitAccount {
+                public_keys: pks,
+                threshold,
+                vp_code_hash,
+            } => write!(
+                f,
+                "Initialize an account with VP {vp_code_hash}, threshold \
+                 {threshold} and public keys [{}]",
+                public_keys(pks)
+            ),
+            Self::UpdateAccount {
+                address,
+                vp_code_hash,
+                public_keys: pks,
+                threshold,
+            } => {
+                write!(f, "Update account {address}")?;
+                if let Some(vp_code_hash) = vp_code_hash {
+                    write!(f, ", set VP {vp_code_hash}")?;
+                }
+                if let Some(threshold) = threshold {
+                    write!(f, ", set threshold {threshold}")?;
+                }
+                if !pks.is_empty() {
+                    write!(f, ", set public keys [{}]", public_keys(pks))?;
+                }
+                Ok(())
+            }
+            Self::Bond {
+                source,
+                validator,
+                amount,
+            } => write!(
+                f,
+                "Bond {} NAM from {} to validator {validator}",
+                amount.to_string_native(),
+                pos_source(source, validator)
+            ),
+            Self::Unbond {
+                source,
+                validator,
+                amount,
+            } => write!(
+                f,
+                "Unbond {} NAM of {} from validator {validator}",
+                amount.to_string_native(),
+                pos_source(source, validator)
+            ),
+            Self::Withdraw { source, validator } => write!(
+                f,
+                "Withdraw the unbonded tokens of {} from validator {validator}",
+                pos_source(source, validator)
+            ),
+            Self::RevealPk(pk) => write!(f, "Reveal public key {pk}"),
+            Self::Unknown { data_hash, size } => write!(
+                f,
+                "Unknown tx with {size} bytes of data of hash {data_hash}"
+            ),
+        }
+    }
+}
+
+impl Display for TxSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.action)?;
+        if let Some(wrapper) = &self.wrapper {
+            let fee = &wrapper.fee;
+            let amount = if fee.token == address::nam() {
+                format!("{} NAM", fee.amount_per_gas_unit.to_string_native())
+            } else {
+                format!(
+                    "{} raw units of {}",
+                    fee.amount_per_gas_unit.raw_amount(),
+                    fee.token
+                )
+            };
+            write!(
+                f,
+                "\nFee: {amount} per gas unit, up to {} gas units, paid by {}",
+                u64::from(wrapper.gas_limit.clone()),
+                Address::from(&wrapper.pk)
+            )?;
+        }
+        if let Some(expiration) = &self.expiration {
+            write!(f, "\nExpires at: {expiration}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh_ext::BorshSerializeExt;
+
+    use super::*;
+    use crate::proto::{Code, Data};
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::chain::ChainId;
+    use crate::types::key::testing::keypair_1;
+    use crate::types::key::RefTo;
+    use crate::types::storage::Epoch;
+    use crate::types::transaction::{hash_tx, Fee, GasLimit};
+
+    /// Build a tx with the given code tag and data
+    fn tx_with(tag: &str, data: Vec<u8>) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(b"wasm code".to_vec(), Some(tag.to_string()))
+            .add_serialized_data(data);
+        tx
+    }
+
+    #[test]
+    fn test_describe_transfer() {
+        let transfer = token::Transfer {
+            source: established_address_1(),
+            target: established_address_2(),
+            token: address::nam(),
+            amount: token::Amount::native_whole(10).native_denominated(),
+            key: None,
+            shielded: None,
+        };
+        let keypair = keypair_1();
+        let expiration = DateTimeUtc::now();
+        let mut tx = tx_with("tx_transfer.wasm", transfer.serialize_to_vec());
+        tx.header.expiration = Some(expiration);
+        tx.add_wrapper(
+            Fee {
+                amount_per_gas_unit: token::Amount::native_whole(1),
+                token: address::nam(),
+            },
+            keypair.ref_to(),
+            Epoch(1),
+            GasLimit::from(20_000),
+            None,
+        );
+
+        let summary = tx.describe();
+        assert_eq!(
+            summary.action,
+            TxAction::Transfer {
+                source: transfer.source,
+                target: transfer.target,
+                token: transfer.token,
+                amount: transfer.amount,
+                shielded: false,
+            }
+        );
+        assert_eq!(summary.expiration, Some(expiration));
+        let wrapper = summary.wrapper.clone().unwrap();
+        assert_eq!(wrapper.pk, keypair.ref_to());
+        assert_eq!(u64::from(wrapper.gas_limit), 20_000);
+
+        let preview = summary.to_string();
+        assert!(preview.contains("Fee: 1.000000 NAM per gas unit"));
+        assert!(preview.contains(&expiration.to_string()));
+    }
+
+    #[test]
+    fn test_describe_accounts() {
+        let vp_code = b"vp code".to_vec();
+        let mut tx = tx_with("tx_init_account.wasm", vec![]);
+        let vp_section_hash = tx
+            .add_section(Section::ExtraData(Code::new(vp_code.clone(), None)))
+            .get_hash();
+        let init = account::InitAccount {
+            public_keys: vec![keypair_1().ref_to()],
+            vp_code_hash: vp_section_hash,
+            threshold: 1,
+        };
+        tx.set_data(Data::new(init.serialize_to_vec()));
+        assert_eq!(
+            tx.describe().action,
+            TxAction::InitAccount {
+                public_keys: init.public_keys,
+                threshold: 1,
+                vp_code_hash: hash_tx(&vp_code),
+            }
+        );
+
+        let stored_vp_code_hash = Hash::sha256(b"stored vp");
+        let update = account::UpdateAccount {
+            addr: established_address_1(),
+            vp_code_hash: None,
+            stored_vp_code_hash: Some(stored_vp_code_hash),
+            public_keys: vec![],
+            threshold: Some(2),
+        };
+        let tx = tx_with("tx_update_account.wasm", update.serialize_to_vec());
+        assert_eq!(
+            tx.describe().action,
+            TxAction::UpdateAccount {
+                address: update.addr,
+                vp_code_hash: Some(stored_vp_code_hash),
+                public_keys: vec![],
+                threshold: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn test_describe_pos() {
+        let bond = pos::Bond {
+            validator: established_address_1(),
+            amount: token::Amount::native_whole(100),
+            source: Some(established_address_2()),
+        };
+        let tx = tx_with("tx_bond.wasm", bond.serialize_to_vec());
+        assert_eq!(
+            tx.describe().action,
+            TxAction::Bond {
+                source: bond.source.clone(),
+                validator: bond.validator.clone(),
+                amount: bond.amount,
+            }
+        );
+        let tx = tx_with("tx_unbond.wasm", bond.serialize_to_vec());
+        assert_eq!(
+            tx.describe().action,
+            TxAction::Unbond {
+                source: bond.source,
+                validator: bond.validator,
+                amount: bond.amount,
+            }
+        );
+
+        let withdraw = pos::Withdraw {
+            validator: established_address_1(),
+            source: None,
+        };
+        let tx = tx_with("tx_withdraw.wasm", withdraw.serialize_to_vec());
+        let summary = tx.describe();
+        assert_eq!(
+            summary.action,
+            TxAction::Withdraw {
+                source: None,
+                validator: withdraw.validator.clone(),
+            }
+        );
+        // The validator withdraws its own tokens
+        assert!(
+            summary
+                .to_string()
+                .contains(&format!("of {}", withdraw.validator))
+        );
+
+        let pk = keypair_1().ref_to();
+        let tx = tx_with("tx_reveal_pk.wasm", pk.serialize_to_vec());
+        assert_eq!(tx.describe().action, TxAction::RevealPk(pk));
+    }
+
+    #[test]
+    fn test_describe_unknown() {
+        // Unknown tag
+        let tx = tx_with("tx_custom.wasm", b"custom data".to_vec());
+        let summary = tx.describe();
+        assert_eq!(
+            summary.action,
+            TxAction::Unknown {
+                data_hash: *tx.data_sechash(),
+                size: 11,
+            }
+        );
+        assert!(summary.wrapper.is_none());
+        assert!(summary.expiration.is_none());
+
+        // Known tag, but data that doesn't decode as the tagged kind
+        let tx = tx_with("tx_transfer.wasm", b"not a transfer".to_vec());
+        assert_eq!(
+            tx.describe().action,
+            TxAction::Unknown {
+                data_hash: *tx.data_sechash(),
+                size: 14,
+            }
+        );
+
+        // No code tag
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(b"wasm code".to_vec(), None)
+            .add_serialized_data(vec![]);
+        assert_eq!(
+            tx.describe().action,
+            TxAction::Unknown {
+                data_hash: *tx.data_sechash(),
+                size: 0,
+            }
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
@@ -13,6 +13,7 @@ use masp_primitives::transaction::builder::Builder;
 use masp_primitives::transaction::components::sapling::builder::SaplingMetadata;
 use masp_primitives::transaction::Transaction;
 use masp_primitives::zip32::ExtendedFullViewingKey;
+use prost::bytes::Bytes;
 use prost::Message;
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Serialize};
@@ -20,15 +21,18 @@ use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 use super::generated::types;
+use super::section_bytes::{self, SectionBytes};
+use super::section_policy::SectionPolicyError;
 use crate::ledger::gas;
 use crate::ledger::storage::{KeccakHasher, Sha256Hasher, StorageHasher};
+use crate::ledger::storage_api::account::PkResolver;
 use crate::types::account::AccountPublicKeysMap;
 use crate::types::address::Address;
 use crate::types::chain::ChainId;
 use crate::types::keccak::{keccak_hash, KeccakHash};
 use crate::types::key::{self, *};
 use crate::types::storage::Epoch;
-use crate::types::time::DateTimeUtc;
+use crate::types::time::{DateTimeUtc, TimestampError};
 use crate::types::token::MaspDenom;
 use crate::types::transaction::protocol::ProtocolTx;
 use crate::types::transaction::{
@@ -46,13 +50,15 @@ pub enum Error {
     #[error("Timestamp is empty")]
     NoTimestampError,
     #[error("Timestamp is invalid: {0}")]
-    InvalidTimestamp(prost_types::TimestampError),
+    InvalidTimestamp(TimestampError),
     #[error("The section signature is invalid: {0}")]
     InvalidSectionSignature(String),
     #[error("Couldn't serialize transaction from JSON at {0}")]
     InvalidJSONDeserialization(String),
-    #[error("The wrapper signature is invalid.")]
-    InvalidWrapperSignature,
+    #[error("The wrapper signature is invalid: {0}")]
+    InvalidWrapperSignature(VerifySigError),
+    #[error("The section signature is by unknown signer {1} of account {0}")]
+    UnknownSigner(Address, u8),
     #[error("Signature verification went out of gas: {0}")]
     OutOfGas(gas::Error),
 }
@@ -131,9 +137,10 @@ impl Signable<KeccakHash> for SignableEthMessage {
 /// A generic signed data wrapper for serialize-able types.
 ///
 /// The default serialization method is [`BorshSerialize`].
-#[derive(
-    Clone, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
-)]
+///
+/// Its JSON representation is defined in the `signed_json` module, and is
+/// kept stable for off-chain use.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
 pub struct Signed<T, S = SerializeWithBorsh> {
     /// Arbitrary data to be signed
     pub data: T,
@@ -222,6 +229,15 @@ impl<T, S: Signable<T>> Signed<T, S> {
             &signed_bytes,
             &self.sig,
         )
+        .map_err(|err| match err {
+            VerifySigError::MismatchedScheme => err,
+            _ => VerifySigError::InvalidSignature {
+                target: crate::types::hash::Hash(
+                    signed_bytes.signable_hash::<S::Hasher>(),
+                ),
+                signer: pk.clone(),
+            },
+        })
     }
 }
 
@@ -261,7 +277,7 @@ pub fn verify_standalone_sig<T, S: Signable<T>>(
 )]
 pub struct Data {
     pub salt: [u8; 8],
-    pub data: Vec<u8>,
+    pub data: SectionBytes,
 }
 
 impl Data {
@@ -269,17 +285,42 @@ impl Data {
     pub fn new(data: Vec<u8>) -> Self {
         Self {
             salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
-            data,
+            data: data.into(),
         }
     }
 
     /// Hash this data section
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        hasher.update(self.serialize_to_vec());
-        hasher
+        hash_borsh(hasher, self)
+    }
+}
+
+/// A writer feeding the bytes written to it into a hasher
+struct HashWriter<'a>(&'a mut Sha256);
+
+impl std::io::Write for HashWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
+/// Feed the Borsh encoding of the given value into the given hasher, without
+/// allocating the encoding
+fn hash_borsh<'a, T: BorshSerialize + ?Sized>(
+    hasher: &'a mut Sha256,
+    value: &T,
+) -> &'a mut Sha256 {
+    value
+        .serialize(&mut HashWriter(hasher))
+        .expect("Writing to a hasher should not fail");
+    hasher
+}
+
 /// Error representing the case where the supplied code has incorrect hash
 pub struct CommitmentError;
 
@@ -297,14 +338,14 @@ pub enum Commitment {
     /// Result of applying hash function to bytes
     Hash(crate::types::hash::Hash),
     /// Result of applying identity function to bytes
-    Id(Vec<u8>),
+    Id(SectionBytes),
 }
 
 impl Commitment {
     /// Substitute bytes with their SHA-256 hash
     pub fn contract(&mut self) {
         if let Self::Id(code) = self {
-            *self = Self::Hash(hash_tx(code));
+            *self = Self::Hash(code.digest(&[]));
         }
     }
 
@@ -317,17 +358,18 @@ impl Commitment {
         match self {
             Self::Id(c) if *c == code => Ok(()),
             Self::Hash(hash) if *hash == hash_tx(&code) => {
-                *self = Self::Id(code);
+                *self = Self::Id(code.into());
                 Ok(())
             }
             _ => Err(CommitmentError),
         }
     }
 
-    /// Return the contained hash commitment
+    /// Return the contained hash commitment. The hash of code bytes is only
+    /// computed once.
     pub fn hash(&self) -> crate::types::hash::Hash {
         match self {
-            Self::Id(code) => hash_tx(code),
+            Self::Id(code) => code.digest(&[]),
             Self::Hash(hash) => *hash,
         }
     }
@@ -335,7 +377,7 @@ impl Commitment {
     /// Return the result of applying identity function if there is any
     pub fn id(&self) -> Option<Vec<u8>> {
         if let Self::Id(code) = self {
-            Some(code.clone())
+            Some(code.to_vec())
         } else {
             None
         }
@@ -366,7 +408,7 @@ impl Code {
     pub fn new(code: Vec<u8>, tag: Option<String>) -> Self {
         Self {
             salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
-            code: Commitment::Id(code),
+            code: Commitment::Id(code.into()),
             tag,
         }
     }
@@ -387,8 +429,7 @@ impl Code {
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
         hasher.update(self.salt);
         hasher.update(self.code.hash());
-        hasher.update(self.tag.serialize_to_vec());
-        hasher
+        hash_borsh(hasher, &self.tag)
     }
 }
 
@@ -492,10 +533,11 @@ pub struct Signature {
 }
 
 impl Signature {
-    /// Sign the given section hash with the given key and return a section
-    pub fn new(
+    /// Sign the given section hash with the given key and return a section.
+    /// The secret keys can be given by reference to avoid copying them.
+    pub fn new<K: Borrow<common::SecretKey>>(
         targets: Vec<crate::types::hash::Hash>,
-        secret_keys: BTreeMap<u8, common::SecretKey>,
+        secret_keys: BTreeMap<u8, K>,
         signer: Option<Address>,
     ) -> Self {
         // If no signer address is given, then derive the signer's public keys
@@ -509,27 +551,28 @@ impl Signature {
                 secret_keys.keys().cloned().eq(0..(secret_keys.len() as u8)),
                 "secret keys must be enumerateed when signer address is absent"
             );
-            Signer::PubKeys(secret_keys.values().map(RefTo::ref_to).collect())
+            Signer::PubKeys(
+                secret_keys
+                    .values()
+                    .map(|sk| sk.borrow().ref_to())
+                    .collect(),
+            )
         };
 
         // Commit to the given targets
-        let partial = Self {
-            targets,
-            signer,
-            signatures: BTreeMap::new(),
-        };
-        let target = partial.get_raw_hash();
+        let target = Self::raw_hash(&targets);
         // Turn the map of secret keys into a map of signatures over the
         // commitment made above
         let signatures = secret_keys
             .iter()
             .map(|(index, secret_key)| {
-                (*index, common::SigScheme::sign(secret_key, target))
+                (*index, common::SigScheme::sign(secret_key.borrow(), target))
             })
             .collect();
         Self {
+            targets,
+            signer,
             signatures,
-            ..partial
         }
     }
 
@@ -539,8 +582,9 @@ impl Signature {
 
     /// Hash this signature section
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        hasher.update(self.serialize_to_vec());
-        hasher
+        hash_borsh(hasher, &self.targets);
+        hash_borsh(hasher, &self.signer);
+        hash_borsh(hasher, &self.signatures)
     }
 
     /// Get the hash of this section
@@ -550,13 +594,22 @@ impl Signature {
         )
     }
 
+    /// Get the hash signed over by the signers, that of this section without
+    /// its signer nor its signatures
     pub fn get_raw_hash(&self) -> crate::types::hash::Hash {
-        Self {
-            signer: Signer::PubKeys(vec![]),
-            signatures: BTreeMap::new(),
-            ..self.clone()
-        }
-        .get_hash()
+        Self::raw_hash(&self.targets)
+    }
+
+    /// The hash of a signature section over the given targets, without signer
+    /// nor signatures
+    fn raw_hash(
+        targets: &[crate::types::hash::Hash],
+    ) -> crate::types::hash::Hash {
+        let mut hasher = Sha256::new();
+        hash_borsh(&mut hasher, targets);
+        hash_borsh(&mut hasher, &Signer::PubKeys(vec![]));
+        hash_borsh(&mut hasher, &BTreeMap::<u8, common::Signature>::new());
+        crate::types::hash::Hash(hasher.finalize().into())
     }
 
     /// Verify that the signature contained in this section is valid
@@ -572,23 +625,21 @@ impl Signature {
     {
         // Records whether there are any successful verifications
         let mut verifications = 0;
+        let raw_hash = self.get_raw_hash();
         match &self.signer {
             // Verify the signatures against the given public keys if the
             // account addresses match
             Signer::Address(addr) if Some(addr) == signer.as_ref() => {
                 for (idx, sig) in &self.signatures {
-                    if let Some(pk) =
-                        public_keys_index_map.get_public_key_from_index(*idx)
-                    {
-                        consume_verify_sig_gas()?;
-                        common::SigScheme::verify_signature(
-                            &pk,
-                            &self.get_raw_hash(),
-                            sig,
-                        )?;
-                        verified_pks.insert(*idx);
-                        verifications += 1;
-                    }
+                    let pk = public_keys_index_map
+                        .get_public_key_from_index(*idx)
+                        .ok_or_else(|| {
+                            VerifySigError::UnknownSigner(addr.clone(), *idx)
+                        })?;
+                    consume_verify_sig_gas()?;
+                    Self::verify_raw_signature(&pk, &raw_hash, sig)?;
+                    verified_pks.insert(*idx);
+                    verifications += 1;
                 }
             }
             // If the account addresses do not match, then there is no efficient
@@ -602,9 +653,9 @@ impl Signature {
                         public_keys_index_map.get_index_from_public_key(pk)
                     {
                         consume_verify_sig_gas()?;
-                        common::SigScheme::verify_signature(
+                        Self::verify_raw_signature(
                             pk,
-                            &self.get_raw_hash(),
+                            &raw_hash,
                             &self.signatures[&(idx as u8)],
                         )?;
                         verified_pks.insert(map_idx);
@@ -615,6 +666,88 @@ impl Signature {
         }
         Ok(verifications)
     }
+
+    /// Verify a signature of this section over its raw hash, attributing a
+    /// failure to the given signer
+    fn verify_raw_signature(
+        pk: &common::PublicKey,
+        raw_hash: &crate::types::hash::Hash,
+        sig: &common::Signature,
+    ) -> std::result::Result<(), VerifySigError> {
+        common::SigScheme::verify_signature(pk, raw_hash, sig).map_err(|err| {
+            match err {
+                VerifySigError::MismatchedScheme => err,
+                _ => VerifySigError::InvalidSignature {
+                    target: *raw_hash,
+                    signer: pk.clone(),
+                },
+            }
+        })
+    }
+}
+
+/// A section representing a single MuSig2 signature over other sections,
+/// aggregated from the signatures of an ordered set of participants
+#[derive(
+    Clone,
+    Debug,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct AggregatedSignature {
+    /// The hashes of the sections being signed
+    pub targets: Vec<crate::types::hash::Hash>,
+    /// The ordered public keys of the participants
+    pub participants: Vec<secp256k1::PublicKey>,
+    /// The aggregated signature over the targets and the participants
+    pub signature: musig::Signature,
+}
+
+impl AggregatedSignature {
+    /// The hash that the participants sign over, which commits to the given
+    /// targets and to the ordered set of participants
+    pub fn signing_hash(
+        targets: &[crate::types::hash::Hash],
+        participants: &[secp256k1::PublicKey],
+    ) -> crate::types::hash::Hash {
+        let mut hasher = Sha256::new();
+        hash_borsh(&mut hasher, targets);
+        hash_borsh(&mut hasher, participants);
+        crate::types::hash::Hash(hasher.finalize().into())
+    }
+
+    /// Hash this aggregated signature section
+    pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
+        hash_borsh(hasher, self)
+    }
+
+    /// Get the hash of this section
+    pub fn get_hash(&self) -> crate::types::hash::Hash {
+        crate::types::hash::Hash(
+            self.hash(&mut Sha256::new()).finalize_reset().into(),
+        )
+    }
+
+    /// Get the hash signed over by the participants
+    pub fn get_raw_hash(&self) -> crate::types::hash::Hash {
+        Self::signing_hash(&self.targets, &self.participants)
+    }
+
+    /// The public keys of the participants, as they appear in accounts
+    pub fn signers(&self) -> impl Iterator<Item = common::PublicKey> + '_ {
+        self.participants
+            .iter()
+            .cloned()
+            .map(common::PublicKey::Secp256k1)
+    }
+
+    /// Verify that the aggregated signature is valid for the participants
+    pub fn verify_signature(&self) -> std::result::Result<(), VerifySigError> {
+        musig::verify(&self.participants, &self.get_raw_hash(), &self.signature)
+    }
 }
 
 /// A section representing a multisig over another section
@@ -673,15 +806,24 @@ impl CompressedSignature {
 )]
 pub struct Ciphertext {
     /// Ciphertext representation when ferveo not available
-    pub opaque: Vec<u8>,
+    pub opaque: SectionBytes,
 }
 
 impl Ciphertext {
+    /// The number of bytes that encryption adds to a payload: the nonce
+    /// (a compressed G1 point), the authentication tag of the encapsulated key
+    /// (a compressed G2 point) and the tag of the symmetric cipher
+    pub const ENCRYPTION_OVERHEAD: usize = 48 + 96 + 16;
+
+    /// The number of bytes that sealing a section into a ciphertext section
+    /// adds to the encoding of a tx. On top of the encryption overhead, the
+    /// ciphertext section encodes its own variant tag and payload length.
+    pub const SECTION_OVERHEAD: usize = Self::ENCRYPTION_OVERHEAD + 1 + 4;
+
     /// Get the hash of this ciphertext section. This operation is done in such
     /// a way it matches the hash of the type pun
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        hasher.update(self.serialize_to_vec());
-        hasher
+        hash_borsh(hasher, &self.opaque)
     }
 }
 
@@ -786,8 +928,7 @@ impl MaspBuilder {
     /// Get the hash of this ciphertext section. This operation is done in such
     /// a way it matches the hash of the type pun
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        hasher.update(self.serialize_to_vec());
-        hasher
+        hash_borsh(hasher, self)
     }
 }
 
@@ -838,16 +979,19 @@ pub enum Section {
     MaspBuilder(MaspBuilder),
     /// Wrap a header with a section for the purposes of computing hashes
     Header(Header),
+    /// A MuSig2 signature aggregated over a set of participants
+    AggregatedSignature(AggregatedSignature),
+    /// A testnet faucet withdrawal PoW solution attached to a wrapper tx
+    #[cfg(not(feature = "mainnet"))]
+    PowSolution(crate::ledger::testnet_pow::Solution),
 }
 
 impl Section {
     /// Hash this section. Section hashes are useful for signatures and also for
     /// allowing transaction sections to cross reference.
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        // Get the index corresponding to this variant
-        let discriminant = self.serialize_to_vec()[0];
         // Use Borsh's discriminant in the Section's hash
-        hasher.update([discriminant]);
+        hasher.update([self.discriminant()]);
         match self {
             Self::Data(data) => data.hash(hasher),
             Self::ExtraData(extra) => extra.hash(hasher),
@@ -860,14 +1004,52 @@ impl Section {
                 hasher
             }
             Self::Header(header) => header.hash(hasher),
+            Self::AggregatedSignature(aggregated) => aggregated.hash(hasher),
+            #[cfg(not(feature = "mainnet"))]
+            Self::PowSolution(solution) => hash_borsh(hasher, solution),
         }
     }
 
-    /// Get the hash of this section
+    /// Get the index of the variant of this section in its Borsh encoding
+    pub(super) fn discriminant(&self) -> u8 {
+        match self {
+            Self::Data(_) => 0,
+            Self::ExtraData(_) => 1,
+            Self::Code(_) => 2,
+            Self::Signature(_) => 3,
+            Self::Ciphertext(_) => 4,
+            Self::MaspTx(_) => 5,
+            Self::MaspBuilder(_) => 6,
+            Self::Header(_) => 7,
+            Self::AggregatedSignature(_) => 8,
+            #[cfg(not(feature = "mainnet"))]
+            Self::PowSolution(_) => 9,
+        }
+    }
+
+    /// Get the hash of this section. The digests of the payloads of data,
+    /// code and ciphertext sections are memoized by the payloads themselves,
+    /// so that large sections are only hashed once.
     pub fn get_hash(&self) -> crate::types::hash::Hash {
-        crate::types::hash::Hash(
-            self.hash(&mut Sha256::new()).finalize_reset().into(),
-        )
+        // The bytes that the Borsh encoding of this section puts before its
+        // payload, given the fields that precede the payload
+        let payload_prefix = |fields: &[u8], len: usize| {
+            let mut prefix = vec![self.discriminant()];
+            prefix.extend_from_slice(fields);
+            prefix.extend((len as u32).to_le_bytes());
+            prefix
+        };
+        match self {
+            Self::Data(data) => data
+                .data
+                .digest(&payload_prefix(&data.salt, data.data.len())),
+            Self::Ciphertext(ct) => {
+                ct.opaque.digest(&payload_prefix(&[], ct.opaque.len()))
+            }
+            _ => crate::types::hash::Hash(
+                self.hash(&mut Sha256::new()).finalize_reset().into(),
+            ),
+        }
     }
 
     /// Extract the data from this section if possible
@@ -950,8 +1132,21 @@ impl Section {
             None
         }
     }
+
+    /// Extract the testnet PoW solution from this section if possible
+    #[cfg(not(feature = "mainnet"))]
+    pub fn pow_solution(&self) -> Option<crate::ledger::testnet_pow::Solution> {
+        if let Self::PowSolution(solution) = self {
+            Some(solution.clone())
+        } else {
+            None
+        }
+    }
 }
 
+/// The maximum length of a transaction memo in bytes
+pub const MAX_MEMO_LEN: usize = 512;
+
 /// A Namada transaction header indicating where transaction subcomponents can
 /// be found
 #[derive(
@@ -974,6 +1169,9 @@ pub struct Header {
     pub code_hash: crate::types::hash::Hash,
     /// The SHA-256 hash of the transaction's data section
     pub data_hash: crate::types::hash::Hash,
+    /// The SHA-256 hash of the transaction's memo section, or the default
+    /// hash if the transaction has no memo
+    pub memo_hash: crate::types::hash::Hash,
     /// The type of this transaction
     pub tx_type: TxType,
 }
@@ -988,13 +1186,22 @@ impl Header {
             timestamp: DateTimeUtc::now(),
             code_hash: crate::types::hash::Hash::default(),
             data_hash: crate::types::hash::Hash::default(),
+            memo_hash: crate::types::hash::Hash::default(),
         }
     }
 
     /// Get the hash of this transaction header.
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        hasher.update(self.serialize_to_vec());
-        hasher
+        hash_borsh(hasher, self)
+    }
+
+    /// Check that the timestamp and the expiration of this header can be
+    /// represented by protobuf timestamps
+    pub fn check_timestamps(&self) -> std::result::Result<(), TimestampError> {
+        self.timestamp.check_timestamp_range()?;
+        self.expiration
+            .as_ref()
+            .map_or(Ok(()), DateTimeUtc::check_timestamp_range)
     }
 
     /// Get the wrapper header if it is present
@@ -1034,8 +1241,52 @@ pub enum TxError {
     Unsigned(String),
     #[error("{0}")]
     SigError(String),
+    #[error("{tx_kind} signature verification failed: {reason}")]
+    SigVerification {
+        /// The type of the tx whose signature failed verification
+        tx_kind: &'static str,
+        /// The kind of failure
+        failure: SigFailure,
+        /// The description of the failure
+        reason: String,
+    },
     #[error("Failed to deserialize Tx: {0}")]
     Deserialization(String),
+    #[error("Invalid tx timestamp: {0}")]
+    InvalidTimestamp(TimestampError),
+    #[error("Invalid tx sections: {0}")]
+    InvalidSections(SectionPolicyError),
+}
+
+/// The kind of failure of the verification of the signature of a wrapper or
+/// protocol tx, see [`VerifySigError`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigFailure {
+    /// No signature section signs over one of the targets
+    NoSignatureForTarget,
+    /// The targets are signed, but not by the expected key
+    SignerMismatch,
+    /// A signature by the expected key doesn't verify
+    InvalidSignature,
+    /// Any other failure to verify the signature
+    Other,
+}
+
+impl From<&Error> for SigFailure {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::InvalidWrapperSignature(
+                VerifySigError::NoSignatureForTarget { .. },
+            ) => Self::NoSignatureForTarget,
+            Error::InvalidWrapperSignature(
+                VerifySigError::SignerMismatch { .. },
+            ) => Self::SignerMismatch,
+            Error::InvalidWrapperSignature(
+                VerifySigError::InvalidSignature { .. },
+            ) => Self::InvalidSignature,
+            _ => Self::Other,
+        }
+    }
 }
 
 /// A Namada transaction is represented as a header followed by a series of
@@ -1061,8 +1312,18 @@ impl TryFrom<&[u8]> for Tx {
     type Error = Error;
 
     fn try_from(tx_bytes: &[u8]) -> Result<Self> {
+        Self::try_from(Bytes::copy_from_slice(tx_bytes))
+    }
+}
+
+/// Deserialize Tx from protobufs, sharing the given buffer with the payloads
+/// of the data, code and extra data sections instead of copying them
+impl TryFrom<Bytes> for Tx {
+    type Error = Error;
+
+    fn try_from(tx_bytes: Bytes) -> Result<Self> {
         let tx = types::Tx::decode(tx_bytes).map_err(Error::TxDecodingError)?;
-        BorshDeserialize::try_from_slice(&tx.data)
+        section_bytes::deserialize_tx(&tx.data)
             .map_err(Error::TxDeserializingError)
     }
 }
@@ -1116,6 +1377,18 @@ impl Tx {
         }
     }
 
+    /// Serialize tx to pretty-printed JSON, e.g. for inspection
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self)
+            .expect("serializing a transaction to JSON shouldn't fail")
+    }
+
+    /// Deserialize a tx from its JSON representation
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|err| Error::InvalidJSONDeserialization(err.to_string()))
+    }
+
     /// Get the transaction header
     pub fn header(&self) -> Header {
         self.header.clone()
@@ -1126,6 +1399,13 @@ impl Tx {
         Section::Header(self.header.clone()).get_hash()
     }
 
+    /// Get the hash CometBFT computes for this transaction, i.e. the SHA-256
+    /// digest of its protobuf encoding. This only matches the hash reported
+    /// by CometBFT if the same bytes as [`Tx::to_bytes`] were broadcast.
+    pub fn tendermint_hash(&self) -> crate::types::hash::Hash {
+        hash_tx(&self.to_bytes())
+    }
+
     /// Gets the hash of the decrypted transaction's header
     pub fn raw_header_hash(&self) -> crate::types::hash::Hash {
         let mut raw_header = self.header();
@@ -1230,22 +1510,91 @@ impl Tx {
             .as_ref()
             .map(Cow::as_ref)
         {
-            Some(Section::Data(data)) => Some(data.data.clone()),
+            Some(Section::Data(data)) => Some(data.data.to_vec()),
             _ => None,
         }
     }
 
+    /// Get the transaction memo hash stored in the header
+    pub fn memo_sechash(&self) -> &crate::types::hash::Hash {
+        &self.header.memo_hash
+    }
+
+    /// Set the transaction memo hash stored in the header
+    pub fn set_memo_sechash(&mut self, hash: crate::types::hash::Hash) {
+        self.header.memo_hash = hash
+    }
+
+    /// Get the memo designated by the transaction memo hash in the header
+    pub fn memo(&self) -> Option<Vec<u8>> {
+        match self
+            .get_section(self.memo_sechash())
+            .as_ref()
+            .map(Cow::as_ref)
+        {
+            Some(Section::ExtraData(section)) => section.code.id(),
+            _ => None,
+        }
+    }
+
+    /// Get the bytes that external signers, such as hardware wallets, are
+    /// handed in order to sign this transaction. The encoding is canonical, so
+    /// the same transaction always yields the same payload.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        self.serialize_to_vec()
+    }
+
     /// Convert this transaction into protobufs
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = vec![];
         let tx: types::Tx = types::Tx {
-            data: self.serialize_to_vec(),
+            data: self.serialize_to_vec().into(),
         };
         tx.encode(&mut bytes)
             .expect("encoding a transaction failed");
         bytes
     }
 
+    /// Get the length of the Borsh encoding of this transaction, without
+    /// allocating the encoding
+    pub fn encoded_len(&self) -> usize {
+        /// A writer only counting the bytes written to it
+        struct ByteCounter(usize);
+
+        impl std::io::Write for ByteCounter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0 += buf.len();
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut counter = ByteCounter(0);
+        BorshSerialize::serialize(self, &mut counter)
+            .expect("counting the encoded bytes of a tx cannot fail");
+        counter.0
+    }
+
+    /// Get the length of the protobuf encoding of this transaction once all
+    /// of its sections that aren't ciphertexts yet are sealed into ciphertext
+    /// sections. This allows estimating the size dependent costs of a
+    /// transaction before sealing it.
+    pub fn sealed_len(&self) -> usize {
+        let unsealed = self
+            .sections
+            .iter()
+            .filter(|section| !matches!(section, Section::Ciphertext(_)))
+            .count();
+        let data_len =
+            self.encoded_len() + unsealed * Ciphertext::SECTION_OVERHEAD;
+        prost::encoding::key_len(1)
+            + prost::encoding::encoded_len_varint(data_len as u64)
+            + data_len
+    }
+
     /// Verify that the section with the given hash has been signed by the given
     /// public key
     pub fn verify_signatures<F>(
@@ -1267,48 +1616,70 @@ impl Tx {
         let mut witnesses = Vec::new();
 
         for section in &self.sections {
-            if let Section::Signature(signatures) = section {
-                // Check that the hashes being checked are a subset of those in
-                // this section. Also ensure that all the sections the signature
-                // signs over are present.
-                if hashes.iter().all(|x| {
-                    signatures.targets.contains(x) || section.get_hash() == *x
-                }) && signatures
-                    .targets
-                    .iter()
-                    .all(|x| self.get_section(x).is_some())
-                {
-                    if signatures.total_signatures() > max_signatures {
-                        return Err(Error::InvalidSectionSignature(
-                            "too many signatures.".to_string(),
-                        ));
-                    }
+            if let Some(signatures) = self.covering_signature(section, hashes) {
+                if signatures.total_signatures() > max_signatures {
+                    return Err(Error::InvalidSectionSignature(
+                        "too many signatures.".to_string(),
+                    ));
+                }
 
-                    // Finally verify that the signature itself is valid
-                    let amt_verifieds = signatures
-                        .verify_signature(
-                            &mut verified_pks,
-                            &public_keys_index_map,
-                            signer,
-                            &mut consume_verify_sig_gas,
-                        )
-                        .map_err(|e| {
-                            if let VerifySigError::OutOfGas(inner) = e {
-                                Error::OutOfGas(inner)
-                            } else {
-                                Error::InvalidSectionSignature(
-                                    "found invalid signature.".to_string(),
-                                )
-                            }
-                        });
-                    // Record the section witnessing these signatures
-                    if amt_verifieds? > 0 {
-                        witnesses.push(signatures);
-                    }
-                    // Short-circuit these checks if the threshold is exceeded
-                    if verified_pks.len() >= threshold.into() {
-                        return Ok(witnesses);
-                    }
+                // Finally verify that the signature itself is valid
+                let amt_verifieds = signatures
+                    .verify_signature(
+                        &mut verified_pks,
+                        &public_keys_index_map,
+                        signer,
+                        &mut consume_verify_sig_gas,
+                    )
+                    .map_err(|e| match e {
+                        VerifySigError::OutOfGas(inner) => {
+                            Error::OutOfGas(inner)
+                        }
+                        VerifySigError::UnknownSigner(owner, idx) => {
+                            Error::UnknownSigner(owner, idx)
+                        }
+                        err => Error::InvalidSectionSignature(format!(
+                            "found invalid signature: {}",
+                            err
+                        )),
+                    });
+                // Record the section witnessing these signatures
+                if amt_verifieds? > 0 {
+                    witnesses.push(signatures);
+                }
+                // Short-circuit these checks if the threshold is exceeded
+                if verified_pks.len() >= threshold.into() {
+                    return Ok(witnesses);
+                }
+            } else if let Some(aggregated) =
+                self.covering_aggregated_signature(section, hashes)
+            {
+                if aggregated.participants.len() > usize::from(max_signatures) {
+                    return Err(Error::InvalidSectionSignature(
+                        "too many signatures.".to_string(),
+                    ));
+                }
+                // A valid aggregated signature stands for the signatures of
+                // all of its participants
+                let indices: Vec<_> = aggregated
+                    .signers()
+                    .filter_map(|pk| {
+                        public_keys_index_map.get_index_from_public_key(&pk)
+                    })
+                    .collect();
+                if indices.is_empty() {
+                    continue;
+                }
+                consume_verify_sig_gas().map_err(Error::OutOfGas)?;
+                aggregated.verify_signature().map_err(|err| {
+                    Error::InvalidSectionSignature(format!(
+                        "found invalid signature: {}",
+                        err
+                    ))
+                })?;
+                verified_pks.extend(indices);
+                if verified_pks.len() >= threshold.into() {
+                    return Ok(witnesses);
                 }
             }
         }
@@ -1327,16 +1698,200 @@ impl Tx {
         public_key: &common::PublicKey,
         hashes: &[crate::types::hash::Hash],
     ) -> Result<&Signature> {
-        self.verify_signatures(
-            hashes,
-            AccountPublicKeysMap::from_iter([public_key.clone()].into_iter()),
-            &None,
-            1,
-            None,
-            || Ok(()),
-        )
-        .map(|x| *x.first().unwrap())
-        .map_err(|_| Error::InvalidWrapperSignature)
+        let public_keys_index_map =
+            AccountPublicKeysMap::from_iter([public_key.clone()].into_iter());
+        // Records whether any signature section covers the given hashes
+        let mut covered = false;
+        for section in &self.sections {
+            if let Some(signatures) = self.covering_signature(section, hashes) {
+                covered = true;
+                let verifications = signatures
+                    .verify_signature(
+                        &mut HashSet::new(),
+                        &public_keys_index_map,
+                        &None,
+                        &mut || Ok(()),
+                    )
+                    .map_err(Error::InvalidWrapperSignature)?;
+                if verifications > 0 {
+                    return Ok(signatures);
+                }
+            }
+        }
+        let err = if covered {
+            VerifySigError::SignerMismatch {
+                expected: public_key.clone(),
+            }
+        } else {
+            // Report the first target that no single signature section
+            // covers on its own
+            let target = hashes
+                .iter()
+                .find(|hash| {
+                    !self.sections.iter().any(|section| {
+                        self.covering_signature(section, &[**hash]).is_some()
+                    })
+                })
+                .or_else(|| hashes.first())
+                .copied()
+                .unwrap_or_default();
+            VerifySigError::NoSignatureForTarget { target }
+        };
+        Err(Error::InvalidWrapperSignature(err))
+    }
+
+    /// Get the given section as a signature section if it signs over all the
+    /// given hashes and all the sections it signs over are present in this
+    /// transaction.
+    fn covering_signature<'a>(
+        &self,
+        section: &'a Section,
+        hashes: &[crate::types::hash::Hash],
+    ) -> Option<&'a Signature> {
+        match section {
+            Section::Signature(signatures)
+                if self.signs_over(section, &signatures.targets, hashes) =>
+            {
+                Some(signatures)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the given section as an aggregated signature section if it signs
+    /// over all the given hashes and all the sections it signs over are
+    /// present in this transaction.
+    fn covering_aggregated_signature<'a>(
+        &self,
+        section: &'a Section,
+        hashes: &[crate::types::hash::Hash],
+    ) -> Option<&'a AggregatedSignature> {
+        match section {
+            Section::AggregatedSignature(aggregated)
+                if self.signs_over(section, &aggregated.targets, hashes) =>
+            {
+                Some(aggregated)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check that the hashes being checked are a subset of the targets of the
+    /// given signature section. Also ensure that all the sections the
+    /// signature signs over are present.
+    fn signs_over(
+        &self,
+        section: &Section,
+        targets: &[crate::types::hash::Hash],
+        hashes: &[crate::types::hash::Hash],
+    ) -> bool {
+        hashes
+            .iter()
+            .all(|x| targets.contains(x) || section.get_hash() == *x)
+            && targets.iter().all(|x| self.get_section(x).is_some())
+    }
+
+    /// Resolve the public keys of the signatures made on behalf of `owner`
+    /// over the sections with the given hashes, to verify them with
+    /// [`Tx::verify_signatures`]. The keys are looked up with `resolver` by
+    /// their index in the account's set of keys. The keys that cannot be
+    /// resolved are left out of the map, so that verifying their signatures
+    /// fails with [`Error::UnknownSigner`].
+    pub fn resolve_signer_keys<E, R>(
+        &self,
+        hashes: &[crate::types::hash::Hash],
+        owner: &Address,
+        resolver: &mut R,
+    ) -> std::result::Result<AccountPublicKeysMap, E>
+    where
+        R: PkResolver<E>,
+    {
+        let mut keys = AccountPublicKeysMap::default();
+        for section in &self.sections {
+            let Section::Signature(signatures) = section else {
+                continue;
+            };
+            let Signer::Address(addr) = &signatures.signer else {
+                continue;
+            };
+            // Same section selection as in `Tx::verify_signatures`
+            if addr != owner
+                || !hashes.iter().all(|x| {
+                    signatures.targets.contains(x) || section.get_hash() == *x
+                })
+            {
+                continue;
+            }
+            for idx in signatures.signatures.keys() {
+                if keys.idx_to_pk.contains_key(idx) {
+                    continue;
+                }
+                if let Some(pk) = resolver.resolve_pk(owner, *idx)? {
+                    keys.pk_to_idx.insert(pk.clone(), *idx);
+                    keys.idx_to_pk.insert(*idx, pk);
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Get the public keys that made valid signatures in the signature
+    /// sections of this tx. The public keys of signatures made on behalf of an
+    /// account are obtained from `resolver`, given the account's address and
+    /// the index of the key. Signature sections over sections that are
+    /// missing from this tx and invalid signatures are ignored.
+    pub fn signers<E, R, G>(
+        &self,
+        mut resolver: R,
+        mut consume_verify_sig_gas: G,
+    ) -> std::result::Result<BTreeSet<common::PublicKey>, E>
+    where
+        R: PkResolver<E>,
+        G: FnMut() -> std::result::Result<(), E>,
+    {
+        let mut signers = BTreeSet::new();
+        for section in &self.sections {
+            if let Section::AggregatedSignature(aggregated) = section {
+                if aggregated
+                    .targets
+                    .iter()
+                    .all(|x| self.get_section(x).is_some())
+                {
+                    consume_verify_sig_gas()?;
+                    if aggregated.verify_signature().is_ok() {
+                        signers.extend(aggregated.signers());
+                    }
+                }
+                continue;
+            }
+            let Section::Signature(signatures) = section else {
+                continue;
+            };
+            if !signatures
+                .targets
+                .iter()
+                .all(|x| self.get_section(x).is_some())
+            {
+                continue;
+            }
+            let raw_hash = signatures.get_raw_hash();
+            for (idx, sig) in &signatures.signatures {
+                let pk = match &signatures.signer {
+                    Signer::PubKeys(pks) => pks.get(usize::from(*idx)).cloned(),
+                    Signer::Address(addr) => resolver.resolve_pk(addr, *idx)?,
+                };
+                let Some(pk) = pk else {
+                    continue;
+                };
+                consume_verify_sig_gas()?;
+                if common::SigScheme::verify_signature(&pk, &raw_hash, sig)
+                    .is_ok()
+                {
+                    signers.insert(pk);
+                }
+            }
+        }
+        Ok(signers)
     }
 
     pub fn compute_section_signature(
@@ -1349,7 +1904,7 @@ impl Tx {
         let mut signatures = Vec::new();
         let section = Signature::new(
             targets,
-            public_keys_index_map.index_secret_keys(secret_keys.to_vec()),
+            public_keys_index_map.index_secret_keys(secret_keys),
             signer,
         );
         match section.signer {
@@ -1377,6 +1932,60 @@ impl Tx {
         signatures
     }
 
+    /// Get the signatures by the fee payer that [`Tx::validate_tx`] checks
+    /// to authorize a wrapper tx, each paired with its public key and the
+    /// hash it signs. This allows checking the wrappers of many txs in a
+    /// single batch. Returns [`None`] if this is not a wrapper tx, if its fee
+    /// payer's key is not a user key or if it has no well-formed signature
+    /// section by its fee payer over all of its sections.
+    pub fn wrapper_signatures(
+        &self,
+    ) -> Option<
+        Vec<(
+            common::PublicKey,
+            crate::types::hash::Hash,
+            common::Signature,
+        )>,
+    > {
+        let TxType::Wrapper(wrapper) = &self.header.tx_type else {
+            return None;
+        };
+        if !wrapper.pk.is_user_key() {
+            return None;
+        }
+        let hashes = self.sechashes();
+        for section in &self.sections {
+            let Section::Signature(signatures) = section else {
+                continue;
+            };
+            let Signer::PubKeys(pks) = &signatures.signer else {
+                continue;
+            };
+            // Same section selection as in `Tx::verify_signatures`
+            if !hashes.iter().all(|x| {
+                signatures.targets.contains(x) || section.get_hash() == *x
+            }) || !signatures
+                .targets
+                .iter()
+                .all(|x| self.get_section(x).is_some())
+            {
+                continue;
+            }
+            let raw_hash = signatures.get_raw_hash();
+            let mut items = vec![];
+            for (idx, pk) in pks.iter().enumerate() {
+                if *pk == wrapper.pk {
+                    let sig = signatures.signatures.get(&(idx as u8))?;
+                    items.push((pk.clone(), raw_hash, sig.clone()));
+                }
+            }
+            if !items.is_empty() {
+                return Some(items);
+            }
+        }
+        None
+    }
+
     /// Determines the type of the input Tx
     ///
     /// If it is a raw Tx, signed or not, the Tx is
@@ -1394,26 +2003,37 @@ impl Tx {
     pub fn validate_tx(
         &self,
     ) -> std::result::Result<Option<&Signature>, TxError> {
+        // Txs whose times are out of range are rejected, not normalized
+        self.header
+            .check_timestamps()
+            .map_err(TxError::InvalidTimestamp)?;
+        // Txs whose sections don't fit the type of their header are rejected
+        self.check_sections().map_err(TxError::InvalidSections)?;
         match &self.header.tx_type {
+            // only user keys can pay for and sign wrappers
+            TxType::Wrapper(wrapper) if !wrapper.pk.is_user_key() => {
+                Err(TxError::SigError(
+                    "WrapperTx cannot be signed by a protocol-only key"
+                        .to_string(),
+                ))
+            }
             // verify signature and extract signed data
             TxType::Wrapper(wrapper) => self
                 .verify_signature(&wrapper.pk, &self.sechashes())
                 .map(Option::Some)
-                .map_err(|err| {
-                    TxError::SigError(format!(
-                        "WrapperTx signature verification failed: {}",
-                        err
-                    ))
+                .map_err(|err| TxError::SigVerification {
+                    tx_kind: "WrapperTx",
+                    failure: SigFailure::from(&err),
+                    reason: err.to_string(),
                 }),
             // verify signature and extract signed data
             TxType::Protocol(protocol) => self
                 .verify_signature(&protocol.pk, &self.sechashes())
                 .map(Option::Some)
-                .map_err(|err| {
-                    TxError::SigError(format!(
-                        "ProtocolTx signature verification failed: {}",
-                        err
-                    ))
+                .map_err(|err| TxError::SigVerification {
+                    tx_kind: "ProtocolTx",
+                    failure: SigFailure::from(&err),
+                    reason: err.to_string(),
                 }),
             // we extract the signed data, but don't check the signature
             TxType::Decrypted(_) => Ok(None),
@@ -1484,6 +2104,19 @@ impl Tx {
         (self, sechash)
     }
 
+    /// Add a memo section to the transaction and set the memo hash in the
+    /// header
+    pub fn add_memo(
+        &mut self,
+        memo: &[u8],
+    ) -> (&mut Self, crate::types::hash::Hash) {
+        let sechash = self
+            .add_section(Section::ExtraData(Code::new(memo.to_vec(), None)))
+            .get_hash();
+        self.set_memo_sechash(sechash);
+        (self, sechash)
+    }
+
     /// Add a masp tx section to the tx builder
     pub fn add_masp_tx_section(
         &mut self,
@@ -1499,6 +2132,23 @@ impl Tx {
         self
     }
 
+    /// Add a testnet faucet withdrawal PoW solution section to the tx builder
+    #[cfg(not(feature = "mainnet"))]
+    pub fn add_pow_solution(
+        &mut self,
+        solution: crate::ledger::testnet_pow::Solution,
+    ) -> &mut Self {
+        let _sec = self.add_section(Section::PowSolution(solution));
+        self
+    }
+
+    /// Get the testnet faucet withdrawal PoW solution attached to this tx, if
+    /// any
+    #[cfg(not(feature = "mainnet"))]
+    pub fn pow_solution(&self) -> Option<crate::ledger::testnet_pow::Solution> {
+        self.sections.iter().find_map(Section::pow_solution)
+    }
+
     /// Add wasm code to the tx builder from hash
     pub fn add_code_from_hash(
         &mut self,
@@ -1,11 +1,13 @@
 //! Helper structures to manage accounts
 
+use std::borrow::Borrow;
 use std::collections::{BTreeMap, HashMap};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use super::address::Address;
+use super::hash::Hash;
 use super::key::{common, RefTo};
 
 #[derive(
@@ -90,17 +92,37 @@ impl AccountPublicKeysMap {
         self.pk_to_idx.get(public_key).cloned()
     }
 
-    /// Index the given set of secret keys
-    pub fn index_secret_keys(
+    /// Index the given set of secret keys, either owned or borrowed
+    pub fn index_secret_keys<K: Borrow<common::SecretKey>>(
         &self,
-        secret_keys: Vec<common::SecretKey>,
-    ) -> BTreeMap<u8, common::SecretKey> {
+        secret_keys: impl IntoIterator<Item = K>,
+    ) -> BTreeMap<u8, K> {
         secret_keys
             .into_iter()
-            .filter_map(|secret_key: common::SecretKey| {
-                self.get_index_from_public_key(&secret_key.ref_to())
+            .filter_map(|secret_key| {
+                self.get_index_from_public_key(&secret_key.borrow().ref_to())
                     .map(|index| (index, secret_key))
             })
             .collect()
     }
 }
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+/// An entry of the history of an account's validity predicate updates
+pub struct VpUpdate {
+    /// The hash of the VP code replaced by the update, if any
+    pub old_vp_code_hash: Option<Hash>,
+    /// The hash of the VP code installed by the update
+    pub new_vp_code_hash: Hash,
+    /// The header hash of the tx that applied the update
+    pub tx_hash: Hash,
+}
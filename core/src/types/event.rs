@@ -0,0 +1,54 @@
+//! Events emitted by transactions
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+/// The type of the event emitted on token transfers
+pub const TRANSFER_EVENT: &str = "transfer";
+
+/// The type of the event emitted on initialization of a new account
+pub const INIT_ACCOUNT_EVENT: &str = "init_account";
+
+/// The type of the event emitted on updates of an account's VP
+pub const VP_UPDATE_EVENT: &str = "vp_update";
+
+/// An event emitted by a transaction. The events of a transaction are only
+/// reported when it's accepted, in the order in which they were emitted.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct TxEvent {
+    /// The type of the event
+    pub event_type: String,
+    /// The attributes of the event
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl TxEvent {
+    /// Create a new event of the given type, without attributes
+    pub fn new(event_type: impl Into<String>) -> Self {
+        Self {
+            event_type: event_type.into(),
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Add an attribute to the event
+    pub fn with(
+        mut self,
+        key: impl Into<String>,
+        value: impl ToString,
+    ) -> Self {
+        self.attributes.insert(key.into(), value.to_string());
+        self
+    }
+}
@@ -4,8 +4,9 @@ use std::fmt::{self, Display};
 use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use data_encoding::HEXUPPER;
-use serde::{Deserialize, Serialize};
+use data_encoding::{HEXLOWER, HEXLOWER_PERMISSIVE, HEXUPPER};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
@@ -18,10 +19,15 @@ pub const HEX_HASH_LENGTH: usize = HASH_LENGTH * 2;
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("TEMPORARY error: {error}")]
-    Temporary { error: String },
-    #[error("Failed trying to convert slice to a hash: {0}")]
-    ConversionFailed(std::array::TryFromSliceError),
+    #[error(
+        "Unexpected hash length of {0} bytes, expected {HASH_LENGTH} bytes"
+    )]
+    InvalidLength(usize),
+    #[error(
+        "Unexpected hex hash length of {0} characters, expected \
+         {HEX_HASH_LENGTH} characters"
+    )]
+    InvalidHexLength(usize),
     #[error("Failed to convert string into a hash: {0}")]
     FromStringError(data_encoding::DecodeError),
 }
@@ -42,18 +48,27 @@ pub type HashResult<T> = std::result::Result<T, Error>;
     BorshSerialize,
     BorshDeserialize,
     BorshSchema,
-    Serialize,
-    Deserialize,
 )]
 /// A hash, typically a sha-2 hash of a tx
 pub struct Hash(pub [u8; HASH_LENGTH]);
 
+/// Upper case hex, as in the events and the query paths
 impl Display for Hash {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", HEXUPPER.encode(&self.0))
     }
 }
 
+/// Lower case hex, prefixed with `0x` in the alternate form
+impl fmt::LowerHex for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        write!(f, "{}", HEXLOWER.encode(&self.0))
+    }
+}
+
 impl AsRef<[u8]> for Hash {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -64,18 +79,7 @@ impl TryFrom<&[u8]> for Hash {
     type Error = self::Error;
 
     fn try_from(value: &[u8]) -> HashResult<Self> {
-        if value.len() != HASH_LENGTH {
-            return Err(Error::Temporary {
-                error: format!(
-                    "Unexpected tx hash length {}, expected {}",
-                    value.len(),
-                    HASH_LENGTH
-                ),
-            });
-        }
-        let hash: [u8; HASH_LENGTH] =
-            TryFrom::try_from(value).map_err(Error::ConversionFailed)?;
-        Ok(Hash(hash))
+        Self::try_from_slice_exact(value)
     }
 }
 
@@ -91,18 +95,78 @@ impl TryFrom<&str> for Hash {
     type Error = self::Error;
 
     fn try_from(string: &str) -> HashResult<Self> {
-        let vec = HEXUPPER
-            .decode(string.to_uppercase().as_ref())
-            .map_err(Error::FromStringError)?;
-        Self::try_from(&vec[..])
+        string.parse()
     }
 }
 
+/// Parses upper or lower case hex, with or without a `0x` prefix
 impl FromStr for Hash {
     type Err = self::Error;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        Self::try_from(str)
+        let hex = str
+            .strip_prefix("0x")
+            .or_else(|| str.strip_prefix("0X"))
+            .unwrap_or(str);
+        if hex.len() != HEX_HASH_LENGTH {
+            return Err(Error::InvalidHexLength(hex.len()));
+        }
+        let mut hash = [0; HASH_LENGTH];
+        HEXLOWER_PERMISSIVE
+            .decode_mut(hex.as_bytes(), &mut hash)
+            .map_err(|err| Error::FromStringError(err.error))?;
+        Ok(Self(hash))
+    }
+}
+
+/// A hex string in human-readable formats, bytes otherwise
+impl Serialize for Hash {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HashVisitor;
+
+        impl<'de> Visitor<'de> for HashVisitor {
+            type Value = Hash;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a hex string or {HASH_LENGTH} bytes")
+            }
+
+            fn visit_str<E>(self, hex: &str) -> Result<Hash, E>
+            where
+                E: de::Error,
+            {
+                hex.parse().map_err(E::custom)
+            }
+
+            fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Hash, E>
+            where
+                E: de::Error,
+            {
+                Hash::try_from_slice_exact(bytes).map_err(E::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(HashVisitor)
+        } else {
+            deserializer.deserialize_bytes(HashVisitor)
+        }
     }
 }
 
@@ -114,6 +178,14 @@ impl Hash {
         Self(*digest.as_ref())
     }
 
+    /// Convert the given bytes to a hash, failing if they are not exactly
+    /// [`HASH_LENGTH`] bytes long
+    pub fn try_from_slice_exact(bytes: &[u8]) -> HashResult<Self> {
+        <[u8; HASH_LENGTH]>::try_from(bytes)
+            .map(Self)
+            .map_err(|_| Error::InvalidLength(bytes.len()))
+    }
+
     /// Return zeros
     pub fn zero() -> Self {
         Self([0u8; HASH_LENGTH])
@@ -148,6 +220,7 @@ impl From<Hash> for crate::tendermint::Hash {
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use proptest::prelude::*;
     use proptest::string::{string_regex, RegexGeneratorStrategy};
 
@@ -164,4 +237,87 @@ mod tests {
             let _: Hash = hex_hash.try_into().unwrap();
         }
     }
+
+    #[test]
+    fn test_hash_parse_forms() {
+        let hash = Hash::sha256(b"hash");
+        let upper = hash.to_string();
+        let lower = format!("{:x}", hash);
+        assert_eq!(upper, HEXUPPER.encode(&hash.0));
+        assert_eq!(lower, upper.to_lowercase());
+        assert_eq!(format!("{:#x}", hash), format!("0x{lower}"));
+        let mixed: String = upper
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if i % 2 == 0 {
+                    c.to_ascii_lowercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        for hex in [
+            upper.clone(),
+            lower.clone(),
+            mixed,
+            format!("0x{lower}"),
+            format!("0x{upper}"),
+            format!("0X{upper}"),
+        ] {
+            assert_eq!(hex.parse::<Hash>().unwrap(), hash);
+            assert_eq!(Hash::try_from(hex.as_str()).unwrap(), hash);
+        }
+
+        // Invalid lengths
+        for hex in ["", "0x", &upper[1..], format!("{upper}00").as_str()] {
+            let len = hex.trim_start_matches("0x").len();
+            assert_matches!(
+                hex.parse::<Hash>(),
+                Err(Error::InvalidHexLength(l)) if l == len
+            );
+        }
+        assert_matches!(
+            format!("0x0x{}", &lower[2..]).parse::<Hash>(),
+            Err(Error::FromStringError(_))
+        );
+        // Non-hex input, reported at its position
+        let mut invalid = lower.clone();
+        invalid.replace_range(10..11, "g");
+        assert_matches!(
+            invalid.parse::<Hash>(),
+            Err(Error::FromStringError(err)) if err.position == 10
+        );
+
+        // Bytes
+        assert_eq!(Hash::try_from_slice_exact(&hash.0).unwrap(), hash);
+        for len in [0, HASH_LENGTH - 1, HASH_LENGTH + 1] {
+            assert_matches!(
+                Hash::try_from_slice_exact(&vec![0; len]),
+                Err(Error::InvalidLength(l)) if l == len
+            );
+            assert!(Hash::try_from(&vec![0; len][..]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_hash_serde() {
+        let hash = Hash::sha256(b"hash");
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(json, format!("\"{hash}\""));
+        assert_eq!(serde_json::from_str::<Hash>(&json).unwrap(), hash);
+        let lower = format!("\"{:#x}\"", hash);
+        assert_eq!(serde_json::from_str::<Hash>(&lower).unwrap(), hash);
+        assert!(serde_json::from_str::<Hash>("\"0x1234\"").is_err());
+        assert!(serde_json::from_str::<Hash>("[1, 2, 3]").is_err());
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Hashes {
+            hash: Hash,
+        }
+        let hashes = Hashes { hash };
+        let toml = toml::to_string(&hashes).unwrap();
+        assert_eq!(toml.trim(), format!("hash = \"{hash}\""));
+        assert_eq!(toml::from_str::<Hashes>(&toml).unwrap(), hashes);
+    }
 }
@@ -47,11 +47,19 @@ impl From<bool> for HostEnvResult {
 }
 
 mod tx_queue {
+    use std::collections::VecDeque;
+    use std::io::{Read, Result, Write};
+
     use borsh::{BorshDeserialize, BorshSerialize};
 
     use crate::ledger::gas::Gas;
     use crate::proto::Tx;
 
+    /// The ceiling on the total encoded length of the wrappers of a
+    /// [`TxQueue`]. Proposals whose wrappers exceed it are rejected, so it is
+    /// part of the protocol and must not be configurable per node.
+    pub const TX_QUEUE_MAX_BYTES: usize = 32 * 1024 * 1024;
+
     /// A wrapper for `crate::types::transaction::WrapperTx` to conditionally
     /// add `has_valid_pow` flag for only used in testnets.
     #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
@@ -62,43 +70,192 @@ mod tx_queue {
         /// This allows for a more detailed logging about the gas used by the
         /// wrapper and that used by the inner
         pub gas: Gas,
+        /// Whether the wrapper tx carried a valid testnet faucet withdrawal
+        /// PoW solution
+        #[cfg(not(feature = "mainnet"))]
+        pub has_valid_pow: bool,
+    }
+
+    impl TxInQueue {
+        /// The encoded length of the wrapper tx, which the queue accounts
+        /// for
+        pub fn encoded_len(&self) -> usize {
+            self.tx.encoded_len()
+        }
     }
 
-    #[derive(Default, Debug, Clone, BorshDeserialize, BorshSerialize)]
-    /// Wrapper txs to be decrypted in the next block proposal
-    pub struct TxQueue(std::collections::VecDeque<TxInQueue>);
+    /// Wrapper txs to be decrypted in the next block proposal.
+    ///
+    /// The queue keeps track of the total encoded length of its wrappers,
+    /// which bounds the memory they pin between blocks. Only the wrappers
+    /// are encoded: the total is recomputed when the queue is decoded, and
+    /// the ceiling on it is [`TX_QUEUE_MAX_BYTES`].
+    #[derive(Debug, Clone)]
+    pub struct TxQueue {
+        queue: VecDeque<TxInQueue>,
+        /// The total encoded length of the queued wrappers
+        bytes: usize,
+        /// The ceiling on the total encoded length of the queued wrappers
+        max_bytes: usize,
+    }
+
+    impl Default for TxQueue {
+        fn default() -> Self {
+            Self {
+                queue: VecDeque::new(),
+                bytes: 0,
+                max_bytes: TX_QUEUE_MAX_BYTES,
+            }
+        }
+    }
 
     impl TxQueue {
-        /// Add a new wrapper at the back of the queue
+        /// Add a new wrapper at the back of the queue, regardless of the
+        /// ceiling on the length of the queue. This is used for wrappers
+        /// that were already committed to by a block.
         pub fn push(&mut self, wrapper: TxInQueue) {
-            self.0.push_back(wrapper);
+            self.bytes += wrapper.encoded_len();
+            self.queue.push_back(wrapper);
+        }
+
+        /// Add a new wrapper at the back of the queue if it has room for it.
+        /// Otherwise, the wrapper is given back.
+        pub fn try_push(
+            &mut self,
+            wrapper: TxInQueue,
+        ) -> std::result::Result<(), TxInQueue> {
+            if !self.has_room_for(wrapper.encoded_len()) {
+                return Err(wrapper);
+            }
+            self.push(wrapper);
+            Ok(())
         }
 
         /// Remove the wrapper at the head of the queue
         pub fn pop(&mut self) -> Option<TxInQueue> {
-            self.0.pop_front()
+            let wrapper = self.queue.pop_front()?;
+            self.bytes -= wrapper.encoded_len();
+            Some(wrapper)
         }
 
         /// Get an iterator over the queue
         pub fn iter(&self) -> impl std::iter::Iterator<Item = &TxInQueue> {
-            self.0.iter()
+            self.queue.iter()
         }
 
         /// Check if there are any txs in the queue
         #[allow(dead_code)]
         pub fn is_empty(&self) -> bool {
-            self.0.is_empty()
+            self.queue.is_empty()
+        }
+
+        /// Get the number of queued wrappers
+        pub fn len(&self) -> usize {
+            self.queue.len()
         }
 
         /// Get reference to the element at the given index.
         /// Returns [`None`] if index exceeds the queue lenght.
         pub fn get(&self, index: usize) -> Option<&TxInQueue> {
-            self.0.get(index)
+            self.queue.get(index)
+        }
+
+        /// Get the total encoded length of the queued wrappers
+        pub fn bytes(&self) -> usize {
+            self.bytes
+        }
+
+        /// Get the ceiling on the total encoded length of the queued
+        /// wrappers
+        pub fn max_bytes(&self) -> usize {
+            self.max_bytes
+        }
+
+        /// Set the ceiling on the total encoded length of the queued
+        /// wrappers
+        #[cfg(any(test, feature = "testing"))]
+        pub fn set_max_bytes(&mut self, max_bytes: usize) {
+            self.max_bytes = max_bytes;
+        }
+
+        /// Check if wrappers of the given total encoded length can be added
+        /// to the queue without exceeding its ceiling
+        pub fn has_room_for(&self, bytes: usize) -> bool {
+            self.bytes
+                .checked_add(bytes)
+                .map_or(false, |total| total <= self.max_bytes)
+        }
+    }
+
+    impl BorshSerialize for TxQueue {
+        fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            BorshSerialize::serialize(&self.queue, writer)
+        }
+    }
+
+    impl BorshDeserialize for TxQueue {
+        fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+            let mut tx_queue = Self::default();
+            for wrapper in VecDeque::<TxInQueue>::deserialize_reader(reader)? {
+                tx_queue.push(wrapper);
+            }
+            Ok(tx_queue)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use borsh_ext::BorshSerializeExt;
+
+        use super::*;
+        use crate::types::chain::ChainId;
+
+        /// A queued wrapper with a payload of the given length
+        fn wrapper(payload_len: usize) -> TxInQueue {
+            let mut tx = Tx::new(ChainId::default(), None);
+            tx.add_serialized_data(vec![0; payload_len]);
+            TxInQueue {
+                tx,
+                gas: Gas::default(),
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow: false,
+            }
+        }
+
+        /// Fill the queue up to its ceiling and check that further wrappers
+        /// are refused, and that the accounting holds through dequeuing and
+        /// the decoding of a persisted queue.
+        #[test]
+        fn test_tx_queue_byte_ceiling() {
+            let len = wrapper(1024).encoded_len();
+            let mut tx_queue = TxQueue::default();
+            tx_queue.set_max_bytes(4 * len + len / 2);
+            for _ in 0..4 {
+                tx_queue.try_push(wrapper(1024)).unwrap();
+            }
+            assert_eq!(tx_queue.bytes(), 4 * len);
+            // Neither a wrapper of the same size nor a larger one fit
+            assert!(tx_queue.try_push(wrapper(1024)).is_err());
+            assert!(tx_queue.try_push(wrapper(64 * 1024)).is_err());
+            assert_eq!(tx_queue.len(), 4);
+            assert!(tx_queue.bytes() <= tx_queue.max_bytes());
+            // A wrapper fits once another one is dequeued
+            tx_queue.pop().unwrap();
+            assert_eq!(tx_queue.bytes(), 3 * len);
+            tx_queue.try_push(wrapper(1024)).unwrap();
+
+            // The accounting is carried over through the encoding
+            let mut decoded =
+                TxQueue::try_from_slice(&tx_queue.serialize_to_vec()).unwrap();
+            assert_eq!(decoded.len(), 4);
+            assert_eq!(decoded.bytes(), tx_queue.bytes());
+            while decoded.pop().is_some() {}
+            assert_eq!(decoded.bytes(), 0);
         }
     }
 }
 
-pub use tx_queue::{TxInQueue, TxQueue};
+pub use tx_queue::{TxInQueue, TxQueue, TX_QUEUE_MAX_BYTES};
 
 /// Expired transaction kinds.
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
@@ -0,0 +1,799 @@
+//! BLS12-381 keys and related functionality
+//!
+//! BLS signatures made by many signers over the same data can be aggregated
+//! into a single signature, which is verified against the aggregate of the
+//! signers' public keys. These keys are meant for the protocol signatures of
+//! validators and must not be used to sign user transactions.
+//!
+//! Public keys live in G1 and signatures in G2. Aggregating public keys is
+//! only safe against rogue key attacks if every key has come with a proof of
+//! possession of its secret key, checked with [`verify_possession`].
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{
+    pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar,
+};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
+use data_encoding::HEXLOWER;
+#[cfg(feature = "rand")]
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use super::{
+    ParsePublicKeyError, ParseSecretKeyError, ParseSignatureError, RefTo,
+    SchemeType, SigScheme as SigSchemeTrait, SignableBytes, VerifySigError,
+};
+use crate::types::key::StorageHasher;
+
+const PUBLIC_KEY_LENGTH: usize = 48;
+const SECRET_KEY_LENGTH: usize = 32;
+const SIGNATURE_LENGTH: usize = 96;
+
+/// Domain separation tag of the hash to G2 of signed data
+const SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag of the hash to G2 of proven public keys
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation prefix of the derivation of secret keys from seeds
+const KEYGEN_DST: &[u8] = b"NAMADA_BLS_KEYGEN_";
+
+fn hash_to_g2(data: &[u8], dst: &[u8]) -> G2Affine {
+    let point = <G2Projective as HashToCurve<
+        ExpandMsgXmd<sha2_10::Sha256>,
+    >>::hash_to_curve(data, dst);
+    G2Affine::from(point)
+}
+
+/// Check that `sig` is the signature of the point `msg` by the secret key of
+/// `pk`
+fn pairing_check(pk: &G1Affine, msg: &G2Affine, sig: &G2Affine) -> bool {
+    pairing(pk, msg) == pairing(&G1Affine::generator(), sig)
+}
+
+fn invalid_input(msg: &'static str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+}
+
+/// BLS public key
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicKey(pub G1Affine);
+
+impl super::PublicKey for PublicKey {
+    const TYPE: SchemeType = SigScheme::TYPE;
+
+    fn try_from_pk<PK: super::PublicKey>(
+        pk: &PK,
+    ) -> Result<Self, ParsePublicKeyError> {
+        if PK::TYPE == super::common::PublicKey::TYPE {
+            super::common::PublicKey::try_from_pk(pk).and_then(|x| match x {
+                super::common::PublicKey::Bls(epk) => Ok(epk),
+                _ => Err(ParsePublicKeyError::MismatchedScheme),
+            })
+        } else if PK::TYPE == Self::TYPE {
+            Self::try_from_slice(pk.serialize_to_vec().as_slice())
+                .map_err(ParsePublicKeyError::InvalidEncoding)
+        } else {
+            Err(ParsePublicKeyError::MismatchedScheme)
+        }
+    }
+}
+
+impl BorshDeserialize for PublicKey {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let bytes: [u8; PUBLIC_KEY_LENGTH] =
+            BorshDeserialize::deserialize_reader(reader)?;
+        // The decoding checks that the point is in the prime order subgroup
+        let pk: G1Affine = Option::from(G1Affine::from_compressed(&bytes))
+            .ok_or_else(|| invalid_input("Invalid BLS public key encoding"))?;
+        if bool::from(pk.is_identity()) {
+            return Err(invalid_input("BLS public key cannot be the identity"));
+        }
+        Ok(PublicKey(pk))
+    }
+}
+
+impl BorshSerialize for PublicKey {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.0.to_compressed(), writer)
+    }
+}
+
+impl BorshSchema for PublicKey {
+    fn add_definitions_recursively(
+        definitions: &mut BTreeMap<
+            borsh::schema::Declaration,
+            borsh::schema::Definition,
+        >,
+    ) {
+        // Encoded as `[u8; PUBLIC_KEY_LENGTH]`
+        let elements = "u8".into();
+        let length = PUBLIC_KEY_LENGTH as u32;
+        let definition = borsh::schema::Definition::Array { elements, length };
+        definitions.insert(Self::declaration(), definition);
+    }
+
+    fn declaration() -> borsh::schema::Declaration {
+        "bls::PublicKey".into()
+    }
+}
+
+#[allow(clippy::derived_hash_with_manual_eq)]
+impl Hash for PublicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_compressed().hash(state);
+    }
+}
+
+impl PartialOrd for PublicKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PublicKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.to_compressed().cmp(&other.0.to_compressed())
+    }
+}
+
+impl Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", HEXLOWER.encode(&self.0.to_compressed()))
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = ParsePublicKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vec = HEXLOWER
+            .decode(s.as_ref())
+            .map_err(ParsePublicKeyError::InvalidHex)?;
+        BorshDeserialize::try_from_slice(&vec)
+            .map_err(ParsePublicKeyError::InvalidEncoding)
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Serialize::serialize(&self.to_string(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let encoded: String = Deserialize::deserialize(deserializer)?;
+        Self::from_str(&encoded).map_err(D::Error::custom)
+    }
+}
+
+/// BLS secret key
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SecretKey([u8; SECRET_KEY_LENGTH]);
+
+impl SecretKey {
+    /// Wrap a non-zero scalar
+    fn from_scalar(scalar: Scalar) -> Self {
+        Self(scalar.to_bytes())
+    }
+
+    /// The scalar of this key. The bytes of a key are always the canonical
+    /// encoding of a non-zero scalar.
+    fn scalar(&self) -> Scalar {
+        Scalar::from_bytes(&self.0).unwrap()
+    }
+}
+
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SecretKey")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretKey {}
+
+impl super::SecretKey for SecretKey {
+    type PublicKey = PublicKey;
+
+    const TYPE: SchemeType = SigScheme::TYPE;
+
+    fn try_from_sk<PK: super::SecretKey>(
+        pk: &PK,
+    ) -> Result<Self, ParseSecretKeyError> {
+        if PK::TYPE == super::common::SecretKey::TYPE {
+            super::common::SecretKey::try_from_sk(pk).and_then(|x| match x {
+                super::common::SecretKey::Bls(epk) => Ok(epk),
+                _ => Err(ParseSecretKeyError::MismatchedScheme),
+            })
+        } else if PK::TYPE == Self::TYPE {
+            Self::try_from_slice(pk.serialize_to_vec().as_slice())
+                .map_err(ParseSecretKeyError::InvalidEncoding)
+        } else {
+            Err(ParseSecretKeyError::MismatchedScheme)
+        }
+    }
+}
+
+impl RefTo<PublicKey> for SecretKey {
+    fn ref_to(&self) -> PublicKey {
+        PublicKey(G1Affine::from(G1Projective::generator() * self.scalar()))
+    }
+}
+
+impl BorshDeserialize for SecretKey {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let bytes: Zeroizing<[u8; SECRET_KEY_LENGTH]> =
+            Zeroizing::new(BorshDeserialize::deserialize_reader(reader)?);
+        let scalar: Scalar = Option::from(Scalar::from_bytes(&bytes))
+            .ok_or_else(|| invalid_input("Invalid BLS secret key encoding"))?;
+        if bool::from(scalar.ct_eq(&Scalar::from(0u64))) {
+            return Err(invalid_input("BLS secret key cannot be zero"));
+        }
+        Ok(SecretKey::from_scalar(scalar))
+    }
+}
+
+impl BorshSerialize for SecretKey {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.0, writer)
+    }
+}
+
+impl BorshSchema for SecretKey {
+    fn add_definitions_recursively(
+        definitions: &mut BTreeMap<
+            borsh::schema::Declaration,
+            borsh::schema::Definition,
+        >,
+    ) {
+        // Encoded as `[u8; SECRET_KEY_LENGTH]`
+        let elements = "u8".into();
+        let length = SECRET_KEY_LENGTH as u32;
+        let definition = borsh::schema::Definition::Array { elements, length };
+        definitions.insert(Self::declaration(), definition);
+    }
+
+    fn declaration() -> borsh::schema::Declaration {
+        "bls::SecretKey".into()
+    }
+}
+
+impl Display for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", HEXLOWER.encode(&self.0))
+    }
+}
+
+impl FromStr for SecretKey {
+    type Err = ParseSecretKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vec = Zeroizing::new(
+            HEXLOWER
+                .decode(s.as_ref())
+                .map_err(ParseSecretKeyError::InvalidHex)?,
+        );
+        BorshDeserialize::try_from_slice(&vec)
+            .map_err(ParseSecretKeyError::InvalidEncoding)
+    }
+}
+
+impl Serialize for SecretKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let encoded = Zeroizing::new(self.to_string());
+        Serialize::serialize(&*encoded, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let encoded: Zeroizing<String> =
+            Zeroizing::new(Deserialize::deserialize(deserializer)?);
+        Self::from_str(&encoded).map_err(D::Error::custom)
+    }
+}
+
+/// BLS signature, possibly aggregated from the signatures of many signers
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signature(pub G2Affine);
+
+impl super::Signature for Signature {
+    const TYPE: SchemeType = SigScheme::TYPE;
+
+    fn try_from_sig<PK: super::Signature>(
+        pk: &PK,
+    ) -> Result<Self, ParseSignatureError> {
+        if PK::TYPE == super::common::Signature::TYPE {
+            super::common::Signature::try_from_sig(pk).and_then(|x| match x {
+                super::common::Signature::Bls(epk) => Ok(epk),
+                _ => Err(ParseSignatureError::MismatchedScheme),
+            })
+        } else if PK::TYPE == Self::TYPE {
+            Self::try_from_slice(pk.serialize_to_vec().as_slice())
+                .map_err(ParseSignatureError::InvalidEncoding)
+        } else {
+            Err(ParseSignatureError::MismatchedScheme)
+        }
+    }
+}
+
+impl BorshDeserialize for Signature {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let bytes: [u8; SIGNATURE_LENGTH] =
+            BorshDeserialize::deserialize_reader(reader)?;
+        // The decoding checks that the point is in the prime order subgroup
+        let sig = Option::from(G2Affine::from_compressed(&bytes))
+            .ok_or_else(|| invalid_input("Invalid BLS signature encoding"))?;
+        Ok(Signature(sig))
+    }
+}
+
+impl BorshSerialize for Signature {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.0.to_compressed(), writer)
+    }
+}
+
+impl BorshSchema for Signature {
+    fn add_definitions_recursively(
+        definitions: &mut BTreeMap<
+            borsh::schema::Declaration,
+            borsh::schema::Definition,
+        >,
+    ) {
+        // Encoded as `[u8; SIGNATURE_LENGTH]`
+        let elements = "u8".into();
+        let length = SIGNATURE_LENGTH as u32;
+        let definition = borsh::schema::Definition::Array { elements, length };
+        definitions.insert(Self::declaration(), definition);
+    }
+
+    fn declaration() -> borsh::schema::Declaration {
+        "bls::Signature".into()
+    }
+}
+
+#[allow(clippy::derived_hash_with_manual_eq)]
+impl Hash for Signature {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_compressed().hash(state);
+    }
+}
+
+impl PartialOrd for Signature {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Signature {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.to_compressed().cmp(&other.0.to_compressed())
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", HEXLOWER.encode(&self.0.to_compressed()))
+    }
+}
+
+impl FromStr for Signature {
+    type Err = ParseSignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vec = HEXLOWER
+            .decode(s.as_ref())
+            .map_err(ParseSignatureError::InvalidHex)?;
+        BorshDeserialize::try_from_slice(&vec)
+            .map_err(ParseSignatureError::InvalidEncoding)
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Serialize::serialize(&self.to_string(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let encoded: String = Deserialize::deserialize(deserializer)?;
+        Self::from_str(&encoded).map_err(D::Error::custom)
+    }
+}
+
+/// A proof that the owner of a public key knows its secret key, which must
+/// be checked before the key is aggregated with others
+#[derive(
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct ProofOfPossession(pub Signature);
+
+/// Prove the possession of the given secret key
+pub fn prove_possession(keypair: &SecretKey) -> ProofOfPossession {
+    let pk = keypair.ref_to();
+    let msg = hash_to_g2(&pk.0.to_compressed(), POP_DST);
+    ProofOfPossession(Signature(G2Affine::from(msg * keypair.scalar())))
+}
+
+/// Check a proof of possession of the secret key of the given public key
+pub fn verify_possession(
+    pk: &PublicKey,
+    proof: &ProofOfPossession,
+) -> Result<(), VerifySigError> {
+    let msg = hash_to_g2(&pk.0.to_compressed(), POP_DST);
+    if pairing_check(&pk.0, &msg, &(proof.0).0) {
+        Ok(())
+    } else {
+        Err(VerifySigError::SigVerifyError(
+            "Invalid BLS proof of possession".to_string(),
+        ))
+    }
+}
+
+/// Aggregate the signatures of many signers into one. Returns `None` if
+/// there are no signatures.
+pub fn aggregate_signatures<'a>(
+    sigs: impl IntoIterator<Item = &'a Signature>,
+) -> Option<Signature> {
+    let mut sigs = sigs.into_iter();
+    let first = G2Projective::from(sigs.next()?.0);
+    let sum = sigs.fold(first, |sum, sig| sum + G2Projective::from(sig.0));
+    Some(Signature(G2Affine::from(sum)))
+}
+
+/// Aggregate the public keys of many signers into one. Returns `None` if
+/// there are no keys or if they cancel each other out.
+///
+/// The possession of each of the keys must have been proven beforehand,
+/// otherwise one of the signers could forge the aggregate signature.
+pub fn aggregate_public_keys<'a>(
+    pks: impl IntoIterator<Item = &'a PublicKey>,
+) -> Option<PublicKey> {
+    let mut pks = pks.into_iter();
+    let first = G1Projective::from(pks.next()?.0);
+    let sum = pks.fold(first, |sum, pk| sum + G1Projective::from(pk.0));
+    let sum = G1Affine::from(sum);
+    if bool::from(sum.is_identity()) {
+        None
+    } else {
+        Some(PublicKey(sum))
+    }
+}
+
+/// Check that the aggregate signature was made over the same data by the
+/// owners of all the given public keys, using a SHA256 hasher.
+///
+/// The possession of each of the keys must have been proven beforehand,
+/// otherwise one of the signers could forge the aggregate signature.
+pub fn verify_aggregate_signature(
+    pks: &[PublicKey],
+    data: &impl SignableBytes,
+    sig: &Signature,
+) -> Result<(), VerifySigError> {
+    let pk = aggregate_public_keys(pks).ok_or_else(|| {
+        VerifySigError::SigVerifyError(
+            "No BLS public keys to aggregate".to_string(),
+        )
+    })?;
+    SigScheme::verify_signature(&pk, data, sig)
+}
+
+/// An implementation of the BLS signature scheme over BLS12-381
+#[derive(
+    Debug,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Default,
+)]
+pub struct SigScheme;
+
+impl super::SigScheme for SigScheme {
+    type PublicKey = PublicKey;
+    type SecretKey = SecretKey;
+    type Signature = Signature;
+
+    const TYPE: SchemeType = SchemeType::Bls;
+
+    #[cfg(feature = "rand")]
+    fn generate<R>(csprng: &mut R) -> SecretKey
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut seed = Zeroizing::new([0u8; 32]);
+        csprng.fill_bytes(&mut *seed);
+        Self::from_bytes(*seed)
+    }
+
+    fn from_bytes(bytes: [u8; 32]) -> SecretKey {
+        // Reduce a wide hash of the seed, so that any seed maps to a uniformly
+        // distributed scalar
+        let mut wide = Zeroizing::new([0u8; 64]);
+        wide.copy_from_slice(
+            &Sha512::new().chain(KEYGEN_DST).chain(bytes).finalize(),
+        );
+        let scalar = Scalar::from_bytes_wide(&wide);
+        assert!(
+            !bool::from(scalar.ct_eq(&Scalar::from(0u64))),
+            "BLS secret key cannot be zero"
+        );
+        SecretKey::from_scalar(scalar)
+    }
+
+    fn sign_with_hasher<H>(
+        keypair: &SecretKey,
+        data: impl SignableBytes,
+    ) -> Self::Signature
+    where
+        H: 'static + StorageHasher,
+    {
+        let msg = hash_to_g2(&data.signable_hash::<H>(), SIGNATURE_DST);
+        Signature(G2Affine::from(msg * keypair.scalar()))
+    }
+
+    fn verify_signature_with_hasher<H>(
+        pk: &Self::PublicKey,
+        data: &impl SignableBytes,
+        sig: &Self::Signature,
+    ) -> Result<(), VerifySigError>
+    where
+        H: 'static + StorageHasher,
+    {
+        let msg = hash_to_g2(&data.signable_hash::<H>(), SIGNATURE_DST);
+        if pairing_check(&pk.0, &msg, &sig.0) {
+            Ok(())
+        } else {
+            Err(VerifySigError::SigVerifyError(
+                "Invalid BLS signature".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::ThreadRng;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::types::key::{
+        common, PublicKey as _, SecretKey as _, Signature as _,
+    };
+
+    fn keypairs(n: usize) -> Vec<SecretKey> {
+        let mut rng: ThreadRng = thread_rng();
+        (0..n).map(|_| SigScheme::generate(&mut rng)).collect()
+    }
+
+    /// Check that the signatures of many signers over the same data aggregate
+    /// into one that verifies against their aggregate public key, and only
+    /// against it
+    #[test]
+    fn aggregate_many_signers() {
+        let keypairs = keypairs(64);
+        let pks: Vec<PublicKey> = keypairs.iter().map(RefTo::ref_to).collect();
+        for (pk, sk) in pks.iter().zip(&keypairs) {
+            verify_possession(pk, &prove_possession(sk)).unwrap();
+        }
+        let data = b"validator set update".to_vec();
+        let sigs: Vec<Signature> = keypairs
+            .iter()
+            .map(|sk| SigScheme::sign(sk, &data))
+            .collect();
+        let aggregate = aggregate_signatures(&sigs).unwrap();
+
+        verify_aggregate_signature(&pks, &data, &aggregate).unwrap();
+        // Missing a signer
+        assert!(
+            verify_aggregate_signature(&pks[1..], &data, &aggregate).is_err()
+        );
+        // Other data
+        let other = b"other update".to_vec();
+        assert!(verify_aggregate_signature(&pks, &other, &aggregate).is_err());
+        // Missing a signature
+        let partial = aggregate_signatures(&sigs[1..]).unwrap();
+        assert!(verify_aggregate_signature(&pks, &data, &partial).is_err());
+
+        assert!(aggregate_signatures(Vec::<&Signature>::new()).is_none());
+        assert!(aggregate_public_keys(Vec::<&PublicKey>::new()).is_none());
+    }
+
+    /// Check that a rogue key, which lets its owner alone forge an aggregate
+    /// signature with an honest key, cannot come with a valid proof of
+    /// possession
+    #[test]
+    fn rogue_key_defeated_by_possession() {
+        let [honest, attacker]: [SecretKey; 2] =
+            keypairs(2).try_into().unwrap();
+        let honest_pk = honest.ref_to();
+        // The rogue key is chosen so that it aggregates with the honest key
+        // into the attacker's key
+        let rogue_pk = PublicKey(G1Affine::from(
+            G1Projective::from(attacker.ref_to().0)
+                - G1Projective::from(honest_pk.0),
+        ));
+        let data = b"forged".to_vec();
+        let forged = SigScheme::sign(&attacker, &data);
+        let pks = [honest_pk, rogue_pk.clone()];
+        verify_aggregate_signature(&pks, &data, &forged).unwrap();
+
+        // Without the rogue key's secret, the attacker cannot prove its
+        // possession
+        assert!(
+            verify_possession(&rogue_pk, &prove_possession(&attacker)).is_err()
+        );
+        // Nor can a proof be reused for another key
+        assert!(
+            verify_possession(&rogue_pk, &prove_possession(&honest)).is_err()
+        );
+        // And a plain signature over the key is not a proof
+        let sig = SigScheme::sign(&attacker, rogue_pk.serialize_to_vec());
+        assert!(verify_possession(&rogue_pk, &ProofOfPossession(sig)).is_err());
+    }
+
+    /// Check that the keys and signatures round trip through their encodings,
+    /// also when wrapped in `common` types
+    #[test]
+    fn serialization_round_trip() {
+        let sk = keypairs(1).pop().unwrap();
+        let pk = sk.ref_to();
+        let sig = SigScheme::sign(&sk, b"data".to_vec());
+        let pop = prove_possession(&sk);
+
+        assert_eq!(
+            SecretKey::try_from_slice(&sk.serialize_to_vec()).unwrap(),
+            sk
+        );
+        assert_eq!(SecretKey::from_str(&sk.to_string()).unwrap(), sk);
+        assert_eq!(
+            PublicKey::try_from_slice(&pk.serialize_to_vec()).unwrap(),
+            pk
+        );
+        assert_eq!(PublicKey::from_str(&pk.to_string()).unwrap(), pk);
+        assert_eq!(
+            Signature::try_from_slice(&sig.serialize_to_vec()).unwrap(),
+            sig
+        );
+        assert_eq!(Signature::from_str(&sig.to_string()).unwrap(), sig);
+        assert_eq!(
+            ProofOfPossession::try_from_slice(&pop.serialize_to_vec()).unwrap(),
+            pop
+        );
+        let json = serde_json::to_string(&pk).unwrap();
+        assert_eq!(serde_json::from_str::<PublicKey>(&json).unwrap(), pk);
+
+        let common_sk: common::SecretKey = sk.try_to_sk().unwrap();
+        let common_pk: common::PublicKey = pk.try_to_pk().unwrap();
+        let common_sig: common::Signature = sig.try_to_sig().unwrap();
+        assert_eq!(common_sk.ref_to(), common_pk);
+        assert_eq!(
+            common::PublicKey::from_str(&common_pk.to_string()).unwrap(),
+            common_pk
+        );
+        assert_eq!(
+            common::Signature::try_from_slice(&common_sig.serialize_to_vec())
+                .unwrap(),
+            common_sig
+        );
+        let json = serde_json::to_string(&common_sk).unwrap();
+        assert_eq!(
+            serde_json::from_str::<common::SecretKey>(&json).unwrap(),
+            common_sk
+        );
+        common::SigScheme::verify_signature(
+            &common_pk,
+            &b"data".to_vec(),
+            &common_sig,
+        )
+        .unwrap();
+    }
+
+    /// Check that BLS keys cannot be used by user accounts
+    #[test]
+    fn not_user_keys() {
+        use crate::ledger::storage::testing::TestWlStorage;
+        use crate::ledger::storage_api::{account, key};
+        use crate::types::address::testing::established_address_1;
+
+        let pk: common::PublicKey =
+            keypairs(1)[0].ref_to().try_to_pk().unwrap();
+        assert!(!pk.is_user_key());
+
+        let mut storage = TestWlStorage::default();
+        assert!(key::reveal_pk(&mut storage, &pk).is_err());
+        let owner = established_address_1();
+        assert!(
+            account::init_account_storage(&mut storage, &owner, &[pk], 1)
+                .is_err()
+        );
+    }
+
+    /// Check that the decoding rejects non-canonical and invalid encodings
+    #[test]
+    fn reject_invalid_encodings() {
+        // The identity is not a valid public key
+        let identity = G1Affine::identity().to_compressed();
+        assert!(PublicKey::try_from_slice(&identity).is_err());
+        // Points with the compression flag unset are not canonical
+        let pk = keypairs(1).pop().unwrap().ref_to();
+        let mut bytes = pk.serialize_to_vec();
+        bytes[0] &= 0x7f;
+        assert!(PublicKey::try_from_slice(&bytes).is_err());
+        // Secret keys must be canonical and non-zero
+        assert!(SecretKey::try_from_slice(&[0; SECRET_KEY_LENGTH]).is_err());
+        assert!(SecretKey::try_from_slice(&[0xff; SECRET_KEY_LENGTH]).is_err());
+        // Signatures must be canonical points of G2
+        let sig = SigScheme::sign(&keypairs(1)[0], b"data".to_vec());
+        let mut bytes = sig.serialize_to_vec();
+        bytes[0] &= 0x7f;
+        assert!(Signature::try_from_slice(&bytes).is_err());
+    }
+}
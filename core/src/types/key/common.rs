@@ -10,14 +10,19 @@ use data_encoding::HEXLOWER;
 #[cfg(feature = "rand")]
 use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
 use thiserror::Error;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
 
+#[cfg(feature = "bls")]
+use super::bls;
 use super::{
-    ed25519, secp256k1, ParsePublicKeyError, ParseSecretKeyError,
-    ParseSignatureError, RefTo, SchemeType, SigScheme as SigSchemeTrait,
-    VerifySigError,
+    ed25519, secp256k1, BatchVerifyError, ParsePublicKeyError,
+    ParseSecretKeyError, ParseSignatureError, RefTo, SchemeType,
+    SigScheme as SigSchemeTrait, VerifySigError,
 };
 use crate::impl_display_and_from_str_via_format;
+use crate::ledger::storage::Sha256Hasher;
 use crate::types::ethereum_events::EthAddress;
 use crate::types::key::{SignableBytes, StorageHasher};
 use crate::types::string_encoding;
@@ -35,15 +40,22 @@ use crate::types::string_encoding;
     BorshDeserialize,
     BorshSchema,
 )]
+#[non_exhaustive]
 pub enum PublicKey {
     /// Encapsulate Ed25519 public keys
     Ed25519(ed25519::PublicKey),
     /// Encapsulate Secp256k1 public keys
     Secp256k1(secp256k1::PublicKey),
+    /// Encapsulate BLS public keys, which are only used for validator
+    /// protocol signatures
+    #[cfg(feature = "bls")]
+    Bls(bls::PublicKey),
 }
 
 const ED25519_PK_PREFIX: &str = "ED25519_PK_PREFIX";
 const SECP256K1_PK_PREFIX: &str = "SECP256K1_PK_PREFIX";
+#[cfg(feature = "bls")]
+const BLS_PK_PREFIX: &str = "BLS_PK_PREFIX";
 
 impl Serialize for PublicKey {
     fn serialize<S>(
@@ -57,6 +69,8 @@ impl Serialize for PublicKey {
         let prefix = match self {
             PublicKey::Ed25519(_) => ED25519_PK_PREFIX,
             PublicKey::Secp256k1(_) => SECP256K1_PK_PREFIX,
+            #[cfg(feature = "bls")]
+            PublicKey::Bls(_) => BLS_PK_PREFIX,
         };
         let keypair_string = format!("{}{}", prefix, self);
         Serialize::serialize(&keypair_string, serializer)
@@ -80,6 +94,10 @@ impl<'de> Deserialize<'de> for PublicKey {
         {
             PublicKey::from_str(raw).map_err(D::Error::custom)
         } else {
+            #[cfg(feature = "bls")]
+            if let Some(raw) = keypair_string.strip_prefix(BLS_PK_PREFIX) {
+                return PublicKey::from_str(raw).map_err(D::Error::custom);
+            }
             Err(D::Error::custom(
                 "Could not deserialize SecretKey do to invalid prefix",
             ))
@@ -111,11 +129,32 @@ impl super::PublicKey for PublicKey {
                 .map_err(ParsePublicKeyError::InvalidEncoding)?,
             ))
         } else {
+            #[cfg(feature = "bls")]
+            if PK::TYPE == bls::PublicKey::TYPE {
+                return Ok(Self::Bls(
+                    bls::PublicKey::try_from_slice(
+                        pk.serialize_to_vec().as_slice(),
+                    )
+                    .map_err(ParsePublicKeyError::InvalidEncoding)?,
+                ));
+            }
             Err(ParsePublicKeyError::MismatchedScheme)
         }
     }
 }
 
+impl PublicKey {
+    /// Check if this key may sign user transactions and be used by user
+    /// accounts. BLS keys are reserved for validator protocol signatures.
+    pub fn is_user_key(&self) -> bool {
+        #[cfg(feature = "bls")]
+        if let PublicKey::Bls(_) = self {
+            return false;
+        }
+        true
+    }
+}
+
 /// String decoding error
 pub type DecodeError = string_encoding::DecodeError;
 
@@ -148,6 +187,10 @@ impl From<PublicKey> for crate::tendermint::PublicKey {
             PublicKey::Secp256k1(secp256k1::PublicKey(pk)) => {
                 TmPK::from_raw_secp256k1(&pk.to_sec1_bytes()).unwrap()
             }
+            #[cfg(feature = "bls")]
+            PublicKey::Bls(_) => {
+                panic!("BLS keys cannot be used as Tendermint keys")
+            }
         }
     }
 }
@@ -157,6 +200,9 @@ impl From<PublicKey> for crate::tendermint::PublicKey {
 pub enum EthAddressConvError {
     #[error("Eth key cannot be ed25519, only secp256k1")]
     CannotBeEd25519,
+    #[cfg(feature = "bls")]
+    #[error("Eth key cannot be BLS, only secp256k1")]
+    CannotBeBls,
 }
 
 impl TryFrom<&PublicKey> for EthAddress {
@@ -166,6 +212,8 @@ impl TryFrom<&PublicKey> for EthAddress {
         match value {
             PublicKey::Ed25519(_) => Err(EthAddressConvError::CannotBeEd25519),
             PublicKey::Secp256k1(pk) => Ok(EthAddress::from(pk)),
+            #[cfg(feature = "bls")]
+            PublicKey::Bls(_) => Err(EthAddressConvError::CannotBeBls),
         }
     }
 }
@@ -173,13 +221,45 @@ impl TryFrom<&PublicKey> for EthAddress {
 /// Secret key
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize, BorshSchema)]
 #[allow(clippy::large_enum_variant)]
+#[non_exhaustive]
 pub enum SecretKey {
     /// Encapsulate Ed25519 secret keys
     Ed25519(ed25519::SecretKey),
     /// Encapsulate Secp256k1 secret keys
     Secp256k1(secp256k1::SecretKey),
+    /// Encapsulate BLS secret keys, which are only used for validator
+    /// protocol signatures
+    #[cfg(feature = "bls")]
+    Bls(bls::SecretKey),
+}
+
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        match (self, other) {
+            (SecretKey::Ed25519(sk), SecretKey::Ed25519(other)) => {
+                sk.ct_eq(other)
+            }
+            (SecretKey::Secp256k1(sk), SecretKey::Secp256k1(other)) => {
+                sk.ct_eq(other)
+            }
+            #[cfg(feature = "bls")]
+            (SecretKey::Bls(sk), SecretKey::Bls(other)) => sk.ct_eq(other),
+            _ => Choice::from(0),
+        }
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
 }
 
+impl Eq for SecretKey {}
+
+// All the kinds of wrapped secret keys zeroize themselves on drop
+impl ZeroizeOnDrop for SecretKey {}
+
 impl Serialize for SecretKey {
     fn serialize<S>(
         &self,
@@ -192,9 +272,11 @@ impl Serialize for SecretKey {
         let prefix = match self {
             SecretKey::Ed25519(_) => "ED25519_SK_PREFIX",
             SecretKey::Secp256k1(_) => "SECP256K1_SK_PREFIX",
+            #[cfg(feature = "bls")]
+            SecretKey::Bls(_) => "BLS_SK_PREFIX",
         };
-        let keypair_string = format!("{}{}", prefix, self);
-        Serialize::serialize(&keypair_string, serializer)
+        let keypair_string = Zeroizing::new(format!("{}{}", prefix, self));
+        Serialize::serialize(&*keypair_string, serializer)
     }
 }
 
@@ -205,9 +287,10 @@ impl<'de> Deserialize<'de> for SecretKey {
     {
         use serde::de::Error;
 
-        let keypair_string: String =
+        let keypair_string: Zeroizing<String> = Zeroizing::new(
             serde::Deserialize::deserialize(deserializer)
-                .map_err(D::Error::custom)?;
+                .map_err(D::Error::custom)?,
+        );
         if let Some(raw) = keypair_string.strip_prefix("ED25519_SK_PREFIX") {
             SecretKey::from_str(raw).map_err(D::Error::custom)
         } else if let Some(raw) =
@@ -215,6 +298,10 @@ impl<'de> Deserialize<'de> for SecretKey {
         {
             SecretKey::from_str(raw).map_err(D::Error::custom)
         } else {
+            #[cfg(feature = "bls")]
+            if let Some(raw) = keypair_string.strip_prefix("BLS_SK_PREFIX") {
+                return SecretKey::from_str(raw).map_err(D::Error::custom);
+            }
             Err(D::Error::custom(
                 "Could not deserialize SecretKey do to invalid prefix",
             ))
@@ -255,6 +342,15 @@ impl super::SecretKey for SecretKey {
                 .map_err(ParseSecretKeyError::InvalidEncoding)?,
             ))
         } else {
+            #[cfg(feature = "bls")]
+            if SK::TYPE == bls::SecretKey::TYPE {
+                return Ok(Self::Bls(
+                    bls::SecretKey::try_from_slice(
+                        sk.serialize_to_vec().as_ref(),
+                    )
+                    .map_err(ParseSecretKeyError::InvalidEncoding)?,
+                ));
+            }
             Err(ParseSecretKeyError::MismatchedScheme)
         }
     }
@@ -265,6 +361,8 @@ impl RefTo<PublicKey> for SecretKey {
         match self {
             SecretKey::Ed25519(sk) => PublicKey::Ed25519(sk.ref_to()),
             SecretKey::Secp256k1(sk) => PublicKey::Secp256k1(sk.ref_to()),
+            #[cfg(feature = "bls")]
+            SecretKey::Bls(sk) => PublicKey::Bls(sk.ref_to()),
         }
     }
 }
@@ -300,13 +398,50 @@ impl FromStr for SecretKey {
     Deserialize,
     BorshSerialize,
     BorshDeserialize,
-    BorshSchema,
 )]
+#[non_exhaustive]
 pub enum Signature {
     /// Encapsulate Ed25519 signatures
     Ed25519(ed25519::Signature),
     /// Encapsulate Secp256k1 signatures
     Secp256k1(secp256k1::Signature),
+    /// Encapsulate BLS signatures, which are only made by validators for
+    /// protocol purposes
+    #[cfg(feature = "bls")]
+    Bls(bls::Signature),
+}
+
+impl BorshSchema for Signature {
+    fn add_definitions_recursively(
+        definitions: &mut std::collections::BTreeMap<
+            borsh::schema::Declaration,
+            borsh::schema::Definition,
+        >,
+    ) {
+        // Declared with its module to not clash with the signature section of
+        // a tx, which is also called `Signature`
+        #[allow(unused_mut)]
+        let mut variants = vec![
+            ("Ed25519".into(), ed25519::Signature::declaration()),
+            ("Secp256k1".into(), secp256k1::Signature::declaration()),
+        ];
+        #[cfg(feature = "bls")]
+        variants.push(("Bls".into(), bls::Signature::declaration()));
+        let definition = borsh::schema::Definition::Enum { variants };
+        borsh::schema::add_definition(
+            Self::declaration(),
+            definition,
+            definitions,
+        );
+        ed25519::Signature::add_definitions_recursively(definitions);
+        secp256k1::Signature::add_definitions_recursively(definitions);
+        #[cfg(feature = "bls")]
+        bls::Signature::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> borsh::schema::Declaration {
+        "common::Signature".into()
+    }
 }
 
 impl string_encoding::Format for Signature {
@@ -340,6 +475,13 @@ impl From<secp256k1::Signature> for Signature {
     }
 }
 
+#[cfg(feature = "bls")]
+impl From<bls::Signature> for Signature {
+    fn from(sig: bls::Signature) -> Self {
+        Signature::Bls(sig)
+    }
+}
+
 impl super::Signature for Signature {
     const TYPE: SchemeType = SigScheme::TYPE;
 
@@ -364,6 +506,15 @@ impl super::Signature for Signature {
                 .map_err(ParseSignatureError::InvalidEncoding)?,
             ))
         } else {
+            #[cfg(feature = "bls")]
+            if SIG::TYPE == bls::Signature::TYPE {
+                return Ok(Self::Bls(
+                    bls::Signature::try_from_slice(
+                        sig.serialize_to_vec().as_slice(),
+                    )
+                    .map_err(ParseSignatureError::InvalidEncoding)?,
+                ));
+            }
             Err(ParseSignatureError::MismatchedScheme)
         }
     }
@@ -426,6 +577,10 @@ impl super::SigScheme for SigScheme {
             SecretKey::Secp256k1(kp) => Signature::Secp256k1(
                 secp256k1::SigScheme::sign_with_hasher::<H>(kp, data),
             ),
+            #[cfg(feature = "bls")]
+            SecretKey::Bls(kp) => {
+                Signature::Bls(bls::SigScheme::sign_with_hasher::<H>(kp, data))
+            }
         }
     }
 
@@ -448,9 +603,48 @@ impl super::SigScheme for SigScheme {
                     pk, data, sig,
                 )
             }
+            #[cfg(feature = "bls")]
+            (PublicKey::Bls(pk), Signature::Bls(sig)) => {
+                bls::SigScheme::verify_signature_with_hasher::<H>(pk, data, sig)
+            }
             _ => Err(VerifySigError::MismatchedScheme),
         }
     }
+
+    fn verify_batch<D: SignableBytes>(
+        items: &[(PublicKey, D, Signature)],
+    ) -> Result<(), BatchVerifyError> {
+        // Verify the ed25519 signatures in a single batch and the others one
+        // at a time
+        let mut ed25519_items = vec![];
+        let mut ed25519_positions = vec![];
+        let mut failures = vec![];
+        for (index, (pk, data, sig)) in items.iter().enumerate() {
+            match (pk, sig) {
+                (PublicKey::Ed25519(pk), Signature::Ed25519(sig)) => {
+                    let hash = crate::types::hash::Hash(
+                        data.signable_hash::<Sha256Hasher>(),
+                    );
+                    ed25519_items.push((pk.clone(), hash, sig.clone()));
+                    ed25519_positions.push(index);
+                }
+                _ => {
+                    if let Err(err) = Self::verify_signature(pk, data, sig) {
+                        failures.push((index, err));
+                    }
+                }
+            }
+        }
+        if let Err(err) = ed25519::SigScheme::verify_batch(&ed25519_items) {
+            failures.extend(
+                err.failures
+                    .into_iter()
+                    .map(|(index, err)| (ed25519_positions[index], err)),
+            );
+            failures.sort_by_key(|(index, _)| *index);
+        }
+        BatchVerifyError::check(failures)
+    }
 }
 
 #[cfg(test)]
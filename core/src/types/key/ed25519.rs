@@ -13,12 +13,15 @@ use data_encoding::HEXLOWER;
 #[cfg(feature = "rand")]
 use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use super::{
-    ParsePublicKeyError, ParseSecretKeyError, ParseSignatureError, RefTo,
-    SchemeType, SigScheme as SigSchemeTrait, SignableBytes, VerifySigError,
+    BatchVerifyError, ParsePublicKeyError, ParseSecretKeyError,
+    ParseSignatureError, RefTo, SchemeType, SigScheme as SigSchemeTrait,
+    SignableBytes, VerifySigError,
 };
+use crate::ledger::storage::Sha256Hasher;
 use crate::types::key::StorageHasher;
 
 const PUBLIC_KEY_LENGTH: usize = 32;
@@ -128,9 +131,31 @@ impl FromStr for PublicKey {
 }
 
 /// Ed25519 secret key
-#[derive(Debug, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
 pub struct SecretKey(pub Box<ed25519_consensus::SigningKey>);
 
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SecretKey")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.as_bytes().ct_eq(other.0.as_bytes())
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretKey {}
+
 impl super::SecretKey for SecretKey {
     type PublicKey = PublicKey;
 
@@ -169,23 +194,20 @@ impl Clone for SecretKey {
 
 impl BorshDeserialize for SecretKey {
     fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let bytes: Zeroizing<[u8; SECRET_KEY_LENGTH]> =
+            Zeroizing::new(BorshDeserialize::deserialize_reader(reader)?);
         Ok(SecretKey(Box::new(
-            ed25519_consensus::SigningKey::try_from(
-                <[u8; SECRET_KEY_LENGTH] as BorshDeserialize>::deserialize_reader(
-                    reader,
-                )?
-                    .as_ref(),
-            )
-                .map_err(|e| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
-                })?,
+            ed25519_consensus::SigningKey::try_from(bytes.as_ref()).map_err(
+                |e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e),
+            )?,
         )))
     }
 }
 
 impl BorshSerialize for SecretKey {
     fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
-        BorshSerialize::serialize(&self.0.to_bytes(), writer)
+        let bytes = Zeroizing::new(self.0.to_bytes());
+        BorshSerialize::serialize(&*bytes, writer)
     }
 }
 
@@ -365,4 +387,23 @@ impl super::SigScheme for SigScheme {
         pk.0.verify(&sig.0, &data.signable_hash::<H>())
             .map_err(|err| VerifySigError::SigVerifyError(err.to_string()))
     }
+
+    fn verify_batch<D: SignableBytes>(
+        items: &[(PublicKey, D, Signature)],
+    ) -> Result<(), BatchVerifyError> {
+        #[cfg(feature = "rand")]
+        {
+            let mut verifier = ed25519_consensus::batch::Verifier::new();
+            for (pk, data, sig) in items {
+                let msg = data.signable_hash::<Sha256Hasher>();
+                verifier.queue((pk.0.into(), sig.0, &msg));
+            }
+            if verifier.verify(rand::thread_rng()).is_ok() {
+                return Ok(());
+            }
+        }
+        // A failed batch doesn't tell which signatures are invalid, so look
+        // for them one at a time
+        super::verify_each::<Self, D>(items)
+    }
 }
@@ -1,7 +1,11 @@
 //! Cryptographic keys
 
+#[cfg(feature = "bls")]
+pub mod bls;
 pub mod common;
 pub mod ed25519;
+pub mod musig;
+pub mod pop;
 pub mod secp256k1;
 
 use std::fmt::{Debug, Display};
@@ -19,6 +23,7 @@ use serde::Serialize;
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+use super::account::VpUpdate;
 use super::address::Address;
 use super::storage::{self, DbKeySeg, Key};
 use crate::ledger::storage::{Sha256Hasher, StorageHasher};
@@ -31,6 +36,7 @@ struct Keys {
     public_keys: &'static str,
     threshold: &'static str,
     protocol_public_keys: &'static str,
+    vp_history: &'static str,
 }
 
 /// Obtain a storage key for user's public key.
@@ -98,6 +104,24 @@ pub fn protocol_pk_key(owner: &Address) -> storage::Key {
     }
 }
 
+/// Obtain a storage key prefix for the history of an account's VP updates.
+pub fn vp_history_key_prefix(owner: &Address) -> storage::Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(owner.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.vp_history.to_string()),
+        ],
+    }
+}
+
+/// LazyMap handler for the history of an account's VP updates, indexed by
+/// the height of the block in which they were applied
+pub fn vp_history_handle(
+    owner: &Address,
+) -> LazyMap<storage::BlockHeight, VpUpdate> {
+    LazyMap::open(vp_history_key_prefix(owner))
+}
+
 /// Check if the given storage key is a public key. If it is, returns the owner.
 pub fn is_protocol_pk_key(key: &Key) -> Option<&Address> {
     match &key.segments[..] {
@@ -118,14 +142,57 @@ pub enum VerifySigError {
     SigVerifyError(String),
     #[error("Signature verification failed to encode the data: {0}")]
     DataEncodingError(std::io::Error),
-    #[error("Transaction doesn't have any data with a signature.")]
-    MissingData,
+    #[error("No signature section signs over the target {target}.")]
+    NoSignatureForTarget {
+        /// The hash of the section that isn't signed over
+        target: crate::types::hash::Hash,
+    },
+    #[error("No signature over the targets was made by the key {expected}.")]
+    SignerMismatch {
+        /// The public key that was expected to have signed
+        expected: common::PublicKey,
+    },
     #[error("Signature belongs to a different scheme from the public key.")]
     MismatchedScheme,
+    #[error("Invalid signature by {signer} over the target {target}.")]
+    InvalidSignature {
+        /// The hash the signature was made over
+        target: crate::types::hash::Hash,
+        /// The public key the signature was checked against
+        signer: common::PublicKey,
+    },
+    #[error("Signer at index {1} of account {0} has no known public key.")]
+    UnknownSigner(Address, u8),
     #[error("Signature verification went out of gas: {0}")]
     OutOfGas(#[from] crate::ledger::gas::Error),
 }
 
+/// Failure to verify a batch of signatures
+#[derive(Error, Debug)]
+#[error("{} signature(s) of the batch failed verification", .failures.len())]
+pub struct BatchVerifyError {
+    /// The position in the batch of each signature that failed
+    /// verification, along with the reason
+    pub failures: Vec<(usize, VerifySigError)>,
+}
+
+impl BatchVerifyError {
+    /// The positions in the batch of the signatures that failed
+    /// verification, in increasing order
+    pub fn offenders(&self) -> impl Iterator<Item = usize> + '_ {
+        self.failures.iter().map(|(index, _)| *index)
+    }
+
+    /// Turn the failures found in a batch into a result
+    fn check(failures: Vec<(usize, VerifySigError)>) -> Result<(), Self> {
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { failures })
+        }
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum ParsePublicKeyError {
@@ -178,6 +245,7 @@ pub trait TryFromRef<T: ?Sized>: Sized {
 
 /// Type capturing signature scheme IDs
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[non_exhaustive]
 pub enum SchemeType {
     /// Type identifier for Ed25519 scheme
     Ed25519,
@@ -185,6 +253,9 @@ pub enum SchemeType {
     Secp256k1,
     /// Type identifier for Common
     Common,
+    /// Type identifier for BLS scheme
+    #[cfg(feature = "bls")]
+    Bls,
 }
 
 impl FromStr for SchemeType {
@@ -195,6 +266,8 @@ impl FromStr for SchemeType {
             "ed25519" => Ok(Self::Ed25519),
             "secp256k1" => Ok(Self::Secp256k1),
             "common" => Ok(Self::Common),
+            #[cfg(feature = "bls")]
+            "bls" => Ok(Self::Bls),
             _ => Err(()),
         }
     }
@@ -356,6 +429,35 @@ pub trait SigScheme: Eq + Ord + Debug + Serialize + Default {
     ) -> Result<(), VerifySigError> {
         Self::verify_signature_with_hasher::<Sha256Hasher>(pk, data, sig)
     }
+
+    /// Check a batch of signatures, each over the data it is paired with,
+    /// using a SHA256 hasher. On failure, all the signatures that do not
+    /// verify are reported.
+    ///
+    /// By default, the signatures are checked one at a time.
+    fn verify_batch<D: SignableBytes>(
+        items: &[(Self::PublicKey, D, Self::Signature)],
+    ) -> Result<(), BatchVerifyError> {
+        verify_each::<Self, D>(items)
+    }
+}
+
+/// Check a batch of signatures one at a time, reporting all the signatures
+/// that do not verify
+fn verify_each<S: SigScheme, D: SignableBytes>(
+    items: &[(S::PublicKey, D, S::Signature)],
+) -> Result<(), BatchVerifyError> {
+    BatchVerifyError::check(
+        items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (pk, data, sig))| {
+                S::verify_signature(pk, data, sig)
+                    .err()
+                    .map(|err| (index, err))
+            })
+            .collect(),
+    )
 }
 
 /// Public key hash derived from `common::Key` borsh encoded bytes (hex string
@@ -467,6 +569,8 @@ pub fn tm_consensus_key_raw_hash(pk: &common::PublicKey) -> String {
     let pkh = match pk {
         common::PublicKey::Ed25519(pk) => PublicKeyHash::from(pk),
         common::PublicKey::Secp256k1(pk) => PublicKeyHash::from(pk),
+        #[cfg(feature = "bls")]
+        common::PublicKey::Bls(pk) => PublicKeyHash::from(pk),
     };
     pkh.to_string()
 }
@@ -655,6 +759,30 @@ macro_rules! sigscheme_test {
                         .is_ok()
                 );
             }
+
+            /// Verify a batch of signatures in which exactly one is invalid
+            /// and check that it is identified.
+            #[test]
+            fn batch_verify_finds_offender() {
+                use rand::prelude::ThreadRng;
+                use rand::thread_rng;
+
+                let mut rng: ThreadRng = thread_rng();
+                let mut items: Vec<_> = (0..64u8)
+                    .map(|i| {
+                        let sk = <$type>::generate(&mut rng);
+                        let msg = vec![i; 32];
+                        let sig = <$type>::sign(&sk, &msg);
+                        (sk.ref_to(), msg, sig)
+                    })
+                    .collect();
+                assert!(<$type>::verify_batch(&items).is_ok());
+
+                let sk = <$type>::generate(&mut rng);
+                items[17].2 = <$type>::sign(&sk, &items[17].1);
+                let err = <$type>::verify_batch(&items).unwrap_err();
+                assert_eq!(err.offenders().collect::<Vec<_>>(), vec![17]);
+            }
         }
     };
 }
@@ -668,6 +796,39 @@ sigscheme_test! {secp256k1_test, secp256k1::SigScheme}
 mod more_tests {
     use super::*;
 
+    /// Check that a batch mixing signature schemes reports the positions of
+    /// the invalid signatures in the original batch.
+    #[test]
+    fn batch_verify_mixed_schemes() {
+        let keys = [
+            testing::keypair_1(),
+            testing::keypair_3(),
+            testing::keypair_2(),
+            testing::keypair_4(),
+        ];
+        let mut items: Vec<_> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| {
+                let msg = vec![i as u8; 32];
+                let sig = common::SigScheme::sign(sk, &msg);
+                (sk.ref_to(), msg, sig)
+            })
+            .collect();
+        assert!(common::SigScheme::verify_batch(&items).is_ok());
+
+        // An ed25519 signature over the wrong data
+        items[2].2 = common::SigScheme::sign(&keys[2], b"forged");
+        // A secp256k1 key paired with an ed25519 signature
+        items[3].2 = items[0].2.clone();
+        let err = common::SigScheme::verify_batch(&items).unwrap_err();
+        assert_eq!(err.offenders().collect::<Vec<_>>(), vec![2, 3]);
+        assert!(matches!(
+            err.failures[1].1,
+            VerifySigError::MismatchedScheme
+        ));
+    }
+
     #[test]
     fn zeroize_keypair_ed25519() {
         use rand::thread_rng;
@@ -684,6 +845,76 @@ mod more_tests {
         });
     }
 
+    #[test]
+    fn zeroize_keypair_common() {
+        use rand::thread_rng;
+
+        let sk = common::SecretKey::Ed25519(ed25519::SigScheme::generate(
+            &mut thread_rng(),
+        ));
+        let common::SecretKey::Ed25519(ed25519_sk) = &sk else {
+            unreachable!()
+        };
+        let sk_bytes = ed25519_sk.0.as_bytes();
+        let len = sk_bytes.len();
+        let ptr = sk_bytes.as_ptr();
+
+        drop(sk);
+
+        assert_eq!(&[0u8; 32], unsafe {
+            core::slice::from_raw_parts(ptr, len)
+        });
+    }
+
+    /// Check that the debug representation of secret keys doesn't leak the
+    /// key material
+    #[test]
+    fn secret_key_debug_is_redacted() {
+        let keys = [testing::keypair_1(), testing::keypair_3()];
+        for sk in keys {
+            let debug = format!("{:?}", sk);
+            assert!(debug.contains("<redacted>"), "{debug}");
+            let hex = match &sk {
+                common::SecretKey::Ed25519(sk) => sk.to_string(),
+                common::SecretKey::Secp256k1(sk) => sk.to_string(),
+                #[cfg(feature = "bls")]
+                common::SecretKey::Bls(sk) => sk.to_string(),
+            };
+            assert!(!debug.contains(&hex), "{debug}");
+        }
+    }
+
+    /// Check the equality of secret keys, which is computed in constant time
+    #[test]
+    fn secret_key_eq() {
+        use subtle::ConstantTimeEq;
+
+        assert_eq!(testing::keypair_1(), testing::keypair_1());
+        assert_eq!(testing::keypair_3(), testing::keypair_3());
+        assert_ne!(testing::keypair_1(), testing::keypair_2());
+        assert_ne!(testing::keypair_3(), testing::keypair_4());
+        assert_ne!(testing::keypair_1(), testing::keypair_3());
+        assert!(!bool::from(
+            testing::keypair_1().ct_eq(&testing::keypair_2())
+        ));
+    }
+
+    /// Secret keys must not be implicitly copied around in memory, as the
+    /// copies would not get zeroized. This fails to compile if a secret key
+    /// type implements `Copy`.
+    #[test]
+    fn secret_keys_are_not_copy() {
+        trait AmbiguousIfCopy<A> {
+            fn check() {}
+        }
+        impl<T: ?Sized> AmbiguousIfCopy<()> for T {}
+        impl<T: ?Sized + Copy> AmbiguousIfCopy<u8> for T {}
+
+        <ed25519::SecretKey as AmbiguousIfCopy<_>>::check();
+        <secp256k1::SecretKey as AmbiguousIfCopy<_>>::check();
+        <common::SecretKey as AmbiguousIfCopy<_>>::check();
+    }
+
     #[test]
     fn zeroize_keypair_secp256k1() {
         use rand::thread_rng;
@@ -0,0 +1,500 @@
+//! MuSig2 multi-signatures over secp256k1
+//!
+//! An ordered set of participants jointly produces a single Schnorr signature
+//! that verifies against the aggregate of their public keys. Signing takes
+//! two rounds: the participants first exchange public nonces, then partial
+//! signatures over the aggregate nonce, which anyone can combine into the
+//! final signature with [`SessionContext::aggregate`].
+//!
+//! The aggregate key weighs each key by a coefficient committing to the whole
+//! ordered set of participants, which defeats rogue key attacks without
+//! proofs of possession, and binds a signature to the set it was made by.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+#[cfg(feature = "rand")]
+use k256::NonZeroScalar;
+use k256::{FieldBytes, ProjectivePoint, Scalar, U256};
+#[cfg(feature = "rand")]
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use super::{secp256k1, RefTo, VerifySigError};
+use crate::types::hash::Hash;
+
+/// Domain separation tag of the hash of the participant set
+const KEY_LIST_TAG: &[u8] = b"NAMADA_MUSIG2_KEY_LIST";
+/// Domain separation tag of the key aggregation coefficients
+const KEY_COEFFICIENT_TAG: &[u8] = b"NAMADA_MUSIG2_KEY_COEFFICIENT";
+/// Domain separation tag of the nonce aggregation coefficient
+const NONCE_COEFFICIENT_TAG: &[u8] = b"NAMADA_MUSIG2_NONCE_COEFFICIENT";
+/// Domain separation tag of the signature challenge
+const CHALLENGE_TAG: &[u8] = b"NAMADA_MUSIG2_CHALLENGE";
+
+/// Errors of MuSig2 signing sessions
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MusigError {
+    #[error("A MuSig2 signature needs at least one participant")]
+    NoParticipants,
+    #[error("The MuSig2 participant {0} is listed more than once")]
+    DuplicateParticipant(secp256k1::PublicKey),
+    #[error("The key {0} is not a participant of the MuSig2 session")]
+    UnknownParticipant(secp256k1::PublicKey),
+    #[error("Expected {expected} MuSig2 public nonces, got {got}")]
+    MissingNonces { expected: usize, got: usize },
+    #[error("The secret nonce doesn't match the public nonce of {0}")]
+    MismatchedNonce(secp256k1::PublicKey),
+    #[error("The MuSig2 nonces aggregate to the point at infinity")]
+    InfiniteNonce,
+    #[error("Invalid MuSig2 partial signature of participant {0}")]
+    InvalidPartialSignature(usize),
+}
+
+/// Hash the given parts into a scalar, under the given domain separation tag
+fn hash_to_scalar(tag: &[u8], parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update(tag);
+    for part in parts {
+        hasher.update(part);
+    }
+    let digest = FieldBytes::clone_from_slice(&hasher.finalize());
+    <Scalar as Reduce<U256>>::reduce_bytes(&digest)
+}
+
+/// The compressed encoding of a point, or a single zero byte for the point at
+/// infinity
+fn encode_point(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+}
+
+fn to_public_key(
+    point: &ProjectivePoint,
+) -> Result<secp256k1::PublicKey, MusigError> {
+    k256::PublicKey::from_affine(point.to_affine())
+        .map(secp256k1::PublicKey)
+        .map_err(|_| MusigError::InfiniteNonce)
+}
+
+fn decode_scalar(bytes: &[u8; 32]) -> Option<Scalar> {
+    Option::from(Scalar::from_repr(FieldBytes::clone_from_slice(bytes)))
+}
+
+/// The aggregate of the public keys of an ordered set of participants
+#[derive(Clone, Debug)]
+pub struct KeyAggregation {
+    participants: Vec<secp256k1::PublicKey>,
+    coefficients: Vec<Scalar>,
+    aggregate: ProjectivePoint,
+}
+
+impl KeyAggregation {
+    /// Aggregate the public keys of the given participants, in order
+    pub fn new(
+        participants: &[secp256k1::PublicKey],
+    ) -> Result<Self, MusigError> {
+        if participants.is_empty() {
+            return Err(MusigError::NoParticipants);
+        }
+        for (index, pk) in participants.iter().enumerate() {
+            if participants[..index].contains(pk) {
+                return Err(MusigError::DuplicateParticipant(pk.clone()));
+            }
+        }
+        let encoded: Vec<_> =
+            participants.iter().map(|pk| pk.0.to_sec1_bytes()).collect();
+        let list_hash = {
+            let mut hasher = Sha256::new();
+            hasher.update(KEY_LIST_TAG);
+            for pk in &encoded {
+                hasher.update(pk);
+            }
+            hasher.finalize()
+        };
+        let coefficients: Vec<_> = encoded
+            .iter()
+            .map(|pk| {
+                hash_to_scalar(KEY_COEFFICIENT_TAG, &[&list_hash, &pk[..]])
+            })
+            .collect();
+        let aggregate = participants
+            .iter()
+            .zip(&coefficients)
+            .fold(ProjectivePoint::IDENTITY, |acc, (pk, coefficient)| {
+                acc + pk.0.to_projective() * coefficient
+            });
+        Ok(Self {
+            participants: participants.to_vec(),
+            coefficients,
+            aggregate,
+        })
+    }
+
+    /// The ordered participants whose keys are aggregated
+    pub fn participants(&self) -> &[secp256k1::PublicKey] {
+        &self.participants
+    }
+
+    /// The position of the given key in the set of participants
+    pub fn position(&self, pk: &secp256k1::PublicKey) -> Option<usize> {
+        self.participants
+            .iter()
+            .position(|participant| participant == pk)
+    }
+}
+
+/// The secret nonces of a participant for a single signing session. They are
+/// consumed by signing, as reusing them would leak the secret key.
+pub struct SecretNonce([Scalar; 2]);
+
+impl Drop for SecretNonce {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl SecretNonce {
+    /// Sample fresh secret nonces, returning them with their public
+    /// counterparts to share with the other participants
+    #[cfg(feature = "rand")]
+    pub fn generate<R>(csprng: &mut R) -> (Self, PublicNonce)
+    where
+        R: CryptoRng + RngCore,
+    {
+        let nonce = Self([
+            *NonZeroScalar::random(&mut *csprng),
+            *NonZeroScalar::random(&mut *csprng),
+        ]);
+        let public = nonce.public();
+        (nonce, public)
+    }
+
+    /// The public nonces of these secret nonces
+    pub fn public(&self) -> PublicNonce {
+        let [first, second] = self
+            .0
+            .map(|nonce| to_public_key(&(ProjectivePoint::GENERATOR * nonce)));
+        PublicNonce(
+            first.expect("Non-zero nonces have a finite point"),
+            second.expect("Non-zero nonces have a finite point"),
+        )
+    }
+}
+
+/// The public nonces of a participant, shared in the first signing round
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct PublicNonce(pub secp256k1::PublicKey, pub secp256k1::PublicKey);
+
+/// The partial signature of a participant, shared in the second signing round
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct PartialSignature(pub [u8; 32]);
+
+/// An aggregated Schnorr signature
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Signature {
+    /// The aggregate nonce point
+    pub nonce: secp256k1::PublicKey,
+    /// The sum of the partial signatures
+    pub s: [u8; 32],
+}
+
+/// The challenge of a signature by `aggregate` over `msg` with the given
+/// nonce point
+fn challenge(
+    aggregate: &ProjectivePoint,
+    nonce: &ProjectivePoint,
+    msg: &Hash,
+) -> Scalar {
+    hash_to_scalar(
+        CHALLENGE_TAG,
+        &[&encode_point(aggregate), &encode_point(nonce), &msg.0],
+    )
+}
+
+/// The state of a signing session once all the public nonces are known,
+/// from which partial signatures are made, checked and aggregated
+#[derive(Clone, Debug)]
+pub struct SessionContext {
+    keys: KeyAggregation,
+    public_nonces: Vec<PublicNonce>,
+    nonce_coefficient: Scalar,
+    nonce: ProjectivePoint,
+    challenge: Scalar,
+}
+
+impl SessionContext {
+    /// Start the second round of a session over `msg`, given the public
+    /// nonces of all the participants, in order
+    pub fn new(
+        keys: KeyAggregation,
+        public_nonces: Vec<PublicNonce>,
+        msg: &Hash,
+    ) -> Result<Self, MusigError> {
+        if public_nonces.len() != keys.participants.len() {
+            return Err(MusigError::MissingNonces {
+                expected: keys.participants.len(),
+                got: public_nonces.len(),
+            });
+        }
+        let (first, second) = public_nonces.iter().fold(
+            (ProjectivePoint::IDENTITY, ProjectivePoint::IDENTITY),
+            |(first, second), nonce| {
+                (
+                    first + nonce.0 .0.to_projective(),
+                    second + nonce.1 .0.to_projective(),
+                )
+            },
+        );
+        let nonce_coefficient = hash_to_scalar(
+            NONCE_COEFFICIENT_TAG,
+            &[
+                &encode_point(&keys.aggregate),
+                &encode_point(&first),
+                &encode_point(&second),
+                &msg.0,
+            ],
+        );
+        let nonce = first + second * nonce_coefficient;
+        if nonce == ProjectivePoint::IDENTITY {
+            return Err(MusigError::InfiniteNonce);
+        }
+        let challenge = challenge(&keys.aggregate, &nonce, msg);
+        Ok(Self {
+            keys,
+            public_nonces,
+            nonce_coefficient,
+            nonce,
+            challenge,
+        })
+    }
+
+    /// Make the partial signature of the participant owning `secret_key`,
+    /// consuming its secret nonce
+    pub fn partial_sign(
+        &self,
+        secret_key: &secp256k1::SecretKey,
+        secret_nonce: SecretNonce,
+    ) -> Result<PartialSignature, MusigError> {
+        let pk = secret_key.ref_to();
+        let index = self
+            .keys
+            .position(&pk)
+            .ok_or_else(|| MusigError::UnknownParticipant(pk.clone()))?;
+        if secret_nonce.public() != self.public_nonces[index] {
+            return Err(MusigError::MismatchedNonce(pk));
+        }
+        let [first, second] = secret_nonce.0;
+        let secret = *secret_key.0.to_nonzero_scalar();
+        let s = first
+            + second * self.nonce_coefficient
+            + self.challenge * self.keys.coefficients[index] * secret;
+        Ok(PartialSignature(s.to_bytes().into()))
+    }
+
+    /// Check the partial signature of the participant at the given position
+    pub fn verify_partial(
+        &self,
+        index: usize,
+        partial: &PartialSignature,
+    ) -> Result<(), MusigError> {
+        let invalid = MusigError::InvalidPartialSignature(index);
+        let (Some(pk), Some(nonce)) = (
+            self.keys.participants.get(index),
+            self.public_nonces.get(index),
+        ) else {
+            return Err(invalid);
+        };
+        let s = decode_scalar(&partial.0).ok_or_else(|| invalid.clone())?;
+        let expected = nonce.0 .0.to_projective()
+            + nonce.1 .0.to_projective() * self.nonce_coefficient
+            + pk.0.to_projective()
+                * (self.challenge * self.keys.coefficients[index]);
+        if ProjectivePoint::GENERATOR * s == expected {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    /// Check the partial signatures of all the participants, in order, and
+    /// combine them into the aggregated signature
+    pub fn aggregate(
+        &self,
+        partials: &[PartialSignature],
+    ) -> Result<Signature, MusigError> {
+        if partials.len() != self.keys.participants.len() {
+            return Err(MusigError::InvalidPartialSignature(partials.len()));
+        }
+        let mut s = Scalar::ZERO;
+        for (index, partial) in partials.iter().enumerate() {
+            self.verify_partial(index, partial)?;
+            s += decode_scalar(&partial.0).expect("Checked above");
+        }
+        Ok(Signature {
+            nonce: to_public_key(&self.nonce)?,
+            s: s.to_bytes().into(),
+        })
+    }
+}
+
+/// Verify an aggregated signature over `msg` by the given ordered set of
+/// participants
+pub fn verify(
+    participants: &[secp256k1::PublicKey],
+    msg: &Hash,
+    sig: &Signature,
+) -> Result<(), VerifySigError> {
+    let keys = KeyAggregation::new(participants)
+        .map_err(|err| VerifySigError::SigVerifyError(err.to_string()))?;
+    let s = decode_scalar(&sig.s).ok_or_else(|| {
+        VerifySigError::SigVerifyError(
+            "Non-canonical MuSig2 signature scalar".to_string(),
+        )
+    })?;
+    let nonce = sig.nonce.0.to_projective();
+    let challenge = challenge(&keys.aggregate, &nonce, msg);
+    if ProjectivePoint::GENERATOR * s == nonce + keys.aggregate * challenge {
+        Ok(())
+    } else {
+        Err(VerifySigError::SigVerifyError(
+            "Invalid MuSig2 signature".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::types::key::testing::gen_keypair;
+
+    /// Run a full signing session over `msg` with the given participants
+    fn sign_all(
+        secret_keys: &[secp256k1::SecretKey],
+        msg: &Hash,
+    ) -> (Vec<secp256k1::PublicKey>, Signature) {
+        let participants: Vec<_> =
+            secret_keys.iter().map(|sk| sk.ref_to()).collect();
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) = secret_keys
+            .iter()
+            .map(|_| SecretNonce::generate(&mut thread_rng()))
+            .unzip();
+        let keys = KeyAggregation::new(&participants).unwrap();
+        let context = SessionContext::new(keys, public_nonces, msg).unwrap();
+        let partials: Vec<_> = secret_keys
+            .iter()
+            .zip(secret_nonces)
+            .map(|(sk, nonce)| context.partial_sign(sk, nonce).unwrap())
+            .collect();
+        (participants, context.aggregate(&partials).unwrap())
+    }
+
+    /// Check that three participants produce a signature verifying against
+    /// their ordered set only.
+    #[test]
+    fn three_party_aggregation() {
+        let secret_keys: Vec<_> = (0..3)
+            .map(|_| gen_keypair::<secp256k1::SigScheme>())
+            .collect();
+        let msg = Hash::sha256(b"three party message");
+        let (participants, sig) = sign_all(&secret_keys, &msg);
+        assert!(verify(&participants, &msg, &sig).is_ok());
+
+        assert!(verify(&participants, &Hash::sha256(b"other"), &sig).is_err());
+        let mut reordered = participants.clone();
+        reordered.swap(0, 1);
+        assert!(verify(&reordered, &msg, &sig).is_err());
+        assert!(verify(&participants[..2], &msg, &sig).is_err());
+    }
+
+    /// Check that a participant substituting their key is caught, both in
+    /// their partial signature and in the final signature.
+    #[test]
+    fn substituted_participant_key() {
+        let secret_keys: Vec<_> = (0..3)
+            .map(|_| gen_keypair::<secp256k1::SigScheme>())
+            .collect();
+        let msg = Hash::sha256(b"substituted key");
+        let (mut participants, sig) = sign_all(&secret_keys, &msg);
+        participants[2] = gen_keypair::<secp256k1::SigScheme>().ref_to();
+        assert!(verify(&participants, &msg, &sig).is_err());
+
+        // A partial signature by another key than the announced one
+        let participants: Vec<_> =
+            secret_keys.iter().map(|sk| sk.ref_to()).collect();
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| SecretNonce::generate(&mut thread_rng()))
+            .unzip();
+        let keys = KeyAggregation::new(&participants).unwrap();
+        let context = SessionContext::new(keys, public_nonces, &msg).unwrap();
+        let mut secret_nonces = secret_nonces.into_iter();
+        let partial = context
+            .partial_sign(&secret_keys[0], secret_nonces.next().unwrap())
+            .unwrap();
+        assert!(context.verify_partial(0, &partial).is_ok());
+        assert_eq!(
+            context.verify_partial(1, &partial),
+            Err(MusigError::InvalidPartialSignature(1))
+        );
+        let outsider = gen_keypair::<secp256k1::SigScheme>();
+        assert_eq!(
+            context
+                .partial_sign(&outsider, secret_nonces.next().unwrap())
+                .unwrap_err(),
+            MusigError::UnknownParticipant(outsider.ref_to())
+        );
+    }
+
+    /// Check that participant sets with duplicate keys are rejected.
+    #[test]
+    fn duplicate_participants() {
+        let pk = gen_keypair::<secp256k1::SigScheme>().ref_to();
+        assert_eq!(
+            KeyAggregation::new(&[pk.clone(), pk.clone()]).unwrap_err(),
+            MusigError::DuplicateParticipant(pk)
+        );
+        assert_eq!(
+            KeyAggregation::new(&[]).unwrap_err(),
+            MusigError::NoParticipants
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Proofs of possession of the secret keys of registered public keys
+//!
+//! Registering a public key that one doesn't control would let them claim
+//! another validator's key, or craft a rogue key that cancels out honest
+//! keys in an aggregate. Keys registered on chain must thus come with a
+//! [`ProofOfPossession`], a signature by the key over a domain-tagged message
+//! committing to both the key and the address registering it, so that a proof
+//! cannot be replayed for another address.
+//!
+//! BLS keys use the standard proof of possession of their scheme instead,
+//! which is what makes the aggregation of BLS keys safe.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "bls")]
+use super::bls;
+use super::{common, RefTo, SigScheme, VerifySigError};
+use crate::types::address::Address;
+
+/// Domain separation tag of the messages signed by proofs of possession
+const POSSESSION_TAG: &[u8] = b"NAMADA_KEY_POSSESSION_V1";
+
+/// A proof that the owner of a public key knows its secret key, made for a
+/// given address
+#[derive(
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct ProofOfPossession(pub common::Signature);
+
+/// The message signed over to prove the possession of the secret key of the
+/// given public key for the given address
+fn possession_message(pk: &common::PublicKey, address: &Address) -> Vec<u8> {
+    let mut msg = POSSESSION_TAG.to_vec();
+    msg.extend(pk.serialize_to_vec());
+    msg.extend(address.serialize_to_vec());
+    msg
+}
+
+/// Prove the possession of the given secret key for the given address
+pub fn prove_possession(
+    keypair: &common::SecretKey,
+    address: &Address,
+) -> ProofOfPossession {
+    match keypair {
+        #[cfg(feature = "bls")]
+        common::SecretKey::Bls(sk) => ProofOfPossession(
+            common::Signature::Bls(bls::prove_possession(sk).0),
+        ),
+        _ => ProofOfPossession(common::SigScheme::sign(
+            keypair,
+            possession_message(&keypair.ref_to(), address),
+        )),
+    }
+}
+
+/// Check a proof of possession of the secret key of the given public key,
+/// made for the given address
+pub fn verify_possession(
+    pk: &common::PublicKey,
+    address: &Address,
+    proof: &ProofOfPossession,
+) -> Result<(), VerifySigError> {
+    match (pk, &proof.0) {
+        #[cfg(feature = "bls")]
+        (common::PublicKey::Bls(pk), common::Signature::Bls(sig)) => {
+            bls::verify_possession(pk, &bls::ProofOfPossession(sig.clone()))
+        }
+        #[cfg(feature = "bls")]
+        (common::PublicKey::Bls(_), _) => Err(VerifySigError::MismatchedScheme),
+        _ => common::SigScheme::verify_signature(
+            pk,
+            &possession_message(pk, address),
+            &proof.0,
+        )
+        .map_err(|err| match err {
+            VerifySigError::MismatchedScheme => err,
+            _ => VerifySigError::SigVerifyError(format!(
+                "Invalid proof of possession of the key {pk} for the address \
+                 {address}"
+            )),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::key::secp256k1;
+    use crate::types::key::testing::{gen_keypair, keypair_1, keypair_2};
+
+    /// Check that proofs are accepted for the key and address they were made
+    /// for only.
+    #[test]
+    fn test_proof_of_possession() {
+        let secp_key =
+            common::SecretKey::Secp256k1(gen_keypair::<secp256k1::SigScheme>());
+        for keypair in [keypair_1(), secp_key] {
+            let address = established_address_1();
+            let proof = prove_possession(&keypair, &address);
+            assert!(
+                verify_possession(&keypair.ref_to(), &address, &proof).is_ok()
+            );
+            // A proof made for another address
+            assert!(
+                verify_possession(
+                    &keypair.ref_to(),
+                    &established_address_2(),
+                    &proof
+                )
+                .is_err()
+            );
+            // A proof of another key
+            let other = prove_possession(&keypair_2(), &address);
+            assert!(
+                verify_possession(&keypair.ref_to(), &address, &other).is_err()
+            );
+        }
+    }
+
+    /// Check that BLS keys use the standard proof of possession of their
+    /// scheme.
+    #[cfg(feature = "bls")]
+    #[test]
+    fn test_bls_proof_of_possession() {
+        let sk = gen_keypair::<bls::SigScheme>();
+        let keypair = common::SecretKey::Bls(sk.clone());
+        let proof = prove_possession(&keypair, &established_address_1());
+        assert_eq!(
+            proof.0,
+            common::Signature::Bls(bls::prove_possession(&sk).0)
+        );
+        assert!(
+            verify_possession(
+                &keypair.ref_to(),
+                &established_address_1(),
+                &proof
+            )
+            .is_ok()
+        );
+        // A plain signature over the message of other schemes is no proof
+        let sig = common::SigScheme::sign(
+            &keypair,
+            possession_message(&keypair.ref_to(), &established_address_1()),
+        );
+        assert!(
+            verify_possession(
+                &keypair.ref_to(),
+                &established_address_1(),
+                &ProofOfPossession(sig)
+            )
+            .is_err()
+        );
+    }
+}
@@ -19,6 +19,8 @@ use rand::{CryptoRng, RngCore};
 use serde::de::{Error, SeqAccess, Visitor};
 use serde::ser::SerializeTuple;
 use serde::{Deserialize, Serialize, Serializer};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 use super::{
     ParsePublicKeyError, ParseSecretKeyError, ParseSignatureError, RefTo,
@@ -162,9 +164,34 @@ impl From<&PublicKey> for EthAddress {
 }
 
 /// Secp256k1 secret key
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct SecretKey(pub Box<k256::SecretKey>);
 
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SecretKey")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretKey {}
+
+// The wrapped `k256` secret key zeroizes itself on drop
+impl ZeroizeOnDrop for SecretKey {}
+
 impl super::SecretKey for SecretKey {
     type PublicKey = PublicKey;
 
@@ -192,8 +219,9 @@ impl Serialize for SecretKey {
     where
         S: Serializer,
     {
-        let arr: [u8; SECRET_KEY_SIZE] = self.0.to_bytes().into();
-        serde::Serialize::serialize(&arr, serializer)
+        let arr: Zeroizing<[u8; SECRET_KEY_SIZE]> =
+            Zeroizing::new(self.0.to_bytes().into());
+        serde::Serialize::serialize(&*arr, serializer)
     }
 }
 
@@ -202,10 +230,10 @@ impl<'de> Deserialize<'de> for SecretKey {
     where
         D: serde::Deserializer<'de>,
     {
-        let arr_res: [u8; SECRET_KEY_SIZE] =
-            serde::Deserialize::deserialize(deserializer)?;
-        let key =
-            k256::SecretKey::from_slice(&arr_res).map_err(D::Error::custom);
+        let arr_res: Zeroizing<[u8; SECRET_KEY_SIZE]> =
+            Zeroizing::new(serde::Deserialize::deserialize(deserializer)?);
+        let key = k256::SecretKey::from_slice(arr_res.as_ref())
+            .map_err(D::Error::custom);
         Ok(SecretKey(Box::new(key.unwrap())))
     }
 }
@@ -213,9 +241,9 @@ impl<'de> Deserialize<'de> for SecretKey {
 impl BorshDeserialize for SecretKey {
     fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
         // deserialize the bytes first
-        let bytes: [u8; SECRET_KEY_SIZE] =
-            BorshDeserialize::deserialize_reader(reader)?;
-        let sk = k256::SecretKey::from_slice(&bytes).map_err(|e| {
+        let bytes: Zeroizing<[u8; SECRET_KEY_SIZE]> =
+            Zeroizing::new(BorshDeserialize::deserialize_reader(reader)?);
+        let sk = k256::SecretKey::from_slice(bytes.as_ref()).map_err(|e| {
             std::io::Error::new(
                 ErrorKind::InvalidInput,
                 format!("Error decoding secp256k1 secret key: {}", e),
@@ -227,8 +255,9 @@ impl BorshDeserialize for SecretKey {
 
 impl BorshSerialize for SecretKey {
     fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
-        let bytes: [u8; SECRET_KEY_SIZE] = self.0.to_bytes().into();
-        BorshSerialize::serialize(&bytes, writer)
+        let bytes: Zeroizing<[u8; SECRET_KEY_SIZE]> =
+            Zeroizing::new(self.0.to_bytes().into());
+        BorshSerialize::serialize(&*bytes, writer)
     }
 }
 
@@ -615,10 +615,16 @@ impl Key {
 
     /// Returns a key of the wasm code hash of the given code path
     pub fn wasm_hash(code_path: impl AsRef<str>) -> Self {
+        let mut segments = Self::wasm_hash_prefix().segments;
+        segments.push(DbKeySeg::StringSeg(code_path.as_ref().to_string()));
+        Key { segments }
+    }
+
+    /// Returns the prefix of the keys mapping wasm code names to their hashes
+    pub fn wasm_hash_prefix() -> Self {
         let mut segments =
             Self::from(WASM_KEY_PREFIX.to_owned().to_db_key()).segments;
         segments.push(DbKeySeg::StringSeg(WASM_HASH_PREFIX.to_owned()));
-        segments.push(DbKeySeg::StringSeg(code_path.as_ref().to_string()));
         Key { segments }
     }
 
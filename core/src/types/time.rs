@@ -11,6 +11,9 @@ use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use chrono::ParseError;
 pub use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::proto::Error as ProtoError;
 
 /// Check if the given `duration` has passed since the given `start.
 pub fn duration_passed(
@@ -140,16 +143,76 @@ pub struct DateTimeUtc(pub DateTime<Utc>);
 /// The minimum possible DateTime<Utc>.
 pub const MIN_UTC: DateTimeUtc = DateTimeUtc(chrono::DateTime::<Utc>::MIN_UTC);
 
+/// The seconds since the Unix epoch of the earliest time representable by a
+/// protobuf timestamp, 0001-01-01T00:00:00Z
+pub const MIN_TIMESTAMP_SECONDS: i64 = -62_135_596_800;
+
+/// The seconds since the Unix epoch of the latest second representable by a
+/// protobuf timestamp, 9999-12-31T23:59:59Z
+pub const MAX_TIMESTAMP_SECONDS: i64 = 253_402_300_799;
+
+/// The number of nanoseconds in a second, the exclusive bound of the nanos of
+/// a protobuf timestamp
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+/// The reasons a time can't be represented by a protobuf timestamp
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampError {
+    #[error(
+        "The seconds {0} are out of the range of the years 1 to 9999 of \
+         timestamps"
+    )]
+    SecondsOutOfRange(i64),
+    #[error("The nanos {0} are out of the range 0 to 999,999,999")]
+    NanosOutOfRange(i32),
+}
+
 impl Display for DateTimeUtc {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.to_rfc3339())
     }
 }
 
+/// The source of the current time on `wasm32-unknown-unknown`, which has no
+/// system clock
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+static CLOCK: std::sync::OnceLock<fn() -> DateTimeUtc> =
+    std::sync::OnceLock::new();
+
+/// Set the source of the current time returned by [`DateTimeUtc::now`] on
+/// `wasm32-unknown-unknown`, which has no system clock, e.g. a binding to
+/// `Date.now()` in browsers. The source can only be set once, returns `false`
+/// if it has already been set.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub fn set_clock(clock: fn() -> DateTimeUtc) -> bool {
+    CLOCK.set(clock).is_ok()
+}
+
 impl DateTimeUtc {
     /// Returns a DateTimeUtc which corresponds to the current date.
+    ///
+    /// On `wasm32-unknown-unknown`, the current date is read from the source
+    /// set with [`set_clock`], which must have been set beforehand.
     pub fn now() -> Self {
-        Self(Utc::now())
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            Self(Utc::now())
+        }
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        {
+            let clock = CLOCK.get().expect(
+                "The source of the current time must be set with `set_clock` \
+                 on wasm32-unknown-unknown",
+            );
+            clock()
+        }
+    }
+
+    /// Returns the DateTimeUtc of the given number of milliseconds since the
+    /// Unix epoch, as returned by `Date.now()` in browsers.
+    pub fn from_unix_millis(millis: i64) -> Option<Self> {
+        Utc.timestamp_millis_opt(millis).single().map(Self)
     }
 
     /// Returns an rfc3339 string or an error.
@@ -161,6 +224,25 @@ impl DateTimeUtc {
     pub fn next_second(&self) -> Self {
         *self + DurationSecs(0)
     }
+
+    /// Check that this time can be represented by a protobuf timestamp,
+    /// whose range is the years 1 to 9999
+    pub fn check_timestamp_range(&self) -> Result<(), TimestampError> {
+        self.timestamp_parts().map(|_| ())
+    }
+
+    /// The seconds and nanos of the protobuf timestamp of this time. The
+    /// nanos of a leap second, from 1e9, are carried over to the next second,
+    /// as timestamps don't represent leap seconds.
+    fn timestamp_parts(&self) -> Result<(i64, i32), TimestampError> {
+        let nanos = self.0.timestamp_subsec_nanos();
+        let seconds = self.0.timestamp() + i64::from(nanos / NANOS_PER_SECOND);
+        if (MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds) {
+            Ok((seconds, (nanos % NANOS_PER_SECOND) as i32))
+        } else {
+            Err(TimestampError::SecondsOutOfRange(seconds))
+        }
+    }
 }
 
 impl FromStr for DateTimeUtc {
@@ -245,29 +327,47 @@ impl From<DateTime<Utc>> for DateTimeUtc {
     }
 }
 
+/// Timestamps out of range are rejected rather than normalized
 impl TryFrom<prost_types::Timestamp> for DateTimeUtc {
-    type Error = prost_types::TimestampError;
+    type Error = ProtoError;
 
     fn try_from(
         timestamp: prost_types::Timestamp,
     ) -> Result<Self, Self::Error> {
-        let system_time: std::time::SystemTime = timestamp.try_into()?;
-        Ok(Self(system_time.into()))
+        let prost_types::Timestamp { seconds, nanos } = timestamp;
+        let nanos = u32::try_from(nanos)
+            .ok()
+            .filter(|nanos| *nanos < NANOS_PER_SECOND)
+            .ok_or(ProtoError::InvalidTimestamp(
+                TimestampError::NanosOutOfRange(nanos),
+            ))?;
+        if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds) {
+            return Err(ProtoError::InvalidTimestamp(
+                TimestampError::SecondsOutOfRange(seconds),
+            ));
+        }
+        Utc.timestamp_opt(seconds, nanos).single().map(Self).ok_or(
+            ProtoError::InvalidTimestamp(TimestampError::SecondsOutOfRange(
+                seconds,
+            )),
+        )
     }
 }
 
-impl From<DateTimeUtc> for prost_types::Timestamp {
-    fn from(dt: DateTimeUtc) -> Self {
-        let seconds = dt.0.timestamp();
-        let nanos = dt.0.timestamp_subsec_nanos() as i32;
-        prost_types::Timestamp { seconds, nanos }
+impl TryFrom<DateTimeUtc> for prost_types::Timestamp {
+    type Error = ProtoError;
+
+    fn try_from(dt: DateTimeUtc) -> Result<Self, Self::Error> {
+        let (seconds, nanos) =
+            dt.timestamp_parts().map_err(ProtoError::InvalidTimestamp)?;
+        Ok(prost_types::Timestamp { seconds, nanos })
     }
 }
 
 impl TryFrom<crate::tendermint_proto::google::protobuf::Timestamp>
     for DateTimeUtc
 {
-    type Error = prost_types::TimestampError;
+    type Error = ProtoError;
 
     fn try_from(
         timestamp: crate::tendermint_proto::google::protobuf::Timestamp,
@@ -327,3 +427,114 @@ impl From<DurationNanos> for crate::tendermint::Timeout {
         Self::from(std::time::Duration::from(val))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Convert the given seconds and nanos to a [`DateTimeUtc`]
+    fn from_parts(seconds: i64, nanos: i32) -> Result<DateTimeUtc, ProtoError> {
+        DateTimeUtc::try_from(prost_types::Timestamp { seconds, nanos })
+    }
+
+    #[test]
+    fn test_timestamp_boundaries() {
+        // The first and last nanosecond of the years 1 to 9999
+        let first = from_parts(MIN_TIMESTAMP_SECONDS, 0).unwrap();
+        assert_eq!(first.to_rfc3339(), "0001-01-01T00:00:00+00:00");
+        let last = from_parts(MAX_TIMESTAMP_SECONDS, 999_999_999).unwrap();
+        assert_eq!(last.to_rfc3339(), "9999-12-31T23:59:59.999999999+00:00");
+        for dt in [first, last] {
+            let timestamp = prost_types::Timestamp::try_from(dt).unwrap();
+            assert_eq!(DateTimeUtc::try_from(timestamp).unwrap(), dt);
+        }
+
+        // Just out of the range of the years
+        assert_matches!(
+            from_parts(MIN_TIMESTAMP_SECONDS - 1, 999_999_999),
+            Err(ProtoError::InvalidTimestamp(
+                TimestampError::SecondsOutOfRange(seconds)
+            )) if seconds == MIN_TIMESTAMP_SECONDS - 1
+        );
+        assert_matches!(
+            from_parts(MAX_TIMESTAMP_SECONDS + 1, 0),
+            Err(ProtoError::InvalidTimestamp(
+                TimestampError::SecondsOutOfRange(_)
+            ))
+        );
+        let beyond = DateTimeUtc(last.0 + Duration::nanoseconds(1));
+        assert_matches!(
+            beyond.check_timestamp_range(),
+            Err(TimestampError::SecondsOutOfRange(_))
+        );
+        assert!(prost_types::Timestamp::try_from(beyond).is_err());
+        assert!(prost_types::Timestamp::try_from(MIN_UTC).is_err());
+
+        // The nanos are not normalized into the seconds
+        for nanos in [1_000_000_000, -1, i32::MIN, i32::MAX] {
+            assert_matches!(
+                from_parts(0, nanos),
+                Err(ProtoError::InvalidTimestamp(
+                    TimestampError::NanosOutOfRange(n)
+                )) if n == nanos
+            );
+        }
+        // Negative seconds are valid before the Unix epoch
+        assert_eq!(
+            from_parts(-1, 500_000_000).unwrap().to_rfc3339(),
+            "1969-12-31T23:59:59.500+00:00"
+        );
+    }
+
+    #[test]
+    fn test_leap_second_timestamp() {
+        let leap = DateTimeUtc(
+            Utc.timestamp_opt(946_684_799, 1_500_000_000)
+                .single()
+                .unwrap(),
+        );
+        assert_eq!(
+            prost_types::Timestamp::try_from(leap).unwrap(),
+            prost_types::Timestamp {
+                seconds: 946_684_800,
+                nanos: 500_000_000,
+            }
+        );
+    }
+
+    proptest! {
+        /// Check that timestamps either convert back to themselves or are
+        /// rejected for being out of range, without panicking
+        #[test]
+        fn test_timestamp_round_trip(
+            seconds in prop_oneof![
+                any::<i64>(),
+                MIN_TIMESTAMP_SECONDS - 2..MIN_TIMESTAMP_SECONDS + 2,
+                MAX_TIMESTAMP_SECONDS - 2..MAX_TIMESTAMP_SECONDS + 2,
+            ],
+            nanos in prop_oneof![
+                any::<i32>(),
+                -2..2,
+                NANOS_PER_SECOND as i32 - 2..NANOS_PER_SECOND as i32 + 2,
+            ],
+        ) {
+            let in_range = (MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS)
+                .contains(&seconds)
+                && (0..NANOS_PER_SECOND as i32).contains(&nanos);
+            match from_parts(seconds, nanos) {
+                Ok(dt) => {
+                    prop_assert!(in_range);
+                    prop_assert_eq!(
+                        prost_types::Timestamp::try_from(dt).unwrap(),
+                        prost_types::Timestamp { seconds, nanos }
+                    );
+                }
+                Err(ProtoError::InvalidTimestamp(_)) => prop_assert!(!in_range),
+                Err(err) => panic!("Unexpected error {}", err),
+            }
+        }
+    }
+}
@@ -43,6 +43,9 @@ pub struct UpdateAccount {
     pub addr: Address,
     /// The new VP code hash
     pub vp_code_hash: Option<Hash>,
+    /// The hash of a VP code already stored on chain, to be installed instead
+    /// of the VP code section referenced by `vp_code_hash`
+    pub stored_vp_code_hash: Option<Hash>,
     /// Public keys to be written into the account's storage. This can be used
     /// for signature verification of transactions for the newly created
     /// account.
@@ -50,3 +53,41 @@ pub struct UpdateAccount {
     /// The account signature threshold
     pub threshold: Option<u8>,
 }
+
+/// A tx data type to update several accounts in a single tx. Either all the
+/// updates are applied or none of them.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateAccountBatch {
+    /// The updates of the accounts
+    pub updates: Vec<UpdateAccount>,
+}
+
+/// A tx data type to rotate the public keys of an established account without
+/// changing its validity predicate
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct ChangeKeys {
+    /// An address of the account
+    pub addr: Address,
+    /// The public keys that replace all the account's current keys
+    pub new_keys: Vec<common::PublicKey>,
+    /// The account signature threshold for the new keys
+    pub new_threshold: u8,
+}
@@ -22,15 +22,20 @@ use std::fmt;
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use borsh_ext::BorshSerializeExt;
 pub use decrypted::*;
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
 pub use wrapper::*;
 
 use crate::ledger::gas::{Gas, VpsGas};
 use crate::types::address::Address;
+use crate::types::event::TxEvent;
 use crate::types::hash::Hash;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage;
+use crate::types::token::Amount;
 use crate::types::transaction::protocol::ProtocolTx;
 
 /// Get the hash of a transaction
@@ -53,6 +58,10 @@ pub struct TxResult {
     pub initialized_accounts: Vec<Address>,
     /// IBC events emitted by the transaction
     pub ibc_events: BTreeSet<IbcEvent>,
+    /// The events emitted by the transaction, in order
+    pub tx_events: Vec<TxEvent>,
+    /// The fee charged to a wrapper transaction, in its fee token
+    pub wrapper_fee: Option<Amount>,
 }
 
 impl TxResult {
@@ -78,6 +87,73 @@ pub struct VpsResult {
     pub invalid_sig: bool,
 }
 
+/// Result of dry running a transaction
+// TODO derive BorshSchema after <https://github.com/near/borsh-rs/issues/82>
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct DryRunResult {
+    /// The result of applying the transaction
+    pub tx_result: TxResult,
+    /// Summary of the write log left behind by the transaction, ordered by
+    /// storage key
+    pub storage_changes: Vec<StorageChange>,
+}
+
+/// A change to the value of a storage key predicted by a dry run
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct StorageChange {
+    /// The modified storage key
+    pub key: storage::Key,
+    /// The value before the transaction, if the key was present
+    pub old: Option<StorageValue>,
+    /// The value after the transaction, or `None` if the key was deleted
+    pub new: Option<StorageValue>,
+}
+
+impl StorageChange {
+    /// Check if the transaction deleted the key
+    pub fn is_deletion(&self) -> bool {
+        self.new.is_none()
+    }
+}
+
+/// A storage value together with its hash
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct StorageValue {
+    /// The SHA-256 hash of the value bytes
+    pub hash: Hash,
+    /// The value bytes
+    pub bytes: Vec<u8>,
+}
+
+impl StorageValue {
+    /// Wrap the given value bytes, computing their hash
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            hash: Hash::sha256(&bytes),
+            bytes,
+        }
+    }
+}
+
+impl fmt::Display for StorageChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (None, None) => write!(f, "{}: unchanged", self.key),
+            (None, Some(new)) => write!(f, "{}: new {}", self.key, new.hash),
+            (Some(old), None) => {
+                write!(f, "{}: {} deleted", self.key, old.hash)
+            }
+            (Some(old), Some(new)) => {
+                write!(f, "{}: {} -> {}", self.key, old.hash, new.hash)
+            }
+        }
+    }
+}
+
 impl fmt::Display for TxResult {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -161,6 +237,113 @@ impl TxType {
     }
 }
 
+/// The error with which a transaction wasm aborted its execution. It's
+/// passed from the wasm to the host, so that the protocol can tell apart the
+/// reasons for a tx failure.
+#[derive(
+    Error,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub enum TxWasmError {
+    /// The tx data couldn't be decoded or is not valid
+    #[error("Invalid tx data")]
+    InvalidData,
+    /// A section required by the tx is missing
+    #[error("Missing tx section {0}")]
+    MissingSection(Hash),
+    /// The tx is not authorized to perform the requested action
+    #[error("Unauthorized tx")]
+    Unauthorized,
+    /// Any other error
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// The result codes that the ledger may send back to a client to indicate the
+/// status of their submitted tx
+#[derive(Debug, Copy, Clone, FromPrimitive, ToPrimitive, PartialEq, Eq)]
+pub enum ResultCode {
+    Ok = 0,
+    WasmRuntimeError = 1,
+    InvalidTx = 2,
+    InvalidSig = 3,
+    InvalidOrder = 4,
+    ExtraTxs = 5,
+    Undecryptable = 6,
+    AllocationError = 7,
+    ReplayTx = 8,
+    InvalidChainId = 9,
+    ExpiredTx = 10,
+    TxGasLimit = 11,
+    FeeError = 12,
+    InvalidVoteExtension = 13,
+    TooLarge = 14,
+    InvalidTxData = 15,
+    MissingTxSection = 16,
+    UnauthorizedTx = 17,
+}
+
+impl ResultCode {
+    /// Checks if the given [`ResultCode`] value is a protocol level error,
+    /// that can be recovered from at the finalize block stage.
+    pub const fn is_recoverable(&self) -> bool {
+        use ResultCode::*;
+        // NOTE: pattern match on all `ResultCode` variants, in order
+        // to catch potential bugs when adding new codes
+        match self {
+            Ok | WasmRuntimeError | InvalidTxData | MissingTxSection
+            | UnauthorizedTx => true,
+            InvalidTx | InvalidSig | InvalidOrder | ExtraTxs
+            | Undecryptable | AllocationError | ReplayTx | InvalidChainId
+            | ExpiredTx | TxGasLimit | FeeError | InvalidVoteExtension
+            | TooLarge => false,
+        }
+    }
+}
+
+impl From<&TxWasmError> for ResultCode {
+    fn from(error: &TxWasmError) -> Self {
+        match error {
+            TxWasmError::InvalidData => Self::InvalidTxData,
+            TxWasmError::MissingSection(_) => Self::MissingTxSection,
+            TxWasmError::Unauthorized => Self::UnauthorizedTx,
+            TxWasmError::Custom(_) => Self::WasmRuntimeError,
+        }
+    }
+}
+
+impl TryFrom<u32> for ResultCode {
+    type Error = u32;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        Self::from_u32(code).ok_or(code)
+    }
+}
+
+impl From<ResultCode> for u32 {
+    fn from(code: ResultCode) -> u32 {
+        code.to_u32().unwrap()
+    }
+}
+
+impl From<ResultCode> for String {
+    fn from(code: ResultCode) -> String {
+        u32::from(code).to_string()
+    }
+}
+
+impl From<ResultCode> for crate::tendermint::abci::Code {
+    fn from(value: ResultCode) -> Self {
+        Self::from(u32::from(value))
+    }
+}
+
 /// Sentinel used in transactions to signal events that require special
 /// replay protection handling back to the protocol.
 #[derive(Debug, Default)]
@@ -172,6 +355,8 @@ pub enum TxSentinel {
     OutOfGas,
     /// Found invalid commtiment to one of the transaction's sections
     InvalidCommitment,
+    /// The tx wasm aborted with the given error
+    Aborted(TxWasmError),
 }
 
 impl TxSentinel {
@@ -184,12 +369,22 @@ impl TxSentinel {
     pub fn set_invalid_commitment(&mut self) {
         *self = Self::InvalidCommitment
     }
+
+    /// Set the sentinel for an error the tx wasm aborted with. This doesn't
+    /// override an out of gas or an invalid section commitment error.
+    pub fn set_aborted(&mut self, error: TxWasmError) {
+        if let Self::None = self {
+            *self = Self::Aborted(error)
+        }
+    }
 }
 
 #[cfg(test)]
 mod test_process_tx {
     use super::*;
-    use crate::proto::{Code, Data, Section, Signature, Tx, TxError};
+    use crate::proto::{
+        Code, Data, Section, SigFailure, Signature, Tx, TxError,
+    };
     use crate::types::address::nam;
     use crate::types::key::*;
     use crate::types::storage::Epoch;
@@ -318,7 +513,13 @@ mod test_process_tx {
         tx.set_code(Code::new("wasm code".as_bytes().to_owned(), None));
         tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
         let result = tx.validate_tx().expect_err("Test failed");
-        assert_matches!(result, TxError::SigError(_));
+        assert_matches!(
+            result,
+            TxError::SigVerification {
+                failure: SigFailure::NoSignatureForTarget,
+                ..
+            }
+        );
     }
 }
 
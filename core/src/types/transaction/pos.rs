@@ -6,7 +6,10 @@ use serde::{Deserialize, Serialize};
 use crate::types::address::Address;
 use crate::types::dec::Dec;
 use crate::types::hash::Hash;
-use crate::types::key::{common, secp256k1};
+use crate::types::key::pop::{
+    prove_possession, verify_possession, ProofOfPossession,
+};
+use crate::types::key::{common, secp256k1, RefTo, VerifySigError};
 use crate::types::token;
 
 /// A tx data type to initialize a new validator account.
@@ -51,6 +54,102 @@ pub struct InitValidator {
     pub discord_handle: Option<String>,
     /// The VP code for validator account
     pub validator_vp_code_hash: Hash,
+    /// Proofs of possession of the consensus, Ethereum bridge and protocol
+    /// keys, made for the [`InitValidator::registering_address`]
+    pub key_proofs: ValidatorKeyProofs,
+}
+
+impl InitValidator {
+    /// The address that the keys of the new validator are proven for. The
+    /// address of the validator account is only derived once the tx is
+    /// applied, so the keys are instead proven for the implicit address of
+    /// the first account key, whose signature the tx must carry.
+    pub fn registering_address(&self) -> Option<Address> {
+        self.account_keys.first().map(Address::from)
+    }
+
+    /// Check the proofs of possession of the consensus, Ethereum bridge and
+    /// protocol keys of the new validator
+    pub fn verify_key_proofs(&self) -> Result<(), VerifySigError> {
+        let address = self.registering_address().ok_or_else(|| {
+            VerifySigError::SigVerifyError(
+                "A validator needs at least one account key".to_string(),
+            )
+        })?;
+        self.key_proofs.verify(
+            &address,
+            &self.consensus_key,
+            &self.eth_cold_key,
+            &self.eth_hot_key,
+            &self.protocol_key,
+        )
+    }
+}
+
+/// Proofs of possession of the secret keys of a validator's consensus,
+/// Ethereum bridge and protocol keys
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct ValidatorKeyProofs {
+    /// Proof of possession of the consensus key
+    pub consensus_key: ProofOfPossession,
+    /// Proof of possession of the Eth bridge governance key
+    pub eth_cold_key: ProofOfPossession,
+    /// Proof of possession of the Eth bridge hot signing key
+    pub eth_hot_key: ProofOfPossession,
+    /// Proof of possession of the protocol key
+    pub protocol_key: ProofOfPossession,
+}
+
+impl ValidatorKeyProofs {
+    /// Prove the possession of the given validator keys for the given
+    /// address
+    pub fn new(
+        address: &Address,
+        consensus_key: &common::SecretKey,
+        eth_cold_key: &common::SecretKey,
+        eth_hot_key: &common::SecretKey,
+        protocol_key: &common::SecretKey,
+    ) -> Self {
+        Self {
+            consensus_key: prove_possession(consensus_key, address),
+            eth_cold_key: prove_possession(eth_cold_key, address),
+            eth_hot_key: prove_possession(eth_hot_key, address),
+            protocol_key: prove_possession(protocol_key, address),
+        }
+    }
+
+    /// Check the proofs of possession of the given validator keys for the
+    /// given address
+    pub fn verify(
+        &self,
+        address: &Address,
+        consensus_key: &common::PublicKey,
+        eth_cold_key: &secp256k1::PublicKey,
+        eth_hot_key: &secp256k1::PublicKey,
+        protocol_key: &common::PublicKey,
+    ) -> Result<(), VerifySigError> {
+        verify_possession(consensus_key, address, &self.consensus_key)?;
+        verify_possession(
+            &common::PublicKey::Secp256k1(eth_cold_key.clone()),
+            address,
+            &self.eth_cold_key,
+        )?;
+        verify_possession(
+            &common::PublicKey::Secp256k1(eth_hot_key.clone()),
+            address,
+            &self.eth_hot_key,
+        )?;
+        verify_possession(protocol_key, address, &self.protocol_key)
+    }
 }
 
 /// A bond is a validator's self-bond or a delegation from non-validator to a
@@ -212,4 +311,19 @@ pub struct ConsensusKeyChange {
     pub validator: Address,
     /// The new consensus key
     pub consensus_key: common::PublicKey,
+    /// Proof of possession of the new consensus key, made for the validator
+    /// address
+    pub consensus_key_proof: ProofOfPossession,
+}
+
+impl ConsensusKeyChange {
+    /// Change the consensus key of the given validator to the given key,
+    /// proving its possession
+    pub fn new(validator: Address, consensus_key: &common::SecretKey) -> Self {
+        Self {
+            consensus_key_proof: prove_possession(consensus_key, &validator),
+            consensus_key: consensus_key.ref_to(),
+            validator,
+        }
+    }
 }
@@ -53,6 +53,28 @@ impl ProtocolTx {
     }
 }
 
+/// Sign the given data of a protocol tx of the given type and wrap it in a
+/// [`Tx`]
+pub fn sign_protocol_tx(
+    tx_type: ProtocolTxType,
+    tx_data: Vec<u8>,
+    signing_key: &common::SecretKey,
+    chain_id: ChainId,
+) -> Tx {
+    let mut outer_tx = Tx::from_type(TxType::Protocol(Box::new(ProtocolTx {
+        pk: signing_key.ref_to(),
+        tx: tx_type,
+    })));
+    outer_tx.header.chain_id = chain_id;
+    outer_tx.set_data(Data::new(tx_data));
+    outer_tx.add_section(Section::Signature(Signature::new(
+        outer_tx.sechashes(),
+        [(0, signing_key)].into_iter().collect(),
+        None,
+    )));
+    outer_tx
+}
+
 macro_rules! ethereum_tx_data_deserialize_inner {
     ($variant:ty) => {
         impl TryFrom<&Tx> for $variant {
@@ -160,19 +182,7 @@ impl EthereumTxData {
         chain_id: ChainId,
     ) -> Tx {
         let (tx_data, tx_type) = self.serialize();
-        let mut outer_tx =
-            Tx::from_type(TxType::Protocol(Box::new(ProtocolTx {
-                pk: signing_key.ref_to(),
-                tx: tx_type,
-            })));
-        outer_tx.header.chain_id = chain_id;
-        outer_tx.set_data(Data::new(tx_data));
-        outer_tx.add_section(Section::Signature(Signature::new(
-            outer_tx.sechashes(),
-            [(0, signing_key.clone())].into_iter().collect(),
-            None,
-        )));
-        outer_tx
+        sign_protocol_tx(tx_type, tx_data, signing_key, chain_id)
     }
 
     /// Serialize Ethereum protocol transaction data.
@@ -225,6 +235,11 @@ impl EthereumTxData {
                 BorshDeserialize::try_from_slice(data)
                     .map(EthereumTxData::ValSetUpdateVext)
             },
+            ProtocolTxType::Slash => {
+                return Err(TxError::Deserialization(
+                    "Expected an Ethereum protocol tx type".into(),
+                ));
+            }
         };
         deserialize(data)
             .map_err(|err| TxError::Deserialization(err.to_string()))
@@ -257,6 +272,9 @@ pub enum ProtocolTxType {
     BridgePoolVext,
     /// Validator set update signed by some validator
     ValSetUpdateVext,
+    /// Evidence of a slashable event delivered by CometBFT to the proposer
+    /// of a block
+    Slash,
 }
 
 impl ProtocolTxType {
@@ -80,6 +80,19 @@ pub fn transaction(attr: TokenStream, input: TokenStream) -> TokenStream {
 
             if let Err(err) = #ident(&mut ctx, tx_data) {
                 namada_tx_prelude::debug_log!("Transaction error: {}", err);
+                // report the error to the protocol, falling back to a custom
+                // error for untyped ones
+                let err = match err
+                    .downcast::<namada_tx_prelude::transaction::TxWasmError>()
+                {
+                    Ok(err) => *err,
+                    Err(err) => {
+                        namada_tx_prelude::transaction::TxWasmError::Custom(
+                            err.to_string(),
+                        )
+                    }
+                };
+                namada_tx_prelude::TxEnv::set_error(&mut ctx, &err);
                 // crash the transaction to abort
                 panic!();
             }
@@ -4,6 +4,7 @@ use std::num::TryFromIntError;
 use namada_core::ledger::storage_api;
 use namada_core::types::address::Address;
 use namada_core::types::dec::Dec;
+use namada_core::types::key::VerifySigError;
 use namada_core::types::storage::Epoch;
 use thiserror::Error;
 
@@ -31,6 +32,8 @@ pub enum InflationError {
 pub enum BecomeValidatorError {
     #[error("The given address {0} is already a validator")]
     AlreadyValidator(Address),
+    #[error("Invalid proof of possession of a validator key: {0}")]
+    InvalidKeyProof(VerifySigError),
 }
 
 #[allow(missing_docs)]
@@ -103,6 +106,11 @@ pub enum CommissionRateChangeError {
     LargerThanOne(Dec, Address),
     #[error("Rate change of {0} is too large for validator {1}")]
     RateChangeTooLarge(Dec, Address),
+    #[error(
+        "The commission rate of validator {0} has already been changed in the \
+         current epoch"
+    )]
+    AlreadyChangedInEpoch(Address),
     #[error(
         "There is no maximum rate change written in storage for validator {0}"
     )]
@@ -175,6 +183,8 @@ pub enum MetadataError {
 pub enum ConsensusKeyChangeError {
     #[error("The consensus key is required to be an Ed25519 key")]
     MustBeEd25519,
+    #[error("Invalid proof of possession of the consensus key: {0}")]
+    InvalidKeyProof(VerifySigError),
 }
 
 impl From<BecomeValidatorError> for storage_api::Error {
@@ -33,6 +33,7 @@ use namada_core::ledger::storage_api::{
 };
 use namada_core::types::address::{self, Address, InternalAddress};
 use namada_core::types::dec::Dec;
+use namada_core::types::key::pop::{verify_possession, ProofOfPossession};
 use namada_core::types::key::{
     common, protocol_pk_key, tm_consensus_key_raw_hash, PublicKeyTmRawHash,
 };
@@ -2855,7 +2856,7 @@ where
     // Require that the new consensus key is an Ed25519 key
     match consensus_key {
         common::PublicKey::Ed25519(_) => {}
-        common::PublicKey::Secp256k1(_) => {
+        _ => {
             return Err(ConsensusKeyChangeError::MustBeEd25519.into());
         }
     }
@@ -2878,6 +2879,17 @@ where
     Ok(())
 }
 
+/// Check the proof of possession of a validator's new consensus key, which
+/// must have been made for the validator's address
+pub fn verify_consensus_key_proof(
+    validator: &Address,
+    consensus_key: &common::PublicKey,
+    proof: &ProofOfPossession,
+) -> storage_api::Result<()> {
+    verify_possession(consensus_key, validator, proof)
+        .map_err(|err| ConsensusKeyChangeError::InvalidKeyProof(err).into())
+}
+
 /// Withdraw tokens from those that have been unbonded from proof-of-stake
 pub fn withdraw_tokens<S>(
     storage: &mut S,
@@ -2958,6 +2970,15 @@ where
         );
     }
 
+    // Check that at least one of the unbonds has matured
+    if unbonds_and_redelegated_unbonds.is_empty() {
+        return Err(WithdrawError::NoWithdrawableUnbond(BondId {
+            source: source.clone(),
+            validator: validator.clone(),
+        })
+        .into());
+    }
+
     let slashes = find_validator_slashes(storage, validator)?;
 
     // `val resultSlashing`
@@ -3062,6 +3083,18 @@ where
     if new_rate == rate_at_pipeline {
         return Ok(());
     }
+    // Only a single rate change is allowed per epoch. A rate set at the
+    // pipeline epoch could have only been written in the current epoch.
+    if commission_handle
+        .get_data_handler()
+        .get(storage, &pipeline_epoch)?
+        .is_some()
+    {
+        return Err(CommissionRateChangeError::AlreadyChangedInEpoch(
+            validator.clone(),
+        )
+        .into());
+    }
     let rate_before_pipeline = commission_handle
         .get(storage, pipeline_epoch.prev(), &params)?
         .expect("Could not find a rate in given epoch");
@@ -4438,6 +4471,40 @@ where
     Ok(())
 }
 
+/// Check whether a slash for the given misbehavior of a validator was already
+/// recorded by [`slash`], so that evidence delivered more than once is only
+/// slashed once
+pub fn is_slash_enqueued<S>(
+    storage: &S,
+    params: &PosParams,
+    evidence_epoch: Epoch,
+    evidence_block_height: impl Into<u64>,
+    slash_type: SlashType,
+    validator: &Address,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    let evidence_block_height: u64 = evidence_block_height.into();
+    let processing_epoch =
+        evidence_epoch + params.slash_processing_epoch_offset();
+    for slash in enqueued_slashes_handle()
+        .get_data_handler()
+        .at(&processing_epoch)
+        .at(validator)
+        .iter(storage)?
+    {
+        let slash = slash?;
+        if slash.epoch == evidence_epoch
+            && slash.block_height == evidence_block_height
+            && slash.r#type == slash_type
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 /// Process slashes NEW
 pub fn process_slashes<S>(
     storage: &mut S,
@@ -78,7 +78,7 @@ use crate::{
     validator_total_redelegated_unbonded_handle, withdraw_tokens,
     write_pos_params, write_validator_address_raw_hash, BecomeValidator,
     EagerRedelegatedUnbonds, FoldRedelegatedBondsResult, ModifiedRedelegation,
-    RedelegationError,
+    RedelegationError, WithdrawError,
 };
 
 proptest! {
@@ -1129,11 +1129,69 @@ fn test_become_validator_aux(
     for _ in 0..withdrawable_offset {
         current_epoch = advance_epoch(&mut s, &params);
     }
+    for _ in 0..params.cubic_slashing_window_length {
+        current_epoch = advance_epoch(&mut s, &params);
+    }
 
     // Withdraw the self-bond
     withdraw_tokens(&mut s, None, &new_validator, current_epoch).unwrap();
 }
 
+/// Test that withdrawing an unbond before it matures fails, and that it can
+/// be withdrawn once the cubic slashing window has passed
+#[test]
+fn test_withdraw_before_maturity() {
+    let mut s = TestWlStorage::default();
+    let validator = address::testing::established_address_1();
+    let genesis_validator = GenesisValidator {
+        address: validator.clone(),
+        tokens: token::Amount::native_whole(1_000),
+        consensus_key: key::testing::keypair_1().ref_to(),
+        protocol_key: key::testing::keypair_2().ref_to(),
+        eth_cold_key: key::testing::keypair_3().ref_to(),
+        eth_hot_key: key::testing::keypair_4().ref_to(),
+        commission_rate: Dec::new(5, 2).expect("Cannot fail"),
+        max_commission_rate_change: Dec::new(1, 2).expect("Cannot fail"),
+        metadata: Default::default(),
+    };
+
+    // Genesis
+    let mut current_epoch = s.storage.block.epoch;
+    let params = test_init_genesis(
+        &mut s,
+        OwnedPosParams::default(),
+        [genesis_validator].into_iter(),
+        current_epoch,
+    )
+    .unwrap();
+    s.commit_block().unwrap();
+
+    // Unbond a part of the self-bond
+    let amount = token::Amount::native_whole(100);
+    unbond_tokens(&mut s, None, &validator, amount, current_epoch, false)
+        .unwrap();
+
+    // The unbond isn't withdrawable before the end of the cubic slashing
+    // window
+    for _ in 0..params.unbonding_len + params.pipeline_len {
+        current_epoch = advance_epoch(&mut s, &params);
+    }
+    let err =
+        withdraw_tokens(&mut s, None, &validator, current_epoch).unwrap_err();
+    let err_str = err.to_string();
+    assert_matches!(
+        err.downcast::<WithdrawError>().unwrap().deref(),
+        WithdrawError::NoWithdrawableUnbond(BondId { source, validator: id })
+            if source == &validator && id == &validator,
+        "Premature withdrawal must be rejected, got {err_str}",
+    );
+
+    for _ in 0..params.cubic_slashing_window_length {
+        current_epoch = advance_epoch(&mut s, &params);
+    }
+    withdraw_tokens(&mut s, None, &validator, current_epoch).unwrap();
+}
+
 fn test_slashes_with_unbonding_aux(
     mut params: OwnedPosParams,
     validators: Vec<GenesisValidator>,
@@ -577,6 +577,29 @@ pub enum SlashType {
     LightClientAttack,
 }
 
+/// The evidence of a slashable event, as carried by the slash protocol txs
+/// of a block
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    BorshSchema,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub struct SlashEvidence {
+    /// The misbehaving validator
+    pub validator: Address,
+    /// Block height at which the slashable event occurred
+    pub block_height: u64,
+    /// A type of slashable event
+    pub r#type: SlashType,
+}
+
 /// VoteInfo inspired from tendermint for validators whose signature was
 /// included in the last block
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
@@ -11,6 +11,7 @@ use namada_core::types::address::Address;
 use namada_core::types::chain::ChainId;
 use namada_core::types::dec::Dec;
 use namada_core::types::ethereum_events::EthAddress;
+use namada_core::types::hash::Hash;
 use namada_core::types::keccak::KeccakHash;
 use namada_core::types::key::{common, SchemeType};
 use namada_core::types::masp::MaspValue;
@@ -112,7 +113,37 @@ pub struct QueryResult<C: NamadaTypes = SdkTypes> {
     /// Common query args
     pub query: Query<C>,
     /// Hash of transaction to lookup
-    pub tx_hash: String,
+    pub tx_hash: Hash,
+}
+
+/// Dump a transaction included in a block
+#[derive(Clone, Debug)]
+pub struct QueryTxDump<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// Hash of the transaction to dump
+    pub tx_hash: Hash,
+    /// Also look up the sections of the decrypted inner transaction
+    pub decrypted: bool,
+    /// The folder to write the dump to
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Decode a transaction from the hex or base64 encoding of its bytes
+#[derive(Clone, Debug)]
+pub struct QueryTxDecode<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The hex or base64 encoded bytes of the transaction
+    pub blob: String,
+    /// Broadcast the decoded transaction instead of dumping it
+    pub rebroadcast: bool,
+    /// Maximum time to wait for the wrapper of the broadcast transaction to
+    /// be included in a block
+    pub wrapper_timeout: Option<StdDuration>,
+    /// Maximum time to wait for the inner transaction to be applied, once its
+    /// wrapper has been included in a block
+    pub inner_timeout: Option<StdDuration>,
 }
 
 /// Custom transaction arguments
@@ -225,6 +256,8 @@ pub struct TxTransfer<C: NamadaTypes = SdkTypes> {
     pub amount: InputAmount,
     /// Native token address
     pub native_token: C::NativeAddress,
+    /// Memo to attach to the transfer
+    pub memo: Option<String>,
     /// Path to the TX WASM code file
     pub tx_code_path: PathBuf,
 }
@@ -270,6 +303,14 @@ impl<C: NamadaTypes> TxTransfer<C> {
         }
     }
 
+    /// Memo to attach to the transfer
+    pub fn memo(self, memo: String) -> Self {
+        Self {
+            memo: Some(memo),
+            ..self
+        }
+    }
+
     /// Path to the TX WASM code file
     pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
         Self {
@@ -1735,6 +1776,68 @@ pub struct SignTx<C: NamadaTypes = SdkTypes> {
     pub owner: C::Address,
 }
 
+/// Export a transaction for signing on an offline machine
+#[derive(Clone, Debug)]
+pub struct ExportUnsignedTx<C: NamadaTypes = SdkTypes> {
+    /// The serialized transaction
+    pub tx_data: C::Data,
+    /// The folder to write the unsigned transaction file to
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Sign an exported transaction offline
+#[derive(Clone, Debug)]
+pub struct SignUnsignedTx<C: NamadaTypes = SdkTypes> {
+    /// The exported unsigned transaction
+    pub unsigned_tx: C::Data,
+    /// The keys to sign with
+    pub signing_keys: Vec<C::Keypair>,
+    /// The folder to write the signatures file to
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Attach signatures made offline to a transaction
+#[derive(Clone, Debug)]
+pub struct ImportSignatures<C: NamadaTypes = SdkTypes> {
+    /// The serialized transaction
+    pub tx_data: C::Data,
+    /// The signatures files
+    pub signatures: Vec<C::Data>,
+    /// The folder to write the signed transaction to
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Start collecting the signatures of a multisig account over a transaction
+#[derive(Clone, Debug)]
+pub struct MultisigCreate<C: NamadaTypes = SdkTypes> {
+    /// The serialized transaction
+    pub tx_data: C::Data,
+    /// The multisig account on behalf of which the transaction is signed
+    pub owner: C::Address,
+    /// The folder to write the multisig bundle to
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Add signatures to a multisig bundle
+#[derive(Clone, Debug)]
+pub struct MultisigSign<C: NamadaTypes = SdkTypes> {
+    /// The multisig bundle
+    pub bundle: C::Data,
+    /// The keys to sign with
+    pub signing_keys: Vec<C::Keypair>,
+    /// The folder to write the signed multisig bundle to
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Merge multisig bundles and submit their transaction
+#[derive(Clone, Debug)]
+pub struct MultisigSubmit<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The multisig bundles signed by the co-signers
+    pub bundles: Vec<C::Data>,
+}
+
 /// Query PoS commission rate
 #[derive(Clone, Debug)]
 pub struct QueryCommissionRate<C: NamadaTypes = SdkTypes> {
@@ -1843,6 +1946,16 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     pub password: Option<Zeroizing<String>>,
     /// Use device to sign the transaction
     pub use_device: bool,
+    /// Show a summary of the tx and wait for confirmation before signing it
+    pub confirm_signing: bool,
+    /// Build the tx header from the given chain id and expiration only,
+    /// instead of the context queried from the connected chain
+    pub offline: bool,
+    /// Embed the tx code in the tx when the chain doesn't store it, instead
+    /// of failing to build the tx
+    pub embed_missing_code: bool,
+    /// The directory to read the embedded tx code from
+    pub wasm_dir: Option<PathBuf>,
 }
 
 /// Builder functions for Tx
@@ -1990,6 +2103,42 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
             ..x
         })
     }
+    /// Embed the tx code in the tx when the chain doesn't store it, instead
+    /// of failing to build the tx
+    fn embed_missing_code(self, embed_missing_code: bool) -> Self {
+        self.tx(|x| Tx {
+            embed_missing_code,
+            ..x
+        })
+    }
+    /// Show a summary of the tx and wait for confirmation before signing it
+    fn confirm_signing(self, confirm_signing: bool) -> Self {
+        self.tx(|x| Tx {
+            confirm_signing,
+            ..x
+        })
+    }
+    /// Build the tx header from the given chain id and expiration only,
+    /// instead of the context queried from the connected chain
+    fn offline(
+        self,
+        chain_id: ChainId,
+        expiration: Option<DateTimeUtc>,
+    ) -> Self {
+        self.tx(|x| Tx {
+            offline: true,
+            chain_id: Some(chain_id),
+            expiration,
+            ..x
+        })
+    }
+    /// The directory to read the embedded tx code from
+    fn wasm_dir(self, wasm_dir: PathBuf) -> Self {
+        self.tx(|x| Tx {
+            wasm_dir: Some(wasm_dir),
+            ..x
+        })
+    }
 }
 
 impl<C: NamadaTypes> TxBuilder<C> for Tx<C> {
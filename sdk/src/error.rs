@@ -2,8 +2,11 @@
 
 use namada_core::proto::Tx;
 use namada_core::types::address::Address;
+use namada_core::types::chain::ChainId;
 use namada_core::types::dec::Dec;
 use namada_core::types::ethereum_events::EthAddress;
+use namada_core::types::hash::Hash;
+use namada_core::types::key::musig::MusigError;
 use namada_core::types::storage;
 use namada_core::types::storage::Epoch;
 use prost::EncodeError;
@@ -11,6 +14,7 @@ use tendermint_rpc::Error as RpcError;
 use thiserror::Error;
 
 use crate::error::Error::Pinned;
+use crate::tx::BroadcastReceipt;
 
 /// The standard Result type that most code ought to return
 pub type Result<T> = std::result::Result<T, Error>;
@@ -42,6 +46,9 @@ pub enum Error {
     /// Ethereum bridge related errors
     #[error("{0}")]
     EthereumBridge(#[from] EthereumBridgeError),
+    /// Errors reported by hardware wallets
+    #[error("{0}")]
+    Ledger(#[from] LedgerError),
     /// Any Other errors that are uncategorized
     #[error("{0}")]
     Other(String),
@@ -75,6 +82,15 @@ pub enum EventError {
     /// Missing value in attributes.
     #[error("Attributes missing value: {0}")]
     MissingValue(String),
+    /// An event could not be decoded from its attributes.
+    #[error("Failed to decode {0} event: {1}")]
+    Decoding(String, String),
+    /// The connection of an event subscription failed.
+    #[error("Event subscription error: {0}")]
+    Subscription(String),
+    /// The queue of an event subscription overflowed.
+    #[error("More than {0} events were queued for the subscription")]
+    SubscriptionOverflow(usize),
 }
 
 /// Errors that deal with querying some kind of data
@@ -124,6 +140,9 @@ pub enum TxError {
     /// Applied tx timeout
     #[error("Timed out waiting for tx to be applied")]
     AppliedTimeout,
+    /// The inner tx could not be decrypted by the ledger
+    #[error("Inner tx {0} could not be decrypted")]
+    Undecryptable(String),
     /// Expect a dry running transaction
     #[error(
         "Expected a dry-run transaction, received a wrapper transaction \
@@ -246,6 +265,29 @@ pub enum TxError {
     /// Error in the fee unshielding transaction
     #[error("Error in fee unshielding: {0}")]
     FeeUnshieldingError(String),
+    /// The token is not accepted for fee payment
+    #[error("The token {0} is not allowed for fee payment")]
+    FeeTokenNotAllowed(Address),
+    /// The chain id of the tx isn't the one of the connected chain
+    #[error(
+        "The tx is intended for the chain {given}, but the connected chain is \
+         {connected}"
+    )]
+    ChainIdMismatch {
+        /// The chain id given for the tx
+        given: ChainId,
+        /// The chain id of the connected chain
+        connected: ChainId,
+    },
+    /// The context of the connected chain couldn't be queried
+    #[error(
+        "Failed to query the context of the connected chain: {0}. To build \
+         the tx without it, use the offline mode with an explicit chain id."
+    )]
+    MissingChainContext(String),
+    /// The offline mode was requested without a chain id
+    #[error("A chain id must be given to build a tx in offline mode")]
+    OfflineMissingChainId,
     /// Encoding transaction failure
     #[error("Encoding tx data, {0}, shouldn't fail")]
     EncodeTxFailure(String),
@@ -293,6 +335,18 @@ pub enum TxError {
     /// Account threshold is not set
     #[error("Account threshold must be set.")]
     MissingAccountThreshold,
+    /// Account threshold is out of the range of the number of public keys
+    #[error(
+        "Account threshold {0} must be between 1 and the number of public \
+         keys {1}."
+    )]
+    InvalidAccountThreshold(u8, usize),
+    /// The same public key is given more than once for an account
+    #[error("The public key {0} is given more than once.")]
+    DuplicateAccountPublicKey(String),
+    /// The memo of a tx is too long
+    #[error("The memo is {0} bytes long, but the maximum is {1} bytes.")]
+    MemoTooLong(usize, usize),
     /// Not enough signature
     #[error("Account threshold is {0} but the valid signatures are {1}.")]
     MissingSigningKeys(u8, u8),
@@ -330,6 +384,84 @@ pub enum TxError {
     /// The consensus key is not unique
     #[error("The consensus key has already been registered and is not unique")]
     ConsensusKeyNotUnique,
+    /// The exported unsigned tx is inconsistent with its own header
+    #[error("The unsigned tx file is inconsistent: {0}")]
+    InvalidUnsignedTx(String),
+    /// The offline signatures were made over a different tx
+    #[error(
+        "The signatures were made over the tx with raw header hash {0}, but \
+         this tx has raw header hash {1}. The tx was modified after it was \
+         exported for signing."
+    )]
+    SignaturesTargetMismatch(Hash, Hash),
+    /// A section committed to by the tx header is missing
+    #[error("The {0} section {1} committed to by the tx header is missing")]
+    MissingTxSection(String, Hash),
+    /// An offline signature does not verify against the tx
+    #[error("The signature by {0} is not valid for this tx")]
+    InvalidOfflineSignature(String),
+    /// The node rejected the tx during `CheckTx`
+    #[error(
+        "The tx {} was rejected by CheckTx with code {}: {}",
+        .0.wrapper_hash,
+        .0.code,
+        .0.log
+    )]
+    CheckTxRejected(Box<BroadcastReceipt>),
+    /// Only raw txs can be put into a wrapper
+    #[error("Only raw txs can be wrapped, but the given tx is of another type")]
+    NotARawTx,
+    /// Only wrapper txs can be re-sealed
+    #[error("The given tx is not a wrapper tx")]
+    NotAWrapperTx,
+    /// The signer of a wrapper is not its fee payer
+    #[error("The wrapper is paid for by {0}, but the signer is {1}")]
+    FeePayerMismatch(String, String),
+    /// A signer could not produce a signature over the tx
+    #[error("Failed to sign the tx with {0}: {1}")]
+    SignerFailed(String, String),
+    /// The tx would be rejected by the ledger, as found before broadcasting
+    #[error(
+        "The tx would be rejected by the ledger: {0}. Use --force to \
+         broadcast it anyway"
+    )]
+    RejectedBeforeBroadcast(String),
+    /// The blob is neither hex nor base64 encoded
+    #[error("The given tx blob is neither valid hex nor valid base64")]
+    InvalidTxBlob,
+    /// The decoded blob does not hold a tx
+    #[error("The decoded bytes are not a Namada transaction: {0}")]
+    NotATx(String),
+    /// The decoded tx is not valid
+    #[error("The transaction decodes but fails validation: {0}")]
+    DecodedTxInvalid(String),
+    /// The multisig bundle is inconsistent with its own tx
+    #[error("The multisig bundle is invalid: {0}")]
+    InvalidMultisigBundle(String),
+    /// The multisig bundles to merge hold divergent copies of a tx
+    #[error(
+        "The multisig bundles for the tx with raw header hash {0} hold \
+         divergent contents"
+    )]
+    ConflictingMultisigBundles(Hash),
+    /// Not enough signatures were collected for a multisig account
+    #[error(
+        "The tx has {1} valid signature(s) on behalf of {0}, but its \
+         threshold is {2}"
+    )]
+    MultisigThresholdNotMet(Address, usize, u8),
+    /// The tx code is not stored on chain and embedding it wasn't requested
+    #[error(
+        "The wasm code {0} is not stored on chain. Use --embed-missing-code \
+         to include the code in the tx instead"
+    )]
+    MissingTxCode(String),
+    /// The tx code to embed could not be read
+    #[error("Failed to read the wasm code {0}: {1}")]
+    ReadTxCode(String, String),
+    /// A MuSig2 signing session could not make progress
+    #[error("MuSig2 signing session failed: {0}")]
+    MusigSession(MusigError),
     /// Other Errors that may show up when using the interface
     #[error("{0}")]
     Other(String),
@@ -387,6 +519,35 @@ pub enum EthereumBridgeError {
     TransferAlreadyInPool,
 }
 
+/// Errors reported by or about Ledger hardware wallets
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LedgerError {
+    /// The user rejected the request on the device
+    #[error("The request was rejected on the Ledger device")]
+    UserRejected,
+    /// The Namada app is not the one open on the device
+    #[error("The Namada app is not open on the Ledger device")]
+    WrongApp,
+    /// The device is locked
+    #[error("The Ledger device is locked")]
+    DeviceLocked,
+    /// Any other status reported by the device
+    #[error("The Ledger device returned the error code {0:#06x}")]
+    Device(u16),
+    /// The device could not be reached
+    #[error("Unable to communicate with the Ledger device: {0}")]
+    Transport(String),
+    /// The device answered with malformed data
+    #[error("Invalid response from the Ledger device: {0}")]
+    InvalidResponse(String),
+}
+
+impl From<MusigError> for Error {
+    fn from(err: MusigError) -> Self {
+        TxError::MusigSession(err).into()
+    }
+}
+
 /// Checks if the given error is an invalid viewing key
 pub fn is_pinned_error<T>(err: &Result<T>) -> bool {
     matches!(err, Err(Pinned(PinnedBalanceError::InvalidViewingKey)))
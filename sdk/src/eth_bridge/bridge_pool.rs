@@ -38,9 +38,9 @@ use crate::queries::{
     Client, GenBridgePoolProofReq, GenBridgePoolProofRsp, TransferToErcArgs,
     RPC,
 };
-use crate::rpc::{query_storage_value, query_wasm_code_hash, validate_amount};
+use crate::rpc::{query_storage_value, validate_amount};
 use crate::signing::aux_signing_data;
-use crate::tx::prepare_tx;
+use crate::tx::{new_tx, prepare_tx, TxCode};
 use crate::{
     args, display, display_line, edisplay_line, Namada, SigningTxData,
 };
@@ -62,7 +62,7 @@ pub async fn build_bridge_pool_tx(
     }: args::EthereumBridgePool,
 ) -> Result<(Tx, SigningTxData, Option<Epoch>), Error> {
     let sender_ = sender.clone();
-    let (transfer, tx_code_hash, signing_data) = futures::try_join!(
+    let (transfer, tx_code, signing_data) = futures::try_join!(
         validate_bridge_pool_tx(
             context,
             tx_args.force,
@@ -75,7 +75,7 @@ pub async fn build_bridge_pool_tx(
             fee_payer,
             fee_token,
         ),
-        query_wasm_code_hash(context, code_path.to_string_lossy()),
+        TxCode::query(context, &tx_args, &code_path),
         aux_signing_data(
             context,
             &tx_args,
@@ -86,17 +86,8 @@ pub async fn build_bridge_pool_tx(
         ),
     )?;
 
-    let chain_id = tx_args
-        .chain_id
-        .clone()
-        .ok_or_else(|| Error::Other("No chain id available".into()))?;
-
-    let mut tx = Tx::new(chain_id, tx_args.expiration);
-    tx.add_code_from_hash(
-        tx_code_hash,
-        Some(code_path.to_string_lossy().into_owned()),
-    )
-    .add_data(transfer);
+    let mut tx = new_tx(context, &tx_args).await?;
+    tx_code.add_to(&mut tx, &code_path).add_data(transfer);
 
     let epoch = prepare_tx(
         context,
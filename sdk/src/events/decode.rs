@@ -0,0 +1,675 @@
+//! Typed decoding of the events emitted by the ledger.
+//!
+//! Clients receive events either from Tendermint, whose event attributes are
+//! raw bytes under ABCI and strings under ABCI++, or as [`Event`]s returned
+//! by the `applied` shell query. Both are first normalized into a
+//! [`RawEvent`], and then parsed from their documented attribute keys.
+
+use std::collections::HashMap;
+
+use namada_core::types::address::Address;
+use namada_core::types::event::TxEvent;
+use namada_core::types::ibc::IbcEvent;
+use namada_core::types::storage::BlockHeight;
+use namada_core::types::token::{self, NATIVE_MAX_DECIMAL_PLACES};
+use namada_core::types::transaction::ResultCode;
+
+use crate::error::{Error, EventError};
+use crate::events::{Event, EventType};
+use crate::tendermint::abci;
+use crate::tendermint_proto::{v0_34, v0_37};
+
+/// An event whose attributes have not been parsed yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEvent {
+    /// The type of the event
+    pub kind: String,
+    /// Key-value attributes of the event
+    pub attributes: HashMap<String, String>,
+}
+
+impl RawEvent {
+    fn attribute(&self, key: &str) -> Result<&str, Error> {
+        self.attributes
+            .get(key)
+            .map(String::as_str)
+            .ok_or_else(|| self.error(format!("missing attribute \"{key}\"")))
+    }
+
+    fn error(&self, msg: String) -> Error {
+        Error::from(EventError::Decoding(self.kind.clone(), msg))
+    }
+}
+
+impl From<Event> for RawEvent {
+    fn from(event: Event) -> Self {
+        Self {
+            kind: event.event_type.to_string(),
+            attributes: event.attributes,
+        }
+    }
+}
+
+impl From<abci::Event> for RawEvent {
+    fn from(event: abci::Event) -> Self {
+        Self {
+            kind: event.kind,
+            attributes: event
+                .attributes
+                .into_iter()
+                .map(|attr| (attr.key, attr.value))
+                .collect(),
+        }
+    }
+}
+
+/// ABCI++ events carry their attributes as strings
+impl From<v0_37::abci::Event> for RawEvent {
+    fn from(event: v0_37::abci::Event) -> Self {
+        Self {
+            kind: event.r#type,
+            attributes: event
+                .attributes
+                .into_iter()
+                .map(|attr| (attr.key, attr.value))
+                .collect(),
+        }
+    }
+}
+
+/// ABCI events carry their attributes as bytes, which must be valid UTF-8
+impl TryFrom<v0_34::abci::Event> for RawEvent {
+    type Error = Error;
+
+    fn try_from(event: v0_34::abci::Event) -> Result<Self, Self::Error> {
+        let decode = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec()).map_err(|err| {
+                Error::from(EventError::Decoding(
+                    event.r#type.clone(),
+                    format!("attribute is not valid UTF-8: {err}"),
+                ))
+            })
+        };
+        let attributes = event
+            .attributes
+            .iter()
+            .map(|attr| Ok((decode(&attr.key)?, decode(&attr.value)?)))
+            .collect::<Result<_, Error>>()?;
+        Ok(Self {
+            kind: event.r#type,
+            attributes,
+        })
+    }
+}
+
+/// The outcome of a tx, as reported by its `accepted` or `applied` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOutcome {
+    /// Hash of the tx
+    pub hash: String,
+    /// Height of the block in which the tx was included
+    pub height: BlockHeight,
+    /// Result code of the tx
+    pub code: ResultCode,
+    /// Gas used by the tx
+    pub gas_used: u64,
+    /// Response information
+    pub info: String,
+    /// Response log
+    pub log: String,
+    /// Accounts initialized by the tx
+    pub initialized_accounts: Vec<Address>,
+}
+
+impl TryFrom<&RawEvent> for TxOutcome {
+    type Error = Error;
+
+    fn try_from(event: &RawEvent) -> Result<Self, Self::Error> {
+        let hash = event.attribute("hash")?.to_string();
+        let height = event
+            .attribute("height")?
+            .parse()
+            .map(BlockHeight)
+            .map_err(|err| event.error(format!("invalid height: {err}")))?;
+        let code = event
+            .attribute("code")?
+            .parse::<u32>()
+            .map_err(|err| event.error(format!("invalid code: {err}")))
+            .and_then(|code| {
+                ResultCode::try_from(code).map_err(|code| {
+                    event.error(format!("unknown result code {code}"))
+                })
+            })?;
+        // Txs that never run, such as undecryptable ones, may not report
+        // any gas
+        let gas_used = match event.attributes.get("gas_used") {
+            Some(gas) => gas.parse().map_err(|err| {
+                event.error(format!("invalid gas used: {err}"))
+            })?,
+            None => 0,
+        };
+        let info = event.attributes.get("info").cloned().unwrap_or_default();
+        let log = event.attributes.get("log").cloned().unwrap_or_default();
+        let initialized_accounts = event
+            .attributes
+            .get("initialized_accounts")
+            .map_or(Ok(vec![]), |accounts| serde_json::from_str(accounts))
+            .map_err(|err| {
+                event.error(format!("invalid initialized accounts: {err}"))
+            })?;
+
+        Ok(Self {
+            hash,
+            height,
+            code,
+            gas_used,
+            info,
+            log,
+            initialized_accounts,
+        })
+    }
+}
+
+/// The outcome of a governance proposal, as reported by its `proposal` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalOutcome {
+    /// Id of the proposal
+    pub proposal_id: u64,
+    /// Whether the proposal passed
+    pub passed: bool,
+    /// Whether the proposal carried some code to execute
+    pub has_proposal_code: bool,
+    /// Whether the execution of the proposal succeeded
+    pub execution_succeeded: bool,
+}
+
+impl TryFrom<&RawEvent> for ProposalOutcome {
+    type Error = Error;
+
+    fn try_from(event: &RawEvent) -> Result<Self, Self::Error> {
+        let proposal_id =
+            event.attribute("proposal_id")?.parse().map_err(|err| {
+                event.error(format!("invalid proposal id: {err}"))
+            })?;
+        let passed = match event.attribute("tally_result")? {
+            "passed" => true,
+            "rejected" => false,
+            other => {
+                return Err(
+                    event.error(format!("invalid tally result {other}"))
+                );
+            }
+        };
+        // The ledger encodes these flags negated, i.e. `0` stands for `true`
+        let negated_flag = |key: &str| match event.attribute(key)? {
+            "0" => Ok(true),
+            "1" => Ok(false),
+            other => Err(event.error(format!("invalid {key} {other}"))),
+        };
+        Ok(Self {
+            proposal_id,
+            passed,
+            has_proposal_code: negated_flag("has_proposal_code")?,
+            execution_succeeded: negated_flag("proposal_code_exit_status")?,
+        })
+    }
+}
+
+/// A payment made by PGF, as reported by its `pgf_payment` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgfPayment {
+    /// The recipient of the payment
+    pub target: Address,
+    /// The amount of native tokens paid
+    pub amount: token::Amount,
+    /// Whether the recipient is a steward, as opposed to a continuous
+    /// funding target
+    pub is_steward: bool,
+    /// Whether the payment succeeded
+    pub success: bool,
+}
+
+impl TryFrom<&RawEvent> for PgfPayment {
+    type Error = Error;
+
+    fn try_from(event: &RawEvent) -> Result<Self, Self::Error> {
+        let target = event
+            .attribute("target")?
+            .parse()
+            .map_err(|err| event.error(format!("invalid target: {err}")))?;
+        let amount = token::Amount::from_str(
+            event.attribute("amount")?,
+            NATIVE_MAX_DECIMAL_PLACES,
+        )
+        .map_err(|err| event.error(format!("invalid amount: {err}")))?;
+        let flag = |key: &str| {
+            event
+                .attribute(key)?
+                .parse::<bool>()
+                .map_err(|err| event.error(format!("invalid {key}: {err}")))
+        };
+        Ok(Self {
+            target,
+            amount,
+            is_steward: flag("is_steward")?,
+            success: flag("successed")?,
+        })
+    }
+}
+
+/// A fee charged to a wrapper tx, as reported by its `fee` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeePayment {
+    /// Hash of the wrapper tx
+    pub hash: String,
+    /// Height of the block in which the wrapper tx was included
+    pub height: BlockHeight,
+    /// The payer of the fee
+    pub payer: Address,
+    /// The token in which the fee was paid
+    pub token: Address,
+    /// The amount of the fee, in the smallest unit of the token
+    pub amount: token::Amount,
+}
+
+impl TryFrom<&RawEvent> for FeePayment {
+    type Error = Error;
+
+    fn try_from(event: &RawEvent) -> Result<Self, Self::Error> {
+        let hash = event.attribute("hash")?.to_string();
+        let height = event
+            .attribute("height")?
+            .parse()
+            .map(BlockHeight)
+            .map_err(|err| event.error(format!("invalid height: {err}")))?;
+        let address = |key: &str| {
+            event
+                .attribute(key)?
+                .parse()
+                .map_err(|err| event.error(format!("invalid {key}: {err}")))
+        };
+        let amount =
+            token::Amount::from_str(event.attribute("amount")?, 0u8)
+                .map_err(|err| event.error(format!("invalid amount: {err}")))?;
+        Ok(Self {
+            hash,
+            height,
+            payer: address("payer")?,
+            token: address("token")?,
+            amount,
+        })
+    }
+}
+
+/// An event emitted by the ledger, decoded according to its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypedEvent {
+    /// A wrapper tx was included in a block
+    Accepted(TxOutcome),
+    /// A decrypted or protocol tx was applied
+    Applied(TxOutcome),
+    /// An event emitted by the IBC handler, whose attributes are defined by
+    /// the IBC specification
+    Ibc(IbcEvent),
+    /// An event emitted by a tx, such as a transfer
+    Tx(TxEvent),
+    /// A governance proposal was executed
+    Proposal(ProposalOutcome),
+    /// A PGF payment was made
+    PgfPayment(PgfPayment),
+    /// A fee was charged to a wrapper tx
+    Fee(FeePayment),
+    /// An event of a type that this client does not know about
+    Unknown(RawEvent),
+}
+
+impl TryFrom<RawEvent> for TypedEvent {
+    type Error = Error;
+
+    fn try_from(event: RawEvent) -> Result<Self, Self::Error> {
+        let event_type = match event.kind.parse() {
+            Ok(event_type) => event_type,
+            Err(_) => return Ok(Self::Unknown(event)),
+        };
+        Ok(match event_type {
+            EventType::Accepted => Self::Accepted((&event).try_into()?),
+            EventType::Applied => Self::Applied((&event).try_into()?),
+            EventType::Ibc(event_type) => Self::Ibc(IbcEvent {
+                event_type,
+                attributes: event.attributes,
+            }),
+            EventType::Tx(event_type) => Self::Tx(TxEvent {
+                event_type,
+                attributes: event.attributes.into_iter().collect(),
+            }),
+            EventType::Proposal => Self::Proposal((&event).try_into()?),
+            EventType::PgfPayment => Self::PgfPayment((&event).try_into()?),
+            EventType::Fee => Self::Fee((&event).try_into()?),
+        })
+    }
+}
+
+/// The typed response of a tx, decoded from the events emitted by the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedTxResponse {
+    /// The outcome reported by the `accepted` or `applied` event of the tx
+    pub outcome: TxOutcome,
+    /// The other events emitted by the tx, such as IBC events
+    pub events: Vec<TypedEvent>,
+}
+
+impl TypedTxResponse {
+    /// Check if the tx was successfully applied
+    pub fn is_success(&self) -> bool {
+        self.outcome.code == ResultCode::Ok
+    }
+
+    /// Find the response of the tx with the given hash among the events of a
+    /// block, e.g. the end block events returned by Tendermint.
+    ///
+    /// The ledger emits the events of a tx right before its own `accepted`
+    /// or `applied` event, which is how they get attributed to it. Events
+    /// about the block as a whole, such as proposal outcomes, are never
+    /// attributed to a tx. Only the events that end up in the response are
+    /// decoded, such that malformed events of other txs are not an error.
+    pub fn from_block_events(
+        hash: &str,
+        events: impl IntoIterator<Item = RawEvent>,
+    ) -> Result<Option<Self>, Error> {
+        let mut pending = vec![];
+        for event in events {
+            match event.kind.parse() {
+                Ok(EventType::Accepted | EventType::Applied) => {
+                    if event.attributes.get("hash").map(String::as_str)
+                        == Some(hash)
+                    {
+                        return Ok(Some(Self {
+                            outcome: (&event).try_into()?,
+                            events: pending
+                                .into_iter()
+                                .map(TypedEvent::try_from)
+                                .collect::<Result<_, _>>()?,
+                        }));
+                    }
+                    pending.clear();
+                }
+                Ok(EventType::Proposal | EventType::PgfPayment) => {}
+                Ok(EventType::Ibc(_) | EventType::Tx(_) | EventType::Fee)
+                | Err(_) => pending.push(event),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Decode the payload of the `applied` shell query, which only holds the
+/// event of the tx itself
+impl TryFrom<Event> for TypedTxResponse {
+    type Error = Error;
+
+    fn try_from(event: Event) -> Result<Self, Self::Error> {
+        let event = RawEvent::from(event);
+        match TypedEvent::try_from(event.clone())? {
+            TypedEvent::Accepted(outcome) | TypedEvent::Applied(outcome) => {
+                Ok(Self {
+                    outcome,
+                    events: vec![],
+                })
+            }
+            _ => Err(event.error("not the event of a tx".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::types::address;
+    use namada_core::types::address::testing::established_address_1;
+
+    use super::*;
+
+    /// Build a raw event from the given attributes
+    fn raw_event(kind: &str, attributes: &[(&str, &str)]) -> RawEvent {
+        RawEvent {
+            kind: kind.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Build the event of a tx with the given hash and result code
+    fn tx_event(kind: &str, hash: &str, code: &str) -> RawEvent {
+        raw_event(
+            kind,
+            &[
+                ("hash", hash),
+                ("height", "3"),
+                ("code", code),
+                ("gas_used", "42"),
+                ("info", "Transaction is valid."),
+                ("log", ""),
+            ],
+        )
+    }
+
+    /// Test decoding the `accepted` and `applied` events of txs
+    #[test]
+    fn test_decode_tx_events() {
+        let event = tx_event("accepted", "wrapper", "0");
+        let expected = TxOutcome {
+            hash: "wrapper".to_string(),
+            height: BlockHeight(3),
+            code: ResultCode::Ok,
+            gas_used: 42,
+            info: "Transaction is valid.".to_string(),
+            log: String::new(),
+            initialized_accounts: vec![],
+        };
+        assert_eq!(
+            TypedEvent::try_from(event).unwrap(),
+            TypedEvent::Accepted(expected.clone())
+        );
+
+        let address = established_address_1();
+        let mut event = tx_event("applied", "inner", "2");
+        event.attributes.insert(
+            "initialized_accounts".to_string(),
+            serde_json::to_string(&vec![address.clone()]).unwrap(),
+        );
+        let expected = TxOutcome {
+            hash: "inner".to_string(),
+            code: ResultCode::InvalidTx,
+            initialized_accounts: vec![address],
+            ..expected
+        };
+        assert_eq!(
+            TypedEvent::try_from(event).unwrap(),
+            TypedEvent::Applied(expected)
+        );
+
+        let event = tx_event("applied", "inner", "9000");
+        assert!(TypedEvent::try_from(event).is_err());
+    }
+
+    /// Test decoding the events of proposals and PGF payments
+    #[test]
+    fn test_decode_block_events() {
+        let event = raw_event(
+            "proposal",
+            &[
+                ("tally_result", "passed"),
+                ("proposal_id", "7"),
+                ("has_proposal_code", "0"),
+                ("proposal_code_exit_status", "1"),
+            ],
+        );
+        assert_eq!(
+            TypedEvent::try_from(event).unwrap(),
+            TypedEvent::Proposal(ProposalOutcome {
+                proposal_id: 7,
+                passed: true,
+                has_proposal_code: true,
+                execution_succeeded: false,
+            })
+        );
+
+        let target = established_address_1();
+        let amount = token::Amount::native_whole(5);
+        let event = raw_event(
+            "pgf_payment",
+            &[
+                ("target", target.to_string().as_str()),
+                ("amount", amount.to_string_native().as_str()),
+                ("is_steward", "true"),
+                ("successed", "true"),
+            ],
+        );
+        assert_eq!(
+            TypedEvent::try_from(event).unwrap(),
+            TypedEvent::PgfPayment(PgfPayment {
+                target,
+                amount,
+                is_steward: true,
+                success: true,
+            })
+        );
+    }
+
+    /// Test that IBC and tx events are kept as is and that unknown event
+    /// types are tolerated
+    #[test]
+    fn test_decode_ibc_tx_and_unknown_events() {
+        let event = raw_event("send_packet", &[("packet_sequence", "1")]);
+        assert_eq!(
+            TypedEvent::try_from(event.clone()).unwrap(),
+            TypedEvent::Ibc(IbcEvent {
+                event_type: "send_packet".to_string(),
+                attributes: event.attributes,
+            })
+        );
+
+        let event = raw_event("transfer", &[("amount", "not a number")]);
+        assert_eq!(
+            TypedEvent::try_from(event).unwrap(),
+            TypedEvent::Tx(
+                TxEvent::new("transfer").with("amount", "not a number")
+            )
+        );
+
+        let event = raw_event("unbond", &[("amount", "not a number")]);
+        assert_eq!(
+            TypedEvent::try_from(event.clone()).unwrap(),
+            TypedEvent::Unknown(event)
+        );
+    }
+
+    /// Test that ABCI events with byte attributes and ABCI++ events with
+    /// string attributes decode to the same event
+    #[test]
+    fn test_decode_abci_attribute_encodings() {
+        let attributes = [("hash", "inner"), ("height", "3"), ("code", "0")];
+        let abci_event = v0_34::abci::Event {
+            r#type: "applied".to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| v0_34::abci::EventAttribute {
+                    key: key.as_bytes().to_vec().into(),
+                    value: value.as_bytes().to_vec().into(),
+                    index: true,
+                })
+                .collect(),
+        };
+        let abcipp_event = v0_37::abci::Event {
+            r#type: "applied".to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| v0_37::abci::EventAttribute {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    index: true,
+                })
+                .collect(),
+        };
+        let from_bytes = RawEvent::try_from(abci_event).unwrap();
+        let from_strings = RawEvent::from(abcipp_event);
+        assert_eq!(from_bytes, from_strings);
+        assert_eq!(from_bytes, raw_event("applied", &attributes));
+
+        let invalid_event = v0_34::abci::Event {
+            r#type: "applied".to_string(),
+            attributes: vec![v0_34::abci::EventAttribute {
+                key: b"hash".to_vec().into(),
+                value: vec![0xff, 0xfe].into(),
+                index: true,
+            }],
+        };
+        assert!(RawEvent::try_from(invalid_event).is_err());
+    }
+
+    /// Test that the events of a tx are attributed to it among the events
+    /// of a block
+    #[test]
+    fn test_response_from_block_events() {
+        let ibc_event = raw_event("send_packet", &[("packet_sequence", "1")]);
+        let unknown_event = raw_event("unbond", &[]);
+        let payer = established_address_1();
+        let token = address::nam();
+        let fee_event = raw_event(
+            "fee",
+            &[
+                ("hash", "third"),
+                ("height", "3"),
+                ("payer", payer.to_string().as_str()),
+                ("token", token.to_string().as_str()),
+                ("amount", "25000"),
+            ],
+        );
+        let events = vec![
+            raw_event("proposal", &[]),
+            ibc_event.clone(),
+            tx_event("applied", "first", "0"),
+            unknown_event.clone(),
+            ibc_event.clone(),
+            tx_event("applied", "second", "0"),
+            fee_event,
+            tx_event("accepted", "third", "0"),
+        ];
+
+        let response =
+            TypedTxResponse::from_block_events("second", events.clone())
+                .unwrap()
+                .unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.outcome.hash, "second");
+        assert_eq!(
+            response.events,
+            vec![
+                TypedEvent::Unknown(unknown_event),
+                TypedEvent::try_from(ibc_event).unwrap(),
+            ]
+        );
+
+        let response =
+            TypedTxResponse::from_block_events("third", events.clone())
+                .unwrap()
+                .unwrap();
+        assert_eq!(
+            response.events,
+            vec![TypedEvent::Fee(FeePayment {
+                hash: "third".to_string(),
+                height: BlockHeight(3),
+                payer,
+                token,
+                amount: token::Amount::from(25_000),
+            })]
+        );
+
+        assert!(
+            TypedTxResponse::from_block_events("missing", events)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -60,6 +60,30 @@ impl QueryMatcher {
         }
     }
 
+    /// Returns a query matching the applied transaction whose wrapper was
+    /// broadcast with the given CometBFT hash.
+    pub fn applied_by_tendermint_hash(tendermint_hash: Hash) -> Self {
+        let mut attributes = HashMap::new();
+        attributes
+            .insert("tendermint_hash".to_string(), tendermint_hash.to_string());
+        Self {
+            event_type: EventType::Applied,
+            attributes,
+        }
+    }
+
+    /// Returns a query matching the accepted wrapper transaction that was
+    /// broadcast with the given CometBFT hash.
+    pub fn accepted_by_tendermint_hash(tendermint_hash: Hash) -> Self {
+        let mut attributes = HashMap::new();
+        attributes
+            .insert("tendermint_hash".to_string(), tendermint_hash.to_string());
+        Self {
+            event_type: EventType::Accepted,
+            attributes,
+        }
+    }
+
     /// Returns a query matching the given IBC UpdateClient parameters
     pub fn ibc_update_client(
         client_id: ClientId,
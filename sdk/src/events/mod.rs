@@ -1,5 +1,7 @@
 //! Logic to do with events emitted by the ledger.
+pub mod decode;
 pub mod log;
+pub mod subscription;
 
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -8,7 +10,12 @@ use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::types::address::Address;
+use namada_core::types::event::{
+    TxEvent, INIT_ACCOUNT_EVENT, TRANSFER_EVENT, VP_UPDATE_EVENT,
+};
 use namada_core::types::ibc::IbcEvent;
+use namada_core::types::token::Amount;
 use namada_core::types::transaction::TxType;
 use serde_json::Value;
 
@@ -48,10 +55,14 @@ pub enum EventType {
     Applied,
     /// The IBC transaction was applied during block finalization
     Ibc(String),
+    /// An event emitted by an applied transaction
+    Tx(String),
     /// The proposal that has been executed
     Proposal,
     /// The pgf payment
     PgfPayment,
+    /// The fee charged to a wrapper transaction
+    Fee,
 }
 
 impl Display for EventType {
@@ -60,8 +71,10 @@ impl Display for EventType {
             EventType::Accepted => write!(f, "accepted"),
             EventType::Applied => write!(f, "applied"),
             EventType::Ibc(t) => write!(f, "{}", t),
+            EventType::Tx(t) => write!(f, "{}", t),
             EventType::Proposal => write!(f, "proposal"),
             EventType::PgfPayment => write!(f, "pgf_payment"),
+            EventType::Fee => write!(f, "fee"),
         }?;
         Ok(())
     }
@@ -75,13 +88,18 @@ impl FromStr for EventType {
             "accepted" => Ok(EventType::Accepted),
             "applied" => Ok(EventType::Applied),
             "proposal" => Ok(EventType::Proposal),
-            "pgf_payments" => Ok(EventType::PgfPayment),
+            "pgf_payment" => Ok(EventType::PgfPayment),
+            "fee" => Ok(EventType::Fee),
             // IBC
             "update_client" => Ok(EventType::Ibc("update_client".to_string())),
             "send_packet" => Ok(EventType::Ibc("send_packet".to_string())),
             "write_acknowledgement" => {
                 Ok(EventType::Ibc("write_acknowledgement".to_string()))
             }
+            // Tx
+            TRANSFER_EVENT | INIT_ACCOUNT_EVENT | VP_UPDATE_EVENT => {
+                Ok(EventType::Tx(s.to_string()))
+            }
             _ => Err(EventError::InvalidEventType),
         }
     }
@@ -130,6 +148,28 @@ impl Event {
         event
     }
 
+    /// Creates a new event for the fee charged to a wrapper transaction, with
+    /// the hash and height of its own event. The amount is given in the
+    /// smallest unit of the token.
+    pub fn new_fee_event(
+        wrapper_event: &Event,
+        payer: &Address,
+        token: &Address,
+        amount: Amount,
+    ) -> Self {
+        let mut event = Event {
+            event_type: EventType::Fee,
+            level: EventLevel::Tx,
+            attributes: HashMap::new(),
+        };
+        event["hash"] = wrapper_event["hash"].clone();
+        event["height"] = wrapper_event["height"].clone();
+        event["payer"] = payer.to_string();
+        event["token"] = token.to_string();
+        event["amount"] = amount.raw_amount().to_string();
+        event
+    }
+
     /// Check if the events keys contains a given string
     pub fn contains_key(&self, key: &str) -> bool {
         self.attributes.contains_key(key)
@@ -169,6 +209,16 @@ impl From<IbcEvent> for Event {
     }
 }
 
+impl From<TxEvent> for Event {
+    fn from(tx_event: TxEvent) -> Self {
+        Self {
+            event_type: EventType::Tx(tx_event.event_type),
+            level: EventLevel::Tx,
+            attributes: tx_event.attributes.into_iter().collect(),
+        }
+    }
+}
+
 /// Convert our custom event into the necessary tendermint proto type
 impl From<Event> for crate::tendermint_proto::v0_37::abci::Event {
     fn from(event: Event) -> Self {
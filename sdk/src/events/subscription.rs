@@ -0,0 +1,824 @@
+//! Push-based subscription to the events emitted by the ledger.
+//!
+//! A [`SubscriptionDriver`] receives the events of new blocks from a
+//! [`BlockEventSource`], such as Tendermint's websocket `subscribe` endpoint,
+//! and forwards the ones selected by the filters of the subscription to an
+//! [`EventSubscription`], decoded as [`TypedEvent`]s. When the connection
+//! drops, the driver reconnects and catches up with the blocks committed in
+//! the meantime by querying them, so that no event is missed nor delivered
+//! twice.
+//!
+//! The events are buffered in a bounded queue between the driver and the
+//! subscription. What happens when a slow consumer lets the queue fill up is
+//! set by the [`OverflowPolicy`] of the subscription.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use futures::{Stream, StreamExt};
+use namada_core::types::address::Address;
+use namada_core::types::hash::Hash;
+use namada_core::types::storage::BlockHeight;
+use tokio::sync::Notify;
+
+use crate::control_flow::time::{self, Duration};
+use crate::error::{Error, EventError};
+use crate::events::decode::{RawEvent, TypedEvent};
+use crate::events::EventType;
+
+/// The events emitted by the ledger in a committed block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEvents {
+    /// The height of the block
+    pub height: BlockHeight,
+    /// The events of the block, in the order in which they were emitted
+    pub events: Vec<RawEvent>,
+}
+
+/// A stream of the events of new blocks, which ends when the connection
+/// drops
+pub type BlockEventStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<BlockEvents, Error>> + 'a>>;
+
+/// A source of the events of committed blocks
+#[async_trait::async_trait(?Send)]
+pub trait BlockEventSource {
+    /// Open a new connection streaming the events of new blocks
+    async fn subscribe(&self) -> Result<BlockEventStream<'_>, Error>;
+
+    /// Query the events of the block committed at the given height
+    async fn block_events(
+        &self,
+        height: BlockHeight,
+    ) -> Result<BlockEvents, Error>;
+
+    /// Query the height of the last committed block
+    async fn latest_height(&self) -> Result<BlockHeight, Error>;
+}
+
+/// Selects the events delivered by a subscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventFilter {
+    /// The events of the given type
+    Type(EventType),
+    /// The events with an attribute holding the given address, e.g. the
+    /// sender or receiver of a transfer
+    Address(Address),
+    /// The `applied` event of the inner tx with the given hash, along with
+    /// the events emitted by this tx
+    InnerTxHash(Hash),
+}
+
+/// What to do with new events when the queue of a subscription is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued event to make room for the new one. The
+    /// number of dropped events is reported by
+    /// [`EventSubscription::dropped`].
+    DropOldest,
+    /// End the subscription with an error, once the queued events have been
+    /// received
+    Error,
+}
+
+/// The configuration of a subscription
+#[derive(Debug, Clone)]
+pub struct SubscriptionConfig {
+    /// The filters selecting the delivered events. An event is delivered if
+    /// it matches any of them, or if there are none.
+    pub filters: Vec<EventFilter>,
+    /// The maximum number of events queued for the subscription, at least
+    /// one
+    pub capacity: usize,
+    /// What to do with new events when the queue is full
+    pub overflow: OverflowPolicy,
+    /// The height of the last block whose events were already seen. The
+    /// events of the blocks committed since are delivered first.
+    pub last_seen_height: Option<BlockHeight>,
+    /// The delay before reconnecting after the connection dropped
+    pub reconnect_delay: Duration,
+    /// The number of consecutive failed connections after which the
+    /// subscription ends with the last error
+    pub max_reconnect_attempts: u32,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            filters: vec![],
+            capacity: 1024,
+            overflow: OverflowPolicy::Error,
+            last_seen_height: None,
+            reconnect_delay: Duration::from_secs(1),
+            max_reconnect_attempts: 10,
+        }
+    }
+}
+
+/// An event delivered by a subscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscribedEvent {
+    /// The height of the block that emitted the event
+    pub height: BlockHeight,
+    /// The decoded event
+    pub event: TypedEvent,
+}
+
+/// The state of the queue between a driver and its subscription
+#[derive(Debug, Default)]
+struct QueueState {
+    items: VecDeque<Result<SubscribedEvent, Error>>,
+    dropped: u64,
+    /// The driver stopped, possibly with an error to deliver last
+    finished: bool,
+    error: Option<Error>,
+    /// The subscription was dropped, so the driver must stop
+    closed: bool,
+}
+
+/// A bounded queue with a single producer and a single consumer
+#[derive(Debug)]
+struct Queue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    capacity: usize,
+    overflow: OverflowPolicy,
+}
+
+impl Queue {
+    /// Queue the given item. Returns `false` if no more items can be queued.
+    fn push(&self, item: Result<SubscribedEvent, Error>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed || state.finished {
+            return false;
+        }
+        if state.items.len() >= self.capacity {
+            match self.overflow {
+                OverflowPolicy::DropOldest => {
+                    state.items.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Error => {
+                    state.finished = true;
+                    state.error = Some(Error::from(
+                        EventError::SubscriptionOverflow(self.capacity),
+                    ));
+                    self.notify.notify_one();
+                    return false;
+                }
+            }
+        }
+        state.items.push_back(item);
+        self.notify.notify_one();
+        true
+    }
+
+    /// Stop queueing items, delivering the given error after the queued ones
+    fn finish(&self, error: Option<Error>) {
+        let mut state = self.state.lock().unwrap();
+        if !state.finished {
+            state.finished = true;
+            state.error = error;
+        }
+        self.notify.notify_one();
+    }
+}
+
+/// The receiving end of a subscription to the events emitted by the ledger.
+/// It only receives events while its [`SubscriptionDriver`] runs.
+#[derive(Debug)]
+pub struct EventSubscription {
+    queue: Arc<Queue>,
+}
+
+impl EventSubscription {
+    /// Receive the next event of the subscription. Events that fail to decode
+    /// are received as errors without ending the subscription. Returns `None`
+    /// once the driver stopped, right after the error that stopped it if any.
+    pub async fn recv(&mut self) -> Option<Result<SubscribedEvent, Error>> {
+        loop {
+            let notified = self.queue.notify.notified();
+            {
+                let mut state = self.queue.state.lock().unwrap();
+                if let Some(item) = state.items.pop_front() {
+                    return Some(item);
+                }
+                if state.finished {
+                    return state.error.take().map(Err);
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// The number of events dropped under [`OverflowPolicy::DropOldest`]
+    pub fn dropped(&self) -> u64 {
+        self.queue.state.lock().unwrap().dropped
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().closed = true;
+    }
+}
+
+/// Drives a subscription by forwarding the events received from a source,
+/// reconnecting to it as needed
+pub struct SubscriptionDriver<S> {
+    source: S,
+    config: SubscriptionConfig,
+    queue: Arc<Queue>,
+    last_height: Option<BlockHeight>,
+    failed_attempts: u32,
+}
+
+/// Subscribe to the events received from the given source. The events are
+/// only received while the returned driver runs, e.g. in a task of its own.
+pub fn subscribe<S: BlockEventSource>(
+    source: S,
+    config: SubscriptionConfig,
+) -> (EventSubscription, SubscriptionDriver<S>) {
+    let queue = Arc::new(Queue {
+        state: Mutex::new(QueueState::default()),
+        notify: Notify::new(),
+        capacity: config.capacity.max(1),
+        overflow: config.overflow,
+    });
+    let subscription = EventSubscription {
+        queue: queue.clone(),
+    };
+    let driver = SubscriptionDriver {
+        source,
+        last_height: config.last_seen_height,
+        config,
+        queue,
+        failed_attempts: 0,
+    };
+    (subscription, driver)
+}
+
+impl<S: BlockEventSource> SubscriptionDriver<S> {
+    /// Forward the events of new blocks until the subscription is dropped,
+    /// its queue overflows under [`OverflowPolicy::Error`], or the source
+    /// can't be reconnected to. The error that stopped the driver, if any,
+    /// is delivered to the subscription.
+    pub async fn run(mut self) {
+        loop {
+            let err = match self.connect().await {
+                Ok(()) => return,
+                Err(err) => err,
+            };
+            self.failed_attempts += 1;
+            if self.failed_attempts > self.config.max_reconnect_attempts {
+                self.queue.finish(Some(err));
+                return;
+            }
+            tracing::debug!(
+                error = %err,
+                attempt = self.failed_attempts,
+                "Event subscription disconnected, reconnecting"
+            );
+            time::sleep(self.config.reconnect_delay).await;
+        }
+    }
+
+    /// Forward the events of the blocks missed since the last seen one and
+    /// of the new blocks received over a new connection. Returns an error
+    /// when the connection drops, or `Ok` once no more events can be queued.
+    async fn connect(&mut self) -> Result<(), Error> {
+        // Subscribe before catching up, such that the blocks committed in
+        // between are received at least once
+        let mut stream = self.source.subscribe().await?;
+        if let Some(last_height) = self.last_height {
+            let latest_height = self.source.latest_height().await?;
+            for height in (last_height.0 + 1)..=latest_height.0 {
+                let block =
+                    self.source.block_events(BlockHeight(height)).await?;
+                if !self.forward(block) {
+                    return Ok(());
+                }
+            }
+        }
+        while let Some(block) = stream.next().await {
+            let block = block?;
+            if self.last_height.map_or(false, |last| block.height <= last) {
+                continue;
+            }
+            if !self.forward(block) {
+                return Ok(());
+            }
+        }
+        Err(Error::from(EventError::Subscription(
+            "the connection was closed".to_string(),
+        )))
+    }
+
+    /// Forward the selected events of the given block. Returns `false` if no
+    /// more events can be queued.
+    fn forward(&mut self, block: BlockEvents) -> bool {
+        self.failed_attempts = 0;
+        self.last_height = Some(block.height);
+        for event in select_events(&self.config.filters, block.events) {
+            let item =
+                TypedEvent::try_from(event).map(|event| SubscribedEvent {
+                    height: block.height,
+                    event,
+                });
+            if !self.queue.push(item) {
+                return false;
+            }
+        }
+        // Report the closing of the subscription even if the block had no
+        // selected events
+        !self.queue.state.lock().unwrap().closed
+    }
+}
+
+/// Select the events of a block matching any of the given filters, or all
+/// of them if there are no filters
+fn select_events(
+    filters: &[EventFilter],
+    events: Vec<RawEvent>,
+) -> Vec<RawEvent> {
+    if filters.is_empty() {
+        return events;
+    }
+    // The ledger emits the events of a tx right before its own `accepted` or
+    // `applied` event, see `TypedTxResponse::from_block_events`
+    let mut owner = None;
+    let mut owners = vec![None; events.len()];
+    for (index, event) in events.iter().enumerate().rev() {
+        match event.kind.parse() {
+            Ok(EventType::Accepted | EventType::Applied) => {
+                owner = event.attributes.get("hash").cloned();
+                owners[index] = owner.clone();
+            }
+            Ok(EventType::Proposal | EventType::PgfPayment) => {}
+            Ok(EventType::Ibc(_) | EventType::Tx(_) | EventType::Fee)
+            | Err(_) => {
+                owners[index] = owner.clone();
+            }
+        }
+    }
+    events
+        .into_iter()
+        .zip(owners)
+        .filter(|(event, owner)| {
+            filters.iter().any(|filter| match filter {
+                EventFilter::Type(event_type) => {
+                    event.kind == event_type.to_string()
+                }
+                EventFilter::Address(address) => {
+                    let address = address.encode();
+                    event
+                        .attributes
+                        .values()
+                        .any(|value| value.contains(&address))
+                }
+                EventFilter::InnerTxHash(hash) => {
+                    let hash = hash.to_string();
+                    event.kind != EventType::Accepted.to_string()
+                        && owner.as_deref().map_or(false, |owner| {
+                            owner.eq_ignore_ascii_case(&hash)
+                        })
+                }
+            })
+        })
+        .map(|(event, _)| event)
+        .collect()
+}
+
+#[cfg(feature = "websocket-client")]
+pub use websocket::WebSocketEventSource;
+
+#[cfg(feature = "websocket-client")]
+mod websocket {
+    use super::*;
+    use crate::rpc;
+    use crate::tendermint_rpc::event::{Event, EventData};
+    use crate::tendermint_rpc::query::EventType as TendermintEventType;
+    use crate::tendermint_rpc::{
+        SubscriptionClient, WebSocketClient, WebSocketClientUrl,
+    };
+
+    /// Receives the events of new blocks from Tendermint's websocket
+    /// `subscribe` endpoint, and queries the events of the missed blocks with
+    /// an RPC client
+    pub struct WebSocketEventSource<'a, C> {
+        url: WebSocketClientUrl,
+        client: &'a C,
+    }
+
+    impl<'a, C> WebSocketEventSource<'a, C> {
+        /// Connect to the websocket endpoint at the given URL, and query
+        /// missed blocks with the given client
+        pub fn new(url: WebSocketClientUrl, client: &'a C) -> Self {
+            Self { url, client }
+        }
+    }
+
+    /// Convert an error of the websocket client
+    fn subscription_error(err: crate::tendermint_rpc::Error) -> Error {
+        Error::from(EventError::Subscription(err.to_string()))
+    }
+
+    /// Get the events of a new block notified by Tendermint
+    fn new_block_events(event: Event) -> Result<BlockEvents, Error> {
+        match event.data {
+            EventData::NewBlock {
+                block: Some(block),
+                result_begin_block,
+                result_end_block,
+            } => Ok(BlockEvents {
+                height: BlockHeight(block.header.height.value()),
+                events: result_begin_block
+                    .into_iter()
+                    .flat_map(|result| result.events)
+                    .chain(
+                        result_end_block
+                            .into_iter()
+                            .flat_map(|result| result.events),
+                    )
+                    .map(RawEvent::from)
+                    .collect(),
+            }),
+            _ => Err(Error::from(EventError::Subscription(format!(
+                "unexpected event for the query {}",
+                event.query
+            )))),
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl<'a, C> BlockEventSource for WebSocketEventSource<'a, C>
+    where
+        C: crate::queries::Client + Sync,
+    {
+        async fn subscribe(&self) -> Result<BlockEventStream<'_>, Error> {
+            let (ws_client, driver) = WebSocketClient::new(self.url.clone())
+                .await
+                .map_err(subscription_error)?;
+            tokio::spawn(async move {
+                if let Err(err) = driver.run().await {
+                    tracing::debug!(
+                        error = %err,
+                        "Websocket connection closed"
+                    );
+                }
+            });
+            let subscription = ws_client
+                .subscribe(TendermintEventType::NewBlock.into())
+                .await
+                .map_err(subscription_error)?;
+            // The client is kept alive along with its subscription, and
+            // closes the connection once dropped
+            Ok(Box::pin(subscription.map(move |event| {
+                let _ = &ws_client;
+                event.map_err(subscription_error).and_then(new_block_events)
+            })))
+        }
+
+        async fn block_events(
+            &self,
+            height: BlockHeight,
+        ) -> Result<BlockEvents, Error> {
+            let tm_height = crate::tendermint::block::Height::try_from(
+                height.0,
+            )
+            .map_err(|err| {
+                Error::from(EventError::Subscription(err.to_string()))
+            })?;
+            let results = self
+                .client
+                .block_results(tm_height)
+                .await
+                .map_err(subscription_error)?;
+            Ok(BlockEvents {
+                height,
+                events: results
+                    .begin_block_events
+                    .into_iter()
+                    .flatten()
+                    .chain(results.end_block_events.into_iter().flatten())
+                    .map(RawEvent::from)
+                    .collect(),
+            })
+        }
+
+        async fn latest_height(&self) -> Result<BlockHeight, Error> {
+            let last_block = rpc::query_block(self.client).await?;
+            Ok(last_block.map_or(BlockHeight(0), |block| block.height))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    use assert_matches::assert_matches;
+    use futures::stream;
+    use namada_core::types::address::testing::established_address_1;
+
+    use super::*;
+
+    /// A mocked websocket server replaying recorded frames
+    struct MockServer {
+        /// The frames sent over each successive connection, which is closed
+        /// after its last frame. Connections are refused once there are no
+        /// more.
+        connections: Rc<RefCell<VecDeque<Vec<Result<BlockEvents, Error>>>>>,
+        /// The committed blocks
+        blocks: BTreeMap<BlockHeight, BlockEvents>,
+    }
+
+    impl MockServer {
+        /// A server with the given committed blocks and connections, whose
+        /// frames are the blocks at the given heights
+        fn new(blocks: Vec<BlockEvents>, connections: &[&[u64]]) -> Self {
+            let blocks: BTreeMap<_, _> = blocks
+                .into_iter()
+                .map(|block| (block.height, block))
+                .collect();
+            let connections = connections
+                .iter()
+                .map(|heights| {
+                    heights
+                        .iter()
+                        .map(|height| Ok(blocks[&BlockHeight(*height)].clone()))
+                        .collect()
+                })
+                .collect();
+            Self {
+                connections: Rc::new(RefCell::new(connections)),
+                blocks,
+            }
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl BlockEventSource for MockServer {
+        async fn subscribe(&self) -> Result<BlockEventStream<'_>, Error> {
+            let frames =
+                self.connections.borrow_mut().pop_front().ok_or_else(|| {
+                    Error::from(EventError::Subscription(
+                        "connection refused".to_string(),
+                    ))
+                })?;
+            Ok(Box::pin(stream::iter(frames)))
+        }
+
+        async fn block_events(
+            &self,
+            height: BlockHeight,
+        ) -> Result<BlockEvents, Error> {
+            self.blocks.get(&height).cloned().ok_or_else(|| {
+                Error::from(EventError::Subscription(format!(
+                    "no block at height {height}"
+                )))
+            })
+        }
+
+        async fn latest_height(&self) -> Result<BlockHeight, Error> {
+            Ok(self.blocks.keys().last().copied().unwrap_or(BlockHeight(0)))
+        }
+    }
+
+    /// Build a raw event from the given attributes
+    fn raw_event(kind: &str, attributes: &[(&str, &str)]) -> RawEvent {
+        RawEvent {
+            kind: kind.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Build the event of a tx with the given hash
+    fn tx_event(kind: &str, hash: &Hash, height: u64) -> RawEvent {
+        raw_event(
+            kind,
+            &[
+                ("hash", &hash.to_string()),
+                ("height", &height.to_string()),
+                ("code", "0"),
+                ("gas_used", "42"),
+                ("info", "Transaction is valid."),
+                ("log", ""),
+            ],
+        )
+    }
+
+    /// A block whose single event is the `applied` event of a tx
+    fn applied_block(height: u64) -> BlockEvents {
+        let hash = Hash::sha256(height.to_le_bytes());
+        BlockEvents {
+            height: BlockHeight(height),
+            events: vec![tx_event("applied", &hash, height)],
+        }
+    }
+
+    /// Receive all the events of a subscription until it ends
+    async fn receive_all(
+        mut subscription: EventSubscription,
+    ) -> Vec<Result<SubscribedEvent, Error>> {
+        let mut received = vec![];
+        while let Some(event) = subscription.recv().await {
+            received.push(event);
+        }
+        received
+    }
+
+    /// The heights of the received events, up to the first error
+    fn heights(received: &[Result<SubscribedEvent, Error>]) -> Vec<u64> {
+        received
+            .iter()
+            .map_while(|event| event.as_ref().ok())
+            .map(|event| event.height.0)
+            .collect()
+    }
+
+    /// A configuration that reconnects right away
+    fn config() -> SubscriptionConfig {
+        SubscriptionConfig {
+            reconnect_delay: Duration::from_millis(0),
+            max_reconnect_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Test that the blocks committed while disconnected are queried after
+    /// reconnecting, and that no event is delivered twice
+    #[tokio::test]
+    async fn test_reconnect_resumes_from_last_seen_height() {
+        let blocks = (1..=5).map(applied_block).collect();
+        let server = MockServer::new(blocks, &[&[1, 2], &[5]]);
+        let (subscription, driver) = subscribe(server, config());
+        driver.run().await;
+
+        let received = receive_all(subscription).await;
+        assert_eq!(heights(&received), vec![1, 2, 3, 4, 5]);
+        // The subscription ends once the server refuses connections
+        assert_eq!(received.len(), 6);
+        assert_matches!(
+            received.last(),
+            Some(Err(Error::Event(EventError::Subscription(_))))
+        );
+    }
+
+    /// Test that a subscription starts with the blocks committed since the
+    /// configured last seen height
+    #[tokio::test]
+    async fn test_resume_from_configured_height() {
+        let blocks = (1..=5).map(applied_block).collect();
+        let server = MockServer::new(blocks, &[&[5]]);
+        let config = SubscriptionConfig {
+            last_seen_height: Some(BlockHeight(3)),
+            ..config()
+        };
+        let (subscription, driver) = subscribe(server, config);
+        driver.run().await;
+
+        let received = receive_all(subscription).await;
+        assert_eq!(heights(&received), vec![4, 5]);
+    }
+
+    /// Test the selection of events by type, address and inner tx hash
+    #[test]
+    fn test_filter_matching() {
+        let address = established_address_1();
+        let inner_hash = Hash::sha256(b"inner");
+        let other_hash = Hash::sha256(b"other");
+        let packet = raw_event(
+            "send_packet",
+            &[(
+                "packet_data",
+                &format!("{{\"sender\":\"{}\"}}", address.encode()),
+            )],
+        );
+        let inner = tx_event("applied", &inner_hash, 1);
+        let wrapper = tx_event("accepted", &Hash::sha256(b"wrapper"), 1);
+        let other = tx_event("applied", &other_hash, 1);
+        let events = vec![
+            packet.clone(),
+            inner.clone(),
+            wrapper.clone(),
+            other.clone(),
+        ];
+
+        let select =
+            |filters: &[EventFilter]| select_events(filters, events.clone());
+        assert_eq!(select(&[]), events);
+        assert_eq!(
+            select(&[EventFilter::InnerTxHash(inner_hash)]),
+            vec![packet.clone(), inner.clone()]
+        );
+        assert_eq!(
+            select(&[EventFilter::Address(address)]),
+            vec![packet]
+        );
+        assert_eq!(
+            select(&[EventFilter::Type(EventType::Applied)]),
+            vec![inner, other.clone()]
+        );
+        // An event is selected if it matches any filter
+        assert_eq!(
+            select(&[
+                EventFilter::Type(EventType::Accepted),
+                EventFilter::InnerTxHash(other_hash),
+            ]),
+            vec![wrapper, other]
+        );
+        assert!(
+            select(&[EventFilter::InnerTxHash(Hash::sha256(b"unknown"))])
+                .is_empty()
+        );
+    }
+
+    /// Test that the events selected by the filters of a subscription are
+    /// delivered decoded
+    #[tokio::test]
+    async fn test_subscription_filters() {
+        let inner_hash = Hash::sha256(b"inner");
+        let block = BlockEvents {
+            height: BlockHeight(1),
+            events: vec![
+                tx_event("applied", &Hash::sha256(b"other"), 1),
+                tx_event("applied", &inner_hash, 1),
+            ],
+        };
+        let server = MockServer::new(vec![block], &[&[1]]);
+        let config = SubscriptionConfig {
+            filters: vec![EventFilter::InnerTxHash(inner_hash)],
+            ..config()
+        };
+        let (subscription, driver) = subscribe(server, config);
+        driver.run().await;
+
+        let received = receive_all(subscription).await;
+        assert_matches!(
+            &received[0],
+            Ok(SubscribedEvent {
+                height: BlockHeight(1),
+                event: TypedEvent::Applied(outcome),
+            }) if outcome.hash == inner_hash.to_string()
+        );
+        assert_matches!(
+            &received[1..],
+            [Err(Error::Event(EventError::Subscription(_)))]
+        );
+    }
+
+    /// Test that a full queue either drops its oldest events or ends the
+    /// subscription with an error, according to its overflow policy
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        let blocks: Vec<_> = (1..=3).map(applied_block).collect();
+
+        let server = MockServer::new(blocks.clone(), &[&[1, 2, 3]]);
+        let config = SubscriptionConfig {
+            capacity: 2,
+            overflow: OverflowPolicy::DropOldest,
+            ..config()
+        };
+        let (subscription, driver) = subscribe(server, config);
+        driver.run().await;
+        assert_eq!(subscription.dropped(), 1);
+        let received = receive_all(subscription).await;
+        assert_eq!(heights(&received), vec![2, 3]);
+
+        let server = MockServer::new(blocks, &[&[1, 2, 3]]);
+        let config = SubscriptionConfig {
+            capacity: 2,
+            overflow: OverflowPolicy::Error,
+            ..config()
+        };
+        let (subscription, driver) = subscribe(server, config);
+        driver.run().await;
+        let received = receive_all(subscription).await;
+        assert_eq!(heights(&received), vec![1, 2]);
+        assert_eq!(received.len(), 3);
+        assert_matches!(
+            received.last(),
+            Some(Err(Error::Event(EventError::SubscriptionOverflow(2))))
+        );
+    }
+
+    /// Test that the driver stops once its subscription is dropped
+    #[tokio::test]
+    async fn test_driver_stops_with_subscription() {
+        let blocks = (1..=2).map(applied_block).collect();
+        let server = MockServer::new(blocks, &[&[1], &[2]]);
+        let connections = server.connections.clone();
+        let (subscription, driver) = subscribe(server, config());
+        drop(subscription);
+        driver.run().await;
+        // The driver didn't reconnect after forwarding the first block
+        assert_eq!(connections.borrow().len(), 1);
+    }
+}
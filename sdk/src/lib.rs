@@ -15,6 +15,7 @@ pub mod rpc;
 
 pub mod args;
 pub mod masp;
+pub mod signer;
 pub mod signing;
 #[allow(clippy::result_large_err)]
 pub mod tx;
@@ -48,6 +49,7 @@ use crate::masp::{ShieldedContext, ShieldedUtils};
 use crate::proto::Tx;
 use crate::rpc::{
     denominate_amount, format_denominated_amount, query_native_token,
+    CodeHashCache, TxChainContextCache,
 };
 use crate::signing::SigningTxData;
 use crate::token::DenominatedAmount;
@@ -106,6 +108,13 @@ pub trait Namada<'a>: Sized {
     /// Return the native token
     fn native_token(&self) -> Address;
 
+    /// Obtain the cache of the wasm code hashes stored on chain
+    fn code_hashes(&self) -> &CodeHashCache;
+
+    /// Obtain the cache of the parameters of the connected chain that fill in
+    /// the headers of new txs
+    fn chain_context(&self) -> &TxChainContextCache;
+
     /// Make a tx builder using no arguments
     fn tx_builder(&self) -> args::Tx {
         args::Tx {
@@ -132,6 +141,10 @@ pub trait Namada<'a>: Sized {
             verification_key: None,
             password: None,
             use_device: false,
+            confirm_signing: false,
+            offline: false,
+            embed_missing_code: false,
+            wasm_dir: None,
         }
     }
 
@@ -151,6 +164,7 @@ pub trait Namada<'a>: Sized {
             tx_code_path: PathBuf::from(TX_TRANSFER_WASM),
             tx: self.tx_builder(),
             native_token: self.native_token(),
+            memo: None,
         }
     }
 
@@ -556,6 +570,10 @@ where
     native_token: Address,
     /// The default builder for a Tx
     prototype: args::Tx,
+    /// The hashes of the wasm codes stored on chain
+    code_hashes: CodeHashCache,
+    /// The parameters of the connected chain that fill in new tx headers
+    chain_context: TxChainContextCache,
 }
 
 impl<'a, C, U, V, I> NamadaImpl<'a, C, U, V, I>
@@ -603,7 +621,13 @@ where
                 verification_key: None,
                 password: None,
                 use_device: false,
+                confirm_signing: false,
+                offline: false,
+                embed_missing_code: false,
+                wasm_dir: None,
             },
+            code_hashes: CodeHashCache::default(),
+            chain_context: TxChainContextCache::default(),
         }
     }
 
@@ -647,6 +671,14 @@ where
         self.native_token.clone()
     }
 
+    fn code_hashes(&self) -> &CodeHashCache {
+        &self.code_hashes
+    }
+
+    fn chain_context(&self) -> &TxChainContextCache {
+        &self.chain_context
+    }
+
     fn io(&self) -> &'a Self::Io {
         self.io
     }
@@ -8,18 +8,20 @@ use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
 use namada_core::hints;
+use namada_core::ledger::fee_records::{self, EpochFees};
 use namada_core::ledger::storage::traits::StorageHasher;
 use namada_core::ledger::storage::{DBIter, LastBlock, DB};
 use namada_core::ledger::storage_api::{self, ResultExt, StorageRead};
 use namada_core::types::account::{Account, AccountPublicKeysMap};
 use namada_core::types::address::Address;
+use namada_core::types::chain::ChainId;
 use namada_core::types::hash::Hash;
 use namada_core::types::storage::{
     self, BlockHeight, BlockResults, Epoch, KeySeg, PrefixValue,
 };
 use namada_core::types::token::MaspDenom;
 #[cfg(any(test, feature = "async-client"))]
-use namada_core::types::transaction::TxResult;
+use namada_core::types::transaction::DryRunResult;
 
 use self::eth_bridge::{EthBridge, ETH_BRIDGE};
 use crate::events::log::dumb_queries;
@@ -56,6 +58,9 @@ router! {SHELL,
     // The address of the native token
     ( "native_token" ) -> Address = native_token,
 
+    // The id of the chain
+    ( "chain_id" ) -> ChainId = chain_id,
+
     // Epoch of the input block height
     ( "epoch_at_height" / [height: BlockHeight]) -> Option<Epoch> = epoch_at_height,
 
@@ -67,7 +72,7 @@ router! {SHELL,
         -> Vec<u8> = (with_options storage_value),
 
     // Dry run a transaction
-    ( "dry_run_tx" ) -> TxResult = (with_options dry_run_tx),
+    ( "dry_run_tx" ) -> DryRunResult = (with_options dry_run_tx),
 
     // Raw storage access - prefix iterator
     ( "prefix" / [storage_key: storage::Key] )
@@ -92,6 +97,19 @@ router! {SHELL,
     // was the transaction applied?
     ( "applied" / [tx_hash: Hash] ) -> Option<Event> = applied,
 
+    // was the tx hash recorded for replay protection?
+    ( "replay_protected" / [tx_hash: Hash] ) -> bool = replay_protected,
+
+    // Hashes of the wasm codes stored on chain, keyed by code name
+    ( "code_hashes" ) -> BTreeMap<String, Hash> = code_hashes,
+
+    // Borsh encoded tx section retained at the given block height
+    ( "tx_section" / [height: BlockHeight] / [section_hash: Hash] )
+        -> Option<Vec<u8>> = tx_section,
+
+    // Fees collected over the given epoch, with the per block breakdown
+    ( "fees" / [epoch: Epoch] ) -> EpochFees = epoch_fees,
+
     // Query account subspace
     ( "account" / [owner: Address] ) -> Option<Account> = account,
 
@@ -231,6 +249,17 @@ where
     Ok(data)
 }
 
+fn chain_id<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+) -> storage_api::Result<ChainId>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let data = ctx.wl_storage.storage.chain_id.clone();
+    Ok(data)
+}
+
 fn epoch_at_height<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     height: BlockHeight,
@@ -410,6 +439,12 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let matcher = dumb_queries::QueryMatcher::accepted(tx_hash);
+    if let Some(event) = ctx.event_log.iter_with_matcher(matcher).next() {
+        return Ok(Some(event.clone()));
+    }
+    // Fall back to looking up the wrapper by its CometBFT hash
+    let matcher =
+        dumb_queries::QueryMatcher::accepted_by_tendermint_hash(tx_hash);
     Ok(ctx
         .event_log
         .iter_with_matcher(matcher)
@@ -427,6 +462,12 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let matcher = dumb_queries::QueryMatcher::applied(tx_hash);
+    if let Some(event) = ctx.event_log.iter_with_matcher(matcher).next() {
+        return Ok(Some(event.clone()));
+    }
+    // Fall back to looking up the tx by the CometBFT hash of its wrapper
+    let matcher =
+        dumb_queries::QueryMatcher::applied_by_tendermint_hash(tx_hash);
     Ok(ctx
         .event_log
         .iter_with_matcher(matcher)
@@ -435,6 +476,75 @@ where
         .cloned())
 }
 
+fn replay_protected<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    tx_hash: Hash,
+) -> storage_api::Result<bool>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    ctx.wl_storage
+        .storage
+        .has_replay_protection_entry(&tx_hash)
+        .into_storage_result()
+}
+
+fn code_hashes<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+) -> storage_api::Result<BTreeMap<String, Hash>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let prefix = storage::Key::wasm_hash_prefix();
+    storage_api::iter_prefix_bytes(ctx.wl_storage, &prefix)?
+        .map(|iter_result| {
+            let (key, hash) = iter_result?;
+            let name = match key.last() {
+                Some(storage::DbKeySeg::StringSeg(name)) => name.clone(),
+                _ => {
+                    return Err(storage_api::Error::new(format!(
+                        "Unexpected wasm hash key {key}"
+                    )));
+                }
+            };
+            let hash =
+                Hash::try_from_slice_exact(&hash).into_storage_result()?;
+            Ok((name, hash))
+        })
+        .collect()
+}
+
+/// Query the Borsh encoding of a section of a tx included in the block at the
+/// given height. The sections are only retained by the nodes configured to do
+/// so, for a bounded number of blocks.
+fn tx_section<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    height: BlockHeight,
+    section_hash: Hash,
+) -> storage_api::Result<Option<Vec<u8>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    ctx.wl_storage
+        .storage
+        .read_tx_section(height, &section_hash)
+        .into_storage_result()
+}
+
+fn epoch_fees<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    epoch: Epoch,
+) -> storage_api::Result<EpochFees>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    fee_records::read_epoch_fees(ctx.wl_storage, epoch)
+}
+
 fn ibc_client_update<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     client_id: ClientId,
@@ -527,6 +637,8 @@ where
 
 #[cfg(test)]
 mod test {
+    use namada_core::types::hash::Hash;
+    use namada_core::types::storage::{BlockHeight, Epoch};
     use namada_core::types::{address, token};
 
     use crate::queries::RPC;
@@ -550,5 +662,73 @@ mod test {
 
         let path = RPC.shell().storage_has_key_path(&key);
         assert_eq!(format!("/shell/has_key/{}", key), path);
+
+        let height = BlockHeight(1);
+        let hash = Hash::sha256(b"section");
+        let path = RPC.shell().tx_section_path(&height, &hash);
+        assert_eq!(format!("/shell/tx_section/{}/{}", height, hash), path);
+
+        let epoch = Epoch(3);
+        let path = RPC.shell().epoch_fees_path(&epoch);
+        assert_eq!(format!("/shell/fees/{}", epoch), path);
+    }
+
+    /// Check that the hashes in query paths can be given in upper or lower
+    /// case hex, with or without a `0x` prefix
+    #[tokio::test]
+    async fn test_shell_queries_string_hashes() {
+        use std::collections::HashMap;
+
+        use borsh::BorshDeserialize;
+
+        use crate::events::{Event, EventLevel, EventType};
+        use crate::queries::testing::TestClient;
+        use crate::queries::Client;
+
+        let mut client = TestClient::new(RPC);
+        let hash = Hash::sha256(b"tx");
+        let event = Event {
+            event_type: EventType::Applied,
+            level: EventLevel::Tx,
+            attributes: HashMap::from([("hash".to_string(), hash.to_string())]),
+        };
+        client.event_log.log_events(vec![event.clone()]);
+
+        for hex in [
+            hash.to_string(),
+            format!("{:x}", hash),
+            format!("{:#x}", hash),
+            format!("0X{}", hash),
+        ] {
+            let response = client
+                .request(format!("/shell/applied/{hex}"), None, None, false)
+                .await
+                .unwrap();
+            let applied =
+                Option::<Event>::try_from_slice(&response.data).unwrap();
+            assert_eq!(applied, Some(event.clone()));
+
+            let response = client
+                .request(
+                    format!("/shell/replay_protected/{hex}"),
+                    None,
+                    None,
+                    false,
+                )
+                .await
+                .unwrap();
+            assert!(!bool::try_from_slice(&response.data).unwrap());
+        }
+
+        // Hashes of invalid lengths or with non-hex digits don't route
+        let lower = format!("{:x}", hash);
+        for hex in [&lower[2..], format!("g{}", &lower[1..]).as_str()] {
+            assert!(
+                client
+                    .request(format!("/shell/applied/{hex}"), None, None, false)
+                    .await
+                    .is_err()
+            );
+        }
     }
 }
@@ -3,6 +3,7 @@
 use std::cell::Cell;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ops::ControlFlow;
+use std::sync::Mutex;
 
 use borsh::BorshDeserialize;
 use masp_primitives::asset_type::AssetType;
@@ -11,20 +12,30 @@ use masp_primitives::sapling::Node;
 use namada_core::ledger::governance::parameters::GovernanceParameters;
 use namada_core::ledger::governance::storage::proposal::StorageProposal;
 use namada_core::ledger::governance::utils::Vote;
+use namada_core::ledger::gas;
 use namada_core::ledger::ibc::storage::{
     ibc_denom_key, ibc_denom_key_prefix, is_ibc_denom_key,
 };
+use namada_core::ledger::parameters::{
+    storage as parameter_storage, EpochDuration,
+};
 use namada_core::ledger::storage::LastBlock;
 use namada_core::types::account::Account;
 use namada_core::types::address::{Address, InternalAddress};
+use namada_core::types::chain::ChainId;
 use namada_core::types::hash::Hash;
 use namada_core::types::key::common;
 use namada_core::types::storage::{
     BlockHeight, BlockResults, Epoch, Key, PrefixValue,
 };
+use namada_core::types::time::{DateTimeUtc, DurationSecs};
 use namada_core::types::token::{
     Amount, DenominatedAmount, Denomination, MaspDenom,
 };
+use namada_core::types::transaction::{
+    hash_tx, DryRunResult, Fee, GasLimit, ResultCode, StorageChange,
+    StorageValue, TxType,
+};
 use namada_core::types::{storage, token};
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::types::{
@@ -35,10 +46,11 @@ use serde::Serialize;
 use crate::args::InputAmount;
 use crate::control_flow::time;
 use crate::error::{EncodingError, Error, QueryError, TxError};
+use crate::events::decode::TypedTxResponse;
 use crate::events::Event;
 use crate::internal_macros::echo_error;
 use crate::io::Io;
-use crate::proto::Tx;
+use crate::proto::{Section, Tx};
 use crate::queries::vp::pos::EnrichedBondsAndUnbondsDetails;
 use crate::queries::{Client, RPC};
 use crate::tendermint::block::Height;
@@ -48,11 +60,11 @@ use crate::tendermint_rpc::query::Query;
 use crate::tendermint_rpc::Order;
 use crate::{display_line, edisplay_line, error, Namada};
 
-/// Query the status of a given transaction.
+/// Wait for the event of a given transaction.
 ///
 /// If a response is not delivered until `deadline`, we exit the cli with an
 /// error.
-pub async fn query_tx_status<'a>(
+pub async fn wait_for_tx_event<'a>(
     context: &impl Namada<'a>,
     status: TxEventQuery<'_>,
     deadline: time::Instant,
@@ -102,6 +114,103 @@ pub async fn query_tx_status<'a>(
     })
 }
 
+/// The status of a tx, as known to the node that is queried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStatus {
+    /// The tx is in the mempool, waiting to be included in a block
+    Pending,
+    /// The wrapper tx was included in a block, its inner tx is yet to be
+    /// applied
+    WrapperAccepted {
+        /// Height of the block that included the wrapper tx
+        height: BlockHeight,
+    },
+    /// The inner tx was applied, successfully or not
+    InnerApplied {
+        /// Height of the block in which the inner tx was applied
+        height: BlockHeight,
+        /// The result of the inner tx
+        result: TypedTxResponse,
+    },
+    /// The inner tx could not be decrypted, so it was not applied
+    Undecryptable {
+        /// Height of the block in which the inner tx was due
+        height: BlockHeight,
+    },
+    /// The tx is unknown to the node
+    Unknown,
+}
+
+/// Query the status of a tx, given the hash of its wrapper, of its inner tx or
+/// the CometBFT hash of its wrapper.
+///
+/// The inner tx hash is only known to the node once the inner tx has been
+/// applied, so it resolves to [`TxStatus::Unknown`] until then. Pending txs
+/// are reported with [`query_tx_status_with_mempool`].
+pub async fn query_tx_status<C: crate::queries::Client + Sync>(
+    client: &C,
+    hash: &Hash,
+) -> Result<TxStatus, Error> {
+    query_tx_status_with_mempool(client, hash, &[]).await
+}
+
+/// Query the status of a tx like [`query_tx_status`], additionally looking
+/// it up in `unconfirmed_txs`, the raw txs in the mempool of the node as
+/// returned by the `unconfirmed_txs` endpoint of CometBFT.
+pub async fn query_tx_status_with_mempool<C: crate::queries::Client + Sync>(
+    client: &C,
+    hash: &Hash,
+    unconfirmed_txs: &[Vec<u8>],
+) -> Result<TxStatus, Error> {
+    // The hash of an inner tx or the CometBFT hash of its wrapper
+    if let Some(event) =
+        convert_response::<C, _>(RPC.shell().applied(client, hash).await)?
+    {
+        return applied_tx_status(event);
+    }
+    // The hash of a wrapper tx or its CometBFT hash
+    if let Some(event) =
+        convert_response::<C, _>(RPC.shell().accepted(client, hash).await)?
+    {
+        // Follow the wrapper to its inner tx, which may have been applied
+        // since
+        if let Some(tendermint_hash) = event
+            .get("tendermint_hash")
+            .and_then(|hash| hash.parse::<Hash>().ok())
+        {
+            if let Some(event) = convert_response::<C, _>(
+                RPC.shell().applied(client, &tendermint_hash).await,
+            )? {
+                return applied_tx_status(event);
+            }
+        }
+        let height = TypedTxResponse::try_from(event)?.outcome.height;
+        return Ok(TxStatus::WrapperAccepted { height });
+    }
+    let is_pending = unconfirmed_txs.iter().any(|tx_bytes| {
+        hash_tx(tx_bytes) == *hash
+            || Tx::try_from(tx_bytes.as_slice()).map_or(false, |tx| {
+                tx.header_hash() == *hash || tx.raw_header_hash() == *hash
+            })
+    });
+    Ok(if is_pending {
+        TxStatus::Pending
+    } else {
+        TxStatus::Unknown
+    })
+}
+
+/// Get the status of a tx from its `applied` event
+fn applied_tx_status(event: Event) -> Result<TxStatus, Error> {
+    let result = TypedTxResponse::try_from(event)?;
+    let height = result.outcome.height;
+    Ok(if result.outcome.code == ResultCode::Undecryptable {
+        TxStatus::Undecryptable { height }
+    } else {
+        TxStatus::InnerApplied { height, result }
+    })
+}
+
 /// Query the epoch of the last committed block
 pub async fn query_epoch<C: crate::queries::Client + Sync>(
     client: &C,
@@ -116,6 +225,13 @@ pub async fn query_native_token<C: crate::queries::Client + Sync>(
     convert_response::<C, _>(RPC.shell().native_token(client).await)
 }
 
+/// Query the id of the chain
+pub async fn query_chain_id<C: crate::queries::Client + Sync>(
+    client: &C,
+) -> Result<ChainId, error::Error> {
+    convert_response::<C, _>(RPC.shell().chain_id(client).await)
+}
+
 /// Query the epoch of the given block height, if it exists.
 /// Will return none if the input block height is greater than
 /// the latest committed block height.
@@ -160,6 +276,73 @@ pub async fn query_results<C: crate::queries::Client + Sync>(
     convert_response::<C, _>(RPC.shell().read_results(client).await)
 }
 
+/// Query the section with the given hash of a tx included in the block at the
+/// given height
+pub async fn query_tx_section<C: crate::queries::Client + Sync>(
+    client: &C,
+    height: BlockHeight,
+    section_hash: &Hash,
+) -> Result<Option<Section>, Error> {
+    let section = convert_response::<C, _>(
+        RPC.shell().tx_section(client, &height, section_hash).await,
+    )?;
+    section
+        .map(|bytes| {
+            Section::try_from_slice(&bytes).map_err(|err| {
+                Error::from(EncodingError::Decoding(err.to_string()))
+            })
+        })
+        .transpose()
+}
+
+/// Look up a tx included in a block by the hash reported in its `accepted` or
+/// `applied` event, i.e. the hash of a wrapper tx or of an inner tx. Returns
+/// the height of the block that included the tx along with the tx. Only txs
+/// whose events are still in the node's event log can be found.
+pub async fn query_tx_by_hash<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx_hash: &Hash,
+) -> Result<Option<(BlockHeight, Tx)>, Error> {
+    // A wrapper tx is accepted and the decrypted inner tx is applied
+    let event = match convert_response::<C, _>(
+        RPC.shell().accepted(client, tx_hash).await,
+    )? {
+        Some(event) => event,
+        None => match convert_response::<C, _>(
+            RPC.shell().applied(client, tx_hash).await,
+        )? {
+            Some(event) => event,
+            None => return Ok(None),
+        },
+    };
+    let height = event
+        .get("height")
+        .and_then(|height| height.parse::<u64>().ok())
+        .ok_or_else(|| {
+            Error::from(QueryError::General(format!(
+                "The event of tx {} has no valid height",
+                tx_hash
+            )))
+        })?;
+    let block_height = Height::try_from(height)
+        .map_err(|err| Error::from(QueryError::General(err.to_string())))?;
+    let block = client
+        .block(block_height)
+        .await
+        .map_err(|err| Error::from(QueryError::General(err.to_string())))?
+        .block;
+    let tx = block.data.iter().find_map(|bytes| {
+        let tx = Tx::try_from(bytes.as_slice()).ok()?;
+        // The applied event of a decrypted tx is keyed by its inner tx hash
+        let hash = match tx.header().tx_type {
+            TxType::Decrypted(_) => tx.raw_header_hash(),
+            _ => tx.header_hash(),
+        };
+        (hash == *tx_hash).then_some(tx)
+    });
+    Ok(tx.map(|tx| (BlockHeight(height), tx)))
+}
+
 /// Query token amount of owner.
 pub async fn get_token_balance<C: crate::queries::Client + Sync>(
     client: &C,
@@ -300,6 +483,172 @@ pub async fn query_wasm_code_hash<'a>(
     }
 }
 
+/// A client-side cache of the hashes of the wasm codes stored on chain, keyed
+/// by code name
+#[derive(Debug, Default)]
+pub struct CodeHashCache(Mutex<Option<BTreeMap<String, Hash>>>);
+
+impl CodeHashCache {
+    /// Look up the hash of the wasm code stored on chain under the given
+    /// name. All the hashes are fetched on first use, and a code missing from
+    /// the cache is looked up again in case it was stored since. Returns
+    /// `None` if the chain doesn't store such code.
+    pub async fn lookup<C: crate::queries::Client + Sync>(
+        &self,
+        client: &C,
+        name: &str,
+    ) -> Result<Option<Hash>, error::Error> {
+        let cached = self
+            .0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|hashes| hashes.get(name).copied());
+        match cached {
+            Some(Some(hash)) => Ok(Some(hash)),
+            Some(None) => {
+                let hash_key = Key::wasm_hash(name);
+                let hash =
+                    query_storage_value_bytes(client, &hash_key, None, false)
+                        .await?
+                        .0
+                        .map(|hash| Hash::try_from(&hash[..]))
+                        .transpose()
+                        .map_err(|err| {
+                            Error::from(EncodingError::Decoding(
+                                err.to_string(),
+                            ))
+                        })?;
+                if let Some(hash) = hash {
+                    self.extend([(name.to_string(), hash)]);
+                }
+                Ok(hash)
+            }
+            None => {
+                let hashes = query_code_hashes(client).await?;
+                let hash = hashes.get(name).copied();
+                self.extend(hashes);
+                Ok(hash)
+            }
+        }
+    }
+
+    /// Forget the cached hashes, e.g. after the codes stored on chain were
+    /// updated by governance
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+
+    /// Cache the given code hashes
+    fn extend(&self, new_hashes: impl IntoIterator<Item = (String, Hash)>) {
+        self.0
+            .lock()
+            .unwrap()
+            .get_or_insert_with(Default::default)
+            .extend(new_hashes);
+    }
+}
+
+/// Query the hashes of all the wasm codes stored on chain, keyed by code name
+pub async fn query_code_hashes<C: crate::queries::Client + Sync>(
+    client: &C,
+) -> Result<BTreeMap<String, Hash>, error::Error> {
+    convert_response::<C, _>(RPC.shell().code_hashes(client).await)
+}
+
+/// The lower bound of the suggested time to live of a tx, leaving enough time
+/// to sign and submit it on chains with very short epochs
+pub const MIN_TX_EXPIRATION_DELTA: DurationSecs = DurationSecs(60 * 60);
+
+/// The parameters of the connected chain that fill in the headers of the txs
+/// built by the SDK
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxChainContext {
+    /// The id of the chain
+    pub chain_id: ChainId,
+    /// The epoch of the last committed block at the time of the query
+    pub epoch: Epoch,
+    /// The suggested time to live of a tx, i.e. the minimum duration of an
+    /// epoch but no less than [`MIN_TX_EXPIRATION_DELTA`]
+    pub expiration_delta: DurationSecs,
+    /// The minimum gas price of each token allowed for fee payment
+    pub min_fees: BTreeMap<Address, Amount>,
+}
+
+impl TxChainContext {
+    /// The suggested expiration of a tx built now
+    pub fn default_expiration(&self) -> DateTimeUtc {
+        DateTimeUtc::now() + self.expiration_delta
+    }
+}
+
+/// Query the parameters of the connected chain that fill in the headers of
+/// the txs built by the SDK
+pub async fn query_tx_chain_context<C: crate::queries::Client + Sync>(
+    client: &C,
+) -> Result<TxChainContext, error::Error> {
+    let chain_id = query_chain_id(client).await?;
+    let epoch = query_epoch(client).await?;
+    let epoch_duration: EpochDuration = query_storage_value(
+        client,
+        &parameter_storage::get_epoch_duration_storage_key(),
+    )
+    .await?;
+    let min_fees =
+        query_storage_value(client, &parameter_storage::get_gas_cost_key())
+            .await?;
+    Ok(TxChainContext {
+        chain_id,
+        epoch,
+        expiration_delta: DurationSecs(
+            epoch_duration.min_duration.0.max(MIN_TX_EXPIRATION_DELTA.0),
+        ),
+        min_fees,
+    })
+}
+
+/// A client-side cache of the [`TxChainContext`], queried once per session
+#[derive(Debug, Default)]
+pub struct TxChainContextCache(Mutex<Option<TxChainContext>>);
+
+impl TxChainContextCache {
+    /// Get the context of the connected chain, querying it on first use
+    pub async fn get<C: crate::queries::Client + Sync>(
+        &self,
+        client: &C,
+    ) -> Result<TxChainContext, error::Error> {
+        let cached = self.0.lock().unwrap().clone();
+        match cached {
+            Some(chain_context) => Ok(chain_context),
+            None => {
+                let chain_context = query_tx_chain_context(client).await?;
+                *self.0.lock().unwrap() = Some(chain_context.clone());
+                Ok(chain_context)
+            }
+        }
+    }
+
+    /// Forget the cached context, e.g. after the parameters of the chain
+    /// changed at an epoch boundary
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+/// Resolve the hash of the wasm code stored on chain under the given name,
+/// going through the context's code hash cache. Returns an error if the chain
+/// doesn't store such code.
+pub async fn resolve_tx_code<'a>(
+    context: &impl Namada<'a>,
+    name: &str,
+) -> Result<Hash, error::Error> {
+    context
+        .code_hashes()
+        .lookup(context.client(), name)
+        .await?
+        .ok_or_else(|| Error::from(QueryError::Wasm(name.to_string())))
+}
+
 /// Query a storage value and decode it with [`BorshDeserialize`].
 pub async fn query_storage_value<C, T>(
     client: &C,
@@ -475,8 +824,171 @@ pub async fn dry_run_tx<'a, N: Namada<'a>>(
             .await,
     )?
     .data;
-    display_line!(context.io(), "Dry-run result: {}", result);
-    Ok(result)
+    display_line!(context.io(), "Dry-run result: {}", result.tx_result);
+    if !result.storage_changes.is_empty() {
+        display_line!(context.io(), "Predicted storage changes:");
+        for change in &result.storage_changes {
+            display_line!(
+                context.io(),
+                "  {}",
+                format_storage_change(context.client(), context.io(), change)
+                    .await
+            );
+        }
+    }
+    Ok(result.tx_result)
+}
+
+/// Simulate the application of the given unencrypted tx on top of the last
+/// committed state. Nothing is committed, the result includes the storage
+/// changes the tx would make.
+pub async fn simulate_tx<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx: &Tx,
+) -> Result<DryRunResult, Error> {
+    let (data, height, prove) = (Some(tx.to_bytes()), None, false);
+    convert_response::<C, _>(
+        RPC.shell().dry_run_tx(client, data, height, prove).await,
+    )
+    .map(|response| response.data)
+}
+
+/// The margin added to the estimated gas of a tx for the suggested gas limit
+/// of a [`FeeEstimate`], in percent
+pub const FEE_SAFETY_MARGIN_PERCENT: u64 = 20;
+
+/// The fee estimated for a tx by [`estimate_fee`]
+#[derive(Debug, Clone)]
+pub struct FeeEstimate {
+    /// The fee per gas unit, i.e. the minimum gas price of the fee token
+    pub fee: Fee,
+    /// The estimated length of the tx once its sections are sealed
+    pub sealed_tx_len: u64,
+    /// The smallest gas limit covering both the wrapper and the inner tx
+    pub min_gas_limit: GasLimit,
+    /// The minimum gas limit increased by the safety margin
+    pub suggested_gas_limit: GasLimit,
+}
+
+impl FeeEstimate {
+    /// The minimal fee the tx is expected to be accepted with
+    pub fn min_fee(&self) -> Amount {
+        self.fee.amount_per_gas_unit * u64::from(self.min_gas_limit)
+    }
+
+    /// The fee to pay for the suggested gas limit
+    pub fn suggested_fee(&self) -> Amount {
+        self.fee.amount_per_gas_unit * u64::from(self.suggested_gas_limit)
+    }
+}
+
+/// Estimate the fee of the given tx when paid in the given token.
+///
+/// The gas of the inner tx is taken from a dry run, while the gas charged for
+/// the wrapper is derived from the size of the tx after sealing its sections.
+/// The tx should therefore already be wrapped and signed, since updating the
+/// fee and gas limit of the wrapper doesn't change its size.
+pub async fn estimate_fee<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx: &Tx,
+    token: &Address,
+) -> Result<FeeEstimate, Error> {
+    let gas_cost_key = parameter_storage::get_gas_cost_key();
+    let amount_per_gas_unit =
+        query_storage_value::<C, BTreeMap<Address, Amount>>(
+            client,
+            &gas_cost_key,
+        )
+        .await?
+        .remove(token)
+        .ok_or_else(|| TxError::FeeTokenNotAllowed(token.clone()))?;
+
+    // Dry run the inner tx alone, the wrapper doesn't have a valid fee yet
+    let mut inner_tx = tx.clone();
+    inner_tx.update_header(TxType::Raw);
+    let inner_gas = simulate_tx(client, &inner_tx).await?.tx_result.gas_used;
+
+    let sealed_tx_len = tx.sealed_len() as u64;
+    let min_gas = gas::get_wrapper_gas(sealed_tx_len)
+        .ok()
+        .and_then(|wrapper_gas| wrapper_gas.checked_add(inner_gas))
+        .ok_or_else(|| Error::Other("Overflow in gas".to_string()))?
+        .get_whole_gas_units();
+    let margin = (min_gas * FEE_SAFETY_MARGIN_PERCENT + 99) / 100;
+
+    Ok(FeeEstimate {
+        fee: Fee {
+            amount_per_gas_unit,
+            token: token.clone(),
+        },
+        sealed_tx_len,
+        min_gas_limit: min_gas.into(),
+        suggested_gas_limit: (min_gas + margin).into(),
+    })
+}
+
+/// Describe a storage change predicted by a dry run. The values of balance,
+/// bond and validity predicate keys are decoded, any other change is
+/// described by the hashes of its values.
+pub async fn format_storage_change(
+    client: &(impl Client + Sync),
+    io: &impl Io,
+    change: &StorageChange,
+) -> String {
+    if let Some([token, owner]) = token::is_any_token_balance_key(&change.key) {
+        if let Some((old, new)) = decode_storage_change::<Amount>(change) {
+            let (old, new) = (old.unwrap_or_default(), new.unwrap_or_default());
+            let (sign, delta) = if new >= old {
+                ("+", new - old)
+            } else {
+                ("-", old - new)
+            };
+            return format!(
+                "Balance of {owner} in {token}: {} -> {} ({sign}{})",
+                format_denominated_amount(client, io, token, old).await,
+                format_denominated_amount(client, io, token, new).await,
+                format_denominated_amount(client, io, token, delta).await,
+            );
+        }
+    }
+    if let Some((bond_id, epoch)) =
+        namada_proof_of_stake::storage::is_bond_key(&change.key)
+    {
+        if let Some((old, new)) = decode_storage_change::<Amount>(change) {
+            return format!(
+                "Bond of {} to {} from epoch {epoch}: {} -> {}",
+                bond_id.source,
+                bond_id.validator,
+                old.unwrap_or_default().to_string_native(),
+                new.unwrap_or_default().to_string_native(),
+            );
+        }
+    }
+    if let Some(owner) = change.key.is_validity_predicate() {
+        if let Some((old, new)) = decode_storage_change::<Hash>(change) {
+            let describe = |hash: Option<Hash>| {
+                hash.map_or_else(|| "none".to_string(), |hash| hash.to_string())
+            };
+            return format!(
+                "Validity predicate of {owner}: {} -> {}",
+                describe(old),
+                describe(new),
+            );
+        }
+    }
+    change.to_string()
+}
+
+/// Decode the old and new values of a storage change. Returns `None` if either
+/// of the values fails to decode.
+fn decode_storage_change<T: BorshDeserialize>(
+    change: &StorageChange,
+) -> Option<(Option<T>, Option<T>)> {
+    let decode = |value: &Option<StorageValue>| match value {
+        Some(value) => T::try_from_slice(&value.bytes).ok().map(Some),
+        None => Some(None),
+    };
+    Some((decode(&change.old)?, decode(&change.new)?))
 }
 
 /// Data needed for broadcasting a tx and
@@ -583,7 +1095,7 @@ impl TxResponse {
 }
 
 /// Lookup the full response accompanying the specified transaction event
-// TODO: maybe remove this in favor of `query_tx_status`
+// TODO: maybe remove this in favor of `wait_for_tx_event`
 pub async fn query_tx_response<C: crate::queries::Client + Sync>(
     client: &C,
     tx_query: TxEventQuery<'_>,
@@ -653,6 +1165,60 @@ pub async fn query_tx_response<C: crate::queries::Client + Sync>(
     Ok(result)
 }
 
+/// Wait for the inner tx with the given hash to be applied, and return its
+/// decoded result.
+///
+/// Inner txs are executed in the block following the one that included
+/// their wrapper. The `applied` event log of the node is polled until an
+/// event matching `inner_hash` shows up, or `timeout` elapses. A rejected
+/// inner tx is not an error: the returned [`TypedTxResponse`] carries its
+/// result code. Undecryptable inner txs and timeouts are reported as
+/// [`TxError::Undecryptable`] and [`TxError::AppliedTimeout`], respectively.
+pub async fn wait_for_inner_applied<C: crate::queries::Client + Sync>(
+    client: &C,
+    inner_hash: &Hash,
+    timeout: time::Duration,
+) -> Result<TypedTxResponse, Error> {
+    let deadline = time::Instant::now() + timeout;
+    let event = time::Sleep {
+        strategy: time::LinearBackoff {
+            delta: time::Duration::from_secs(1),
+        },
+    }
+    .timeout(deadline, || async {
+        match RPC.shell().applied(client, inner_hash).await {
+            Ok(Some(event)) => {
+                tracing::debug!(?event, "Found applied inner tx event");
+                ControlFlow::Break(event)
+            }
+            Ok(None) => {
+                tracing::debug!(
+                    %inner_hash,
+                    "Inner tx not yet applied, retrying after timeout",
+                );
+                ControlFlow::Continue(())
+            }
+            Err(err) => {
+                tracing::debug!(
+                    %inner_hash,
+                    %err,
+                    "ABCI query failed, retrying inner tx status query \
+                     after timeout",
+                );
+                ControlFlow::Continue(())
+            }
+        }
+    })
+    .await
+    .map_err(|_| Error::Tx(TxError::AppliedTimeout))?;
+
+    let response = TypedTxResponse::try_from(event)?;
+    if response.outcome.code == ResultCode::Undecryptable {
+        return Err(Error::Tx(TxError::Undecryptable(response.outcome.hash)));
+    }
+    Ok(response)
+}
+
 /// Get the PoS parameters
 pub async fn get_pos_params<C: crate::queries::Client + Sync>(
     client: &C,
@@ -825,6 +1391,17 @@ pub async fn is_public_key_revealed<C: crate::queries::Client + Sync>(
     convert_response::<C, bool>(RPC.shell().revealed(client, owner).await)
 }
 
+/// Query if the given tx hash was recorded by the replay protection, i.e. if a
+/// tx with this hash was already applied
+pub async fn is_replay_protected<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx_hash: &Hash,
+) -> Result<bool, error::Error> {
+    convert_response::<C, bool>(
+        RPC.shell().replay_protected(client, tx_hash).await,
+    )
+}
+
 /// Query an account substorage at a specific index
 pub async fn get_public_key_at<C: crate::queries::Client + Sync>(
     client: &C,
@@ -1176,3 +1753,126 @@ pub async fn query_ibc_denom<'a, N: Namada<'a>>(
 
     token.to_string()
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::events::{EventLevel, EventType};
+    use crate::queries::testing::TestClient;
+
+    /// Build an `applied` event for the given inner tx hash
+    fn applied_event(hash: &Hash, code: u32, gas_used: u64) -> Event {
+        let mut event = Event {
+            event_type: EventType::Applied,
+            level: EventLevel::Tx,
+            attributes: HashMap::new(),
+        };
+        event["hash"] = hash.to_string();
+        event["height"] = "2".to_string();
+        event["code"] = code.to_string();
+        event["gas_used"] = gas_used.to_string();
+        event["info"] = "Transaction is valid.".to_string();
+        event["log"] = String::new();
+        event
+    }
+
+    /// Test that successful and rejected inner txs are decoded from their
+    /// `applied` event.
+    #[tokio::test]
+    async fn test_wait_for_inner_applied() {
+        let mut client = TestClient::new(RPC);
+        let accepted = Hash::sha256(b"accepted");
+        let rejected = Hash::sha256(b"rejected");
+        client.event_log.log_events([
+            applied_event(&accepted, 0, 42),
+            applied_event(&rejected, 2, 7),
+        ]);
+
+        let timeout = time::Duration::from_secs(1);
+        let result = wait_for_inner_applied(&client, &accepted, timeout)
+            .await
+            .unwrap();
+        assert!(result.is_success());
+        assert_eq!(result.outcome.hash, accepted.to_string());
+        assert_eq!(result.outcome.height, BlockHeight(2));
+        assert_eq!(result.outcome.gas_used, 42);
+
+        let result = wait_for_inner_applied(&client, &rejected, timeout)
+            .await
+            .unwrap();
+        assert!(!result.is_success());
+        assert_eq!(result.outcome.code, ResultCode::InvalidTx);
+        assert_eq!(result.outcome.gas_used, 7);
+    }
+
+    /// Test that undecryptable inner txs and missing events are reported
+    /// with distinct errors.
+    #[tokio::test]
+    async fn test_wait_for_inner_applied_errors() {
+        let mut client = TestClient::new(RPC);
+        let undecryptable = Hash::sha256(b"undecryptable");
+        let mut event =
+            applied_event(&undecryptable, ResultCode::Undecryptable.into(), 0);
+        event.attributes.remove("gas_used");
+        client.event_log.log_events([event]);
+
+        let timeout = time::Duration::from_millis(100);
+        let result =
+            wait_for_inner_applied(&client, &undecryptable, timeout).await;
+        assert_matches!(result, Err(Error::Tx(TxError::Undecryptable(_))));
+
+        let missing = Hash::sha256(b"missing");
+        let result = wait_for_inner_applied(&client, &missing, timeout).await;
+        assert_matches!(result, Err(Error::Tx(TxError::AppliedTimeout)));
+    }
+
+    /// Test that the code hashes are fetched from the chain once, and that
+    /// codes stored since are looked up again.
+    #[tokio::test]
+    async fn test_code_hash_cache() {
+        let mut client = TestClient::new(RPC);
+        let transfer_hash = Hash::sha256(b"tx_transfer");
+        client
+            .wl_storage
+            .storage
+            .write(&Key::wasm_hash("tx_transfer.wasm"), transfer_hash.0)
+            .unwrap();
+
+        let hashes = query_code_hashes(&client).await.unwrap();
+        assert_eq!(
+            hashes,
+            BTreeMap::from([("tx_transfer.wasm".to_string(), transfer_hash)])
+        );
+
+        let cache = CodeHashCache::default();
+        let hash = cache.lookup(&client, "tx_transfer.wasm").await.unwrap();
+        assert_eq!(hash, Some(transfer_hash));
+        let hash = cache.lookup(&client, "tx_bond.wasm").await.unwrap();
+        assert_eq!(hash, None);
+
+        let bond_hash = Hash::sha256(b"tx_bond");
+        client
+            .wl_storage
+            .storage
+            .write(&Key::wasm_hash("tx_bond.wasm"), bond_hash.0)
+            .unwrap();
+        let hash = cache.lookup(&client, "tx_bond.wasm").await.unwrap();
+        assert_eq!(hash, Some(bond_hash));
+
+        // The cached hash is served even once the code is gone from storage
+        client
+            .wl_storage
+            .storage
+            .delete(&Key::wasm_hash("tx_transfer.wasm"))
+            .unwrap();
+        let hash = cache.lookup(&client, "tx_transfer.wasm").await.unwrap();
+        assert_eq!(hash, Some(transfer_hash));
+        cache.clear();
+        let hash = cache.lookup(&client, "tx_transfer.wasm").await.unwrap();
+        assert_eq!(hash, None);
+    }
+}
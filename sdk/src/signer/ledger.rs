@@ -0,0 +1,430 @@
+//! Signing with Ledger hardware wallets.
+//!
+//! The device is handed the whole signing payload of a transaction so that it
+//! can display its contents, together with the commitment hash that it is
+//! asked to sign. Payloads exceeding the size of a single APDU are split into
+//! chunks that the device reassembles.
+
+use borsh::BorshDeserialize;
+use borsh_ext::BorshSerializeExt;
+use namada_core::proto::Tx;
+use namada_core::types::hash::Hash;
+use namada_core::types::key::*;
+
+use super::Signer;
+use crate::error::{Error, LedgerError};
+use crate::wallet::DerivationPath;
+
+/// The instruction class of the Namada Ledger app
+pub const CLA: u8 = 0x57;
+/// Instruction to get the public key at a derivation path
+pub const INS_GET_PUBKEY: u8 = 0x01;
+/// Instruction to sign a payload with the key at a derivation path
+pub const INS_SIGN: u8 = 0x02;
+/// The maximum number of data bytes carried by a single APDU
+pub const CHUNK_SIZE: usize = 250;
+/// Marks the first chunk of a payload
+pub const P1_INIT: u8 = 0x00;
+/// Marks an intermediate chunk of a payload
+pub const P1_ADD: u8 = 0x01;
+/// Marks the last chunk of a payload
+pub const P1_LAST: u8 = 0x02;
+
+/// Status word of a successful exchange
+const SW_OK: u16 = 0x9000;
+/// Status words of a request denied by the user
+const SW_DENIED: [u16; 2] = [0x6985, 0x6986];
+/// Status words of a request the open app does not understand
+const SW_WRONG_APP: [u16; 3] = [0x6d00, 0x6e00, 0x6e01];
+/// Status word of a locked device
+const SW_LOCKED: u16 = 0x5515;
+
+/// A command sent to a Ledger device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApduCommand {
+    /// The instruction class
+    pub cla: u8,
+    /// The instruction code
+    pub ins: u8,
+    /// The first instruction parameter
+    pub p1: u8,
+    /// The second instruction parameter
+    pub p2: u8,
+    /// The command data
+    pub data: Vec<u8>,
+}
+
+/// The answer of a Ledger device to a command
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApduAnswer {
+    /// The answer data
+    pub data: Vec<u8>,
+    /// The status word reported by the device
+    pub retcode: u16,
+}
+
+impl ApduAnswer {
+    /// Map the status word of this answer to an error if it is not a success
+    pub fn check(self) -> Result<Self, LedgerError> {
+        match self.retcode {
+            SW_OK => Ok(self),
+            SW_LOCKED => Err(LedgerError::DeviceLocked),
+            code if SW_DENIED.contains(&code) => Err(LedgerError::UserRejected),
+            code if SW_WRONG_APP.contains(&code) => Err(LedgerError::WrongApp),
+            code => Err(LedgerError::Device(code)),
+        }
+    }
+}
+
+/// A channel for exchanging APDUs with a Ledger device
+#[async_trait::async_trait(?Send)]
+pub trait LedgerTransport {
+    /// Send the given command to the device and wait for its answer
+    async fn exchange(
+        &self,
+        command: &ApduCommand,
+    ) -> Result<ApduAnswer, LedgerError>;
+}
+
+/// Serialize a derivation path the way the device expects it
+fn serialize_path(path: &DerivationPath) -> Vec<u8> {
+    path.path()
+        .iter()
+        .flat_map(|index| index.to_bits().to_le_bytes())
+        .collect()
+}
+
+/// Split the given data into the chunked commands of the given instruction.
+/// The first chunk carries the given header and the last one is flagged so
+/// that the device knows when the data is complete.
+pub fn chunk_commands(
+    ins: u8,
+    header: Vec<u8>,
+    data: &[u8],
+) -> Vec<ApduCommand> {
+    let mut commands = vec![ApduCommand {
+        cla: CLA,
+        ins,
+        p1: P1_INIT,
+        p2: 0,
+        data: header,
+    }];
+    let chunks = data.chunks(CHUNK_SIZE).collect::<Vec<_>>();
+    let last = chunks.len().saturating_sub(1);
+    for (idx, chunk) in chunks.into_iter().enumerate() {
+        commands.push(ApduCommand {
+            cla: CLA,
+            ins,
+            p1: if idx == last { P1_LAST } else { P1_ADD },
+            p2: 0,
+            data: chunk.to_vec(),
+        });
+    }
+    commands
+}
+
+/// A signer backed by a key held on a Ledger device
+pub struct LedgerSigner<T: LedgerTransport> {
+    transport: T,
+    path: DerivationPath,
+    public_key: common::PublicKey,
+    payload: Vec<u8>,
+}
+
+impl<T: LedgerTransport> LedgerSigner<T> {
+    /// Connect to the key at the given derivation path of the device behind
+    /// the given transport, in order to sign the given tx
+    pub async fn new(
+        transport: T,
+        path: DerivationPath,
+        tx: &Tx,
+    ) -> Result<Self, Error> {
+        let answer = transport
+            .exchange(&ApduCommand {
+                cla: CLA,
+                ins: INS_GET_PUBKEY,
+                p1: 0,
+                p2: 0,
+                data: serialize_path(&path),
+            })
+            .await?
+            .check()?;
+        let public_key = common::PublicKey::deserialize(&mut &answer.data[..])
+            .map_err(|err| {
+                LedgerError::InvalidResponse(format!(
+                    "unable to decode the public key: {}",
+                    err
+                ))
+            })?;
+        Ok(Self {
+            transport,
+            path,
+            public_key,
+            payload: tx.signing_payload(),
+        })
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<T: LedgerTransport> Signer for LedgerSigner<T> {
+    async fn sign(&self, target: &Hash) -> Result<common::Signature, Error> {
+        let mut header = serialize_path(&self.path);
+        header.extend(target.serialize_to_vec());
+        let mut answer = None;
+        for command in chunk_commands(INS_SIGN, header, &self.payload) {
+            answer = Some(self.transport.exchange(&command).await?.check()?);
+        }
+        // The signature comes with the answer to the last chunk
+        let answer = answer.expect("at least one chunk is always sent");
+        let signature = common::Signature::try_from_slice(&answer.data)
+            .map_err(|err| {
+                LedgerError::InvalidResponse(format!(
+                    "unable to decode the signature: {}",
+                    err
+                ))
+            })?;
+        common::SigScheme::verify_signature(
+            &self.public_key,
+            target,
+            &signature,
+        )
+        .map_err(|_| {
+            LedgerError::InvalidResponse(
+                "the signature is not over the requested target".to_string(),
+            )
+        })?;
+        Ok(signature)
+    }
+
+    fn public_key(&self) -> common::PublicKey {
+        self.public_key.clone()
+    }
+}
+
+#[cfg(feature = "ledger-hid")]
+mod hid {
+    use ledger_transport::APDUCommand;
+    use ledger_transport_hid::hidapi::HidApi;
+    use ledger_transport_hid::TransportNativeHID;
+
+    use super::*;
+
+    /// A transport to a Ledger device connected over USB HID
+    pub struct HidTransport(TransportNativeHID);
+
+    impl HidTransport {
+        /// Connect to the first Ledger device found
+        pub fn connect() -> Result<Self, LedgerError> {
+            let hidapi = HidApi::new()
+                .map_err(|err| LedgerError::Transport(err.to_string()))?;
+            TransportNativeHID::new(&hidapi)
+                .map(Self)
+                .map_err(|err| LedgerError::Transport(err.to_string()))
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl LedgerTransport for HidTransport {
+        async fn exchange(
+            &self,
+            command: &ApduCommand,
+        ) -> Result<ApduAnswer, LedgerError> {
+            let answer = self
+                .0
+                .exchange(&APDUCommand {
+                    cla: command.cla,
+                    ins: command.ins,
+                    p1: command.p1,
+                    p2: command.p2,
+                    data: command.data.as_slice(),
+                })
+                .map_err(|err| LedgerError::Transport(err.to_string()))?;
+            Ok(ApduAnswer {
+                data: answer.data().to_vec(),
+                retcode: answer.retcode(),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "ledger-hid")]
+pub use hid::HidTransport;
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use assert_matches::assert_matches;
+    use namada_core::types::account::AccountPublicKeysMap;
+    use namada_core::types::chain::ChainId;
+    use namada_core::types::key::testing::keypair_1;
+
+    use super::*;
+    use crate::signer;
+
+    /// A device holding a single key that reassembles the chunks it is sent
+    struct MockDevice {
+        keypair: common::SecretKey,
+        /// The status word to answer the last chunk with instead of signing
+        reject_with: Option<u16>,
+        commands: RefCell<Vec<ApduCommand>>,
+        buffer: RefCell<Vec<u8>>,
+    }
+
+    impl MockDevice {
+        fn new(reject_with: Option<u16>) -> Self {
+            Self {
+                keypair: keypair_1(),
+                reject_with,
+                commands: RefCell::new(vec![]),
+                buffer: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl LedgerTransport for &MockDevice {
+        async fn exchange(
+            &self,
+            command: &ApduCommand,
+        ) -> Result<ApduAnswer, LedgerError> {
+            self.commands.borrow_mut().push(command.clone());
+            assert!(command.data.len() <= CHUNK_SIZE);
+            let ok = |data| ApduAnswer {
+                data,
+                retcode: SW_OK,
+            };
+            match (command.ins, command.p1) {
+                (INS_GET_PUBKEY, _) => {
+                    Ok(ok(self.keypair.ref_to().serialize_to_vec()))
+                }
+                (INS_SIGN, P1_INIT) => {
+                    *self.buffer.borrow_mut() = command.data.clone();
+                    Ok(ok(vec![]))
+                }
+                (INS_SIGN, P1_ADD) => {
+                    self.buffer.borrow_mut().extend(&command.data);
+                    Ok(ok(vec![]))
+                }
+                (INS_SIGN, P1_LAST) => {
+                    if let Some(retcode) = self.reject_with {
+                        return Ok(ApduAnswer {
+                            data: vec![],
+                            retcode,
+                        });
+                    }
+                    self.buffer.borrow_mut().extend(&command.data);
+                    // Skip the derivation path to get to the target
+                    let buffer = self.buffer.borrow();
+                    let target = Hash::try_from_slice(&buffer[20..52]).unwrap();
+                    let signature =
+                        common::SigScheme::sign(&self.keypair, target);
+                    Ok(ok(signature.serialize_to_vec()))
+                }
+                _ => Ok(ApduAnswer {
+                    data: vec![],
+                    retcode: 0x6d00,
+                }),
+            }
+        }
+    }
+
+    /// Build a tx whose signing payload spans several chunks
+    fn large_tx() -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(b"code".to_vec(), None)
+            .add_serialized_data(vec![0xab; 3 * CHUNK_SIZE]);
+        tx
+    }
+
+    fn path() -> DerivationPath {
+        DerivationPath::default_for_scheme(SchemeType::Ed25519).unwrap()
+    }
+
+    /// Test that payloads are split into flagged chunks within the APDU limit
+    #[test]
+    fn test_chunk_commands() {
+        let data = vec![7; 2 * CHUNK_SIZE + 1];
+        let commands = chunk_commands(INS_SIGN, vec![1, 2], &data);
+        let flags: Vec<_> = commands.iter().map(|cmd| cmd.p1).collect();
+        assert_eq!(flags, vec![P1_INIT, P1_ADD, P1_ADD, P1_LAST]);
+        assert_eq!(commands[0].data, vec![1, 2]);
+        assert!(commands.iter().all(|cmd| cmd.data.len() <= CHUNK_SIZE));
+        let reassembled: Vec<u8> = commands[1..]
+            .iter()
+            .flat_map(|cmd| cmd.data.clone())
+            .collect();
+        assert_eq!(reassembled, data);
+    }
+
+    /// Test that the device reassembles the signing payload of the tx and that
+    /// its signature ends up in a signature section under its public key.
+    #[tokio::test]
+    async fn test_ledger_signer_signs_tx() {
+        let device = MockDevice::new(None);
+        let mut tx = large_tx();
+        let ledger = LedgerSigner::new(&device, path(), &tx).await.unwrap();
+        assert_eq!(ledger.public_key(), keypair_1().ref_to());
+
+        let pks_map = AccountPublicKeysMap::from_iter([ledger.public_key()]);
+        let payload = tx.signing_payload();
+        signer::sign_raw(&mut tx, &[&ledger], &pks_map, None)
+            .await
+            .unwrap();
+
+        // The device got the path, the target and then the whole payload
+        let buffer = device.buffer.borrow();
+        assert_eq!(&buffer[52..], &payload[..]);
+        let section = tx
+            .sections
+            .iter()
+            .find_map(|section| section.signature())
+            .expect("the tx should be signed");
+        assert_eq!(
+            section.signer,
+            namada_core::proto::Signer::PubKeys(vec![keypair_1().ref_to()])
+        );
+        assert_eq!(
+            Hash::try_from_slice(&buffer[20..52]).unwrap(),
+            section.get_raw_hash()
+        );
+        assert!(
+            common::SigScheme::verify_signature(
+                &keypair_1().ref_to(),
+                &section.get_raw_hash(),
+                &section.signatures[&0],
+            )
+            .is_ok()
+        );
+    }
+
+    /// Test that a rejection on the device aborts signing with a typed error
+    #[tokio::test]
+    async fn test_ledger_signer_rejection() {
+        let device = MockDevice::new(Some(0x6986));
+        let mut tx = large_tx();
+        let before = tx.to_bytes();
+        let ledger = LedgerSigner::new(&device, path(), &tx).await.unwrap();
+        let pks_map = AccountPublicKeysMap::from_iter([ledger.public_key()]);
+
+        let err = signer::sign_raw(&mut tx, &[&ledger], &pks_map, None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, Error::Ledger(LedgerError::UserRejected));
+        assert_eq!(tx.to_bytes(), before);
+    }
+
+    /// Test that the status words of the device map to the matching errors
+    #[test]
+    fn test_status_words() {
+        let answer = |retcode| ApduAnswer {
+            data: vec![],
+            retcode,
+        };
+        assert!(answer(SW_OK).check().is_ok());
+        assert_eq!(answer(0x6985).check(), Err(LedgerError::UserRejected));
+        assert_eq!(answer(0x6e00).check(), Err(LedgerError::WrongApp));
+        assert_eq!(answer(0x5515).check(), Err(LedgerError::DeviceLocked));
+        assert_eq!(answer(0x6a80).check(), Err(LedgerError::Device(0x6a80)));
+    }
+}
@@ -0,0 +1,471 @@
+//! Abstraction over the entities able to produce transaction signatures.
+//!
+//! Signing a transaction only requires a signature over a section commitment
+//! hash, so the secret key material does not need to be held in memory. This
+//! allows transactions to be signed by external devices such as hardware
+//! wallets, remote key management services or threshold signers.
+
+use std::collections::BTreeMap;
+
+use namada_core::proto::{Section, Signature, Signer as SignatureSigner, Tx};
+use namada_core::types::account::AccountPublicKeysMap;
+use namada_core::types::address::Address;
+use namada_core::types::hash::Hash;
+use namada_core::types::key::*;
+use namada_core::types::storage::Epoch;
+use namada_core::types::transaction::{Fee, GasLimit, TxType};
+
+use crate::error::{Error, TxError};
+
+pub mod ledger;
+pub mod musig;
+pub mod threshold;
+
+/// An entity able to sign a commitment hash on behalf of a public key
+#[async_trait::async_trait(?Send)]
+pub trait Signer {
+    /// Produce a signature over the given target hash
+    async fn sign(&self, target: &Hash) -> Result<common::Signature, Error>;
+
+    /// The public key against which the produced signatures verify
+    fn public_key(&self) -> common::PublicKey;
+}
+
+/// A signer holding its secret key in memory
+#[derive(Clone)]
+pub struct SoftwareSigner {
+    secret_key: common::SecretKey,
+}
+
+impl SoftwareSigner {
+    /// Wrap the given secret key into a signer
+    pub fn new(secret_key: common::SecretKey) -> Self {
+        Self { secret_key }
+    }
+}
+
+impl From<common::SecretKey> for SoftwareSigner {
+    fn from(secret_key: common::SecretKey) -> Self {
+        Self::new(secret_key)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Signer for SoftwareSigner {
+    async fn sign(&self, target: &Hash) -> Result<common::Signature, Error> {
+        Ok(common::SigScheme::sign(&self.secret_key, target))
+    }
+
+    fn public_key(&self) -> common::PublicKey {
+        self.secret_key.ref_to()
+    }
+}
+
+/// Build a signature section over the given targets with the given indexed
+/// signers. This mirrors [`Signature::new`], except that the signatures are
+/// requested from the signers instead of being computed from secret keys.
+pub async fn make_signature_section<S: Signer + ?Sized>(
+    targets: Vec<Hash>,
+    signers: BTreeMap<u8, &S>,
+    owner: Option<Address>,
+) -> Result<Signature, Error> {
+    // If no owner address is given, then the signers' public keys are listed
+    // in the section instead
+    let signer = if let Some(addr) = owner {
+        SignatureSigner::Address(addr)
+    } else {
+        if !signers.keys().cloned().eq(0..(signers.len() as u8)) {
+            return Err(Error::Other(
+                "signers must be enumerated when the owner is absent"
+                    .to_string(),
+            ));
+        }
+        SignatureSigner::PubKeys(
+            signers.values().map(|signer| signer.public_key()).collect(),
+        )
+    };
+    let mut section = Signature {
+        targets,
+        signer,
+        signatures: BTreeMap::new(),
+    };
+    // Every signer commits to the same hash of the section
+    let target = section.get_raw_hash();
+    for (index, signer) in signers {
+        let signature =
+            signer.sign(&target).await.map_err(|err| match err {
+                // Device errors are kept as is so that they can be acted upon
+                Error::Ledger(_) => err,
+                _ => TxError::SignerFailed(
+                    signer.public_key().to_string(),
+                    err.to_string(),
+                )
+                .into(),
+            })?;
+        section.signatures.insert(index, signature);
+    }
+    Ok(section)
+}
+
+/// Sign the raw header of the given tx with the signers whose public keys
+/// belong to the given account. Signers that are not part of the account are
+/// ignored. The tx is left untouched if any of the signers fails.
+pub async fn sign_raw<S: Signer + ?Sized>(
+    tx: &mut Tx,
+    signers: &[&S],
+    account_public_keys_map: &AccountPublicKeysMap,
+    owner: Option<Address>,
+) -> Result<(), Error> {
+    // The inner tx signer signs the Decrypted version of the Header
+    let hashes = vec![tx.raw_header_hash()];
+    let indexed = signers
+        .iter()
+        .filter_map(|signer| {
+            account_public_keys_map
+                .get_index_from_public_key(&signer.public_key())
+                .map(|index| (index, *signer))
+        })
+        .collect();
+    let section = make_signature_section(hashes, indexed, owner).await?;
+    tx.protocol_filter();
+    tx.add_section(Section::Signature(section));
+    Ok(())
+}
+
+/// Sign the wrapper header and all the sections of the given tx with the given
+/// fee payer signer. The tx is left untouched if the signer fails.
+pub async fn sign_wrapper<S: Signer + ?Sized>(
+    tx: &mut Tx,
+    signer: &S,
+) -> Result<(), Error> {
+    let mut filtered = tx.clone();
+    filtered.protocol_filter();
+    let section = make_signature_section(
+        filtered.sechashes(),
+        [(0, signer)].into_iter().collect(),
+        None,
+    )
+    .await?;
+    *tx = filtered;
+    tx.add_section(Section::Signature(section));
+    Ok(())
+}
+
+/// Check that the sections committed to by the header of the given tx and by
+/// its signatures are all present. The code and data sections are mandatory.
+fn check_commitments(tx: &Tx) -> Result<(), Error> {
+    let header = tx.header();
+    for (name, hash, required) in [
+        ("code", header.code_hash, true),
+        ("data", header.data_hash, true),
+        ("memo", header.memo_hash, false),
+    ] {
+        let unset = hash == Hash::default();
+        if (required && unset) || (!unset && tx.get_section(&hash).is_none()) {
+            return Err(
+                TxError::MissingTxSection(name.to_string(), hash).into()
+            );
+        }
+    }
+    for section in &tx.sections {
+        let targets = match section {
+            Section::Signature(signature) => &signature.targets,
+            Section::AggregatedSignature(aggregated) => &aggregated.targets,
+            _ => continue,
+        };
+        for target in targets {
+            if tx.get_section(target).is_none() {
+                return Err(TxError::MissingTxSection(
+                    "signed".to_string(),
+                    *target,
+                )
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Wrap the given raw tx, whose code and data sections and author signatures
+/// are already in place, into a wrapper paying the given fee. The wrapper
+/// header and all the sections are then signed with the fee payer's signer.
+/// Refuses to wrap a tx whose commitments do not resolve to its sections.
+pub async fn wrap_tx(
+    mut inner: Tx,
+    fee: Fee,
+    gas_limit: GasLimit,
+    epoch: Epoch,
+    fee_signer: &dyn Signer,
+) -> Result<Tx, Error> {
+    if !matches!(inner.header.tx_type, TxType::Raw) {
+        return Err(TxError::NotARawTx.into());
+    }
+    check_commitments(&inner)?;
+    inner.add_wrapper(fee, fee_signer.public_key(), epoch, gas_limit, None);
+    sign_wrapper(&mut inner, fee_signer).await?;
+    Ok(inner)
+}
+
+/// Re-seal the given wrapper tx for the given epoch: the wrapper header is
+/// updated and re-signed with the fee payer's signer, in place of its former
+/// signature. The author signatures, which only commit to the raw header and
+/// thus not to the wrapper, remain valid. The tx is left untouched if the
+/// signer fails.
+pub async fn reseal_wrapper<S: Signer + ?Sized>(
+    tx: &mut Tx,
+    epoch: Epoch,
+    fee_signer: &S,
+) -> Result<(), Error> {
+    let mut wrapper = match &tx.header.tx_type {
+        TxType::Wrapper(wrapper) => wrapper.clone(),
+        _ => return Err(TxError::NotAWrapperTx.into()),
+    };
+    if wrapper.pk != fee_signer.public_key() {
+        return Err(TxError::FeePayerMismatch(
+            wrapper.pk.to_string(),
+            fee_signer.public_key().to_string(),
+        )
+        .into());
+    }
+    let stale_header_hash = tx.header_hash();
+    let mut resealed = tx.clone();
+    resealed.sections.retain(|section| {
+        !matches!(
+            section,
+            Section::Signature(signature)
+                if signature.targets.contains(&stale_header_hash)
+        )
+    });
+    wrapper.epoch = epoch;
+    resealed.update_header(TxType::Wrapper(wrapper));
+    sign_wrapper(&mut resealed, fee_signer).await?;
+    *tx = resealed;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use assert_matches::assert_matches;
+    use namada_core::types::address::nam;
+    use namada_core::types::chain::ChainId;
+    use namada_core::types::key::testing::{keypair_1, keypair_2};
+    use namada_core::types::token;
+
+    use super::*;
+
+    /// A signer that records the targets it is asked to sign
+    struct RecordingSigner {
+        inner: SoftwareSigner,
+        targets: RefCell<Vec<Hash>>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl Signer for RecordingSigner {
+        async fn sign(
+            &self,
+            target: &Hash,
+        ) -> Result<common::Signature, Error> {
+            self.targets.borrow_mut().push(*target);
+            self.inner.sign(target).await
+        }
+
+        fn public_key(&self) -> common::PublicKey {
+            self.inner.public_key()
+        }
+    }
+
+    /// A signer whose device always refuses to sign
+    struct FailingSigner(common::PublicKey);
+
+    #[async_trait::async_trait(?Send)]
+    impl Signer for FailingSigner {
+        async fn sign(
+            &self,
+            _target: &Hash,
+        ) -> Result<common::Signature, Error> {
+            Err(Error::Other("device disconnected".to_string()))
+        }
+
+        fn public_key(&self) -> common::PublicKey {
+            self.0.clone()
+        }
+    }
+
+    fn unsigned_tx() -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(b"code".to_vec(), None)
+            .add_serialized_data(b"data".to_vec());
+        tx
+    }
+
+    /// Test that signing through the software signer produces the same
+    /// sections as signing with the secret keys directly.
+    #[tokio::test]
+    async fn test_software_signer_matches_secret_key_signing() {
+        let pks_map = AccountPublicKeysMap::from_iter([
+            keypair_1().ref_to(),
+            keypair_2().ref_to(),
+        ]);
+        let mut tx = unsigned_tx();
+        let mut expected = tx.clone();
+        expected
+            .sign_raw(vec![keypair_1(), keypair_2()], pks_map.clone(), None)
+            .sign_wrapper(keypair_1());
+
+        let signer_1 = SoftwareSigner::new(keypair_1());
+        let signer_2 = SoftwareSigner::new(keypair_2());
+        sign_raw(&mut tx, &[&signer_1, &signer_2], &pks_map, None)
+            .await
+            .unwrap();
+        sign_wrapper(&mut tx, &signer_1).await.unwrap();
+
+        assert_eq!(tx.to_bytes(), expected.to_bytes());
+    }
+
+    /// Test that the signers are asked to sign the signature section
+    /// commitments and that the resulting signatures verify.
+    #[tokio::test]
+    async fn test_signer_receives_section_commitments() {
+        let signer = RecordingSigner {
+            inner: SoftwareSigner::new(keypair_1()),
+            targets: RefCell::new(vec![]),
+        };
+        let pks_map = AccountPublicKeysMap::from_iter([signer.public_key()]);
+        let mut tx = unsigned_tx();
+        sign_raw(&mut tx, &[&signer], &pks_map, None).await.unwrap();
+        sign_wrapper(&mut tx, &signer).await.unwrap();
+
+        let sections: Vec<_> = tx
+            .sections
+            .iter()
+            .filter_map(|section| section.signature())
+            .collect();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].targets, vec![tx.raw_header_hash()]);
+        let expected_targets: Vec<_> = sections
+            .iter()
+            .map(|section| section.get_raw_hash())
+            .collect();
+        assert_eq!(*signer.targets.borrow(), expected_targets);
+        for section in sections {
+            assert!(
+                common::SigScheme::verify_signature(
+                    &signer.public_key(),
+                    &section.get_raw_hash(),
+                    &section.signatures[&0],
+                )
+                .is_ok()
+            );
+        }
+    }
+
+    /// Test that a failing signer aborts signing and leaves the tx untouched
+    #[tokio::test]
+    async fn test_failing_signer_aborts_signing() {
+        let good = SoftwareSigner::new(keypair_1());
+        let bad = FailingSigner(keypair_2().ref_to());
+        let pks_map =
+            AccountPublicKeysMap::from_iter([good.public_key(), bad.0.clone()]);
+        let mut tx = unsigned_tx();
+        let before = tx.to_bytes();
+
+        let signers: [&dyn Signer; 2] = [&good, &bad];
+        let err = sign_raw(&mut tx, &signers, &pks_map, None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, Error::Tx(TxError::SignerFailed(_, _)));
+        assert_eq!(tx.to_bytes(), before);
+
+        let err = sign_wrapper(&mut tx, &bad).await.unwrap_err();
+        assert_matches!(err, Error::Tx(TxError::SignerFailed(_, _)));
+        assert_eq!(tx.to_bytes(), before);
+    }
+
+    fn fee() -> Fee {
+        Fee {
+            amount_per_gas_unit: token::Amount::native_whole(1),
+            token: nam(),
+        }
+    }
+
+    /// Test that a signed inner tx is wrapped without invalidating the
+    /// author's signature and that the fee payer signs the whole wrapper.
+    #[tokio::test]
+    async fn test_wrap_signed_tx() {
+        let author = SoftwareSigner::new(keypair_1());
+        let fee_payer = SoftwareSigner::new(keypair_2());
+        let pks_map = AccountPublicKeysMap::from_iter([author.public_key()]);
+        let mut inner = unsigned_tx();
+        inner.add_memo(b"memo");
+        sign_raw(&mut inner, &[&author], &pks_map, None)
+            .await
+            .unwrap();
+        let raw_header_hash = inner.raw_header_hash();
+
+        let wrapper = wrap_tx(
+            inner,
+            fee(),
+            GasLimit::from(100_000),
+            Epoch(0),
+            &fee_payer,
+        )
+        .await
+        .unwrap();
+
+        let TxType::Wrapper(header) = &wrapper.header.tx_type else {
+            panic!("the tx should be a wrapper");
+        };
+        assert_eq!(header.pk, fee_payer.public_key());
+        assert_eq!(wrapper.raw_header_hash(), raw_header_hash);
+        assert!(wrapper.validate_tx().unwrap().is_some());
+        assert!(
+            wrapper
+                .verify_signatures(
+                    &[raw_header_hash],
+                    pks_map,
+                    &None,
+                    1,
+                    None,
+                    || Ok(()),
+                )
+                .is_ok()
+        );
+    }
+
+    /// Test that txs without code or data sections are not wrapped
+    #[tokio::test]
+    async fn test_wrap_tx_without_data() {
+        let fee_payer = SoftwareSigner::new(keypair_2());
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(b"code".to_vec(), None);
+
+        let err =
+            wrap_tx(tx, fee(), GasLimit::from(100_000), Epoch(0), &fee_payer)
+                .await
+                .unwrap_err();
+        assert_matches!(
+            err,
+            Error::Tx(TxError::MissingTxSection(name, _)) if name == "data"
+        );
+    }
+
+    /// Test that txs whose commitments do not resolve are not wrapped
+    #[tokio::test]
+    async fn test_wrap_tx_with_dangling_commitment() {
+        let fee_payer = SoftwareSigner::new(keypair_2());
+        let mut tx = unsigned_tx();
+        let dangling = Hash::sha256(b"missing memo");
+        tx.set_memo_sechash(dangling);
+
+        let err =
+            wrap_tx(tx, fee(), GasLimit::from(100_000), Epoch(0), &fee_payer)
+                .await
+                .unwrap_err();
+        assert_matches!(
+            err,
+            Error::Tx(TxError::MissingTxSection(_, hash)) if hash == dangling
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Two-round MuSig2 signing sessions, producing a single aggregated signature
+//! section on behalf of an ordered set of secp256k1 keys.
+//!
+//! A coordinator opens a [`SigningSession`] over the raw header of a tx and
+//! shares it with the participants. In the first round, every participant
+//! commits to fresh nonces with [`SigningSession::commit`], keeping the
+//! secret nonce for the second round. Once the session holds the public
+//! nonces of all the participants, each of them signs with
+//! [`SigningSession::partial_sign`]. The partial signatures are checked as
+//! they are added, and [`SigningSession::finalize`] aggregates them into the
+//! section to attach to the tx.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::proto::{AggregatedSignature, Tx};
+use namada_core::types::hash::Hash;
+use namada_core::types::key::musig::{
+    KeyAggregation, MusigError, PartialSignature, PublicNonce, SecretNonce,
+    SessionContext,
+};
+use namada_core::types::key::{secp256k1, RefTo};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// The state of a MuSig2 signing session, shared between its participants
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct SigningSession {
+    /// The hashes of the sections being signed
+    targets: Vec<Hash>,
+    /// The ordered public keys of the participants
+    participants: Vec<secp256k1::PublicKey>,
+    /// The public nonces collected in the first round, by participant
+    nonces: BTreeMap<u8, PublicNonce>,
+    /// The partial signatures collected in the second round, by participant
+    partials: BTreeMap<u8, PartialSignature>,
+}
+
+impl SigningSession {
+    /// Open a session for the given participants to sign the raw header of
+    /// the given tx
+    pub fn new(
+        tx: &Tx,
+        participants: Vec<secp256k1::PublicKey>,
+    ) -> Result<Self, Error> {
+        KeyAggregation::new(&participants)?;
+        if participants.len() > usize::from(u8::MAX) {
+            return Err(Error::Other(
+                "too many participants in the signing session".to_string(),
+            ));
+        }
+        Ok(Self {
+            targets: vec![tx.raw_header_hash()],
+            participants,
+            nonces: BTreeMap::new(),
+            partials: BTreeMap::new(),
+        })
+    }
+
+    /// The hash signed over by the participants
+    pub fn signing_hash(&self) -> Hash {
+        AggregatedSignature::signing_hash(&self.targets, &self.participants)
+    }
+
+    /// The participants that have yet to take part in the current round
+    pub fn pending(&self) -> Vec<&secp256k1::PublicKey> {
+        let round: Vec<u8> = if self.nonces.len() < self.participants.len() {
+            self.nonces.keys().copied().collect()
+        } else {
+            self.partials.keys().copied().collect()
+        };
+        self.participants
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !round.contains(&(*index as u8)))
+            .map(|(_, pk)| pk)
+            .collect()
+    }
+
+    fn index_of(&self, pk: &secp256k1::PublicKey) -> Result<u8, Error> {
+        self.participants
+            .iter()
+            .position(|participant| participant == pk)
+            .map(|index| index as u8)
+            .ok_or_else(|| MusigError::UnknownParticipant(pk.clone()).into())
+    }
+
+    /// Record the public nonce of the given participant
+    pub fn add_nonce(
+        &mut self,
+        pk: &secp256k1::PublicKey,
+        nonce: PublicNonce,
+    ) -> Result<(), Error> {
+        let index = self.index_of(pk)?;
+        if !self.partials.is_empty() {
+            return Err(Error::Other(
+                "the nonces of a signing session cannot change after partial \
+                 signatures were made"
+                    .to_string(),
+            ));
+        }
+        self.nonces.insert(index, nonce);
+        Ok(())
+    }
+
+    /// Take part in the first round with fresh nonces. The returned secret
+    /// nonce must be kept for the second round, and used for this session
+    /// only.
+    pub fn commit(
+        &mut self,
+        secret_key: &secp256k1::SecretKey,
+    ) -> Result<SecretNonce, Error> {
+        let (secret_nonce, public_nonce) = SecretNonce::generate(&mut OsRng);
+        self.add_nonce(&secret_key.ref_to(), public_nonce)?;
+        Ok(secret_nonce)
+    }
+
+    /// The context of the second round, available once all the public
+    /// nonces are known
+    fn context(&self) -> Result<SessionContext, Error> {
+        let keys = KeyAggregation::new(&self.participants)?;
+        let nonces = self.nonces.values().cloned().collect();
+        Ok(SessionContext::new(keys, nonces, &self.signing_hash())?)
+    }
+
+    /// Take part in the second round, consuming the secret nonce committed to
+    /// in the first round
+    pub fn partial_sign(
+        &mut self,
+        secret_key: &secp256k1::SecretKey,
+        secret_nonce: SecretNonce,
+    ) -> Result<(), Error> {
+        let partial = self.context()?.partial_sign(secret_key, secret_nonce)?;
+        self.add_partial_signature(&secret_key.ref_to(), partial)
+    }
+
+    /// Check and record the partial signature of the given participant
+    pub fn add_partial_signature(
+        &mut self,
+        pk: &secp256k1::PublicKey,
+        partial: PartialSignature,
+    ) -> Result<(), Error> {
+        let index = self.index_of(pk)?;
+        self.context()?.verify_partial(index.into(), &partial)?;
+        self.partials.insert(index, partial);
+        Ok(())
+    }
+
+    /// Aggregate the partial signatures of all the participants into a
+    /// signature section
+    pub fn finalize(&self) -> Result<AggregatedSignature, Error> {
+        let partials: Vec<_> = self.partials.values().cloned().collect();
+        let signature = self.context()?.aggregate(&partials)?;
+        Ok(AggregatedSignature {
+            targets: self.targets.clone(),
+            participants: self.participants.clone(),
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use namada_core::proto::Section;
+    use namada_core::types::account::AccountPublicKeysMap;
+    use namada_core::types::chain::ChainId;
+    use namada_core::types::key::common;
+    use namada_core::types::key::testing::gen_keypair;
+
+    use super::*;
+
+    /// Pass a session through JSON, as it would be between the participants
+    fn through_json(session: &SigningSession) -> SigningSession {
+        serde_json::from_str(&serde_json::to_string(session).unwrap()).unwrap()
+    }
+
+    /// Check that three participants produce a section satisfying the
+    /// threshold of an account made of their keys.
+    #[test]
+    fn test_three_party_session() {
+        let secret_keys: Vec<_> = (0..3)
+            .map(|_| gen_keypair::<secp256k1::SigScheme>())
+            .collect();
+        let participants: Vec<_> =
+            secret_keys.iter().map(|sk| sk.ref_to()).collect();
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1], None).add_serialized_data(vec![2]);
+
+        let mut session =
+            SigningSession::new(&tx, participants.clone()).unwrap();
+        assert!(session.finalize().is_err());
+        let mut secret_nonces = vec![];
+        for sk in &secret_keys {
+            let mut copy = through_json(&session);
+            secret_nonces.push(copy.commit(sk).unwrap());
+            session = copy;
+        }
+        assert_eq!(session.pending(), participants.iter().collect::<Vec<_>>());
+        for (sk, nonce) in secret_keys.iter().zip(secret_nonces) {
+            let mut copy = through_json(&session);
+            copy.partial_sign(sk, nonce).unwrap();
+            session = copy;
+        }
+        assert!(session.pending().is_empty());
+        let section = session.finalize().unwrap();
+        assert!(section.verify_signature().is_ok());
+
+        tx.add_section(Section::AggregatedSignature(section));
+        let pks_map = AccountPublicKeysMap::from_iter(
+            participants
+                .iter()
+                .cloned()
+                .map(common::PublicKey::Secp256k1),
+        );
+        assert!(
+            tx.verify_signatures(
+                &[tx.raw_header_hash()],
+                pks_map.clone(),
+                &None,
+                3,
+                None,
+                || Ok(()),
+            )
+            .is_ok()
+        );
+        assert!(
+            tx.verify_signatures(
+                &[tx.raw_header_hash()],
+                pks_map,
+                &None,
+                3,
+                Some(2),
+                || Ok(()),
+            )
+            .is_err()
+        );
+    }
+
+    /// Check that a participant substituting their key, in their partial
+    /// signature or in the final section, is caught.
+    #[test]
+    fn test_substituted_participant() {
+        let secret_keys: Vec<_> = (0..3)
+            .map(|_| gen_keypair::<secp256k1::SigScheme>())
+            .collect();
+        let participants: Vec<_> =
+            secret_keys.iter().map(|sk| sk.ref_to()).collect();
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1], None).add_serialized_data(vec![2]);
+
+        let mut session =
+            SigningSession::new(&tx, participants.clone()).unwrap();
+        let secret_nonces: Vec<_> = secret_keys
+            .iter()
+            .map(|sk| session.commit(sk).unwrap())
+            .collect();
+        let mut secret_nonces = secret_nonces.into_iter();
+
+        // An outsider cannot sign in place of a participant
+        let outsider = gen_keypair::<secp256k1::SigScheme>();
+        let partial = session
+            .context()
+            .unwrap()
+            .partial_sign(&secret_keys[0], secret_nonces.next().unwrap())
+            .unwrap();
+        assert!(
+            session
+                .add_partial_signature(&participants[1], partial.clone())
+                .is_err()
+        );
+        assert!(
+            session
+                .partial_sign(&outsider, SecretNonce::generate(&mut OsRng).0)
+                .is_err()
+        );
+        session
+            .add_partial_signature(&participants[0], partial)
+            .unwrap();
+        for (sk, nonce) in secret_keys[1..].iter().zip(secret_nonces) {
+            session.partial_sign(sk, nonce).unwrap();
+        }
+
+        // Swapping a participant's key in the section voids the signature
+        let mut section = session.finalize().unwrap();
+        section.participants[2] = outsider.ref_to();
+        assert!(section.verify_signature().is_err());
+        tx.add_section(Section::AggregatedSignature(section));
+        let pks_map = AccountPublicKeysMap::from_iter(
+            [
+                participants[0].clone(),
+                participants[1].clone(),
+                outsider.ref_to(),
+            ]
+            .into_iter()
+            .map(common::PublicKey::Secp256k1),
+        );
+        assert!(
+            tx.verify_signatures(
+                &[tx.raw_header_hash()],
+                pks_map,
+                &None,
+                1,
+                None,
+                || Ok(()),
+            )
+            .is_err()
+        );
+    }
+}
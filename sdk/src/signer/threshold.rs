@@ -0,0 +1,305 @@
+//! Threshold signing sessions, collecting the signatures of the members of a
+//! multisig account over the targets of a tx.
+//!
+//! A coordinator opens a [`SigningSession`] over a tx on behalf of an account
+//! and hands out the hashes to sign, as given by
+//! [`SigningSession::signing_hash`], to the members of the account. The
+//! [`Contribution`]s sent back are checked as they are added to the session,
+//! which can be persisted between rounds. Once enough members contributed
+//! to every target, [`SigningSession::finalize`] yields the signature
+//! sections to attach to the tx.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::proto::{Signature, Signer, Tx};
+use namada_core::types::account::AccountPublicKeysMap;
+use namada_core::types::address::Address;
+use namada_core::types::hash::Hash;
+use namada_core::types::key::{common, RefTo, SigScheme};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, TxError};
+
+/// A signature by a member of an account over one of the targets of a
+/// signing session
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct Contribution {
+    /// The target signed over
+    pub target: Hash,
+    /// The public key of the contributing member
+    pub pubkey: common::PublicKey,
+    /// The signature over the signing hash of the target
+    pub signature: common::Signature,
+}
+
+/// The state of a threshold signing session
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct SigningSession {
+    /// The account on behalf of which the tx is signed
+    owner: Address,
+    /// The public keys of the members of the account, by index
+    public_keys: Vec<common::PublicKey>,
+    /// The hashes of the sections that must be signed
+    targets: Vec<Hash>,
+    /// The signatures collected so far for each target, by key index
+    signatures: Vec<BTreeMap<u8, common::Signature>>,
+}
+
+impl SigningSession {
+    /// Open a session for the members of the given account to sign the raw
+    /// header of the given tx, as inner tx signers do
+    pub fn new(
+        tx: &Tx,
+        owner: Address,
+        public_keys_map: &AccountPublicKeysMap,
+    ) -> Self {
+        let public_keys = (0..=u8::MAX)
+            .map_while(|index| public_keys_map.get_public_key_from_index(index))
+            .collect();
+        let targets = vec![tx.raw_header_hash()];
+        Self {
+            owner,
+            public_keys,
+            signatures: vec![BTreeMap::new(); targets.len()],
+            targets,
+        }
+    }
+
+    /// The hashes of the sections that must be signed
+    pub fn targets(&self) -> &[Hash] {
+        &self.targets
+    }
+
+    /// The hash that the members sign to contribute to the given target
+    pub fn signing_hash(&self, target: &Hash) -> Hash {
+        Signature {
+            targets: vec![*target],
+            signer: Signer::Address(self.owner.clone()),
+            signatures: BTreeMap::new(),
+        }
+        .get_raw_hash()
+    }
+
+    /// Sign the given target with the given key
+    pub fn sign(
+        &self,
+        target: &Hash,
+        secret_key: &common::SecretKey,
+    ) -> Contribution {
+        Contribution {
+            target: *target,
+            pubkey: secret_key.ref_to(),
+            signature: common::SigScheme::sign(
+                secret_key,
+                self.signing_hash(target),
+            ),
+        }
+    }
+
+    /// Check and record the given contribution. Contributions to unknown
+    /// targets, by keys not registered to the account, with invalid
+    /// signatures, or already made, are rejected.
+    pub fn add_contribution(
+        &mut self,
+        contribution: Contribution,
+    ) -> Result<(), Error> {
+        let target = self
+            .targets
+            .iter()
+            .position(|target| *target == contribution.target)
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "{} is not a target of the signing session",
+                    contribution.target
+                ))
+            })?;
+        let index = self
+            .public_keys
+            .iter()
+            .position(|pk| *pk == contribution.pubkey)
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "the key {} is not registered to the account {}",
+                    contribution.pubkey, self.owner
+                ))
+            })? as u8;
+        common::SigScheme::verify_signature(
+            &contribution.pubkey,
+            &self.signing_hash(&contribution.target),
+            &contribution.signature,
+        )
+        .map_err(|_| {
+            TxError::InvalidOfflineSignature(contribution.pubkey.to_string())
+        })?;
+        if self.signatures[target].contains_key(&index) {
+            return Err(Error::Other(format!(
+                "the key {} already signed the target {}",
+                contribution.pubkey, contribution.target
+            )));
+        }
+        self.signatures[target].insert(index, contribution.signature);
+        Ok(())
+    }
+
+    /// The targets paired with the keys that have yet to sign them
+    pub fn remaining(&self) -> Vec<(Hash, &common::PublicKey)> {
+        self.targets
+            .iter()
+            .zip(&self.signatures)
+            .flat_map(|(target, signatures)| {
+                self.public_keys
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| {
+                        !signatures.contains_key(&(*index as u8))
+                    })
+                    .map(|(_, pk)| (*target, pk))
+            })
+            .collect()
+    }
+
+    /// Check whether every target was signed by at least the given number of
+    /// keys
+    pub fn is_complete(&self, threshold: u8) -> bool {
+        self.signatures
+            .iter()
+            .all(|signatures| signatures.len() >= usize::from(threshold))
+    }
+
+    /// Make the signature sections of the collected signatures, one per
+    /// target. Fails if the session isn't complete for the given threshold.
+    pub fn finalize(&self, threshold: u8) -> Result<Vec<Signature>, Error> {
+        if !self.is_complete(threshold) {
+            return Err(Error::Other(format!(
+                "the signing session has not reached the threshold of {} \
+                 signatures",
+                threshold
+            )));
+        }
+        Ok(self
+            .targets
+            .iter()
+            .zip(&self.signatures)
+            .map(|(target, signatures)| Signature {
+                targets: vec![*target],
+                signer: Signer::Address(self.owner.clone()),
+                signatures: signatures.clone(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use namada_core::proto::Section;
+    use namada_core::types::address::testing::established_address_1;
+    use namada_core::types::chain::ChainId;
+    use namada_core::types::key::testing::gen_keypair;
+    use namada_core::types::key::{ed25519, secp256k1};
+
+    use super::*;
+
+    /// Pass a session through JSON, as it would be when persisted between
+    /// rounds
+    fn through_json(session: &SigningSession) -> SigningSession {
+        serde_json::from_str(&serde_json::to_string(session).unwrap()).unwrap()
+    }
+
+    /// Simulate a 3-of-5 ceremony with an invalid and a duplicate
+    /// contribution, ending in a tx that meets the threshold of the account.
+    #[test]
+    fn test_three_of_five_session() {
+        let secret_keys: Vec<common::SecretKey> = (0..5)
+            .map(|index| {
+                if index % 2 == 0 {
+                    common::SecretKey::Ed25519(
+                        gen_keypair::<ed25519::SigScheme>(),
+                    )
+                } else {
+                    common::SecretKey::Secp256k1(
+                        gen_keypair::<secp256k1::SigScheme>(),
+                    )
+                }
+            })
+            .collect();
+        let pks_map = AccountPublicKeysMap::from_iter(
+            secret_keys.iter().map(|sk| sk.ref_to()),
+        );
+        let owner = established_address_1();
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![1], None).add_serialized_data(vec![2]);
+
+        let mut session = SigningSession::new(&tx, owner.clone(), &pks_map);
+        assert_eq!(session.targets(), &[tx.raw_header_hash()]);
+        let target = session.targets()[0];
+        assert_eq!(session.remaining().len(), 5);
+
+        // An invalid contribution, signing the target itself
+        let mut invalid = session.sign(&target, &secret_keys[0]);
+        invalid.signature = common::SigScheme::sign(&secret_keys[0], target);
+        assert!(session.add_contribution(invalid).is_err());
+        // A contribution by an outsider
+        let outsider =
+            common::SecretKey::Ed25519(gen_keypair::<ed25519::SigScheme>());
+        let outsider = session.sign(&target, &outsider);
+        assert!(session.add_contribution(outsider).is_err());
+        // A contribution to another target
+        let other = session.sign(&Hash([1; 32]), &secret_keys[0]);
+        assert!(session.add_contribution(other).is_err());
+
+        for sk in &secret_keys[..2] {
+            let mut copy = through_json(&session);
+            copy.add_contribution(copy.sign(&target, sk)).unwrap();
+            session = copy;
+        }
+        // A duplicate contribution
+        let duplicate = session.sign(&target, &secret_keys[1]);
+        assert!(session.add_contribution(duplicate).is_err());
+        assert!(!session.is_complete(3));
+        assert!(session.finalize(3).is_err());
+
+        session
+            .add_contribution(session.sign(&target, &secret_keys[4]))
+            .unwrap();
+        assert!(session.is_complete(3));
+        assert!(!session.is_complete(4));
+        assert_eq!(
+            session.remaining(),
+            vec![
+                (target, &secret_keys[2].ref_to()),
+                (target, &secret_keys[3].ref_to())
+            ]
+        );
+
+        for section in session.finalize(3).unwrap() {
+            tx.add_section(Section::Signature(section));
+        }
+        assert!(
+            tx.verify_signatures(
+                &[tx.raw_header_hash()],
+                pks_map.clone(),
+                &Some(owner.clone()),
+                3,
+                None,
+                || Ok(()),
+            )
+            .is_ok()
+        );
+        assert!(
+            tx.verify_signatures(
+                &[tx.raw_header_hash()],
+                pks_map,
+                &Some(owner),
+                4,
+                None,
+                || Ok(()),
+            )
+            .is_err()
+        );
+    }
+}
@@ -1,21 +1,23 @@
 //! Functions to sign transactions
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Display;
 
 use borsh::BorshDeserialize;
 use borsh_ext::BorshSerializeExt;
-use data_encoding::HEXLOWER;
+use data_encoding::{HEXLOWER, HEXUPPER};
 use itertools::Itertools;
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::transaction::components::sapling::fees::{
     InputView, OutputView,
 };
+use namada_core::ledger::ibc::resolve_msg_sections;
 use namada_core::ledger::parameters::storage as parameter_storage;
 use namada_core::proto::SignatureIndex;
-use namada_core::types::account::AccountPublicKeysMap;
+use namada_core::types::account::{Account, AccountPublicKeysMap};
 use namada_core::types::address::{
     masp_tx_key, Address, ImplicitAddress, InternalAddress, MASP,
 };
+use namada_core::types::hash::Hash;
 use namada_core::types::key::*;
 use namada_core::types::masp::{ExtendedViewingKey, PaymentAddress};
 use namada_core::types::storage::Epoch;
@@ -28,7 +30,7 @@ use namada_core::types::transaction::governance::{
     InitProposalData, VoteProposalData,
 };
 use namada_core::types::transaction::pos::InitValidator;
-use namada_core::types::transaction::{pos, Fee};
+use namada_core::types::transaction::{pos, Fee, TxType};
 use prost::Message;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
@@ -45,8 +47,9 @@ use crate::ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use crate::ibc_proto::google::protobuf::Any;
 use crate::io::*;
 use crate::masp::make_asset_type;
-use crate::proto::{MaspBuilder, Section, Tx};
+use crate::proto::{Header, MaspBuilder, Section, Signature, Signer, Tx};
 use crate::rpc::validate_amount;
+use crate::signer::{self, SoftwareSigner};
 use crate::tx::{
     TX_BOND_WASM, TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
     TX_CHANGE_METADATA_WASM, TX_CLAIM_REWARDS_WASM,
@@ -58,7 +61,7 @@ use crate::tx::{
 };
 pub use crate::wallet::store::AddressVpType;
 use crate::wallet::{Wallet, WalletIo};
-use crate::{args, display_line, rpc, Namada};
+use crate::{args, display_line, edisplay_line, rpc, Namada};
 
 #[cfg(feature = "std")]
 /// Env. var specifying where to store signing test vectors
@@ -252,30 +255,37 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
     // Then try to sign the raw header with private keys in the software wallet
     if let Some(account_public_keys_map) = signing_data.account_public_keys_map
     {
-        let mut wallet = context.wallet_mut().await;
-        let signing_tx_keypairs = signing_data
-            .public_keys
-            .iter()
-            .filter_map(|public_key| {
-                if used_pubkeys.contains(public_key) {
-                    None
-                } else {
-                    match find_key_by_pk(*wallet, args, public_key) {
-                        Ok(secret_key) => {
-                            used_pubkeys.insert(public_key.clone());
-                            Some(secret_key)
+        let signing_tx_signers = {
+            // Release the wallet before handing the keys over to the signers
+            let mut wallet = context.wallet_mut().await;
+            signing_data
+                .public_keys
+                .iter()
+                .filter_map(|public_key| {
+                    if used_pubkeys.contains(public_key) {
+                        None
+                    } else {
+                        match find_key_by_pk(*wallet, args, public_key) {
+                            Ok(secret_key) => {
+                                used_pubkeys.insert(public_key.clone());
+                                Some(secret_key)
+                            }
+                            Err(_) => None,
                         }
-                        Err(_) => None,
                     }
-                }
-            })
-            .collect::<Vec<common::SecretKey>>();
-        if !signing_tx_keypairs.is_empty() {
-            tx.sign_raw(
-                signing_tx_keypairs,
-                account_public_keys_map,
+                })
+                .map(SoftwareSigner::new)
+                .collect::<Vec<_>>()
+        };
+        if !signing_tx_signers.is_empty() {
+            let signers = signing_tx_signers.iter().collect::<Vec<_>>();
+            signer::sign_raw(
+                tx,
+                &signers,
+                &account_public_keys_map,
                 signing_data.owner,
-            );
+            )
+            .await?;
         }
     }
 
@@ -301,11 +311,21 @@ pub async fn sign_tx<'a, F: std::future::Future<Output = Result<Tx, Error>>>(
         // Lock the wallet just long enough to extract a key from it without
         // interfering with the sign closure call
         let mut wallet = context.wallet_mut().await;
-        find_key_by_pk(*wallet, args, &signing_data.fee_payer)
+        let key = find_key_by_pk(*wallet, args, &signing_data.fee_payer);
+        // Persist the keys whose encryption got upgraded while looking them up
+        if let Err(err) = wallet.save_migrated() {
+            edisplay_line!(
+                context.io(),
+                "Failed to save the re-encrypted wallet keys: {}",
+                err
+            );
+        }
+        key
     };
     match key {
         Ok(fee_payer_keypair) => {
-            tx.sign_wrapper(fee_payer_keypair);
+            signer::sign_wrapper(tx, &SoftwareSigner::new(fee_payer_keypair))
+                .await?;
         }
         Err(_) => {
             *tx = sign(
@@ -671,7 +691,7 @@ pub async fn wrap_tx<'a, N: Namada<'a>>(
         let mut hasher = sha2::Sha256::new();
         section.hash(&mut hasher);
         tx.add_section(section);
-        namada_core::types::hash::Hash(hasher.finalize().into())
+        Hash(hasher.finalize().into())
     });
 
     tx.add_wrapper(
@@ -1415,12 +1435,15 @@ pub async fn to_ledger_vector<'a>(
         )
         .await;
     } else if code_sec.tag == Some(TX_IBC_WASM.to_string()) {
-        let any_msg = Any::decode(
-            tx.data()
-                .ok_or_else(|| Error::Other("Invalid Data".to_string()))?
-                .as_ref(),
-        )
-        .map_err(|x| Error::from(EncodingError::Conversion(x.to_string())))?;
+        let tx_data = tx
+            .data()
+            .ok_or_else(|| Error::Other("Invalid Data".to_string()))?;
+        let tx_data = resolve_msg_sections(tx, tx_data).map_err(|x| {
+            Error::from(EncodingError::Conversion(x.to_string()))
+        })?;
+        let any_msg = Any::decode(tx_data.as_ref()).map_err(|x| {
+            Error::from(EncodingError::Conversion(x.to_string()))
+        })?;
 
         tv.name = "IBC_0".to_string();
         tv.output.push("Type : IBC".to_string());
@@ -1769,3 +1792,555 @@ pub async fn to_ledger_vector<'a>(
     format_outputs(&mut tv.output_expert);
     Ok(tv)
 }
+
+/// A tx exported for signing on an offline machine. It holds the header that
+/// the inner tx signatures commit to, the section hashes committed to by that
+/// header and a human-readable summary of the tx, but none of the sections.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedTxFile {
+    /// The header of the exported tx
+    pub header: Header,
+    /// The hash of the raw header, over which the signatures are made
+    pub raw_header_hash: Hash,
+    /// The hash of the code section committed to by the header
+    pub code_hash: Hash,
+    /// The hash of the data section committed to by the header
+    pub data_hash: Hash,
+    /// The hash of the memo section committed to by the header, if any
+    pub memo_hash: Option<Hash>,
+    /// A summary of the tx for the signer to review
+    pub summary: Vec<String>,
+}
+
+/// Signatures made offline over a tx exported with [`export_unsigned_tx`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignaturesFile {
+    /// The hash of the raw header of the signed tx
+    pub raw_header_hash: Hash,
+    /// The signatures over the raw header
+    pub signatures: Vec<SignatureIndex>,
+}
+
+/// Get the hash that inner tx signatures over the given raw header hash sign
+fn raw_header_signature_target(raw_header_hash: Hash) -> Hash {
+    Signature {
+        targets: vec![raw_header_hash],
+        signer: Signer::PubKeys(vec![]),
+        signatures: BTreeMap::new(),
+    }
+    .get_raw_hash()
+}
+
+/// Export the given tx so that it can be signed on a machine without network
+/// access
+pub fn export_unsigned_tx(tx: &Tx) -> UnsignedTxFile {
+    let header = tx.header();
+    let raw_header_hash = tx.raw_header_hash();
+    let memo_hash =
+        (header.memo_hash != Hash::default()).then_some(header.memo_hash);
+    let code_tag = tx
+        .get_section(&header.code_hash)
+        .and_then(|section| section.code_sec())
+        .and_then(|code| code.tag);
+
+    let mut summary = vec![
+        format!("Chain ID : {}", header.chain_id),
+        format!("Timestamp : {}", header.timestamp.0),
+    ];
+    if let Some(expiration) = &header.expiration {
+        summary.push(format!("Expiration : {}", expiration.0));
+    }
+    summary.push(format!(
+        "Code : {}",
+        code_tag.unwrap_or_else(|| header.code_hash.to_string())
+    ));
+    if let Some(data) = tx.data() {
+        summary.push(format!("Data : {}", HEXLOWER.encode(&data)));
+    }
+    if let Some(memo) = tx.memo() {
+        summary.push(format!("Memo : {}", String::from_utf8_lossy(&memo)));
+    }
+    summary.push(format!("Signing hash : {}", raw_header_hash));
+
+    UnsignedTxFile {
+        raw_header_hash,
+        code_hash: header.code_hash,
+        data_hash: header.data_hash,
+        memo_hash,
+        summary,
+        header,
+    }
+}
+
+/// Sign an exported tx with the given keys. The signatures can be attached to
+/// the original tx with [`import_signatures`].
+pub fn sign_unsigned_tx(
+    unsigned_tx: &UnsignedTxFile,
+    secret_keys: &[common::SecretKey],
+) -> Result<SignaturesFile, Error> {
+    // Only sign over hashes that can be recomputed from the header
+    let mut raw_header = unsigned_tx.header.clone();
+    raw_header.tx_type = TxType::Raw;
+    let raw_header_hash = Section::Header(raw_header.clone()).get_hash();
+    if raw_header_hash != unsigned_tx.raw_header_hash {
+        return Err(Error::from(TxError::InvalidUnsignedTx(format!(
+            "the raw header hash is {}, but the header hashes to {}",
+            unsigned_tx.raw_header_hash, raw_header_hash
+        ))));
+    }
+    if unsigned_tx.code_hash != raw_header.code_hash
+        || unsigned_tx.data_hash != raw_header.data_hash
+        || unsigned_tx.memo_hash.unwrap_or_default() != raw_header.memo_hash
+    {
+        return Err(Error::from(TxError::InvalidUnsignedTx(
+            "the section hashes differ from those in the header".to_string(),
+        )));
+    }
+
+    let target = raw_header_signature_target(raw_header_hash);
+    let signatures = secret_keys
+        .iter()
+        .map(|secret_key| {
+            SignatureIndex::from_single_signature(
+                secret_key.ref_to(),
+                common::SigScheme::sign(secret_key, target),
+            )
+        })
+        .collect();
+    Ok(SignaturesFile {
+        raw_header_hash,
+        signatures,
+    })
+}
+
+/// Attach signatures made offline to the given tx. Fails if the signatures
+/// were made over a different tx, e.g. because the tx was modified after it
+/// was exported, or if any of them is invalid.
+pub fn import_signatures(
+    tx: &mut Tx,
+    signatures: SignaturesFile,
+) -> Result<(), Error> {
+    let raw_header_hash = tx.raw_header_hash();
+    if signatures.raw_header_hash != raw_header_hash {
+        return Err(Error::from(TxError::SignaturesTargetMismatch(
+            signatures.raw_header_hash,
+            raw_header_hash,
+        )));
+    }
+    // The signed header is only meaningful with the sections it commits to
+    let header = tx.header();
+    for (name, hash) in [
+        ("code", header.code_hash),
+        ("data", header.data_hash),
+        ("memo", header.memo_hash),
+    ] {
+        if hash != Hash::default() && tx.get_section(&hash).is_none() {
+            return Err(Error::from(TxError::MissingTxSection(
+                name.to_string(),
+                hash,
+            )));
+        }
+    }
+
+    let target = raw_header_signature_target(raw_header_hash);
+    for signature in &signatures.signatures {
+        common::SigScheme::verify_signature(
+            &signature.pubkey,
+            &target,
+            &signature.signature,
+        )
+        .map_err(|_| {
+            TxError::InvalidOfflineSignature(signature.pubkey.to_string())
+        })?;
+    }
+    tx.add_signatures(signatures.signatures);
+    Ok(())
+}
+
+/// The version of the multisig bundle format written by this client
+pub const MULTISIG_BUNDLE_VERSION: u32 = 1;
+
+/// A tx collecting the signatures of the members of a multisig account. The
+/// bundle is passed from co-signer to co-signer as a JSON file, each adding
+/// their signatures, until the copies are merged for submission.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultisigBundle {
+    /// The version of the bundle format
+    pub version: u32,
+    /// The account on behalf of which the tx is signed
+    pub owner: Address,
+    /// The hex encoded tx
+    pub tx: String,
+    /// The hash of the raw header of the tx, over which the signatures are
+    /// made
+    pub raw_header_hash: Hash,
+    /// A summary of the tx for the co-signers to review
+    pub summary: Vec<String>,
+    /// The signatures collected so far, at most one per public key
+    pub signatures: Vec<SignatureIndex>,
+}
+
+impl MultisigBundle {
+    /// Start collecting signatures over the given tx on behalf of the owner
+    pub fn new(tx: &Tx, owner: Address) -> Self {
+        let UnsignedTxFile {
+            raw_header_hash,
+            summary,
+            ..
+        } = export_unsigned_tx(tx);
+        Self {
+            version: MULTISIG_BUNDLE_VERSION,
+            owner,
+            tx: tx.serialize(),
+            raw_header_hash,
+            summary,
+            signatures: vec![],
+        }
+    }
+
+    /// Decode the tx of this bundle. Fails if the hash and summary of the
+    /// bundle are not those of the tx, or if a signature is invalid.
+    pub fn tx(&self) -> Result<Tx, Error> {
+        let invalid = |reason: String| {
+            Error::from(TxError::InvalidMultisigBundle(reason))
+        };
+        if self.version != MULTISIG_BUNDLE_VERSION {
+            return Err(invalid(format!(
+                "version {} is not supported",
+                self.version
+            )));
+        }
+        let tx = HEXUPPER
+            .decode(self.tx.as_bytes())
+            .ok()
+            .and_then(|bytes| Tx::try_from_slice(&bytes).ok())
+            .ok_or_else(|| invalid("the tx can't be decoded".to_string()))?;
+        // Co-signers review the summary, so it must describe what they sign
+        let UnsignedTxFile {
+            raw_header_hash,
+            summary,
+            ..
+        } = export_unsigned_tx(&tx);
+        if raw_header_hash != self.raw_header_hash {
+            return Err(invalid(format!(
+                "the raw header hash is {}, but the tx has raw header hash {}",
+                self.raw_header_hash, raw_header_hash
+            )));
+        }
+        if summary != self.summary {
+            return Err(invalid(
+                "the summary doesn't describe the tx".to_string(),
+            ));
+        }
+
+        let target = raw_header_signature_target(raw_header_hash);
+        for signature in &self.signatures {
+            common::SigScheme::verify_signature(
+                &signature.pubkey,
+                &target,
+                &signature.signature,
+            )
+            .map_err(|_| {
+                TxError::InvalidOfflineSignature(signature.pubkey.to_string())
+            })?;
+        }
+        Ok(tx)
+    }
+
+    /// Sign the tx of this bundle with the given keys, replacing previous
+    /// signatures by the same keys
+    pub fn sign(
+        &mut self,
+        secret_keys: &[common::SecretKey],
+    ) -> Result<(), Error> {
+        self.tx()?;
+        let target = raw_header_signature_target(self.raw_header_hash);
+        for secret_key in secret_keys {
+            self.add_signature(SignatureIndex::from_single_signature(
+                secret_key.ref_to(),
+                common::SigScheme::sign(secret_key, target),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Merge the signatures of copies of the same bundle. Fails if the copies
+    /// diverge in anything but their signatures.
+    pub fn merge(
+        bundles: impl IntoIterator<Item = MultisigBundle>,
+    ) -> Result<Self, Error> {
+        let mut bundles = bundles.into_iter();
+        let mut merged = bundles.next().ok_or_else(|| {
+            TxError::InvalidMultisigBundle("no bundle to merge".to_string())
+        })?;
+        let commitment = merged.commitment()?;
+        for bundle in bundles {
+            if bundle.commitment()? != commitment {
+                return Err(Error::from(TxError::ConflictingMultisigBundles(
+                    merged.raw_header_hash,
+                )));
+            }
+            for signature in bundle.signatures {
+                merged.add_signature(signature);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Attach the collected signatures to the tx on behalf of the given
+    /// on-chain account. Fails if the signatures by the account's keys don't
+    /// meet its threshold.
+    pub fn finalize(self, account: &Account) -> Result<Tx, Error> {
+        let mut tx = self.tx()?;
+        if account.address != self.owner {
+            return Err(Error::from(TxError::InvalidMultisigBundle(format!(
+                "the tx is signed on behalf of {}, not {}",
+                self.owner, account.address
+            ))));
+        }
+        let signatures: Vec<_> = self
+            .signatures
+            .into_iter()
+            .filter_map(|signature| {
+                let index =
+                    account.get_index_from_public_key(&signature.pubkey)?;
+                Some(SignatureIndex {
+                    index: Some((account.address.clone(), index)),
+                    ..signature
+                })
+            })
+            .collect();
+        if signatures.len() < usize::from(account.threshold) {
+            return Err(Error::from(TxError::MultisigThresholdNotMet(
+                account.address.clone(),
+                signatures.len(),
+                account.threshold,
+            )));
+        }
+        tx.add_signatures(signatures);
+        Ok(tx)
+    }
+
+    /// What the co-signers commit to: the owner, the raw header and all the
+    /// sections of the tx except signatures
+    fn commitment(&self) -> Result<(Address, Hash, BTreeSet<Hash>), Error> {
+        let sections = self
+            .tx()?
+            .sections
+            .iter()
+            .filter(|section| !matches!(section, Section::Signature(_)))
+            .map(Section::get_hash)
+            .collect();
+        Ok((self.owner.clone(), self.raw_header_hash, sections))
+    }
+
+    /// Add the given signature, replacing any other by the same key
+    fn add_signature(&mut self, signature: SignatureIndex) {
+        self.signatures
+            .retain(|existing| existing.pubkey != signature.pubkey);
+        self.signatures.push(signature);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::path::Path;
+
+    use assert_matches::assert_matches;
+    use namada_core::types::chain::ChainId;
+    use namada_core::types::address::testing::established_address_1;
+    use namada_core::types::key::testing::{keypair_1, keypair_2, keypair_3};
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::proto::Data;
+
+    /// Build an unsigned tx with code, data and a memo
+    fn unsigned_tx() -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code_from_hash(Hash::default(), Some(TX_TRANSFER_WASM.into()))
+            .add_serialized_data(b"transfer".to_vec())
+            .add_memo(b"offline");
+        tx
+    }
+
+    /// Write the given value to a JSON file and read it back
+    fn through_file<T: Serialize + DeserializeOwned>(
+        path: &Path,
+        value: &T,
+    ) -> T {
+        serde_json::to_writer_pretty(File::create(path).unwrap(), value)
+            .unwrap();
+        serde_json::from_reader(File::open(path).unwrap()).unwrap()
+    }
+
+    /// Test that a tx can be exported, signed offline and have the signatures
+    /// imported back, with everything passing through files on disk.
+    #[test]
+    fn test_offline_signing_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut tx = unsigned_tx();
+
+        let unsigned = through_file(
+            &dir.path().join("unsigned.json"),
+            &export_unsigned_tx(&tx),
+        );
+        assert_eq!(unsigned.raw_header_hash, tx.raw_header_hash());
+        assert!(
+            unsigned
+                .summary
+                .contains(&format!("Code : {}", TX_TRANSFER_WASM))
+        );
+        assert!(unsigned.summary.contains(&"Memo : offline".to_string()));
+
+        let signatures =
+            sign_unsigned_tx(&unsigned, &[keypair_1(), keypair_2()]).unwrap();
+        let signatures =
+            through_file(&dir.path().join("signatures.json"), &signatures);
+
+        import_signatures(&mut tx, signatures).unwrap();
+        for keypair in [keypair_1(), keypair_2()] {
+            tx.verify_signature(&keypair.ref_to(), &[tx.raw_header_hash()])
+                .unwrap();
+        }
+    }
+
+    /// Test that signatures are not imported into a tx whose data changed
+    /// after it was exported.
+    #[test]
+    fn test_import_signatures_into_tampered_tx() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut tx = unsigned_tx();
+
+        let unsigned = through_file(
+            &dir.path().join("unsigned.json"),
+            &export_unsigned_tx(&tx),
+        );
+        let signatures = sign_unsigned_tx(&unsigned, &[keypair_1()]).unwrap();
+        let signatures =
+            through_file(&dir.path().join("signatures.json"), &signatures);
+
+        tx.set_data(Data::new(b"tampered".to_vec()));
+        let sections = tx.sections.len();
+        assert_matches!(
+            import_signatures(&mut tx, signatures.clone()),
+            Err(Error::Tx(TxError::SignaturesTargetMismatch(signed, actual)))
+                if signed == unsigned.raw_header_hash
+                    && actual == tx.raw_header_hash()
+        );
+        assert_eq!(tx.sections.len(), sections);
+
+        // Claiming the tampered tx as the target doesn't help either
+        let forged = SignaturesFile {
+            raw_header_hash: tx.raw_header_hash(),
+            ..signatures
+        };
+        assert_matches!(
+            import_signatures(&mut tx, forged),
+            Err(Error::Tx(TxError::InvalidOfflineSignature(_)))
+        );
+    }
+
+    /// Test that an exported tx whose header doesn't match its hashes is not
+    /// signed.
+    #[test]
+    fn test_sign_inconsistent_unsigned_tx() {
+        let mut unsigned = export_unsigned_tx(&unsigned_tx());
+        unsigned.header.data_hash = Hash::default();
+        assert_matches!(
+            sign_unsigned_tx(&unsigned, &[keypair_1()]),
+            Err(Error::Tx(TxError::InvalidUnsignedTx(_)))
+        );
+    }
+
+    /// A 2-of-3 multisig account
+    fn multisig_account() -> Account {
+        Account {
+            public_keys_map: AccountPublicKeysMap::from_iter([
+                keypair_1().ref_to(),
+                keypair_2().ref_to(),
+                keypair_3().ref_to(),
+            ]),
+            threshold: 2,
+            address: established_address_1(),
+        }
+    }
+
+    /// Test a 2-of-3 multisig flow where each co-signer signs their own copy
+    /// of the bundle, with the copies then merged and checked against the
+    /// account's threshold.
+    #[test]
+    fn test_multisig_bundle_flow() {
+        let dir = tempfile::tempdir().unwrap();
+        let account = multisig_account();
+        let bundle = through_file(
+            &dir.path().join("bundle.json"),
+            &MultisigBundle::new(&unsigned_tx(), account.address.clone()),
+        );
+
+        let mut first = bundle.clone();
+        first.sign(&[keypair_1()]).unwrap();
+        let first = through_file(&dir.path().join("first.json"), &first);
+        // A single signature doesn't meet the threshold
+        assert_matches!(
+            first.clone().finalize(&account),
+            Err(Error::Tx(TxError::MultisigThresholdNotMet(_, 1, 2)))
+        );
+
+        let mut second = bundle;
+        second.sign(&[keypair_2()]).unwrap();
+        let second = through_file(&dir.path().join("second.json"), &second);
+
+        // Signatures in both copies are only counted once
+        let merged =
+            MultisigBundle::merge([first.clone(), second, first]).unwrap();
+        assert_eq!(merged.signatures.len(), 2);
+        let tx = merged.finalize(&account).unwrap();
+        tx.verify_signatures(
+            &[tx.raw_header_hash()],
+            account.public_keys_map,
+            &Some(account.address),
+            account.threshold,
+            None,
+            || Ok(()),
+        )
+        .unwrap();
+    }
+
+    /// Test that merging a bundle whose tx was tampered with is rejected.
+    #[test]
+    fn test_merge_tampered_multisig_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let account = multisig_account();
+        let mut bundle =
+            MultisigBundle::new(&unsigned_tx(), account.address.clone());
+        bundle.sign(&[keypair_1()]).unwrap();
+        let bundle = through_file(&dir.path().join("bundle.json"), &bundle);
+
+        // A section not committed to by the header was added, so the raw
+        // header hash and the signatures are unchanged
+        let mut tx = bundle.tx().unwrap();
+        tx.add_extra_section(b"tampered".to_vec(), None);
+        let mut tampered = MultisigBundle {
+            tx: tx.serialize(),
+            ..bundle.clone()
+        };
+        tampered.sign(&[keypair_2()]).unwrap();
+        let tampered =
+            through_file(&dir.path().join("tampered.json"), &tampered);
+        assert_matches!(
+            MultisigBundle::merge([bundle.clone(), tampered]),
+            Err(Error::Tx(TxError::ConflictingMultisigBundles(hash)))
+                if hash == bundle.raw_header_hash
+        );
+
+        // A summary that doesn't describe the tx is rejected outright
+        let mut misleading = bundle.clone();
+        misleading.summary.push("Amount : 1".to_string());
+        assert_matches!(
+            MultisigBundle::merge([bundle, misleading]),
+            Err(Error::Tx(TxError::InvalidMultisigBundle(_)))
+        );
+    }
+}
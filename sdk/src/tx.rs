@@ -2,10 +2,12 @@
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use borsh::BorshSerialize;
+use data_encoding::{BASE64, BASE64_NOPAD, HEXLOWER_PERMISSIVE};
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::transaction::builder;
 use masp_primitives::transaction::builder::Builder;
@@ -29,15 +31,18 @@ use namada_core::ledger::governance::cli::onchain::{
 };
 use namada_core::ledger::governance::storage::proposal::ProposalType;
 use namada_core::ledger::governance::storage::vote::StorageProposalVote;
+use namada_core::ledger::ibc::add_msg_sections;
 use namada_core::ledger::ibc::storage::channel_key;
+use namada_core::ledger::parameters::storage as parameter_storage;
 use namada_core::ledger::pgf::cli::steward::Commission;
 use namada_core::types::address::{Address, InternalAddress, MASP};
+use namada_core::types::chain::ChainId;
 use namada_core::types::dec::Dec;
 use namada_core::types::hash::Hash;
 use namada_core::types::ibc::IbcShieldedTransfer;
 use namada_core::types::key::*;
 use namada_core::types::masp::{TransferSource, TransferTarget};
-use namada_core::types::storage::Epoch;
+use namada_core::types::storage::{BlockHeight, Epoch};
 use namada_core::types::time::DateTimeUtc;
 use namada_core::types::token::MaspDenom;
 use namada_core::types::transaction::account::{InitAccount, UpdateAccount};
@@ -45,23 +50,26 @@ use namada_core::types::transaction::governance::{
     InitProposalData, VoteProposalData,
 };
 use namada_core::types::transaction::pgf::UpdateStewardCommission;
-use namada_core::types::transaction::pos;
+use namada_core::types::transaction::{hash_tx, pos, ResultCode, TxType};
 use namada_core::types::{storage, token};
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::types::{CommissionPair, ValidatorState};
+use serde::{Deserialize, Serialize};
 
 use crate::args::{self, InputAmount};
 use crate::control_flow::time;
 use crate::error::{EncodingError, Error, QueryError, Result, TxError};
+use crate::events::decode::TypedTxResponse;
 use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::io::Io;
 use crate::masp::TransferErr::Build;
 use crate::masp::{make_asset_type, ShieldedContext, ShieldedTransfer};
-use crate::proto::{MaspBuilder, Tx};
+use crate::proto::{MaspBuilder, Section, Tx, MAX_MEMO_LEN};
 use crate::queries::Client;
 use crate::rpc::{
-    self, query_wasm_code_hash, validate_amount, TxBroadcastData, TxResponse,
+    self, validate_amount, TxBroadcastData, TxChainContext, TxResponse,
 };
+use crate::signer::{self, Signer};
 use crate::signing::{self, SigningTxData, TxSourcePostBalance};
 use crate::tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use crate::tendermint_rpc::error::Error as RpcError;
@@ -86,6 +94,8 @@ pub const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
 pub const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
 /// Update validity predicate WASM path
 pub const TX_UPDATE_ACCOUNT_WASM: &str = "tx_update_account.wasm";
+/// Change account keys WASM path
+pub const TX_CHANGE_KEYS_WASM: &str = "tx_change_keys.wasm";
 /// Transfer transaction WASM path
 pub const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 /// IBC transaction WASM path
@@ -129,7 +139,7 @@ pub enum ProcessTxResponse {
     /// Result of submitting a transaction to the blockchain
     Applied(TxResponse),
     /// Result of submitting a transaction to the mempool
-    Broadcast(Response),
+    Broadcast(BroadcastResponse),
     /// Result of dry running transaction
     DryRun,
     /// Dump transaction to disk
@@ -146,6 +156,551 @@ impl ProcessTxResponse {
     }
 }
 
+/// The response of a node to the broadcast of a transaction, along with the
+/// hashes that can be used to follow the transaction on chain
+#[derive(Debug)]
+pub struct BroadcastResponse {
+    /// The `CheckTx` response of the node
+    pub response: Response,
+    /// The hash CometBFT computes over the broadcast bytes, as used by block
+    /// explorers and by its `tx_search` endpoint
+    pub tendermint_hash: Hash,
+    /// The header hash of the broadcast wrapper transaction
+    pub header_hash: Hash,
+}
+
+/// The identifiers under which a broadcast transaction can be followed on
+/// chain, along with the result of its initial `CheckTx` validation
+#[derive(Debug, Clone)]
+pub struct BroadcastReceipt {
+    /// The hash CometBFT computes over the broadcast bytes
+    pub tendermint_hash: Hash,
+    /// The header hash of the wrapper, under which its acceptance is reported
+    pub wrapper_hash: Hash,
+    /// The header hash of the decrypted inner transaction, under which its
+    /// application is reported
+    pub decrypted_hash: Hash,
+    /// The hash of the code section committed to by the header
+    pub code_hash: Hash,
+    /// The hash of the data section committed to by the header
+    pub data_hash: Hash,
+    /// The raw `CheckTx` result code
+    pub code: u32,
+    /// The `CheckTx` result code, if it is one known to the ledger
+    pub result_code: Option<ResultCode>,
+    /// The `CheckTx` log
+    pub log: String,
+}
+
+impl BroadcastReceipt {
+    /// Collect the identifiers of the given transaction along with the given
+    /// `CheckTx` result
+    pub fn new(tx: &Tx, code: u32, log: String) -> Self {
+        Self {
+            tendermint_hash: tx.tendermint_hash(),
+            wrapper_hash: tx.header_hash(),
+            decrypted_hash: tx.raw_header_hash(),
+            code_hash: *tx.code_sechash(),
+            data_hash: *tx.data_sechash(),
+            code,
+            result_code: ResultCode::try_from(code).ok(),
+            log,
+        }
+    }
+
+    /// Check whether the transaction passed `CheckTx`
+    pub fn is_ok(&self) -> bool {
+        self.result_code == Some(ResultCode::Ok)
+    }
+}
+
+/// Broadcast the given transaction and return the identifiers under which it
+/// can be followed on chain. A transaction rejected by `CheckTx` results in a
+/// [`TxError::CheckTxRejected`] error carrying the receipt.
+pub async fn broadcast_tx_with_receipt<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx: &Tx,
+) -> Result<BroadcastReceipt> {
+    let response =
+        lift_rpc_error(client.broadcast_tx_sync(tx.to_bytes()).await)?;
+    let receipt =
+        BroadcastReceipt::new(tx, response.code.value(), response.log);
+    if receipt.is_ok() {
+        Ok(receipt)
+    } else {
+        Err(TxError::CheckTxRejected(Box::new(receipt)).into())
+    }
+}
+
+/// How the broadcast of a wrapper tx is retried when it is rejected because
+/// its header went stale while the tx was being signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BroadcastRetryPolicy {
+    /// Maximum number of broadcasts of the tx, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first rebroadcast, doubled before each subsequent
+    /// one
+    pub backoff: time::Duration,
+}
+
+impl Default for BroadcastRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: time::Duration::from_secs(1),
+        }
+    }
+}
+
+/// Check whether the rejection of a wrapper built in `wrapper_epoch` can be
+/// blamed on its header having gone stale, given the current epoch. Expired
+/// txs are not deemed stale, as their expiration is part of the raw header
+/// that the author signatures commit to, hence it cannot be refreshed by
+/// re-sealing the wrapper.
+fn is_stale_rejection(
+    receipt: &BroadcastReceipt,
+    wrapper_epoch: Epoch,
+    current_epoch: Epoch,
+) -> bool {
+    wrapper_epoch < current_epoch
+        && matches!(
+            receipt.result_code,
+            Some(ResultCode::InvalidTx | ResultCode::FeeError)
+        )
+}
+
+/// Broadcast the given wrapper tx like [`broadcast_tx_with_receipt`]. If it
+/// is rejected because its epoch went stale, the wrapper is re-sealed for the
+/// current epoch with the fee payer's signer and rebroadcast, up to the
+/// number of attempts of the given policy. The author signatures are kept as
+/// they are. Rejections for other reasons are not retried. Returns the tx
+/// that was eventually accepted, along with its receipt.
+pub async fn broadcast_tx_with_retry<C, S>(
+    client: &C,
+    mut tx: Tx,
+    fee_signer: &S,
+    policy: &BroadcastRetryPolicy,
+) -> Result<(Tx, BroadcastReceipt)>
+where
+    C: crate::queries::Client + Sync,
+    S: Signer + ?Sized,
+{
+    let mut backoff = policy.backoff;
+    let mut attempt = 1;
+    loop {
+        let receipt = match broadcast_tx_with_receipt(client, &tx).await {
+            Ok(receipt) => return Ok((tx, receipt)),
+            Err(Error::Tx(TxError::CheckTxRejected(receipt)))
+                if attempt < policy.max_attempts =>
+            {
+                receipt
+            }
+            Err(err) => return Err(err),
+        };
+        let wrapper_epoch = match &tx.header.tx_type {
+            TxType::Wrapper(wrapper) => wrapper.epoch,
+            _ => return Err(TxError::NotAWrapperTx.into()),
+        };
+        let current_epoch = rpc::query_epoch(client).await?;
+        if !is_stale_rejection(&receipt, wrapper_epoch, current_epoch) {
+            return Err(TxError::CheckTxRejected(receipt).into());
+        }
+        tracing::debug!(
+            wrapper_hash = %receipt.wrapper_hash,
+            %wrapper_epoch,
+            %current_epoch,
+            attempt,
+            "The wrapper went stale, re-sealing it after {backoff:?}",
+        );
+        time::sleep(backoff).await;
+        backoff *= 2;
+        signer::reseal_wrapper(&mut tx, current_epoch, fee_signer).await?;
+        attempt += 1;
+    }
+}
+
+/// How long [`track_tx`] waits for each of the phases of a tx
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxTrackingTimeouts {
+    /// Maximum time to wait for the wrapper tx to be included in a block
+    pub wrapper: time::Duration,
+    /// Maximum time to wait for the inner tx to be applied, once its wrapper
+    /// has been included in a block
+    pub inner: time::Duration,
+    /// Delay between two queries of the events of the tx
+    pub poll_interval: time::Duration,
+}
+
+impl Default for TxTrackingTimeouts {
+    fn default() -> Self {
+        let timeout = time::Duration::from_secs(
+            DEFAULT_NAMADA_EVENTS_MAX_WAIT_TIME_SECONDS,
+        );
+        Self {
+            wrapper: timeout,
+            inner: timeout,
+            poll_interval: time::Duration::from_secs(1),
+        }
+    }
+}
+
+/// The combined outcome of the two phases of a tx tracked by [`track_tx`]:
+/// the inclusion of its wrapper in a block, then the application of its
+/// inner tx in one of the following blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackedTxOutcome {
+    /// The wrapper tx was not included in a block before the timeout
+    WrapperTimedOut,
+    /// The wrapper tx was included in a block but rejected, so its inner tx
+    /// will never be applied
+    WrapperRejected {
+        /// The response of the wrapper tx
+        wrapper: TypedTxResponse,
+    },
+    /// The wrapper tx was included in a block, but its inner tx was not
+    /// applied before the timeout
+    InnerTimedOut {
+        /// The response of the wrapper tx
+        wrapper: TypedTxResponse,
+    },
+    /// The inner tx could not be decrypted, so it was not applied
+    Undecryptable {
+        /// The response of the wrapper tx
+        wrapper: TypedTxResponse,
+        /// The response of the inner tx
+        inner: TypedTxResponse,
+    },
+    /// The inner tx was applied, successfully or not
+    InnerApplied {
+        /// The response of the wrapper tx
+        wrapper: TypedTxResponse,
+        /// The response of the inner tx
+        inner: TypedTxResponse,
+    },
+}
+
+impl TrackedTxOutcome {
+    /// Check whether the inner tx was successfully applied. An accepted
+    /// wrapper tx alone is not a success.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::InnerApplied { inner, .. } if inner.is_success())
+    }
+
+    /// The response of the wrapper tx, if it was included in a block
+    pub fn wrapper(&self) -> Option<&TypedTxResponse> {
+        match self {
+            Self::WrapperTimedOut => None,
+            Self::WrapperRejected { wrapper }
+            | Self::InnerTimedOut { wrapper }
+            | Self::Undecryptable { wrapper, .. }
+            | Self::InnerApplied { wrapper, .. } => Some(wrapper),
+        }
+    }
+
+    /// The response of the inner tx, if it was due in a block
+    pub fn inner(&self) -> Option<&TypedTxResponse> {
+        match self {
+            Self::Undecryptable { inner, .. }
+            | Self::InnerApplied { inner, .. } => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+/// Track a broadcast tx through its two phases: wait for its wrapper to be
+/// included in a block, then for its inner tx to be applied in one of the
+/// following blocks. Each phase gives up after its own timeout, which is
+/// reported as a distinct outcome.
+pub async fn track_tx<C: crate::queries::Client + Sync>(
+    client: &C,
+    receipt: &BroadcastReceipt,
+    timeouts: &TxTrackingTimeouts,
+) -> Result<TrackedTxOutcome> {
+    let wrapper_hash = receipt.wrapper_hash.to_string();
+    let Some(wrapper) = poll_tx_event(
+        client,
+        rpc::TxEventQuery::Accepted(&wrapper_hash),
+        timeouts.wrapper,
+        timeouts.poll_interval,
+    )
+    .await?
+    else {
+        return Ok(TrackedTxOutcome::WrapperTimedOut);
+    };
+    if !wrapper.is_success() {
+        return Ok(TrackedTxOutcome::WrapperRejected { wrapper });
+    }
+    let decrypted_hash = receipt.decrypted_hash.to_string();
+    let Some(inner) = poll_tx_event(
+        client,
+        rpc::TxEventQuery::Applied(&decrypted_hash),
+        timeouts.inner,
+        timeouts.poll_interval,
+    )
+    .await?
+    else {
+        return Ok(TrackedTxOutcome::InnerTimedOut { wrapper });
+    };
+    Ok(if inner.outcome.code == ResultCode::Undecryptable {
+        TrackedTxOutcome::Undecryptable { wrapper, inner }
+    } else {
+        TrackedTxOutcome::InnerApplied { wrapper, inner }
+    })
+}
+
+/// Poll the event of a tx until it is found, or `None` if the timeout
+/// elapses first
+async fn poll_tx_event<C: crate::queries::Client + Sync>(
+    client: &C,
+    query: rpc::TxEventQuery<'_>,
+    timeout: time::Duration,
+    poll_interval: time::Duration,
+) -> Result<Option<TypedTxResponse>> {
+    let deadline = time::Instant::now() + timeout;
+    let event = time::Sleep {
+        strategy: time::Constant(poll_interval),
+    }
+    .timeout(deadline, || async {
+        match rpc::query_tx_events(client, query).await {
+            Ok(Some(event)) => ControlFlow::Break(event),
+            Ok(None) => ControlFlow::Continue(()),
+            Err(err) => {
+                tracing::debug!(
+                    ?query,
+                    %err,
+                    "ABCI query failed, retrying tx event query",
+                );
+                ControlFlow::Continue(())
+            }
+        }
+    })
+    .await;
+    match event {
+        Ok(event) => Ok(Some(TypedTxResponse::try_from(event)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// A problem with a transaction found by [`validate_before_broadcast`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TxWarning {
+    /// The transaction is not wrapped, thus it cannot be broadcast
+    #[error("The transaction is not a wrapper transaction")]
+    NotAWrapper,
+    /// The transaction exceeds the maximum size accepted by the ledger
+    #[error(
+        "The transaction is {size} bytes long, whereas at most {max} bytes \
+         are accepted"
+    )]
+    TooLarge {
+        /// Size of the serialized transaction
+        size: usize,
+        /// Maximum size of a transaction
+        max: usize,
+    },
+    /// The transaction has already expired
+    #[error("The transaction expired at {expiration}, it is now {now}")]
+    Expired {
+        /// Expiration of the transaction
+        expiration: DateTimeUtc,
+        /// Current time
+        now: DateTimeUtc,
+    },
+    /// The wrapper header was not signed
+    #[error("The wrapper header is not signed")]
+    MissingHeaderSignature,
+    /// The transaction is not consistent with itself, e.g. its signature
+    /// does not verify
+    #[error("The transaction is invalid: {0}")]
+    Invalid(String),
+    /// The fee token cannot be used to pay for gas
+    #[error("Gas cannot be paid in token {0}")]
+    FeeTokenNotAllowed(Address),
+    /// The gas price is below the minimum one
+    #[error(
+        "The gas price {} is lower than the minimum one {} for token {token}",
+        .price.to_string_native(),
+        .minimum.to_string_native()
+    )]
+    FeeTooLow {
+        /// The fee token
+        token: Address,
+        /// The gas price of the transaction
+        price: token::Amount,
+        /// The minimum gas price for the token
+        minimum: token::Amount,
+    },
+    /// The fee payer cannot afford the fee. This is not an error if the
+    /// wrapper unshields the missing funds.
+    #[error(
+        "The fee payer {payer} only has {} of token {token}, whereas the fee \
+         is {}",
+        .balance.to_string_native(),
+        .fee.to_string_native()
+    )]
+    InsufficientBalance {
+        /// The fee payer
+        payer: Address,
+        /// The fee token
+        token: Address,
+        /// Balance of the fee payer
+        balance: token::Amount,
+        /// Total fee of the transaction
+        fee: token::Amount,
+        /// Whether the wrapper unshields funds to pay for fees
+        unshields_fees: bool,
+    },
+    /// A transaction with the same hash was already applied
+    #[error("A transaction with hash {0} was already applied")]
+    Replay(Hash),
+    /// One of the checks could not be carried out
+    #[error("Could not check the transaction: {0}")]
+    Unchecked(String),
+}
+
+impl TxWarning {
+    /// Check whether the ledger is bound to reject the transaction because of
+    /// this problem
+    pub fn is_error(&self) -> bool {
+        match self {
+            Self::InsufficientBalance { unshields_fees, .. } => !unshields_fees,
+            Self::Unchecked(_) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Run the checks that the mempool of a node performs on the given
+/// transaction, such that problems can be found before broadcasting it.
+///
+/// Besides the stateless checks, the parameters of the chain, the balance of
+/// the fee payer and the replay protection are queried. The checks that
+/// could not be carried out are reported as [`TxWarning::Unchecked`]. The
+/// expiration is checked against the local time, rather than against the
+/// time of the last block as the ledger does.
+pub async fn validate_before_broadcast<C: crate::queries::Client + Sync>(
+    client: &C,
+    tx: &Tx,
+) -> Vec<TxWarning> {
+    let mut warnings = vec![];
+
+    let wrapper = match &tx.header().tx_type {
+        TxType::Wrapper(wrapper) => wrapper.clone(),
+        _ => return vec![TxWarning::NotAWrapper],
+    };
+
+    let size = tx.to_bytes().len();
+    let max_tx_bytes_key = parameter_storage::get_max_tx_bytes_key();
+    match rpc::query_storage_value::<_, u32>(client, &max_tx_bytes_key).await {
+        Ok(max) if size > max as usize => warnings.push(TxWarning::TooLarge {
+            size,
+            max: max as usize,
+        }),
+        Ok(_) => {}
+        Err(err) => warnings.push(TxWarning::Unchecked(err.to_string())),
+    }
+
+    if let Some(expiration) = tx.header().expiration {
+        let now = DateTimeUtc::now();
+        if now > expiration {
+            warnings.push(TxWarning::Expired { expiration, now });
+        }
+    }
+
+    let header_hash = tx.header_hash();
+    let header_signed = tx.sections.iter().any(|section| {
+        matches!(
+            section,
+            Section::Signature(signature)
+                if signature.targets.contains(&header_hash)
+        )
+    });
+    if !header_signed {
+        warnings.push(TxWarning::MissingHeaderSignature);
+    } else if let Err(err) = tx.validate_tx() {
+        warnings.push(TxWarning::Invalid(err.to_string()));
+    }
+
+    let token = wrapper.fee.token.clone();
+    let gas_cost_key = parameter_storage::get_gas_cost_key();
+    match rpc::query_storage_value::<_, BTreeMap<Address, token::Amount>>(
+        client,
+        &gas_cost_key,
+    )
+    .await
+    {
+        Ok(gas_costs) => match gas_costs.get(&token) {
+            Some(minimum) if wrapper.fee.amount_per_gas_unit < *minimum => {
+                warnings.push(TxWarning::FeeTooLow {
+                    token: token.clone(),
+                    price: wrapper.fee.amount_per_gas_unit,
+                    minimum: *minimum,
+                })
+            }
+            Some(_) => {}
+            None => warnings.push(TxWarning::FeeTokenNotAllowed(token.clone())),
+        },
+        Err(err) => warnings.push(TxWarning::Unchecked(err.to_string())),
+    }
+
+    let payer = wrapper.fee_payer();
+    match wrapper.get_tx_fee() {
+        Ok(fee) => match rpc::get_token_balance(client, &token, &payer).await {
+            Ok(balance) if balance < fee => {
+                warnings.push(TxWarning::InsufficientBalance {
+                    payer,
+                    token,
+                    balance,
+                    fee,
+                    unshields_fees: wrapper.unshield_section_hash.is_some(),
+                })
+            }
+            Ok(_) => {}
+            Err(err) => warnings.push(TxWarning::Unchecked(err.to_string())),
+        },
+        Err(err) => warnings.push(TxWarning::Invalid(err.to_string())),
+    }
+
+    for hash in [tx.raw_header_hash(), header_hash] {
+        match rpc::is_replay_protected(client, &hash).await {
+            Ok(true) => warnings.push(TxWarning::Replay(hash)),
+            Ok(false) => {}
+            Err(err) => warnings.push(TxWarning::Unchecked(err.to_string())),
+        }
+    }
+
+    warnings
+}
+
+/// Display the problems found with the given transaction before broadcasting
+/// it, and refuse to broadcast it if the ledger is bound to reject it, unless
+/// forced to.
+async fn check_before_broadcast<'a>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+    tx: &Tx,
+) -> Result<()> {
+    let warnings = validate_before_broadcast(context.client(), tx).await;
+    for warning in &warnings {
+        let level = if warning.is_error() {
+            "Error"
+        } else {
+            "Warning"
+        };
+        edisplay_line!(context.io(), "{level}: {warning}");
+    }
+    let errors: Vec<_> = warnings
+        .iter()
+        .filter(|warning| warning.is_error())
+        .map(ToString::to_string)
+        .collect();
+    if errors.is_empty() || args.force {
+        Ok(())
+    } else {
+        Err(TxError::RejectedBeforeBroadcast(errors.join("; ")).into())
+    }
+}
+
 /// Build and dump a transaction either to file or to screen
 pub fn dump_tx<IO: Io>(io: &IO, args: &args::Tx, tx: Tx) {
     let tx_id = tx.header_hash();
@@ -170,6 +725,111 @@ pub fn dump_tx<IO: Io>(io: &IO, args: &args::Tx, tx: Tx) {
     }
 }
 
+/// A tx included in a block, dumped as JSON for inspection
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TxDump {
+    /// The height of the block that included the tx
+    pub height: BlockHeight,
+    /// The hash of the tx header
+    pub header_hash: Hash,
+    /// The hash of the inner tx
+    pub raw_header_hash: Hash,
+    /// The hashes of the sections of the tx that are encrypted
+    pub encrypted_sections: Vec<Hash>,
+    /// The sections of the decrypted tx that are missing from the tx, if the
+    /// decrypted tx was looked up
+    pub decrypted_sections: Option<Vec<Section>>,
+    /// The tx itself
+    pub tx: Tx,
+}
+
+impl TxDump {
+    /// Dump the given tx included at the given height. If the tx that was
+    /// applied after decrypting it is given, the sections that are only in
+    /// the decrypted tx are dumped too.
+    pub fn new(height: BlockHeight, tx: Tx, decrypted: Option<&Tx>) -> Self {
+        let encrypted_sections = tx
+            .sections
+            .iter()
+            .filter(|section| matches!(section, Section::Ciphertext(_)))
+            .map(Section::get_hash)
+            .collect();
+        let decrypted_sections = decrypted.map(|decrypted| {
+            let hashes: HashSet<_> =
+                tx.sections.iter().map(Section::get_hash).collect();
+            decrypted
+                .sections
+                .iter()
+                .filter(|section| !hashes.contains(&section.get_hash()))
+                .cloned()
+                .collect()
+        });
+        Self {
+            height,
+            header_hash: tx.header_hash(),
+            raw_header_hash: tx.raw_header_hash(),
+            encrypted_sections,
+            decrypted_sections,
+            tx,
+        }
+    }
+}
+
+/// The encoding of the bytes of a tx pasted as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxBlobEncoding {
+    /// Hexadecimal, as displayed by block explorers
+    Hex,
+    /// Base64, as returned by the `tx` endpoint of CometBFT
+    Base64,
+}
+
+/// Decode a tx from the hex or base64 encoding of its bytes, e.g. as copied
+/// from a block explorer or from a CometBFT RPC result. The encoding is
+/// detected automatically, whitespace and a `0x` prefix are ignored. The
+/// signature of the decoded tx is verified.
+pub fn decode_tx_blob(blob: &str) -> Result<(TxBlobEncoding, Tx)> {
+    let blob: String = blob.split_whitespace().collect();
+    let hex = blob
+        .strip_prefix("0x")
+        .or_else(|| blob.strip_prefix("0X"))
+        .unwrap_or(&blob);
+    // A blob made of hex digits only may also be valid base64, in which case
+    // both decodings are tried
+    let candidates: Vec<_> = [
+        HEXLOWER_PERMISSIVE
+            .decode(hex.as_bytes())
+            .map(|bytes| (TxBlobEncoding::Hex, bytes)),
+        BASE64
+            .decode(blob.as_bytes())
+            .or_else(|_| BASE64_NOPAD.decode(blob.as_bytes()))
+            .map(|bytes| (TxBlobEncoding::Base64, bytes)),
+    ]
+    .into_iter()
+    .filter_map(std::result::Result::ok)
+    .collect();
+    if candidates.is_empty() {
+        return Err(TxError::InvalidTxBlob.into());
+    }
+
+    let mut decode_error = None;
+    for (encoding, bytes) in candidates {
+        match Tx::try_from(&bytes[..]) {
+            Ok(tx) => {
+                tx.validate_tx().map_err(|err| {
+                    TxError::DecodedTxInvalid(err.to_string())
+                })?;
+                return Ok((encoding, tx));
+            }
+            Err(err) => {
+                decode_error.get_or_insert(err);
+            }
+        }
+    }
+    let err = decode_error.expect("There is at least one candidate");
+    Err(TxError::NotATx(err.to_string()).into())
+}
+
 /// Prepare a transaction for signing and submission by adding a wrapper header
 /// to it.
 #[allow(clippy::too_many_arguments)]
@@ -182,6 +842,15 @@ pub async fn prepare_tx<'a>(
 ) -> Result<Option<Epoch>> {
     if !args.dry_run {
         let epoch = rpc::query_epoch(context.client()).await?;
+        // The parameters of the chain may have changed since the cached
+        // context was queried in an earlier epoch
+        if !args.offline {
+            let chain_context =
+                context.chain_context().get(context.client()).await?;
+            if chain_context.epoch != epoch {
+                context.chain_context().clear();
+            }
+        }
 
         signing::wrap_tx(context, tx, args, tx_source_balance, epoch, fee_payer)
             .await
@@ -190,6 +859,58 @@ pub async fn prepare_tx<'a>(
     }
 }
 
+/// Make a new tx whose header is filled in from the context of the connected
+/// chain, queried once per session. In offline mode, the header is built from
+/// the chain id and expiration given in the arguments instead.
+pub async fn new_tx<'a>(
+    context: &impl Namada<'a>,
+    args: &args::Tx,
+) -> Result<Tx> {
+    let chain_context = if args.offline {
+        None
+    } else {
+        let chain_context = context
+            .chain_context()
+            .get(context.client())
+            .await
+            .map_err(|err| TxError::MissingChainContext(err.to_string()))?;
+        Some(chain_context)
+    };
+    let (chain_id, expiration) = tx_header(args, chain_context.as_ref())?;
+    Ok(Tx::new(chain_id, expiration))
+}
+
+/// Get the chain id and expiration of a new tx, defaulting to the context of
+/// the connected chain if any. Otherwise, the tx is built in offline mode.
+fn tx_header(
+    args: &args::Tx,
+    chain_context: Option<&TxChainContext>,
+) -> Result<(ChainId, Option<DateTimeUtc>)> {
+    match chain_context {
+        Some(chain_context) => {
+            if let Some(chain_id) = &args.chain_id {
+                if chain_id != &chain_context.chain_id {
+                    return Err(Error::from(TxError::ChainIdMismatch {
+                        given: chain_id.clone(),
+                        connected: chain_context.chain_id.clone(),
+                    }));
+                }
+            }
+            let expiration = args
+                .expiration
+                .or_else(|| Some(chain_context.default_expiration()));
+            Ok((chain_context.chain_id.clone(), expiration))
+        }
+        None => {
+            let chain_id = args
+                .chain_id
+                .clone()
+                .ok_or(TxError::OfflineMissingChainId)?;
+            Ok((chain_id, args.expiration))
+        }
+    }
+}
+
 /// Submit transaction and wait for result. Returns a list of addresses
 /// initialized in the transaction if any. In dry run, this is always empty.
 pub async fn process_tx<'a>(
@@ -210,6 +931,7 @@ pub async fn process_tx<'a>(
     if args.dry_run || args.dry_run_wrapper {
         expect_dry_broadcast(TxBroadcastData::DryRun(tx), context).await
     } else {
+        check_before_broadcast(context, args, &tx).await?;
         // We use this to determine when the wrapper tx makes it on-chain
         let wrapper_hash = tx.header_hash().to_string();
         // We use this to determine when the decrypted inner tx makes it
@@ -295,7 +1017,7 @@ pub async fn build_reveal_pk<'a>(
 pub async fn broadcast_tx<'a>(
     context: &impl Namada<'a>,
     to_broadcast: &TxBroadcastData,
-) -> Result<Response> {
+) -> Result<BroadcastResponse> {
     let (tx, wrapper_tx_hash, decrypted_tx_hash) = match to_broadcast {
         TxBroadcastData::Live {
             tx,
@@ -313,9 +1035,11 @@ pub async fn broadcast_tx<'a>(
     // TODO: configure an explicit timeout value? we need to hack away at
     // `tendermint-rs` for this, which is currently using a hard-coded 30s
     // timeout.
-    let response = lift_rpc_error(
-        context.client().broadcast_tx_sync(tx.to_bytes()).await,
-    )?;
+    let tx_bytes = tx.to_bytes();
+    // Hash the exact bytes being broadcast, as CometBFT does
+    let tendermint_hash = hash_tx(&tx_bytes);
+    let response =
+        lift_rpc_error(context.client().broadcast_tx_sync(tx_bytes).await)?;
 
     if response.code == 0.into() {
         display_line!(
@@ -336,8 +1060,17 @@ pub async fn broadcast_tx<'a>(
                 "Inner transaction hash: {:?}",
                 decrypted_tx_hash
             );
+            display_line!(
+                context.io(),
+                "CometBFT transaction hash: {:?}",
+                tendermint_hash.to_string()
+            );
         }
-        Ok(response)
+        Ok(BroadcastResponse {
+            response,
+            tendermint_hash,
+            header_hash: tx.header_hash(),
+        })
     } else {
         Err(Error::from(TxError::TxBroadcast(RpcError::server(
             serde_json::to_string(&response).map_err(|err| {
@@ -385,7 +1118,7 @@ pub async fn submit_tx<'a>(
     let parsed = {
         let wrapper_query = rpc::TxEventQuery::Accepted(wrapper_hash.as_str());
         let event =
-            rpc::query_tx_status(context, wrapper_query, deadline).await?;
+            rpc::wait_for_tx_event(context, wrapper_query, deadline).await?;
         let parsed = TxResponse::from_event(event);
         let tx_to_str = |parsed| {
             serde_json::to_string_pretty(parsed).map_err(|err| {
@@ -405,7 +1138,7 @@ pub async fn submit_tx<'a>(
             let decrypted_query =
                 rpc::TxEventQuery::Applied(decrypted_hash.as_str());
             let event =
-                rpc::query_tx_status(context, decrypted_query, deadline)
+                rpc::wait_for_tx_event(context, decrypted_query, deadline)
                     .await?;
             let parsed = TxResponse::from_event(event);
             display_line!(
@@ -1976,10 +2709,7 @@ pub async fn build_ibc_transfer<'a>(
         token: args.token.clone(),
     });
 
-    let tx_code_hash =
-        query_wasm_code_hash(context, args.tx_code_path.to_str().unwrap())
-            .await
-            .map_err(|e| Error::from(QueryError::Wasm(e.to_string())))?;
+    let tx_code = TxCode::query(context, &args.tx, &args.tx_code_path).await?;
 
     let ibc_denom =
         rpc::query_ibc_denom(context, &args.token, Some(&source)).await;
@@ -2031,17 +2761,15 @@ pub async fn build_ibc_transfer<'a>(
     };
 
     let any_msg = msg.to_any();
-    let mut data = vec![];
-    prost::Message::encode(&any_msg, &mut data)
-        .map_err(TxError::EncodeFailure)?;
-
-    let chain_id = args.tx.chain_id.clone().unwrap();
-    let mut tx = Tx::new(chain_id, args.tx.expiration);
-    tx.add_code_from_hash(
-        tx_code_hash,
-        Some(args.tx_code_path.to_string_lossy().into_owned()),
-    )
-    .add_serialized_data(data);
+
+    let mut tx = new_tx(context, &args.tx).await?;
+    // The message fields are carried by extra data sections and only their
+    // envelope is set as the tx data
+    let data = add_msg_sections(&mut tx, &any_msg)
+        .map_err(|e| TxError::EncodeTxFailure(e.to_string()))?;
+    tx_code
+        .add_to(&mut tx, &args.tx_code_path)
+        .add_serialized_data(data);
 
     let epoch = prepare_tx(
         context,
@@ -2096,22 +2824,13 @@ where
     F: FnOnce(&mut Tx, &mut D) -> Result<()>,
     D: BorshSerialize,
 {
-    let chain_id = tx_args.chain_id.clone().unwrap();
-
-    let mut tx_builder = Tx::new(chain_id, tx_args.expiration);
+    let mut tx_builder = new_tx(context, tx_args).await?;
 
-    let tx_code_hash = query_wasm_code_hash(context, path.to_string_lossy())
-        .await
-        .map_err(|e| Error::from(QueryError::Wasm(e.to_string())))?;
+    let tx_code = TxCode::query(context, tx_args, &path).await?;
 
     on_tx(&mut tx_builder, &mut data)?;
 
-    tx_builder
-        .add_code_from_hash(
-            tx_code_hash,
-            Some(path.to_string_lossy().into_owned()),
-        )
-        .add_data(data);
+    tx_code.add_to(&mut tx_builder, &path).add_data(data);
 
     let epoch = prepare_tx(
         context,
@@ -2196,6 +2915,16 @@ pub async fn build_transfer<'a, N: Namada<'a>>(
     let source = args.source.effective_address();
     let target = args.target.effective_address();
 
+    // Check that the memo fits in a tx
+    if let Some(memo) = &args.memo {
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::from(TxError::MemoTooLong(
+                memo.len(),
+                MAX_MEMO_LEN,
+            )));
+        }
+    }
+
     // Check that the source address exists on chain
     source_exists_or_err(source.clone(), args.tx.force, context).await?;
     // Check that the target address exists on chain
@@ -2290,7 +3019,11 @@ pub async fn build_transfer<'a, N: Namada<'a>>(
         shielded: None,
     };
 
+    let memo = args.memo.clone();
     let add_shielded = |tx: &mut Tx, transfer: &mut token::Transfer| {
+        if let Some(memo) = memo {
+            tx.add_memo(memo.as_bytes());
+        }
         // Add the MASP Transaction and its Builder to facilitate validation
         if let Some(ShieldedTransfer {
             builder,
@@ -2376,6 +3109,19 @@ pub async fn build_init_account<'a>(
             }
         }
     };
+    if threshold == 0 || threshold as usize > public_keys.len() {
+        return Err(Error::from(TxError::InvalidAccountThreshold(
+            threshold,
+            public_keys.len(),
+        )));
+    }
+    for (index, public_key) in public_keys.iter().enumerate() {
+        if public_keys[..index].contains(public_key) {
+            return Err(Error::from(TxError::DuplicateAccountPublicKey(
+                public_key.to_string(),
+            )));
+        }
+    }
 
     let data = InitAccount {
         public_keys: public_keys.clone(),
@@ -2444,8 +3190,7 @@ pub async fn build_update_account<'a>(
         None => None,
     };
 
-    let chain_id = tx_args.chain_id.clone().unwrap();
-    let mut tx = Tx::new(chain_id, tx_args.expiration);
+    let mut tx = new_tx(context, tx_args).await?;
     let extra_section_hash = vp_code_path.as_ref().zip(vp_code_hash).map(
         |(code_path, vp_code_hash)| {
             tx.add_extra_section_from_hash(
@@ -2458,6 +3203,7 @@ pub async fn build_update_account<'a>(
     let data = UpdateAccount {
         addr,
         vp_code_hash: extra_section_hash,
+        stored_vp_code_hash: None,
         public_keys: public_keys.clone(),
         threshold: *threshold,
     };
@@ -2515,13 +3261,9 @@ pub async fn build_custom<'a>(
         let code_path = code_path
             .as_ref()
             .ok_or(Error::Other("No code path supplied".to_string()))?;
-        let tx_code_hash = query_wasm_code_hash_buf(context, code_path).await?;
-        let chain_id = tx_args.chain_id.clone().unwrap();
-        let mut tx = Tx::new(chain_id, tx_args.expiration);
-        tx.add_code_from_hash(
-            tx_code_hash,
-            Some(code_path.to_string_lossy().into_owned()),
-        );
+        let tx_code = TxCode::query(context, tx_args, code_path).await?;
+        let mut tx = new_tx(context, tx_args).await?;
+        tx_code.add_to(&mut tx, code_path);
         data_path.clone().map(|data| tx.add_serialized_data(data));
         tx
     };
@@ -2829,7 +3571,90 @@ async fn query_wasm_code_hash_buf<'a>(
     context: &impl Namada<'a>,
     path: &Path,
 ) -> Result<Hash> {
-    query_wasm_code_hash(context, path.to_string_lossy()).await
+    rpc::resolve_tx_code(context, &path.to_string_lossy()).await
+}
+
+/// The code section of a tx under construction
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TxCode {
+    /// The code is stored on chain under this hash
+    Stored(Hash),
+    /// The code isn't stored on chain, so it gets embedded in the tx
+    Embedded(Vec<u8>),
+}
+
+impl TxCode {
+    /// Look up the tx code at the given path on chain, falling back to the
+    /// code from the wasm directory if the tx args allow embedding it
+    pub(crate) async fn query<'a>(
+        context: &impl Namada<'a>,
+        tx_args: &args::Tx,
+        path: &Path,
+    ) -> Result<Self> {
+        let stored = context
+            .code_hashes()
+            .lookup(context.client(), &path.to_string_lossy())
+            .await?;
+        Self::new(
+            stored,
+            path,
+            tx_args.embed_missing_code,
+            tx_args.wasm_dir.as_deref(),
+        )
+    }
+
+    /// Reference the code by its stored hash if there is one, otherwise embed
+    /// the code from the wasm directory or fail
+    fn new(
+        stored: Option<Hash>,
+        path: &Path,
+        embed_missing_code: bool,
+        wasm_dir: Option<&Path>,
+    ) -> Result<Self> {
+        match stored {
+            Some(hash) => Ok(Self::Stored(hash)),
+            None if embed_missing_code => {
+                read_wasm_code(wasm_dir, path).map(Self::Embedded)
+            }
+            None => Err(Error::from(TxError::MissingTxCode(
+                path.to_string_lossy().into_owned(),
+            ))),
+        }
+    }
+
+    /// Set the code section of the given tx, tagged with the code path
+    pub(crate) fn add_to(self, tx: &mut Tx, path: &Path) -> &mut Tx {
+        let tag = Some(path.to_string_lossy().into_owned());
+        match self {
+            Self::Stored(hash) => tx.add_code_from_hash(hash, tag),
+            Self::Embedded(code) => tx.add_code(code, tag),
+        }
+    }
+}
+
+/// Read the wasm code at the given path from the wasm directory, resolving
+/// its file name through the directory's checksums file if any
+fn read_wasm_code(wasm_dir: Option<&Path>, path: &Path) -> Result<Vec<u8>> {
+    let read_err = |err: String| {
+        Error::from(TxError::ReadTxCode(
+            path.to_string_lossy().into_owned(),
+            err,
+        ))
+    };
+    let wasm_dir = wasm_dir
+        .ok_or_else(|| read_err("no wasm directory was given".to_string()))?;
+    let checksums: HashMap<String, String> =
+        match std::fs::read(wasm_dir.join("checksums.json")) {
+            Ok(checksums) => serde_json::from_slice(&checksums)
+                .map_err(|err| read_err(err.to_string()))?,
+            Err(_) => HashMap::new(),
+        };
+    let file_name = checksums
+        .get(path.to_string_lossy().as_ref())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| path.to_path_buf());
+    std::fs::read(wasm_dir.join(file_name))
+        .map_err(|err| read_err(err.to_string()))
 }
 
 /// A helper for [`fn build`] that can be used for `on_tx` arg that does nothing
@@ -2844,3 +3669,790 @@ fn proposal_to_vec(proposal: OnChainProposal) -> Result<Vec<u8>> {
     borsh::to_vec(&proposal.content)
         .map_err(|e| Error::from(EncodingError::Conversion(e.to_string())))
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use assert_matches::assert_matches;
+    use data_encoding::HEXUPPER;
+    use namada_core::types::address::nam;
+    use namada_core::types::chain::ChainId;
+    use namada_core::ledger::parameters::EpochDuration;
+    use namada_core::ledger::replay_protection;
+    use namada_core::types::account::AccountPublicKeysMap;
+    use namada_core::ledger::storage::testing::TestStorage;
+    use namada_core::types::key::testing::{keypair_1, keypair_2};
+    use namada_core::types::time::DurationSecs;
+    use namada_core::types::transaction::{DecryptedTx, Fee, GasLimit};
+
+    use super::*;
+    use crate::args::TxBuilder;
+    use crate::events::Event;
+    use crate::proto::{Ciphertext, Commitment};
+    use crate::queries::testing::TestClient;
+    use crate::queries::EncodedResponseQuery;
+    use crate::signer::SoftwareSigner;
+
+    /// Test that a dump of a tx with encrypted sections decodes back into the
+    /// same tx, with its encrypted and decrypted sections marked.
+    #[test]
+    fn test_decode_dump_with_encrypted_sections() {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_serialized_data(b"plaintext".to_vec());
+        let ciphertext_hash = tx
+            .add_section(Section::Ciphertext(Ciphertext {
+                opaque: b"encrypted".to_vec().into(),
+            }))
+            .get_hash();
+        let mut decrypted = tx.clone();
+        decrypted.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        decrypted.add_memo(b"decrypted memo");
+
+        let dump = TxDump::new(BlockHeight(3), tx.clone(), Some(&decrypted));
+        let json = serde_json::to_string_pretty(&dump).unwrap();
+        let decoded: TxDump = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.height, BlockHeight(3));
+        assert_eq!(decoded.header_hash, tx.header_hash());
+        assert_eq!(decoded.encrypted_sections, vec![ciphertext_hash]);
+        let decrypted_sections = decoded.decrypted_sections.unwrap();
+        assert_eq!(decrypted_sections.len(), 1);
+        assert_eq!(
+            decrypted_sections[0].extra_data(),
+            Some(b"decrypted memo".to_vec())
+        );
+        assert_eq!(decoded.tx.to_bytes(), tx.to_bytes());
+    }
+
+    /// A client answering broadcasts with a fixed `CheckTx` result
+    struct CheckTxClient {
+        code: u32,
+        log: &'static str,
+        broadcast: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl CheckTxClient {
+        fn new(code: u32, log: &'static str) -> Self {
+            Self {
+                code,
+                log,
+                broadcast: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "async-send", async_trait::async_trait)]
+    #[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+    impl Client for CheckTxClient {
+        type Error = std::io::Error;
+
+        async fn request(
+            &self,
+            _path: String,
+            _data: Option<Vec<u8>>,
+            _height: Option<BlockHeight>,
+            _prove: bool,
+        ) -> std::result::Result<EncodedResponseQuery, Self::Error> {
+            unimplemented!("no queries are expected")
+        }
+
+        async fn perform<R>(
+            &self,
+            _request: R,
+        ) -> std::result::Result<R::Output, RpcError>
+        where
+            R: crate::tendermint_rpc::SimpleRequest,
+        {
+            unimplemented!("no other requests are expected")
+        }
+
+        async fn broadcast_tx_sync(
+            &self,
+            tx: impl Into<Vec<u8>>,
+        ) -> std::result::Result<Response, RpcError> {
+            self.broadcast.lock().unwrap().push(tx.into());
+            Ok(Response {
+                code: self.code.into(),
+                data: Default::default(),
+                log: self.log.to_string(),
+                hash: Default::default(),
+            })
+        }
+    }
+
+    fn unsigned_wrapper_tx(expiration: Option<DateTimeUtc>) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), expiration);
+        tx.add_code(b"code".to_vec(), None)
+            .add_serialized_data(b"data".to_vec())
+            .add_wrapper(
+                Fee {
+                    amount_per_gas_unit: token::Amount::native_whole(1),
+                    token: nam(),
+                },
+                keypair_1().ref_to(),
+                Epoch(0),
+                100_000.into(),
+                None,
+            );
+        tx
+    }
+
+    fn wrapper_tx() -> Tx {
+        let mut tx = unsigned_wrapper_tx(None);
+        tx.sign_wrapper(keypair_1());
+        tx
+    }
+
+    /// Test that the receipt of a broadcast carries the hashes under which
+    /// the node reports the acceptance and application of the tx
+    #[tokio::test]
+    async fn test_broadcast_receipt_hashes() {
+        let client = CheckTxClient::new(0, "");
+        let tx = wrapper_tx();
+
+        let receipt = broadcast_tx_with_receipt(&client, &tx).await.unwrap();
+
+        assert_eq!(receipt.result_code, Some(ResultCode::Ok));
+        let broadcast = client.broadcast.lock().unwrap();
+        assert_eq!(receipt.tendermint_hash, hash_tx(&broadcast[0]));
+        assert_eq!(receipt.code_hash, *tx.code_sechash());
+        assert_eq!(receipt.data_hash, *tx.data_sechash());
+        let accepted = Event::new_tx_event(&tx, 1);
+        assert_eq!(accepted["hash"], receipt.wrapper_hash.to_string());
+        let mut decrypted = tx.clone();
+        decrypted.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        let applied = Event::new_tx_event(&decrypted, 2);
+        assert_eq!(applied["hash"], receipt.decrypted_hash.to_string());
+    }
+
+    /// Test that a tx rejected by `CheckTx` yields an error with its receipt
+    #[tokio::test]
+    async fn test_broadcast_rejected_by_check_tx() {
+        let client = CheckTxClient::new(
+            ResultCode::FeeError.into(),
+            "Insufficient balance for fee",
+        );
+        let tx = wrapper_tx();
+
+        let err = broadcast_tx_with_receipt(&client, &tx).await.unwrap_err();
+
+        assert_matches!(
+            err,
+            Error::Tx(TxError::CheckTxRejected(receipt))
+                if receipt.result_code == Some(ResultCode::FeeError)
+                    && receipt.log == "Insufficient balance for fee"
+                    && receipt.wrapper_hash == tx.header_hash()
+        );
+    }
+
+    /// A client answering queries from its test storage and broadcasts like
+    /// a node that rejects wrappers built in a past epoch, or rejects all of
+    /// them with a fixed `CheckTx` result if any
+    struct StaleEpochClient {
+        client: TestClient<crate::queries::RPC>,
+        rejection: Option<(ResultCode, &'static str)>,
+        broadcast: Mutex<Vec<Tx>>,
+    }
+
+    impl StaleEpochClient {
+        fn new(
+            current_epoch: Epoch,
+            rejection: Option<(ResultCode, &'static str)>,
+        ) -> Self {
+            let mut client = TestClient::new(crate::queries::RPC);
+            client.wl_storage.storage.last_epoch = current_epoch;
+            Self {
+                client,
+                rejection,
+                broadcast: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "async-send", async_trait::async_trait)]
+    #[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+    impl Client for StaleEpochClient {
+        type Error = std::io::Error;
+
+        async fn request(
+            &self,
+            path: String,
+            data: Option<Vec<u8>>,
+            height: Option<BlockHeight>,
+            prove: bool,
+        ) -> std::result::Result<EncodedResponseQuery, Self::Error> {
+            self.client.request(path, data, height, prove).await
+        }
+
+        async fn perform<R>(
+            &self,
+            _request: R,
+        ) -> std::result::Result<R::Output, RpcError>
+        where
+            R: crate::tendermint_rpc::SimpleRequest,
+        {
+            unimplemented!("no other requests are expected")
+        }
+
+        async fn broadcast_tx_sync(
+            &self,
+            tx: impl Into<Vec<u8>>,
+        ) -> std::result::Result<Response, RpcError> {
+            let tx = Tx::try_from(tx.into().as_slice()).unwrap();
+            let wrapper_epoch = tx.header().wrapper().unwrap().epoch;
+            self.broadcast.lock().unwrap().push(tx);
+            let (code, log) = match self.rejection {
+                Some(rejection) => rejection,
+                None if wrapper_epoch
+                    < self.client.wl_storage.storage.last_epoch =>
+                {
+                    (ResultCode::InvalidTx, "The wrapper epoch is stale")
+                }
+                None => (ResultCode::Ok, ""),
+            };
+            Ok(Response {
+                code: u32::from(code).into(),
+                data: Default::default(),
+                log: log.to_string(),
+                hash: Default::default(),
+            })
+        }
+    }
+
+    /// A retry policy that does not wait between broadcasts
+    fn eager_retry_policy() -> BroadcastRetryPolicy {
+        BroadcastRetryPolicy {
+            max_attempts: 3,
+            backoff: time::Duration::ZERO,
+        }
+    }
+
+    /// Test that a wrapper rejected because its epoch went stale while it
+    /// was being signed is re-sealed and rebroadcast, keeping the author
+    /// signature of the inner tx
+    #[tokio::test]
+    async fn test_broadcast_retry_after_stale_epoch() {
+        let client = StaleEpochClient::new(Epoch(1), None);
+        let fee_payer = SoftwareSigner::new(keypair_1());
+        let author = SoftwareSigner::new(keypair_2());
+        let pks_map = AccountPublicKeysMap::from_iter([author.public_key()]);
+        let mut tx = unsigned_wrapper_tx(None);
+        signer::sign_raw(&mut tx, &[&author], &pks_map, None)
+            .await
+            .unwrap();
+        let author_signature = tx.sections[tx.sections.len() - 1].get_hash();
+        signer::sign_wrapper(&mut tx, &fee_payer).await.unwrap();
+
+        let (resealed, receipt) = broadcast_tx_with_retry(
+            &client,
+            tx.clone(),
+            &fee_payer,
+            &eager_retry_policy(),
+        )
+        .await
+        .unwrap();
+
+        assert!(receipt.is_ok());
+        let broadcast = client.broadcast.lock().unwrap();
+        assert_eq!(broadcast.len(), 2);
+        assert_eq!(broadcast[0].header_hash(), tx.header_hash());
+        assert_eq!(broadcast[1].to_bytes(), resealed.to_bytes());
+        assert_eq!(receipt.wrapper_hash, resealed.header_hash());
+        assert_eq!(resealed.header().wrapper().unwrap().epoch, Epoch(1));
+        // Only the wrapper changed, so the inner tx is the same
+        assert_eq!(resealed.raw_header_hash(), tx.raw_header_hash());
+        assert!(
+            resealed
+                .sections
+                .iter()
+                .any(|section| section.get_hash() == author_signature)
+        );
+        assert!(resealed.validate_tx().unwrap().is_some());
+        assert!(
+            resealed
+                .verify_signatures(
+                    &[resealed.raw_header_hash()],
+                    pks_map,
+                    &None,
+                    1,
+                    None,
+                    || Ok(()),
+                )
+                .is_ok()
+        );
+    }
+
+    /// Test that a wrapper rejected for a reason other than staleness is not
+    /// rebroadcast
+    #[tokio::test]
+    async fn test_broadcast_no_retry_for_low_fee() {
+        let client = StaleEpochClient::new(
+            Epoch(0),
+            Some((ResultCode::FeeError, "The gas price is too low")),
+        );
+        let fee_payer = SoftwareSigner::new(keypair_1());
+        let tx = wrapper_tx();
+
+        let err = broadcast_tx_with_retry(
+            &client,
+            tx.clone(),
+            &fee_payer,
+            &eager_retry_policy(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_matches!(
+            err,
+            Error::Tx(TxError::CheckTxRejected(receipt))
+                if receipt.result_code == Some(ResultCode::FeeError)
+                    && receipt.wrapper_hash == tx.header_hash()
+        );
+        assert_eq!(client.broadcast.lock().unwrap().len(), 1);
+    }
+
+    /// Write a value straight to the storage queried by the test client
+    fn write_value(
+        client: &mut TestClient<crate::queries::RPC>,
+        key: &storage::Key,
+        value: impl BorshSerialize,
+    ) {
+        client
+            .wl_storage
+            .storage
+            .write(key, borsh::to_vec(&value).unwrap())
+            .unwrap();
+    }
+
+    /// A test client whose storage holds the parameters checked before
+    /// broadcasting, along with enough funds for the fee payer of
+    /// [`wrapper_tx`]
+    fn validation_client() -> TestClient<crate::queries::RPC> {
+        let mut client = TestClient::new(crate::queries::RPC);
+        write_value(
+            &mut client,
+            &parameter_storage::get_max_tx_bytes_key(),
+            1024 * 1024_u32,
+        );
+        write_value(
+            &mut client,
+            &parameter_storage::get_gas_cost_key(),
+            BTreeMap::from([(nam(), token::Amount::native_whole(1))]),
+        );
+        let payer = Address::from(&keypair_1().ref_to());
+        write_value(
+            &mut client,
+            &token::balance_key(&nam(), &payer),
+            token::Amount::native_whole(1_000_000),
+        );
+        client
+    }
+
+    /// Test that a well-formed tx passes the checks before broadcasting
+    #[tokio::test]
+    async fn test_validate_before_broadcast() {
+        let client = validation_client();
+        let warnings = validate_before_broadcast(&client, &wrapper_tx()).await;
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    /// Test that txs violating the stateless checks of the mempool are
+    /// reported
+    #[tokio::test]
+    async fn test_validate_before_broadcast_stateless() {
+        let mut client = validation_client();
+
+        let mut raw = wrapper_tx();
+        raw.update_header(TxType::Raw);
+        let warnings = validate_before_broadcast(&client, &raw).await;
+        assert_eq!(warnings, vec![TxWarning::NotAWrapper]);
+
+        let now = DateTimeUtc::now();
+        let expiration = now - namada_core::types::time::Duration::hours(1);
+        let mut expired = unsigned_wrapper_tx(Some(expiration));
+        expired.sign_wrapper(keypair_1());
+        let warnings = validate_before_broadcast(&client, &expired).await;
+        assert_matches!(
+            warnings.as_slice(),
+            [TxWarning::Expired { expiration: found, .. }]
+                if *found == expiration
+        );
+
+        let unsigned = unsigned_wrapper_tx(None);
+        let warnings = validate_before_broadcast(&client, &unsigned).await;
+        assert_eq!(warnings, vec![TxWarning::MissingHeaderSignature]);
+
+        let mut wrong_signer = unsigned_wrapper_tx(None);
+        wrong_signer.sign_wrapper(keypair_2());
+        let warnings = validate_before_broadcast(&client, &wrong_signer).await;
+        assert_matches!(warnings.as_slice(), [TxWarning::Invalid(_)]);
+
+        let tx = wrapper_tx();
+        write_value(
+            &mut client,
+            &parameter_storage::get_max_tx_bytes_key(),
+            16_u32,
+        );
+        let warnings = validate_before_broadcast(&client, &tx).await;
+        assert_eq!(
+            warnings,
+            vec![TxWarning::TooLarge {
+                size: tx.to_bytes().len(),
+                max: 16,
+            }]
+        );
+        assert!(warnings.iter().all(TxWarning::is_error));
+    }
+
+    /// Test that txs that would be rejected given the state of the chain are
+    /// reported
+    #[tokio::test]
+    async fn test_validate_before_broadcast_stateful() {
+        let tx = wrapper_tx();
+        let payer = Address::from(&keypair_1().ref_to());
+        let gas_cost_key = parameter_storage::get_gas_cost_key();
+
+        let mut client = validation_client();
+        write_value(
+            &mut client,
+            &gas_cost_key,
+            BTreeMap::from([(nam(), token::Amount::native_whole(2))]),
+        );
+        let warnings = validate_before_broadcast(&client, &tx).await;
+        assert_eq!(
+            warnings,
+            vec![TxWarning::FeeTooLow {
+                token: nam(),
+                price: token::Amount::native_whole(1),
+                minimum: token::Amount::native_whole(2),
+            }]
+        );
+
+        write_value(
+            &mut client,
+            &gas_cost_key,
+            BTreeMap::<Address, token::Amount>::new(),
+        );
+        let warnings = validate_before_broadcast(&client, &tx).await;
+        assert_eq!(warnings, vec![TxWarning::FeeTokenNotAllowed(nam())]);
+
+        let mut client = validation_client();
+        write_value(
+            &mut client,
+            &token::balance_key(&nam(), &payer),
+            token::Amount::native_whole(1),
+        );
+        let warnings = validate_before_broadcast(&client, &tx).await;
+        assert_eq!(
+            warnings,
+            vec![TxWarning::InsufficientBalance {
+                payer,
+                token: nam(),
+                balance: token::Amount::native_whole(1),
+                fee: token::Amount::native_whole(100_000),
+                unshields_fees: false,
+            }]
+        );
+        assert!(warnings[0].is_error());
+
+        let mut client = validation_client();
+        let mut batch = TestStorage::batch();
+        let inner_hash = tx.raw_header_hash();
+        client
+            .wl_storage
+            .storage
+            .write_replay_protection_entry(
+                &mut batch,
+                &replay_protection::get_replay_protection_last_subkey(
+                    &inner_hash,
+                ),
+                None,
+            )
+            .unwrap();
+        let warnings = validate_before_broadcast(&client, &tx).await;
+        assert_eq!(warnings, vec![TxWarning::Replay(inner_hash)]);
+    }
+
+    /// Test that checks which cannot be carried out are not reported as
+    /// errors
+    #[tokio::test]
+    async fn test_validate_before_broadcast_unchecked() {
+        let mut client = validation_client();
+        client
+            .wl_storage
+            .storage
+            .delete(&parameter_storage::get_max_tx_bytes_key())
+            .unwrap();
+        let warnings = validate_before_broadcast(&client, &wrapper_tx()).await;
+        assert_matches!(warnings.as_slice(), [TxWarning::Unchecked(_)]);
+        assert!(!warnings[0].is_error());
+    }
+
+    /// Get the code commitment of the code section of the given tx
+    fn code_commitment(tx: &Tx) -> Commitment {
+        match tx.get_section(tx.code_sechash()).as_deref() {
+            Some(Section::Code(code)) => code.code.clone(),
+            section => panic!("Unexpected code section {section:?}"),
+        }
+    }
+
+    /// Test that tx code stored on chain is referenced by its hash.
+    #[test]
+    fn test_stored_tx_code() {
+        let path = Path::new(TX_TRANSFER_WASM);
+        let hash = Hash::sha256(b"tx_transfer");
+        let tx_code = TxCode::new(Some(hash), path, true, None).unwrap();
+        assert_eq!(tx_code, TxCode::Stored(hash));
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx_code.add_to(&mut tx, path);
+        assert_matches!(
+            code_commitment(&tx),
+            Commitment::Hash(stored) if stored == hash
+        );
+    }
+
+    /// Test that tx code missing on chain is embedded from the wasm directory
+    /// only when requested, resolving its file name through the checksums.
+    #[test]
+    fn test_missing_tx_code() {
+        let path = Path::new(TX_TRANSFER_WASM);
+        let wasm_dir = tempfile::tempdir().unwrap();
+        let code = b"tx_transfer code".to_vec();
+        std::fs::write(wasm_dir.path().join("tx_transfer.abcd.wasm"), &code)
+            .unwrap();
+        std::fs::write(
+            wasm_dir.path().join("checksums.json"),
+            r#"{"tx_transfer.wasm": "tx_transfer.abcd.wasm"}"#,
+        )
+        .unwrap();
+
+        let err =
+            TxCode::new(None, path, false, Some(wasm_dir.path())).unwrap_err();
+        assert_matches!(
+            err,
+            Error::Tx(TxError::MissingTxCode(name)) if name == TX_TRANSFER_WASM
+        );
+
+        let tx_code =
+            TxCode::new(None, path, true, Some(wasm_dir.path())).unwrap();
+        assert_eq!(tx_code, TxCode::Embedded(code.clone()));
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx_code.add_to(&mut tx, path);
+        assert_matches!(
+            code_commitment(&tx),
+            Commitment::Id(embedded) if embedded == code
+        );
+
+        let err = TxCode::new(
+            None,
+            Path::new(TX_BOND_WASM),
+            true,
+            Some(wasm_dir.path()),
+        )
+        .unwrap_err();
+        assert_matches!(err, Error::Tx(TxError::ReadTxCode(..)));
+        let err = TxCode::new(None, path, true, None).unwrap_err();
+        assert_matches!(err, Error::Tx(TxError::ReadTxCode(..)));
+    }
+
+    /// Test that the encoding of tx blobs is detected, regardless of their
+    /// whitespace, case and prefix
+    #[test]
+    fn test_decode_tx_blob_encodings() {
+        let bytes = wrapper_tx().to_bytes();
+        let hex = HEXUPPER.encode(&bytes);
+        let wrapped_hex = hex
+            .as_bytes()
+            .chunks(64)
+            .map(|line| std::str::from_utf8(line).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n  ");
+        let base64 = BASE64.encode(&bytes);
+        let wrapped_base64 =
+            format!("\t{}\n{}\n", &base64[..10], &base64[10..]);
+
+        let blobs = [
+            (TxBlobEncoding::Hex, hex.clone()),
+            (TxBlobEncoding::Hex, format!("0x{}", hex.to_lowercase())),
+            (TxBlobEncoding::Hex, wrapped_hex),
+            (TxBlobEncoding::Base64, base64),
+            (TxBlobEncoding::Base64, wrapped_base64),
+            (TxBlobEncoding::Base64, BASE64_NOPAD.encode(&bytes)),
+        ];
+        for (expected, blob) in blobs {
+            let (encoding, tx) = decode_tx_blob(&blob).unwrap();
+            assert_eq!(encoding, expected);
+            assert_eq!(tx.to_bytes(), bytes);
+        }
+    }
+
+    /// Test that the errors of decoding a corrupted blob tell whether the
+    /// encoding, the bytes or the tx itself are faulty
+    #[test]
+    fn test_decode_corrupted_tx_blob() {
+        assert_matches!(
+            decode_tx_blob("not a tx!"),
+            Err(Error::Tx(TxError::InvalidTxBlob))
+        );
+
+        let bytes = wrapper_tx().to_bytes();
+        let truncated = HEXUPPER.encode(&bytes[..bytes.len() / 2]);
+        assert_matches!(
+            decode_tx_blob(&truncated),
+            Err(Error::Tx(TxError::NotATx(_)))
+        );
+
+        // Changing the header after signing it invalidates the signature
+        let mut tampered = wrapper_tx();
+        tampered.add_memo(b"tampered");
+        assert_matches!(
+            decode_tx_blob(&BASE64.encode(&tampered.to_bytes())),
+            Err(Error::Tx(TxError::DecodedTxInvalid(_)))
+        );
+    }
+
+    /// Test the decoding of a wrapper tx as broadcast to the shell, whose
+    /// hashes must be preserved so that it can be looked up and resubmitted
+    #[test]
+    fn test_decode_shell_tx_blob() {
+        let tx = wrapper_tx();
+        let (_, decoded) =
+            decode_tx_blob(&HEXUPPER.encode(&tx.to_bytes())).unwrap();
+
+        assert_eq!(decoded.header_hash(), tx.header_hash());
+        assert_eq!(decoded.raw_header_hash(), tx.raw_header_hash());
+        assert_eq!(decoded.tendermint_hash(), tx.tendermint_hash());
+        let wrapper = decoded.header().wrapper().unwrap();
+        assert_eq!(wrapper.pk, keypair_1().ref_to());
+        assert_eq!(decoded.data(), Some(b"data".to_vec()));
+    }
+
+    /// Common tx arguments without a chain id nor an expiration
+    fn chain_agnostic_tx_args() -> args::Tx {
+        args::Tx {
+            dry_run: false,
+            dry_run_wrapper: false,
+            dump_tx: false,
+            output_folder: None,
+            force: false,
+            broadcast_only: false,
+            ledger_address: (),
+            initialized_account_alias: None,
+            wallet_alias_force: false,
+            fee_amount: None,
+            wrapper_fee_payer: None,
+            fee_token: nam(),
+            fee_unshield: None,
+            gas_limit: GasLimit::from(20_000),
+            expiration: None,
+            disposable_signing_key: false,
+            chain_id: None,
+            signing_keys: vec![],
+            signatures: vec![],
+            tx_reveal_code_path: PathBuf::from(TX_REVEAL_PK),
+            verification_key: None,
+            password: None,
+            use_device: false,
+            confirm_signing: false,
+            offline: false,
+            embed_missing_code: false,
+            wasm_dir: None,
+        }
+    }
+
+    /// Query the context of a chain with the given id whose epochs last a
+    /// day
+    async fn query_chain_context(chain_id: &ChainId) -> TxChainContext {
+        let mut client = TestClient::new(crate::queries::RPC);
+        client.wl_storage.storage.chain_id = chain_id.clone();
+        write_value(
+            &mut client,
+            &parameter_storage::get_epoch_duration_storage_key(),
+            EpochDuration {
+                min_num_of_blocks: 10,
+                min_duration: DurationSecs(60 * 60 * 24),
+            },
+        );
+        write_value(
+            &mut client,
+            &parameter_storage::get_gas_cost_key(),
+            BTreeMap::from([(nam(), token::Amount::native_whole(1))]),
+        );
+        let chain_context = rpc::TxChainContextCache::default();
+        chain_context.get(&client).await.unwrap()
+    }
+
+    /// Test that the header of a tx is filled in from the context of the
+    /// connected chain
+    #[tokio::test]
+    async fn test_tx_header_from_chain_context() {
+        let chain_id = ChainId("connected-chain".to_string());
+        let chain_context = query_chain_context(&chain_id).await;
+        assert_eq!(chain_context.chain_id, chain_id);
+        assert_eq!(chain_context.expiration_delta, DurationSecs(60 * 60 * 24));
+        assert_eq!(
+            chain_context.min_fees,
+            BTreeMap::from([(nam(), token::Amount::native_whole(1))])
+        );
+
+        let before = DateTimeUtc::now() + chain_context.expiration_delta;
+        let (header_chain_id, expiration) =
+            tx_header(&chain_agnostic_tx_args(), Some(&chain_context))
+                .unwrap();
+        let after = DateTimeUtc::now() + chain_context.expiration_delta;
+        assert_eq!(header_chain_id, chain_id);
+        let expiration = expiration.unwrap();
+        assert!(before <= expiration && expiration <= after);
+
+        // Explicit values consistent with the connected chain are kept
+        let expiration = DateTimeUtc::now();
+        let args = chain_agnostic_tx_args()
+            .chain_id(chain_id.clone())
+            .tx(|x| args::Tx {
+                expiration: Some(expiration),
+                ..x
+            });
+        let header = tx_header(&args, Some(&chain_context)).unwrap();
+        assert_eq!(header, (chain_id, Some(expiration)));
+    }
+
+    /// Test that the header of a tx built in offline mode only comes from the
+    /// given chain id and expiration
+    #[test]
+    fn test_offline_tx_header() {
+        let chain_id = ChainId("offline-chain".to_string());
+        let args = chain_agnostic_tx_args().offline(chain_id.clone(), None);
+        assert!(args.offline);
+        let header = tx_header(&args, None).unwrap();
+        assert_eq!(header, (chain_id.clone(), None));
+
+        let expiration = DateTimeUtc::now();
+        let args = chain_agnostic_tx_args()
+            .offline(chain_id.clone(), Some(expiration));
+        let header = tx_header(&args, None).unwrap();
+        assert_eq!(header, (chain_id, Some(expiration)));
+
+        assert_matches!(
+            tx_header(&chain_agnostic_tx_args(), None),
+            Err(Error::Tx(TxError::OfflineMissingChainId))
+        );
+    }
+
+    /// Test that a tx can't be built for a chain other than the connected one
+    #[tokio::test]
+    async fn test_tx_header_chain_id_mismatch() {
+        let connected = ChainId("connected-chain".to_string());
+        let chain_context = query_chain_context(&connected).await;
+        let given = ChainId("other-chain".to_string());
+        let args = chain_agnostic_tx_args().chain_id(given.clone());
+        assert_matches!(
+            tx_header(&args, Some(&chain_context)),
+            Err(Error::Tx(TxError::ChainIdMismatch {
+                given: err_given,
+                connected: err_connected,
+            })) if err_given == given && err_connected == connected
+        );
+    }
+}
@@ -18,6 +18,8 @@ const NAMADA_COIN_TYPE: u32 = 877;
 pub enum DerivationPathError {
     #[error("invalid derivation path: {0}")]
     InvalidDerivationPath(String),
+    #[error("no default derivation path for the {0:?} scheme")]
+    UnsupportedScheme(SchemeType),
 }
 
 #[derive(Clone, Debug)]
@@ -44,15 +46,15 @@ impl DerivationPath {
         }
     }
 
-    fn bip44_base_indexes_for_scheme(scheme: SchemeType) -> Vec<ChildIndex> {
-        vec![
-            ChildIndex::Hardened(44),
-            match scheme {
-                SchemeType::Secp256k1 => ChildIndex::Hardened(ETH_COIN_TYPE),
-                SchemeType::Ed25519 => ChildIndex::Hardened(NAMADA_COIN_TYPE),
-                SchemeType::Common => unimplemented!("not implemented"),
-            },
-        ]
+    fn bip44_base_indexes_for_scheme(
+        scheme: SchemeType,
+    ) -> Result<Vec<ChildIndex>, DerivationPathError> {
+        let coin_type = match scheme {
+            SchemeType::Secp256k1 => ETH_COIN_TYPE,
+            SchemeType::Ed25519 => NAMADA_COIN_TYPE,
+            _ => return Err(DerivationPathError::UnsupportedScheme(scheme)),
+        };
+        Ok(vec![ChildIndex::Hardened(44), ChildIndex::Hardened(coin_type)])
     }
 
     fn bip44(
@@ -60,12 +62,12 @@ impl DerivationPath {
         account: u32,
         change: u32,
         address: u32,
-    ) -> Self {
-        let mut indexes = Self::bip44_base_indexes_for_scheme(scheme);
+    ) -> Result<Self, DerivationPathError> {
+        let mut indexes = Self::bip44_base_indexes_for_scheme(scheme)?;
         indexes.push(ChildIndex::Hardened(account));
         indexes.push(ChildIndex::Normal(change));
         indexes.push(ChildIndex::Normal(address));
-        Self::new(indexes)
+        Ok(Self::new(indexes))
     }
 
     fn hardened(&self, scheme: SchemeType) -> Self {
@@ -80,9 +82,11 @@ impl DerivationPath {
         )
     }
 
-    pub fn default_for_scheme(scheme: SchemeType) -> Self {
-        let path = Self::bip44(scheme, 0, 0, 0);
-        path.hardened(scheme)
+    pub fn default_for_scheme(
+        scheme: SchemeType,
+    ) -> Result<Self, DerivationPathError> {
+        let path = Self::bip44(scheme, 0, 0, 0)?;
+        Ok(path.hardened(scheme))
     }
 
     pub fn from_path_str(
@@ -4,6 +4,7 @@ use std::fmt::Display;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
+use argon2::{Algorithm, Argon2, Version};
 use borsh::{BorshDeserialize, BorshSerialize};
 use borsh_ext::BorshSerializeExt;
 use data_encoding::HEXLOWER;
@@ -16,6 +17,11 @@ use crate::wallet::WalletIo;
 
 const ENCRYPTED_KEY_PREFIX: &str = "encrypted:";
 const UNENCRYPTED_KEY_PREFIX: &str = "unencrypted:";
+/// Prefix of the keypairs encrypted with Argon2id, followed by its parameters.
+/// The keypairs encrypted with the legacy key derivation have no prefix.
+const ARGON2ID_PREFIX: &str = "argon2id:";
+/// The length of the encryption key in bytes
+const ENCRYPTION_KEY_LEN: usize = 32;
 
 /// A keypair stored in a wallet
 #[derive(Debug)]
@@ -112,24 +118,145 @@ pub enum DeserializeStoredKeypairError {
     MissingPrefix,
 }
 
+/// Tunable parameters of the Argon2id key derivation function that derives
+/// the encryption key of a keypair from a password
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The second recommended option of RFC 9106, which fits in the memory of
+    /// constrained environments
+    fn default() -> Self {
+        Self {
+            memory_kib: 1 << 16,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Construct the parameters from the memory cost in KiB, the number of
+    /// iterations and the degree of parallelism. Returns `None` if they are
+    /// out of the ranges supported by Argon2.
+    pub fn new(
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    ) -> Option<Self> {
+        let params = Self {
+            memory_kib,
+            iterations,
+            parallelism,
+        };
+        params.argon2().map(|_| params)
+    }
+
+    /// The memory cost in KiB
+    pub fn memory_kib(&self) -> u32 {
+        self.memory_kib
+    }
+
+    /// The number of iterations
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// The degree of parallelism
+    pub fn parallelism(&self) -> u32 {
+        self.parallelism
+    }
+
+    /// The Argon2id hasher with these parameters
+    fn argon2(&self) -> Option<Argon2<'static>> {
+        let params = argon2::Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(ENCRYPTION_KEY_LEN),
+        )
+        .ok()?;
+        Some(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// The key derivation function used to encrypt a keypair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kdf {
+    /// Argon2i with fixed parameters, written by older versions of the wallet
+    LegacyArgon2i,
+    /// Argon2id with the given parameters
+    Argon2id(KdfParams),
+}
+
 /// An encrypted keypair stored in a wallet
 #[derive(Debug)]
-pub struct EncryptedKeypair<T: BorshSerialize + BorshDeserialize>(
-    Vec<u8>,
-    PhantomData<T>,
-);
+pub struct EncryptedKeypair<T: BorshSerialize + BorshDeserialize> {
+    kdf: Kdf,
+    /// The salt followed by the sealed keypair
+    data: Vec<u8>,
+    phantom: PhantomData<T>,
+}
 
 impl<T: BorshSerialize + BorshDeserialize> Display for EncryptedKeypair<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", HEXLOWER.encode(self.0.as_ref()))
+        match &self.kdf {
+            Kdf::LegacyArgon2i => {
+                write!(f, "{}", HEXLOWER.encode(self.data.as_ref()))
+            }
+            Kdf::Argon2id(params) => write!(
+                f,
+                "{}{}:{}:{}:{}",
+                ARGON2ID_PREFIX,
+                params.memory_kib,
+                params.iterations,
+                params.parallelism,
+                HEXLOWER.encode(self.data.as_ref())
+            ),
+        }
     }
 }
 
 impl<T: BorshSerialize + BorshDeserialize> FromStr for EncryptedKeypair<T> {
-    type Err = data_encoding::DecodeError;
+    type Err = DeserializeStoredKeypairError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        HEXLOWER.decode(s.as_ref()).map(|x| Self(x, PhantomData))
+        let invalid = |err: String| {
+            DeserializeStoredKeypairError::InvalidStoredKeypairString(err)
+        };
+        let (kdf, data) = match s.strip_prefix(ARGON2ID_PREFIX) {
+            Some(rest) => {
+                let mut parts = rest.splitn(4, ':');
+                let mut param = || -> Result<u32, Self::Err> {
+                    parts
+                        .next()
+                        .ok_or_else(|| invalid("Missing KDF parameter".into()))?
+                        .parse()
+                        .map_err(|err: std::num::ParseIntError| {
+                            invalid(err.to_string())
+                        })
+                };
+                let params = KdfParams::new(param()?, param()?, param()?)
+                    .ok_or_else(|| invalid("Invalid KDF parameters".into()))?;
+                let data = parts
+                    .next()
+                    .ok_or_else(|| invalid("Missing encrypted data".into()))?;
+                (Kdf::Argon2id(params), data)
+            }
+            None => (Kdf::LegacyArgon2i, s),
+        };
+        let data = HEXLOWER
+            .decode(data.as_ref())
+            .map_err(|err| invalid(err.to_string()))?;
+        Ok(Self {
+            kdf,
+            data,
+            phantom: PhantomData,
+        })
     }
 }
 
@@ -138,6 +265,8 @@ impl<T: BorshSerialize + BorshDeserialize> FromStr for EncryptedKeypair<T> {
 pub enum DecryptionError {
     #[error("Unexpected encryption salt")]
     BadSalt,
+    #[error("Invalid key derivation parameters")]
+    BadKdfParams,
     #[error("Unable to decrypt the keypair. Is the password correct?")]
     DecryptionError,
     #[error("Unable to deserialize the keypair")]
@@ -194,22 +323,89 @@ where
             StoredKeypair::Raw(_) => false,
         }
     }
+
+    /// Indicates whether this key is encrypted with the legacy key derivation
+    /// and should be re-encrypted.
+    ///
+    /// Unencrypted keys deliberately don't need a migration: they are only
+    /// stored raw when their owner opted out of encryption (e.g. with
+    /// `--unsafe-dont-encrypt`), so there is no password of theirs to encrypt
+    /// them with, and prompting for a new one on an unrelated command would
+    /// override that choice.
+    pub fn needs_migration(&self) -> bool {
+        match self {
+            StoredKeypair::Encrypted(encrypted) => encrypted.is_legacy(),
+            StoredKeypair::Raw(_) => false,
+        }
+    }
+
+    /// Re-encrypt a key encrypted with the legacy key derivation using the
+    /// current one, if the given password decrypts it. Returns whether the
+    /// key has been re-encrypted. Unencrypted keys are left as they are, see
+    /// [`StoredKeypair::needs_migration`].
+    pub fn migrate(&mut self, password: &Zeroizing<String>) -> bool {
+        let StoredKeypair::Encrypted(encrypted) = self else {
+            return false;
+        };
+        if !encrypted.is_legacy() {
+            return false;
+        }
+        match encrypted.decrypt(password.clone()) {
+            Ok(keypair) => {
+                *encrypted = EncryptedKeypair::new(&keypair, password.clone());
+                true
+            }
+            Err(_) => false,
+        }
+    }
 }
 
 impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
     /// Encrypt a keypair and store it with its salt.
     pub fn new(keypair: &T, password: Zeroizing<String>) -> Self {
+        Self::new_with_params(keypair, password, KdfParams::default())
+    }
+
+    /// Encrypt a keypair with a key derived using the given parameters and
+    /// store it with its salt.
+    pub fn new_with_params(
+        keypair: &T,
+        password: Zeroizing<String>,
+        params: KdfParams,
+    ) -> Self {
         let salt = encryption_salt();
-        let encryption_key = encryption_key(&salt, &password);
+        let encryption_key = argon2id_encryption_key(&salt, &password, &params)
+            .expect("Generation of encryption secret key shouldn't fail");
+        Self::seal(keypair, Kdf::Argon2id(params), salt, &encryption_key)
+    }
 
-        let data = keypair.serialize_to_vec();
+    /// Encrypt a keypair with the legacy key derivation, as older versions of
+    /// the wallet did
+    #[cfg(test)]
+    pub(crate) fn new_legacy(keypair: &T, password: Zeroizing<String>) -> Self {
+        let salt = encryption_salt();
+        let encryption_key = legacy_encryption_key(&salt, &password);
+        Self::seal(keypair, Kdf::LegacyArgon2i, salt, &encryption_key)
+    }
+
+    fn seal(
+        keypair: &T,
+        kdf: Kdf,
+        salt: kdf::Salt,
+        encryption_key: &aead::SecretKey,
+    ) -> Self {
+        let data = Zeroizing::new(keypair.serialize_to_vec());
 
-        let encrypted_keypair = aead::seal(&encryption_key, &data)
+        let encrypted_keypair = aead::seal(encryption_key, &data)
             .expect("Encryption of data shouldn't fail");
 
         let encrypted_data = [salt.as_ref(), &encrypted_keypair].concat();
 
-        Self(encrypted_data, PhantomData)
+        Self {
+            kdf,
+            data: encrypted_data,
+            phantom: PhantomData,
+        }
     }
 
     /// Decrypt an encrypted keypair
@@ -218,19 +414,35 @@ impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
         password: Zeroizing<String>,
     ) -> Result<T, DecryptionError> {
         let salt_len = encryption_salt().len();
-        let (raw_salt, cipher) = self.0.split_at(salt_len);
+        if self.data.len() < salt_len {
+            return Err(DecryptionError::BadSalt);
+        }
+        let (raw_salt, cipher) = self.data.split_at(salt_len);
 
         let salt = kdf::Salt::from_slice(raw_salt)
             .map_err(|_| DecryptionError::BadSalt)?;
 
-        let encryption_key = encryption_key(&salt, &password);
+        let encryption_key = match &self.kdf {
+            Kdf::LegacyArgon2i => legacy_encryption_key(&salt, &password),
+            Kdf::Argon2id(params) => {
+                argon2id_encryption_key(&salt, &password, params)?
+            }
+        };
 
-        let decrypted_data = aead::open(&encryption_key, cipher)
-            .map_err(|_| DecryptionError::DecryptionError)?;
+        let decrypted_data = Zeroizing::new(
+            aead::open(&encryption_key, cipher)
+                .map_err(|_| DecryptionError::DecryptionError)?,
+        );
 
         T::try_from_slice(&decrypted_data)
             .map_err(|_| DecryptionError::DeserializingError)
     }
+
+    /// Indicates whether this keypair is encrypted with the legacy key
+    /// derivation
+    pub fn is_legacy(&self) -> bool {
+        self.kdf == Kdf::LegacyArgon2i
+    }
 }
 
 /// Keypair encryption salt
@@ -238,9 +450,138 @@ fn encryption_salt() -> kdf::Salt {
     kdf::Salt::default()
 }
 
-/// Make encryption secret key from a password.
-fn encryption_key(salt: &kdf::Salt, password: &str) -> kdf::SecretKey {
+/// Make encryption secret key from a password with Argon2id.
+fn argon2id_encryption_key(
+    salt: &kdf::Salt,
+    password: &str,
+    params: &KdfParams,
+) -> Result<aead::SecretKey, DecryptionError> {
+    let mut key = Zeroizing::new([0u8; ENCRYPTION_KEY_LEN]);
+    params
+        .argon2()
+        .ok_or(DecryptionError::BadKdfParams)?
+        .hash_password_into(password.as_bytes(), salt.as_ref(), &mut *key)
+        .map_err(|_| DecryptionError::BadKdfParams)?;
+    Ok(aead::SecretKey::from_slice(&*key)
+        .expect("Encryption secret key should have a valid length"))
+}
+
+/// Make encryption secret key from a password with the legacy key derivation.
+fn legacy_encryption_key(salt: &kdf::Salt, password: &str) -> kdf::SecretKey {
     kdf::Password::from_slice(password.as_bytes())
-        .and_then(|password| kdf::derive_key(&password, salt, 3, 1 << 17, 32))
+        .and_then(|password| {
+            kdf::derive_key(
+                &password,
+                salt,
+                3,
+                1 << 17,
+                ENCRYPTION_KEY_LEN as u32,
+            )
+        })
         .expect("Generation of encryption secret key shouldn't fail")
 }
+
+#[cfg(test)]
+mod tests {
+    use namada_core::types::key::testing::keypair_1;
+    use namada_core::types::key::{common, RefTo};
+
+    use super::*;
+
+    fn password() -> Zeroizing<String> {
+        Zeroizing::new("VeRySeCuR3".to_string())
+    }
+
+    /// Cheap parameters to keep the tests fast
+    fn test_params() -> KdfParams {
+        KdfParams::new(1 << 10, 1, 1).unwrap()
+    }
+
+    /// Test that an encrypted keypair survives its string encoding and is
+    /// decrypted with the password it was encrypted with
+    #[test]
+    fn test_encryption_round_trip() {
+        for params in [test_params(), KdfParams::default()] {
+            let encrypted = EncryptedKeypair::new_with_params(
+                &keypair_1(),
+                password(),
+                params,
+            );
+            let encoded = encrypted.to_string();
+            assert!(encoded.starts_with(&format!(
+                "{}{}:{}:{}:",
+                ARGON2ID_PREFIX,
+                params.memory_kib(),
+                params.iterations(),
+                params.parallelism()
+            )));
+            let decoded: EncryptedKeypair<common::SecretKey> =
+                encoded.parse().unwrap();
+            assert!(!decoded.is_legacy());
+            let keypair = decoded.decrypt(password()).unwrap();
+            assert_eq!(keypair.ref_to(), keypair_1().ref_to());
+        }
+    }
+
+    /// Test that a wrong password fails to decrypt a keypair
+    #[test]
+    fn test_wrong_password() {
+        let encrypted = EncryptedKeypair::new_with_params(
+            &keypair_1(),
+            password(),
+            test_params(),
+        );
+        let wrong_password = Zeroizing::new("wrong".to_string());
+        assert!(matches!(
+            encrypted.decrypt(wrong_password),
+            Err(DecryptionError::DecryptionError)
+        ));
+    }
+
+    /// Test that out of range parameters are rejected
+    #[test]
+    fn test_invalid_params() {
+        assert!(KdfParams::new(1 << 10, 0, 1).is_none());
+        assert!(KdfParams::new(1 << 10, 1, 0).is_none());
+        assert!(KdfParams::new(0, 1, 1).is_none());
+        let encoded = format!("{}0:1:1:00", ARGON2ID_PREFIX);
+        assert!(
+            encoded
+                .parse::<EncryptedKeypair<common::SecretKey>>()
+                .is_err()
+        );
+    }
+
+    /// Test that a keypair in the legacy format is still decrypted, and
+    /// re-encrypted with the current key derivation by a migration
+    #[test]
+    fn test_migrate_legacy_keypair() {
+        let legacy = EncryptedKeypair::new_legacy(&keypair_1(), password());
+        let encoded = format!("{}{}", ENCRYPTED_KEY_PREFIX, legacy);
+        let mut stored: StoredKeypair<common::SecretKey> =
+            serde_json::from_value(encoded.into()).unwrap();
+        assert!(stored.needs_migration());
+
+        assert!(!stored.migrate(&Zeroizing::new("wrong".to_string())));
+        assert!(stored.needs_migration());
+        assert!(stored.migrate(&password()));
+        assert!(!stored.needs_migration());
+        assert!(!stored.migrate(&password()));
+
+        let StoredKeypair::Encrypted(encrypted) = &stored else {
+            panic!("The keypair should remain encrypted");
+        };
+        let keypair = encrypted.decrypt(password()).unwrap();
+        assert_eq!(keypair.ref_to(), keypair_1().ref_to());
+    }
+
+    /// Test that an unencrypted keypair is deliberately left unencrypted by
+    /// a migration
+    #[test]
+    fn test_migrate_raw_keypair() {
+        let (mut stored, _) = StoredKeypair::new(keypair_1(), None);
+        assert!(!stored.needs_migration());
+        assert!(!stored.migrate(&password()));
+        assert!(!stored.is_encrypted());
+    }
+}
@@ -14,6 +14,7 @@ use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use borsh::{BorshDeserialize, BorshSerialize};
 use namada_core::types::address::Address;
 use namada_core::types::key::*;
+use namada_core::types::key::pop::{prove_possession, ProofOfPossession};
 use namada_core::types::masp::{
     ExtendedSpendingKey, ExtendedViewingKey, PaymentAddress,
 };
@@ -25,7 +26,7 @@ use thiserror::Error;
 use zeroize::Zeroizing;
 
 pub use self::derivation_path::{DerivationPath, DerivationPathError};
-pub use self::keys::{DecryptionError, StoredKeypair};
+pub use self::keys::{DecryptionError, KdfParams, StoredKeypair};
 pub use self::store::{ConfirmationResponse, ValidatorData, ValidatorKeys};
 use crate::wallet::store::derive_hd_secret_key;
 
@@ -44,6 +45,9 @@ pub enum GenRestoreKeyError {
     /// Key storage error
     #[error("Key storage error")]
     KeyStorageError,
+    /// Keys of the scheme can't be generated by the wallet
+    #[error("Cannot generate secret keys of the {0:?} scheme")]
+    UnsupportedScheme(SchemeType),
 }
 
 /// Captures the interactive parts of the wallet's functioning
@@ -121,6 +125,12 @@ pub trait WalletStorage: Sized + Clone {
 
     /// Load a wallet from the store file.
     fn load<U>(&self, wallet: &mut Wallet<U>) -> Result<(), LoadStoreError>;
+
+    /// Back up the store file before it gets migrated to a new format. The
+    /// first backup must be kept by later calls.
+    fn backup(&self) -> Result<(), LoadStoreError> {
+        Ok(())
+    }
 }
 
 #[cfg(feature = "std")]
@@ -143,6 +153,11 @@ pub mod fs {
 
     /// Wallet file name
     const FILE_NAME: &str = "wallet.toml";
+    /// Name of the temporary file that the wallet is written to before it
+    /// replaces the wallet file
+    const TMP_FILE_NAME: &str = "wallet.toml.tmp";
+    /// Name of the copy of the wallet file made before its migration
+    const BACKUP_FILE_NAME: &str = "wallet.toml.bak";
 
     impl<F: FsWalletStorage> WalletStorage for F {
         fn save<U>(&self, wallet: &Wallet<U>) -> Result<(), LoadStoreError> {
@@ -153,18 +168,26 @@ pub mod fs {
             fs::create_dir_all(wallet_dir).map_err(|err| {
                 LoadStoreError::StoreNewWallet(err.to_string())
             })?;
-            // Write the file
+            // Lock the wallet file for the duration of the write
             let mut options = fs::OpenOptions::new();
-            options.create(true).write(true).truncate(true);
+            options.create(true).write(true);
             let mut lock =
-                RwLock::new(options.open(wallet_path).map_err(|err| {
+                RwLock::new(options.open(&wallet_path).map_err(|err| {
                     LoadStoreError::StoreNewWallet(err.to_string())
                 })?);
-            let mut guard = lock.write().map_err(|err| {
+            let _guard = lock.write().map_err(|err| {
                 LoadStoreError::StoreNewWallet(err.to_string())
             })?;
-            guard
-                .write_all(&data)
+            // Write a temporary file and atomically move it over the wallet
+            // file, so that a crash mid-save leaves the previous wallet intact
+            let tmp_path = wallet_dir.join(TMP_FILE_NAME);
+            let write_tmp = || -> std::io::Result<()> {
+                let mut tmp_file = fs::File::create(&tmp_path)?;
+                tmp_file.write_all(&data)?;
+                tmp_file.sync_all()
+            };
+            write_tmp()
+                .and_then(|()| fs::rename(&tmp_path, &wallet_path))
                 .map_err(|err| LoadStoreError::StoreNewWallet(err.to_string()))
         }
 
@@ -199,6 +222,17 @@ pub mod fs {
                 Store::decode(store).map_err(LoadStoreError::Decode)?;
             Ok(())
         }
+
+        fn backup(&self) -> Result<(), LoadStoreError> {
+            let wallet_path = self.store_dir().join(FILE_NAME);
+            let backup_path = self.store_dir().join(BACKUP_FILE_NAME);
+            if backup_path.exists() || !wallet_path.exists() {
+                return Ok(());
+            }
+            fs::copy(wallet_path, backup_path)
+                .map(|_| ())
+                .map_err(|err| LoadStoreError::StoreNewWallet(err.to_string()))
+        }
     }
 
     /// For a non-interactive filesystem based wallet
@@ -224,21 +258,139 @@ pub mod fs {
             &self.store_dir
         }
     }
+
+    #[cfg(test)]
+    mod test {
+        use std::path::Path;
+
+        use namada_core::types::key::testing::keypair_1;
+        use tempfile::tempdir;
+
+        use super::*;
+        use crate::wallet::keys::EncryptedKeypair;
+
+        fn password() -> Zeroizing<String> {
+            Zeroizing::new("VeRySeCuR3".to_string())
+        }
+
+        /// Write a wallet file holding a secret key under the alias `alice`
+        /// encrypted with the legacy key derivation
+        fn write_legacy_wallet(store_dir: &Path) -> Vec<u8> {
+            let keypair =
+                EncryptedKeypair::new_legacy(&keypair_1(), password());
+            let mut store: toml::Value =
+                toml::from_slice(&Store::default().encode()).unwrap();
+            store
+                .get_mut("secret_keys")
+                .and_then(toml::Value::as_table_mut)
+                .unwrap()
+                .insert("alice".into(), format!("encrypted:{keypair}").into());
+            let data = toml::to_vec(&store).unwrap();
+            fs::write(store_dir.join(FILE_NAME), &data).unwrap();
+            data
+        }
+
+        /// Test that a key encrypted with the legacy key derivation is
+        /// re-encrypted once it has been decrypted, after backing up the
+        /// wallet file
+        #[test]
+        fn test_migrate_legacy_wallet() {
+            let dir = tempdir().unwrap();
+            let legacy = write_legacy_wallet(dir.path());
+
+            let mut wallet = FsWalletUtils::new(dir.path().to_path_buf());
+            wallet.load().unwrap();
+            assert!(wallet.store().needs_migration());
+            let key =
+                wallet.find_secret_key("alice", Some(password())).unwrap();
+            assert_eq!(key.ref_to(), keypair_1().ref_to());
+            wallet.save_migrated().unwrap();
+            assert_eq!(
+                fs::read(dir.path().join(BACKUP_FILE_NAME)).unwrap(),
+                legacy
+            );
+
+            let mut wallet = FsWalletUtils::new(dir.path().to_path_buf());
+            wallet.load().unwrap();
+            assert!(!wallet.store().needs_migration());
+            let key =
+                wallet.find_secret_key("alice", Some(password())).unwrap();
+            assert_eq!(key.ref_to(), keypair_1().ref_to());
+        }
+
+        /// Test that a wrong password neither decrypts nor migrates a key
+        #[test]
+        fn test_wrong_password_does_not_migrate() {
+            let dir = tempdir().unwrap();
+            let legacy = write_legacy_wallet(dir.path());
+
+            let mut wallet = FsWalletUtils::new(dir.path().to_path_buf());
+            wallet.load().unwrap();
+            let wrong_password = Zeroizing::new("wrong".to_string());
+            assert!(matches!(
+                wallet.find_secret_key("alice", Some(wrong_password)),
+                Err(FindKeyError::KeyDecryptionError(
+                    DecryptionError::DecryptionError
+                ))
+            ));
+            wallet.save_migrated().unwrap();
+            assert!(wallet.store().needs_migration());
+            assert!(!dir.path().join(BACKUP_FILE_NAME).exists());
+            assert_eq!(fs::read(dir.path().join(FILE_NAME)).unwrap(), legacy);
+        }
+
+        /// Test that a crash while writing the wallet leaves the previous
+        /// wallet file readable and doesn't prevent later saves
+        #[test]
+        fn test_crash_during_save() {
+            let dir = tempdir().unwrap();
+            let mut wallet = FsWalletUtils::new(dir.path().to_path_buf());
+            wallet
+                .insert_keypair(
+                    "alice".into(),
+                    true,
+                    keypair_1(),
+                    Some(password()),
+                    None,
+                    None,
+                )
+                .unwrap();
+            wallet.save().unwrap();
+
+            // Simulate a crash after part of the new wallet has been written
+            let data = wallet.store().encode();
+            fs::write(dir.path().join(TMP_FILE_NAME), &data[..data.len() / 2])
+                .unwrap();
+
+            let mut wallet = FsWalletUtils::new(dir.path().to_path_buf());
+            wallet.load().unwrap();
+            let key =
+                wallet.find_secret_key("alice", Some(password())).unwrap();
+            assert_eq!(key.ref_to(), keypair_1().ref_to());
+
+            wallet.save().unwrap();
+            assert!(!dir.path().join(TMP_FILE_NAME).exists());
+            let mut wallet = FsWalletUtils::new(dir.path().to_path_buf());
+            wallet.load().unwrap();
+            assert!(wallet.store().find_secret_key("alice").is_some());
+        }
+    }
 }
 
 /// Generate a new secret key.
 pub fn gen_secret_key(
     scheme: SchemeType,
     csprng: &mut (impl CryptoRng + RngCore),
-) -> common::SecretKey {
-    match scheme {
+) -> Result<common::SecretKey, GenRestoreKeyError> {
+    let sk = match scheme {
         SchemeType::Ed25519 => ed25519::SigScheme::generate(csprng).try_to_sk(),
         SchemeType::Secp256k1 => {
             secp256k1::SigScheme::generate(csprng).try_to_sk()
         }
         SchemeType::Common => common::SigScheme::generate(csprng).try_to_sk(),
-    }
-    .unwrap()
+        _ => return Err(GenRestoreKeyError::UnsupportedScheme(scheme)),
+    };
+    Ok(sk.unwrap())
 }
 
 fn gen_spending_key(
@@ -269,6 +421,8 @@ pub struct Wallet<U> {
     store: Store,
     decrypted_key_cache: HashMap<Alias, common::SecretKey>,
     decrypted_spendkey_cache: HashMap<Alias, ExtendedSpendingKey>,
+    /// Whether some keys have been re-encrypted but not saved yet
+    pending_migration: bool,
 }
 
 impl<U> From<Wallet<U>> for Store {
@@ -285,6 +439,7 @@ impl<U> Wallet<U> {
             store,
             decrypted_key_cache: HashMap::default(),
             decrypted_spendkey_cache: HashMap::default(),
+            pending_migration: false,
         }
     }
 
@@ -489,6 +644,18 @@ impl<U: WalletStorage> Wallet<U> {
     pub fn save(&self) -> Result<(), LoadStoreError> {
         self.utils.save(self)
     }
+
+    /// Save the keys that have been re-encrypted with the current key
+    /// derivation since they were decrypted, if any, after backing up the
+    /// store file.
+    pub fn save_migrated(&mut self) -> Result<(), LoadStoreError> {
+        if self.pending_migration {
+            self.utils.backup()?;
+            self.save()?;
+            self.pending_migration = false;
+        }
+        Ok(())
+    }
 }
 
 impl<U: WalletIo> Wallet<U> {
@@ -521,7 +688,7 @@ impl<U: WalletIo> Wallet<U> {
             scheme,
             seed.as_bytes(),
             derivation_path.clone(),
-        );
+        )?;
 
         self.insert_keypair(
             alias.unwrap_or_default(),
@@ -570,7 +737,7 @@ impl<U: WalletIo> Wallet<U> {
         password: Option<Zeroizing<String>>,
         rng: &mut (impl CryptoRng + RngCore),
     ) -> Result<(String, common::SecretKey), GenRestoreKeyError> {
-        let sk = gen_secret_key(scheme, rng);
+        let sk = gen_secret_key(scheme, rng)?;
         self.insert_keypair(
             alias.unwrap_or_default(),
             alias_force,
@@ -624,7 +791,7 @@ impl<U: WalletIo> Wallet<U> {
             scheme,
             seed.as_bytes(),
             derivation_path.clone(),
-        );
+        )?;
         self.insert_keypair(
             alias.unwrap_or_default(),
             alias_force,
@@ -689,12 +856,14 @@ impl<U: WalletIo> Wallet<U> {
             .store
             .find_secret_key(alias_pkh_or_pk.as_ref())
             .ok_or(FindKeyError::KeyNotFound)?;
-        Self::decrypt_stored_key::<_>(
+        let (key, legacy_password) = Self::decrypt_stored_key::<_>(
             &mut self.decrypted_key_cache,
             stored_key,
             alias_pkh_or_pk.into(),
             password,
-        )
+        )?;
+        self.migrate_encryption(legacy_password);
+        Ok(key)
     }
 
     /// Find the public key by an alias or a public key hash.
@@ -727,12 +896,14 @@ impl<U: WalletIo> Wallet<U> {
             .store
             .find_spending_key(alias.as_ref())
             .ok_or(FindKeyError::KeyNotFound)?;
-        Self::decrypt_stored_key::<_>(
+        let (key, legacy_password) = Self::decrypt_stored_key::<_>(
             &mut self.decrypted_spendkey_cache,
             stored_spendkey,
             alias.into(),
             password,
-        )
+        )?;
+        self.migrate_encryption(legacy_password);
+        Ok(key)
     }
 
     /// Find the stored key by a public key.
@@ -749,6 +920,20 @@ impl<U: WalletIo> Wallet<U> {
         self.find_key_by_pkh(&pkh, password)
     }
 
+    /// Prove the possession of the secret key of the given public key for the
+    /// given address, for the registration of the key on chain. If the key is
+    /// encrypted and password not supplied, then password will be
+    /// interactively prompted for.
+    pub fn prove_key_possession(
+        &mut self,
+        pk: &common::PublicKey,
+        address: &Address,
+        password: Option<Zeroizing<String>>,
+    ) -> Result<ProofOfPossession, FindKeyError> {
+        let keypair = self.find_key_by_pk(pk, password)?;
+        Ok(prove_possession(&keypair, address))
+    }
+
     /// Find a derivation path by public key hash
     pub fn find_path_by_pkh(
         &self,
@@ -796,18 +981,32 @@ impl<U: WalletIo> Wallet<U> {
             .store
             .find_key_by_pkh(pkh)
             .ok_or(FindKeyError::KeyNotFound)?;
-        Self::decrypt_stored_key(
+        let (key, legacy_password) = Self::decrypt_stored_key(
             &mut self.decrypted_key_cache,
             stored_key,
             alias,
             password,
-        )
+        )?;
+        self.migrate_encryption(legacy_password);
+        Ok(key)
+    }
+
+    /// Re-encrypt the keys encrypted with the legacy key derivation that the
+    /// given password decrypts, if any. They are persisted by
+    /// [`Wallet::save_migrated`].
+    fn migrate_encryption(&mut self, password: Option<Zeroizing<String>>) {
+        if let Some(password) = password {
+            if self.store.migrate_encryption(&password) > 0 {
+                self.pending_migration = true;
+            }
+        }
     }
 
     /// Decrypt stored key, if it's not stored un-encrypted.
     /// If a given storage key needs to be decrypted and password is not
     /// supplied, then interactively prompt for password and if successfully
-    /// decrypted, store it in a cache.
+    /// decrypted, store it in a cache. The password is returned along with
+    /// the key if it was encrypted with the legacy key derivation.
     fn decrypt_stored_key<
         T: FromStr + Display + BorshSerialize + BorshDeserialize + Clone,
     >(
@@ -815,7 +1014,7 @@ impl<U: WalletIo> Wallet<U> {
         stored_key: &StoredKeypair<T>,
         alias: Alias,
         password: Option<Zeroizing<String>>,
-    ) -> Result<T, FindKeyError>
+    ) -> Result<(T, Option<Zeroizing<String>>), FindKeyError>
     where
         <T as std::str::FromStr>::Err: Display,
     {
@@ -824,15 +1023,16 @@ impl<U: WalletIo> Wallet<U> {
                 let password =
                     password.unwrap_or_else(|| U::read_password(false));
                 let key = encrypted
-                    .decrypt(password)
+                    .decrypt(password.clone())
                     .map_err(FindKeyError::KeyDecryptionError)?;
                 decrypted_key_cache.insert(alias.clone(), key);
-                decrypted_key_cache
+                let key = decrypted_key_cache
                     .get(&alias)
                     .cloned()
-                    .ok_or(FindKeyError::KeyNotFound)
+                    .ok_or(FindKeyError::KeyNotFound)?;
+                Ok((key, encrypted.is_legacy().then_some(password)))
             }
-            StoredKeypair::Raw(raw) => Ok(raw.clone()),
+            StoredKeypair::Raw(raw) => Ok((raw.clone(), None)),
         }
     }
 
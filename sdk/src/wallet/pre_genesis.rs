@@ -77,9 +77,12 @@ pub fn gen_key_to_store(
     scheme: SchemeType,
     password: Option<Zeroizing<String>>,
     rng: &mut (impl CryptoRng + Rng),
-) -> (StoredKeypair<common::SecretKey>, common::SecretKey) {
-    let sk = wallet::gen_secret_key(scheme, rng);
-    StoredKeypair::new(sk, password)
+) -> Result<
+    (StoredKeypair<common::SecretKey>, common::SecretKey),
+    wallet::GenRestoreKeyError,
+> {
+    let sk = wallet::gen_secret_key(scheme, rng)?;
+    Ok(StoredKeypair::new(sk, password))
 }
 
 impl From<wallet::keys::DecryptionError> for ReadError {
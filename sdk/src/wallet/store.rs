@@ -19,7 +19,7 @@ use zeroize::Zeroizing;
 use super::alias::{self, Alias};
 use super::derivation_path::DerivationPath;
 use super::pre_genesis;
-use crate::wallet::{StoredKeypair, WalletIo};
+use crate::wallet::{GenRestoreKeyError, StoredKeypair, WalletIo};
 
 /// Actions that can be taken when there is an alias conflict
 pub enum ConfirmationResponse {
@@ -253,6 +253,35 @@ impl Store {
         &self.spend_keys
     }
 
+    /// Indicates whether any of the stored keys is encrypted with the legacy
+    /// key derivation. Unencrypted keys are not migrated, see
+    /// [`StoredKeypair::needs_migration`].
+    pub fn needs_migration(&self) -> bool {
+        self.secret_keys
+            .values()
+            .any(StoredKeypair::needs_migration)
+            || self.spend_keys.values().any(StoredKeypair::needs_migration)
+    }
+
+    /// Re-encrypt the keys encrypted with the legacy key derivation that the
+    /// given password decrypts. Returns the number of re-encrypted keys.
+    pub fn migrate_encryption(
+        &mut self,
+        password: &Zeroizing<String>,
+    ) -> usize {
+        let secret_keys = self
+            .secret_keys
+            .values_mut()
+            .filter(|key| key.migrate(password))
+            .count();
+        let spend_keys = self
+            .spend_keys
+            .values_mut()
+            .filter(|key| key.migrate(password))
+            .count();
+        secret_keys + spend_keys
+    }
+
     /// Add validator data to the store
     pub fn add_validator_data(
         &mut self,
@@ -695,8 +724,8 @@ pub fn derive_hd_secret_key(
     scheme: SchemeType,
     seed: &[u8],
     derivation_path: DerivationPath,
-) -> common::SecretKey {
-    match scheme {
+) -> Result<common::SecretKey, GenRestoreKeyError> {
+    let sk = match scheme {
         SchemeType::Ed25519 => {
             let indexes = derivation_path
                 .path()
@@ -724,7 +753,9 @@ pub fn derive_hd_secret_key(
                  alternative scheme."
             )
         }
-    }
+        _ => return Err(GenRestoreKeyError::UnsupportedScheme(scheme)),
+    };
+    Ok(sk)
 }
 
 impl Display for AddressVpType {
@@ -798,7 +829,8 @@ mod test_wallet {
             DerivationPath::from_path_str(SCHEME, DERIVATION_PATH)
                 .expect("Derivation path construction cannot fail");
 
-        let sk = derive_hd_secret_key(SCHEME, seed.as_bytes(), derivation_path);
+        let sk = derive_hd_secret_key(SCHEME, seed.as_bytes(), derivation_path)
+            .unwrap();
 
         assert_eq!(&sk.to_string()[2..], SK_EXPECTED);
     }
@@ -828,18 +860,105 @@ mod test_wallet {
             DerivationPath::from_path_str(SCHEME, DERIVATION_PATH_HARDENED)
                 .expect("Derivation path construction cannot fail");
 
-        let sk = derive_hd_secret_key(SCHEME, seed.as_bytes(), derivation_path);
+        let sk = derive_hd_secret_key(SCHEME, seed.as_bytes(), derivation_path)
+            .unwrap();
 
         let sk_hard = derive_hd_secret_key(
             SCHEME,
             seed.as_bytes(),
             derivation_path_hardened,
-        );
+        )
+        .unwrap();
 
         // check that indexes are promoted to hardened
         assert_eq!(&sk.to_string(), &sk_hard.to_string());
     }
 
+    /// Wallet IO for non-interactive tests
+    #[derive(Clone)]
+    struct TestWalletIo;
+
+    impl WalletIo for TestWalletIo {
+        type Rng = rand_core::OsRng;
+    }
+
+    /// Test that keys derived from a mnemonic record their derivation path
+    /// and sign txs whose signatures verify
+    #[tokio::test]
+    async fn derived_keys_sign_verifiable_txs() {
+        use namada_core::proto::Tx;
+        use namada_core::types::account::AccountPublicKeysMap;
+        use namada_core::types::chain::ChainId;
+
+        use crate::signer::{sign_raw, Signer, SoftwareSigner};
+        use crate::wallet::Wallet;
+
+        const MNEMONIC_CODE: &str = "cruise ball fame lucky fabric govern \
+                                     length fruit permit tonight fame pear \
+                                     horse park key chimney furnace lobster \
+                                     foot example shoot dry fuel lawn";
+        const PASSPHRASE: &str = "test";
+
+        let mut wallet = Wallet::new(TestWalletIo, Store::default());
+        for (scheme, alias) in [
+            (SchemeType::Ed25519, "ed25519-key"),
+            (SchemeType::Secp256k1, "secp256k1-key"),
+        ] {
+            let mnemonic =
+                Mnemonic::from_phrase(MNEMONIC_CODE, Language::English)
+                    .expect("Mnemonic construction cannot fail.");
+            let (alias, sk) = wallet
+                .derive_key_from_mnemonic_code(
+                    scheme,
+                    Some(alias.to_string()),
+                    false,
+                    DerivationPath::default_for_scheme(scheme).unwrap(),
+                    Some((
+                        mnemonic.clone(),
+                        Zeroizing::new(PASSPHRASE.to_string()),
+                    )),
+                    Some(Zeroizing::new("password".to_string())),
+                )
+                .expect("Key derivation cannot fail");
+            let pk = sk.ref_to();
+            assert!(
+                wallet
+                    .store()
+                    .find_secret_key(&alias)
+                    .unwrap()
+                    .is_encrypted()
+            );
+
+            // The recorded derivation path re-derives the same key
+            let path = wallet
+                .store()
+                .find_path_by_pkh(&PublicKeyHash::from(&pk))
+                .expect("The derivation path must be recorded");
+            assert_eq!(
+                path.to_string(),
+                DerivationPath::default_for_scheme(scheme)
+                    .unwrap()
+                    .to_string()
+            );
+            let seed = Seed::new(&mnemonic, PASSPHRASE);
+            let rederived =
+                derive_hd_secret_key(scheme, seed.as_bytes(), path).unwrap();
+            assert_eq!(rederived.ref_to(), pk);
+
+            // The derived key signs through the `Signer` trait
+            let signer = SoftwareSigner::new(sk);
+            let pks_map = AccountPublicKeysMap::from_iter([pk.clone()]);
+            let mut tx = Tx::new(ChainId::default(), None);
+            tx.add_code(b"code".to_vec(), None)
+                .add_serialized_data(b"data".to_vec());
+            sign_raw(&mut tx, &[&signer], &pks_map, None)
+                .await
+                .expect("Signing cannot fail");
+            assert_eq!(signer.public_key(), pk);
+            assert!(tx.verify_signature(&pk, &[tx.raw_header_hash()]).is_ok());
+        }
+    }
+
     fn do_test_gen_sk_from_seed_and_derivation_path(
         scheme: SchemeType,
         seed: &str,
@@ -854,7 +973,8 @@ mod test_wallet {
                 .as_slice(),
             DerivationPath::from_path_str(scheme, derivation_path)
                 .expect("Derivation path construction cannot fail"),
-        );
+        )
+        .expect("Key derivation cannot fail");
         let sk_expected = if priv_key.starts_with("xprv") {
             // this is an extended private key encoded in base58
             let xprv =
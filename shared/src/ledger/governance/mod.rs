@@ -24,8 +24,10 @@ use crate::ledger::native_vp::{Ctx, NativeVp};
 use crate::ledger::storage_api::StorageRead;
 use crate::ledger::{native_vp, pos};
 use crate::types::address::{Address, InternalAddress};
+use crate::types::hash::Hash;
 use crate::types::storage::{Epoch, Key};
 use crate::types::token;
+use crate::types::transaction::governance::InitProposalData;
 use crate::vm::WasmCacheAccess;
 
 /// for handling Governance NativeVP errors
@@ -92,13 +94,13 @@ where
                     self.is_valid_vote_key(proposal_id, key, verifiers)
                 }
                 (KeyType::CONTENT, Some(proposal_id)) => {
-                    self.is_valid_content_key(proposal_id)
+                    self.is_valid_content_key(proposal_id, tx_data)
                 }
                 (KeyType::TYPE, Some(proposal_id)) => {
                     self.is_valid_proposal_type(proposal_id)
                 }
                 (KeyType::PROPOSAL_CODE, Some(proposal_id)) => {
-                    self.is_valid_proposal_code(proposal_id)
+                    self.is_valid_proposal_code(proposal_id, tx_data)
                 }
                 (KeyType::GRACE_EPOCH, Some(proposal_id)) => {
                     self.is_valid_grace_epoch(proposal_id)
@@ -272,8 +274,13 @@ where
         Ok(is_delegator)
     }
 
-    /// Validate a content key
-    pub fn is_valid_content_key(&self, proposal_id: u64) -> Result<bool> {
+    /// Validate a content key. The content must be the one committed by the
+    /// extra data section of the init proposal tx.
+    pub fn is_valid_content_key(
+        &self,
+        proposal_id: u64,
+        tx: &Tx,
+    ) -> Result<bool> {
         let content_key: Key = gov_storage::get_content_key(proposal_id);
         let max_content_length_parameter_key =
             gov_storage::get_max_proposal_content_key();
@@ -294,8 +301,18 @@ where
                 "Max content length {max_content_length}, got {}.",
                 post_content.len()
             );
+            return Ok(false);
+        }
+
+        let committed_content =
+            Self::committed_section(tx, |data| Some(data.content));
+        let is_committed = committed_content.as_ref() == Some(&post_content);
+        if !is_committed {
+            tracing::info!(
+                "The proposal content doesn't match the committed section."
+            );
         }
-        Ok(is_valid)
+        Ok(is_committed)
     }
 
     /// Validate the proposal type
@@ -344,8 +361,13 @@ where
         }
     }
 
-    /// Validate a proposal code
-    pub fn is_valid_proposal_code(&self, proposal_id: u64) -> Result<bool> {
+    /// Validate a proposal code. The code must be the one committed by the
+    /// extra data section of the init proposal tx.
+    pub fn is_valid_proposal_code(
+        &self,
+        proposal_id: u64,
+        tx: &Tx,
+    ) -> Result<bool> {
         let proposal_type_key = gov_storage::get_proposal_type_key(proposal_id);
         let proposal_type: ProposalType =
             self.force_read(&proposal_type_key, ReadType::Post)?;
@@ -368,7 +390,37 @@ where
         let post_code: Vec<u8> =
             self.ctx.read_bytes_post(&code_key)?.unwrap_or_default();
 
-        Ok(post_code.len() <= max_proposal_length)
+        if post_code.len() > max_proposal_length {
+            tracing::info!(
+                "Max code length {max_proposal_length}, got {}.",
+                post_code.len()
+            );
+            return Ok(false);
+        }
+
+        let committed_code = Self::committed_section(
+            tx,
+            InitProposalData::get_section_code_hash,
+        );
+        let is_committed = committed_code.as_ref() == Some(&post_code);
+        if !is_committed {
+            tracing::info!(
+                "The proposal code doesn't match the committed section."
+            );
+        }
+        Ok(is_committed)
+    }
+
+    /// Read the extra data section referred to by the data of an init proposal
+    /// tx
+    fn committed_section(
+        tx: &Tx,
+        section_hash: impl FnOnce(&InitProposalData) -> Option<Hash>,
+    ) -> Option<Vec<u8>> {
+        let data = tx.data()?;
+        let data = InitProposalData::try_from_slice(&data[..]).ok()?;
+        let hash = section_hash(&data)?;
+        tx.get_section(&hash)?.extra_data()
     }
 
     /// Validate a grace_epoch key
@@ -663,7 +715,9 @@ where
         }
     }
 
-    /// Check if a vote is from a delegator
+    /// Check if a vote is from a delegator. The delegator must not be a
+    /// validator and must have a bond to the validator whose vote it overrides
+    /// at the given epoch.
     pub fn is_delegator(
         &self,
         epoch: Epoch,
@@ -671,13 +725,30 @@ where
         address: &Address,
         delegation_address: &Address,
     ) -> Result<bool> {
-        Ok(address != delegation_address
-            && verifiers.contains(address)
-            && pos::namada_proof_of_stake::is_delegator(
-                &self.ctx.pre(),
-                address,
-                Some(epoch),
-            )?)
+        if address == delegation_address || !verifiers.contains(address) {
+            return Ok(false);
+        }
+        if is_validator(&self.ctx.pre(), address)? {
+            tracing::info!("Validator {address} can't vote as a delegator.");
+            return Ok(false);
+        }
+        let bond_id = pos::BondId {
+            source: address.clone(),
+            validator: delegation_address.clone(),
+        };
+        let bond_amount = pos::namada_proof_of_stake::bond_amount(
+            &self.ctx.pre(),
+            &bond_id,
+            epoch,
+        )?;
+        if bond_amount.is_zero() {
+            tracing::info!(
+                "Delegator {address} has no bond to {delegation_address} at \
+                 epoch {epoch}."
+            );
+            return Ok(false);
+        }
+        Ok(true)
     }
 }
 
@@ -34,11 +34,16 @@ where
     H: 'static + StorageHasher + Sync,
     CA: 'static + WasmCacheAccess + Sync,
 {
+    use std::collections::BTreeSet;
+
     use borsh_ext::BorshSerializeExt;
     use namada_core::ledger::gas::{Gas, GasMetering, TxGasMeter};
     use namada_core::ledger::storage::TempWlStorage;
+    use namada_core::ledger::storage_api::StorageRead;
     use namada_core::proto::Tx;
-    use namada_core::types::transaction::DecryptedTx;
+    use namada_core::types::transaction::{
+        DecryptedTx, DryRunResult, StorageChange, StorageValue,
+    };
 
     use crate::ledger::protocol::ShellParams;
     use crate::types::storage::TxIndex;
@@ -49,6 +54,7 @@ where
 
     let mut temp_wl_storage = TempWlStorage::new(&ctx.wl_storage.storage);
     let mut cumulated_gas = Gas::default();
+    let mut modified_keys = BTreeSet::new();
 
     // Wrapper dry run to allow estimating the gas cost of a transaction
     let mut tx_gas_meter = match tx.header().tx_type {
@@ -70,6 +76,7 @@ where
             )
             .into_storage_result()?;
 
+            modified_keys.extend(temp_wl_storage.write_log.get_keys());
             temp_wl_storage.write_log.commit_tx();
             cumulated_gas = tx_gas_meter.get_tx_consumed_gas();
 
@@ -102,12 +109,15 @@ where
     let mut data = protocol::apply_wasm_tx(
         tx,
         &TxIndex(0),
+        None,
         ShellParams::new(
             &mut tx_gas_meter,
             &mut temp_wl_storage,
             &mut ctx.vp_wasm_cache,
             &mut ctx.tx_wasm_cache,
         ),
+        #[cfg(not(feature = "mainnet"))]
+        false,
     )
     .into_storage_result()?;
     cumulated_gas = cumulated_gas
@@ -118,8 +128,26 @@ where
     // Account gas for both inner and wrapper (if available)
     data.gas_used = cumulated_gas;
     // NOTE: the keys changed by the wrapper transaction (if any) are not
-    // returned from this function
-    let data = data.serialize_to_vec();
+    // returned in the tx result, but they are part of the storage changes
+    modified_keys.extend(temp_wl_storage.write_log.get_keys());
+    let mut storage_changes = Vec::with_capacity(modified_keys.len());
+    for key in modified_keys {
+        let (old, _gas) =
+            temp_wl_storage.storage.read(&key).into_storage_result()?;
+        let new = temp_wl_storage.read_bytes(&key)?;
+        if old != new {
+            storage_changes.push(StorageChange {
+                key,
+                old: old.map(StorageValue::new),
+                new: new.map(StorageValue::new),
+            });
+        }
+    }
+    let data = DryRunResult {
+        tx_result: data,
+        storage_changes,
+    }
+    .serialize_to_vec();
     Ok(EncodedResponseQuery {
         data,
         proof: None,
@@ -129,14 +157,20 @@ where
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use borsh::BorshDeserialize;
     use borsh_ext::BorshSerializeExt;
+    use namada_core::ledger::gas::{GasMetering, TxGasMeter};
+    use namada_core::ledger::parameters;
     use namada_core::ledger::storage::testing::TestWlStorage;
     use namada_core::ledger::storage_api::{self, StorageWrite};
     use namada_core::types::hash::Hash;
-    use namada_core::types::storage::{BlockHeight, Key};
+    use namada_core::types::key::testing::keypair_1;
+    use namada_core::types::key::RefTo;
+    use namada_core::types::storage::{BlockHeight, Epoch, Key};
     use namada_core::types::transaction::decrypted::DecryptedTx;
-    use namada_core::types::transaction::TxType;
+    use namada_core::types::transaction::{Fee, GasLimit, TxType, WrapperTx};
     use namada_core::types::{address, token};
     use namada_sdk::queries::{Router, RPC};
     use namada_test_utils::TestWasms;
@@ -299,7 +333,7 @@ mod test {
             .dry_run_tx(&client, Some(tx_bytes), None, false)
             .await
             .unwrap();
-        assert!(result.data.is_accepted());
+        assert!(result.data.tx_result.is_accepted());
 
         // Request storage value for a balance key ...
         let token_addr = address::testing::established_address_1();
@@ -366,4 +400,105 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_fee_estimate_meets_check_tx_floor() {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+        // store the wasm code
+        let tx_no_op = TestWasms::TxNoOp.read_bytes();
+        let tx_hash = Hash::sha256(&tx_no_op);
+        let key = Key::wasm_code(&tx_hash);
+        let len_key = Key::wasm_code_len(&tx_hash);
+        client.wl_storage.storage.write(&key, &tx_no_op).unwrap();
+        client
+            .wl_storage
+            .storage
+            .write(&len_key, (tx_no_op.len() as u64).serialize_to_vec())
+            .unwrap();
+        // Only NAM is allowed for fee payment
+        let gas_price = token::Amount::from_u64(10);
+        client
+            .wl_storage
+            .storage
+            .write(
+                &parameters::storage::get_gas_cost_key(),
+                BTreeMap::from([(address::nam(), gas_price)])
+                    .serialize_to_vec(),
+            )
+            .unwrap();
+
+        let chain_id = client.wl_storage.storage.chain_id.clone();
+        let wrap = |fee: Fee, gas_limit: GasLimit, data_len: usize| {
+            let keypair = keypair_1();
+            let mut tx =
+                Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                    fee,
+                    keypair.ref_to(),
+                    Epoch(0),
+                    gas_limit,
+                    None,
+                ))));
+            tx.header.chain_id = chain_id.clone();
+            tx.set_code(Code::from_hash(tx_hash, None));
+            tx.set_data(Data::new(vec![1; data_len]));
+            tx.sign_wrapper(keypair);
+            tx
+        };
+
+        for data_len in [0, 1_000, 20_000, 200_000] {
+            let unpriced = Fee {
+                amount_per_gas_unit: token::Amount::zero(),
+                token: address::nam(),
+            };
+            let tx = wrap(unpriced, GasLimit::from(0), data_len);
+            let estimate =
+                namada_sdk::rpc::estimate_fee(&client, &tx, &address::nam())
+                    .await
+                    .unwrap();
+            assert_eq!(estimate.fee.amount_per_gas_unit, gas_price);
+            assert!(
+                u64::from(estimate.suggested_gas_limit)
+                    > u64::from(estimate.min_gas_limit)
+            );
+            assert!(estimate.suggested_fee() > estimate.min_fee());
+
+            // Applying the estimate doesn't change the size of the tx
+            let priced =
+                wrap(estimate.fee.clone(), estimate.min_gas_limit, data_len);
+            assert_eq!(priced.sealed_len() as u64, estimate.sealed_tx_len);
+
+            // The fee is not below the minimum gas price ...
+            let min_gas_price =
+                parameters::read_gas_cost(&client.wl_storage, &address::nam())
+                    .unwrap()
+                    .unwrap();
+            assert!(estimate.fee.amount_per_gas_unit >= min_gas_price);
+
+            // ... and the gas limit covers the sealed wrapper and the inner
+            // tx, but not with a unit less
+            let mut inner = priced.clone();
+            inner.update_header(TxType::Raw);
+            let inner_gas = RPC
+                .shell()
+                .dry_run_tx(&client, Some(inner.to_bytes()), None, false)
+                .await
+                .unwrap()
+                .data
+                .tx_result
+                .gas_used;
+            let sealed_bytes = vec![0; priced.sealed_len()];
+            let mut gas_meter = TxGasMeter::new(estimate.min_gas_limit);
+            gas_meter.add_wrapper_gas(&sealed_bytes).unwrap();
+            gas_meter.consume(inner_gas.into()).unwrap();
+
+            let tight_limit =
+                GasLimit::from(u64::from(estimate.min_gas_limit) - 1);
+            let mut gas_meter = TxGasMeter::new(tight_limit);
+            let consumed = gas_meter
+                .add_wrapper_gas(&sealed_bytes)
+                .and_then(|()| gas_meter.consume(inner_gas.into()));
+            assert!(consumed.is_err());
+        }
+    }
 }
@@ -12,7 +12,8 @@ use namada_core::ledger::gas::{
     IBC_ACTION_EXECUTE_GAS, IBC_ACTION_VALIDATE_GAS,
 };
 use namada_core::ledger::ibc::{
-    Error as ActionError, IbcActions, TransferModule, ValidationParams,
+    resolve_msg_sections, Error as ActionError, IbcActions, TransferModule,
+    ValidationParams,
 };
 use namada_core::ledger::storage::write_log::StorageModification;
 use namada_core::ledger::storage::{self as ledger_storage, StorageHasher};
@@ -77,6 +78,7 @@ where
     ) -> VpResult<bool> {
         let signed = tx_data;
         let tx_data = signed.data().ok_or(Error::NoTxData)?;
+        let tx_data = resolve_msg_sections(signed, tx_data)?;
 
         // Pseudo execution and compare them
         self.validate_state(&tx_data, keys_changed)?;
@@ -525,6 +525,11 @@ where
                 PrefixIterators::default();
             let mut result_buffer: Option<Vec<u8>> = None;
             let mut vp_wasm_cache = self.vp_wasm_cache.clone();
+            // Native VPs are not triggered by testnet faucet withdrawals
+            #[cfg(not(feature = "mainnet"))]
+            let has_valid_pow = false;
+            // The wrapper of the tx is not available to native VPs
+            let wrapper = None;
 
             let ctx = VpCtx::new(
                 self.address,
@@ -534,11 +539,14 @@ where
                 &mut self.sentinel.borrow_mut(),
                 self.tx,
                 self.tx_index,
+                &wrapper,
                 &mut iterators,
                 self.verifiers,
                 &mut result_buffer,
                 self.keys_changed,
                 &eval_runner,
+                #[cfg(not(feature = "mainnet"))]
+                &has_valid_pow,
                 &mut vp_wasm_cache,
             );
             match eval_runner.eval_native_result(ctx, vp_code_hash, input_data)
@@ -4,6 +4,7 @@ use std::collections::BTreeSet;
 use borsh_ext::BorshSerializeExt;
 use eyre::{eyre, WrapErr};
 use masp_primitives::transaction::Transaction;
+use namada_core::ledger::fee_records;
 use namada_core::ledger::gas::TxGasMeter;
 use namada_core::ledger::storage::wl_storage::WriteLogAndStorage;
 use namada_core::ledger::storage_api::StorageRead;
@@ -16,7 +17,7 @@ use namada_sdk::tx::TX_TRANSFER_WASM;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use thiserror::Error;
 
-use crate::ledger::gas::{GasMetering, VpGasMeter};
+use crate::ledger::gas::{GasMetering, VpGasMeter, VpsGas};
 use crate::ledger::governance::GovernanceVp;
 use crate::ledger::native_vp::ethereum_bridge::bridge_pool_vp::BridgePoolVp;
 use crate::ledger::native_vp::ethereum_bridge::nut::NonUsableTokens;
@@ -31,12 +32,16 @@ use crate::ledger::pos::{self, PosVP};
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{DBIter, Storage, StorageHasher, WlStorage, DB};
 use crate::ledger::storage_api;
-use crate::proto::{self, Tx};
+use crate::ledger::vp_host_fns;
+use crate::proto::{self, Header, Tx};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::storage;
 use crate::types::storage::TxIndex;
 use crate::types::transaction::protocol::{EthereumTxData, ProtocolTxType};
-use crate::types::transaction::{DecryptedTx, TxResult, TxType, VpsResult};
+use crate::types::transaction::{
+    DecryptedTx, TxResult, TxType, TxWasmError, VpsResult,
+};
+use crate::types::validity_predicate::VpSentinel;
 use crate::vm::wasm::{TxCache, VpCache};
 use crate::vm::{self, wasm, WasmCacheAccess};
 
@@ -51,6 +56,8 @@ pub enum Error {
     TxDecodingError(proto::Error),
     #[error("Transaction runner error: {0}")]
     TxRunnerError(vm::wasm::run::Error),
+    #[error("Transaction aborted: {0}")]
+    TxAborted(TxWasmError),
     #[error("{0:?}")]
     ProtocolTxError(#[from] eyre::Error),
     #[error("Txs must either be encrypted or a decryption of an encrypted tx")]
@@ -59,6 +66,12 @@ pub enum Error {
     FeeUnshieldingError(crate::types::transaction::WrapperTxErr),
     #[error("Gas error: {0}")]
     GasError(String),
+    #[error("Gas error: {error}")]
+    WrapperGasError {
+        /// The fee charged to the wrapper tx before the error
+        fee: Amount,
+        error: String,
+    },
     #[error("Error while processing transaction's fees: {0}")]
     FeeError(String),
     #[error("Invalid transaction signature")]
@@ -95,6 +108,8 @@ pub enum Error {
     MaspNativeVpError(native_vp::masp::Error),
     #[error("Access to an internal address {0:?} is forbidden")]
     AccessForbidden(InternalAddress),
+    #[error("Error getting the tx signers: {0}")]
+    TxSignersError(vp_host_fns::RuntimeError),
 }
 
 /// Shell parameters for running wasm transactions.
@@ -141,16 +156,21 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// If the given tx is a successfully decrypted payload apply the necessary
 /// vps. Otherwise, we include the tx on chain with the gas charge added
 /// but no further validations.
+///
+/// The header of the wrapper that carried a decrypted tx, if any, is made
+/// available to its wasm code and VPs.
 #[allow(clippy::too_many_arguments)]
 pub fn dispatch_tx<'a, D, H, CA>(
     tx: Tx,
     tx_bytes: &'a [u8],
     tx_index: TxIndex,
+    wrapper: Option<&'a Header>,
     tx_gas_meter: &'a mut TxGasMeter,
     wl_storage: &'a mut WlStorage<D, H>,
     vp_wasm_cache: &'a mut VpCache<CA>,
     tx_wasm_cache: &'a mut TxCache<CA>,
     block_proposer: Option<&'a Address>,
+    #[cfg(not(feature = "mainnet"))] has_valid_pow: bool,
 ) -> Result<TxResult>
 where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
@@ -162,12 +182,15 @@ where
         TxType::Decrypted(DecryptedTx::Decrypted) => apply_wasm_tx(
             tx,
             &tx_index,
+            wrapper,
             ShellParams {
                 tx_gas_meter,
                 wl_storage,
                 vp_wasm_cache,
                 tx_wasm_cache,
             },
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow,
         ),
         TxType::Protocol(protocol_tx) => {
             apply_protocol_tx(protocol_tx.tx, tx.data(), wl_storage)
@@ -175,7 +198,7 @@ where
         TxType::Wrapper(ref wrapper) => {
             let fee_unshielding_transaction =
                 get_fee_unshielding_transaction(&tx, wrapper);
-            let changed_keys = apply_wrapper_tx(
+            let (changed_keys, fee) = apply_wrapper_tx(
                 tx,
                 wrapper,
                 fee_unshielding_transaction,
@@ -194,6 +217,8 @@ where
                 vps_result: VpsResult::default(),
                 initialized_accounts: vec![],
                 ibc_events: BTreeSet::default(),
+                tx_events: vec![],
+                wrapper_fee: Some(fee),
             })
         }
         TxType::Decrypted(DecryptedTx::Undecryptable) => {
@@ -223,7 +248,8 @@ where
 ///  - fee payment
 ///  - gas accounting
 ///
-/// Returns the set of changed storage keys.
+/// Returns the set of changed storage keys and the fee charged to the
+/// wrapper.
 pub(crate) fn apply_wrapper_tx<'a, D, H, CA, WLS>(
     tx: Tx,
     wrapper: &WrapperTx,
@@ -231,7 +257,7 @@ pub(crate) fn apply_wrapper_tx<'a, D, H, CA, WLS>(
     tx_bytes: &[u8],
     mut shell_params: ShellParams<'a, CA, WLS>,
     block_proposer: Option<&Address>,
-) -> Result<BTreeSet<Key>>
+) -> Result<(BTreeSet<Key>, Amount)>
 where
     CA: 'static + WasmCacheAccess + Sync,
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
@@ -243,11 +269,11 @@ where
     // Write wrapper tx hash to storage
     shell_params
         .wl_storage
-        .write_tx_hash(tx.header_hash())
+        .write_tx_hash(tx.header_hash(), tx.header.expiration)
         .expect("Error while writing tx hash to storage");
 
     // Charge fee before performing any fallible operations
-    charge_fee(
+    let fee = charge_fee(
         wrapper,
         fee_unshield_transaction,
         &mut shell_params,
@@ -255,13 +281,16 @@ where
         &mut changed_keys,
     )?;
 
-    // Account for gas
+    // Account for gas. The fee stays charged if this fails.
     shell_params
         .tx_gas_meter
         .add_wrapper_gas(tx_bytes)
-        .map_err(|err| Error::GasError(err.to_string()))?;
+        .map_err(|err| Error::WrapperGasError {
+            fee,
+            error: err.to_string(),
+        })?;
 
-    Ok(changed_keys)
+    Ok((changed_keys, fee))
 }
 
 /// Retrieve the Masp `Transaction` for fee unshielding from the provided
@@ -282,8 +311,10 @@ pub fn get_fee_unshielding_transaction(
         })
 }
 
-/// Charge fee for the provided wrapper transaction. In ABCI returns an error if
-/// the balance of the block proposer overflows. In ABCI plus returns error if:
+/// Charge fee for the provided wrapper transaction and return the amount
+/// charged, which is zero if no block proposer is given. In ABCI returns an
+/// error if the balance of the block proposer overflows. In ABCI plus returns
+/// error if:
 /// - The unshielding fails
 /// - Fee amount overflows
 /// - Not enough funds are available to pay the entire amount of the fee
@@ -294,7 +325,7 @@ fn charge_fee<'a, D, H, CA, WLS>(
     shell_params: &mut ShellParams<'a, CA, WLS>,
     block_proposer: Option<&Address>,
     changed_keys: &mut BTreeSet<Key>,
-) -> Result<()>
+) -> Result<Amount>
 where
     CA: 'static + WasmCacheAccess + Sync,
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
@@ -339,12 +370,15 @@ where
                 match apply_wasm_tx(
                     fee_unshielding_tx,
                     &TxIndex::default(),
+                    None,
                     ShellParams {
                         tx_gas_meter: &mut tx_gas_meter,
                         wl_storage: *wl_storage,
                         vp_wasm_cache,
                         tx_wasm_cache,
                     },
+                    #[cfg(not(feature = "mainnet"))]
+                    false,
                 ) {
                     Ok(result) => {
                         // NOTE: do not commit yet cause this could be
@@ -373,26 +407,29 @@ where
     }
 
     // Charge or check fees
-    match block_proposer {
+    let fee = match block_proposer {
         Some(proposer) => transfer_fee(*wl_storage, proposer, wrapper)?,
-        None => check_fees(*wl_storage, wrapper)?,
-    }
+        None => {
+            check_fees(*wl_storage, wrapper)?;
+            Amount::default()
+        }
+    };
 
     changed_keys.extend(wl_storage.write_log_mut().get_keys_with_precommit());
 
     // Commit tx write log even in case of subsequent errors
     wl_storage.write_log_mut().commit_tx();
 
-    Ok(())
+    Ok(fee)
 }
 
 /// Perform the actual transfer of fess from the fee payer to the block
-/// proposer.
+/// proposer, and record the fee that was charged. Returns the fee charged.
 pub fn transfer_fee<WLS>(
     wl_storage: &mut WLS,
     block_proposer: &Address,
     wrapper: &WrapperTx,
-) -> Result<()>
+) -> Result<Amount>
 where
     WLS: WriteLogAndStorage + StorageRead,
 {
@@ -413,7 +450,8 @@ where
                     block_proposer,
                     fees,
                 )
-                .map_err(|e| Error::FeeError(e.to_string()))
+                .map_err(|e| Error::FeeError(e.to_string()))?;
+                record_fee(wl_storage, block_proposer, wrapper, fees)
             } else {
                 // Balance was insufficient for fee payment, move all the
                 // available funds in the transparent balance of
@@ -433,6 +471,7 @@ where
                     balance,
                 )
                 .map_err(|e| Error::FeeError(e.to_string()))?;
+                record_fee(wl_storage, block_proposer, wrapper, balance)?;
 
                 Err(Error::FeeError(
                     "Transparent balance of wrapper's signer was insufficient \
@@ -459,6 +498,27 @@ where
     }
 }
 
+/// Record the fee charged to the given wrapper, see
+/// [`fee_records::record_fee`]. Returns the recorded amount.
+fn record_fee<WLS>(
+    wl_storage: &mut WLS,
+    block_proposer: &Address,
+    wrapper: &WrapperTx,
+    amount: Amount,
+) -> Result<Amount>
+where
+    WLS: WriteLogAndStorage + StorageRead,
+{
+    fee_records::record_fee(
+        wl_storage,
+        block_proposer,
+        &wrapper.fee.token,
+        amount,
+    )
+    .map_err(|e| Error::FeeError(e.to_string()))?;
+    Ok(amount)
+}
+
 /// Transfer `token` from `src` to `dest`. Returns an `Err` if `src` has
 /// insufficient balance or if the transfer the `dest` would overflow (This can
 /// only happen if the total supply does't fit in `token::Amount`). Contrary to
@@ -544,7 +604,9 @@ where
 pub fn apply_wasm_tx<'a, D, H, CA, WLS>(
     tx: Tx,
     tx_index: &TxIndex,
+    wrapper: Option<&Header>,
     shell_params: ShellParams<'a, CA, WLS>,
+    #[cfg(not(feature = "mainnet"))] has_valid_pow: bool,
 ) -> Result<TxResult>
 where
     CA: 'static + WasmCacheAccess + Sync,
@@ -580,6 +642,7 @@ where
     let verifiers = execute_tx(
         &tx,
         tx_index,
+        wrapper,
         storage,
         tx_gas_meter,
         write_log,
@@ -590,17 +653,21 @@ where
     let vps_result = check_vps(CheckVps {
         tx: &tx,
         tx_index,
+        wrapper,
         storage,
         tx_gas_meter,
         write_log,
         verifiers_from_tx: &verifiers,
         vp_wasm_cache,
+        #[cfg(not(feature = "mainnet"))]
+        has_valid_pow,
     })?;
 
     let gas_used = tx_gas_meter.get_tx_consumed_gas();
     let initialized_accounts = write_log.get_initialized_accounts();
     let changed_keys = write_log.get_keys();
     let ibc_events = write_log.take_ibc_events();
+    let tx_events = write_log.take_tx_events();
 
     Ok(TxResult {
         gas_used,
@@ -608,6 +675,8 @@ where
         vps_result,
         initialized_accounts,
         ibc_events,
+        tx_events,
+        wrapper_fee: None,
     })
 }
 
@@ -637,6 +706,11 @@ where
             eyre!("Protocol tx data must be present")),
         );
     };
+    if let ProtocolTxType::Slash = tx {
+        // The evidence of a slash tx is applied by the shell, along with the
+        // evidence delivered by CometBFT, so that it's only slashed once
+        return Ok(TxResult::default());
+    }
     let ethereum_tx_data = EthereumTxData::deserialize(&tx, &data)
         .wrap_err_with(|| {
             format!(
@@ -692,6 +766,7 @@ where
 fn execute_tx<D, H, CA>(
     tx: &Tx,
     tx_index: &TxIndex,
+    wrapper: Option<&Header>,
     storage: &Storage<D, H>,
     tx_gas_meter: &mut TxGasMeter,
     write_log: &mut WriteLog,
@@ -709,12 +784,14 @@ where
         tx_gas_meter,
         tx_index,
         tx,
+        wrapper,
         vp_wasm_cache,
         tx_wasm_cache,
     )
     .map_err(|err| match err {
         wasm::run::Error::GasError(msg) => Error::GasError(msg),
         wasm::run::Error::MissingSection(msg) => Error::MissingSection(msg),
+        wasm::run::Error::TxAborted(err) => Error::TxAborted(err),
         _ => Error::TxRunnerError(err),
     })
 }
@@ -728,11 +805,14 @@ where
 {
     tx: &'a Tx,
     tx_index: &'a TxIndex,
+    wrapper: Option<&'a Header>,
     storage: &'a Storage<D, H>,
     tx_gas_meter: &'a mut TxGasMeter,
     write_log: &'a WriteLog,
     verifiers_from_tx: &'a BTreeSet<Address>,
     vp_wasm_cache: &'a mut VpCache<CA>,
+    #[cfg(not(feature = "mainnet"))]
+    has_valid_pow: bool,
 }
 
 /// Check the acceptance of a transaction by validity predicates
@@ -740,11 +820,14 @@ fn check_vps<D, H, CA>(
     CheckVps {
         tx,
         tx_index,
+        wrapper,
         storage,
         tx_gas_meter,
         write_log,
         verifiers_from_tx,
         vp_wasm_cache,
+        #[cfg(not(feature = "mainnet"))]
+        has_valid_pow,
     }: CheckVps<'_, D, H, CA>,
 ) -> Result<VpsResult>
 where
@@ -752,18 +835,24 @@ where
     H: 'static + StorageHasher + Sync,
     CA: 'static + WasmCacheAccess + Sync,
 {
-    let (verifiers, keys_changed) =
+    let (mut verifiers, keys_changed) =
         write_log.verifiers_and_changed_keys(verifiers_from_tx);
+    // The signers of the tx are verifiers too, so that the VPs can rely on
+    // their presence to authorize the tx
+    verifiers.extend(tx_signers(tx, storage, write_log, tx_gas_meter)?);
 
     let vps_result = execute_vps(
         verifiers,
         keys_changed,
         tx,
         tx_index,
+        wrapper,
         storage,
         write_log,
         tx_gas_meter,
         vp_wasm_cache,
+        #[cfg(not(feature = "mainnet"))]
+        has_valid_pow,
     )?;
     tracing::debug!("Total VPs gas cost {:?}", vps_result.gas_used);
 
@@ -774,6 +863,45 @@ where
     Ok(vps_result)
 }
 
+/// Get the implicit addresses of the public keys that made valid signatures
+/// over the sections of the tx
+fn tx_signers<D, H>(
+    tx: &Tx,
+    storage: &Storage<D, H>,
+    write_log: &WriteLog,
+    tx_gas_meter: &mut TxGasMeter,
+) -> Result<BTreeSet<Address>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let mut gas_meter = VpGasMeter::new_from_tx_meter(tx_gas_meter);
+    let mut sentinel = VpSentinel::default();
+    let signers = vp_host_fns::get_tx_signers(
+        &mut gas_meter,
+        storage,
+        write_log,
+        tx,
+        &mut sentinel,
+    )
+    .map_err(|err| match err {
+        vp_host_fns::RuntimeError::OutOfGas(err) => {
+            Error::GasError(err.to_string())
+        }
+        err => Error::TxSignersError(err),
+    })?;
+
+    let mut signers_gas = VpsGas::default();
+    signers_gas
+        .set(gas_meter)
+        .map_err(|err| Error::GasError(err.to_string()))?;
+    tx_gas_meter
+        .add_vps_gas(&signers_gas)
+        .map_err(|err| Error::GasError(err.to_string()))?;
+
+    Ok(signers.iter().map(Address::from).collect())
+}
+
 /// Execute verifiers' validity predicates
 #[allow(clippy::too_many_arguments)]
 fn execute_vps<D, H, CA>(
@@ -781,10 +909,12 @@ fn execute_vps<D, H, CA>(
     keys_changed: BTreeSet<storage::Key>,
     tx: &Tx,
     tx_index: &TxIndex,
+    wrapper: Option<&Header>,
     storage: &Storage<D, H>,
     write_log: &WriteLog,
     tx_gas_meter: &TxGasMeter,
     vp_wasm_cache: &mut VpCache<CA>,
+    #[cfg(not(feature = "mainnet"))] has_valid_pow: bool,
 ) -> Result<VpsResult>
 where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
@@ -816,6 +946,7 @@ where
                         vp_code_hash,
                         tx,
                         tx_index,
+                        wrapper,
                         addr,
                         storage,
                         write_log,
@@ -823,6 +954,8 @@ where
                         &keys_changed,
                         &verifiers,
                         vp_wasm_cache.clone(),
+                        #[cfg(not(feature = "mainnet"))]
+                        has_valid_pow,
                     )
                     .map_err(|err| match err {
                         wasm::run::Error::GasError(msg) => Error::GasError(msg),
@@ -1099,13 +1232,16 @@ mod tests {
 
     use borsh::BorshDeserialize;
     use eyre::Result;
+    use namada_core::ledger::storage::testing::TestWlStorage;
     use namada_core::ledger::storage_api::StorageRead;
-    use namada_core::proto::{SignableEthMessage, Signed};
+    use namada_core::proto::{SignableEthMessage, Signed, Signer};
+    use namada_core::types::account::AccountPublicKeysMap;
     use namada_core::types::ethereum_events::testing::DAI_ERC20_ETH_ADDRESS;
     use namada_core::types::ethereum_events::{
         EthereumEvent, TransferToNamada,
     };
     use namada_core::types::keccak::keccak_hash;
+    use namada_core::types::key::RefTo;
     use namada_core::types::storage::BlockHeight;
     use namada_core::types::token::Amount;
     use namada_core::types::vote_extensions::bridge_pool_roots::BridgePoolRootVext;
@@ -1252,4 +1388,86 @@ mod tests {
 
         Ok(())
     }
+
+    /// Tests that the signers of a tx are the public keys of its valid
+    /// signatures, including those made on behalf of an account
+    #[test]
+    fn test_tx_signers() {
+        let mut wl_storage = TestWlStorage::default();
+        let account = address::testing::established_address_1();
+        let account_keypair = key::testing::keypair_1();
+        let other_keypair = key::testing::keypair_2();
+        storage_api::account::init_account_storage(
+            &mut wl_storage,
+            &account,
+            &[account_keypair.ref_to()],
+            1,
+        )
+        .unwrap();
+        // The account's keys are read from the prior state
+        wl_storage.commit_tx();
+
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.add_code(vec![], None)
+            .add_serialized_data(vec![1, 2, 3])
+            .sign_raw(
+                vec![account_keypair.clone()],
+                AccountPublicKeysMap::from_iter([account_keypair.ref_to()]),
+                Some(account),
+            )
+            .sign_raw(
+                vec![other_keypair.clone()],
+                AccountPublicKeysMap::from_iter([other_keypair.ref_to()]),
+                None,
+            );
+
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(u64::MAX.into());
+        let signers = tx_signers(
+            &tx,
+            &wl_storage.storage,
+            &wl_storage.write_log,
+            &mut gas_meter,
+        )
+        .unwrap();
+        assert_eq!(
+            signers,
+            BTreeSet::from([
+                Address::from(&account_keypair.ref_to()),
+                Address::from(&other_keypair.ref_to()),
+            ])
+        );
+
+        // Replace the signature of the other key with the account key's
+        // signature, which is valid, but not for the other key
+        let account_sig = tx
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::Signature(sig)
+                    if matches!(sig.signer, Signer::Address(_)) =>
+                {
+                    Some(sig.signatures[&0].clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        for section in &mut tx.sections {
+            if let Section::Signature(sig) = section {
+                if matches!(sig.signer, Signer::PubKeys(_)) {
+                    sig.signatures.insert(0, account_sig.clone());
+                }
+            }
+        }
+        let signers = tx_signers(
+            &tx,
+            &wl_storage.storage,
+            &wl_storage.write_log,
+            &mut gas_meter,
+        )
+        .unwrap();
+        assert_eq!(
+            signers,
+            BTreeSet::from([Address::from(&account_keypair.ref_to())])
+        );
+    }
 }
@@ -1,10 +1,14 @@
 //! Host functions for VPs used for both native and WASM VPs.
 
+use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::num::TryFromIntError;
 
+use borsh::BorshDeserialize;
 use namada_core::ledger::gas::MEMORY_ACCESS_GAS_PER_BYTE;
 use namada_core::types::address::{Address, ESTABLISHED_ADDRESS_BYTES_LEN};
-use namada_core::types::hash::{Hash, HASH_LENGTH};
+use namada_core::types::hash::{HASH_LENGTH, Hash};
+use namada_core::types::key::{self, common};
 use namada_core::types::storage::{
     BlockHash, BlockHeight, Epoch, Header, Key, TxIndex, TX_INDEX_LENGTH,
 };
@@ -296,6 +300,49 @@ where
     Ok(hash)
 }
 
+/// Getting the public keys that made valid signatures over the sections of the
+/// transaction. The public keys of the accounts on behalf of which the
+/// signatures are made are read from the prior state.
+pub fn get_tx_signers<DB, H>(
+    gas_meter: &mut VpGasMeter,
+    storage: &Storage<DB, H>,
+    write_log: &WriteLog,
+    tx: &Tx,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<BTreeSet<common::PublicKey>>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    // Both of the callbacks need to charge gas
+    let gas_meter = RefCell::new(gas_meter);
+    let sentinel = RefCell::new(sentinel);
+    tx.signers(
+        |owner: &Address, index: u8| {
+            let pk_key = key::pks_handle(owner).get_data_key(&index);
+            read_pre(
+                &mut gas_meter.borrow_mut(),
+                storage,
+                write_log,
+                &pk_key,
+                &mut sentinel.borrow_mut(),
+            )?
+            .map(|bytes| {
+                common::PublicKey::try_from_slice(&bytes[..])
+                    .map_err(RuntimeError::EncodingError)
+            })
+            .transpose()
+        },
+        || {
+            add_gas(
+                &mut gas_meter.borrow_mut(),
+                gas::VERIFY_TX_SIG_GAS,
+                &mut sentinel.borrow_mut(),
+            )
+        },
+    )
+}
+
 /// Getting the block hash. The height is that of the block to which the
 /// current transaction is being applied.
 pub fn get_tx_code_hash(
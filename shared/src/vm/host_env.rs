@@ -9,11 +9,12 @@ use borsh_ext::BorshSerializeExt;
 use masp_primitives::transaction::Transaction;
 use namada_core::ledger::gas::{
     GasMetering, TxGasMeter, MEMORY_ACCESS_GAS_PER_BYTE,
+    TX_SECTION_ACCESS_GAS, WASM_CODE_VALIDATION_GAS_PER_BYTE,
 };
 use namada_core::types::address::{ESTABLISHED_ADDRESS_BYTES_LEN, MASP};
 use namada_core::types::internal::KeyVal;
 use namada_core::types::storage::TX_INDEX_LENGTH;
-use namada_core::types::transaction::TxSentinel;
+use namada_core::types::transaction::{TxSentinel, TxWasmError};
 use namada_core::types::validity_predicate::VpSentinel;
 use thiserror::Error;
 
@@ -21,14 +22,15 @@ use thiserror::Error;
 use super::wasm::TxCache;
 #[cfg(feature = "wasm-runtime")]
 use super::wasm::VpCache;
-use super::WasmCacheAccess;
+use super::{validate_untrusted_vp_wasm, WasmCacheAccess, WasmValidationError};
 use crate::ledger::gas::{self, VpGasMeter};
 use crate::ledger::storage::write_log::{self, WriteLog};
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::ledger::storage_api::{self, ResultExt};
 use crate::ledger::vp_host_fns;
-use crate::proto::Tx;
+use crate::proto::{Header, Tx};
 use crate::types::address::{self, Address};
+use crate::types::event::TxEvent;
 use crate::types::hash::Hash;
 use crate::types::ibc::{IbcEvent, IbcShieldedTransfer};
 use crate::types::internal::HostEnvResult;
@@ -53,6 +55,8 @@ pub enum TxRuntimeError {
         "Trying to use a validity predicate with an invalid WASM code hash {0}"
     )]
     InvalidVpCodeHash(String),
+    #[error("Trying to use an invalid validity predicate WASM code: {0}")]
+    InvalidVpCode(WasmValidationError),
     #[error("A validity predicate of an account cannot be deleted")]
     CannotDeleteVp,
     #[error("Storage modification error: {0}")]
@@ -114,6 +118,8 @@ where
     /// The transaction index is used to identify a shielded transaction's
     /// parent
     pub tx_index: HostRef<'a, &'a TxIndex>,
+    /// The header of the wrapper that carried the transaction, if any
+    pub wrapper: HostRef<'a, &'a Option<Header>>,
     /// The verifiers whose validity predicates should be triggered.
     pub verifiers: MutHostRef<'a, &'a BTreeSet<Address>>,
     /// Cache for 2-step reads from host environment.
@@ -154,6 +160,7 @@ where
         sentinel: &mut TxSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        wrapper: &Option<Header>,
         verifiers: &mut BTreeSet<Address>,
         result_buffer: &mut Option<Vec<u8>>,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
@@ -166,6 +173,7 @@ where
         let sentinel = unsafe { MutHostRef::new(sentinel) };
         let tx = unsafe { HostRef::new(tx) };
         let tx_index = unsafe { HostRef::new(tx_index) };
+        let wrapper = unsafe { HostRef::new(wrapper) };
         let verifiers = unsafe { MutHostRef::new(verifiers) };
         let result_buffer = unsafe { MutHostRef::new(result_buffer) };
         #[cfg(feature = "wasm-runtime")]
@@ -180,6 +188,7 @@ where
             sentinel,
             tx,
             tx_index,
+            wrapper,
             verifiers,
             result_buffer,
             #[cfg(feature = "wasm-runtime")]
@@ -224,6 +233,7 @@ where
             sentinel: self.sentinel.clone(),
             tx: self.tx.clone(),
             tx_index: self.tx_index.clone(),
+            wrapper: self.wrapper.clone(),
             verifiers: self.verifiers.clone(),
             result_buffer: self.result_buffer.clone(),
             #[cfg(feature = "wasm-runtime")]
@@ -276,6 +286,8 @@ where
     /// The transaction index is used to identify a shielded transaction's
     /// parent
     pub tx_index: HostRef<'a, &'a TxIndex>,
+    /// The header of the wrapper that carried the transaction, if any
+    pub wrapper: HostRef<'a, &'a Option<Header>>,
     /// The runner of the [`vp_eval`] function
     pub eval_runner: HostRef<'a, &'a EVAL>,
     /// Cache for 2-step reads from host environment.
@@ -285,6 +297,10 @@ where
     /// The verifiers whose validity predicates should be triggered. Used for
     /// calls to `eval`.
     pub verifiers: HostRef<'a, &'a BTreeSet<Address>>,
+    /// This is true when the wrapper of this tx contained a valid testnet
+    /// faucet withdrawal PoW solution
+    #[cfg(not(feature = "mainnet"))]
+    pub has_valid_pow: HostRef<'a, &'a bool>,
     /// VP WASM compilation cache
     #[cfg(feature = "wasm-runtime")]
     pub vp_wasm_cache: MutHostRef<'a, &'a VpCache<CA>>,
@@ -342,11 +358,13 @@ where
         sentinel: &mut VpSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        wrapper: &Option<Header>,
         iterators: &mut PrefixIterators<'a, DB>,
         verifiers: &BTreeSet<Address>,
         result_buffer: &mut Option<Vec<u8>>,
         keys_changed: &BTreeSet<Key>,
         eval_runner: &EVAL,
+        #[cfg(not(feature = "mainnet"))] has_valid_pow: &bool,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
     ) -> Self {
         let ctx = VpCtx::new(
@@ -357,11 +375,14 @@ where
             sentinel,
             tx,
             tx_index,
+            wrapper,
             iterators,
             verifiers,
             result_buffer,
             keys_changed,
             eval_runner,
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow,
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache,
         );
@@ -409,11 +430,13 @@ where
         sentinel: &mut VpSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        wrapper: &Option<Header>,
         iterators: &mut PrefixIterators<'a, DB>,
         verifiers: &BTreeSet<Address>,
         result_buffer: &mut Option<Vec<u8>>,
         keys_changed: &BTreeSet<Key>,
         eval_runner: &EVAL,
+        #[cfg(not(feature = "mainnet"))] has_valid_pow: &bool,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
     ) -> Self {
         let address = unsafe { HostRef::new(address) };
@@ -421,6 +444,7 @@ where
         let write_log = unsafe { HostRef::new(write_log) };
         let tx = unsafe { HostRef::new(tx) };
         let tx_index = unsafe { HostRef::new(tx_index) };
+        let wrapper = unsafe { HostRef::new(wrapper) };
         let iterators = unsafe { MutHostRef::new(iterators) };
         let gas_meter = unsafe { MutHostRef::new(gas_meter) };
         let sentinel = unsafe { MutHostRef::new(sentinel) };
@@ -428,6 +452,8 @@ where
         let result_buffer = unsafe { MutHostRef::new(result_buffer) };
         let keys_changed = unsafe { HostRef::new(keys_changed) };
         let eval_runner = unsafe { HostRef::new(eval_runner) };
+        #[cfg(not(feature = "mainnet"))]
+        let has_valid_pow = unsafe { HostRef::new(has_valid_pow) };
         #[cfg(feature = "wasm-runtime")]
         let vp_wasm_cache = unsafe { MutHostRef::new(vp_wasm_cache) };
         Self {
@@ -439,10 +465,13 @@ where
             sentinel,
             tx,
             tx_index,
+            wrapper,
             eval_runner,
             result_buffer,
             keys_changed,
             verifiers,
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow,
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache,
             #[cfg(not(feature = "wasm-runtime"))]
@@ -468,10 +497,13 @@ where
             sentinel: self.sentinel.clone(),
             tx: self.tx.clone(),
             tx_index: self.tx_index.clone(),
+            wrapper: self.wrapper.clone(),
             eval_runner: self.eval_runner.clone(),
             result_buffer: self.result_buffer.clone(),
             keys_changed: self.keys_changed.clone(),
             verifiers: self.verifiers.clone(),
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow: self.has_valid_pow.clone(),
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache: self.vp_wasm_cache.clone(),
             #[cfg(not(feature = "wasm-runtime"))]
@@ -996,6 +1028,31 @@ where
     tx_charge_gas(env, gas)
 }
 
+/// Emitting an event function exposed to the wasm VM Tx environment. The
+/// given event will be set to the write log.
+pub fn tx_emit_event<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    event_ptr: u64,
+    event_len: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (event, gas) = env
+        .memory
+        .read_bytes(event_ptr, event_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let event: TxEvent = BorshDeserialize::try_from_slice(&event)
+        .map_err(TxRuntimeError::EncodingError)?;
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let gas = write_log.emit_tx_event(event);
+    tx_charge_gas(env, gas)
+}
+
 /// Getting an IBC event function exposed to the wasm VM Tx environment.
 pub fn tx_get_ibc_events<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
@@ -1466,6 +1523,7 @@ where
     tx_charge_gas(env, gas)?;
 
     tx_validate_vp_code_hash(env, &code_hash, &code_tag)?;
+    tx_validate_vp_code(env, &code_hash)?;
 
     let write_log = unsafe { env.ctx.write_log.get() };
     let (gas, _size_diff) = write_log
@@ -1504,6 +1562,7 @@ where
         .map_err(TxRuntimeError::EncodingError)?;
 
     tx_validate_vp_code_hash(env, &code_hash, &code_tag)?;
+    tx_validate_vp_code(env, &code_hash)?;
 
     tracing::debug!("tx_init_account");
 
@@ -1597,6 +1656,29 @@ where
     Ok(tx_idx.0)
 }
 
+/// Check whether the wrapper of the current transaction contained a valid
+/// testnet faucet withdrawal PoW solution. Always false with the "mainnet"
+/// feature.
+pub fn vp_has_valid_pow<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, MEMORY_ACCESS_GAS_PER_BYTE, sentinel)?;
+    #[cfg(feature = "mainnet")]
+    let has_valid_pow = false;
+    #[cfg(not(feature = "mainnet"))]
+    let has_valid_pow = *unsafe { env.ctx.has_valid_pow.get() };
+    Ok(HostEnvResult::from(has_valid_pow).to_i64())
+}
+
 /// Getting the block hash function exposed to the wasm VM Tx environment. The
 /// hash is that of the block to which the current transaction is being applied.
 pub fn tx_get_block_hash<MEM, DB, H, CA>(
@@ -1694,6 +1776,82 @@ where
     })
 }
 
+/// Getting a section of the executing tx by its hash, exposed to the wasm VM
+/// Tx environment. Every call is charged a fixed cost, and the returned
+/// section is charged for per byte.
+///
+/// Returns `-1` when the tx has no section with the given hash, or the length
+/// of the serialized section otherwise.
+pub fn tx_get_section<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    hash_ptr: u64,
+    hash_len: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (hash, gas) = env
+        .memory
+        .read_bytes(hash_ptr, hash_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let hash =
+        Hash::try_from_slice(&hash).map_err(TxRuntimeError::EncodingError)?;
+
+    tx_charge_gas(env, TX_SECTION_ACCESS_GAS)?;
+    let tx = unsafe { env.ctx.tx.get() };
+    Ok(match tx.get_section(&hash) {
+        Some(section) => {
+            let value = section.serialize_to_vec();
+            let gas = (value.len() as u64)
+                .checked_mul(MEMORY_ACCESS_GAS_PER_BYTE)
+                .ok_or(TxRuntimeError::OutOfGas(gas::Error::GasOverflow))?;
+            tx_charge_gas(env, gas)?;
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Getting the header of the wrapper that carried the executing tx, exposed to
+/// the wasm VM Tx environment.
+///
+/// Returns `-1` when the tx wasn't carried by a wrapper, or the length of the
+/// serialized header otherwise.
+pub fn tx_get_wrapper_header<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let wrapper = unsafe { env.ctx.wrapper.get() };
+    Ok(match wrapper {
+        Some(header) => {
+            let value = header.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
 /// Getting the chain ID function exposed to the wasm VM VP environment.
 pub fn vp_get_chain_id<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
@@ -1878,6 +2036,68 @@ where
     Ok(len)
 }
 
+/// Getting the public keys of the signers of the current transaction function
+/// exposed to the wasm VM VP environment. Only the public keys that made valid
+/// signatures are included.
+pub fn vp_get_tx_signers<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let tx = unsafe { env.ctx.tx.get() };
+    let signers = vp_host_fns::get_tx_signers(
+        gas_meter, storage, write_log, tx, sentinel,
+    )?;
+    let value = signers.serialize_to_vec();
+    let len: i64 = value
+        .len()
+        .try_into()
+        .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+    let result_buffer = unsafe { env.ctx.result_buffer.get() };
+    result_buffer.replace(value);
+    Ok(len)
+}
+
+/// Getting the header of the wrapper that carried the current tx, exposed to
+/// the wasm VM VP environment.
+///
+/// Returns `-1` when the tx wasn't carried by a wrapper, or the length of the
+/// serialized header otherwise.
+pub fn vp_get_wrapper_header<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let wrapper = unsafe { env.ctx.wrapper.get() };
+    Ok(match wrapper {
+        Some(header) => {
+            let value = header.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
 /// Verify a transaction signature
 /// TODO: this is just a warkaround to track gas for multiple singature
 /// verifications. When the runtime gas meter is implemented, this funcion can
@@ -1910,7 +2130,7 @@ where
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
-    let hashes = <[Hash; 1]>::try_from_slice(&hash_list)
+    let hashes = Vec::<Hash>::try_from_slice(&hash_list)
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
     let (public_keys_map, gas) = env
@@ -1929,7 +2149,7 @@ where
         .read_bytes(signer_ptr, signer_len as _)
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
-    let signer = Address::try_from_slice(&signer)
+    let signer = Option::<Address>::try_from_slice(&signer)
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
     let (max_signatures, gas) = env
@@ -1945,7 +2165,7 @@ where
     match tx.verify_signatures(
         &hashes,
         public_keys_map,
-        &Some(signer),
+        &signer,
         threshold,
         max_signatures,
         || gas_meter.consume(gas::VERIFY_TX_SIG_GAS),
@@ -1956,7 +2176,9 @@ where
                 sentinel.set_out_of_gas();
                 Err(vp_host_fns::RuntimeError::OutOfGas(inner))
             }
-            namada_core::proto::Error::InvalidSectionSignature(_) => {
+            namada_core::proto::Error::InvalidSectionSignature(_)
+            | namada_core::proto::Error::UnknownSigner(..) => {
+                tracing::debug!("vp_verify_tx_section_signature {}", err);
                 sentinel.set_invalid_signature();
                 Ok(HostEnvResult::Fail.to_i64())
             }
@@ -2002,13 +2224,23 @@ where
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use namada_core::ledger::ibc::{IbcActions, TransferModule};
+    use namada_core::ledger::ibc::{
+        resolve_msg_sections, Error as IbcError, IbcActions, TransferModule,
+    };
 
-    let tx_data = unsafe { env.ctx.tx.get().data() }.ok_or_else(|| {
+    let tx = unsafe { env.ctx.tx.get() };
+    let tx_data = tx.data().ok_or_else(|| {
         let sentinel = unsafe { env.ctx.sentinel.get() };
         sentinel.set_invalid_commitment();
         TxRuntimeError::MissingTxData
     })?;
+    let tx_data = resolve_msg_sections(tx, tx_data).map_err(|err| {
+        if let IbcError::MissingSection(_) | IbcError::InvalidSection(_) = err {
+            let sentinel = unsafe { env.ctx.sentinel.get() };
+            sentinel.set_invalid_commitment();
+        }
+        TxRuntimeError::Ibc(err)
+    })?;
     let ctx = Rc::new(RefCell::new(env.ctx.clone()));
     let mut actions = IbcActions::new(ctx.clone());
     let module = TransferModule::new(ctx);
@@ -2079,6 +2311,48 @@ where
     Ok(())
 }
 
+/// Validate the WASM code of the VP with the given hash before installing it
+/// on an account, so that the account cannot be left with an unusable VP.
+/// Requires the code to be present in storage.
+fn tx_validate_vp_code<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    code_hash: &[u8],
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let code_hash = Hash::try_from(code_hash)
+        .map_err(|e| TxRuntimeError::InvalidVpCodeHash(e.to_string()))?;
+    let code_key = Key::wasm_code(&code_hash);
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let (result, gas) = write_log.read(&code_key);
+    tx_charge_gas(env, gas)?;
+    let code = match result {
+        Some(write_log::StorageModification::Write { value }) => value.clone(),
+        _ => {
+            let storage = unsafe { env.ctx.storage.get() };
+            let (result, gas) = storage
+                .read(&code_key)
+                .map_err(TxRuntimeError::StorageError)?;
+            tx_charge_gas(env, gas)?;
+            result.ok_or_else(|| {
+                TxRuntimeError::InvalidVpCodeHash(
+                    "The corresponding VP code doesn't exist".to_string(),
+                )
+            })?
+        }
+    };
+
+    let gas = (code.len() as u64)
+        .checked_mul(WASM_CODE_VALIDATION_GAS_PER_BYTE)
+        .ok_or(TxRuntimeError::OutOfGas(gas::Error::GasOverflow))?;
+    tx_charge_gas(env, gas)?;
+    validate_untrusted_vp_wasm(code).map_err(TxRuntimeError::InvalidVpCode)
+}
+
 /// Set the sentinel for an invalid tx section commitment
 pub fn tx_set_commitment_sentinel<MEM, DB, H, CA>(env: &TxVmEnv<MEM, DB, H, CA>)
 where
@@ -2091,6 +2365,30 @@ where
     sentinel.set_invalid_commitment();
 }
 
+/// Set the error with which the tx wasm is about to abort
+pub fn tx_set_error<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    error_ptr: u64,
+    error_len: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (error, gas) = env
+        .memory
+        .read_bytes(error_ptr, error_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let error = TxWasmError::try_from_slice(&error)
+        .map_err(TxRuntimeError::EncodingError)?;
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    sentinel.set_aborted(error);
+    Ok(())
+}
+
 /// Verify a transaction signature
 #[allow(clippy::too_many_arguments)]
 pub fn tx_verify_tx_section_signature<MEM, DB, H, CA>(
@@ -2117,7 +2415,7 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     tx_charge_gas(env, gas)?;
-    let hashes = <[Hash; 1]>::try_from_slice(&hash_list)
+    let hashes = Vec::<Hash>::try_from_slice(&hash_list)
         .map_err(TxRuntimeError::EncodingError)?;
 
     let (public_keys_map, gas) = env
@@ -2157,7 +2455,9 @@ where
                 sentinel.set_out_of_gas();
                 Err(TxRuntimeError::OutOfGas(inner))
             }
-            namada_core::proto::Error::InvalidSectionSignature(_) => {
+            namada_core::proto::Error::InvalidSectionSignature(_)
+            | namada_core::proto::Error::UnknownSigner(..) => {
+                tracing::debug!("tx_verify_tx_section_signature {}", err);
                 Ok(HostEnvResult::Fail.to_i64())
             }
             _ => Ok(HostEnvResult::Fail.to_i64()),
@@ -2661,6 +2961,7 @@ pub mod testing {
         sentinel: &mut TxSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        wrapper: &Option<Header>,
         result_buffer: &mut Option<Vec<u8>>,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
         #[cfg(feature = "wasm-runtime")] tx_wasm_cache: &mut TxCache<CA>,
@@ -2679,6 +2980,7 @@ pub mod testing {
             sentinel,
             tx,
             tx_index,
+            wrapper,
             verifiers,
             result_buffer,
             #[cfg(feature = "wasm-runtime")]
@@ -2699,10 +3001,12 @@ pub mod testing {
         sentinel: &mut VpSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        wrapper: &Option<Header>,
         verifiers: &BTreeSet<Address>,
         result_buffer: &mut Option<Vec<u8>>,
         keys_changed: &BTreeSet<Key>,
         eval_runner: &EVAL,
+        #[cfg(not(feature = "mainnet"))] has_valid_pow: &bool,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
     ) -> VpVmEnv<'static, NativeMemory, DB, H, EVAL, CA>
     where
@@ -2720,11 +3024,14 @@ pub mod testing {
             sentinel,
             tx,
             tx_index,
+            wrapper,
             iterators,
             verifiers,
             result_buffer,
             keys_changed,
             eval_runner,
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow,
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache,
         )
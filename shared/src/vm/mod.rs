@@ -4,7 +4,9 @@ use std::ffi::c_void;
 use std::marker::PhantomData;
 use std::slice;
 
-use wasmparser::{Validator, WasmFeatures};
+use wasmparser::{
+    ExternalKind, Parser, Payload, ValType, Validator, WasmFeatures,
+};
 
 pub mod host_env;
 pub mod memory;
@@ -14,6 +16,25 @@ pub mod types;
 pub mod wasm;
 use thiserror::Error;
 
+/// The entrypoint exported by the wasm code of validity predicates
+pub const VP_ENTRYPOINT: &str = "_validate_tx";
+
+/// The maximum size in bytes of the wasm code that can be installed as the
+/// validity predicate of an account
+pub const MAX_VP_CODE_SIZE: usize = 4 * 1024 * 1024;
+
+/// The maximum number of wasm pages (64 KiB each) that the linear memory of
+/// an untrusted wasm module may declare, either as its initial or its maximum
+/// size
+pub const MAX_WASM_MEMORY_PAGES: u32 = 200; // 12.8 MiB
+
+/// The maximum number of elements that a table of an untrusted wasm module may
+/// declare, either as its initial or its maximum size
+pub const MAX_WASM_TABLE_ELEMENTS: u32 = 10_000;
+
+/// The maximum number of globals that an untrusted wasm module may declare
+pub const MAX_WASM_GLOBALS: usize = 1_000;
+
 const UNTRUSTED_WASM_FEATURES: WasmFeatures = WasmFeatures {
     mutable_global: false,
     saturating_float_to_int: false,
@@ -44,6 +65,30 @@ pub enum WasmValidationError {
          {UNTRUSTED_WASM_FEATURES:?}"
     )]
     ForbiddenWasmFeatures(wasmparser::BinaryReaderError),
+    #[error(
+        "The WASM code of {0} bytes exceeds the maximum size of \
+         {MAX_VP_CODE_SIZE} bytes"
+    )]
+    CodeTooLarge(usize),
+    #[error("The WASM code doesn't export the {0} function")]
+    MissingEntrypoint(&'static str),
+    #[error("The exported WASM function {0} has an invalid signature")]
+    InvalidEntrypointSignature(&'static str),
+    #[error(
+        "The WASM code declares a memory of {0} pages, exceeding the maximum \
+         of {MAX_WASM_MEMORY_PAGES} pages"
+    )]
+    MemoryTooLarge(u64),
+    #[error(
+        "The WASM code declares a table of {0} elements, exceeding the \
+         maximum of {MAX_WASM_TABLE_ELEMENTS} elements"
+    )]
+    TableTooLarge(u32),
+    #[error(
+        "The WASM code declares {0} globals, exceeding the maximum of \
+         {MAX_WASM_GLOBALS} globals"
+    )]
+    TooManyGlobals(usize),
 }
 
 /// WASM Cache access level, used to limit dry-ran transactions to read-only
@@ -231,13 +276,91 @@ impl<'a, T: 'a> MutHostSlice<'a, &[T]> {
 }
 
 /// Validate an untrusted wasm code with restrictions that we place such code
-/// (e.g. transaction and validity predicates)
+/// (e.g. transaction and validity predicates). Besides the allowed features,
+/// the memories, tables and globals declared by the code must be within
+/// [`MAX_WASM_MEMORY_PAGES`], [`MAX_WASM_TABLE_ELEMENTS`] and
+/// [`MAX_WASM_GLOBALS`], so that no module can request more resources than the
+/// VM is willing to provide at instantiation.
 pub fn validate_untrusted_wasm(
     wasm_code: impl AsRef<[u8]>,
 ) -> Result<(), WasmValidationError> {
     let mut validator = Validator::new_with_features(UNTRUSTED_WASM_FEATURES);
-    let _types = validator
+    let types = validator
         .validate_all(wasm_code.as_ref())
         .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+    validate_wasm_limits(&types)
+}
+
+/// Check the resources declared by a validated wasm module against the limits
+/// placed on untrusted wasm code
+fn validate_wasm_limits(
+    types: &wasmparser::types::Types,
+) -> Result<(), WasmValidationError> {
+    for index in 0..types.memory_count() as u32 {
+        if let Some(memory) = types.memory_at(index) {
+            let pages = memory.maximum.unwrap_or_default().max(memory.initial);
+            if pages > u64::from(MAX_WASM_MEMORY_PAGES) {
+                return Err(WasmValidationError::MemoryTooLarge(pages));
+            }
+        }
+    }
+    for index in 0..types.table_count() as u32 {
+        if let Some(table) = types.table_at(index) {
+            let elements = table.maximum.unwrap_or_default().max(table.initial);
+            if elements > MAX_WASM_TABLE_ELEMENTS {
+                return Err(WasmValidationError::TableTooLarge(elements));
+            }
+        }
+    }
+    let globals = types.global_count();
+    if globals > MAX_WASM_GLOBALS {
+        return Err(WasmValidationError::TooManyGlobals(globals));
+    }
+    Ok(())
+}
+
+/// Validate an untrusted wasm code that is about to be installed as the
+/// validity predicate of an account. On top of the checks of
+/// [`validate_untrusted_wasm`], the code must not exceed [`MAX_VP_CODE_SIZE`]
+/// and it must export the [`VP_ENTRYPOINT`] with the signature expected by the
+/// VM.
+pub fn validate_untrusted_vp_wasm(
+    wasm_code: impl AsRef<[u8]>,
+) -> Result<(), WasmValidationError> {
+    let wasm_code = wasm_code.as_ref();
+    if wasm_code.len() > MAX_VP_CODE_SIZE {
+        return Err(WasmValidationError::CodeTooLarge(wasm_code.len()));
+    }
+    let mut validator = Validator::new_with_features(UNTRUSTED_WASM_FEATURES);
+    let types = validator
+        .validate_all(wasm_code)
+        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+    validate_wasm_limits(&types)?;
+
+    let entrypoint = Parser::new(0)
+        .parse_all(wasm_code)
+        .filter_map(|payload| match payload {
+            Ok(Payload::ExportSection(exports)) => Some(exports),
+            _ => None,
+        })
+        .flat_map(|exports| exports.into_iter().filter_map(Result::ok))
+        .find(|export| {
+            export.name == VP_ENTRYPOINT && export.kind == ExternalKind::Func
+        })
+        .ok_or(WasmValidationError::MissingEntrypoint(VP_ENTRYPOINT))?;
+    // The VM calls the entrypoint with 8 `i64` arguments and expects an `i64`
+    // result
+    let is_valid_signature = types
+        .function_at(entrypoint.index)
+        .map(|func_type| {
+            func_type.params() == [ValType::I64; 8]
+                && func_type.results() == [ValType::I64]
+        })
+        .unwrap_or_default();
+    if !is_valid_signature {
+        return Err(WasmValidationError::InvalidEntrypointSignature(
+            VP_ENTRYPOINT,
+        ));
+    }
     Ok(())
 }
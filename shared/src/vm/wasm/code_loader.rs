@@ -0,0 +1,219 @@
+//! Loading of the wasm code referenced by hash by the txs and VPs of a block.
+//!
+//! The code of a tx or a VP referenced by its hash is only resolved by the
+//! [`CodeLoader`] at the moment it's about to run, so that a tx rejected
+//! before its execution never loads it. The loader is shared by the clones of
+//! a wasm [`Cache`] and deduplicates concurrent requests for the same hash: a
+//! request waits on one in progress, which leaves the compiled module in the
+//! cache. The lengths of the code read from storage, on which the gas is
+//! charged, are kept until [`CodeLoader::clear`] is called once the block is
+//! committed, as the committed state doesn't change in the meantime.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use borsh::BorshDeserialize;
+use namada_core::ledger::storage::write_log::StorageModification;
+use wasmer::{Module, Store};
+
+use super::run::{Error, Result};
+use crate::ledger::storage::write_log::WriteLog;
+use crate::ledger::storage::{self, Storage, StorageHasher};
+use crate::types::hash::Hash;
+use crate::types::storage::Key;
+use crate::vm::wasm::{Cache, CacheName};
+use crate::vm::WasmCacheAccess;
+
+/// The length of some code loaded at the current block, if known, behind its
+/// own lock so that the loads of the same code wait on one another
+type Slot = Arc<Mutex<Option<u64>>>;
+
+/// Loader of the wasm code referenced by hash. Thread-safe.
+#[derive(Debug, Default)]
+pub struct CodeLoader {
+    /// The slots of the code loaded at the current block, by hash
+    slots: Mutex<HashMap<Hash, Slot>>,
+    /// The number of times some code was read from the write log or storage
+    code_reads: AtomicUsize,
+}
+
+impl CodeLoader {
+    /// Get the compiled module of the code with the given hash from the given
+    /// cache, or read the code and compile it. Returns the module along with
+    /// the length of the code, on which the loading and compiling gas is
+    /// charged.
+    pub fn load<DB, H, CN, CA>(
+        &self,
+        wasm_cache: &mut Cache<CN, CA>,
+        code_hash: &Hash,
+        write_log: &WriteLog,
+        storage: &Storage<DB, H>,
+    ) -> Result<(Module, Store, u64)>
+    where
+        DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: 'static + StorageHasher,
+        CN: 'static + CacheName,
+        CA: 'static + WasmCacheAccess,
+    {
+        let slot = self.slot(code_hash);
+        let mut code_len = lock(&slot);
+        if let Some((module, store)) = wasm_cache.fetch(code_hash)? {
+            // Gas accounting even if the compiled module is in cache
+            let len = self.memoized_len(
+                &mut code_len,
+                code_hash,
+                write_log,
+                storage,
+            )?;
+            return Ok((module, store, len));
+        }
+        let (code, from_storage) = read_code(code_hash, write_log, storage)?;
+        self.code_reads.fetch_add(1, Ordering::Relaxed);
+        let len = u64::try_from(code.len())
+            .map_err(|e| Error::ConversionError(e.to_string()))?;
+        match wasm_cache.compile_or_fetch(code)? {
+            Some((module, store)) => {
+                if from_storage {
+                    *code_len = Some(len);
+                }
+                Ok((module, store, len))
+            }
+            None => Err(Error::NoCompiledWasmCode),
+        }
+    }
+
+    /// Get the length of the code with the given hash, without loading the
+    /// code itself
+    pub fn code_len<DB, H>(
+        &self,
+        code_hash: &Hash,
+        write_log: &WriteLog,
+        storage: &Storage<DB, H>,
+    ) -> Result<u64>
+    where
+        DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: 'static + StorageHasher,
+    {
+        let slot = self.slot(code_hash);
+        let mut code_len = lock(&slot);
+        self.memoized_len(&mut code_len, code_hash, write_log, storage)
+    }
+
+    /// The number of times some code was read since the loader was created
+    pub fn code_reads(&self) -> usize {
+        self.code_reads.load(Ordering::Relaxed)
+    }
+
+    /// Forget the lengths of the loaded code, once their block is committed
+    pub fn clear(&self) {
+        lock(&self.slots).clear();
+    }
+
+    /// Get the slot of the code with the given hash
+    fn slot(&self, code_hash: &Hash) -> Slot {
+        lock(&self.slots).entry(*code_hash).or_default().clone()
+    }
+
+    /// Get the memoized length of some code, or read it. Only the lengths
+    /// read from storage are memoized, as those written in the write log may
+    /// still be dropped.
+    fn memoized_len<DB, H>(
+        &self,
+        code_len: &mut Option<u64>,
+        code_hash: &Hash,
+        write_log: &WriteLog,
+        storage: &Storage<DB, H>,
+    ) -> Result<u64>
+    where
+        DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: 'static + StorageHasher,
+    {
+        if let Some(len) = *code_len {
+            return Ok(len);
+        }
+        let (len, from_storage) = read_code_len(code_hash, write_log, storage)?;
+        if from_storage {
+            *code_len = Some(len);
+        }
+        Ok(len)
+    }
+}
+
+/// Lock the given mutex. The data behind the locks of the loader are left
+/// consistent by every critical section.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Read the wasm code with the given hash from the write log or storage, along
+/// with whether it was read from storage
+fn read_code<DB, H>(
+    code_hash: &Hash,
+    write_log: &WriteLog,
+    storage: &Storage<DB, H>,
+) -> Result<(Vec<u8>, bool)>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let key = Key::wasm_code(code_hash);
+    if let Some(StorageModification::Write { value }) = write_log.read(&key).0 {
+        return Ok((value.clone(), false));
+    }
+    match storage
+        .read(&key)
+        .map_err(|e| {
+            Error::LoadWasmCode(format!(
+                "Read wasm code failed from storage: key {}, error {}",
+                key, e
+            ))
+        })?
+        .0
+    {
+        Some(code) => Ok((code, true)),
+        None => Err(Error::LoadWasmCode(format!(
+            "No wasm code in storage: key {}",
+            key
+        ))),
+    }
+}
+
+/// Read the length of the wasm code with the given hash from the write log or
+/// storage, along with whether it was read from storage
+fn read_code_len<DB, H>(
+    code_hash: &Hash,
+    write_log: &WriteLog,
+    storage: &Storage<DB, H>,
+) -> Result<(u64, bool)>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let key = Key::wasm_code_len(code_hash);
+    if let Some(StorageModification::Write { value }) = write_log.read(&key).0 {
+        let len = u64::try_from_slice(value)
+            .map_err(|e| Error::ConversionError(e.to_string()))?;
+        return Ok((len, false));
+    }
+    match storage
+        .read(&key)
+        .map_err(|e| {
+            Error::LoadWasmCode(format!(
+                "Read wasm code length failed from storage: key {}, error {}",
+                key, e
+            ))
+        })?
+        .0
+    {
+        Some(v) => u64::try_from_slice(&v)
+            .map(|len| (len, true))
+            .map_err(|e| Error::ConversionError(e.to_string())),
+        None => Err(Error::LoadWasmCode(format!(
+            "No wasm code length in storage: key {}",
+            key
+        ))),
+    }
+}
@@ -20,6 +20,7 @@ use wasmer_cache::{FileSystemCache, Hash as CacheHash};
 
 use crate::core::types::hash::Hash;
 use crate::types::control_flow::time::{ExponentialBackoff, SleepStrategy};
+use crate::vm::wasm::code_loader::CodeLoader;
 use crate::vm::wasm::run::untrusted_wasm_store;
 use crate::vm::wasm::{self, memory};
 use crate::vm::{WasmCacheAccess, WasmCacheRoAccess};
@@ -37,6 +38,9 @@ pub struct Cache<N: CacheName, A: WasmCacheAccess> {
     name: PhantomData<N>,
     /// Cache access level
     access: PhantomData<A>,
+    /// Loader of the code referenced by hash, shared by the clones of the
+    /// cache
+    code_loader: Arc<CodeLoader>,
 }
 
 /// This trait is used to give names to different caches
@@ -89,9 +93,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
             in_memory,
             name: Default::default(),
             access: Default::default(),
+            code_loader: Default::default(),
         }
     }
 
+    /// The loader of the code referenced by hash
+    pub fn code_loader(&self) -> &Arc<CodeLoader> {
+        &self.code_loader
+    }
+
     /// Get a WASM module from LRU cache, from a file or compile it and cache
     /// it. If the cache access is set to [`crate::vm::WasmCacheRwAccess`], it
     /// updates the position in the LRU cache. Otherwise, the compiled
@@ -443,6 +453,9 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
             in_memory: self.in_memory.clone(),
             name: Default::default(),
             access: Default::default(),
+            // Read-only caches may run against another state than the one of
+            // the block being executed
+            code_loader: Default::default(),
         }
     }
 }
@@ -853,6 +866,41 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_fetch_or_compile_different_codes() {
+        // Load two different WASMs
+        let tx_read_storage_key = load_wasm(TestWasms::TxReadStorageKey.path());
+        let tx_no_op = load_wasm(TestWasms::TxNoOp.path());
+        assert_ne!(tx_read_storage_key.hash, tx_no_op.hash);
+
+        // Create a new cache large enough to hold both modules
+        let (mut cache, _tmp_dir) = testing::cache::<TestCache>();
+        for wasm in [&tx_read_storage_key, &tx_no_op] {
+            let fetched = cache.compile_or_fetch(&wasm.code).unwrap();
+            assert_matches!(fetched, Some(_), "The code should be compiled");
+        }
+        assert_eq!(cache.get_size(), 2, "Each code must have its own entry");
+
+        // Each hash must fetch the module compiled from its own code, both
+        // from memory and from file
+        for from_file in [false, true] {
+            if from_file {
+                cache.in_memory.write().unwrap().clear();
+                cache.progress = Default::default();
+            }
+            assert!(
+                imports_of(&mut cache, &tx_read_storage_key.hash)
+                    .contains(&"namada_tx_read".to_string()),
+                "The module must be the one of `tx_read_storage_key`"
+            );
+            assert!(
+                !imports_of(&mut cache, &tx_no_op.hash)
+                    .contains(&"namada_tx_read".to_string()),
+                "The module must be the one of `tx_no_op`"
+            );
+        }
+    }
+
     #[test]
     fn test_pre_compile_valid_wasm() {
         // Load some WASMs and find their hashes and in-memory size
@@ -1037,6 +1085,18 @@ mod test {
         WasmWithMeta { code, hash, size }
     }
 
+    /// Get the names of the imports of the cached module with the given hash
+    fn imports_of(
+        cache: &mut Cache<TestCache, WasmCacheRwAccess>,
+        hash: &Hash,
+    ) -> Vec<String> {
+        let (module, _store) = cache.fetch(hash).unwrap().unwrap();
+        module
+            .imports()
+            .map(|import| import.name().to_string())
+            .collect()
+    }
+
     /// A test helper for loading WASM and finding its hash and size
     #[derive(Clone, Debug)]
     struct WasmWithMeta {
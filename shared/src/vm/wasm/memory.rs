@@ -19,6 +19,7 @@ use wasmer_vm::{
 use crate::proto::Tx;
 use crate::vm::memory::VmMemory;
 use crate::vm::types::VpInput;
+use crate::vm::{MAX_WASM_MEMORY_PAGES, MAX_WASM_TABLE_ELEMENTS};
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -44,11 +45,11 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Initial pages in tx memory
 pub const TX_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
 /// Mamixmum pages in tx memory
-pub const TX_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
+pub const TX_MEMORY_MAX_PAGES: u32 = MAX_WASM_MEMORY_PAGES; // 12.8 MiB
 /// Initial pages in VP memory
 pub const VP_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
 /// Mamixmum pages in VP memory
-pub const VP_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
+pub const VP_MEMORY_MAX_PAGES: u32 = MAX_WASM_MEMORY_PAGES; // 12.8 MiB
 
 /// Prepare memory for instantiating a transaction module
 pub fn prepare_tx_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
@@ -297,6 +298,8 @@ pub struct Limit<T: Tunables> {
     /// each). Since Wasmer ensures there is only none or one memory, this
     /// is practically an upper limit for the guest memory.
     limit: Pages,
+    /// The maximum number of elements a table is allowed to have
+    table_limit: u32,
     /// The base implementation we delegate all the logic to
     base: T,
 }
@@ -306,13 +309,21 @@ pub struct Limit<T: Tunables> {
 pub fn vp_limit() -> Limit<BaseTunables> {
     let base = BaseTunables::for_target(&Target::default());
     let limit = Pages(VP_MEMORY_MAX_PAGES);
-    Limit { limit, base }
+    Limit {
+        limit,
+        table_limit: MAX_WASM_TABLE_ELEMENTS,
+        base,
+    }
 }
 /// A [`Limit`] with memory limit setup for transaction WASM execution.
 pub fn tx_limit() -> Limit<BaseTunables> {
     let base = BaseTunables::for_target(&Target::default());
     let limit = Pages(TX_MEMORY_MAX_PAGES);
-    Limit { limit, base }
+    Limit {
+        limit,
+        table_limit: MAX_WASM_TABLE_ELEMENTS,
+        base,
+    }
 }
 
 impl<T: Tunables> Limit<T> {
@@ -328,6 +339,17 @@ impl<T: Tunables> Limit<T> {
         adjusted
     }
 
+    /// Takes an input table type as requested by the guest and sets a maximum
+    /// if missing. The resulting table type must be validated with
+    /// validate_table before creating the table.
+    fn adjust_table(&self, requested: &TableType) -> TableType {
+        let mut adjusted = *requested;
+        if requested.maximum.is_none() {
+            adjusted.maximum = Some(self.table_limit);
+        }
+        adjusted
+    }
+
     /// Ensures the a given memory type does not exceed the memory limit.
     /// Call this after adjusting the memory.
     fn validate_memory(
@@ -352,6 +374,24 @@ impl<T: Tunables> Limit<T> {
 
         Ok(())
     }
+
+    /// Ensures the a given table type does not exceed the table limit.
+    /// Call this after adjusting the table.
+    fn validate_table(
+        &self,
+        ty: &TableType,
+    ) -> std::result::Result<(), String> {
+        if ty.minimum > self.table_limit {
+            return Err("Minimum exceeds the allowed table limit".to_string());
+        }
+        match ty.maximum {
+            Some(max) if max > self.table_limit => {
+                Err("Maximum exceeds the allowed table limit".to_string())
+            }
+            Some(_) => Ok(()),
+            None => Err("Maximum unset".to_string()),
+        }
+    }
 }
 
 impl<T: Tunables> Tunables for Limit<T> {
@@ -404,26 +444,33 @@ impl<T: Tunables> Tunables for Limit<T> {
     /// Create a table owned by the host given a [`TableType`] and a
     /// [`TableStyle`].
     ///
-    /// Delegated to base.
+    /// The requested table type is validated, adjusted to the limit and then
+    /// passed to base.
     fn create_host_table(
         &self,
         ty: &TableType,
         style: &TableStyle,
     ) -> std::result::Result<Arc<dyn vm::Table>, String> {
-        self.base.create_host_table(ty, style)
+        let adjusted = self.adjust_table(ty);
+        self.validate_table(&adjusted)?;
+        self.base.create_host_table(&adjusted, style)
     }
 
     /// Create a table owned by the VM given a [`TableType`] and a
     /// [`TableStyle`].
     ///
-    /// Delegated to base.
+    /// The requested table type is validated, adjusted to the limit and then
+    /// passed to base.
     unsafe fn create_vm_table(
         &self,
         ty: &TableType,
         style: &TableStyle,
         vm_definition_location: NonNull<VMTableDefinition>,
     ) -> std::result::Result<Arc<dyn vm::Table>, String> {
-        self.base.create_vm_table(ty, style, vm_definition_location)
+        let adjusted = self.adjust_table(ty);
+        self.validate_table(&adjusted)?;
+        self.base
+            .create_vm_table(&adjusted, style, vm_definition_location)
     }
 }
 
@@ -452,7 +499,11 @@ pub mod tests {
 
         let base = BaseTunables::for_target(&Target::default());
         let limit = Pages(24);
-        let tunables = Limit { limit, base };
+        let tunables = Limit {
+            limit,
+            table_limit: MAX_WASM_TABLE_ELEMENTS,
+            base,
+        };
 
         // Create a store, that holds the engine and our custom tunables
         let store = Store::new_with_tunables(&engine, tunables);
@@ -479,4 +530,50 @@ pub mod tests {
         println!("Memory of this instance: {:?}", first_memory);
         assert_eq!(first_memory.ty().maximum.unwrap(), limit);
     }
+
+    #[test]
+    fn test_wasm_tunables_limit_table() {
+        let compiler = Cranelift::default();
+        let engine = wasmer_engine_universal::Universal::new(compiler).engine();
+        let base = BaseTunables::for_target(&Target::default());
+        let table_limit = 100;
+        let tunables = Limit {
+            limit: Pages(24),
+            table_limit,
+            base,
+        };
+        let store = Store::new_with_tunables(&engine, tunables);
+        let import_object = wasmer::imports! {};
+
+        // A table within the limit without a maximum gets the limit as its
+        // maximum
+        let wat = br#"(module (table 10 funcref) (export "table" (table 0)))"#;
+        let module = Module::new(&store, wat2wasm(wat).unwrap()).unwrap();
+        let instance = Instance::new(&module, &import_object).unwrap();
+        let table = instance.exports.get_table("table").unwrap();
+        assert_eq!(table.ty().maximum, Some(table_limit));
+
+        // A table over the limit cannot be instantiated
+        let wat = br#"(module (table 101 funcref) (export "table" (table 0)))"#;
+        let module = Module::new(&store, wat2wasm(wat).unwrap()).unwrap();
+        let error = Instance::new(&module, &import_object).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Minimum exceeds the allowed table limit"),
+            "Unexpected error {error}"
+        );
+
+        // Neither can a table whose maximum is over the limit
+        let wat =
+            br#"(module (table 10 200 funcref) (export "table" (table 0)))"#;
+        let module = Module::new(&store, wat2wasm(wat).unwrap()).unwrap();
+        let error = Instance::new(&module, &import_object).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Maximum exceeds the allowed table limit"),
+            "Unexpected error {error}"
+        );
+    }
 }
@@ -1,10 +1,12 @@
 //! Modules related to wasm
 
+pub mod code_loader;
 pub mod compilation_cache;
 pub mod host_env;
 pub mod memory;
 pub mod run;
 
+pub use code_loader::CodeLoader;
 pub use compilation_cache::common::{Cache, CacheName};
 pub use compilation_cache::tx::TxCache;
 pub use compilation_cache::vp::VpCache;
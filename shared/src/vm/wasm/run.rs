@@ -5,8 +5,7 @@ use std::marker::PhantomData;
 
 use borsh::BorshDeserialize;
 use namada_core::ledger::gas::{GasMetering, TxGasMeter, WASM_MEMORY_PAGE_GAS};
-use namada_core::ledger::storage::write_log::StorageModification;
-use namada_core::types::transaction::TxSentinel;
+use namada_core::types::transaction::{TxSentinel, TxWasmError};
 use namada_core::types::validity_predicate::VpSentinel;
 use parity_wasm::elements;
 use thiserror::Error;
@@ -17,7 +16,7 @@ use super::TxCache;
 use crate::ledger::gas::VpGasMeter;
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{self, Storage, StorageHasher};
-use crate::proto::{Commitment, Section, Tx};
+use crate::proto::{Commitment, Header, Section, Tx};
 use crate::types::address::Address;
 use crate::types::hash::{Error as TxHashError, Hash};
 use crate::types::internal::HostEnvResult;
@@ -29,10 +28,10 @@ use crate::vm::wasm::host_env::{tx_imports, vp_imports};
 use crate::vm::wasm::{memory, Cache, CacheName, VpCache};
 use crate::vm::{
     validate_untrusted_wasm, WasmCacheAccess, WasmValidationError,
+    VP_ENTRYPOINT,
 };
 
 const TX_ENTRYPOINT: &str = "_apply_tx";
-const VP_ENTRYPOINT: &str = "_validate_tx";
 const WASM_STACK_LIMIT: u32 = u16::MAX as u32;
 
 #[allow(missing_docs)]
@@ -58,6 +57,8 @@ pub enum Error {
     MissingModuleEntrypoint(wasmer::ExportError),
     #[error("Failed running wasm with: {0}")]
     RuntimeError(wasmer::RuntimeError),
+    #[error("Transaction aborted: {0}")]
+    TxAborted(TxWasmError),
     #[error("Failed instantiating wasm module with: {0}")]
     // Boxed cause it's 128b
     InstantiationError(Box<wasmer::InstantiationError>),
@@ -97,6 +98,7 @@ pub fn tx<DB, H, CA>(
     gas_meter: &mut TxGasMeter,
     tx_index: &TxIndex,
     tx: &Tx,
+    wrapper: Option<&Header>,
     vp_wasm_cache: &mut VpCache<CA>,
     tx_wasm_cache: &mut TxCache<CA>,
 ) -> Result<BTreeSet<Address>>
@@ -152,11 +154,14 @@ where
         storage,
         gas_meter,
     )?;
+    charge_declared_memory(&module, memory::TX_MEMORY_INIT_PAGES, gas_meter)?;
 
     let mut iterators: PrefixIterators<'_, DB> = PrefixIterators::default();
     let mut verifiers = BTreeSet::new();
     let mut result_buffer: Option<Vec<u8>> = None;
 
+    let wrapper = wrapper.cloned();
+
     let mut sentinel = TxSentinel::default();
     let env = TxVmEnv::new(
         WasmMemory::default(),
@@ -167,6 +172,7 @@ where
         &mut sentinel,
         tx,
         tx_index,
+        &wrapper,
         &mut verifiers,
         &mut result_buffer,
         vp_wasm_cache,
@@ -209,6 +215,7 @@ where
             TxSentinel::InvalidCommitment => {
                 Error::MissingSection(err.to_string())
             }
+            TxSentinel::Aborted(error) => Error::TxAborted(error),
         }
     })?;
 
@@ -223,6 +230,7 @@ pub fn vp<DB, H, CA>(
     vp_code_hash: Hash,
     tx: &Tx,
     tx_index: &TxIndex,
+    wrapper: Option<&Header>,
     address: &Address,
     storage: &Storage<DB, H>,
     write_log: &WriteLog,
@@ -230,6 +238,7 @@ pub fn vp<DB, H, CA>(
     keys_changed: &BTreeSet<Key>,
     verifiers: &BTreeSet<Address>,
     mut vp_wasm_cache: VpCache<CA>,
+    #[cfg(not(feature = "mainnet"))] has_valid_pow: bool,
 ) -> Result<bool>
 where
     DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
@@ -244,6 +253,7 @@ where
         storage,
         gas_meter,
     )?;
+    charge_declared_memory(&module, memory::VP_MEMORY_INIT_PAGES, gas_meter)?;
 
     let mut iterators: PrefixIterators<'_, DB> = PrefixIterators::default();
     let mut result_buffer: Option<Vec<u8>> = None;
@@ -252,6 +262,7 @@ where
         hasher: PhantomData,
         cache_access: PhantomData,
     };
+    let wrapper = wrapper.cloned();
 
     let mut sentinel = VpSentinel::default();
     let env = VpVmEnv::new(
@@ -263,11 +274,14 @@ where
         &mut sentinel,
         tx,
         tx_index,
+        &wrapper,
         &mut iterators,
         verifiers,
         &mut result_buffer,
         keys_changed,
         &eval_runner,
+        #[cfg(not(feature = "mainnet"))]
+        &has_valid_pow,
         &mut vp_wasm_cache,
     );
 
@@ -455,6 +469,11 @@ where
             storage,
             gas_meter,
         )?;
+        charge_declared_memory(
+            &module,
+            memory::VP_MEMORY_INIT_PAGES,
+            gas_meter,
+        )?;
 
         let initial_memory =
             memory::prepare_vp_memory(&store).map_err(Error::MemoryError)?;
@@ -519,74 +538,9 @@ where
 {
     match code_or_hash {
         Commitment::Hash(code_hash) => {
-            let (module, store, tx_len) = match wasm_cache.fetch(code_hash)? {
-                Some((module, store)) => {
-                    // Gas accounting even if the compiled module is in cache
-                    let key = Key::wasm_code_len(code_hash);
-                    let tx_len = match write_log.read(&key).0 {
-                        Some(StorageModification::Write { value }) => {
-                            u64::try_from_slice(value).map_err(|e| {
-                                Error::ConversionError(e.to_string())
-                            })
-                        }
-                        _ => match storage
-                            .read(&key)
-                            .map_err(|e| {
-                                Error::LoadWasmCode(format!(
-                                    "Read wasm code length failed from \
-                                     storage: key {}, error {}",
-                                    key, e
-                                ))
-                            })?
-                            .0
-                        {
-                            Some(v) => u64::try_from_slice(&v).map_err(|e| {
-                                Error::ConversionError(e.to_string())
-                            }),
-                            None => Err(Error::LoadWasmCode(format!(
-                                "No wasm code length in storage: key {}",
-                                key
-                            ))),
-                        },
-                    }?;
-
-                    (module, store, tx_len)
-                }
-                None => {
-                    let key = Key::wasm_code(code_hash);
-                    let code = match write_log.read(&key).0 {
-                        Some(StorageModification::Write { value }) => {
-                            value.clone()
-                        }
-                        _ => match storage
-                            .read(&key)
-                            .map_err(|e| {
-                                Error::LoadWasmCode(format!(
-                                    "Read wasm code failed from storage: key \
-                                     {}, error {}",
-                                    key, e
-                                ))
-                            })?
-                            .0
-                        {
-                            Some(v) => v,
-                            None => {
-                                return Err(Error::LoadWasmCode(format!(
-                                    "No wasm code in storage: key {}",
-                                    key
-                                )));
-                            }
-                        },
-                    };
-                    let tx_len = u64::try_from(code.len())
-                        .map_err(|e| Error::ConversionError(e.to_string()))?;
-
-                    match wasm_cache.compile_or_fetch(code)? {
-                        Some((module, store)) => (module, store, tx_len),
-                        None => return Err(Error::NoCompiledWasmCode),
-                    }
-                }
-            };
+            let code_loader = wasm_cache.code_loader().clone();
+            let (module, store, tx_len) =
+                code_loader.load(wasm_cache, code_hash, write_log, storage)?;
 
             gas_meter
                 .add_wasm_load_from_storage_gas(tx_len)
@@ -614,6 +568,33 @@ where
     }
 }
 
+// Charge gas for the linear memory that the module declares beyond the initial
+// pages that we provide to every module. Memory grown at runtime is metered by
+// the `memory.grow` cost injected into the code, while this accounts for the
+// memory allocated at instantiation.
+fn charge_declared_memory(
+    module: &Module,
+    init_pages: u32,
+    gas_meter: &mut dyn GasMetering,
+) -> Result<()> {
+    let declared_pages = module
+        .exports()
+        .memories()
+        .map(|memory| memory.ty().minimum.0)
+        .chain(
+            module
+                .imports()
+                .memories()
+                .map(|memory| memory.ty().minimum.0),
+        )
+        .max()
+        .unwrap_or_default();
+    let extra_pages = declared_pages.saturating_sub(init_pages);
+    gas_meter
+        .consume(u64::from(extra_pages) * u64::from(WASM_MEMORY_PAGE_GAS))
+        .map_err(|e| Error::GasError(e.to_string()))
+}
+
 /// Get the gas rules used to meter wasm operations
 fn get_gas_rules() -> wasm_instrument::gas_metering::ConstantCostRules {
     // NOTE: costs set to 0 don't actually trigger the injection of a call to
@@ -632,6 +613,7 @@ fn get_gas_rules() -> wasm_instrument::gas_metering::ConstantCostRules {
 mod tests {
     use borsh_ext::BorshSerializeExt;
     use itertools::Either;
+    use namada_core::ledger::gas::Gas;
     use namada_test_utils::TestWasms;
     use test_log::test;
     use wasmer_vm::TrapCode;
@@ -642,7 +624,7 @@ mod tests {
     use crate::types::hash::Hash;
     use crate::types::transaction::TxType;
     use crate::types::validity_predicate::EvalVp;
-    use crate::vm::wasm;
+    use crate::vm::{wasm, MAX_WASM_GLOBALS};
 
     const TX_GAS_LIMIT: u64 = 10_000_000_000;
 
@@ -732,6 +714,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         );
@@ -749,6 +732,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         )
@@ -816,6 +800,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -823,6 +808,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache.clone(),
+            #[cfg(not(feature = "mainnet"))]
+            false,
         )
         .unwrap();
         assert!(passed);
@@ -848,6 +835,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -855,6 +843,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         )
         .unwrap();
 
@@ -900,6 +890,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -907,6 +898,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache.clone(),
+            #[cfg(not(feature = "mainnet"))]
+            false,
         );
         assert!(result.is_ok(), "Expected success, got {:?}", result);
 
@@ -920,6 +913,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -927,6 +921,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         )
         .expect_err("Expected to run out of memory");
 
@@ -971,6 +967,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         );
@@ -1032,6 +1029,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -1039,6 +1037,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         );
         // Depending on platform, we get a different error from the running out
         // of memory
@@ -1105,6 +1105,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         )
@@ -1158,6 +1159,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -1165,6 +1167,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         )
         .expect_err("Expected to run out of memory");
 
@@ -1235,6 +1239,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -1242,11 +1247,192 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         )
         .unwrap();
         assert!(!passed);
     }
 
+    /// Test that a transaction wasm declaring more memory, table elements or
+    /// globals than allowed is rejected before instantiation with the same
+    /// error every time.
+    #[test]
+    fn test_tx_declared_resources_limits() {
+        let assert_rejected = |resources: &str, expected: &str| {
+            // Run twice with fresh caches to ensure that the rejection doesn't
+            // depend on the state of the node
+            let errors: Vec<String> = (0..2)
+                .map(|_| {
+                    let (result, _gas) = tx_declaring(resources);
+                    let error = result.expect_err("Expected the tx to fail");
+                    assert!(
+                        matches!(error, Error::ValidationError(_)),
+                        "Expected a validation error, got {:?}",
+                        error
+                    );
+                    error.to_string()
+                })
+                .collect();
+            assert_eq!(errors[0], errors[1]);
+            assert!(
+                errors[0].contains(expected),
+                "Unexpected error {}",
+                errors[0]
+            );
+        };
+
+        assert_rejected("(memory (;0;) 1000)", "memory of 1000 pages");
+        assert_rejected("(memory (;0;) 16 1000)", "memory of 1000 pages");
+        assert_rejected(
+            "(memory (;0;) 16) (table (;0;) 100000 funcref)",
+            "table of 100000 elements",
+        );
+        let globals = (0..=MAX_WASM_GLOBALS)
+            .map(|_| "(global i32 (i32.const 0))")
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_rejected(
+            &format!("(memory (;0;) 16) {globals}"),
+            &format!("{} globals", MAX_WASM_GLOBALS + 1),
+        );
+
+        // A module within the limits is unaffected
+        let (result, _gas) = tx_declaring(&format!(
+            "(memory (;0;) {}) (table (;0;) 1 1 funcref)",
+            memory::TX_MEMORY_MAX_PAGES
+        ));
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+    }
+
+    /// Test that the memory declared by a transaction wasm beyond the initial
+    /// pages is charged, while memory within the initial pages is free.
+    #[test]
+    fn test_tx_declared_memory_gas() {
+        let (result, small_gas) = tx_declaring("(memory (;0;) 16)");
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+        let (result, init_gas) = tx_declaring(&format!(
+            "(memory (;0;) {})",
+            memory::TX_MEMORY_INIT_PAGES
+        ));
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+        // Keep the number of pages below 128 so that it's encoded in a single
+        // byte and the codes have the same length, hence the same validation
+        // and compilation costs
+        let extra_pages = 27;
+        let (result, large_gas) = tx_declaring(&format!(
+            "(memory (;0;) {})",
+            memory::TX_MEMORY_INIT_PAGES + extra_pages
+        ));
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+
+        assert_eq!(small_gas, init_gas);
+        assert_eq!(
+            large_gas.checked_sub(init_gas).unwrap(),
+            Gas::from(u64::from(extra_pages) * u64::from(WASM_MEMORY_PAGE_GAS))
+        );
+    }
+
+    /// Test that the code referenced by several txs of a block is only read
+    /// once, that the same gas is charged for it to each tx, and that a tx
+    /// referencing missing code fails without affecting the others.
+    #[test]
+    fn test_tx_code_loaded_once() {
+        let tx_code = TestWasms::TxNoOp.read_bytes();
+        let tx_index = TxIndex::default();
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+
+        // store the tx code
+        let code_hash = Hash::sha256(&tx_code);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
+        storage.write(&Key::wasm_code(&code_hash), tx_code).unwrap();
+        storage
+            .write(&Key::wasm_code_len(&code_hash), code_len)
+            .unwrap();
+        let missing_hash = Hash::sha256(b"missing");
+        let code_loader = tx_cache.code_loader().clone();
+
+        let mut run_tx = |code_hash: Hash| {
+            let mut gas_meter =
+                TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+            let mut outer_tx = Tx::from_type(TxType::Raw);
+            outer_tx.set_code(Code::from_hash(code_hash, None));
+            outer_tx.set_data(Data::new(vec![]));
+            tx(
+                &storage,
+                &mut write_log,
+                &mut gas_meter,
+                &tx_index,
+                &outer_tx,
+                None,
+                &mut vp_cache,
+                &mut tx_cache,
+            )
+            .map(|_| gas_meter.get_tx_consumed_gas())
+        };
+        let first_gas = run_tx(code_hash).expect("unexpected error");
+        assert_eq!(run_tx(code_hash).expect("unexpected error"), first_gas);
+        assert!(matches!(run_tx(missing_hash), Err(Error::LoadWasmCode(_))));
+        assert_eq!(run_tx(code_hash).expect("unexpected error"), first_gas);
+        // The compiled module outlives the block
+        code_loader.clear();
+        assert_eq!(run_tx(code_hash).expect("unexpected error"), first_gas);
+
+        assert_eq!(code_loader.code_reads(), 1);
+    }
+
+    /// Run a no-op transaction wasm passed in the tx with the given memory,
+    /// table and global declarations. Returns the result of the execution and
+    /// the gas used.
+    fn tx_declaring(resources: &str) -> (Result<BTreeSet<Address>>, Gas) {
+        let tx_code = wasmer::wat2wasm(
+            format!(
+                r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                {}
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#,
+                resources
+            )
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let tx_index = TxIndex::default();
+        let storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::new(tx_code, None));
+        outer_tx.set_data(Data::new(vec![]));
+
+        let result = tx(
+            &storage,
+            &mut write_log,
+            &mut gas_meter,
+            &tx_index,
+            &outer_tx,
+            None,
+            &mut vp_cache,
+            &mut tx_cache,
+        );
+        (result, gas_meter.get_tx_consumed_gas())
+    }
+
     fn loop_in_tx_wasm(loops: u32) -> Result<BTreeSet<Address>> {
         // A transaction with a recursive loop.
         // The boilerplate code is generated from tx_template.wasm using
@@ -1310,6 +1496,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         )
@@ -1367,6 +1554,7 @@ mod tests {
             code_hash,
             &outer_tx,
             &tx_index,
+            None,
             &addr,
             &storage,
             &write_log,
@@ -1374,6 +1562,8 @@ mod tests {
             &keys_changed,
             &verifiers,
             vp_cache,
+            #[cfg(not(feature = "mainnet"))]
+            false,
         )
     }
 
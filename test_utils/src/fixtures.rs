@@ -0,0 +1,203 @@
+//! Deterministic keys and transactions for tests.
+//!
+//! A [`TestFixtures`] factory derives everything it makes from its seed:
+//! keypairs, section salts and tx timestamps. Two factories created from the
+//! same seed and driven through the same calls thus build identical tx bytes,
+//! so that the failures of tests using them are reproducible and their
+//! hashes can be asserted.
+
+use namada_core::proto::{Code, Commitment, Data, Section, Signature, Tx};
+use namada_core::types::chain::ChainId;
+use namada_core::types::key::{common, ed25519, secp256k1, RefTo, SigScheme};
+use namada_core::types::storage::Epoch;
+use namada_core::types::time::DateTimeUtc;
+use namada_core::types::transaction::{
+    DecryptedTx, Fee, GasLimit, TxType, WrapperTx,
+};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// The timestamp of the first tx made by a factory, as milliseconds since
+/// the Unix epoch
+const FIRST_TIMESTAMP_MILLIS: i64 = 1_700_000_000_000;
+
+/// A factory of deterministic keypairs and txs, seeded for reproducibility
+pub struct TestFixtures {
+    rng: StdRng,
+    /// The number of timestamps given out so far
+    ticks: i64,
+}
+
+impl TestFixtures {
+    /// Create a factory whose fixtures are all derived from the given seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            ticks: 0,
+        }
+    }
+
+    /// Generate the next keypair of the given scheme
+    pub fn keypair<S: SigScheme>(&mut self) -> S::SecretKey {
+        S::generate(&mut self.rng)
+    }
+
+    /// Generate the next ed25519 keypair
+    pub fn ed25519_keypair(&mut self) -> common::SecretKey {
+        common::SecretKey::Ed25519(self.keypair::<ed25519::SigScheme>())
+    }
+
+    /// Generate the next secp256k1 keypair
+    pub fn secp256k1_keypair(&mut self) -> common::SecretKey {
+        common::SecretKey::Secp256k1(self.keypair::<secp256k1::SigScheme>())
+    }
+
+    /// Generate the next BLS keypair
+    #[cfg(feature = "bls")]
+    pub fn bls_keypair(&mut self) -> common::SecretKey {
+        use namada_core::types::key::bls;
+
+        common::SecretKey::Bls(self.keypair::<bls::SigScheme>())
+    }
+
+    /// Generate the next section salt
+    pub fn salt(&mut self) -> [u8; 8] {
+        let mut salt = [0; 8];
+        self.rng.fill_bytes(&mut salt);
+        salt
+    }
+
+    /// The next tx timestamp. Timestamps start from a fixed date and advance
+    /// by one second every call.
+    pub fn timestamp(&mut self) -> DateTimeUtc {
+        let millis = FIRST_TIMESTAMP_MILLIS + self.ticks * 1_000;
+        self.ticks += 1;
+        DateTimeUtc::from_unix_millis(millis)
+            .expect("The fixture timestamps must be valid")
+    }
+
+    /// Make a code section with the given bytes and a fixture salt
+    pub fn code(&mut self, code: impl Into<Vec<u8>>) -> Code {
+        let code: Vec<u8> = code.into();
+        Code {
+            salt: self.salt(),
+            code: Commitment::Id(code.into()),
+            tag: None,
+        }
+    }
+
+    /// Make a data section with the given bytes and a fixture salt
+    pub fn data(&mut self, data: impl Into<Vec<u8>>) -> Data {
+        let data: Vec<u8> = data.into();
+        Data {
+            salt: self.salt(),
+            data: data.into(),
+        }
+    }
+
+    /// Make an unsigned tx of the given type on the given chain, with the
+    /// given code and data and a fixture timestamp
+    pub fn tx(
+        &mut self,
+        tx_type: TxType,
+        chain_id: ChainId,
+        code: impl Into<Vec<u8>>,
+        data: impl Into<Vec<u8>>,
+    ) -> Tx {
+        let mut tx = Tx::from_type(tx_type);
+        tx.header.chain_id = chain_id;
+        tx.header.timestamp = self.timestamp();
+        tx.set_code(self.code(code));
+        tx.set_data(self.data(data));
+        tx
+    }
+
+    /// Make a wrapper tx of the given code and data, signed by the given fee
+    /// payer
+    pub fn wrapper_tx(
+        &mut self,
+        chain_id: ChainId,
+        fee: Fee,
+        fee_payer: &common::SecretKey,
+        gas_limit: GasLimit,
+        code: impl Into<Vec<u8>>,
+        data: impl Into<Vec<u8>>,
+    ) -> Tx {
+        let wrapper =
+            WrapperTx::new(fee, fee_payer.ref_to(), Epoch(0), gas_limit, None);
+        let mut tx =
+            self.tx(TxType::Wrapper(Box::new(wrapper)), chain_id, code, data);
+        tx.add_section(Section::Signature(Signature::new(
+            tx.sechashes(),
+            [(0, fee_payer.clone())].into_iter().collect(),
+            None,
+        )));
+        tx
+    }
+
+    /// Make a wrapper tx as with [`TestFixtures::wrapper_tx`], along with the
+    /// decrypted tx that it is expected to turn into in the next block
+    pub fn wrapper_and_decrypted_txs(
+        &mut self,
+        chain_id: ChainId,
+        fee: Fee,
+        fee_payer: &common::SecretKey,
+        gas_limit: GasLimit,
+        code: impl Into<Vec<u8>>,
+        data: impl Into<Vec<u8>>,
+    ) -> (Tx, Tx) {
+        let wrapper =
+            self.wrapper_tx(chain_id, fee, fee_payer, gas_limit, code, data);
+        let mut decrypted = wrapper.clone();
+        decrypted.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        (wrapper, decrypted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::types::address::nam;
+    use namada_core::types::token::Amount;
+
+    use super::*;
+
+    /// Build a few fixtures from the given seed
+    fn build(seed: u64) -> (Vec<common::PublicKey>, Tx, Tx) {
+        let mut fixtures = TestFixtures::new(seed);
+        let keys = vec![
+            fixtures.ed25519_keypair().ref_to(),
+            fixtures.secp256k1_keypair().ref_to(),
+        ];
+        let fee_payer = fixtures.ed25519_keypair();
+        let (wrapper, decrypted) = fixtures.wrapper_and_decrypted_txs(
+            ChainId::default(),
+            Fee {
+                amount_per_gas_unit: Amount::from(1),
+                token: nam(),
+            },
+            &fee_payer,
+            GasLimit::from(100_000),
+            "wasm_code",
+            "transaction data",
+        );
+        (keys, wrapper, decrypted)
+    }
+
+    /// Check that the fixtures built from a seed are the same every time, and
+    /// differ from those of another seed.
+    #[test]
+    fn test_fixtures_are_deterministic() {
+        let (keys, wrapper, decrypted) = build(0);
+        let (same_keys, same_wrapper, same_decrypted) = build(0);
+        assert_eq!(keys, same_keys);
+        assert_eq!(wrapper.to_bytes(), same_wrapper.to_bytes());
+        assert_eq!(wrapper.header_hash(), same_wrapper.header_hash());
+        assert_eq!(decrypted.to_bytes(), same_decrypted.to_bytes());
+        assert_eq!(decrypted.header_hash(), same_decrypted.header_hash());
+        assert_eq!(wrapper.raw_header_hash(), decrypted.raw_header_hash());
+
+        let (other_keys, other_wrapper, _) = build(1);
+        assert_ne!(keys, other_keys);
+        assert_ne!(wrapper.header_hash(), other_wrapper.header_hash());
+    }
+}
@@ -1,5 +1,6 @@
 //! Utilities for use in tests.
 
+pub mod fixtures;
 pub mod tx_data;
 
 use std::env;
@@ -0,0 +1,93 @@
+use color_eyre::eyre::Result;
+use namada_apps::node::ledger::shell::testing::client::run;
+use namada_apps::node::ledger::shell::testing::node::MockNode;
+use namada_apps::node::ledger::shell::testing::utils::{Bin, CapturedOutput};
+use test_log::test;
+
+use super::setup;
+use crate::e2e::setup::constants::{ALBERT_KEY, BERTHA_KEY, BTC};
+
+/// In this test we verify that the balance changes predicted by a dry run
+/// of a transfer match the balances produced by applying it.
+#[test]
+fn dry_run_predicts_transfer_balances() -> Result<()> {
+    // This address doesn't matter for tests. But an argument is required.
+    let validator_one_rpc = "127.0.0.1:26567";
+    let (node, _services) = setup::setup()?;
+    let transfer = vec![
+        "transfer",
+        "--source",
+        ALBERT_KEY,
+        "--target",
+        BERTHA_KEY,
+        "--token",
+        BTC,
+        "--amount",
+        "10.1",
+        "--node",
+        validator_one_rpc,
+    ];
+
+    let albert_before = query_btc_balance(&node, ALBERT_KEY, validator_one_rpc);
+    let bertha_before = query_btc_balance(&node, BERTHA_KEY, validator_one_rpc);
+
+    // Simulate the transfer
+    let captured = CapturedOutput::of(|| {
+        run(
+            &node,
+            Bin::Client,
+            vec![transfer.clone(), vec!["--dry-run"]].concat(),
+        )
+    });
+    assert!(captured.result.is_ok());
+    assert!(captured.contains("Transaction is valid"));
+    assert!(captured.contains("Predicted storage changes"));
+
+    // The dry run must not have changed anything
+    assert_eq!(
+        query_btc_balance(&node, ALBERT_KEY, validator_one_rpc),
+        albert_before
+    );
+
+    // Apply the transfer for real. The fees are paid in NAM, so they don't
+    // affect the BTC balances.
+    run(&node, Bin::Client, transfer)?;
+    node.assert_success();
+
+    let albert_after = query_btc_balance(&node, ALBERT_KEY, validator_one_rpc);
+    let bertha_after = query_btc_balance(&node, BERTHA_KEY, validator_one_rpc);
+    assert_ne!(albert_before, albert_after);
+    assert_ne!(bertha_before, bertha_after);
+
+    // The predicted balances must match the applied ones
+    for (before, after) in
+        [(albert_before, albert_after), (bertha_before, bertha_after)]
+    {
+        let needle = format!("{before} -> {after}");
+        assert!(
+            captured.output.contains(&needle),
+            "Missing predicted change '{needle}' in dry run output:\n{}",
+            captured.output
+        );
+    }
+
+    Ok(())
+}
+
+/// Query the BTC balance of the given owner
+fn query_btc_balance(node: &MockNode, owner: &str, rpc: &str) -> String {
+    let captured = CapturedOutput::of(|| {
+        run(
+            node,
+            Bin::Client,
+            vec!["balance", "--owner", owner, "--token", BTC, "--node", rpc],
+        )
+    });
+    assert!(captured.result.is_ok());
+    captured
+        .output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("btc: "))
+        .expect("The BTC balance should be displayed")
+        .to_string()
+}
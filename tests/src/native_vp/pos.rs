@@ -98,7 +98,11 @@
 use namada::proof_of_stake::parameters::{OwnedPosParams, PosParams};
 use namada::proof_of_stake::test_utils::test_init_genesis as init_genesis;
 use namada::proof_of_stake::types::GenesisValidator;
+use namada::types::address::{self, Address};
+use namada::types::dec::Dec;
+use namada::types::key::{self, RefTo};
 use namada::types::storage::Epoch;
+use namada::types::token;
 
 use crate::tx::tx_host_env;
 
@@ -144,6 +148,41 @@ pub fn init_pos(
     })
 }
 
+/// A genesis validator with the given address and consensus key, a stake of
+/// 1000 native tokens, a commission rate of 5% and a maximum commission rate
+/// change of 1%.
+pub fn genesis_validator(
+    address: Address,
+    consensus_key: key::common::PublicKey,
+) -> GenesisValidator {
+    GenesisValidator {
+        address,
+        tokens: token::Amount::native_whole(1_000),
+        consensus_key,
+        protocol_key: key::testing::keypair_2().ref_to(),
+        eth_cold_key: key::testing::keypair_3().ref_to(),
+        eth_hot_key: key::testing::keypair_4().ref_to(),
+        commission_rate: Dec::new(5, 2).expect("Cannot fail"),
+        max_commission_rate_change: Dec::new(1, 2).expect("Cannot fail"),
+        metadata: Default::default(),
+    }
+}
+
+/// initialize proof-of-stake genesis with default parameters and a single
+/// [`genesis_validator`]. Returns the validator and the parameters.
+pub fn init_pos_with_validator() -> (Address, PosParams) {
+    let validator = address::testing::established_address_1();
+    let pos_params = init_pos(
+        &[genesis_validator(
+            validator.clone(),
+            key::testing::keypair_1().ref_to(),
+        )],
+        &Default::default(),
+        Epoch(0),
+    );
+    (validator, pos_params)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -24,20 +24,31 @@ mod tests {
     use borsh_ext::BorshSerializeExt;
     use itertools::Itertools;
     use namada::ibc::core::Msg;
+    use namada::ledger::gas::{
+        GasMetering, TxGasMeter, MEMORY_ACCESS_GAS_PER_BYTE,
+        TX_SECTION_ACCESS_GAS,
+    };
     use namada::ledger::ibc::storage as ibc_storage;
     use namada::ledger::native_vp::ibc::{
         get_dummy_header as tm_dummy_header, Error as IbcError,
     };
     use namada::ledger::tx_env::TxEnv;
-    use namada::proto::Tx;
+    use namada::proto::{Header, Section, Tx};
     use namada::types::hash::Hash;
     use namada::types::key::*;
-    use namada::types::storage::{self, BlockHash, BlockHeight, Key, KeySeg};
+    use namada::types::storage::{
+        self, BlockHash, BlockHeight, Epoch, Key, KeySeg,
+    };
     use namada::types::time::DateTimeUtc;
     use namada::types::token::{self, Amount};
+    use namada::types::transaction::{
+        Fee, GasLimit, TxSentinel, TxType, TxWasmError, WrapperTx,
+    };
     use namada::types::{address, key};
     use namada_core::ledger::ibc::context::transfer_mod::testing::DummyTransferModule;
-    use namada_core::ledger::ibc::Error as IbcActionError;
+    use namada_core::ledger::ibc::{
+        add_msg_sections, resolve_msg_sections, Error as IbcActionError,
+    };
     use namada_test_utils::TestWasms;
     use namada_tx_prelude::address::InternalAddress;
     use namada_tx_prelude::chain::ChainId;
@@ -231,6 +242,396 @@ mod tests {
         tx::ctx().init_account(code_hash, &None).unwrap();
     }
 
+    #[test]
+    fn test_tx_init_account_validates_code() {
+        // The environment must be initialized first
+        let mut env = TestTxEnv::default();
+        // Some code which is not wasm at all
+        let text_code = "not a validity predicate".as_bytes().to_vec();
+        let text_hash = Hash::sha256(&text_code);
+        env.store_wasm_code(text_code);
+        // Some wasm code which doesn't export the VP entrypoint
+        let tx_code = TestWasms::TxNoOp.read_bytes();
+        let tx_hash = Hash::sha256(&tx_code);
+        env.store_wasm_code(tx_code);
+        tx_host_env::set(env);
+
+        // Initializing an account with invalid code should fail
+        for invalid_hash in [text_hash, tx_hash] {
+            assert!(
+                panic::catch_unwind(|| {
+                    tx::ctx().init_account(invalid_hash, &None).unwrap()
+                })
+                .err()
+                .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+                .unwrap()
+                .contains("InvalidVpCode")
+            );
+        }
+        assert!(tx_host_env::with(|env| env
+            .wl_storage
+            .write_log
+            .get_initialized_accounts()
+            .is_empty()));
+    }
+
+    #[test]
+    fn test_tx_update_vp_validates_code() {
+        // The environment must be initialized first
+        let mut env = TestTxEnv::default();
+        let test_account = address::testing::established_address_1();
+        env.spawn_accounts([&test_account]);
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let vp_hash = Hash::sha256(&vp_code);
+        env.store_wasm_code(vp_code);
+        // Some code which is not wasm at all
+        let text_code = "not a validity predicate".as_bytes().to_vec();
+        let text_hash = Hash::sha256(&text_code);
+        env.store_wasm_code(text_code);
+        // Some wasm code which doesn't export the VP entrypoint
+        let tx_code = TestWasms::TxNoOp.read_bytes();
+        let tx_hash = Hash::sha256(&tx_code);
+        env.store_wasm_code(tx_code);
+        tx_host_env::set(env);
+
+        let vp_key = storage::Key::validity_predicate(&test_account);
+        let initial_vp = tx::ctx().read_bytes(&vp_key).unwrap();
+
+        // Installing invalid code should fail and leave the VP untouched
+        for invalid_hash in [text_hash, tx_hash] {
+            assert!(
+                panic::catch_unwind(|| {
+                    tx::ctx()
+                        .update_validity_predicate(
+                            &test_account,
+                            invalid_hash,
+                            &None,
+                        )
+                        .unwrap()
+                })
+                .err()
+                .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+                .unwrap()
+                .contains("InvalidVpCode")
+            );
+            assert_eq!(tx::ctx().read_bytes(&vp_key).unwrap(), initial_vp);
+        }
+
+        // Installing a valid VP should succeed
+        tx::ctx()
+            .update_validity_predicate(&test_account, vp_hash, &None)
+            .unwrap();
+        assert_eq!(
+            tx::ctx().read_bytes(&vp_key).unwrap(),
+            Some(vp_hash.0.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_tx_verify_signatures_of_pks() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Install a tx with some arbitrary code and data, signed by a key
+        let keypair = key::testing::keypair_1();
+        let signed_tx = tx_host_env::with(|env| {
+            env.set_tx_code_and_data(vec![4, 3, 2, 1, 0], vec![1, 2, 3, 4]);
+            env.sign_tx(vec![keypair.clone()], None);
+            env.tx.clone()
+        });
+        assert!(
+            tx_host_env::verify_signatures_of_pks(
+                tx::ctx(),
+                &signed_tx,
+                vec![keypair.ref_to()]
+            )
+            .unwrap()
+        );
+
+        // The signature must not be accepted for another key
+        assert!(
+            !tx_host_env::verify_signatures_of_pks(
+                tx::ctx(),
+                &signed_tx,
+                vec![key::testing::keypair_2().ref_to()]
+            )
+            .unwrap()
+        );
+
+        // The signature must not be accepted when the tx has been modified
+        // after it was signed
+        let mut modified_tx = signed_tx;
+        modified_tx.add_serialized_data(vec![5, 6, 7, 8]);
+        tx_host_env::set_tx(modified_tx.clone());
+        assert!(
+            !tx_host_env::verify_signatures_of_pks(
+                tx::ctx(),
+                &modified_tx,
+                vec![keypair.ref_to()]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tx_verify_section_signature() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let keypair_1 = key::testing::keypair_1();
+        let keypair_2 = key::testing::keypair_2();
+        let pks = [
+            keypair_1.ref_to(),
+            keypair_2.ref_to(),
+            key::testing::keypair_3().ref_to(),
+        ];
+
+        // Install a tx signed by the first two keys
+        let signed_tx = tx_host_env::with(|env| {
+            env.set_tx_code_and_data(vec![], vec![1, 2, 3]);
+            env.sign_tx(vec![keypair_1.clone(), keypair_2.clone()], None);
+            env.tx.clone()
+        });
+        let target = signed_tx.raw_header_hash();
+
+        // Valid signatures by the signing keys
+        assert!(
+            tx::ctx()
+                .verify_section_signature(&pks[0], &target)
+                .unwrap()
+        );
+        assert!(
+            tx::ctx()
+                .verify_section_signature(&pks[1], &target)
+                .unwrap()
+        );
+        // No signature by a key that didn't sign
+        assert!(
+            !tx::ctx()
+                .verify_section_signature(&pks[2], &target)
+                .unwrap()
+        );
+        // No signature over a section that isn't a target
+        let data_hash = *signed_tx.data_sechash();
+        assert!(
+            !tx::ctx()
+                .verify_section_signature(&pks[0], &data_hash)
+                .unwrap()
+        );
+
+        // The threshold is met by the two signing keys, but not more
+        assert!(
+            tx::ctx()
+                .verify_section_signatures(&pks, 2, &target)
+                .unwrap()
+        );
+        assert!(
+            !tx::ctx()
+                .verify_section_signatures(&pks, 3, &target)
+                .unwrap()
+        );
+
+        // Invalid signatures are not accepted
+        tx_host_env::with(|env| swap_signatures(&mut env.tx));
+        assert!(
+            !tx::ctx()
+                .verify_section_signature(&pks[0], &target)
+                .unwrap()
+        );
+        assert!(
+            !tx::ctx()
+                .verify_section_signatures(&pks, 2, &target)
+                .unwrap()
+        );
+    }
+
+    /// Swap the first two signatures of every signature section of the tx to
+    /// invalidate them
+    fn swap_signatures(tx: &mut Tx) {
+        for section in &mut tx.sections {
+            if let Section::Signature(sig) = section {
+                let first = sig.signatures[&0].clone();
+                let second = sig.signatures.insert(1, first).unwrap();
+                sig.signatures.insert(0, second);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tx_get_section() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Install a tx with an extra data section
+        let extra_data = vec![1_u8; 1024];
+        let mut tx = Tx::new(ChainId::default(), None);
+        let extra_hash = tx.add_extra_section(extra_data.clone(), None).1;
+        tx.add_code(vec![], None)
+            .add_serialized_data(vec![1, 2, 3, 4]);
+        tx_host_env::set_tx(tx.clone());
+
+        // Fetch the extra data section
+        let gas_pre =
+            tx_host_env::with(|env| env.gas_meter.get_tx_consumed_gas());
+        let section = tx::ctx().get_tx_section(&extra_hash).unwrap();
+        let gas_post =
+            tx_host_env::with(|env| env.gas_meter.get_tx_consumed_gas());
+        assert_eq!(section.get_hash(), extra_hash);
+        assert_eq!(section.extra_data(), Some(extra_data));
+
+        // The returned section is charged for per byte
+        let section_len = section.serialize_to_vec().len() as u64;
+        let gas_used = u64::from(gas_post.checked_sub(gas_pre).unwrap());
+        assert!(
+            gas_used
+                >= TX_SECTION_ACCESS_GAS
+                    + section_len * MEMORY_ACCESS_GAS_PER_BYTE
+        );
+
+        // Fetch the data section by the hash committed in the header
+        let section = tx::ctx().get_tx_section(tx.data_sechash()).unwrap();
+        assert_eq!(section.get_hash(), *tx.data_sechash());
+        assert_eq!(section.data().unwrap().data, vec![1, 2, 3, 4]);
+
+        // A section that isn't in the tx can't be found
+        assert!(
+            tx::ctx()
+                .get_tx_section(&Hash::sha256(b"missing section"))
+                .is_none()
+        );
+    }
+
+    /// Call the host function fetching the section with the given hash of the
+    /// tx of the environment, under the given gas limit. Returns the result
+    /// of the call, whether it ran out of gas and the gas it consumed.
+    fn tx_get_section_with_gas_limit(
+        hash: &Hash,
+        gas_limit: u64,
+    ) -> (namada::vm::host_env::TxResult<i64>, bool, u64) {
+        tx_host_env::with(|env| {
+            env.gas_meter = TxGasMeter::new_from_sub_limit(gas_limit.into());
+            env.sentinel = Default::default();
+            let TestTxEnv {
+                wl_storage,
+                iterators,
+                verifiers,
+                gas_meter,
+                sentinel,
+                result_buffer,
+                tx_index,
+                wrapper,
+                vp_wasm_cache,
+                tx_wasm_cache,
+                tx,
+                ..
+            } = env;
+            let tx_env = namada::vm::host_env::testing::tx_env(
+                &wl_storage.storage,
+                &mut wl_storage.write_log,
+                iterators,
+                verifiers,
+                gas_meter,
+                sentinel,
+                tx,
+                tx_index,
+                wrapper,
+                result_buffer,
+                vp_wasm_cache,
+                tx_wasm_cache,
+            );
+            let result = namada::vm::host_env::tx_get_section(
+                &tx_env,
+                hash.as_ptr() as _,
+                hash.len() as _,
+            );
+            (
+                result,
+                matches!(sentinel, TxSentinel::OutOfGas),
+                u64::from(gas_meter.get_tx_consumed_gas()),
+            )
+        })
+    }
+
+    #[test]
+    fn test_tx_get_section_gas() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Install a tx with a large extra data section
+        let extra_data = vec![1_u8; 1024 * 1024];
+        let mut tx = Tx::new(ChainId::default(), None);
+        let extra_hash = tx.add_extra_section(extra_data, None).1;
+        tx.add_code(vec![], None).add_serialized_data(vec![]);
+        let section_len = tx
+            .get_section(&extra_hash)
+            .unwrap()
+            .serialize_to_vec()
+            .len() as u64;
+        tx_host_env::set_tx(tx);
+
+        // The gas of a call is fixed, on top of the memory accesses to the
+        // hash and the returned section
+        let expected_gas = TX_SECTION_ACCESS_GAS
+            + (extra_hash.len() as u64 + section_len)
+                * MEMORY_ACCESS_GAS_PER_BYTE;
+
+        // Under a generous limit, the section is returned and charged for
+        let (result, out_of_gas, gas_used) =
+            tx_get_section_with_gas_limit(&extra_hash, 10 * expected_gas);
+        assert_eq!(result.unwrap(), section_len as i64);
+        assert!(!out_of_gas);
+        assert_eq!(gas_used, expected_gas);
+
+        // Under a limit too tight to return the section, the call aborts out
+        // of gas, the same way every time
+        for _ in 0..2 {
+            let (result, out_of_gas, _) =
+                tx_get_section_with_gas_limit(&extra_hash, expected_gas - 1);
+            assert!(matches!(
+                result,
+                Err(namada::vm::host_env::TxRuntimeError::OutOfGas(_))
+            ));
+            assert!(out_of_gas);
+        }
+
+        // A missing section is charged the fixed cost of the call too
+        let missing_hash = Hash::sha256(b"missing section");
+        let (result, out_of_gas, gas_used) =
+            tx_get_section_with_gas_limit(&missing_hash, expected_gas);
+        assert_eq!(result.unwrap(), -1);
+        assert!(!out_of_gas);
+        assert_eq!(
+            gas_used,
+            TX_SECTION_ACCESS_GAS
+                + missing_hash.len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE
+        );
+    }
+
+    #[test]
+    fn test_tx_set_error() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // The error is passed on to the protocol via the sentinel
+        let error = TxWasmError::MissingSection(Hash::sha256(b"section"));
+        tx::ctx().set_error(&error);
+        tx_host_env::with(|env| {
+            assert!(matches!(
+                &env.sentinel,
+                TxSentinel::Aborted(aborted) if *aborted == error
+            ))
+        });
+
+        // An error doesn't override an invalid section commitment, which
+        // requires special handling by the protocol
+        tx_host_env::init();
+        tx::ctx().set_commitment_sentinel();
+        tx::ctx().set_error(&TxWasmError::InvalidData);
+        tx_host_env::with(|env| {
+            assert!(matches!(env.sentinel, TxSentinel::InvalidCommitment))
+        });
+    }
+
     #[test]
     fn test_tx_get_metadata() {
         // The environment must be initialized first
@@ -270,6 +671,56 @@ mod tests {
         );
     }
 
+    /// Build the header of a wrapper with the given fee, gas limit and
+    /// expiration.
+    fn wrapper_header(
+        fee: Fee,
+        gas_limit: GasLimit,
+        expiration: DateTimeUtc,
+    ) -> Header {
+        let wrapper = WrapperTx::new(
+            fee,
+            key::testing::keypair_1().ref_to(),
+            Epoch(0),
+            gas_limit,
+            None,
+        );
+        let mut header = Header::new(TxType::Wrapper(Box::new(wrapper)));
+        header.expiration = Some(expiration);
+        header
+    }
+
+    #[test]
+    fn test_tx_get_wrapper_metadata() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // A tx that wasn't carried by a wrapper has no wrapper metadata
+        assert_eq!(tx::ctx().get_tx_fee(), None);
+        assert_eq!(tx::ctx().get_gas_limit(), None);
+        assert_eq!(tx::ctx().get_tx_expiration(), None);
+
+        // Execute the tx on behalf of a wrapper
+        let fee = Fee {
+            amount_per_gas_unit: Amount::from_uint(10, 0).unwrap(),
+            token: address::nam(),
+        };
+        let gas_limit = GasLimit::from(20_000);
+        let expiration = DateTimeUtc::now();
+        tx_host_env::with(|env| {
+            env.wrapper =
+                Some(wrapper_header(fee.clone(), gas_limit, expiration))
+        });
+        assert_eq!(tx::ctx().get_tx_fee(), Some(fee));
+        assert_eq!(tx::ctx().get_gas_limit(), Some(gas_limit));
+        assert_eq!(tx::ctx().get_tx_expiration(), Some(expiration));
+        // The chain ID is the one of the chain executing the tx either way
+        assert_eq!(
+            tx::ctx().get_chain_id().unwrap(),
+            tx_host_env::with(|env| env.wl_storage.storage.get_chain_id().0)
+        );
+    }
+
     /// An example how to write a VP host environment integration test
     #[test]
     fn test_vp_host_env() {
@@ -443,64 +894,170 @@ mod tests {
         let keypair = key::testing::keypair_1();
         let pk = keypair.ref_to();
 
-        let _ = pks_handle(&addr).insert(&mut env.wl_storage, 0_u8, pk.clone());
+        let _ = pks_handle(&addr).insert(&mut env.wl_storage, 0_u8, pk);
+        // Commit the key, so that it's found in the prior state
+        env.wl_storage.commit_tx();
+        env.wl_storage.commit_block().unwrap();
 
         // Initialize the environment
         vp_host_env::set(env);
 
         // Use some arbitrary bytes for tx code
         let code = vec![4, 3, 2, 1, 0];
-        let expiration = Some(DateTimeUtc::now());
-        for data in &[
+        for data in [
             // Tx with some arbitrary data
             vec![1, 2, 3, 4].repeat(10),
             // Tx without any data
             vec![],
         ] {
-            let keypairs = vec![keypair.clone()];
-            let pks_map = AccountPublicKeysMap::from_iter(vec![pk.clone()]);
-            let signed_tx_data = vp_host_env::with(|env| {
-                let chain_id = env.wl_storage.storage.chain_id.clone();
-                let mut tx = Tx::new(chain_id, expiration);
-                tx.add_code(code.clone(), None)
-                    .add_serialized_data(data.to_vec())
-                    .sign_raw(keypairs.clone(), pks_map.clone(), None)
-                    .sign_wrapper(keypair.clone());
-                env.tx = tx;
+            // Install a tx signed on behalf of the account
+            let signed_tx = vp_host_env::with(|env| {
+                env.set_tx_code_and_data(code.clone(), data.clone());
+                env.sign_tx(vec![keypair.clone()], Some(addr.clone()));
                 env.tx.clone()
             });
-            assert_eq!(signed_tx_data.data().as_ref(), Some(data));
+            assert_eq!(signed_tx.data(), Some(data.clone()));
             assert!(
-                signed_tx_data
-                    .verify_signatures(
-                        &[signed_tx_data.header_hash(),],
-                        pks_map,
-                        &None,
-                        1,
-                        None,
-                        || Ok(())
-                    )
-                    .is_ok()
+                vp_host_env::verify_signatures(vp::ctx(), &signed_tx, &addr)
+                    .unwrap()
             );
 
+            // Install a tx signed by a key that doesn't belong to the account
             let other_keypair = key::testing::keypair_2();
+            let signed_tx = vp_host_env::with(|env| {
+                env.set_tx_code_and_data(code.clone(), data.clone());
+                env.sign_tx(vec![other_keypair.clone()], None);
+                env.tx.clone()
+            });
             assert!(
-                signed_tx_data
-                    .verify_signatures(
-                        &[signed_tx_data.header_hash(),],
-                        AccountPublicKeysMap::from_iter([
-                            other_keypair.ref_to()
-                        ]),
-                        &None,
-                        1,
-                        None,
-                        || Ok(())
-                    )
-                    .is_err()
+                !vp_host_env::verify_signatures(vp::ctx(), &signed_tx, &addr)
+                    .unwrap()
             );
         }
     }
 
+    #[test]
+    fn test_vp_get_tx_signers() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        let required_keypair = key::testing::keypair_1();
+        let other_keypair = key::testing::keypair_2();
+        // A VP that accepts a tx only if it's signed by the required key
+        let validate_tx = || {
+            vp::CTX
+                .get_tx_signers()
+                .unwrap()
+                .contains(&required_keypair.ref_to())
+        };
+
+        // A tx signed by the required key is accepted
+        vp_host_env::with(|env| {
+            env.set_tx_code_and_data(vec![], vec![1, 2, 3]);
+            env.sign_tx(
+                vec![required_keypair.clone(), other_keypair.clone()],
+                None,
+            );
+        });
+        assert_eq!(
+            vp::CTX.get_tx_signers().unwrap(),
+            BTreeSet::from([required_keypair.ref_to(), other_keypair.ref_to()])
+        );
+        assert!(validate_tx());
+
+        // A tx signed only by another key is rejected
+        vp_host_env::with(|env| {
+            env.set_tx_code_and_data(vec![], vec![1, 2, 3]);
+            env.sign_tx(vec![other_keypair.clone()], None);
+        });
+        assert_eq!(
+            vp::CTX.get_tx_signers().unwrap(),
+            BTreeSet::from([other_keypair.ref_to()])
+        );
+        assert!(!validate_tx());
+
+        // Corrupted signatures don't contribute any signers
+        vp_host_env::with(|env| {
+            env.set_tx_code_and_data(vec![], vec![1, 2, 3]);
+            env.sign_tx(
+                vec![required_keypair.clone(), other_keypair.clone()],
+                None,
+            );
+            swap_signatures(&mut env.tx);
+        });
+        assert!(vp::CTX.get_tx_signers().unwrap().is_empty());
+        assert!(!validate_tx());
+    }
+
+    #[test]
+    fn test_vp_verify_section_signature() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        let keypair_1 = key::testing::keypair_1();
+        let keypair_2 = key::testing::keypair_2();
+        let pks = [
+            keypair_1.ref_to(),
+            keypair_2.ref_to(),
+            key::testing::keypair_3().ref_to(),
+        ];
+
+        // Install a tx signed by the first two keys
+        let signed_tx = vp_host_env::with(|env| {
+            env.set_tx_code_and_data(vec![], vec![1, 2, 3]);
+            env.sign_tx(vec![keypair_1.clone(), keypair_2.clone()], None);
+            env.tx.clone()
+        });
+        let target = signed_tx.raw_header_hash();
+
+        // Valid signatures by the signing keys
+        assert!(vp::CTX.verify_section_signature(&pks[0], &target).unwrap());
+        assert!(vp::CTX.verify_section_signature(&pks[1], &target).unwrap());
+        // No signature by a key that didn't sign
+        assert!(!vp::CTX.verify_section_signature(&pks[2], &target).unwrap());
+        // No signature over a section that isn't a target
+        let data_hash = *signed_tx.data_sechash();
+        assert!(
+            !vp::CTX
+                .verify_section_signature(&pks[0], &data_hash)
+                .unwrap()
+        );
+
+        // The threshold is met by the two signing keys, but not more
+        assert!(vp::CTX.verify_section_signatures(&pks, 2, &target).unwrap());
+        assert!(!vp::CTX.verify_section_signatures(&pks, 3, &target).unwrap());
+
+        // Invalid signatures are not accepted
+        vp_host_env::with(|env| swap_signatures(&mut env.tx));
+        assert!(!vp::CTX.verify_section_signature(&pks[0], &target).unwrap());
+        assert!(!vp::CTX.verify_section_signatures(&pks, 2, &target).unwrap());
+    }
+
+    #[test]
+    fn test_vp_get_tx_code_hash() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // Install a tx with some arbitrary bytes for tx code
+        let code = vec![4, 3, 2, 1, 0];
+        vp_host_env::with(|env| {
+            env.set_tx_code_and_data(code.clone(), vec![]);
+        });
+        assert_eq!(
+            vp::CTX.get_tx_code_hash().unwrap(),
+            Some(Hash::sha256(&code))
+        );
+
+        // A tx code given by its hash must give the same hash
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code_from_hash(Hash::sha256(&code), None);
+        vp_host_env::set_tx(tx);
+        assert_eq!(
+            vp::CTX.get_tx_code_hash().unwrap(),
+            Some(Hash::sha256(&code))
+        );
+    }
+
     #[test]
     fn test_vp_get_metadata() {
         // The environment must be initialized first
@@ -540,6 +1097,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vp_get_wrapper_metadata() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // A tx that wasn't carried by a wrapper has no wrapper metadata
+        assert_eq!(vp::CTX.get_tx_fee(), None);
+        assert_eq!(vp::CTX.get_gas_limit(), None);
+        assert_eq!(vp::CTX.get_tx_expiration(), None);
+
+        // Validate the tx on behalf of a wrapper
+        let fee = Fee {
+            amount_per_gas_unit: Amount::from_uint(10, 0).unwrap(),
+            token: address::nam(),
+        };
+        let gas_limit = GasLimit::from(20_000);
+        let expiration = DateTimeUtc::now();
+        vp_host_env::with(|env| {
+            env.wrapper =
+                Some(wrapper_header(fee.clone(), gas_limit, expiration))
+        });
+        assert_eq!(vp::CTX.get_tx_fee(), Some(fee));
+        assert_eq!(vp::CTX.get_gas_limit(), Some(gas_limit));
+        assert_eq!(vp::CTX.get_tx_expiration(), Some(expiration));
+        // The chain ID is the one of the chain executing the tx either way
+        assert_eq!(
+            vp::CTX.get_chain_id().unwrap(),
+            vp_host_env::with(|env| env.wl_storage.storage.get_chain_id().0)
+        );
+    }
+
     #[test]
     fn test_vp_eval() {
         // The environment must be initialized first
@@ -674,6 +1262,74 @@ mod tests {
         assert!(result.expect("validation failed unexpectedly"));
     }
 
+    #[test]
+    fn test_ibc_client_update_with_sections() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        ibc::init_storage();
+        let keypair = key::testing::keypair_1();
+        let keypairs = vec![keypair.clone()];
+        let pks_map = AccountPublicKeysMap::from_iter([
+            key::testing::keypair_1().ref_to(),
+        ]);
+        let (client_id, _client_state, writes) = ibc::prepare_client();
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+        // update the block height for the following client update
+        tx_host_env::with(|env| {
+            env.wl_storage
+                .storage
+                .begin_block(BlockHash::default(), BlockHeight(2))
+                .unwrap();
+            env.wl_storage
+                .storage
+                .set_header(tm_dummy_header())
+                .unwrap();
+        });
+
+        // Start a transaction to update the client with the message split
+        // across extra data sections
+        let msg = ibc::msg_update_client(client_id);
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = add_msg_sections(&mut tx, &msg.to_any())
+            .expect("splitting the message failed");
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.clone())
+            .sign_raw(keypairs.clone(), pks_map.clone(), None)
+            .sign_wrapper(keypair.clone());
+        let msg_data = resolve_msg_sections(&tx, tx_data.clone())
+            .expect("resolving the message failed");
+        // update the client with the message
+        tx_host_env::ibc::ibc_actions(tx::ctx())
+            .execute(&msg_data)
+            .expect("updating a client failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+
+        // The same envelope without its sections can't be validated
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data)
+            .sign_raw(keypairs, pks_map, None)
+            .sign_wrapper(keypair);
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(matches!(
+            result,
+            Err(IbcError::IbcAction(IbcActionError::MissingSection(_)))
+        ));
+    }
+
     #[test]
     fn test_ibc_connection_init_and_open() {
         // The environment must be initialized first
@@ -7,7 +7,7 @@ use namada::ledger::storage::mockdb::MockDB;
 use namada::ledger::storage::testing::TestStorage;
 use namada::ledger::storage::write_log::WriteLog;
 use namada::ledger::storage::{Sha256Hasher, WlStorage};
-use namada::proto::Tx;
+use namada::proto::{Header, Tx};
 use namada::types::address::Address;
 use namada::types::hash::Hash;
 use namada::types::storage::{Key, TxIndex};
@@ -21,7 +21,8 @@ use namada::vm::{self, WasmCacheRwAccess};
 use namada_tx_prelude::borsh_ext::BorshSerializeExt;
 use namada_tx_prelude::transaction::TxSentinel;
 use namada_tx_prelude::{storage_api, Ctx};
-use namada_vp_prelude::key::common;
+use namada_vp_prelude::account::AccountPublicKeysMap;
+use namada_vp_prelude::key::{common, RefTo};
 use tempfile::TempDir;
 
 use crate::vp::TestVpEnv;
@@ -54,6 +55,7 @@ pub struct TestTxEnv {
     pub gas_meter: TxGasMeter,
     pub sentinel: TxSentinel,
     pub tx_index: TxIndex,
+    pub wrapper: Option<Header>,
     pub result_buffer: Option<Vec<u8>>,
     pub vp_wasm_cache: VpCache<WasmCacheRwAccess>,
     pub vp_cache_dir: TempDir,
@@ -79,6 +81,7 @@ impl Default for TestTxEnv {
             gas_meter: TxGasMeter::new_from_sub_limit(100_000_000.into()),
             sentinel: TxSentinel::default(),
             tx_index: TxIndex::default(),
+            wrapper: None,
             verifiers: BTreeSet::default(),
             result_buffer: None,
             vp_wasm_cache,
@@ -224,6 +227,29 @@ impl TestTxEnv {
             .unwrap();
     }
 
+    /// Replace the tx under test with a new tx for the chain of this
+    /// environment with the given code and data sections, like the SDK's tx
+    /// builders do. Returns the tx to allow to add more sections to it.
+    pub fn set_tx_code_and_data(
+        &mut self,
+        code: Vec<u8>,
+        data: Vec<u8>,
+    ) -> &mut Tx {
+        self.tx = new_tx(&self.wl_storage, code, data);
+        &mut self.tx
+    }
+
+    /// Sign the tx under test with the given keys, like the SDK's
+    /// `Tx::sign_raw`. When an `owner` is given, the signatures are made on
+    /// its behalf using the indices of the owner's public keys in storage.
+    pub fn sign_tx(
+        &mut self,
+        keypairs: Vec<common::SecretKey>,
+        owner: Option<Address>,
+    ) {
+        sign_tx(&self.wl_storage, &mut self.tx, keypairs, owner)
+    }
+
     /// Apply the tx changes to the write log.
     pub fn execute_tx(&mut self) -> Result<(), Error> {
         wasm::run::tx(
@@ -232,6 +258,7 @@ impl TestTxEnv {
             &mut self.gas_meter,
             &self.tx_index,
             &self.tx,
+            self.wrapper.as_ref(),
             &mut self.vp_wasm_cache,
             &mut self.tx_wasm_cache,
         )
@@ -239,6 +266,38 @@ impl TestTxEnv {
     }
 }
 
+/// Build a new tx for the chain of the given storage with the given code and
+/// data sections.
+pub(crate) fn new_tx(
+    wl_storage: &WlStorage<MockDB, Sha256Hasher>,
+    code: Vec<u8>,
+    data: Vec<u8>,
+) -> Tx {
+    let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+    tx.add_code(code, None).add_serialized_data(data);
+    tx
+}
+
+/// Sign the tx with the given keys, indexed by the owner's public keys in
+/// the given storage if there's an owner, or by their order otherwise.
+pub(crate) fn sign_tx(
+    wl_storage: &WlStorage<MockDB, Sha256Hasher>,
+    tx: &mut Tx,
+    keypairs: Vec<common::SecretKey>,
+    owner: Option<Address>,
+) {
+    let public_keys_index_map = match &owner {
+        Some(owner) => {
+            storage_api::account::public_keys_index_map(wl_storage, owner)
+                .expect("Unable to read the owner's public keys")
+        }
+        None => AccountPublicKeysMap::from_iter(
+            keypairs.iter().map(|keypair| keypair.ref_to()),
+        ),
+    };
+    tx.sign_raw(keypairs, public_keys_index_map, owner);
+}
+
 /// This module allows to test code with tx host environment functions.
 /// It keeps a thread-local global `TxEnv`, which is passed to any of
 /// invoked host environment functions and so it must be initialized
@@ -312,6 +371,13 @@ mod native_tx_host_env {
         with(|env| env.commit_tx_and_block())
     }
 
+    /// Install the given tx as the tx under test in [`ENV`], so that the host
+    /// env functions answer from its sections. The [`ENV`] must be
+    /// initialized.
+    pub fn set_tx(tx: Tx) {
+        with(|env| env.tx = tx.clone())
+    }
+
     /// Set the [`TestTxEnv`] back from a [`TestVpEnv`]. This is useful when
     /// testing validation with multiple transactions that accumulate some state
     /// changes.
@@ -350,6 +416,7 @@ mod native_tx_host_env {
                                 sentinel,
                                 result_buffer,
                                 tx_index,
+                                wrapper,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                                 tx_wasm_cache,
@@ -366,6 +433,7 @@ mod native_tx_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 result_buffer,
                                 vp_wasm_cache,
                                 tx_wasm_cache,
@@ -386,6 +454,7 @@ mod native_tx_host_env {
                     extern "C" fn extern_fn_name( $($arg: $type),* ) -> $ret {
                         with(|TestTxEnv {
                             tx_index,
+                                wrapper,
                                 wl_storage,
                                 iterators,
                                 verifiers,
@@ -408,6 +477,7 @@ mod native_tx_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 result_buffer,
                                 vp_wasm_cache,
                                 tx_wasm_cache,
@@ -434,6 +504,7 @@ mod native_tx_host_env {
                                 sentinel,
                                 result_buffer,
                                 tx_index,
+                                wrapper,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                                 tx_wasm_cache,
@@ -450,6 +521,7 @@ mod native_tx_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 result_buffer,
                                 vp_wasm_cache,
                                 tx_wasm_cache,
@@ -501,6 +573,7 @@ mod native_tx_host_env {
     ));
     native_host_fn!(tx_emit_ibc_event(event_ptr: u64, event_len: u64));
     native_host_fn!(tx_get_ibc_events(event_type_ptr: u64, event_type_len: u64) -> i64);
+    native_host_fn!(tx_emit_event(event_ptr: u64, event_len: u64));
     native_host_fn!(tx_get_chain_id(result_ptr: u64));
     native_host_fn!(tx_get_block_height() -> u64);
     native_host_fn!(tx_get_tx_index() -> u32);
@@ -508,9 +581,12 @@ mod native_tx_host_env {
     native_host_fn!(tx_get_block_hash(result_ptr: u64));
     native_host_fn!(tx_get_block_epoch() -> u64);
     native_host_fn!(tx_get_native_token(result_ptr: u64));
+    native_host_fn!(tx_get_section(hash_ptr: u64, hash_len: u64) -> i64);
+    native_host_fn!(tx_get_wrapper_header() -> i64);
     native_host_fn!(tx_log_string(str_ptr: u64, str_len: u64));
     native_host_fn!(tx_charge_gas(used_gas: u64));
     native_host_fn!("non-result", tx_set_commitment_sentinel());
+    native_host_fn!(tx_set_error(error_ptr: u64, error_len: u64));
     native_host_fn!(tx_verify_tx_section_signature(
         hash_list_ptr: u64,
         hash_list_len: u64,
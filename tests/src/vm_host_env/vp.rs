@@ -5,7 +5,7 @@ use namada::ledger::storage::mockdb::MockDB;
 use namada::ledger::storage::testing::TestStorage;
 use namada::ledger::storage::write_log::WriteLog;
 use namada::ledger::storage::{Sha256Hasher, WlStorage};
-use namada::proto::Tx;
+use namada::proto::{Header, Tx};
 use namada::types::address::{self, Address};
 use namada::types::storage::{self, Key, TxIndex};
 use namada::types::transaction::TxType;
@@ -15,9 +15,10 @@ use namada::vm::{self, WasmCacheRwAccess};
 use namada_core::ledger::gas::TxGasMeter;
 use namada_tx_prelude::validity_predicate::VpSentinel;
 use namada_vp_prelude::Ctx;
+use namada_vp_prelude::key::common;
 use tempfile::TempDir;
 
-use crate::tx::{tx_host_env, TestTxEnv};
+use crate::tx::{self, tx_host_env, TestTxEnv};
 
 /// VP execution context provides access to host env functions
 pub static CTX: Ctx = unsafe { Ctx::new() };
@@ -48,10 +49,13 @@ pub struct TestVpEnv {
     pub sentinel: VpSentinel,
     pub tx: Tx,
     pub tx_index: TxIndex,
+    pub wrapper: Option<Header>,
     pub keys_changed: BTreeSet<storage::Key>,
     pub verifiers: BTreeSet<Address>,
     pub eval_runner: native_vp_host_env::VpEval,
     pub result_buffer: Option<Vec<u8>>,
+    #[cfg(not(feature = "mainnet"))]
+    pub has_valid_pow: bool,
     pub vp_wasm_cache: VpCache<WasmCacheRwAccess>,
     pub vp_cache_dir: TempDir,
 }
@@ -82,10 +86,13 @@ impl Default for TestVpEnv {
             sentinel: VpSentinel::default(),
             tx,
             tx_index: TxIndex::default(),
+            wrapper: None,
             keys_changed: BTreeSet::default(),
             verifiers: BTreeSet::default(),
             eval_runner,
             result_buffer: None,
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow: false,
             vp_wasm_cache,
             vp_cache_dir,
         }
@@ -103,6 +110,29 @@ impl TestVpEnv {
             .verifiers_and_changed_keys(&self.verifiers)
             .0
     }
+
+    /// Replace the tx under test with a new tx for the chain of this
+    /// environment with the given code and data sections, like the SDK's tx
+    /// builders do. Returns the tx to allow to add more sections to it.
+    pub fn set_tx_code_and_data(
+        &mut self,
+        code: Vec<u8>,
+        data: Vec<u8>,
+    ) -> &mut Tx {
+        self.tx = tx::new_tx(&self.wl_storage, code, data);
+        &mut self.tx
+    }
+
+    /// Sign the tx under test with the given keys, like the SDK's
+    /// `Tx::sign_raw`. When an `owner` is given, the signatures are made on
+    /// its behalf using the indices of the owner's public keys in storage.
+    pub fn sign_tx(
+        &mut self,
+        keypairs: Vec<common::SecretKey>,
+        owner: Option<Address>,
+    ) {
+        tx::sign_tx(&self.wl_storage, &mut self.tx, keypairs, owner)
+    }
 }
 
 /// This module allows to test code with vp host environment functions.
@@ -185,6 +215,13 @@ mod native_vp_host_env {
         })
     }
 
+    /// Install the given tx as the tx under test in [`ENV`], so that the host
+    /// env functions answer from its sections. The [`ENV`] must be
+    /// initialized.
+    pub fn set_tx(tx: Tx) {
+        with(|env| env.tx = tx.clone())
+    }
+
     /// Initialize the VP host environment in [`ENV`] by running a transaction.
     /// The transaction is expected to modify the storage sub-space of the given
     /// address `addr` or to add it to the set of verifiers using
@@ -264,10 +301,13 @@ mod native_vp_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 keys_changed,
                                 verifiers,
                                 eval_runner,
                                 result_buffer,
+                                #[cfg(not(feature = "mainnet"))]
+                                has_valid_pow,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                             }: &mut TestVpEnv| {
@@ -281,10 +321,13 @@ mod native_vp_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 verifiers,
                                 result_buffer,
                                 keys_changed,
                                 eval_runner,
+                                #[cfg(not(feature = "mainnet"))]
+                                has_valid_pow,
                                 vp_wasm_cache,
                             );
 
@@ -309,10 +352,13 @@ mod native_vp_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 keys_changed,
                                 verifiers,
                                 eval_runner,
                                 result_buffer,
+                                #[cfg(not(feature = "mainnet"))]
+                                has_valid_pow,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                             }: &mut TestVpEnv| {
@@ -326,10 +372,13 @@ mod native_vp_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                wrapper,
                                 verifiers,
                                 result_buffer,
                                 keys_changed,
                                 eval_runner,
+                                #[cfg(not(feature = "mainnet"))]
+                                has_valid_pow,
                                 vp_wasm_cache,
                             );
 
@@ -356,6 +405,9 @@ mod native_vp_host_env {
     native_host_fn!(vp_get_chain_id(result_ptr: u64));
     native_host_fn!(vp_get_block_height() -> u64);
     native_host_fn!(vp_get_tx_index() -> u32);
+    native_host_fn!(vp_has_valid_pow() -> i64);
+    native_host_fn!(vp_get_tx_signers() -> i64);
+    native_host_fn!(vp_get_wrapper_header() -> i64);
     native_host_fn!(vp_get_block_header(height: u64) -> i64);
     native_host_fn!(vp_get_block_hash(result_ptr: u64));
     native_host_fn!(vp_get_tx_code_hash(result_ptr: u64));
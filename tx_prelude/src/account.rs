@@ -1,3 +1,9 @@
+use std::collections::HashSet;
+
+use namada_core::types::account::VpUpdate;
+use namada_core::types::event::TxEvent;
+pub use namada_core::types::event::{INIT_ACCOUNT_EVENT, VP_UPDATE_EVENT};
+use namada_core::types::hash::Hash;
 use namada_core::types::transaction::account::InitAccount;
 
 use super::*;
@@ -7,6 +13,19 @@ pub fn init_account(
     owner: &Address,
     data: InitAccount,
 ) -> EnvResult<Address> {
+    if data.threshold == 0 || data.threshold as usize > data.public_keys.len() {
+        return Err(Error::new_const(
+            "The account threshold must be between 1 and the number of public \
+             keys",
+        ));
+    }
+    let distinct_keys = data.public_keys.iter().collect::<HashSet<_>>();
+    if distinct_keys.len() != data.public_keys.len() {
+        return Err(Error::new_const(
+            "The account public keys must be distinct",
+        ));
+    }
+
     storage_api::account::init_account_storage(
         ctx,
         owner,
@@ -14,5 +33,43 @@ pub fn init_account(
         data.threshold,
     )?;
 
+    ctx.emit_event(&TxEvent::new(INIT_ACCOUNT_EVENT).with("address", owner))?;
+
     Ok(owner.to_owned())
 }
+
+/// Update the VP of an account, record the update in the account's VP
+/// history and emit an event with the old and new VP code hashes
+pub fn update_validity_predicate(
+    ctx: &mut Ctx,
+    owner: &Address,
+    code_hash: Hash,
+    code_tag: &Option<String>,
+    tx_hash: Hash,
+) -> TxResult {
+    let vp_key = storage::Key::validity_predicate(owner);
+    let old_vp_code_hash = ctx
+        .read_bytes(&vp_key)?
+        .and_then(|hash| Hash::try_from(&hash[..]).ok());
+    ctx.update_validity_predicate(owner, code_hash, code_tag)?;
+
+    let height = ctx.get_block_height()?;
+    storage_api::account::record_vp_update(
+        ctx,
+        owner,
+        height,
+        VpUpdate {
+            old_vp_code_hash,
+            new_vp_code_hash: code_hash,
+            tx_hash,
+        },
+    )?;
+
+    let mut event = TxEvent::new(VP_UPDATE_EVENT)
+        .with("address", owner)
+        .with("new_vp_code_hash", code_hash);
+    if let Some(old_vp_code_hash) = old_vp_code_hash {
+        event = event.with("old_vp_code_hash", old_vp_code_hash);
+    }
+    ctx.emit_event(&event)
+}
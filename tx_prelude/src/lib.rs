@@ -102,6 +102,96 @@ impl Ctx {
     pub const unsafe fn new() -> Self {
         Self(())
     }
+
+    /// Get a section of the executing tx by its hash. The section is fetched
+    /// from the host's copy of the tx.
+    pub fn get_tx_section(&self, hash: &hash::Hash) -> Option<Section> {
+        let hash = hash.serialize_to_vec();
+        let read_result = unsafe {
+            namada_tx_get_section(hash.as_ptr() as _, hash.len() as _)
+        };
+        read_from_buffer(read_result, namada_tx_result_buffer).map(|value| {
+            Section::try_from_slice(&value[..])
+                .expect("The conversion shouldn't fail")
+        })
+    }
+
+    /// Get the fee declared by the wrapper that carried the executing tx.
+    /// Returns `None` for txs that were not included via a wrapper.
+    pub fn get_tx_fee(&self) -> Option<transaction::Fee> {
+        self.get_wrapper_header()
+            .and_then(|header| header.wrapper())
+            .map(|wrapper| wrapper.fee)
+    }
+
+    /// Get the gas limit declared by the wrapper that carried the executing
+    /// tx. Returns `None` for txs that were not included via a wrapper.
+    pub fn get_gas_limit(&self) -> Option<transaction::GasLimit> {
+        self.get_wrapper_header()
+            .and_then(|header| header.wrapper())
+            .map(|wrapper| wrapper.gas_limit)
+    }
+
+    /// Get the expiration of the wrapper that carried the executing tx.
+    /// Returns `None` for txs that were not included via a wrapper or whose
+    /// wrapper doesn't expire.
+    pub fn get_tx_expiration(&self) -> Option<time::DateTimeUtc> {
+        self.get_wrapper_header()
+            .and_then(|header| header.expiration)
+    }
+
+    /// Get the header of the wrapper that carried the executing tx, if any.
+    fn get_wrapper_header(&self) -> Option<namada_core::proto::Header> {
+        let read_result = unsafe { namada_tx_get_wrapper_header() };
+        read_from_buffer(read_result, namada_tx_result_buffer).map(|value| {
+            namada_core::proto::Header::try_from_slice(&value[..])
+                .expect("The conversion shouldn't fail")
+        })
+    }
+
+    /// Check that the executing tx contains a valid signature by the given
+    /// public key over the section with the given hash.
+    pub fn verify_section_signature(
+        &self,
+        pk: &common::PublicKey,
+        target: &hash::Hash,
+    ) -> EnvResult<bool> {
+        self.verify_section_signatures(&[pk.clone()], 1, target)
+    }
+
+    /// Check that the executing tx contains valid signatures by at least the
+    /// threshold of the given public keys over the section with the given
+    /// hash.
+    pub fn verify_section_signatures(
+        &self,
+        pks: &[common::PublicKey],
+        threshold: u8,
+        target: &hash::Hash,
+    ) -> EnvResult<bool> {
+        let max_signatures_per_transaction =
+            parameters::max_signatures_per_transaction(self)?;
+        let public_keys_index_map =
+            AccountPublicKeysMap::from_iter(pks.iter().cloned());
+
+        // Serialize parameters
+        let max_signatures = max_signatures_per_transaction.serialize_to_vec();
+        let public_keys_map = public_keys_index_map.serialize_to_vec();
+        let targets = vec![*target].serialize_to_vec();
+
+        let valid = unsafe {
+            namada_tx_verify_tx_section_signature(
+                targets.as_ptr() as _,
+                targets.len() as _,
+                public_keys_map.as_ptr() as _,
+                public_keys_map.len() as _,
+                threshold,
+                max_signatures.as_ptr() as _,
+                max_signatures.len() as _,
+            )
+        };
+
+        Ok(HostEnvResult::is_success(valid))
+    }
 }
 
 /// Result of `TxEnv`, `storage_api::StorageRead` or `storage_api::StorageWrite`
@@ -338,6 +428,12 @@ impl TxEnv for Ctx {
         Ok(())
     }
 
+    fn emit_event(&mut self, event: &event::TxEvent) -> Result<(), Error> {
+        let event = borsh::to_vec(event).unwrap();
+        unsafe { namada_tx_emit_event(event.as_ptr() as _, event.len() as _) };
+        Ok(())
+    }
+
     fn charge_gas(&mut self, used_gas: u64) -> Result<(), Error> {
         unsafe { namada_tx_charge_gas(used_gas) };
         Ok(())
@@ -364,6 +460,11 @@ impl TxEnv for Ctx {
     fn set_commitment_sentinel(&mut self) {
         unsafe { namada_tx_set_commitment_sentinel() }
     }
+
+    fn set_error(&mut self, error: &transaction::TxWasmError) {
+        let error = error.serialize_to_vec();
+        unsafe { namada_tx_set_error(error.as_ptr() as _, error.len() as _) }
+    }
 }
 
 /// Execute IBC tx.
@@ -378,29 +479,7 @@ pub fn verify_signatures_of_pks(
     tx: &Tx,
     pks: Vec<common::PublicKey>,
 ) -> EnvResult<bool> {
-    let max_signatures_per_transaction =
-        parameters::max_signatures_per_transaction(ctx)?;
-
     // Require signatures from all the given keys
     let threshold = u8::try_from(pks.len()).into_storage_result()?;
-    let public_keys_index_map = AccountPublicKeysMap::from_iter(pks);
-
-    // Serialize parameters
-    let max_signatures = max_signatures_per_transaction.serialize_to_vec();
-    let public_keys_map = public_keys_index_map.serialize_to_vec();
-    let targets = [tx.raw_header_hash()].serialize_to_vec();
-
-    let valid = unsafe {
-        namada_tx_verify_tx_section_signature(
-            targets.as_ptr() as _,
-            targets.len() as _,
-            public_keys_map.as_ptr() as _,
-            public_keys_map.len() as _,
-            threshold,
-            max_signatures.as_ptr() as _,
-            max_signatures.len() as _,
-        )
-    };
-
-    Ok(HostEnvResult::is_success(valid))
+    ctx.verify_section_signatures(&pks, threshold, &tx.raw_header_hash())
 }
@@ -3,6 +3,7 @@
 use namada_core::types::dec::Dec;
 use namada_core::types::hash::Hash;
 use namada_core::types::key::common;
+use namada_core::types::key::pop::ProofOfPossession;
 use namada_core::types::transaction::pos::InitValidator;
 use namada_core::types::{key, token};
 pub use namada_proof_of_stake::parameters::PosParams;
@@ -12,12 +13,30 @@ use namada_proof_of_stake::{
     change_validator_commission_rate, change_validator_metadata,
     claim_reward_tokens, deactivate_validator, reactivate_validator,
     read_pos_params, redelegate_tokens, unbond_tokens, unjail_validator,
-    withdraw_tokens, BecomeValidator,
+    verify_consensus_key_proof, withdraw_tokens, BecomeValidator,
+    BecomeValidatorError,
 };
 pub use namada_proof_of_stake::{parameters, types, ResultSlashing};
 
 use super::*;
 
+/// Read the bond data of a bond or an unbond tx from its data section. The
+/// source of the bond, or the validator for a self-bond, is added to the
+/// verifiers for its VP to authorize the tx.
+pub fn read_bond_data(
+    ctx: &mut Ctx,
+    tx: &Tx,
+) -> EnvResult<transaction::pos::Bond> {
+    let data = tx.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let bond = transaction::pos::Bond::try_from_slice(&data[..])
+        .wrap_err("Failed to decode the bond data")?;
+    ctx.insert_verifier(bond.source.as_ref().unwrap_or(&bond.validator))?;
+    Ok(bond)
+}
+
 impl Ctx {
     /// Self-bond tokens to a validator when `source` is `None` or equal to
     /// the `validator` address, or delegate tokens from the `source` to the
@@ -28,6 +47,11 @@ impl Ctx {
         validator: &Address,
         amount: token::Amount,
     ) -> TxResult {
+        if amount.is_zero() {
+            return Err(Error::new_const(
+                "The bond amount must be greater than zero",
+            ));
+        }
         let current_epoch = self.get_block_epoch()?;
         bond_tokens(self, source, validator, amount, current_epoch, None)
     }
@@ -57,12 +81,19 @@ impl Ctx {
         withdraw_tokens(self, source, validator, current_epoch)
     }
 
-    /// Change validator consensus key.
+    /// Change validator consensus key, given a proof of possession of the
+    /// new key made for the validator's address.
     pub fn change_validator_consensus_key(
         &mut self,
         validator: &Address,
         consensus_key: &common::PublicKey,
+        consensus_key_proof: &ProofOfPossession,
     ) -> TxResult {
+        verify_consensus_key_proof(
+            validator,
+            consensus_key,
+            consensus_key_proof,
+        )?;
         let current_epoch = self.get_block_epoch()?;
         change_consensus_key(self, validator, consensus_key, current_epoch)
     }
@@ -112,11 +143,19 @@ impl Ctx {
         claim_reward_tokens(self, source, validator, current_epoch)
     }
 
-    /// Attempt to initialize a validator account. On success, returns the
+    /// Attempt to initialize a validator account. The proofs of possession
+    /// of the validator keys must be valid. On success, returns the
     /// initialized validator account's address.
     pub fn init_validator(
         &mut self,
-        InitValidator {
+        init_validator: InitValidator,
+        validator_vp_code_hash: Hash,
+        validator_vp_code_tag: &Option<String>,
+    ) -> EnvResult<Address> {
+        init_validator
+            .verify_key_proofs()
+            .map_err(BecomeValidatorError::InvalidKeyProof)?;
+        let InitValidator {
             account_keys,
             threshold,
             consensus_key,
@@ -130,10 +169,8 @@ impl Ctx {
             website,
             discord_handle,
             validator_vp_code_hash: _,
-        }: InitValidator,
-        validator_vp_code_hash: Hash,
-        validator_vp_code_tag: &Option<String>,
-    ) -> EnvResult<Address> {
+            key_proofs: _,
+        } = init_validator;
         let current_epoch = self.get_block_epoch()?;
         // Init validator account
         let validator_address =
@@ -1,5 +1,9 @@
+use data_encoding::HEXLOWER;
 use masp_primitives::transaction::Transaction;
+use namada_core::proto::MAX_MEMO_LEN;
 use namada_core::types::address::{Address, MASP};
+pub use namada_core::types::event::TRANSFER_EVENT;
+use namada_core::types::event::TxEvent;
 use namada_core::types::storage::KeySeg;
 use namada_core::types::token;
 pub use namada_core::types::token::*;
@@ -116,3 +120,32 @@ pub fn burn(
 
     Ok(())
 }
+
+/// Get the memo of the given tx, if any. Fails if the memo is longer than
+/// [`MAX_MEMO_LEN`].
+pub fn memo(tx: &Tx) -> EnvResult<Option<Vec<u8>>> {
+    match tx.memo() {
+        Some(memo) if memo.len() > MAX_MEMO_LEN => {
+            Err(Error::new_const("The tx memo is too long"))
+        }
+        memo => Ok(memo),
+    }
+}
+
+/// Emit an event for the given transfer. The memo, if any, is included
+/// hex-encoded in the event's attributes.
+pub fn emit_transfer_event(
+    ctx: &mut Ctx,
+    transfer: &Transfer,
+    memo: Option<&[u8]>,
+) -> TxResult {
+    let mut event = TxEvent::new(TRANSFER_EVENT)
+        .with("source", &transfer.source)
+        .with("target", &transfer.target)
+        .with("token", &transfer.token)
+        .with("amount", transfer.amount);
+    if let Some(memo) = memo {
+        event = event.with("memo", HEXLOWER.encode(memo));
+    }
+    ctx.emit_event(&event)
+}
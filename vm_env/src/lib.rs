@@ -88,6 +88,9 @@ pub mod tx {
             event_type_len: u64,
         ) -> i64;
 
+        // Emit an event of the tx
+        pub fn namada_tx_emit_event(event_ptr: u64, event_len: u64);
+
         // Get the chain ID
         pub fn namada_tx_get_chain_id(result_ptr: u64);
 
@@ -109,6 +112,12 @@ pub mod tx {
         // Get the native token address
         pub fn namada_tx_get_native_token(result_ptr: u64);
 
+        // Get a section of the current tx by its hash
+        pub fn namada_tx_get_section(hash_ptr: u64, hash_len: u64) -> i64;
+
+        // Get the header of the wrapper that carried the current tx
+        pub fn namada_tx_get_wrapper_header() -> i64;
+
         // Requires a node running with "Info" log level
         pub fn namada_tx_log_string(str_ptr: u64, str_len: u64);
 
@@ -122,6 +131,9 @@ pub mod tx {
         /// Set the sentinel for a wrong tx section commitment
         pub fn namada_tx_set_commitment_sentinel();
 
+        /// Set the error with which the tx is about to abort
+        pub fn namada_tx_set_error(error_ptr: u64, error_len: u64);
+
         // Verify the signatures of a tx
         pub fn namada_tx_verify_tx_section_signature(
             hash_list_ptr: u64,
@@ -213,6 +225,10 @@ pub mod vp {
         // Get the current tx index
         pub fn namada_vp_get_tx_index() -> u32;
 
+        // Check if the wrapper of the current tx contained a valid testnet
+        // faucet withdrawal PoW solution
+        pub fn namada_vp_has_valid_pow() -> i64;
+
         // Get the native token address
         pub fn namada_vp_get_native_token(result_ptr: u64);
 
@@ -222,6 +238,12 @@ pub mod vp {
             event_type_len: u64,
         ) -> i64;
 
+        // Get the public keys that made valid signatures over the tx
+        pub fn namada_vp_get_tx_signers() -> i64;
+
+        // Get the header of the wrapper that carried the current tx
+        pub fn namada_vp_get_wrapper_header() -> i64;
+
         // Requires a node running with "Info" log level
         pub fn namada_vp_log_string(str_ptr: u64, str_len: u64);
 
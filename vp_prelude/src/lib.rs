@@ -19,11 +19,14 @@ pub use namada_core::ledger::governance::storage as gov_storage;
 pub use namada_core::ledger::parameters;
 pub use namada_core::ledger::pgf::storage as pgf_storage;
 pub use namada_core::ledger::storage_api::{
-    self, iter_prefix, iter_prefix_bytes, Error, OptionExt, ResultExt,
-    StorageRead,
+    self, Error, OptionExt, ResultExt, StorageRead, iter_prefix,
+    iter_prefix_bytes,
 };
+#[cfg(not(feature = "mainnet"))]
+pub use namada_core::ledger::testnet_pow;
 pub use namada_core::ledger::vp_env::VpEnv;
 pub use namada_core::proto::{Section, Tx};
+use namada_core::types::account::AccountPublicKeysMap;
 pub use namada_core::types::address::Address;
 use namada_core::types::chain::CHAIN_ID_LENGTH;
 use namada_core::types::hash::{Hash, HASH_LENGTH};
@@ -79,21 +82,68 @@ pub fn is_proposal_accepted(ctx: &Ctx, proposal_id: u64) -> VpResult {
     ctx.has_key_pre(&proposal_execution_key)
 }
 
-/// Verify section signatures
-pub fn verify_signatures(ctx: &Ctx, tx: &Tx, owner: &Address) -> VpResult {
-    let max_signatures_per_transaction =
-        parameters::max_signatures_per_transaction(&ctx.pre())?;
+/// The hashes of the tx sections that must be covered by the signatures
+/// authorizing a tx. The raw header commits to the hashes of the tx's code and
+/// data sections, so a signature over it authorizes both of them.
+pub fn signature_targets(tx: &Tx) -> Vec<Hash> {
+    vec![tx.raw_header_hash()]
+}
 
+/// Verify that the tx's signature sections over the [`signature_targets`] are
+/// made by at least the threshold of the owner's public keys
+pub fn verify_signatures(ctx: &Ctx, tx: &Tx, owner: &Address) -> VpResult {
     let public_keys_index_map =
         storage_api::account::public_keys_index_map(&ctx.pre(), owner)?;
     let threshold =
         storage_api::account::threshold(&ctx.pre(), owner)?.unwrap_or(1);
 
+    verify_signatures_with_keys(
+        ctx,
+        tx,
+        owner,
+        &public_keys_index_map,
+        threshold,
+    )
+}
+
+/// Verify that the tx's signature sections over the [`signature_targets`] are
+/// made by at least the threshold of the given public keys of the owner
+pub fn verify_signatures_with_keys(
+    ctx: &Ctx,
+    tx: &Tx,
+    owner: &Address,
+    public_keys_index_map: &AccountPublicKeysMap,
+    threshold: u8,
+) -> VpResult {
+    let targets = signature_targets(tx);
+    verify_section_signatures(
+        ctx,
+        &targets,
+        public_keys_index_map,
+        Some(owner),
+        threshold,
+    )
+}
+
+/// Verify that the executing tx contains signatures over the given targets
+/// made by at least the threshold of the given public keys. Signatures made on
+/// behalf of an account are only considered when its address is given as the
+/// signer.
+fn verify_section_signatures(
+    ctx: &Ctx,
+    targets: &[Hash],
+    public_keys_index_map: &AccountPublicKeysMap,
+    signer: Option<&Address>,
+    threshold: u8,
+) -> VpResult {
+    let max_signatures_per_transaction =
+        parameters::max_signatures_per_transaction(&ctx.pre())?;
+
     // Serialize parameters
     let max_signatures = max_signatures_per_transaction.serialize_to_vec();
     let public_keys_map = public_keys_index_map.serialize_to_vec();
-    let targets = [tx.raw_header_hash()].serialize_to_vec();
-    let signer = owner.serialize_to_vec();
+    let targets = targets.serialize_to_vec();
+    let signer = signer.serialize_to_vec();
 
     let valid = unsafe {
         namada_vp_verify_tx_section_signature(
@@ -190,6 +240,93 @@ impl Ctx {
     pub fn post(&self) -> CtxPostStorageRead<'_> {
         CtxPostStorageRead { _ctx: self }
     }
+
+    /// Check if the wrapper of the current tx contained a valid testnet
+    /// faucet withdrawal PoW solution. The solution is validated and
+    /// invalidated by the protocol when it applies the wrapper.
+    #[cfg(not(feature = "mainnet"))]
+    pub fn has_valid_pow(&self) -> bool {
+        let valid = unsafe { namada_vp_has_valid_pow() };
+        HostEnvResult::is_success(valid)
+    }
+
+    /// Get the public keys that made valid signatures over the sections of
+    /// the current tx. The public keys of signatures made on behalf of an
+    /// account are those from the prior state of the account.
+    pub fn get_tx_signers(
+        &self,
+    ) -> EnvResult<BTreeSet<key::common::PublicKey>> {
+        let read_result = unsafe { namada_vp_get_tx_signers() };
+        match read_from_buffer(read_result, namada_vp_result_buffer) {
+            Some(value) => {
+                BTreeSet::try_from_slice(&value[..]).into_storage_result()
+            }
+            None => Ok(BTreeSet::new()),
+        }
+    }
+
+    /// Get the fee declared by the wrapper that carried the current tx.
+    /// Returns `None` for txs that were not included via a wrapper.
+    pub fn get_tx_fee(&self) -> Option<transaction::Fee> {
+        self.get_wrapper_header()
+            .and_then(|header| header.wrapper())
+            .map(|wrapper| wrapper.fee)
+    }
+
+    /// Get the gas limit declared by the wrapper that carried the current
+    /// tx. Returns `None` for txs that were not included via a wrapper.
+    pub fn get_gas_limit(&self) -> Option<transaction::GasLimit> {
+        self.get_wrapper_header()
+            .and_then(|header| header.wrapper())
+            .map(|wrapper| wrapper.gas_limit)
+    }
+
+    /// Get the expiration of the wrapper that carried the current tx.
+    /// Returns `None` for txs that were not included via a wrapper or whose
+    /// wrapper doesn't expire.
+    pub fn get_tx_expiration(&self) -> Option<time::DateTimeUtc> {
+        self.get_wrapper_header()
+            .and_then(|header| header.expiration)
+    }
+
+    /// Get the header of the wrapper that carried the current tx, if any.
+    fn get_wrapper_header(&self) -> Option<namada_core::proto::Header> {
+        let read_result = unsafe { namada_vp_get_wrapper_header() };
+        read_from_buffer(read_result, namada_vp_result_buffer).map(|value| {
+            namada_core::proto::Header::try_from_slice(&value[..])
+                .expect("The conversion shouldn't fail")
+        })
+    }
+
+    /// Check that the current tx contains a valid signature by the given
+    /// public key over the section with the given hash.
+    pub fn verify_section_signature(
+        &self,
+        pk: &key::common::PublicKey,
+        target: &Hash,
+    ) -> VpResult {
+        self.verify_section_signatures(&[pk.clone()], 1, target)
+    }
+
+    /// Check that the current tx contains valid signatures by at least the
+    /// threshold of the given public keys over the section with the given
+    /// hash.
+    pub fn verify_section_signatures(
+        &self,
+        pks: &[key::common::PublicKey],
+        threshold: u8,
+        target: &Hash,
+    ) -> VpResult {
+        let public_keys_index_map =
+            AccountPublicKeysMap::from_iter(pks.iter().cloned());
+        verify_section_signatures(
+            self,
+            &[*target],
+            &public_keys_index_map,
+            None,
+            threshold,
+        )
+    }
 }
 
 /// Read access to the prior storage (state before tx execution) via
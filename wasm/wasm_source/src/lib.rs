@@ -4,6 +4,8 @@ pub mod tx_bond;
 pub mod tx_bridge_pool;
 #[cfg(feature = "tx_change_consensus_key")]
 pub mod tx_change_consensus_key;
+#[cfg(feature = "tx_change_keys")]
+pub mod tx_change_keys;
 #[cfg(feature = "tx_change_validator_commission")]
 pub mod tx_change_validator_commission;
 #[cfg(feature = "tx_change_validator_metadata")]
@@ -42,9 +44,13 @@ pub mod tx_update_steward_commission;
 pub mod tx_vote_proposal;
 #[cfg(feature = "tx_withdraw")]
 pub mod tx_withdraw;
+#[cfg(all(feature = "tx_write", not(feature = "mainnet")))]
+pub mod tx_write;
 
 #[cfg(feature = "vp_implicit")]
 pub mod vp_implicit;
+#[cfg(all(feature = "vp_testnet_faucet", not(feature = "mainnet")))]
+pub mod vp_testnet_faucet;
 #[cfg(feature = "vp_user")]
 pub mod vp_user;
 #[cfg(feature = "vp_validator")]
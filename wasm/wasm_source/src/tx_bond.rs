@@ -4,14 +4,7 @@ use namada_tx_prelude::*;
 
 #[transaction(gas = 1342908)]
 fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
-    let signed = tx_data;
-    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
-        ctx.set_commitment_sentinel();
-        err
-    })?;
-    let bond = transaction::pos::Bond::try_from_slice(&data[..])
-        .wrap_err("failed to decode Bond")
-        .unwrap();
+    let bond = proof_of_stake::read_bond_data(ctx, &tx_data)?;
 
     ctx.bond_tokens(bond.source.as_ref(), &bond.validator, bond.amount)
 }
@@ -20,7 +13,7 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
 mod tests {
     use std::collections::BTreeSet;
 
-    use namada::ledger::pos::{OwnedPosParams, PosVP};
+    use namada::ledger::pos::{OwnedPosParams, PosParams, PosVP};
     use namada::proof_of_stake::types::{GenesisValidator, WeightedValidator};
     use namada::proof_of_stake::{
         bond_handle, read_consensus_validator_set_addresses_with_stake,
@@ -29,7 +22,7 @@ mod tests {
     use namada::types::dec::Dec;
     use namada::types::storage::Epoch;
     use namada_tests::log::test;
-    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::native_vp::pos::{init_pos, init_pos_with_validator};
     use namada_tests::native_vp::TestNativeVpEnv;
     use namada_tests::tx::*;
     use namada_tx_prelude::address::testing::{
@@ -371,4 +364,120 @@ mod tests {
                 }
             })
     }
+
+    /// Build a bond tx with the given bond in its data section
+    fn bond_tx(bond: &transaction::pos::Bond) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(bond.serialize_to_vec());
+        tx
+    }
+
+    /// Credit the given amount of native tokens to the owner
+    fn credit_native_tokens(owner: &Address, amount: token::Amount) {
+        tx_host_env::with(|tx_env| {
+            tx_env.spawn_accounts([owner]);
+            let native_token = tx_env.wl_storage.storage.native_token.clone();
+            tx_env.credit_tokens(owner, &native_token, amount);
+        });
+    }
+
+    /// Validate the changes of the tx with the PoS VP
+    fn validate_with_pos_vp() -> bool {
+        let tx_env = tx_host_env::take();
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, address::POS);
+        vp_env
+            .validate_tx(PosVP::new)
+            .expect("Validation of valid changes must not fail!")
+    }
+
+    /// Test a self-bond of a validator
+    #[test]
+    fn test_self_bond() {
+        let (validator, pos_params) = init_pos_with_validator();
+        let amount = token::Amount::native_whole(100);
+        credit_native_tokens(&validator, amount);
+
+        let bond = transaction::pos::Bond {
+            validator: validator.clone(),
+            amount,
+            source: None,
+        };
+        apply_tx(ctx(), bond_tx(&bond)).unwrap();
+
+        let pipeline_epoch = Epoch(pos_params.pipeline_len);
+        let stake = read_validator_stake(
+            ctx(),
+            &pos_params,
+            &validator,
+            pipeline_epoch,
+        )
+        .unwrap();
+        assert_eq!(stake, token::Amount::native_whole(1_000) + amount);
+        // The validator must authorize its self-bond
+        assert!(tx_host_env::with(|env| env.verifiers.contains(&validator)));
+
+        assert!(validate_with_pos_vp());
+    }
+
+    /// Test a delegation from another source to a validator
+    #[test]
+    fn test_delegation() {
+        let (validator, pos_params) = init_pos_with_validator();
+        let source = address::testing::established_address_2();
+        let amount = token::Amount::native_whole(100);
+        credit_native_tokens(&source, amount);
+
+        let bond = transaction::pos::Bond {
+            validator: validator.clone(),
+            amount,
+            source: Some(source.clone()),
+        };
+        apply_tx(ctx(), bond_tx(&bond)).unwrap();
+
+        let pipeline_epoch = Epoch(pos_params.pipeline_len);
+        let bonded = bond_handle(&source, &validator)
+            .get_sum(ctx(), pipeline_epoch, &pos_params)
+            .unwrap();
+        assert_eq!(bonded, Some(amount));
+        // The source must authorize its delegation with its signature
+        assert!(tx_host_env::with(|env| env.verifiers.contains(&source)));
+
+        assert!(validate_with_pos_vp());
+    }
+
+    /// Test that bonding a zero amount fails
+    #[test]
+    fn test_bond_zero_amount() {
+        let (validator, _pos_params) = init_pos_with_validator();
+
+        let bond = transaction::pos::Bond {
+            validator,
+            amount: token::Amount::zero(),
+            source: None,
+        };
+        let err = apply_tx(ctx(), bond_tx(&bond)).unwrap_err();
+        assert!(err.to_string().contains("greater than zero"), "{err}");
+    }
+
+    /// Test that bonding to an address that isn't a validator fails
+    #[test]
+    fn test_bond_to_non_validator() {
+        let (_validator, _pos_params) = init_pos_with_validator();
+        let source = address::testing::established_address_2();
+        let not_validator = address::testing::established_address_3();
+        let amount = token::Amount::native_whole(100);
+        credit_native_tokens(&source, amount);
+
+        let bond = transaction::pos::Bond {
+            validator: not_validator.clone(),
+            amount,
+            source: Some(source),
+        };
+        let err = apply_tx(ctx(), bond_tx(&bond)).unwrap_err();
+        assert!(
+            err.to_string().contains(&not_validator.to_string()),
+            "{err}"
+        );
+    }
 }
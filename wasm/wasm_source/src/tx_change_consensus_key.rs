@@ -1,4 +1,5 @@
-//! A tx for a validator to change their consensus key.
+//! A tx for a validator to change their consensus key. The new key must come
+//! with a proof of possession made for the validator's address.
 
 use namada_tx_prelude::transaction::pos::ConsensusKeyChange;
 use namada_tx_prelude::*;
@@ -10,7 +11,12 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
     let ConsensusKeyChange {
         validator,
         consensus_key,
+        consensus_key_proof,
     } = transaction::pos::ConsensusKeyChange::try_from_slice(&data[..])
         .wrap_err("failed to decode Dec value")?;
-    ctx.change_validator_consensus_key(&validator, &consensus_key)
+    ctx.change_validator_consensus_key(
+        &validator,
+        &consensus_key,
+        &consensus_key_proof,
+    )
 }
@@ -0,0 +1,147 @@
+//! A tx for rotating the public keys of an established account without
+//! changing its validity predicate.
+//! This tx uses `ChangeKeys` as its input as declared in `shared` crate. All
+//! the account's keys and its threshold are replaced at once.
+//! The change must be authorized by the account's VP, which checks the
+//! signatures against the keys and threshold from before the rotation, so the
+//! new keys alone cannot authorize it.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 968137)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let transaction::account::ChangeKeys {
+        addr,
+        new_keys,
+        new_threshold,
+    } = transaction::account::ChangeKeys::try_from_slice(&data[..])
+        .wrap_err("failed to decode ChangeKeys")?;
+    debug_log!("change keys of: {:#?}", addr);
+
+    storage_api::account::change_keys(ctx, &addr, &new_keys, new_threshold)?;
+
+    // Make sure that the account's VP gets to authorize the change
+    ctx.insert_verifier(&addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_tests::log::test;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::key::RefTo;
+    use namada_tx_prelude::key::testing::{
+        keypair_1, keypair_2, keypair_3, keypair_4,
+    };
+    use namada_tx_prelude::transaction::account::ChangeKeys;
+
+    use super::*;
+
+    /// Setup an account with the given keys and threshold.
+    fn setup(keys: Vec<key::common::PublicKey>, threshold: u8) -> Address {
+        let mut env = TestTxEnv::default();
+        let addr = established_address_1();
+        env.spawn_accounts([&addr]);
+        env.init_account_storage(&addr, keys, threshold);
+        tx_host_env::set(env);
+        addr
+    }
+
+    fn change_keys_tx(
+        addr: Address,
+        new_keys: Vec<key::common::PublicKey>,
+        new_threshold: u8,
+    ) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = ChangeKeys {
+            addr,
+            new_keys,
+            new_threshold,
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx
+    }
+
+    fn read_keys(addr: &Address) -> (Vec<key::common::PublicKey>, Option<u8>) {
+        (
+            storage_api::account::public_keys(ctx(), addr).unwrap(),
+            storage_api::account::threshold(ctx(), addr).unwrap(),
+        )
+    }
+
+    /// Test rotating the key of a single-key account
+    #[test]
+    fn test_change_keys_single_key() {
+        let addr = setup(vec![keypair_1().ref_to()], 1);
+
+        let tx = change_keys_tx(addr.clone(), vec![keypair_2().ref_to()], 1);
+        apply_tx(ctx(), tx).unwrap();
+
+        assert_eq!(read_keys(&addr), (vec![keypair_2().ref_to()], Some(1)));
+        // The account's VP must authorize the change
+        let verifiers = tx_host_env::with(|env| env.verifiers.clone());
+        assert!(verifiers.contains(&addr));
+    }
+
+    /// Test that all the keys of a multisig account are replaced, including
+    /// when there are fewer new keys than the old ones
+    #[test]
+    fn test_change_keys_multisig() {
+        let old_keys = vec![
+            keypair_1().ref_to(),
+            keypair_2().ref_to(),
+            keypair_3().ref_to(),
+        ];
+        let addr = setup(old_keys, 2);
+
+        let new_keys = vec![keypair_3().ref_to(), keypair_4().ref_to()];
+        let tx = change_keys_tx(addr.clone(), new_keys.clone(), 2);
+        apply_tx(ctx(), tx).unwrap();
+
+        assert_eq!(read_keys(&addr), (new_keys, Some(2)));
+    }
+
+    /// Test that invalid key sets are rejected without modifying the account
+    #[test]
+    fn test_change_keys_invalid() {
+        let old_keys = vec![keypair_1().ref_to(), keypair_2().ref_to()];
+        let addr = setup(old_keys.clone(), 1);
+
+        let invalid_changes = [
+            // No keys
+            (vec![], 1),
+            // A zero threshold
+            (vec![keypair_3().ref_to()], 0),
+            // A threshold larger than the number of keys
+            (vec![keypair_3().ref_to()], 2),
+            // Duplicate keys
+            (vec![keypair_3().ref_to(), keypair_3().ref_to()], 1),
+        ];
+        for (new_keys, new_threshold) in invalid_changes {
+            let tx = change_keys_tx(addr.clone(), new_keys, new_threshold);
+            assert!(apply_tx(ctx(), tx).is_err());
+            assert_eq!(read_keys(&addr), (old_keys.clone(), Some(1)));
+        }
+    }
+
+    /// Test that the keys of an account which doesn't exist cannot be set
+    #[test]
+    fn test_change_keys_of_missing_account() {
+        setup(vec![keypair_1().ref_to()], 1);
+        let missing = established_address_2();
+
+        let tx = change_keys_tx(missing.clone(), vec![keypair_2().ref_to()], 1);
+        assert!(apply_tx(ctx(), tx).is_err());
+        assert_eq!(read_keys(&missing), (vec![], None));
+    }
+}
@@ -22,13 +22,13 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
 mod tests {
     use std::cmp;
 
-    use namada::ledger::pos::{OwnedPosParams, PosVP};
+    use namada::ledger::pos::{OwnedPosParams, PosParams, PosVP};
     use namada::proof_of_stake::types::GenesisValidator;
     use namada::proof_of_stake::validator_commission_rate_handle;
     use namada::types::dec::{Dec, POS_DECIMAL_PRECISION};
     use namada::types::storage::Epoch;
     use namada_tests::log::test;
-    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::native_vp::pos::{init_pos, init_pos_with_validator};
     use namada_tests::native_vp::TestNativeVpEnv;
     use namada_tests::tx::*;
     use namada_tx_prelude::address::testing::arb_established_address;
@@ -165,6 +165,74 @@ mod tests {
         Ok(())
     }
 
+    /// Build a commission change tx of the given validator
+    fn commission_change_tx(validator: &Address, new_rate: Dec) -> Tx {
+        let commission_change = transaction::pos::CommissionChange {
+            validator: validator.clone(),
+            new_rate,
+        };
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(commission_change.serialize_to_vec());
+        tx
+    }
+
+    /// Read the commission rate of the validator at the pipeline epoch
+    fn read_pipeline_rate(validator: &Address, pos_params: &PosParams) -> Dec {
+        let pipeline_epoch =
+            ctx().get_block_epoch().unwrap() + pos_params.pipeline_len;
+        validator_commission_rate_handle(validator)
+            .get(ctx(), pipeline_epoch, pos_params)
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Test a commission rate change within the maximum rate change
+    #[test]
+    fn test_commission_change_within_bound() {
+        let (validator, pos_params) = init_pos_with_validator();
+        let new_rate = Dec::new(6, 2).unwrap();
+
+        apply_tx(ctx(), commission_change_tx(&validator, new_rate)).unwrap();
+
+        assert_eq!(read_pipeline_rate(&validator, &pos_params), new_rate);
+    }
+
+    /// Test that a commission rate change larger than the maximum rate change
+    /// is rejected
+    #[test]
+    fn test_commission_change_exceeding_bound() {
+        let (validator, pos_params) = init_pos_with_validator();
+        let new_rate = Dec::new(7, 2).unwrap();
+
+        let err = apply_tx(ctx(), commission_change_tx(&validator, new_rate))
+            .unwrap_err();
+        assert!(err.to_string().contains("is too large"));
+
+        assert_eq!(
+            read_pipeline_rate(&validator, &pos_params),
+            Dec::new(5, 2).unwrap()
+        );
+    }
+
+    /// Test that only a single commission rate change is allowed per epoch
+    #[test]
+    fn test_two_commission_changes_in_epoch() {
+        let (validator, pos_params) = init_pos_with_validator();
+        let first_rate = Dec::new(6, 2).unwrap();
+        let second_rate = Dec::new(55, 3).unwrap();
+
+        apply_tx(ctx(), commission_change_tx(&validator, first_rate)).unwrap();
+        tx_host_env::commit_tx_and_block();
+
+        let err =
+            apply_tx(ctx(), commission_change_tx(&validator, second_rate))
+                .unwrap_err();
+        assert!(err.to_string().contains("already been changed"));
+
+        assert_eq!(read_pipeline_rate(&validator, &pos_params), first_rate);
+    }
+
     fn arb_rate(min: Dec, max: Dec) -> impl Strategy<Value = Dec> {
         let int_min: i128 = (min * scale()).try_into().unwrap();
         let int_max: i128 = (max * scale()).try_into().unwrap();
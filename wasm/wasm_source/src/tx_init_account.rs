@@ -1,5 +1,7 @@
 //! A tx to initialize a new established address with a given public key and
-//! a validity predicate.
+//! a validity predicate. The VP code is referenced by the hash of an extra
+//! data section of the tx. The new address is reported in an `init_account`
+//! event.
 
 use namada_tx_prelude::*;
 
@@ -11,15 +13,16 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
         err
     })?;
     let tx_data = transaction::account::InitAccount::try_from_slice(&data[..])
-        .wrap_err("failed to decode InitAccount")?;
+        .map_err(|_| Error::new(transaction::TxWasmError::InvalidData))?;
     debug_log!("apply_tx called to init a new established account");
 
-    let vp_code_sec = signed
-        .get_section(&tx_data.vp_code_hash)
-        .ok_or_err_msg("vp code section not found")
-        .map_err(|err| {
+    let vp_code_sec = ctx
+        .get_tx_section(&tx_data.vp_code_hash)
+        .ok_or_else(|| {
             ctx.set_commitment_sentinel();
-            err
+            Error::new(transaction::TxWasmError::MissingSection(
+                tx_data.vp_code_hash,
+            ))
         })?
         .extra_data_sec()
         .ok_or_err_msg("vp code section must be tagged as extra")
@@ -31,14 +34,234 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
     let address =
         ctx.init_account(vp_code_sec.code.hash(), &vp_code_sec.tag)?;
 
-    match account::init_account(ctx, &address, tx_data) {
-        Ok(address) => {
-            debug_log!("Created account {}", address.encode(),)
+    let address = account::init_account(ctx, &address, tx_data)?;
+    debug_log!("Created account {}", address.encode());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::proto::Data;
+    use namada::types::hash::Hash;
+    use namada_test_utils::TestWasms;
+    use namada_tests::log::test;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::key::RefTo;
+    use namada_tx_prelude::transaction::account::InitAccount;
+
+    use super::*;
+
+    /// Setup the tx env with the code of the always true VP stored on chain.
+    /// Returns the hash of the stored VP code.
+    fn setup() -> Hash {
+        let mut env = TestTxEnv::default();
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let vp_hash = Hash::sha256(&vp_code);
+        env.store_wasm_code(vp_code);
+        tx_host_env::set(env);
+        vp_hash
+    }
+
+    fn init_account_data(vp_code_hash: Hash) -> InitAccount {
+        InitAccount {
+            public_keys: vec![key::testing::keypair_1().ref_to()],
+            vp_code_hash,
+            threshold: 1,
         }
-        Err(err) => {
-            debug_log!("Account creation failed with: {}", err);
-            panic!()
+    }
+
+    /// Test initializing an account with a VP code from an extra data section
+    #[test]
+    fn test_init_account() {
+        let vp_hash = setup();
+        // The address that the tx is expected to derive
+        let expected_address = tx_host_env::with(|env| {
+            env.wl_storage
+                .storage
+                .address_gen
+                .clone()
+                .generate_address("TODO more randomness".as_bytes())
+        });
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let section_hash = tx.add_extra_section_from_hash(vp_hash, None);
+        let tx_data = init_account_data(section_hash);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        apply_tx(ctx(), tx).unwrap();
+
+        // The new address is reported in an event
+        let events = tx_host_env::with(|env| {
+            env.wl_storage.write_log.get_tx_events().to_vec()
+        });
+        let event = events
+            .iter()
+            .find(|event| event.event_type == account::INIT_ACCOUNT_EVENT)
+            .unwrap();
+        assert_eq!(
+            event.attributes.get("address"),
+            Some(&expected_address.to_string())
+        );
+
+        let vp_key = storage::Key::validity_predicate(&expected_address);
+        assert_eq!(
+            ctx().read_bytes(&vp_key).unwrap(),
+            Some(vp_hash.0.to_vec())
+        );
+        assert_eq!(
+            storage_api::account::public_keys(ctx(), &expected_address)
+                .unwrap(),
+            tx_data.public_keys
+        );
+        assert_eq!(
+            storage_api::account::threshold(ctx(), &expected_address).unwrap(),
+            Some(tx_data.threshold)
+        );
+    }
+
+    /// Test that initializing an account fails when the VP code section is
+    /// absent from the tx
+    #[test]
+    fn test_init_account_missing_vp_section() {
+        let vp_hash = setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        // Refer to the VP code hash without adding its section
+        let tx_data = init_account_data(vp_hash);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert_eq!(
+            *err.downcast::<transaction::TxWasmError>().unwrap(),
+            transaction::TxWasmError::MissingSection(vp_hash)
+        );
+        assert!(tx_host_env::with(|env| env
+            .wl_storage
+            .write_log
+            .get_tx_events()
+            .is_empty()));
+    }
+
+    /// Test that initializing an account with data that can't be decoded
+    /// fails with an invalid data error
+    #[test]
+    fn test_init_account_invalid_data() {
+        setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None).add_serialized_data(vec![1, 2, 3]);
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert_eq!(
+            *err.downcast::<transaction::TxWasmError>().unwrap(),
+            transaction::TxWasmError::InvalidData
+        );
+    }
+
+    /// Test that initializing an account fails when the VP code section is
+    /// not tagged as extra data
+    #[test]
+    fn test_init_account_mis_tagged_vp_section() {
+        let vp_hash = setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        // Refer to a data section instead of an extra data section
+        let section_hash = tx
+            .add_section(Section::Data(Data::new(vp_hash.serialize_to_vec())))
+            .get_hash();
+        let tx_data = init_account_data(section_hash);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        assert!(apply_tx(ctx(), tx).is_err());
+    }
+
+    fn init_account_tx(vp_hash: Hash, tx_data: InitAccount) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        let section_hash = tx.add_extra_section_from_hash(vp_hash, None);
+        let tx_data = InitAccount {
+            vp_code_hash: section_hash,
+            ..tx_data
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+        tx
+    }
+
+    fn public_keys() -> Vec<key::common::PublicKey> {
+        vec![
+            key::testing::keypair_1().ref_to(),
+            key::testing::keypair_2().ref_to(),
+            key::testing::keypair_3().ref_to(),
+        ]
+    }
+
+    /// Test initializing a 2-of-3 multisig account
+    #[test]
+    fn test_init_multisig_account() {
+        let vp_hash = setup();
+        let tx_data = InitAccount {
+            public_keys: public_keys(),
+            vp_code_hash: vp_hash,
+            threshold: 2,
+        };
+
+        apply_tx(ctx(), init_account_tx(vp_hash, tx_data)).unwrap();
+
+        let address = tx_host_env::with(|env| {
+            env.wl_storage.write_log.get_initialized_accounts()
+        })
+        .pop()
+        .unwrap();
+        assert_eq!(
+            storage_api::account::public_keys(ctx(), &address).unwrap(),
+            public_keys()
+        );
+        assert_eq!(
+            storage_api::account::threshold(ctx(), &address).unwrap(),
+            Some(2)
+        );
+    }
+
+    /// Test that a multisig account can't be initialized with a threshold of
+    /// zero or greater than the number of public keys
+    #[test]
+    fn test_init_multisig_account_invalid_threshold() {
+        for threshold in [0, 4] {
+            let vp_hash = setup();
+            let tx_data = InitAccount {
+                public_keys: public_keys(),
+                vp_code_hash: vp_hash,
+                threshold,
+            };
+
+            assert!(
+                apply_tx(ctx(), init_account_tx(vp_hash, tx_data)).is_err()
+            );
         }
     }
-    Ok(())
+
+    /// Test that a multisig account can't be initialized with the same public
+    /// key given twice
+    #[test]
+    fn test_init_multisig_account_duplicate_keys() {
+        let vp_hash = setup();
+        let mut keys = public_keys();
+        keys[2] = keys[0].clone();
+        let tx_data = InitAccount {
+            public_keys: keys,
+            vp_code_hash: vp_hash,
+            threshold: 2,
+        };
+
+        assert!(apply_tx(ctx(), init_account_tx(vp_hash, tx_data)).is_err());
+    }
 }
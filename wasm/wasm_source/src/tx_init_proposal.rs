@@ -13,8 +13,8 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
             .wrap_err("failed to decode InitProposalData")?;
 
     // Get the content from the referred to section
-    let content = tx
-        .get_section(&tx_data.content)
+    let content = ctx
+        .get_tx_section(&tx_data.content)
         .ok_or_err_msg("Missing proposal content")
         .map_err(|err| {
             ctx.set_commitment_sentinel();
@@ -31,7 +31,7 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
     let code_hash = tx_data.get_section_code_hash();
     let code = match code_hash {
         Some(hash) => Some(
-            tx.get_section(&hash)
+            ctx.get_tx_section(&hash)
                 .ok_or_err_msg("Missing proposal code")
                 .map_err(|err| {
                     ctx.set_commitment_sentinel();
@@ -51,3 +51,198 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
 
     governance::init_proposal(ctx, tx_data, content, code)
 }
+
+#[cfg(test)]
+mod tests {
+    use namada::core::ledger::governance::parameters::GovernanceParameters;
+    use namada::core::ledger::governance::storage::proposal::ProposalType;
+    use namada::ledger::governance::GovernanceVp;
+    use namada::types::hash::Hash;
+    use namada::types::transaction::TxSentinel;
+    use namada_tests::log::test;
+    use namada_tests::native_vp::TestNativeVpEnv;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::testing::established_address_1;
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::transaction::governance::InitProposalData;
+
+    use super::*;
+
+    /// Setup the tx env with the governance parameters and an author funded
+    /// with enough tokens for the proposal deposit. Returns the author and the
+    /// governance parameters.
+    fn setup() -> (Address, GovernanceParameters) {
+        let mut env = TestTxEnv::default();
+        let params = GovernanceParameters::default();
+        params.init_storage(&mut env.wl_storage).unwrap();
+        env.commit_tx_and_block();
+
+        let author = established_address_1();
+        env.spawn_accounts([&author]);
+        let native_token = env.wl_storage.storage.native_token.clone();
+        env.credit_tokens(
+            &author,
+            &native_token,
+            token::Amount::native_whole(1000),
+        );
+        tx_host_env::set(env);
+        (author, params)
+    }
+
+    fn init_proposal_data(
+        author: Address,
+        content: Hash,
+        code: Option<Hash>,
+    ) -> InitProposalData {
+        InitProposalData {
+            id: None,
+            content,
+            author,
+            r#type: ProposalType::Default(code),
+            voting_start_epoch: Epoch(3),
+            voting_end_epoch: Epoch(6),
+            grace_epoch: Epoch(12),
+        }
+    }
+
+    /// Validate the changes of the tx with the governance VP
+    fn validate_with_gov_vp(tx: Tx) -> bool {
+        let mut tx_env = tx_host_env::take();
+        tx_env.tx = tx;
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, address::GOV);
+        vp_env
+            .validate_tx(|ctx| GovernanceVp { ctx })
+            .expect("Governance VP must not fail")
+    }
+
+    /// Test creating a proposal with its content and code in extra data
+    /// sections
+    #[test]
+    fn test_init_proposal() {
+        let (author, params) = setup();
+        let content = br#"{"title": "A proposal"}"#.to_vec();
+        let code = vec![1_u8; 64];
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let content_hash = tx.add_extra_section(content.clone(), None).1;
+        let code_hash = tx.add_extra_section(code.clone(), None).1;
+        let tx_data =
+            init_proposal_data(author.clone(), content_hash, Some(code_hash));
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        apply_tx(ctx(), tx.clone()).unwrap();
+
+        let proposal_id = 0;
+        assert_eq!(
+            ctx()
+                .read_bytes(&gov_storage::keys::get_content_key(proposal_id))
+                .unwrap(),
+            Some(content)
+        );
+        assert_eq!(
+            ctx()
+                .read_bytes(&gov_storage::keys::get_proposal_code_key(
+                    proposal_id
+                ))
+                .unwrap(),
+            Some(code)
+        );
+        assert_eq!(
+            ctx()
+                .read::<Address>(&gov_storage::keys::get_author_key(
+                    proposal_id
+                ))
+                .unwrap(),
+            Some(author.clone())
+        );
+        let funds: Option<token::Amount> = ctx()
+            .read(&gov_storage::keys::get_funds_key(proposal_id))
+            .unwrap();
+        assert_eq!(funds, Some(params.min_proposal_fund));
+
+        // The deposit is taken from the author
+        let native_token = ctx().get_native_token().unwrap();
+        let balance =
+            storage_api::token::read_balance(ctx(), &native_token, &author)
+                .unwrap();
+        assert_eq!(
+            balance,
+            token::Amount::native_whole(1000) - params.min_proposal_fund
+        );
+
+        assert!(validate_with_gov_vp(tx));
+    }
+
+    /// Test that a proposal with a content larger than the maximum size is
+    /// rejected
+    #[test]
+    fn test_init_proposal_content_too_large() {
+        let (author, params) = setup();
+        let content = vec![0_u8; params.max_proposal_content_size as usize + 1];
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let content_hash = tx.add_extra_section(content, None).1;
+        let tx_data = init_proposal_data(author, content_hash, None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        assert!(apply_tx(ctx(), tx).is_err());
+        assert!(
+            ctx()
+                .read_bytes(&gov_storage::keys::get_content_key(0))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    /// Test that a proposal referring to a content section that is absent
+    /// from the tx is rejected
+    #[test]
+    fn test_init_proposal_missing_section() {
+        let (author, _params) = setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        // Refer to the content hash without adding its section
+        let tx_data =
+            init_proposal_data(author, Hash::sha256(b"missing content"), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        assert!(apply_tx(ctx(), tx).is_err());
+        tx_host_env::with(|env| {
+            assert!(matches!(env.sentinel, TxSentinel::InvalidCommitment))
+        });
+    }
+
+    /// Test that the governance VP rejects a proposal content that isn't the
+    /// one committed by the tx
+    #[test]
+    fn test_init_proposal_uncommitted_content() {
+        let (author, _params) = setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let content_hash = tx.add_extra_section(b"content".to_vec(), None).1;
+        let tx_data = init_proposal_data(author.clone(), content_hash, None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+        apply_tx(ctx(), tx).unwrap();
+
+        // Validate the changes against a tx committing to another content
+        let mut other_tx = Tx::new(ChainId::default(), None);
+        let content_hash = other_tx
+            .add_extra_section(b"other content".to_vec(), None)
+            .1;
+        let tx_data = init_proposal_data(author, content_hash, None);
+        other_tx
+            .add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+
+        assert!(!validate_with_gov_vp(other_tx));
+    }
+}
@@ -1,5 +1,9 @@
 //! A tx to initialize a new validator account with a given public keys and a
-//! validity predicates.
+//! validity predicates. The VP code is referenced by the hash of an extra data
+//! section of the tx and the tx must carry signatures of all the given keys to
+//! prove their possession. The consensus, Ethereum bridge and protocol keys
+//! must also come with proofs of possession made for the registering address
+//! of the validator.
 
 use namada_tx_prelude::transaction::pos::InitValidator;
 use namada_tx_prelude::*;
@@ -16,8 +20,8 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
     debug_log!("apply_tx called to init a new validator account");
 
     // Get the validator vp code from the extra section
-    let validator_vp_code_sec = signed
-        .get_section(&init_validator.validator_vp_code_hash)
+    let validator_vp_code_sec = ctx
+        .get_tx_section(&init_validator.validator_vp_code_hash)
         .ok_or_err_msg("validator vp section not found")
         .map_err(|err| {
             ctx.set_commitment_sentinel();
@@ -30,6 +34,17 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
             err
         })?;
 
+    // Check that the validator keys are proven for the registering address,
+    // so that they cannot be copied from another validator
+    init_validator
+        .verify_key_proofs()
+        .wrap_err("Validator key proof of possession verification failed")
+        .map_err(|err| {
+            debug_log!("{}", err);
+            ctx.set_commitment_sentinel();
+            err
+        })?;
+
     // Check that the tx has been signed with all the keys to be used for the
     // validator account
     let mut all_pks = init_validator.account_keys.clone();
@@ -41,24 +56,312 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
         init_validator.eth_hot_key.clone(),
     ));
     all_pks.push(init_validator.protocol_key.clone());
-    if !matches!(verify_signatures_of_pks(ctx, &signed, all_pks), Ok(true)) {
+    if !verify_signatures_of_pks(ctx, &signed, all_pks)? {
         debug_log!("Keys ownership signature verification failed");
-        panic!()
+        ctx.set_commitment_sentinel();
+        return Err(Error::new_const(
+            "Keys ownership signature verification failed",
+        ));
     }
 
     // Register the validator in PoS
-    match ctx.init_validator(
-        init_validator,
-        validator_vp_code_sec.code.hash(),
-        &validator_vp_code_sec.tag,
-    ) {
-        Ok(validator_address) => {
-            debug_log!("Created validator {}", validator_address.encode(),)
-        }
-        Err(err) => {
+    let validator_address = ctx
+        .init_validator(
+            init_validator,
+            validator_vp_code_sec.code.hash(),
+            &validator_vp_code_sec.tag,
+        )
+        .map_err(|err| {
             debug_log!("Validator creation failed with: {}", err);
-            panic!()
+            err
+        })?;
+    debug_log!("Created validator {}", validator_address.encode());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::proof_of_stake::types::GenesisValidator;
+    use namada::proof_of_stake::{
+        is_validator, read_pos_params, validator_consensus_key_handle,
+        validator_protocol_key_handle,
+    };
+    use namada::proto::{Section, Signature};
+    use namada::types::account::AccountPublicKeysMap;
+    use namada::types::dec::Dec;
+    use namada::types::hash::Hash;
+    use namada::types::storage::Epoch;
+    use namada_test_utils::TestWasms;
+    use namada_tests::log::test;
+    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::key::pop::prove_possession;
+    use namada_tx_prelude::key::{common, ed25519, secp256k1, RefTo};
+    use namada_tx_prelude::transaction::pos::ValidatorKeyProofs;
+    use namada_tx_prelude::transaction::TxSentinel;
+
+    use super::*;
+
+    /// Setup PoS with a single genesis validator whose consensus key is
+    /// `keypair_1` and store the code of the always true VP on chain. Returns
+    /// the hash of the stored VP code.
+    fn setup() -> Hash {
+        let genesis_validators = [GenesisValidator {
+            address: address::testing::established_address_1(),
+            tokens: token::Amount::native_whole(1_000),
+            consensus_key: key::testing::keypair_1().ref_to(),
+            protocol_key: key::testing::keypair_2().ref_to(),
+            eth_cold_key: gen_secp256k1_keypair().ref_to(),
+            eth_hot_key: gen_secp256k1_keypair().ref_to(),
+            commission_rate: Dec::new(5, 2).unwrap(),
+            max_commission_rate_change: Dec::new(1, 2).unwrap(),
+            metadata: Default::default(),
+        }];
+        init_pos(&genesis_validators[..], &Default::default(), Epoch(0));
+
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let vp_hash = Hash::sha256(&vp_code);
+        tx_host_env::with(|env| env.store_wasm_code(vp_code));
+        vp_hash
+    }
+
+    fn gen_secp256k1_keypair() -> common::SecretKey {
+        common::SecretKey::Secp256k1(key::testing::gen_keypair::<
+            secp256k1::SigScheme,
+        >())
+    }
+
+    fn secp256k1_pk(sk: &common::SecretKey) -> secp256k1::PublicKey {
+        match sk.ref_to() {
+            common::PublicKey::Secp256k1(pk) => pk,
+            _ => panic!("Expected a secp256k1 key"),
         }
     }
-    Ok(())
+
+    /// The keys of a new validator with the given consensus key, in the order
+    /// of the account, consensus, Ethereum cold, Ethereum hot and protocol
+    /// key
+    fn validator_keys(
+        consensus_key: common::SecretKey,
+    ) -> [common::SecretKey; 5] {
+        [
+            key::testing::keypair_3(),
+            consensus_key,
+            gen_secp256k1_keypair(),
+            gen_secp256k1_keypair(),
+            key::testing::keypair_4(),
+        ]
+    }
+
+    /// Proofs of possession of the given validator keys for the given
+    /// address
+    fn key_proofs(
+        keys: &[common::SecretKey; 5],
+        address: &Address,
+    ) -> ValidatorKeyProofs {
+        let [_, consensus_key, eth_cold_key, eth_hot_key, protocol_key] = keys;
+        ValidatorKeyProofs::new(
+            address,
+            consensus_key,
+            eth_cold_key,
+            eth_hot_key,
+            protocol_key,
+        )
+    }
+
+    /// Proofs of possession of the given validator keys for the registering
+    /// address, the implicit address of the account key
+    fn valid_key_proofs(keys: &[common::SecretKey; 5]) -> ValidatorKeyProofs {
+        key_proofs(keys, &Address::from(&keys[0].ref_to()))
+    }
+
+    /// Build an init validator tx with the given keys and proofs of their
+    /// possession, and a signature section from the given signing keys. The
+    /// tx is also set in the tx env for the signature verification.
+    fn init_validator_tx(
+        vp_hash: Hash,
+        keys: &[common::SecretKey; 5],
+        key_proofs: ValidatorKeyProofs,
+        signing_keys: Vec<common::SecretKey>,
+    ) -> (Tx, InitValidator) {
+        let [
+            account_key,
+            consensus_key,
+            eth_cold_key,
+            eth_hot_key,
+            protocol_key,
+        ] = keys;
+        let mut tx = Tx::new(ChainId::default(), None);
+        let validator_vp_code_hash =
+            tx.add_extra_section_from_hash(vp_hash, None);
+        let init_validator = InitValidator {
+            account_keys: vec![account_key.ref_to()],
+            threshold: 1,
+            consensus_key: consensus_key.ref_to(),
+            eth_cold_key: secp256k1_pk(eth_cold_key),
+            eth_hot_key: secp256k1_pk(eth_hot_key),
+            protocol_key: protocol_key.ref_to(),
+            commission_rate: Dec::new(5, 2).unwrap(),
+            max_commission_rate_change: Dec::new(1, 2).unwrap(),
+            email: "validator@namada.net".to_string(),
+            description: None,
+            website: None,
+            discord_handle: None,
+            validator_vp_code_hash,
+            key_proofs,
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(init_validator.serialize_to_vec());
+        let pks_map = AccountPublicKeysMap::from_iter(
+            keys.iter().map(|key| key.ref_to()),
+        );
+        tx.add_section(Section::Signature(Signature::new(
+            vec![tx.raw_header_hash()],
+            pks_map.index_secret_keys(signing_keys),
+            None,
+        )));
+        tx_host_env::set_tx(tx.clone());
+        (tx, init_validator)
+    }
+
+    /// Test the registration of a validator with all the keys signed
+    #[test]
+    fn test_init_validator() {
+        let vp_hash = setup();
+        // The address that the tx is expected to derive
+        let expected_address = tx_host_env::with(|env| {
+            env.wl_storage
+                .storage
+                .address_gen
+                .clone()
+                .generate_address("TODO more randomness".as_bytes())
+        });
+
+        let keys = validator_keys(key::testing::keypair_2());
+        let (tx, init_validator) = init_validator_tx(
+            vp_hash,
+            &keys,
+            valid_key_proofs(&keys),
+            keys.to_vec(),
+        );
+        apply_tx(ctx(), tx).unwrap();
+
+        assert!(is_validator(ctx(), &expected_address).unwrap());
+        let params = read_pos_params(ctx()).unwrap();
+        let pipeline_epoch =
+            ctx().get_block_epoch().unwrap() + params.pipeline_len;
+        assert_eq!(
+            validator_consensus_key_handle(&expected_address)
+                .get(ctx(), pipeline_epoch, &params)
+                .unwrap(),
+            Some(init_validator.consensus_key)
+        );
+        assert_eq!(
+            validator_protocol_key_handle(&expected_address)
+                .get(ctx(), pipeline_epoch, &params)
+                .unwrap(),
+            Some(init_validator.protocol_key)
+        );
+        assert_eq!(
+            storage_api::account::public_keys(ctx(), &expected_address)
+                .unwrap(),
+            init_validator.account_keys
+        );
+    }
+
+    /// Test that the registration fails without a signature of the consensus
+    /// key
+    #[test]
+    fn test_init_validator_missing_key_signature() {
+        let vp_hash = setup();
+
+        let keys = validator_keys(key::testing::keypair_2());
+        let signing_keys = keys
+            .iter()
+            .filter(|key| key.ref_to() != keys[1].ref_to())
+            .cloned()
+            .collect();
+        let (tx, _) = init_validator_tx(
+            vp_hash,
+            &keys,
+            valid_key_proofs(&keys),
+            signing_keys,
+        );
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert!(err.to_string().contains("signature verification failed"));
+        tx_host_env::with(|env| {
+            assert!(matches!(env.sentinel, TxSentinel::InvalidCommitment))
+        });
+    }
+
+    /// Test that the registration fails with a consensus key that is already
+    /// used by another validator
+    #[test]
+    fn test_init_validator_duplicate_consensus_key() {
+        let vp_hash = setup();
+
+        // The consensus key of the genesis validator
+        let keys = validator_keys(key::testing::keypair_1());
+        let (tx, _) = init_validator_tx(
+            vp_hash,
+            &keys,
+            valid_key_proofs(&keys),
+            keys.to_vec(),
+        );
+
+        assert!(apply_tx(ctx(), tx).is_err());
+        tx_host_env::with(|env| {
+            assert!(matches!(env.sentinel, TxSentinel::None))
+        });
+    }
+
+    /// Test that the registration fails with proofs of possession of the
+    /// validator keys made for another address
+    #[test]
+    fn test_init_validator_key_proofs_for_other_address() {
+        let vp_hash = setup();
+
+        let keys = validator_keys(key::testing::keypair_2());
+        let key_proofs =
+            key_proofs(&keys, &address::testing::established_address_2());
+        let (tx, _) =
+            init_validator_tx(vp_hash, &keys, key_proofs, keys.to_vec());
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert!(err.to_string().contains("proof of possession"));
+        tx_host_env::with(|env| {
+            assert!(matches!(env.sentinel, TxSentinel::InvalidCommitment))
+        });
+    }
+
+    /// Test that the registration fails with a protocol key copied from
+    /// another validator, without a proof of its possession
+    #[test]
+    fn test_init_validator_copied_key_without_proof() {
+        let vp_hash = setup();
+
+        // The protocol key of the genesis validator, which cannot be proven
+        // without its secret key
+        let mut keys = validator_keys(common::SecretKey::Ed25519(
+            key::testing::gen_keypair::<ed25519::SigScheme>(),
+        ));
+        keys[4] = key::testing::keypair_2();
+        let mut key_proofs = valid_key_proofs(&keys);
+        key_proofs.protocol_key = prove_possession(
+            &key::testing::keypair_4(),
+            &Address::from(&keys[0].ref_to()),
+        );
+        let signing_keys = keys[..4].to_vec();
+        let (tx, _) =
+            init_validator_tx(vp_hash, &keys, key_proofs, signing_keys);
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert!(err.to_string().contains("proof of possession"));
+        tx_host_env::with(|env| {
+            assert!(matches!(env.sentinel, TxSentinel::InvalidCommitment))
+        });
+    }
 }
@@ -1,6 +1,7 @@
 //! A tx for token transfer.
-//! This tx uses `token::Transfer` wrapped inside `SignedTxData`
-//! as its input as declared in `shared` crate.
+//! This tx uses `token::Transfer` as its input as declared in `shared` crate.
+//! The tx is authorized by the signature sections of the tx. The tx's memo, if
+//! any, is reported in the emitted `transfer` event.
 
 use namada_tx_prelude::*;
 
@@ -14,6 +15,7 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
     let transfer = token::Transfer::try_from_slice(&data[..])
         .wrap_err("failed to decode token::Transfer")?;
     debug_log!("apply_tx called with transfer: {:#?}", transfer);
+    let memo = token::memo(&signed)?;
 
     token::transfer(
         ctx,
@@ -27,9 +29,8 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
         .shielded
         .as_ref()
         .map(|hash| {
-            signed
-                .get_section(hash)
-                .and_then(|x| x.as_ref().masp_tx())
+            ctx.get_tx_section(hash)
+                .and_then(|x| x.masp_tx())
                 .ok_or_err_msg("unable to find shielded section")
                 .map_err(|err| {
                     ctx.set_commitment_sentinel();
@@ -40,5 +41,120 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
     if let Some(shielded) = shielded {
         token::handle_masp_tx(ctx, &transfer, &shielded)?;
     }
-    Ok(())
+    token::emit_transfer_event(ctx, &transfer, memo.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::proto::MAX_MEMO_LEN;
+    use namada_tests::log::test;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+
+    use super::*;
+
+    /// Setup two accounts with some tokens credited to the first one and
+    /// return a transfer between them
+    fn setup() -> token::Transfer {
+        let mut env = TestTxEnv::default();
+        let source = established_address_1();
+        let target = established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::from_uint(1_000, 0).unwrap();
+        env.spawn_accounts([&source, &target, &token]);
+        env.credit_tokens(&source, &token, amount);
+        storage_api::token::write_denom(
+            &mut env.wl_storage,
+            &token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+        tx_host_env::set(env);
+        token::Transfer {
+            source,
+            target,
+            token,
+            amount: token::DenominatedAmount {
+                amount,
+                denom: token::NATIVE_MAX_DECIMAL_PLACES.into(),
+            },
+            key: None,
+            shielded: None,
+        }
+    }
+
+    fn transfer_tx(transfer: &token::Transfer, memo: Option<&[u8]>) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(transfer.serialize_to_vec());
+        if let Some(memo) = memo {
+            tx.add_memo(memo);
+        }
+        tx
+    }
+
+    fn transfer_events() -> Vec<event::TxEvent> {
+        tx_host_env::with(|env| {
+            env.wl_storage
+                .write_log
+                .get_tx_events()
+                .iter()
+                .filter(|event| event.event_type == token::TRANSFER_EVENT)
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Test that the memo of a transfer is reported in its event
+    #[test]
+    fn test_transfer_with_memo() {
+        let transfer = setup();
+
+        apply_tx(ctx(), transfer_tx(&transfer, Some(b"a memo"))).unwrap();
+
+        let events = transfer_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].attributes.get("memo"),
+            Some(&"61206d656d6f".to_string())
+        );
+        assert_eq!(
+            events[0].attributes.get("source"),
+            Some(&transfer.source.to_string())
+        );
+    }
+
+    /// Test that a transfer with a memo which is too long fails without
+    /// moving any tokens
+    #[test]
+    fn test_transfer_with_too_long_memo() {
+        let transfer = setup();
+        let balance_key = token::balance_key(&transfer.token, &transfer.source);
+        let balance_pre: token::Amount =
+            ctx().read(&balance_key).unwrap().unwrap();
+
+        let memo = vec![0; MAX_MEMO_LEN + 1];
+        assert!(apply_tx(ctx(), transfer_tx(&transfer, Some(&memo))).is_err());
+
+        let balance_post: token::Amount =
+            ctx().read(&balance_key).unwrap().unwrap();
+        assert_eq!(balance_post, balance_pre);
+        assert!(transfer_events().is_empty());
+    }
+
+    /// Test that a transfer without a memo emits an event without a memo
+    #[test]
+    fn test_transfer_without_memo() {
+        let transfer = setup();
+
+        apply_tx(ctx(), transfer_tx(&transfer, None)).unwrap();
+
+        let events = transfer_events();
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].attributes.contains_key("memo"));
+    }
 }
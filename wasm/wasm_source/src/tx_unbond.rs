@@ -5,13 +5,7 @@ use namada_tx_prelude::*;
 
 #[transaction(gas = 2645941)]
 fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
-    let signed = tx_data;
-    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
-        ctx.set_commitment_sentinel();
-        err
-    })?;
-    let unbond = transaction::pos::Unbond::try_from_slice(&data[..])
-        .wrap_err("failed to decode Unbond")?;
+    let unbond = proof_of_stake::read_bond_data(ctx, &tx_data)?;
 
     ctx.unbond_tokens(
         unbond.source.as_ref(),
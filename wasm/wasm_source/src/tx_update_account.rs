@@ -1,6 +1,9 @@
 //! A tx for updating an account's validity predicate.
-//! This tx wraps the validity predicate inside `SignedTxData` as
-//! its input as declared in `shared` crate.
+//! This tx uses `UpdateAccount` as its input as declared in `shared` crate,
+//! with the VP code in an extra data section. The input may also be a batch of
+//! updates of several accounts, which are all applied in the same tx.
+//! Every VP update is recorded in the account's VP history and reported in a
+//! `vp_update` event.
 
 use namada_tx_prelude::key::pks_handle;
 use namada_tx_prelude::*;
@@ -12,20 +15,50 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
         ctx.set_commitment_sentinel();
         err
     })?;
-    let tx_data =
-        transaction::account::UpdateAccount::try_from_slice(&data[..])
-            .wrap_err("failed to decode UpdateAccount")?;
+    let updates =
+        match transaction::account::UpdateAccount::try_from_slice(&data[..]) {
+            Ok(tx_data) => vec![tx_data],
+            Err(_) => {
+                transaction::account::UpdateAccountBatch::try_from_slice(
+                    &data[..],
+                )
+                .map_err(|_| {
+                    Error::new(transaction::TxWasmError::InvalidData)
+                })?
+                .updates
+            }
+        };
+    if updates.is_empty() {
+        // The batch of account updates is empty
+        return Err(Error::new(transaction::TxWasmError::InvalidData));
+    }
+
+    for tx_data in updates {
+        update_account(ctx, &signed, tx_data)?;
+    }
 
+    Ok(())
+}
+
+fn update_account(
+    ctx: &mut Ctx,
+    signed: &Tx,
+    tx_data: transaction::account::UpdateAccount,
+) -> TxResult {
     let owner = &tx_data.addr;
     debug_log!("update VP for: {:#?}", tx_data.addr);
 
+    if tx_data.vp_code_hash.is_some() && tx_data.stored_vp_code_hash.is_some() {
+        // Only one of a VP code section or a stored VP code hash can be given
+        return Err(Error::new(transaction::TxWasmError::InvalidData));
+    }
+
     if let Some(hash) = tx_data.vp_code_hash {
-        let vp_code_sec = signed
-            .get_section(&hash)
-            .ok_or_err_msg("vp code section not found")
-            .map_err(|err| {
+        let vp_code_sec = ctx
+            .get_tx_section(&hash)
+            .ok_or_else(|| {
                 ctx.set_commitment_sentinel();
-                err
+                Error::new(transaction::TxWasmError::MissingSection(hash))
             })?
             .extra_data_sec()
             .ok_or_err_msg("vp code section must be tagged as extra")
@@ -34,10 +67,28 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
                 err
             })?;
 
-        ctx.update_validity_predicate(
+        account::update_validity_predicate(
+            ctx,
             owner,
             vp_code_sec.code.hash(),
             &vp_code_sec.tag,
+            signed.header_hash(),
+        )?;
+    }
+
+    if let Some(code_hash) = tx_data.stored_vp_code_hash {
+        let code_key = storage::Key::wasm_code(&code_hash);
+        if !ctx.has_key(&code_key)? {
+            return Err(Error::new_const(
+                "The given VP code hash is not stored on chain",
+            ));
+        }
+        account::update_validity_predicate(
+            ctx,
+            owner,
+            code_hash,
+            &None,
+            signed.header_hash(),
         )?;
     }
 
@@ -54,5 +105,347 @@ fn apply_tx(ctx: &mut Ctx, tx: Tx) -> TxResult {
         }
     }
 
-    Ok(())
+    // Make sure that the VP of every updated account gets to validate the
+    // whole tx
+    ctx.insert_verifier(owner)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::types::account::VpUpdate;
+    use namada::types::hash::Hash;
+    use namada_test_utils::TestWasms;
+    use namada_tests::log::test;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::transaction::account::{
+        UpdateAccount, UpdateAccountBatch,
+    };
+
+    use super::*;
+
+    /// Setup an account and store the code of the always true VP on chain.
+    /// Returns the account's address and the hash of the stored VP code.
+    fn setup() -> (Address, Hash) {
+        let mut env = TestTxEnv::default();
+        let addr = established_address_1();
+        env.spawn_accounts([&addr]);
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let vp_hash = Hash::sha256(&vp_code);
+        env.store_wasm_code(vp_code);
+        tx_host_env::set(env);
+        (addr, vp_hash)
+    }
+
+    fn update_account_data(addr: Address) -> UpdateAccount {
+        UpdateAccount {
+            addr,
+            vp_code_hash: None,
+            stored_vp_code_hash: None,
+            public_keys: vec![],
+            threshold: None,
+        }
+    }
+
+    fn read_vp(addr: &Address) -> Option<Vec<u8>> {
+        ctx()
+            .read_bytes(&storage::Key::validity_predicate(addr))
+            .unwrap()
+    }
+
+    /// Test updating the VP of an account to a VP stored on chain by its hash
+    #[test]
+    fn test_update_vp_by_stored_hash() {
+        let (addr, vp_hash) = setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = UpdateAccount {
+            stored_vp_code_hash: Some(vp_hash),
+            ..update_account_data(addr.clone())
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+
+        apply_tx(ctx(), tx).unwrap();
+        assert_eq!(read_vp(&addr), Some(vp_hash.0.to_vec()));
+    }
+
+    /// Test that updating the VP of an account by a hash which is not stored
+    /// on chain fails without modifying the account
+    #[test]
+    fn test_update_vp_by_missing_hash() {
+        let (addr, _) = setup();
+        let vp_pre = read_vp(&addr);
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = UpdateAccount {
+            stored_vp_code_hash: Some(Hash::sha256(b"missing VP code")),
+            ..update_account_data(addr.clone())
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+
+        assert!(apply_tx(ctx(), tx).is_err());
+        assert_eq!(read_vp(&addr), vp_pre);
+    }
+
+    /// Test updating the VP of an account from a VP code section
+    #[test]
+    fn test_update_vp_by_code_section() {
+        let (addr, vp_hash) = setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let section_hash = tx.add_extra_section_from_hash(vp_hash, None);
+        let tx_data = UpdateAccount {
+            vp_code_hash: Some(section_hash),
+            ..update_account_data(addr.clone())
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        apply_tx(ctx(), tx).unwrap();
+        assert_eq!(read_vp(&addr), Some(vp_hash.0.to_vec()));
+    }
+
+    /// Test that a tx with data that can't be decoded fails with an invalid
+    /// data error
+    #[test]
+    fn test_update_vp_invalid_data() {
+        setup();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None).add_serialized_data(vec![1, 2, 3]);
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert_eq!(
+            *err.downcast::<transaction::TxWasmError>().unwrap(),
+            transaction::TxWasmError::InvalidData
+        );
+    }
+
+    /// Test that updating the VP of an account from a VP code section which
+    /// is not in the tx fails with a missing section error
+    #[test]
+    fn test_update_vp_missing_code_section() {
+        let (addr, _) = setup();
+        let vp_pre = read_vp(&addr);
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let section_hash = Hash::sha256(b"missing VP code section");
+        let tx_data = UpdateAccount {
+            vp_code_hash: Some(section_hash),
+            ..update_account_data(addr.clone())
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx_host_env::set_tx(tx.clone());
+
+        let err = apply_tx(ctx(), tx).unwrap_err();
+        assert_eq!(
+            *err.downcast::<transaction::TxWasmError>().unwrap(),
+            transaction::TxWasmError::MissingSection(section_hash)
+        );
+        assert_eq!(read_vp(&addr), vp_pre);
+    }
+
+    /// Test updating the VPs of several accounts in a single tx
+    #[test]
+    fn test_update_vp_batch() {
+        let (addr, vp_hash) = setup();
+        let addrs = [addr, established_address_2(), established_address_3()];
+        tx_host_env::with(|env| env.spawn_accounts(&addrs[1..]));
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = UpdateAccountBatch {
+            updates: addrs
+                .iter()
+                .map(|addr| UpdateAccount {
+                    stored_vp_code_hash: Some(vp_hash),
+                    ..update_account_data(addr.clone())
+                })
+                .collect(),
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+
+        apply_tx(ctx(), tx).unwrap();
+        let verifiers = tx_host_env::with(|env| env.verifiers.clone());
+        for addr in &addrs {
+            assert_eq!(read_vp(addr), Some(vp_hash.0.to_vec()));
+            // The VP of every updated account must validate the tx
+            assert!(verifiers.contains(addr));
+        }
+    }
+
+    /// Test that a batch of account updates containing an invalid update
+    /// fails as a whole, so that none of the accounts get updated
+    #[test]
+    fn test_update_vp_batch_reverts() {
+        let (addr, vp_hash) = setup();
+        let addrs = [addr, established_address_2(), established_address_3()];
+        tx_host_env::with(|env| env.spawn_accounts(&addrs[1..]));
+        let vps_pre: Vec<_> = addrs.iter().map(read_vp).collect();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        let mut updates: Vec<_> = addrs
+            .iter()
+            .map(|addr| UpdateAccount {
+                stored_vp_code_hash: Some(vp_hash),
+                ..update_account_data(addr.clone())
+            })
+            .collect();
+        // The last update refers to a VP code which is not on chain
+        updates[2].stored_vp_code_hash = Some(Hash::sha256(b"missing VP code"));
+        tx.add_code(vec![], None).add_serialized_data(
+            UpdateAccountBatch { updates }.serialize_to_vec(),
+        );
+
+        assert!(apply_tx(ctx(), tx).is_err());
+        // A failed tx is dropped by the protocol
+        tx_host_env::with(|env| env.wl_storage.drop_tx());
+        let vps_post: Vec<_> = addrs.iter().map(read_vp).collect();
+        assert_eq!(vps_post, vps_pre);
+    }
+
+    /// Apply a tx updating the VP of the given account to a stored VP at
+    /// the given block height and commit it. Returns the tx's hash and the
+    /// events it emitted.
+    fn update_vp_at(
+        addr: &Address,
+        vp_hash: Hash,
+        height: u64,
+    ) -> (Hash, Vec<event::TxEvent>) {
+        tx_host_env::with(|env| {
+            env.wl_storage.storage.block.height = BlockHeight(height)
+        });
+        let mut tx = Tx::new(ChainId::default(), None);
+        let tx_data = UpdateAccount {
+            stored_vp_code_hash: Some(vp_hash),
+            ..update_account_data(addr.clone())
+        };
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+
+        apply_tx(ctx(), tx.clone()).unwrap();
+        let events = tx_host_env::with(|env| {
+            let events = env
+                .wl_storage
+                .write_log
+                .get_tx_events()
+                .to_vec();
+            env.wl_storage.commit_tx();
+            events
+        });
+        (tx.header_hash(), events)
+    }
+
+    /// Test that VP updates are recorded in the account's VP history and
+    /// reported in events
+    #[test]
+    fn test_update_vp_history() {
+        let (addr, vp_hash) = setup();
+        let other_vp_code = TestWasms::VpAlwaysFalse.read_bytes();
+        let other_vp_hash = Hash::sha256(&other_vp_code);
+        tx_host_env::with(|env| env.store_wasm_code(other_vp_code));
+
+        let (tx_hash_1, events_1) = update_vp_at(&addr, vp_hash, 1);
+        let (tx_hash_2, events_2) = update_vp_at(&addr, other_vp_hash, 2);
+
+        let history = storage_api::account::vp_history(ctx(), &addr).unwrap();
+        assert_eq!(
+            history,
+            vec![
+                (
+                    BlockHeight(1),
+                    VpUpdate {
+                        old_vp_code_hash: None,
+                        new_vp_code_hash: vp_hash,
+                        tx_hash: tx_hash_1,
+                    }
+                ),
+                (
+                    BlockHeight(2),
+                    VpUpdate {
+                        old_vp_code_hash: Some(vp_hash),
+                        new_vp_code_hash: other_vp_hash,
+                        tx_hash: tx_hash_2,
+                    }
+                ),
+            ]
+        );
+
+        let event = |attributes: &[(&str, String)]| event::TxEvent {
+            event_type: account::VP_UPDATE_EVENT.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        };
+        assert_eq!(
+            events_1,
+            vec![event(&[
+                ("address", addr.to_string()),
+                ("new_vp_code_hash", vp_hash.to_string()),
+            ])]
+        );
+        assert_eq!(
+            events_2,
+            vec![event(&[
+                ("address", addr.to_string()),
+                ("old_vp_code_hash", vp_hash.to_string()),
+                ("new_vp_code_hash", other_vp_hash.to_string()),
+            ])]
+        );
+    }
+
+    /// Test that the VP history of an account is pruned to the last
+    /// `MAX_VP_HISTORY_LEN` updates
+    #[test]
+    fn test_update_vp_history_is_pruned() {
+        let (addr, vp_hash) = setup();
+        let max_len = storage_api::account::MAX_VP_HISTORY_LEN;
+
+        for height in 1..=max_len + 2 {
+            update_vp_at(&addr, vp_hash, height);
+        }
+
+        let heights: Vec<_> = storage_api::account::vp_history(ctx(), &addr)
+            .unwrap()
+            .into_iter()
+            .map(|(height, _)| height.0)
+            .collect();
+        assert_eq!(heights, (3..=max_len + 2).collect::<Vec<_>>());
+    }
+
+    /// Test that several updates of a VP in the same block are recorded in a
+    /// single history entry
+    #[test]
+    fn test_update_vp_history_same_block() {
+        let (addr, vp_hash) = setup();
+        let other_vp_code = TestWasms::VpAlwaysFalse.read_bytes();
+        let other_vp_hash = Hash::sha256(&other_vp_code);
+        tx_host_env::with(|env| env.store_wasm_code(other_vp_code));
+
+        update_vp_at(&addr, vp_hash, 1);
+        update_vp_at(&addr, other_vp_hash, 2);
+        let (tx_hash, _) = update_vp_at(&addr, vp_hash, 2);
+
+        let history = storage_api::account::vp_history(ctx(), &addr).unwrap();
+        assert_eq!(
+            history.last(),
+            Some(&(
+                BlockHeight(2),
+                VpUpdate {
+                    old_vp_code_hash: Some(vp_hash),
+                    new_vp_code_hash: vp_hash,
+                    tx_hash,
+                }
+            ))
+        );
+    }
 }
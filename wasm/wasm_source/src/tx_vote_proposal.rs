@@ -17,3 +17,199 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
 
     governance::vote_proposal(ctx, tx_data)
 }
+
+#[cfg(test)]
+mod tests {
+    use namada::core::ledger::governance::storage::proposal::ProposalType;
+    use namada::core::ledger::governance::storage::vote::{
+        StorageProposalVote, VoteType,
+    };
+    use namada::ledger::governance::GovernanceVp;
+    use namada::proof_of_stake::{OwnedPosParams, bond_tokens};
+    use namada_tests::log::test;
+    use namada_tests::native_vp::TestNativeVpEnv;
+    use namada_tests::native_vp::pos::{genesis_validator, init_pos};
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::key::RefTo;
+    use namada_tx_prelude::transaction::governance::VoteProposalData;
+
+    use super::*;
+
+    const PROPOSAL_ID: u64 = 0;
+
+    /// Setup PoS with two genesis validators, a delegator bonded to the first
+    /// validator and a proposal voted from epoch 0 to epoch 3. The second
+    /// validator is also bonded to the first one. Returns the validators and
+    /// the delegator.
+    fn setup() -> (Address, Address, Address) {
+        let validator = established_address_1();
+        let other_validator = established_address_2();
+        let delegator = established_address_3();
+        let pos_params = OwnedPosParams {
+            validator_stake_threshold: token::Amount::zero(),
+            ..Default::default()
+        };
+        init_pos(
+            &[
+                genesis_validator(
+                    validator.clone(),
+                    key::testing::keypair_1().ref_to(),
+                ),
+                genesis_validator(
+                    other_validator.clone(),
+                    key::testing::keypair_2().ref_to(),
+                ),
+            ],
+            &pos_params,
+            Epoch(0),
+        );
+
+        tx_host_env::with(|env| {
+            env.spawn_accounts([&delegator]);
+            let native_token = env.wl_storage.storage.native_token.clone();
+            let amount = token::Amount::native_whole(100);
+            for source in [&delegator, &other_validator] {
+                env.credit_tokens(source, &native_token, amount);
+                // Bond without the pipeline offset for the bond to count in
+                // the voting start epoch
+                bond_tokens(
+                    &mut env.wl_storage,
+                    Some(source),
+                    &validator,
+                    amount,
+                    Epoch(0),
+                    Some(0),
+                )
+                .unwrap();
+            }
+
+            let counter_key = gov_storage::keys::get_counter_key();
+            env.wl_storage.write(&counter_key, PROPOSAL_ID + 1).unwrap();
+            env.wl_storage
+                .write(
+                    &gov_storage::keys::get_voting_start_epoch_key(PROPOSAL_ID),
+                    Epoch(0),
+                )
+                .unwrap();
+            env.wl_storage
+                .write(
+                    &gov_storage::keys::get_voting_end_epoch_key(PROPOSAL_ID),
+                    Epoch(3),
+                )
+                .unwrap();
+            env.wl_storage
+                .write(
+                    &gov_storage::keys::get_proposal_type_key(PROPOSAL_ID),
+                    ProposalType::Default(None),
+                )
+                .unwrap();
+            env.commit_tx_and_block();
+        });
+        (validator, other_validator, delegator)
+    }
+
+    fn vote_tx(voter: Address, delegations: Vec<Address>) -> Tx {
+        let tx_data = VoteProposalData {
+            id: PROPOSAL_ID,
+            vote: StorageProposalVote::Yay(VoteType::Default),
+            voter,
+            delegations,
+        };
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(tx_data.serialize_to_vec());
+        tx
+    }
+
+    /// Validate the changes of the tx with the governance VP
+    fn validate_with_gov_vp() -> bool {
+        let tx_env = tx_host_env::take();
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, address::GOV);
+        vp_env
+            .validate_tx(|ctx| GovernanceVp { ctx })
+            .expect("Governance VP must not fail")
+    }
+
+    /// Test a validator voting within the voting window
+    #[test]
+    fn test_vote_proposal_validator() {
+        let (validator, _other_validator, _delegator) = setup();
+
+        let tx = vote_tx(validator.clone(), vec![validator.clone()]);
+        apply_tx(ctx(), tx).unwrap();
+
+        let vote_key = gov_storage::keys::get_vote_proposal_key(
+            PROPOSAL_ID,
+            validator.clone(),
+            validator,
+        );
+        let vote: Option<StorageProposalVote> = ctx().read(&vote_key).unwrap();
+        assert_eq!(vote, Some(StorageProposalVote::Yay(VoteType::Default)));
+
+        assert!(validate_with_gov_vp());
+    }
+
+    /// Test a delegator overriding the vote of its validator
+    #[test]
+    fn test_vote_proposal_delegator() {
+        let (validator, _other_validator, delegator) = setup();
+
+        let tx = vote_tx(delegator.clone(), vec![validator.clone()]);
+        apply_tx(ctx(), tx).unwrap();
+
+        let vote_key = gov_storage::keys::get_vote_proposal_key(
+            PROPOSAL_ID,
+            delegator,
+            validator,
+        );
+        assert!(ctx().has_key(&vote_key).unwrap());
+
+        assert!(validate_with_gov_vp());
+    }
+
+    /// Test that a delegator can't vote for a validator it isn't bonded to
+    #[test]
+    fn test_vote_proposal_delegator_without_bond() {
+        let (_validator, other_validator, delegator) = setup();
+
+        let tx = vote_tx(delegator, vec![other_validator]);
+        apply_tx(ctx(), tx).unwrap();
+
+        assert!(!validate_with_gov_vp());
+    }
+
+    /// Test that a validator can't vote as a delegator of another validator
+    #[test]
+    fn test_vote_proposal_validator_as_delegator() {
+        let (validator, other_validator, _delegator) = setup();
+
+        let tx = vote_tx(other_validator, vec![validator]);
+        apply_tx(ctx(), tx).unwrap();
+
+        assert!(!validate_with_gov_vp());
+    }
+
+    /// Test that a vote after the voting window is closed is rejected
+    #[test]
+    fn test_vote_proposal_after_voting_window() {
+        let (validator, _other_validator, _delegator) = setup();
+        tx_host_env::with(|env| {
+            env.wl_storage.storage.block.epoch = Epoch(4);
+        });
+
+        let tx = vote_tx(validator.clone(), vec![validator.clone()]);
+        assert!(apply_tx(ctx(), tx).is_err());
+
+        let vote_key = gov_storage::keys::get_vote_proposal_key(
+            PROPOSAL_ID,
+            validator.clone(),
+            validator,
+        );
+        assert!(!ctx().has_key(&vote_key).unwrap());
+    }
+}
@@ -23,13 +23,13 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
 
 #[cfg(test)]
 mod tests {
-    use namada::ledger::pos::{OwnedPosParams, PosVP};
-    use namada::proof_of_stake::types::GenesisValidator;
-    use namada::proof_of_stake::unbond_handle;
+    use namada::ledger::pos::{OwnedPosParams, PosParams, PosVP};
+    use namada::proof_of_stake::types::{GenesisValidator, Slash, SlashType};
+    use namada::proof_of_stake::{unbond_handle, validator_slashes_handle};
     use namada::types::dec::Dec;
     use namada::types::storage::Epoch;
     use namada_tests::log::test;
-    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::native_vp::pos::{init_pos, init_pos_with_validator};
     use namada_tests::native_vp::TestNativeVpEnv;
     use namada_tests::tx::*;
     use namada_tx_prelude::address::testing::{
@@ -235,6 +235,125 @@ mod tests {
         Ok(())
     }
 
+    /// Setup a genesis validator with default PoS parameters and unbond the
+    /// given amount from its self-bond in epoch 0
+    fn setup_self_unbond(
+        unbonded_amount: token::Amount,
+    ) -> (Address, PosParams) {
+        let (validator, pos_params) = init_pos_with_validator();
+        if !unbonded_amount.is_zero() {
+            ctx()
+                .unbond_tokens(None, &validator, unbonded_amount)
+                .unwrap();
+        }
+        tx_host_env::commit_tx_and_block();
+        (validator, pos_params)
+    }
+
+    /// Advance the block epoch by the given number of epochs
+    fn advance_epochs(num: u64) {
+        tx_host_env::with(|env| {
+            for _ in 0..num {
+                env.wl_storage.storage.block.epoch =
+                    env.wl_storage.storage.block.epoch.next();
+            }
+        });
+    }
+
+    /// Build a withdraw tx of the given validator's self-bond
+    fn self_withdraw_tx(validator: &Address) -> Tx {
+        let withdraw = transaction::pos::Withdraw {
+            validator: validator.clone(),
+            source: None,
+        };
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(withdraw.serialize_to_vec());
+        tx
+    }
+
+    /// Read the native token balance of the given owner
+    fn read_native_balance(owner: &Address) -> token::Amount {
+        let native_token = tx_host_env::with(|env| {
+            env.wl_storage.storage.native_token.clone()
+        });
+        ctx()
+            .read(&token::balance_key(&native_token, owner))
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    /// Test that a withdrawal of an unbond that was slashed during the
+    /// unbonding period only returns the amount left after the slash
+    #[test]
+    fn test_withdraw_slashed_unbond() {
+        let unbonded_amount = token::Amount::native_whole(100);
+        let (validator, pos_params) = setup_self_unbond(unbonded_amount);
+
+        // Slash the validator for an infraction in the epoch of the unbonded
+        // bond
+        let rate = Dec::new(1, 1).expect("Cannot fail");
+        validator_slashes_handle(&validator)
+            .push(
+                ctx(),
+                Slash {
+                    epoch: Epoch(0),
+                    block_height: 0,
+                    r#type: SlashType::DuplicateVote,
+                    rate,
+                },
+            )
+            .unwrap();
+        tx_host_env::commit_tx_and_block();
+
+        advance_epochs(pos_params.withdrawable_epoch_offset());
+
+        let balance_pre = read_native_balance(&validator);
+        apply_tx(ctx(), self_withdraw_tx(&validator)).unwrap();
+        let balance_post = read_native_balance(&validator);
+
+        let expected = unbonded_amount - unbonded_amount.mul_ceil(rate);
+        assert_eq!(balance_post - balance_pre, expected);
+        assert!(
+            unbond_handle(&validator, &validator)
+                .is_empty(ctx())
+                .unwrap(),
+            "The withdrawn unbond must be removed"
+        );
+    }
+
+    /// Test that a withdrawal before the unbond has matured is rejected
+    #[test]
+    fn test_withdraw_premature() {
+        let unbonded_amount = token::Amount::native_whole(100);
+        let (validator, pos_params) = setup_self_unbond(unbonded_amount);
+
+        advance_epochs(pos_params.withdrawable_epoch_offset() - 1);
+
+        let balance_pre = read_native_balance(&validator);
+        let err = apply_tx(ctx(), self_withdraw_tx(&validator)).unwrap_err();
+        assert!(err.to_string().contains("No unbond may be withdrawn yet"));
+
+        // The unbond is still in place and nothing has been transferred
+        assert!(
+            !unbond_handle(&validator, &validator)
+                .is_empty(ctx())
+                .unwrap()
+        );
+        assert_eq!(read_native_balance(&validator), balance_pre);
+    }
+
+    /// Test that a withdrawal with no unbonds is rejected
+    #[test]
+    fn test_withdraw_without_unbond() {
+        let (validator, pos_params) = setup_self_unbond(token::Amount::zero());
+
+        advance_epochs(pos_params.withdrawable_epoch_offset());
+
+        let err = apply_tx(ctx(), self_withdraw_tx(&validator)).unwrap_err();
+        assert!(err.to_string().contains("No unbond could be found"));
+    }
+
     fn arb_initial_stake_and_unbonded_amount()
     -> impl Strategy<Value = (token::Amount, token::Amount)> {
         // Generate initial stake
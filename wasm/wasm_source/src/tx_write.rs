@@ -0,0 +1,121 @@
+//! A development tx for writing arbitrary values into storage, meant for
+//! setting up state in tests and devnets.
+//! This tx uses a list of `(storage::Key, Option<Vec<u8>>)` pairs as its
+//! input. A `Some` value is written to its key as raw bytes and a `None` value
+//! deletes the key.
+//! Only the keys in the subspace of an established or implicit address can be
+//! touched, so that the writes must be accepted by the owning account's VP.
+//! Keys of internal addresses, such as the parameters, are refused.
+
+use namada_tx_prelude::*;
+
+#[transaction(gas = 1000000)]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
+        ctx.set_commitment_sentinel();
+        err
+    })?;
+    let writes: Vec<(storage::Key, Option<Vec<u8>>)> =
+        BorshDeserialize::try_from_slice(&data[..])
+            .wrap_err("failed to decode the storage writes")?;
+
+    for (key, value) in writes {
+        if !is_account_key(&key) {
+            return Err(Error::new(format!(
+                "Refusing to write outside of an account's subspace: {key}"
+            )));
+        }
+        match value {
+            Some(value) => {
+                debug_log!("writing {} bytes to {}", value.len(), key);
+                ctx.write_bytes(&key, value)?;
+            }
+            None => {
+                debug_log!("deleting {}", key);
+                ctx.delete(&key)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the key belongs to the subspace of a non-internal address.
+fn is_account_key(key: &storage::Key) -> bool {
+    matches!(
+        key.segments.first(),
+        Some(storage::DbKeySeg::AddressSeg(
+            Address::Established(_) | Address::Implicit(_)
+        ))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_tests::log::test;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::testing::established_address_1;
+    use namada_tx_prelude::borsh_ext::BorshSerializeExt;
+    use namada_tx_prelude::chain::ChainId;
+    use namada_tx_prelude::storage::KeySeg;
+
+    use super::*;
+
+    fn write_tx(writes: Vec<(storage::Key, Option<Vec<u8>>)>) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .add_serialized_data(writes.serialize_to_vec());
+        tx
+    }
+
+    fn account_key(addr: &Address, sub_key: &str) -> storage::Key {
+        storage::Key::from(addr.to_db_key())
+            .push(&sub_key.to_owned())
+            .unwrap()
+    }
+
+    /// Test writing and deleting keys in an account's subspace
+    #[test]
+    fn test_tx_write_and_delete() {
+        let mut env = TestTxEnv::default();
+        let addr = established_address_1();
+        env.spawn_accounts([&addr]);
+        tx_host_env::set(env);
+
+        let key_a = account_key(&addr, "a");
+        let key_b = account_key(&addr, "b");
+
+        let tx = write_tx(vec![
+            (key_a.clone(), Some(vec![1, 2, 3])),
+            (key_b.clone(), Some(vec![4])),
+        ]);
+        apply_tx(ctx(), tx).unwrap();
+        assert_eq!(ctx().read_bytes(&key_a).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(ctx().read_bytes(&key_b).unwrap(), Some(vec![4]));
+
+        let tx = write_tx(vec![(key_a.clone(), None)]);
+        apply_tx(ctx(), tx).unwrap();
+        assert_eq!(ctx().read_bytes(&key_a).unwrap(), None);
+        assert_eq!(ctx().read_bytes(&key_b).unwrap(), Some(vec![4]));
+    }
+
+    /// Test that writes outside of the accounts' subspaces are refused
+    #[test]
+    fn test_tx_write_parameters_refused() {
+        let mut env = TestTxEnv::default();
+        env.init_parameters(None, None, None, None);
+        tx_host_env::set(env);
+
+        let key = parameters_storage::get_tx_whitelist_storage_key();
+        let before = ctx().read_bytes(&key).unwrap();
+        assert!(before.is_some());
+
+        let tx = write_tx(vec![(key.clone(), Some(vec![0]))]);
+        assert!(apply_tx(ctx(), tx).is_err());
+        assert_eq!(ctx().read_bytes(&key).unwrap(), before);
+
+        let tx = write_tx(vec![(key.clone(), None)]);
+        assert!(apply_tx(ctx(), tx).is_err());
+        assert_eq!(ctx().read_bytes(&key).unwrap(), before);
+    }
+}
@@ -4,7 +4,8 @@
 //! sending tokens (receiving tokens is permissive).
 //!
 //! It allows to reveal a PK, as long as its address matches with the address
-//! that can be derived from the PK.
+//! that can be derived from the PK. The tx that reveals the PK can also be
+//! authorized by a signature of the revealed PK.
 //!
 //! It allows to bond, unbond and withdraw tokens to and from PoS system with a
 //! valid signature.
@@ -63,7 +64,7 @@ fn validate_tx(
     );
 
     let valid_sig = Lazy::new(|| {
-        matches!(verify_signatures(ctx, &tx_data, &addr), Ok(true))
+        matches!(verify_implicit_signatures(ctx, &tx_data, &addr), Ok(true))
     });
 
     if !is_valid_tx(ctx, &tx_data)? {
@@ -183,6 +184,19 @@ fn validate_tx(
     accept()
 }
 
+/// Verify the signatures of the tx against the account's revealed PK. If the
+/// PK is not revealed yet, the signatures are verified against the PK revealed
+/// by this tx, if any. The validation of the changed keys ensures that such a
+/// PK matches with the account's address.
+fn verify_implicit_signatures(ctx: &Ctx, tx: &Tx, addr: &Address) -> VpResult {
+    if !storage_api::account::public_keys(&ctx.pre(), addr)?.is_empty() {
+        return verify_signatures(ctx, tx, addr);
+    }
+    let public_keys_index_map =
+        storage_api::account::public_keys_index_map(&ctx.post(), addr)?;
+    verify_signatures_with_keys(ctx, tx, addr, &public_keys_index_map, 1)
+}
+
 #[cfg(test)]
 mod tests {
     // Use this as `#[test]` annotation to enable logging
@@ -222,8 +236,9 @@ mod tests {
         );
     }
 
-    /// Test that a PK can be revealed when it's not revealed and cannot be
-    /// revealed anymore once it's already revealed.
+    /// Test that a PK can be revealed when it's not revealed, that revealing
+    /// it again is a no-op and that it cannot be overwritten once it's already
+    /// revealed.
     #[test]
     fn test_can_reveal_pk() {
         // The SK to be used for the implicit account
@@ -263,9 +278,28 @@ mod tests {
         let tx_env = tx_host_env::take();
 
         // Try to reveal it again
+        tx_host_env::set(tx_env);
+        tx_host_env::key::reveal_pk(tx::ctx(), &public_key).unwrap();
+        let tx_env = tx_host_env::take();
+        let (_verifiers, keys_changed) = tx_env
+            .wl_storage
+            .write_log
+            .verifiers_and_changed_keys(&tx_env.verifiers);
+        assert!(
+            keys_changed.is_empty(),
+            "Revealing PK that's already revealed should be a no-op"
+        );
+
+        // Try to overwrite it
         vp_host_env::init_from_tx(addr.clone(), tx_env, |_address| {
-            // Apply reveal_pk in a transaction
-            tx_host_env::key::reveal_pk(tx::ctx(), &public_key).unwrap();
+            // Do the same as reveal_pk, without checking the revealed PK
+            storage_api::account::set_public_key_at(
+                tx_host_env::ctx(),
+                &addr,
+                &public_key,
+                0,
+            )
+            .unwrap();
         });
 
         let vp_env = vp_host_env::take();
@@ -278,7 +312,7 @@ mod tests {
 
         assert!(
             !validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap(),
-            "Revealing PK that's already revealed should be rejected"
+            "Overwriting PK that's already revealed should be rejected"
         );
     }
 
@@ -687,6 +721,92 @@ mod tests {
         );
     }
 
+    /// Run the VP of an implicit account with an unrevealed PK on a tx that
+    /// reveals the PK and debits tokens from the account, optionally signed
+    /// with the revealed PK. Returns whether the tx was accepted.
+    fn validate_debit_transfer_with_reveal(signed: bool) -> bool {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let secret_key = key::testing::keypair_1();
+        let public_key = secret_key.ref_to();
+        let vp_owner: Address = (&public_key).into();
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::from_uint(10_098_123, 0).unwrap();
+
+        tx_env.init_parameters(None, None, None, None);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(&vp_owner, &token, amount);
+        // write the denomination of NAM into storage
+        storage_api::token::write_denom(
+            &mut tx_env.wl_storage,
+            &token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+
+        let amount = token::DenominatedAmount {
+            amount,
+            denom: token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        };
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Reveal the PK and apply transfer in a transaction
+            tx_host_env::key::reveal_pk(tx::ctx(), &public_key).unwrap();
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &target,
+                &token,
+                amount,
+            )
+            .unwrap();
+        });
+
+        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        tx.set_code(Code::new(vec![], None));
+        if signed {
+            tx.add_section(Section::Signature(Signature::new(
+                vec![tx.raw_header_hash()],
+                pks_map.index_secret_keys(vec![secret_key]),
+                None,
+            )));
+        }
+
+        let tx = tx.clone();
+        vp_env.tx = tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+
+        validate_tx(&CTX, tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that a debit transfer from an account whose PK is revealed by the
+    /// same tx is accepted with a signature of the revealed PK.
+    #[test]
+    fn test_signed_debit_transfer_with_reveal_accepted() {
+        assert!(validate_debit_transfer_with_reveal(true));
+    }
+
+    /// Test that a debit transfer from an account whose PK is revealed by the
+    /// same tx is rejected without a signature.
+    #[test]
+    fn test_unsigned_debit_transfer_with_reveal_rejected() {
+        assert!(!validate_debit_transfer_with_reveal(false));
+    }
+
     /// Test that a transfer on with accounts other than self is accepted.
     #[test]
     fn test_transfer_between_other_parties_accepted() {
@@ -827,19 +947,10 @@ mod tests {
                 }
             });
 
-            let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
             let mut vp_env = vp_host_env::take();
-            let mut tx = vp_env.tx.clone();
-            tx.set_data(Data::new(vec![]));
-            tx.set_code(Code::new(vec![], None));
-            tx.add_section(Section::Signature(Signature::new(
-                vec![tx.raw_header_hash()],
-                pks_map.index_secret_keys(vec![secret_key]),
-                None,
-            )));
-            let signed_tx = tx.clone();
-            vp_env.tx = signed_tx.clone();
+            vp_env.set_tx_code_and_data(vec![], vec![]);
+            vp_env.sign_tx(vec![secret_key], None);
+            let signed_tx = vp_env.tx.clone();
             let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
             let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -920,19 +1031,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![secret_key]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![secret_key], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -975,19 +1077,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_code(Code::new(vec![], None));
-        tx.set_data(Data::new(vec![]));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![secret_key]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![secret_key], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
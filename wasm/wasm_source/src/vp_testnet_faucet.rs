@@ -3,6 +3,10 @@
 //! This VP allows anyone to withdraw up to
 //! [`testnet_pow::read_withdrawal_limit`] tokens without the faucet's
 //! signature, but with a valid PoW challenge solution that cannot be replayed.
+//! The solution is attached to the wrapper tx and it's validated by the
+//! protocol, which also enforces the faucet's cooldown period between two
+//! withdrawals to the same address. The withdrawn tokens must be credited to
+//! the address of the solved challenge.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
 
@@ -42,29 +46,16 @@ fn validate_tx(
                 let post: token::Amount =
                     ctx.read_post(key)?.unwrap_or_default();
                 let change = post.change() - pre.change();
-                let maybe_denom =
-                    storage_api::token::read_denom(&ctx.pre(), token)?;
-                if maybe_denom.is_none() {
-                    debug_log!(
-                        "A denomination for token address {} does not exist \
-                         in storage",
-                        token,
-                    );
-                    return reject();
-                }
-                let denom = maybe_denom.unwrap();
                 if !change.non_negative() {
                     // Allow to withdraw without a sig if there's a valid PoW
                     if ctx.has_valid_pow() {
-                        let max_free_debit =
-                            testnet_pow::read_withdrawal_limit(
-                                &ctx.pre(),
-                                &addr,
-                            )?;
-
-                        token::Amount::from_uint(change.abs(), 0).unwrap()
-                            <= token::Amount::from_uint(max_free_debit, denom)
-                                .unwrap()
+                        is_valid_withdrawal(
+                            ctx,
+                            &tx_data,
+                            &addr,
+                            token,
+                            token::Amount::from_change(change),
+                        )?
                     } else {
                         debug_log!("No PoW solution, a signature is required");
                         // Debit without a solution has to signed
@@ -105,6 +96,60 @@ fn validate_tx(
     accept()
 }
 
+/// Check a withdrawal of the given token from the faucet authorized by a PoW
+/// solution, which must have been solved for this faucet. The debit must be
+/// within the faucet's withdrawal limit and it must be credited to the
+/// solution's source.
+fn is_valid_withdrawal(
+    ctx: &Ctx,
+    tx_data: &Tx,
+    faucet: &Address,
+    token: &Address,
+    debit: token::Amount,
+) -> VpResult {
+    let Some(solution) = tx_data.pow_solution() else {
+        debug_log!("Missing the PoW solution section");
+        return reject();
+    };
+    let testnet_pow::Challenge {
+        faucet: solution_faucet,
+        source,
+        ..
+    } = &solution.challenge;
+    if solution_faucet != faucet {
+        debug_log!(
+            "The PoW solution is for a different faucet {}",
+            solution_faucet
+        );
+        return reject();
+    }
+
+    let max_free_debit =
+        testnet_pow::read_withdrawal_limit(&ctx.pre(), faucet)?;
+    if debit > max_free_debit {
+        debug_log!(
+            "The withdrawal of {} exceeds the limit of {}",
+            debit.to_string_native(),
+            max_free_debit.to_string_native()
+        );
+        return reject();
+    }
+
+    let source_key = token::balance_key(token, source);
+    let pre: token::Amount = ctx.read_pre(&source_key)?.unwrap_or_default();
+    let post: token::Amount = ctx.read_post(&source_key)?.unwrap_or_default();
+    let credit = post.checked_sub(pre).unwrap_or_default();
+    if credit < debit {
+        debug_log!(
+            "The withdrawn tokens must be credited to the PoW solution's \
+             source {}",
+            source
+        );
+        return reject();
+    }
+    accept()
+}
+
 #[cfg(test)]
 mod tests {
     use address::testing::arb_non_internal_address;
@@ -118,7 +163,6 @@ mod tests {
     use namada_tests::vp::*;
     use namada_tx_prelude::{StorageWrite, TxEnv};
     use namada_vp_prelude::account::AccountPublicKeysMap;
-    use namada_vp_prelude::borsh_ext::BorshSerializeExt;
     use namada_vp_prelude::key::RefTo;
     use proptest::prelude::*;
     use storage::testing::arb_account_storage_key_no_vp;
@@ -126,7 +170,9 @@ mod tests {
     use super::*;
 
     /// Allows anyone to withdraw up to 1_000 tokens in a single tx
-    pub const MAX_FREE_DEBIT: i128 = 1_000_000_000; // in micro units
+    const MAX_FREE_DEBIT: u64 = 1_000_000_000; // in micro units
+    /// The number of blocks between two withdrawals to the same address
+    const COOLDOWN: u64 = 10;
 
     /// Test that no-op transaction (i.e. no storage modifications) accepted.
     #[test]
@@ -301,118 +347,218 @@ mod tests {
         }
     }
 
-    proptest! {
-    /// Test that a debit of more than [`MAX_FREE_DEBIT`] tokens without a valid signature is rejected.
-    #[test]
-    fn test_unsigned_debit_over_limit_rejected(amount in (MAX_FREE_DEBIT as u64 + 1..)) {
-        // Initialize a tx environment
+    /// Initialize a tx environment with a funded faucet with a zero PoW
+    /// difficulty and a withdrawal target.
+    fn init_faucet_env(faucet: &Address, target: &Address) -> TestTxEnv {
         let mut tx_env = TestTxEnv::default();
-
-        // Init the VP
-        let vp_owner = address::testing::established_address_1();
-        let difficulty = testnet_pow::Difficulty::try_new(0).unwrap();
-        let withdrawal_limit = token::Amount::from_uint(MAX_FREE_DEBIT as u64, 0).unwrap();
-        testnet_pow::init_faucet_storage(&mut tx_env.wl_storage, &vp_owner, difficulty, withdrawal_limit.into()).unwrap();
-
-        let target = address::testing::established_address_2();
         let token = address::nam();
-        let amount = token::Amount::from_uint(amount, 0).unwrap();
 
         // Spawn the accounts to be able to modify their storage
-        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+        tx_env.spawn_accounts([faucet, target, &token]);
 
-        // Credit the tokens to the VP owner before running the transaction to
+        // Init the VP
+        let difficulty = testnet_pow::Difficulty::try_new(0).unwrap();
+        let withdrawal_limit =
+            token::Amount::from_uint(MAX_FREE_DEBIT, 0).unwrap();
+        testnet_pow::init_faucet_storage(
+            &mut tx_env.wl_storage,
+            faucet,
+            difficulty,
+            withdrawal_limit,
+            COOLDOWN,
+        )
+        .unwrap();
+
+        // Credit the tokens to the faucet before running the transaction to
         // be able to transfer from it
-        tx_env.credit_tokens(&vp_owner, &token, amount);
+        tx_env.credit_tokens(
+            faucet,
+            &token,
+            token::Amount::from_uint(10 * MAX_FREE_DEBIT, 0).unwrap(),
+        );
         tx_env.commit_genesis();
+        tx_env
+    }
+
+    /// Withdraw the given amount from the faucet to the target and validate
+    /// the tx with the faucet's VP. Like the protocol, the PoW solution, if
+    /// any, is validated and invalidated when its wrapper tx is applied, in
+    /// the block before the withdrawal. Returns the VP's result and the
+    /// environment with the committed withdrawal.
+    fn withdraw(
+        mut tx_env: TestTxEnv,
+        faucet: &Address,
+        target: &Address,
+        amount: u64,
+        solution: Option<testnet_pow::Solution>,
+    ) -> (bool, TestTxEnv) {
+        let has_valid_pow = match &solution {
+            Some(solution) => {
+                let valid = solution
+                    .invalidate_if_valid(&mut tx_env.wl_storage)
+                    .unwrap();
+                tx_env.commit_tx_and_block();
+                valid
+            }
+            None => false,
+        };
+
+        let token = address::nam();
         let amount = token::DenominatedAmount {
-            amount,
-            denom: token::NATIVE_MAX_DECIMAL_PLACES.into()
+            amount: token::Amount::from_uint(amount, 0).unwrap(),
+            denom: token::NATIVE_MAX_DECIMAL_PLACES.into(),
         };
 
         // Initialize VP environment from a transaction
-        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
-        // Apply transfer in a transaction
-        tx_host_env::token::transfer(tx::ctx(), address, &target, &token, amount).unwrap();
+        vp_host_env::init_from_tx(faucet.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                target,
+                &token,
+                amount,
+            )
+            .unwrap();
         });
 
-        let vp_env = vp_host_env::take();
+        let mut vp_env = vp_host_env::take();
+        // This is set by the protocol from the wrapper tx
+        vp_env.has_valid_pow = has_valid_pow;
         let mut tx_data = Tx::from_type(TxType::Raw);
         tx_data.set_data(Data::new(vec![]));
+        if let Some(solution) = solution {
+            tx_data.add_pow_solution(solution);
+        }
+        vp_env.tx = tx_data.clone();
         let keys_changed: BTreeSet<storage::Key> =
-        vp_env.all_touched_storage_keys();
+            vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
-        assert!(!validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap());
+        let result =
+            validate_tx(&CTX, tx_data, faucet.clone(), keys_changed, verifiers)
+                .unwrap();
+
+        // Commit an accepted withdrawal for any subsequent txs
+        tx_host_env::set_from_vp_env(vp_host_env::take());
+        let mut tx_env = tx_host_env::take();
+        if result {
+            tx_env.commit_tx_and_block();
+        } else {
+            tx_env.wl_storage.drop_tx();
+        }
+        (result, tx_env)
     }
 
-    /// Test that a debit of less than or equal to [`MAX_FREE_DEBIT`] tokens
-    /// without a valid signature but with a valid PoW solution is accepted.
+    /// Test that a withdrawal up to the limit with a valid PoW solution and
+    /// without a signature is accepted.
     #[test]
-    fn test_unsigned_debit_under_limit_accepted(amount in (..MAX_FREE_DEBIT as u64 + 1)) {
-        // Initialize a tx environment
-        let mut tx_env = TestTxEnv::default();
+    fn test_pow_withdrawal_under_limit_accepted() {
+        let faucet = address::testing::established_address_1();
+        let target = address::testing::established_address_2();
+        let tx_env = init_faucet_env(&faucet, &target);
 
-        // Init the VP
-        let vp_owner = address::testing::established_address_1();
-        let difficulty = testnet_pow::Difficulty::try_new(0).unwrap();
-        let withdrawal_limit = token::Amount::from_uint(MAX_FREE_DEBIT as u64, 0).unwrap();
-        testnet_pow::init_faucet_storage(&mut tx_env.wl_storage, &vp_owner, difficulty, withdrawal_limit.into()).unwrap();
+        // Construct a PoW solution like a client would
+        let solution = testnet_pow::Challenge::new(
+            &tx_env.wl_storage,
+            &faucet,
+            target.clone(),
+        )
+        .unwrap()
+        .solve();
 
-        let target = address::testing::established_address_2();
-        let target_key = key::testing::keypair_1();
-        let _public_key = target_key.ref_to();
-        let token = address::nam();
-        let amount = token::Amount::from_uint(amount, 0).unwrap();
+        let (accepted, _) =
+            withdraw(tx_env, &faucet, &target, MAX_FREE_DEBIT, Some(solution));
+        assert!(accepted);
+    }
 
-        // Spawn the accounts to be able to modify their storage
-        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+    /// Test that a withdrawal over the limit with a valid PoW solution and
+    /// without a signature is rejected.
+    #[test]
+    fn test_pow_withdrawal_over_limit_rejected() {
+        let faucet = address::testing::established_address_1();
+        let target = address::testing::established_address_2();
+        let tx_env = init_faucet_env(&faucet, &target);
 
-        // Credit the tokens to the VP owner before running the transaction to
-        // be able to transfer from it
-        tx_env.credit_tokens(&vp_owner, &token, amount);
-        // write the denomination of NAM into storage
-        storage_api::token::write_denom(&mut tx_env.wl_storage, &token, token::NATIVE_MAX_DECIMAL_PLACES.into()).unwrap();
-        tx_env.commit_genesis();
+        let solution = testnet_pow::Challenge::new(
+            &tx_env.wl_storage,
+            &faucet,
+            target.clone(),
+        )
+        .unwrap()
+        .solve();
+
+        let (accepted, _) = withdraw(
+            tx_env,
+            &faucet,
+            &target,
+            MAX_FREE_DEBIT + 1,
+            Some(solution),
+        );
+        assert!(!accepted);
+    }
 
-        // Construct a PoW solution like a client would
-        let challenge = testnet_pow::Challenge::new(&mut tx_env.wl_storage, &vp_owner, target.clone()).unwrap();
-        let solution = challenge.solve();
-        let solution_bytes = solution.serialize_to_vec();
+    /// Test that a second withdrawal to the same address is rejected within
+    /// the faucet's cooldown period, even with a new PoW solution, and
+    /// accepted after it.
+    #[test]
+    fn test_pow_withdrawal_in_cooldown_rejected() {
+        let faucet = address::testing::established_address_1();
+        let target = address::testing::established_address_2();
+        let mut tx_env = init_faucet_env(&faucet, &target);
+        tx_env.wl_storage.storage.block.height = storage::BlockHeight(1);
 
-        let amount = token::DenominatedAmount {
-            amount,
-            denom: token::NATIVE_MAX_DECIMAL_PLACES.into(),
-        };
+        let solution = testnet_pow::Challenge::new(
+            &tx_env.wl_storage,
+            &faucet,
+            target.clone(),
+        )
+        .unwrap()
+        .solve();
+        let (accepted, mut tx_env) =
+            withdraw(tx_env, &faucet, &target, MAX_FREE_DEBIT, Some(solution));
+        assert!(accepted);
+
+        // Within the cooldown
+        tx_env.wl_storage.storage.block.height = storage::BlockHeight(COOLDOWN);
+        let solution = testnet_pow::Challenge::new(
+            &tx_env.wl_storage,
+            &faucet,
+            target.clone(),
+        )
+        .unwrap()
+        .solve();
+        let (accepted, mut tx_env) = withdraw(
+            tx_env,
+            &faucet,
+            &target,
+            MAX_FREE_DEBIT,
+            Some(solution.clone()),
+        );
+        assert!(!accepted);
+
+        // After the cooldown the same challenge can be used, as the rejected
+        // solution wasn't invalidated
+        tx_env.wl_storage.storage.block.height =
+            storage::BlockHeight(1 + COOLDOWN);
+        let (accepted, _) =
+            withdraw(tx_env, &faucet, &target, MAX_FREE_DEBIT, Some(solution));
+        assert!(accepted);
+    }
 
-        // Initialize VP environment from a transaction
-        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
-            // Don't call `Solution::invalidate_if_valid` - this is done by the
-            // shell's finalize_block.
-            let valid = solution.validate(tx::ctx(), address, target.clone()).unwrap();
-            assert!(valid);
-            // Apply transfer in a transaction
-            tx_host_env::token::transfer(tx::ctx(), address, &target, &token, amount).unwrap();
-        });
+    /// Test that a withdrawal without a PoW solution and without a signature
+    /// is rejected.
+    #[test]
+    fn test_withdrawal_without_pow_rejected() {
+        let faucet = address::testing::established_address_1();
+        let target = address::testing::established_address_2();
+        let tx_env = init_faucet_env(&faucet, &target);
 
-        let mut vp_env = vp_host_env::take();
-        // This is set by the protocol when the wrapper tx has a valid PoW
-        vp_env.has_valid_pow = true;
-        let mut tx_data = Tx::from_type(TxType::Raw);
-        tx_data.set_data(Data::new(solution_bytes));
-        tx_data.set_code(Code::new(vec![], None));
-        tx_data.add_section(Section::Signature(Signature::new(
-            vec![tx_data.raw_header_hash()],
-            [(0, target_key)].into_iter().collect(),
-            None,
-        )));
-        let keys_changed: BTreeSet<storage::Key> =
-        vp_env.all_touched_storage_keys();
-        let verifiers: BTreeSet<Address> = BTreeSet::default();
-        vp_host_env::set(vp_env);
-        assert!(validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap());
+        let (accepted, _) = withdraw(tx_env, &faucet, &target, 1, None);
+        assert!(!accepted);
     }
 
+    proptest! {
         /// Test that a signed tx that performs arbitrary storage writes or
         /// deletes to the account is accepted.
         #[test]
@@ -426,8 +572,8 @@ mod tests {
 
             // Init the VP
             let difficulty = testnet_pow::Difficulty::try_new(0).unwrap();
-            let withdrawal_limit = token::Amount::from_uint(MAX_FREE_DEBIT as u64, 0).unwrap();
-            testnet_pow::init_faucet_storage(&mut tx_env.wl_storage, &vp_owner, difficulty, withdrawal_limit.into()).unwrap();
+            let withdrawal_limit = token::Amount::from_uint(MAX_FREE_DEBIT, 0).unwrap();
+            testnet_pow::init_faucet_storage(&mut tx_env.wl_storage, &vp_owner, difficulty, withdrawal_limit, COOLDOWN).unwrap();
 
             let keypair = key::testing::keypair_1();
             let public_key = &keypair.ref_to();
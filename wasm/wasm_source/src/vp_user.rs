@@ -7,6 +7,10 @@
 //! valid signature.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
+//!
+//! A valid signature means that at least the account's threshold of its
+//! distinct registered public keys signed the tx. Signatures from keys that are
+//! not registered on the account are ignored.
 
 use namada_vp_prelude::storage::KeySeg;
 use namada_vp_prelude::*;
@@ -344,7 +348,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner, &target, &token]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Credit the tokens to the VP owner before running the transaction to
         // be able to transfer from it
@@ -375,19 +379,10 @@ mod tests {
             .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -525,7 +520,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&target, &token]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // write the denomination of NAM into storage
         storage_api::token::write_denom(
@@ -550,19 +545,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![secret_key]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![secret_key], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -786,7 +772,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -796,19 +782,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -841,7 +818,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -851,19 +828,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -897,7 +865,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -907,19 +875,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -953,7 +912,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -963,19 +922,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -1009,7 +959,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -1019,26 +969,375 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
+        let mut vp_env = vp_host_env::take();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Run the VP of an account with the given public keys and threshold on
+    /// a tx updating its VP, after letting `sign` set the data of the tx and
+    /// sign it. Returns whether the tx was accepted.
+    fn validate_vp_update(
+        public_keys: Vec<key::common::PublicKey>,
+        threshold: u8,
+        sign: impl FnOnce(&mut Tx, &Address),
+    ) -> bool {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+        tx_env.init_parameters(None, None, None, None);
+
+        let vp_owner = address::testing::established_address_1();
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let vp_hash = sha256(&vp_code);
+        // for the update
+        tx_env.store_wasm_code(vp_code);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+        tx_env.init_account_storage(&vp_owner, public_keys, threshold);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Update VP in a transaction
+            tx::ctx()
+                .update_validity_predicate(address, vp_hash, &None)
+                .unwrap();
+        });
 
         let mut vp_env = vp_host_env::take();
         let mut tx = vp_env.tx.clone();
         tx.set_code(Code::new(vec![], None));
+        sign(&mut tx, &vp_owner);
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// The public keys of a 2-of-3 multisig account
+    fn multisig_keys() -> Vec<key::common::PublicKey> {
+        vec![
+            key::testing::keypair_1().ref_to(),
+            key::testing::keypair_2().ref_to(),
+            key::testing::keypair_3().ref_to(),
+        ]
+    }
+
+    /// Test that a VP update of a multisig account signed by exactly the
+    /// threshold of its keys is accepted.
+    #[test]
+    fn test_multisig_threshold_signed_accepted() {
+        assert!(validate_vp_update(multisig_keys(), 2, |tx, owner| {
+            tx.set_data(Data::new(vec![]));
+            tx.add_section(Section::Signature(Signature::new(
+                vec![tx.raw_header_hash()],
+                [
+                    (0, key::testing::keypair_1()),
+                    (2, key::testing::keypair_3()),
+                ]
+                .into_iter()
+                .collect(),
+                Some(owner.clone()),
+            )));
+        }));
+    }
+
+    /// Test that a VP update of a multisig account signed by less than the
+    /// threshold of its keys is rejected.
+    #[test]
+    fn test_multisig_below_threshold_rejected() {
+        assert!(!validate_vp_update(multisig_keys(), 2, |tx, owner| {
+            tx.set_data(Data::new(vec![]));
+            tx.add_section(Section::Signature(Signature::new(
+                vec![tx.raw_header_hash()],
+                [(0, key::testing::keypair_1())].into_iter().collect(),
+                Some(owner.clone()),
+            )));
+        }));
+    }
+
+    /// Test that a VP update of a multisig account is rejected if one of the
+    /// signatures attributed to its keys is forged.
+    #[test]
+    fn test_multisig_forged_signature_rejected() {
+        assert!(!validate_vp_update(multisig_keys(), 2, |tx, owner| {
+            tx.set_data(Data::new(vec![]));
+            // The signature at the index of the second key is made by a key
+            // which is not registered on the account
+            tx.add_section(Section::Signature(Signature::new(
+                vec![tx.raw_header_hash()],
+                [
+                    (0, key::testing::keypair_1()),
+                    (1, key::testing::keypair_4()),
+                ]
+                .into_iter()
+                .collect(),
+                Some(owner.clone()),
+            )));
+        }));
+    }
+
+    /// Test that signatures from keys which are not registered on a multisig
+    /// account don't count towards its threshold.
+    #[test]
+    fn test_multisig_unregistered_signer_rejected() {
+        assert!(!validate_vp_update(multisig_keys(), 2, |tx, _owner| {
+            tx.set_data(Data::new(vec![]));
+            tx.add_section(Section::Signature(Signature::new(
+                vec![tx.raw_header_hash()],
+                [
+                    (0, key::testing::keypair_1()),
+                    (1, key::testing::keypair_4()),
+                ]
+                .into_iter()
+                .collect(),
+                None,
+            )));
+        }));
+    }
+
+    /// Test that several signatures from the same key of a multisig account
+    /// count only once towards its threshold.
+    #[test]
+    fn test_multisig_duplicate_signatures_rejected() {
+        assert!(!validate_vp_update(multisig_keys(), 2, |tx, owner| {
+            tx.set_data(Data::new(vec![]));
+            let hash = tx.raw_header_hash();
+            for _ in 0..2 {
+                tx.add_section(Section::Signature(Signature::new(
+                    vec![hash],
+                    [(1, key::testing::keypair_2())].into_iter().collect(),
+                    Some(owner.clone()),
+                )));
+            }
+        }));
+    }
+
+    /// Run the VP of a 2-of-3 multisig account on a tx rotating its keys to a
+    /// single new key, signed with the signature sections built from the
+    /// tx's header hash and the account's address. Returns whether the tx was
+    /// accepted.
+    fn validate_multisig_key_rotation(
+        signatures: impl FnOnce(hash::Hash, &Address) -> Vec<Signature>,
+    ) -> bool {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+        tx_env.init_parameters(None, None, None, None);
+
+        let vp_owner = address::testing::established_address_1();
+        let public_keys = vec![
+            key::testing::keypair_1().ref_to(),
+            key::testing::keypair_2().ref_to(),
+            key::testing::keypair_3().ref_to(),
+        ];
+        let new_keys = vec![key::testing::keypair_4().ref_to()];
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+        tx_env.init_account_storage(&vp_owner, public_keys, 2);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Rotate the keys in a transaction
+            storage_api::account::change_keys(tx::ctx(), address, &new_keys, 1)
+                .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
         tx.set_data(Data::new(vec![]));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
+        tx.set_code(Code::new(vec![], None));
+        for signature in signatures(tx.raw_header_hash(), &vp_owner) {
+            tx.add_section(Section::Signature(signature));
+        }
         let signed_tx = tx.clone();
         vp_env.tx = signed_tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
-        assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
-                .unwrap()
-        );
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that a key rotation of a multisig account signed by the threshold
+    /// of its current keys is accepted.
+    #[test]
+    fn test_key_rotation_threshold_signed_accepted() {
+        assert!(validate_multisig_key_rotation(|hash, owner| {
+            vec![Signature::new(
+                vec![hash],
+                [
+                    (0, key::testing::keypair_1()),
+                    (1, key::testing::keypair_2()),
+                ]
+                .into_iter()
+                .collect(),
+                Some(owner.clone()),
+            )]
+        }));
+    }
+
+    /// Test that a key rotation of a multisig account signed by less than the
+    /// threshold of its current keys is rejected.
+    #[test]
+    fn test_key_rotation_below_threshold_rejected() {
+        assert!(!validate_multisig_key_rotation(|hash, owner| {
+            vec![Signature::new(
+                vec![hash],
+                [(2, key::testing::keypair_3())].into_iter().collect(),
+                Some(owner.clone()),
+            )]
+        }));
+    }
+
+    /// Test that a key rotation signed only by the new key, which satisfies
+    /// the new threshold, is rejected.
+    #[test]
+    fn test_key_rotation_signed_by_new_key_rejected() {
+        assert!(!validate_multisig_key_rotation(|hash, owner| {
+            vec![Signature::new(
+                vec![hash],
+                [(0, key::testing::keypair_4())].into_iter().collect(),
+                Some(owner.clone()),
+            )]
+        }));
+    }
+
+    /// Test that a tx with a signature section over the signature targets is
+    /// accepted.
+    #[test]
+    fn test_signature_section_accepted() {
+        let keypair = key::testing::keypair_1();
+        let public_keys = vec![keypair.ref_to()];
+        assert!(validate_vp_update(public_keys, 1, |tx, _owner| {
+            tx.set_data(Data::new(vec![]));
+            tx.add_section(Section::Signature(Signature::new(
+                signature_targets(tx),
+                [(0, keypair)].into_iter().collect(),
+                None,
+            )));
+        }));
+    }
+
+    /// Test that a tx without any signature section is rejected.
+    #[test]
+    fn test_signature_section_missing_rejected() {
+        let keypair = key::testing::keypair_1();
+        let public_keys = vec![keypair.ref_to()];
+        assert!(!validate_vp_update(public_keys, 1, |tx, _owner| {
+            tx.set_data(Data::new(vec![]));
+        }));
+    }
+
+    /// Test that a signature section which doesn't cover the signature
+    /// targets is rejected, even if it covers the data section.
+    #[test]
+    fn test_signature_section_not_covering_targets_rejected() {
+        let keypair = key::testing::keypair_1();
+        let public_keys = vec![keypair.ref_to()];
+        assert!(!validate_vp_update(public_keys, 1, |tx, _owner| {
+            tx.set_data(Data::new(vec![]));
+            tx.add_section(Section::Signature(Signature::new(
+                vec![*tx.data_sechash()],
+                [(0, keypair)].into_iter().collect(),
+                None,
+            )));
+        }));
+    }
+
+    /// Test that a signature embedded in the tx data with the legacy
+    /// `SignedTxData` format doesn't authorize a tx.
+    #[test]
+    fn test_legacy_signed_tx_data_rejected() {
+        use namada_vp_prelude::borsh_ext::BorshSerializeExt;
+        use namada_vp_prelude::key::SigScheme;
+
+        let keypair = key::testing::keypair_1();
+        let public_keys = vec![keypair.ref_to()];
+        assert!(!validate_vp_update(public_keys, 1, |tx, _owner| {
+            let data: Vec<u8> = vec![];
+            let sig = key::common::SigScheme::sign(&keypair, sha256(&data));
+            // The Borsh encoding of a `SignedTxData` with the given data and
+            // signature
+            tx.set_data(Data::new((Some(data), sig).serialize_to_vec()));
+        }));
+    }
+
+    /// Validate a tx writing a proposal vote of the VP owner as a delegator,
+    /// signed with the given closure.
+    fn validate_signed_vote(
+        sign: impl FnOnce(&mut Tx, &key::common::SecretKey),
+    ) -> bool {
+        use namada::core::ledger::governance::storage::vote::{
+            StorageProposalVote, VoteType,
+        };
+
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+        tx_env.init_parameters(None, None, None, None);
+
+        let vp_owner = address::testing::established_address_1();
+        let validator = address::testing::established_address_2();
+        let keypair = key::testing::keypair_1();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &validator]);
+        tx_env.init_account_storage(&vp_owner, vec![keypair.ref_to()], 1);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Vote on a proposal in a transaction
+            let vote_key = gov_storage::keys::get_vote_proposal_key(
+                0,
+                address.clone(),
+                validator.clone(),
+            );
+            tx::ctx()
+                .write(&vote_key, StorageProposalVote::Yay(VoteType::Default))
+                .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_code(Code::new(vec![], None));
+        tx.set_data(Data::new(vec![]));
+        sign(&mut tx, &keypair);
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that a proposal vote signed by the voter is accepted.
+    #[test]
+    fn test_signed_vote_accepted() {
+        assert!(validate_signed_vote(|tx, keypair| {
+            tx.add_section(Section::Signature(Signature::new(
+                signature_targets(tx),
+                [(0, keypair.clone())].into_iter().collect(),
+                None,
+            )));
+        }));
+    }
+
+    /// Test that a proposal vote without the voter's signature is rejected.
+    #[test]
+    fn test_unsigned_vote_rejected() {
+        assert!(!validate_signed_vote(|_tx, _keypair| {}));
     }
 }
@@ -8,11 +8,13 @@
 //!
 //! Currently, the only difference with respect to the user VP is for a tx to
 //! change a validator's commission rate: we require a valid signature only from
-//! the validator whose commission rate is being changed.
+//! the validator whose commission rate is being changed and we check that the
+//! change is within the validator's maximum commission rate change.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
 
-use namada_vp_prelude::storage::KeySeg;
+use namada_vp_prelude::dec::Dec;
+use namada_vp_prelude::storage::{Epoch, KeySeg};
 use namada_vp_prelude::*;
 use once_cell::unsync::Lazy;
 use proof_of_stake::types::ValidatorState;
@@ -131,8 +133,12 @@ fn validate_tx(
                         key,
                     );
                 let valid_commission_rate_change = match comm {
-                    Some((validator, _epoch)) => {
-                        *validator == addr && *valid_sig
+                    Some((validator, epoch)) => {
+                        *validator == addr
+                            && *valid_sig
+                            && is_valid_commission_rate_change(
+                                ctx, key, validator, epoch,
+                            )?
                     }
                     None => true,
                 };
@@ -256,11 +262,67 @@ fn validate_tx(
     accept()
 }
 
+/// Check that a commission rate written at the given epoch is within [0, 1],
+/// that it doesn't differ from the rate in the preceding epoch by more than
+/// the validator's maximum rate change and that the rate hasn't already been
+/// changed in the current epoch.
+fn is_valid_commission_rate_change(
+    ctx: &Ctx,
+    key: &storage::Key,
+    validator: &Address,
+    epoch: Epoch,
+) -> VpResult {
+    let new_rate: Dec = match ctx.read_post(key)? {
+        Some(rate) => rate,
+        // Old rates are removed when the epoched data gets trimmed
+        None => return Ok(true),
+    };
+    let params = proof_of_stake::read_pos_params(&ctx.pre())?;
+    let handle = proof_of_stake::validator_commission_rate_handle(validator);
+    // An unchanged rate may be re-written when the epoched data gets trimmed
+    if handle.get(&ctx.pre(), epoch, &params)? == Some(new_rate) {
+        return Ok(true);
+    }
+    if new_rate.is_negative() || new_rate > Dec::one() {
+        debug_log!("Commission rate {} is out of bounds", new_rate);
+        return Ok(false);
+    }
+    // A rate at this epoch could have only been written in the current epoch
+    if handle.get_data_handler().get(&ctx.pre(), &epoch)?.is_some() {
+        debug_log!("Commission rate has already been changed in this epoch");
+        return Ok(false);
+    }
+    let rate_before = match epoch.checked_sub(1_u64) {
+        Some(prev_epoch) => handle.get(&ctx.pre(), prev_epoch, &params)?,
+        None => None,
+    };
+    let max_change = proof_of_stake::read_validator_max_commission_rate_change(
+        &ctx.pre(),
+        validator,
+    )?;
+    match (rate_before, max_change) {
+        (Some(rate_before), Some(max_change)) => {
+            let change = new_rate.abs_diff(&rate_before);
+            if change > max_change {
+                debug_log!(
+                    "Commission rate change {} exceeds the maximum {}",
+                    change,
+                    max_change
+                );
+            }
+            Ok(change <= max_change)
+        }
+        // The rate of a newly initialized validator
+        (None, _) => Ok(true),
+        (Some(_), None) => Ok(false),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use address::testing::arb_non_internal_address;
     use namada::ledger::pos::{GenesisValidator, OwnedPosParams};
-    use namada::proto::{Code, Data, Signature};
+    use namada::proto::Data;
     use namada::types::dec::Dec;
     use namada::types::storage::Epoch;
     use namada::types::transaction::TxType;
@@ -272,7 +334,6 @@ mod tests {
     use namada_tests::vp::vp_host_env::storage::Key;
     use namada_tests::vp::*;
     use namada_tx_prelude::{StorageWrite, TxEnv};
-    use namada_vp_prelude::account::AccountPublicKeysMap;
     use namada_vp_prelude::key::RefTo;
     use proptest::prelude::*;
     use storage::testing::arb_account_storage_key_no_vp;
@@ -421,7 +482,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner, &target, &token]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Credit the tokens to the VP owner before running the transaction to
         // be able to transfer from it
@@ -452,19 +513,10 @@ mod tests {
             .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -607,7 +659,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&validator, &target, &token]);
-        tx_env.init_account_storage(&validator, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&validator, vec![public_key], 1);
 
         // Credit the tokens to the VP owner before running the transaction to
         // be able to transfer from it
@@ -642,19 +694,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![secret_key]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![secret_key], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -665,6 +708,79 @@ mod tests {
         );
     }
 
+    /// Test that a signed commission rate change that exceeds the validator's
+    /// maximum rate change is rejected.
+    #[test]
+    fn test_signed_commission_change_exceeding_bound_rejected() {
+        // Init PoS genesis
+        let pos_params = OwnedPosParams::default();
+        let validator = address::testing::established_address_3();
+        let initial_stake = token::Amount::from_uint(10_098_123, 0).unwrap();
+        let consensus_key = key::testing::keypair_2().ref_to();
+        let protocol_key = key::testing::keypair_1().ref_to();
+        let commission_rate = Dec::new(5, 2).unwrap();
+        let max_commission_rate_change = Dec::new(1, 2).unwrap();
+
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: initial_stake,
+            consensus_key,
+            protocol_key,
+            commission_rate,
+            max_commission_rate_change,
+            eth_hot_key: key::common::PublicKey::Secp256k1(
+                key::testing::gen_keypair::<key::secp256k1::SigScheme>()
+                    .ref_to(),
+            ),
+            eth_cold_key: key::common::PublicKey::Secp256k1(
+                key::testing::gen_keypair::<key::secp256k1::SigScheme>()
+                    .ref_to(),
+            ),
+            metadata: Default::default(),
+        }];
+
+        let pos_params =
+            init_pos(&genesis_validators[..], &pos_params, Epoch(0));
+
+        // Initialize a tx environment
+        let mut tx_env = tx_host_env::take();
+
+        let secret_key = key::testing::keypair_1();
+        let public_key = secret_key.ref_to();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&validator]);
+        tx_env.init_account_storage(&validator, vec![public_key], 1);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(validator.clone(), tx_env, |_address| {
+            // Write the new rate directly, bypassing the checks of the
+            // commission rate change tx
+            let current_epoch = tx::ctx().get_block_epoch().unwrap();
+            proof_of_stake::validator_commission_rate_handle(&validator)
+                .set(
+                    tx::ctx(),
+                    Dec::new(10, 2).unwrap(),
+                    current_epoch,
+                    pos_params.pipeline_len,
+                )
+                .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![secret_key], None);
+        let signed_tx = vp_env.tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            !validate_tx(&CTX, signed_tx, validator, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
     /// Test that a transfer on with accounts other than self is accepted.
     #[test]
     fn test_transfer_between_other_parties_accepted() {
@@ -789,7 +905,7 @@ mod tests {
             let storage_key_addresses = storage_key.find_addresses();
             tx_env.spawn_accounts(storage_key_addresses);
 
-            tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+            tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
             // Initialize VP environment from a transaction
             vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |_address| {
@@ -801,19 +917,10 @@ mod tests {
                 }
             });
 
-            let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
             let mut vp_env = vp_host_env::take();
-            let mut tx = vp_env.tx.clone();
-            tx.set_data(Data::new(vec![]));
-            tx.set_code(Code::new(vec![], None));
-            tx.add_section(Section::Signature(Signature::new(
-                vec![tx.raw_header_hash()],
-                pks_map.index_secret_keys(vec![keypair]),
-                None,
-            )));
-            let signed_tx = tx.clone();
-            vp_env.tx = signed_tx.clone();
+            vp_env.set_tx_code_and_data(vec![], vec![]);
+            vp_env.sign_tx(vec![keypair], None);
+            let signed_tx = vp_env.tx.clone();
             let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
             let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -877,7 +984,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -887,19 +994,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -932,7 +1030,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -942,19 +1040,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -988,7 +1077,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -998,19 +1087,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -1044,7 +1124,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -1054,19 +1134,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_data(Data::new(vec![]));
-        tx.set_code(Code::new(vec![], None));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
@@ -1100,7 +1171,7 @@ mod tests {
 
         // Spawn the accounts to be able to modify their storage
         tx_env.spawn_accounts([&vp_owner]);
-        tx_env.init_account_storage(&vp_owner, vec![public_key.clone()], 1);
+        tx_env.init_account_storage(&vp_owner, vec![public_key], 1);
 
         // Initialize VP environment from a transaction
         vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
@@ -1110,19 +1181,10 @@ mod tests {
                 .unwrap();
         });
 
-        let pks_map = AccountPublicKeysMap::from_iter(vec![public_key]);
-
         let mut vp_env = vp_host_env::take();
-        let mut tx = vp_env.tx.clone();
-        tx.set_code(Code::new(vec![], None));
-        tx.set_data(Data::new(vec![]));
-        tx.add_section(Section::Signature(Signature::new(
-            vec![tx.raw_header_hash()],
-            pks_map.index_secret_keys(vec![keypair]),
-            None,
-        )));
-        let signed_tx = tx.clone();
-        vp_env.tx = signed_tx.clone();
+        vp_env.set_tx_code_and_data(vec![], vec![]);
+        vp_env.sign_tx(vec![keypair], None);
+        let signed_tx = vp_env.tx.clone();
         let keys_changed: BTreeSet<storage::Key> =
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();